@@ -0,0 +1,18 @@
+// swift-tools-version:5.7
+// The Swift package for iOS and macOS clients, over the UniFFI interface in src/amaze.udl.
+// swift/build-xcframework.sh builds swift/amazeFFI.xcframework and the generated bindings in
+// swift/Sources/Amaze first; it needs macOS and Xcode.
+import PackageDescription
+
+let package = Package(
+    name: "Amaze",
+    platforms: [.iOS(.v13), .macOS(.v10_15)],
+    products: [
+        .library(name: "Amaze", targets: ["Amaze"]),
+    ],
+    targets: [
+        .binaryTarget(name: "amazeFFI", path: "swift/amazeFFI.xcframework"),
+        .target(name: "Amaze", dependencies: ["amazeFFI"], path: "swift/Sources/Amaze"),
+        .testTarget(name: "AmazeTests", dependencies: ["Amaze"], path: "swift/Tests/AmazeTests"),
+    ]
+)
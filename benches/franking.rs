@@ -1,6 +1,6 @@
 use amaze::amf::{
-    franking::{frank, judge, keygen, verify},
-    AMFRole,
+    batch_judge, batch_judge_shared, frank, judge, judge_prepared, keygen, verify, AMFBatchEntry,
+    AMFRole, PreparedPublicKey,
 };
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 
@@ -20,7 +20,7 @@ fn criterion_benchmark(c: &mut Criterion) {
 
     // 4. Frank the message
     let amf_signature = frank(
-        sender_secret_key,
+        sender_secret_key.clone(),
         sender_public_key,
         recipient_public_key,
         judge_public_key,
@@ -31,7 +31,7 @@ fn criterion_benchmark(c: &mut Criterion) {
     group.bench_function("franking", |b| {
         b.iter(|| {
             frank(
-                black_box(sender_secret_key),
+                black_box(sender_secret_key.clone()),
                 black_box(sender_public_key),
                 black_box(recipient_public_key),
                 black_box(judge_public_key),
@@ -42,7 +42,7 @@ fn criterion_benchmark(c: &mut Criterion) {
     group.bench_function("verifying", |b| {
         b.iter(|| {
             verify(
-                black_box(recipient_secret_key),
+                black_box(recipient_secret_key.clone()),
                 black_box(sender_public_key),
                 black_box(recipient_public_key),
                 black_box(judge_public_key),
@@ -54,7 +54,7 @@ fn criterion_benchmark(c: &mut Criterion) {
     group.bench_function("judging", |b| {
         b.iter(|| {
             judge(
-                black_box(judge_secret_key),
+                black_box(judge_secret_key.clone()),
                 black_box(sender_public_key),
                 black_box(recipient_public_key),
                 black_box(judge_public_key),
@@ -63,6 +63,44 @@ fn criterion_benchmark(c: &mut Criterion) {
             )
         })
     });
+    let prepared_judge_public_key = PreparedPublicKey::new(judge_public_key);
+    group.bench_function("judging with a prepared key", |b| {
+        b.iter(|| {
+            judge_prepared(
+                black_box(judge_secret_key.clone()),
+                black_box(sender_public_key),
+                black_box(recipient_public_key),
+                black_box(&prepared_judge_public_key),
+                black_box(message),
+                black_box(amf_signature),
+            )
+        })
+    });
+
+    // 5. Judge 64 reports against one sender, with and without merging the shared keys
+    let reports = vec![
+        AMFBatchEntry {
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        };
+        64
+    ];
+    group.bench_function("batch judging 64 reports", |b| {
+        b.iter(|| batch_judge(black_box(judge_secret_key.clone()), black_box(&reports)))
+    });
+    let shared_keys = [sender_public_key, judge_public_key];
+    group.bench_function("batch judging 64 reports with shared keys", |b| {
+        b.iter(|| {
+            batch_judge_shared(
+                black_box(judge_secret_key.clone()),
+                black_box(&shared_keys),
+                black_box(&reports),
+            )
+        })
+    });
     group.finish();
 }
 
@@ -3,8 +3,8 @@
 
 #[cfg(target_os = "android")]
 use amaze::amf::{
-    franking::{frank, judge, keygen, verify},
-    AMFRole,
+    instrumented::{frank, judge, verify, FrankTimings, VerifyTimings},
+    keygen, AMFRole,
 };
 #[cfg(target_os = "android")]
 use std::time::Instant;
@@ -12,9 +12,9 @@ use std::time::Instant;
 #[cfg(target_os = "android")]
 fn main() {
     let mut keygen_times = Vec::new();
-    let mut frank_times = Vec::new();
-    let mut verify_times = Vec::new();
-    let mut judge_times = Vec::new();
+    let mut frank_timings = Vec::new();
+    let mut verify_timings = Vec::new();
+    let mut judge_timings = Vec::new();
 
     // Store the verify and judge results in an array so they are not optimized out
     let mut verify_results = Vec::new();
@@ -37,19 +37,18 @@ fn main() {
         let message = b"hello world!";
 
         // 4. Frank the message
-        start = Instant::now();
-        let amf_signature = frank(
+        let (amf_signature, timings) = frank(
+            &mut rand::thread_rng(),
             sender_secret_key,
             sender_public_key,
             recipient_public_key,
             judge_public_key,
             message,
         );
-        frank_times.push(start.elapsed().as_micros());
+        frank_timings.push(timings);
 
         // 5. Verify the message
-        start = Instant::now();
-        let verify_result = verify(
+        let (verify_result, timings) = verify(
             recipient_secret_key,
             sender_public_key,
             recipient_public_key,
@@ -57,12 +56,11 @@ fn main() {
             message,
             amf_signature,
         );
-        verify_times.push(start.elapsed().as_micros());
+        verify_timings.push(timings);
         verify_results.push(verify_result);
 
         // 5. Judge the message
-        start = Instant::now();
-        let judge_result = judge(
+        let (judge_result, timings) = judge(
             judge_secret_key,
             sender_public_key,
             recipient_public_key,
@@ -70,14 +68,14 @@ fn main() {
             message,
             amf_signature,
         );
-        judge_times.push(start.elapsed().as_micros());
+        judge_timings.push(timings);
         judge_results.push(judge_result);
     }
 
     print_summary_stats("keygen", keygen_times);
-    print_summary_stats("frank", frank_times);
-    print_summary_stats("verify", verify_times);
-    print_summary_stats("judge", judge_times);
+    print_frank_stats(&frank_timings);
+    print_verify_stats("verify", &verify_timings);
+    print_verify_stats("judge", &judge_timings);
 
     let all_verify_succeeded = verify_results.iter().all(|&item| item == true);
     let all_judge_succeeded = judge_results.iter().all(|&item| item == true);
@@ -96,6 +94,29 @@ fn print_summary_stats(name: &str, times: Vec<u128>) {
     )
 }
 
+#[cfg(target_os = "android")]
+fn print_frank_stats(timings: &[FrankTimings]) {
+    let phase = |phase: fn(&FrankTimings) -> std::time::Duration| {
+        timings.iter().map(|t| phase(t).as_micros()).collect()
+    };
+    print_summary_stats("frank", phase(FrankTimings::total));
+    print_summary_stats("  encryption", phase(|t| t.encryption));
+    print_summary_stats("  commitments", phase(|t| t.commitments));
+    print_summary_stats("  fiat-shamir", phase(|t| t.fiat_shamir_hash));
+    print_summary_stats("  responses", phase(|t| t.responses));
+}
+
+#[cfg(target_os = "android")]
+fn print_verify_stats(name: &str, timings: &[VerifyTimings]) {
+    let phase = |phase: fn(&VerifyTimings) -> std::time::Duration| {
+        timings.iter().map(|t| phase(t).as_micros()).collect()
+    };
+    print_summary_stats(name, phase(VerifyTimings::total));
+    print_summary_stats("  decryption", phase(|t| t.decryption));
+    print_summary_stats("  fiat-shamir", phase(|t| t.fiat_shamir_hash));
+    print_summary_stats("  equations", phase(|t| t.equations));
+}
+
 #[cfg(not(target_os = "android"))]
 fn main() {
     eprintln!(
@@ -0,0 +1,118 @@
+use amaze::pok::{
+    chaum_pedersen::{ChaumPedersenProof, ChaumPedersenWitnessStatement},
+    fiat_shamir::{FiatShamirSecretKey, SignatureScheme},
+    linear_sigma::SigmaProtocol,
+    or_proof::OrWitness,
+    schnorr::SchnorrProof,
+};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT, ristretto::RistrettoPoint, scalar::Scalar,
+};
+
+/// benches proving (commitment and response to a random challenge) and verifying one run of
+/// `protocol` with `witness`
+fn bench_sigma_protocol<Protocol>(
+    c: &mut Criterion,
+    name: &str,
+    protocol: Protocol,
+    witness: Protocol::Witness,
+) where
+    Protocol: SigmaProtocol,
+    Protocol::Witness: Copy,
+    Protocol::Commitment: Copy,
+    Protocol::Response: Copy,
+{
+    let mut rng = rand::thread_rng();
+    let mut group = c.benchmark_group(name);
+    group.significance_level(0.1).sample_size(1000);
+
+    group.bench_function("proving", |b| {
+        b.iter(|| {
+            let (commitment, prover_state) = protocol
+                .generate_commitment(&mut rng, black_box(witness))
+                .unwrap();
+            let challenge = protocol.generate_random_challenge(&mut rng);
+            (
+                commitment,
+                protocol.generate_response_to_challenge(prover_state, challenge),
+            )
+        })
+    });
+
+    let (commitment, prover_state) = protocol.generate_commitment(&mut rng, witness).unwrap();
+    let challenge = protocol.generate_random_challenge(&mut rng);
+    let response = protocol.generate_response_to_challenge(prover_state, challenge);
+    group.bench_function("verifying", |b| {
+        b.iter(|| {
+            protocol.verify_response_to_challenge(
+                black_box(commitment),
+                black_box(challenge),
+                black_box(response),
+            )
+        })
+    });
+    group.finish();
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let mut rng = rand::thread_rng();
+    let g = RISTRETTO_BASEPOINT_POINT;
+
+    // 0. Fix a witness, and the statements about it: a discrete log, an equality of discrete logs,
+    //    and an unrelated point that the OR proofs simulate
+    let witness = Scalar::random(&mut rng);
+    let schnorr = SchnorrProof::new(witness * g);
+    let u = RistrettoPoint::random(&mut rng);
+    let chaum_pedersen = ChaumPedersenProof::new(ChaumPedersenWitnessStatement {
+        u,
+        v: witness * g,
+        w: witness * u,
+    });
+    let unrelated = SchnorrProof::new(RistrettoPoint::random(&mut rng));
+
+    // 1. The primitives, and their compositions
+    bench_sigma_protocol(c, "schnorr", schnorr.clone(), witness);
+    bench_sigma_protocol(c, "chaum-pedersen", chaum_pedersen.clone(), witness);
+    bench_sigma_protocol(
+        c,
+        "or",
+        schnorr.clone().or(unrelated.clone()),
+        OrWitness::Left(witness),
+    );
+    bench_sigma_protocol(
+        c,
+        "and",
+        schnorr.clone().and(chaum_pedersen),
+        (witness, witness),
+    );
+
+    // 2. Fiat-Shamir signatures, which add the hash of the statement, message and commitment
+    let mut group = c.benchmark_group("fiat-shamir");
+    group.significance_level(0.1).sample_size(1000);
+    let message = b"hello world!";
+    let mut schnorr_fiat_shamir = schnorr.fiat_shamir();
+    group.bench_function("signing", |b| {
+        b.iter(|| {
+            schnorr_fiat_shamir
+                .sign(
+                    &mut rng,
+                    FiatShamirSecretKey {
+                        witness: black_box(witness),
+                    },
+                    black_box(message),
+                )
+                .unwrap()
+        })
+    });
+    let signature = schnorr_fiat_shamir
+        .sign(&mut rng, FiatShamirSecretKey { witness }, message)
+        .unwrap();
+    group.bench_function("verifying", |b| {
+        b.iter(|| schnorr_fiat_shamir.verify(black_box(message), black_box(signature)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,109 @@
+//! Reports the serialized sizes of proofs and signatures
+//!
+//! Run with `cargo bench --bench proof_sizes`; prints the size of each proof in bincode and, for
+//! AMF signatures, in the wire format, so that changes to compositions and encodings can be
+//! compared byte for byte.
+
+use amaze::{
+    amf::{frank, keygen, AMFRole},
+    pok::{
+        chaum_pedersen::{ChaumPedersenProof, ChaumPedersenWitnessStatement},
+        fiat_shamir::{FiatShamir, FiatShamirSecretKey, FiatShamirSignature, SignatureScheme},
+        linear_sigma::SigmaProtocol,
+        or_proof::OrWitness,
+        schnorr::SchnorrProof,
+        serialized_size::SerializedSize,
+    },
+};
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT, ristretto::RistrettoPoint, scalar::Scalar,
+};
+use serde::Serialize;
+
+const MESSAGE: &[u8] = b"hello world!";
+
+fn report(name: &str, encoding: &str, size: usize) {
+    println!("{name:<32} {encoding:<12} {size:>5} bytes");
+}
+
+/// signs `MESSAGE` with `fiat_shamir`, and reports the size of the signature
+fn report_fiat_shamir<Protocol>(
+    name: &str,
+    mut fiat_shamir: FiatShamir<Protocol>,
+    witness: Protocol::Witness,
+) where
+    Protocol: SigmaProtocol,
+    FiatShamirSignature<Protocol::Commitment, Protocol::Response>: Serialize + SerializedSize,
+{
+    let signature = fiat_shamir
+        .sign(
+            &mut rand::thread_rng(),
+            FiatShamirSecretKey { witness },
+            MESSAGE,
+        )
+        .unwrap();
+    let encoded = bincode::serialize(&signature).unwrap();
+    assert_eq!(encoded.len(), signature.serialized_size());
+    report(name, "bincode", encoded.len());
+}
+
+fn main() {
+    let mut rng = rand::thread_rng();
+    let g = RISTRETTO_BASEPOINT_POINT;
+
+    // 0. Fix a witness, and the statements about it, as in benches/pok.rs
+    let witness = Scalar::random(&mut rng);
+    let schnorr = SchnorrProof::new(witness * g);
+    let u = RistrettoPoint::random(&mut rng);
+    let chaum_pedersen = ChaumPedersenProof::new(ChaumPedersenWitnessStatement {
+        u,
+        v: witness * g,
+        w: witness * u,
+    });
+    let unrelated = SchnorrProof::new(RistrettoPoint::random(&mut rng));
+
+    // 1. The primitives, and their compositions, as Fiat-Shamir signatures
+    report_fiat_shamir("schnorr", schnorr.clone().fiat_shamir(), witness);
+    report_fiat_shamir(
+        "chaum-pedersen",
+        chaum_pedersen.clone().fiat_shamir(),
+        witness,
+    );
+    report_fiat_shamir(
+        "or(schnorr, schnorr)",
+        schnorr.clone().or(unrelated).fiat_shamir(),
+        OrWitness::Left(witness),
+    );
+    report_fiat_shamir(
+        "and(schnorr, chaum-pedersen)",
+        schnorr.and(chaum_pedersen).fiat_shamir(),
+        (witness, witness),
+    );
+
+    // 2. An AMF signature: its proof pi alone, and the whole signature on the wire
+    let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+    let (recipient_public_key, _) = keygen(AMFRole::Recipient);
+    let (judge_public_key, _) = keygen(AMFRole::Judge);
+    let amf_signature = frank(
+        sender_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        MESSAGE,
+    );
+    report(
+        "amf signature pi",
+        "bincode",
+        bincode::serialize(&amf_signature.pi).unwrap().len(),
+    );
+    report(
+        "amf signature",
+        "wire format",
+        amf_signature.to_bytes().len(),
+    );
+    report(
+        "amf public key",
+        "wire format",
+        sender_public_key.to_bytes().len(),
+    );
+}
@@ -0,0 +1,5 @@
+fn main() {
+    // the UniFFI scaffolding, included by src/uniffi_api.rs
+    #[cfg(feature = "uniffi")]
+    uniffi::generate_scaffolding("src/amaze.udl").unwrap();
+}
@@ -0,0 +1,91 @@
+#ifndef AMAZE_H
+#define AMAZE_H
+
+/* Generated by cbindgen from src/ffi.rs; do not edit. */
+
+#include <stddef.h>
+#include <stdint.h>
+
+// The length of an encoded public key.
+#define AMAZE_PUBLIC_KEY_LENGTH 39
+
+// The length of an encoded secret key.
+#define AMAZE_SECRET_KEY_LENGTH 39
+
+// The length of an encoded signature.
+#define AMAZE_SIGNATURE_LENGTH 486
+
+// The outcome of a call.
+typedef enum AmazeStatus {
+  // The call succeeded, and for `amaze_verify` and `amaze_judge`, the signature is valid.
+  AMAZE_STATUS_OK = 0,
+  // The signature is well-formed, but does not verify.
+  AMAZE_STATUS_REJECTED = 1,
+  // A required pointer was null.
+  AMAZE_STATUS_NULL_POINTER = 2,
+  // The role byte is not 0, 1, or 2, or a key is of another role than its argument.
+  AMAZE_STATUS_INVALID_ROLE = 3,
+  // A key or signature did not decode.
+  AMAZE_STATUS_INVALID_ENCODING = 4,
+  // The library panicked; this is a bug.
+  AMAZE_STATUS_PANIC = 5,
+} AmazeStatus;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Generates a key pair for `role`.
+//
+// # Safety
+//
+// `public_key_out` and `secret_key_out` must be valid for writes of `AMAZE_PUBLIC_KEY_LENGTH` and
+// `AMAZE_SECRET_KEY_LENGTH` bytes.
+AmazeStatus amaze_keygen(uint8_t role, uint8_t *public_key_out, uint8_t *secret_key_out);
+
+// Franks the `message_len` bytes at `message`.
+//
+// # Safety
+//
+// The key pointers must be valid for reads of their `AMAZE_*_LENGTH`, `message` for reads of
+// `message_len` bytes (or null if `message_len` is 0), and `signature_out` for writes of
+// `AMAZE_SIGNATURE_LENGTH` bytes.
+AmazeStatus amaze_frank(const uint8_t *sender_secret_key,
+                        const uint8_t *sender_public_key,
+                        const uint8_t *recipient_public_key,
+                        const uint8_t *judge_public_key,
+                        const uint8_t *message,
+                        size_t message_len,
+                        uint8_t *signature_out);
+
+// Verifies a franked message as its recipient: `Ok` if the signature is valid, `Rejected` if not.
+//
+// # Safety
+//
+// As for `amaze_frank`, with `signature` valid for reads of `AMAZE_SIGNATURE_LENGTH` bytes.
+AmazeStatus amaze_verify(const uint8_t *recipient_secret_key,
+                         const uint8_t *sender_public_key,
+                         const uint8_t *recipient_public_key,
+                         const uint8_t *judge_public_key,
+                         const uint8_t *message,
+                         size_t message_len,
+                         const uint8_t *signature);
+
+// Judges a reported message: `Ok` if the signature is valid, `Rejected` if not.
+//
+// # Safety
+//
+// As for `amaze_verify`.
+AmazeStatus amaze_judge(const uint8_t *judge_secret_key,
+                        const uint8_t *sender_public_key,
+                        const uint8_t *recipient_public_key,
+                        const uint8_t *judge_public_key,
+                        const uint8_t *message,
+                        size_t message_len,
+                        const uint8_t *signature);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* AMAZE_H */
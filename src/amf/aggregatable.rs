@@ -0,0 +1,378 @@
+//! Aggregatable Franking over BLS12-381 (Experimental)
+//!
+//! A franking variant for archives of judged reports, where the size of the stored signatures
+//! dominates: the judge extracts a BLS signature from every report it accepts, and any number of
+//! them, across senders and messages, add up to a single 48-byte `AggregateReport` that anyone can
+//! check against the list of (keys, message) it covers.
+//!
+//! - KeyGen: sk = x, with public keys x * g1 and x * g2 in both source groups.
+//! - Frank: the sender signs H(ctx) with BLS, sigma = x_s * H(ctx), where H hashes to G1 and ctx
+//!   binds the three public keys and the message, and encrypts sigma to the judge with ElGamal in
+//!   G1: (C, R1, R2) = (sigma + r * pk_J, r * g1, r * g2).
+//! - Verify: e(C, g2) == e(H(ctx), pk_S) * e(pk_J, R2), and e(R1, g2) == e(g1, R2).
+//! - Judge: sigma = C - x_J * R1, and e(sigma, g2) == e(H(ctx), pk_S); sigma is the report.
+//! - Aggregate: the sum of the reports, checked with e(sum_i sigma_i, g2) == prod_i e(H(ctx_i),
+//!   pk_S_i). Since every ctx contains the sender's key, rogue-key attacks do not apply.
+//!
+//! Unlike Fig. 5 of [AMF], this variant is **not deniable**: Verify needs no secret, so a franked
+//! message convinces anyone who sees it, not just the recipient, and a report is a plain BLS
+//! signature by the sender. Use it only where deniability is not a requirement. It is
+//! experimental: the construction has no security proof, and there is no wire format for it yet.
+//!
+//! [AMF]: https://eprint.iacr.org/2019/565/20190527:092413
+#![allow(non_snake_case)]
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use bls12_381::{
+    hash_to_curve::{ExpandMsgXmd, HashToCurve},
+    multi_miller_loop, G1Affine, G1Projective, G2Affine, G2Prepared, G2Projective, Gt, Scalar,
+};
+use ff::Field;
+
+use crate::pok::domain;
+
+use super::{codec::CodecError, AMFRole};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AggregatablePublicKey {
+    pub role: AMFRole,
+    /// x * g1, to encrypt to judges
+    pub g1: G1Projective,
+    /// x * g2, to check the signatures of senders
+    pub g2: G2Projective,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub struct AggregatableSecretKey {
+    pub role: AMFRole,
+    pub secret_key: Scalar,
+}
+
+/// Prints the role only, like `amf::AMFSecretKey`.
+impl fmt::Debug for AggregatableSecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AggregatableSecretKey")
+            .field("role", &self.role)
+            .field("secret_key", &format_args!("<redacted>"))
+            .finish()
+    }
+}
+
+/// A franked message's signature: the sender's BLS signature, encrypted to the judge.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AggregatableSignature {
+    pub C: G1Projective,
+    pub R1: G1Projective,
+    pub R2: G2Projective,
+}
+
+/// The sender's BLS signature, extracted by the judge from a franked message.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AggregatableReport {
+    pub sigma: G1Projective,
+}
+
+/// The sum of many reports.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AggregateReport {
+    pub sigma: G1Projective,
+}
+
+/// One report covered by an `AggregateReport`.
+#[derive(Debug, Copy, Clone)]
+pub struct AggregatableEntry<'a> {
+    pub sender_public_key: AggregatablePublicKey,
+    pub recipient_public_key: AggregatablePublicKey,
+    pub judge_public_key: AggregatablePublicKey,
+    pub message: &'a [u8],
+}
+
+impl AggregatableEntry<'_> {
+    /// H(ctx), with the public keys and the message each prefixed by their length
+    fn hash_to_g1(&self) -> G1Projective {
+        let mut ctx = Vec::with_capacity(4 * 8 + 96 + 2 * 48 + self.message.len());
+        for part in [
+            &G2Affine::from(self.sender_public_key.g2).to_compressed()[..],
+            &G1Affine::from(self.recipient_public_key.g1).to_compressed()[..],
+            &G1Affine::from(self.judge_public_key.g1).to_compressed()[..],
+            self.message,
+        ] {
+            ctx.extend((part.len() as u64).to_be_bytes());
+            ctx.extend(part);
+        }
+        <G1Projective as HashToCurve<ExpandMsgXmd<sha2_09::Sha256>>>::hash_to_curve(
+            ctx,
+            domain::AGGREGATABLE_HASH_TO_CURVE_LABEL,
+        )
+    }
+}
+
+/// checks that the product of the pairings e(P_i, Q_i) is the identity, with a single final
+/// exponentiation
+fn pairing_product_is_identity(terms: &[(G1Projective, G2Projective)]) -> bool {
+    let terms: Vec<(G1Affine, G2Prepared)> = terms
+        .iter()
+        .map(|(p, q)| (G1Affine::from(p), G2Prepared::from(G2Affine::from(q))))
+        .collect();
+    let terms: Vec<(&G1Affine, &G2Prepared)> = terms.iter().map(|(p, q)| (p, q)).collect();
+    multi_miller_loop(&terms).final_exponentiation() == Gt::identity()
+}
+
+pub fn keygen(role: AMFRole) -> (AggregatablePublicKey, AggregatableSecretKey) {
+    let secret_key = Scalar::random(&mut rand::thread_rng());
+    (
+        AggregatablePublicKey {
+            role,
+            g1: G1Projective::generator() * secret_key,
+            g2: G2Projective::generator() * secret_key,
+        },
+        AggregatableSecretKey { role, secret_key },
+    )
+}
+
+pub fn frank(
+    sender_secret_key: AggregatableSecretKey,
+    sender_public_key: AggregatablePublicKey,
+    recipient_public_key: AggregatablePublicKey,
+    judge_public_key: AggregatablePublicKey,
+    message: &[u8],
+) -> AggregatableSignature {
+    let entry = AggregatableEntry {
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        message,
+    };
+    let sigma = entry.hash_to_g1() * sender_secret_key.secret_key;
+    let r = Scalar::random(&mut rand::thread_rng());
+    AggregatableSignature {
+        C: sigma + judge_public_key.g1 * r,
+        R1: G1Projective::generator() * r,
+        R2: G2Projective::generator() * r,
+    }
+}
+
+/// Checks that the signature encrypts the sender's signature on the message to the judge. Anyone
+/// can run this check; cf. the module documentation.
+pub fn verify(
+    sender_public_key: AggregatablePublicKey,
+    recipient_public_key: AggregatablePublicKey,
+    judge_public_key: AggregatablePublicKey,
+    message: &[u8],
+    signature: AggregatableSignature,
+) -> bool {
+    let entry = AggregatableEntry {
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        message,
+    };
+    let g2 = G2Projective::generator();
+    pairing_product_is_identity(&[
+        (signature.C, g2),
+        (-entry.hash_to_g1(), sender_public_key.g2),
+        (-judge_public_key.g1, signature.R2),
+    ]) && pairing_product_is_identity(&[
+        (signature.R1, g2),
+        (-G1Projective::generator(), signature.R2),
+    ])
+}
+
+/// Decrypts the sender's signature, and returns it as a report if it is valid.
+pub fn judge(
+    judge_secret_key: AggregatableSecretKey,
+    sender_public_key: AggregatablePublicKey,
+    recipient_public_key: AggregatablePublicKey,
+    judge_public_key: AggregatablePublicKey,
+    message: &[u8],
+    signature: AggregatableSignature,
+) -> Option<AggregatableReport> {
+    let report = AggregatableReport {
+        sigma: signature.C - signature.R1 * judge_secret_key.secret_key,
+    };
+    let entry = AggregatableEntry {
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        message,
+    };
+    verify_aggregate(&aggregate([&report]), &[entry]).then_some(report)
+}
+
+/// Adds up reports, in any order.
+pub fn aggregate<'a, I: IntoIterator<Item = &'a AggregatableReport>>(
+    reports: I,
+) -> AggregateReport {
+    AggregateReport {
+        sigma: reports.into_iter().map(|report| report.sigma).sum(),
+    }
+}
+
+/// Checks that `aggregate` is the sum of valid reports for exactly `entries`, in one multi-pairing.
+pub fn verify_aggregate(aggregate: &AggregateReport, entries: &[AggregatableEntry]) -> bool {
+    let mut terms = Vec::with_capacity(entries.len() + 1);
+    terms.push((aggregate.sigma, -G2Projective::generator()));
+    terms.extend(
+        entries
+            .iter()
+            .map(|entry| (entry.hash_to_g1(), entry.sender_public_key.g2)),
+    );
+    pairing_product_is_identity(&terms)
+}
+
+impl AggregateReport {
+    /// the length of the compressed encoding, independent of the number of reports
+    pub const LENGTH: usize = 48;
+
+    pub fn to_bytes(&self) -> [u8; Self::LENGTH] {
+        G1Affine::from(self.sigma).to_compressed()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CodecError> {
+        let bytes: &[u8; Self::LENGTH] =
+            bytes.try_into().map_err(|_| CodecError::InvalidLength {
+                expected: Self::LENGTH,
+                actual: bytes.len(),
+            })?;
+        Option::from(G1Affine::from_compressed(bytes))
+            .map(|sigma: G1Affine| AggregateReport {
+                sigma: sigma.into(),
+            })
+            .ok_or(CodecError::InvalidPoint)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aggregatable_franking() {
+        // 0. Initialize a Sender, a Recipient, and a Judge
+        let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+        let (recipient_public_key, _) = keygen(AMFRole::Recipient);
+        let (judge_public_key, judge_secret_key) = keygen(AMFRole::Judge);
+
+        // 1. Frank a message, then verify and judge it
+        let message = b"hello world!";
+        let signature = frank(
+            sender_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+        );
+        assert!(verify(
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            signature,
+        ));
+        assert!(judge(
+            judge_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            signature,
+        )
+        .is_some());
+
+        // 2. Another message, sender, or judge is rejected
+        assert!(!verify(
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            b"goodbye world!",
+            signature,
+        ));
+        let (other_public_key, other_secret_key) = keygen(AMFRole::Judge);
+        assert!(!verify(
+            other_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            signature,
+        ));
+        assert!(judge(
+            other_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            other_public_key,
+            message,
+            signature,
+        )
+        .is_none());
+
+        // 3. Inconsistent randomness is caught
+        let mut forged = signature;
+        forged.R1 += G1Projective::generator();
+        assert!(!verify(
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            forged,
+        ));
+    }
+
+    #[test]
+    fn test_aggregate_reports() {
+        // 0. Three senders frank messages, which one judge extracts reports from
+        let (recipient_public_key, _) = keygen(AMFRole::Recipient);
+        let (judge_public_key, judge_secret_key) = keygen(AMFRole::Judge);
+        let messages: [&[u8]; 3] = [b"hello world!", b"hello world!", b"goodbye world!"];
+        let mut entries = Vec::new();
+        let mut reports = Vec::new();
+        for message in messages {
+            let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+            let signature = frank(
+                sender_secret_key,
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+            );
+            reports.push(
+                judge(
+                    judge_secret_key,
+                    sender_public_key,
+                    recipient_public_key,
+                    judge_public_key,
+                    message,
+                    signature,
+                )
+                .unwrap(),
+            );
+            entries.push(AggregatableEntry {
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+            });
+        }
+
+        // 1. Their aggregate verifies against all entries, and round-trips through 48 bytes
+        let aggregate_report = aggregate(&reports);
+        assert!(verify_aggregate(&aggregate_report, &entries));
+        let encoded = aggregate_report.to_bytes();
+        assert_eq!(AggregateReport::from_bytes(&encoded), Ok(aggregate_report));
+        assert_eq!(
+            AggregateReport::from_bytes(&encoded[1..]),
+            Err(CodecError::InvalidLength {
+                expected: 48,
+                actual: 47
+            })
+        );
+
+        // 2. It does not verify with an entry missing or changed
+        assert!(!verify_aggregate(&aggregate_report, &entries[1..]));
+        let mut tampered = entries.clone();
+        tampered[2].message = b"hello world!";
+        assert!(!verify_aggregate(&aggregate_report, &tampered));
+        assert!(!verify_aggregate(&aggregate(&reports[..2]), &entries));
+    }
+}
@@ -0,0 +1,254 @@
+//! proptest Strategies for AMF Values
+//!
+//! With the `test-utils` feature, roles, suites, keys, messages and signatures implement
+//! `proptest::arbitrary::Arbitrary`, so integrations can property-test their own code, e.g. their
+//! storage or their report handling, with `any::<AMFSignature>()`. The values are realistic rather
+//! than arbitrary bytes: keys come from `keygen_with_rng` and signatures from `frank_with_rng`,
+//! each with a generator seeded by proptest, so failing cases replay from their seeds. Shrinking
+//! shrinks the seeds and messages, not the points.
+//!
+//! `FrankedReport` bundles a signature with the message and all the keys that check it, for tests
+//! of what a recipient stores or a judge receives; `with_reports` generates them.
+//!
+//! Franking takes about a millisecond, so strategies of signatures make slow property tests;
+//! lower `ProptestConfig::cases` where that matters.
+
+use alloc::vec::Vec;
+
+use proptest::{
+    arbitrary::{any, Arbitrary},
+    collection::vec,
+    prelude::{prop_oneof, BoxedStrategy, Just, Strategy},
+};
+use rand::{rngs::StdRng, SeedableRng};
+
+use super::{
+    frank_with_rng, keygen_with_rng, AMFMessage, AMFPublicKey, AMFRole, AMFSecretKey, AMFSignature,
+    AMFSuite, SuiteSignature,
+};
+
+/// the length of generated messages, in bytes
+const MAX_MESSAGE_LENGTH: usize = 256;
+
+/// A franked message with the keys of all three roles, as a recipient stores it for a report.
+#[derive(Debug, Clone)]
+pub struct FrankedReport {
+    pub sender_public_key: AMFPublicKey,
+    pub recipient_public_key: AMFPublicKey,
+    pub recipient_secret_key: AMFSecretKey,
+    pub judge_public_key: AMFPublicKey,
+    pub judge_secret_key: AMFSecretKey,
+    pub message: Vec<u8>,
+    pub amf_signature: AMFSignature,
+}
+
+/// Key pairs of `role`, from seeded `keygen_with_rng`.
+pub fn key_pairs(role: AMFRole) -> impl Strategy<Value = (AMFPublicKey, AMFSecretKey)> {
+    any::<[u8; 32]>().prop_map(move |seed| keygen_with_rng(&mut StdRng::from_seed(seed), role))
+}
+
+/// Messages of up to 256 bytes.
+pub fn messages() -> impl Strategy<Value = Vec<u8>> {
+    vec(any::<u8>(), 0..=MAX_MESSAGE_LENGTH)
+}
+
+/// Reports whose signatures verify and judge, cf. `FrankedReport`.
+pub fn with_reports() -> impl Strategy<Value = FrankedReport> {
+    (
+        key_pairs(AMFRole::Sender),
+        key_pairs(AMFRole::Recipient),
+        key_pairs(AMFRole::Judge),
+        messages(),
+        any::<[u8; 32]>(),
+    )
+        .prop_map(|(sender, recipient, judge, message, seed)| {
+            let amf_signature = frank_with_rng(
+                &mut StdRng::from_seed(seed),
+                &sender.1,
+                sender.0,
+                recipient.0,
+                judge.0,
+                &message,
+            );
+            FrankedReport {
+                sender_public_key: sender.0,
+                recipient_public_key: recipient.0,
+                recipient_secret_key: recipient.1,
+                judge_public_key: judge.0,
+                judge_secret_key: judge.1,
+                message,
+                amf_signature,
+            }
+        })
+}
+
+impl Arbitrary for AMFRole {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        prop_oneof![
+            Just(AMFRole::Sender),
+            Just(AMFRole::Recipient),
+            Just(AMFRole::Judge),
+        ]
+        .boxed()
+    }
+}
+
+impl Arbitrary for AMFSuite {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        prop_oneof![Just(AMFSuite::V1), Just(AMFSuite::V2)].boxed()
+    }
+}
+
+/// A public key of any role.
+impl Arbitrary for AMFPublicKey {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        any::<AMFRole>()
+            .prop_flat_map(key_pairs)
+            .prop_map(|(public_key, _)| public_key)
+            .boxed()
+    }
+}
+
+/// A secret key of any role.
+impl Arbitrary for AMFSecretKey {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        any::<AMFRole>()
+            .prop_flat_map(key_pairs)
+            .prop_map(|(_, secret_key)| secret_key)
+            .boxed()
+    }
+}
+
+impl Arbitrary for AMFMessage {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (messages(), vec(any::<u8>(), 0..=32), any::<u64>())
+            .prop_map(|(payload, context, timestamp)| AMFMessage {
+                payload,
+                context,
+                timestamp,
+            })
+            .boxed()
+    }
+}
+
+/// The signature of a `FrankedReport`, valid under keys that the test does not see.
+impl Arbitrary for AMFSignature {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        with_reports()
+            .prop_map(|report| report.amf_signature)
+            .boxed()
+    }
+}
+
+/// A signature of any suite, franked as `AMFSignature`'s are.
+impl Arbitrary for SuiteSignature {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (
+            any::<AMFSuite>(),
+            key_pairs(AMFRole::Sender),
+            any::<AMFPublicKey>(),
+            any::<AMFPublicKey>(),
+            messages(),
+            any::<[u8; 32]>(),
+        )
+            .prop_map(|(suite, sender, recipient, judge, message, seed)| {
+                suite.frank_with_rng(
+                    &mut StdRng::from_seed(seed),
+                    &sender.1,
+                    sender.0,
+                    recipient,
+                    judge,
+                    &message,
+                )
+            })
+            .boxed()
+    }
+}
+
+/// The report of a `FrankedReport`.
+#[cfg(any(feature = "rest-server", feature = "rest-client"))]
+impl Arbitrary for crate::rest::Report {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        with_reports()
+            .prop_map(|report| crate::rest::Report {
+                sender_public_key: report.sender_public_key,
+                recipient_public_key: report.recipient_public_key,
+                message: report.message,
+                signature: report.amf_signature,
+            })
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::{prelude::ProptestConfig, proptest};
+
+    use super::*;
+    use crate::amf::{judge, verify};
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        #[test]
+        fn test_arbitrary_reports_verify(report in with_reports()) {
+            // Generated reports verify and judge, and encode and decode
+            assert!(verify(
+                &report.recipient_secret_key,
+                report.sender_public_key,
+                report.recipient_public_key,
+                report.judge_public_key,
+                &report.message,
+                report.amf_signature,
+            ));
+            assert!(judge(
+                &report.judge_secret_key,
+                report.sender_public_key,
+                report.recipient_public_key,
+                report.judge_public_key,
+                &report.message,
+                report.amf_signature,
+            ));
+            assert_eq!(
+                AMFSignature::from_bytes(&report.amf_signature.to_bytes()).unwrap(),
+                report.amf_signature
+            );
+        }
+
+        #[test]
+        fn test_arbitrary_keys_and_signatures(
+            public_key in any::<AMFPublicKey>(),
+            secret_key in any::<AMFSecretKey>(),
+            amf_signature in any::<SuiteSignature>(),
+        ) {
+            // Generated keys and signatures are valid encodings
+            assert_eq!(AMFPublicKey::from_bytes(&public_key.to_bytes()).unwrap(), public_key);
+            assert_eq!(AMFSecretKey::from_bytes(&secret_key.to_bytes()).unwrap(), secret_key);
+            assert_eq!(SuiteSignature::from_bytes(&amf_signature.to_bytes()).unwrap(), amf_signature);
+        }
+    }
+}
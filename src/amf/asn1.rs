@@ -0,0 +1,352 @@
+//! ASN.1/DER encodings for exposed AMF structs.
+//!
+//! These encodings are unstable, behind the `unstable-der` feature: they carry no algorithm
+//! identifier, since the project has no registered OID arc yet, so nothing in the bytes says that
+//! they are AMF over ristretto255. Applications that store or exchange them must label them
+//! themselves, and should expect the module to change once an OID is assigned.
+//!
+//! The encodings follow the module below. Points and scalars are encoded as 32-byte OCTET
+//! STRINGs holding the compressed Ristretto point and the canonical little-endian scalar.
+//!
+//! ```text
+//! AMF-Module DEFINITIONS ::= BEGIN
+//!
+//! AMFRole ::= ENUMERATED { sender(0), recipient(1), judge(2) }
+//!
+//! AMFPublicKey ::= SEQUENCE {
+//!     role       AMFRole,
+//!     publicKey  OCTET STRING (SIZE(32))
+//! }
+//!
+//! AMFSecretKey ::= SEQUENCE {
+//!     role       AMFRole,
+//!     secretKey  OCTET STRING (SIZE(32))
+//! }
+//!
+//! OrProverResponse ::= SEQUENCE {
+//!     c0  OCTET STRING (SIZE(32)),
+//!     z0  OCTET STRING (SIZE(32)),
+//!     z1  OCTET STRING (SIZE(32))
+//! }
+//!
+//! AMFInternalSignature ::= SEQUENCE {
+//!     senderCommitment    OCTET STRING (SIZE(32)), -- t for pk_s = g^t
+//!     jCommitment         OCTET STRING (SIZE(32)), -- t for J = g^u
+//!     judgeCommitmentV    OCTET STRING (SIZE(32)), -- Chaum-Pedersen v_t
+//!     judgeCommitmentW    OCTET STRING (SIZE(32)), -- Chaum-Pedersen w_t
+//!     rCommitment         OCTET STRING (SIZE(32)), -- t for R = g^w
+//!     response0           OrProverResponse,
+//!     response1           OrProverResponse
+//! }
+//!
+//! AMFSignature ::= SEQUENCE {
+//!     pi         AMFInternalSignature,
+//!     j          OCTET STRING (SIZE(32)),
+//!     r          OCTET STRING (SIZE(32)),
+//!     eJ         OCTET STRING (SIZE(32)),
+//!     eR         OCTET STRING (SIZE(32))
+//! }
+//!
+//! END
+//! ```
+#![allow(non_snake_case)]
+
+use alloc::vec::Vec;
+
+use curve25519_dalek::{
+    ristretto::{CompressedRistretto, RistrettoPoint},
+    scalar::Scalar,
+};
+use der::{asn1::OctetStringRef, Decode, Encode, Enumerated, Sequence, Tag};
+
+use crate::pok::{chaum_pedersen::ChaumPedersenProverCommitment, or_proof::OrProverResponse};
+
+use super::{AMFInternalSignature, AMFPublicKey, AMFRole, AMFSecretKey, AMFSignature};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Enumerated)]
+#[repr(u32)]
+enum Asn1AMFRole {
+    Sender = 0,
+    Recipient = 1,
+    Judge = 2,
+}
+impl From<AMFRole> for Asn1AMFRole {
+    fn from(role: AMFRole) -> Self {
+        match role {
+            AMFRole::Sender => Asn1AMFRole::Sender,
+            AMFRole::Recipient => Asn1AMFRole::Recipient,
+            AMFRole::Judge => Asn1AMFRole::Judge,
+        }
+    }
+}
+impl From<Asn1AMFRole> for AMFRole {
+    fn from(role: Asn1AMFRole) -> Self {
+        match role {
+            Asn1AMFRole::Sender => AMFRole::Sender,
+            Asn1AMFRole::Recipient => AMFRole::Recipient,
+            Asn1AMFRole::Judge => AMFRole::Judge,
+        }
+    }
+}
+
+#[derive(Sequence)]
+struct Asn1AMFKey<'a> {
+    role: Asn1AMFRole,
+    key: OctetStringRef<'a>,
+}
+
+#[derive(Sequence)]
+struct Asn1OrProverResponse<'a> {
+    c0: OctetStringRef<'a>,
+    z0: OctetStringRef<'a>,
+    z1: OctetStringRef<'a>,
+}
+
+#[derive(Sequence)]
+struct Asn1AMFInternalSignature<'a> {
+    sender_commitment: OctetStringRef<'a>,
+    j_commitment: OctetStringRef<'a>,
+    judge_commitment_v: OctetStringRef<'a>,
+    judge_commitment_w: OctetStringRef<'a>,
+    r_commitment: OctetStringRef<'a>,
+    response0: Asn1OrProverResponse<'a>,
+    response1: Asn1OrProverResponse<'a>,
+}
+
+#[derive(Sequence)]
+struct Asn1AMFSignature<'a> {
+    pi: Asn1AMFInternalSignature<'a>,
+    j: OctetStringRef<'a>,
+    r: OctetStringRef<'a>,
+    e_j: OctetStringRef<'a>,
+    e_r: OctetStringRef<'a>,
+}
+
+fn octet_string(bytes: &[u8; 32]) -> OctetStringRef<'_> {
+    // 32 bytes is always a valid OCTET STRING length
+    OctetStringRef::new(bytes).unwrap()
+}
+
+fn point_from_octet_string(octet_string: OctetStringRef<'_>) -> der::Result<RistrettoPoint> {
+    CompressedRistretto::from_slice(octet_string.as_bytes())
+        .ok()
+        .and_then(|compressed| compressed.decompress())
+        .ok_or_else(|| Tag::OctetString.value_error())
+}
+
+fn scalar_from_octet_string(octet_string: OctetStringRef<'_>) -> der::Result<Scalar> {
+    let bytes: [u8; 32] = octet_string
+        .as_bytes()
+        .try_into()
+        .map_err(|_| Tag::OctetString.length_error())?;
+    Option::from(Scalar::from_canonical_bytes(bytes)).ok_or_else(|| Tag::OctetString.value_error())
+}
+
+impl AMFPublicKey {
+    /// Encodes the public key as an ASN.1 DER `AMFPublicKey`.
+    pub fn to_der(&self) -> der::Result<Vec<u8>> {
+        let public_key = self.public_key.compress().to_bytes();
+        Asn1AMFKey {
+            role: self.role.into(),
+            key: octet_string(&public_key),
+        }
+        .to_der()
+    }
+
+    /// Decodes a public key from an ASN.1 DER `AMFPublicKey`.
+    pub fn from_der(bytes: &[u8]) -> der::Result<Self> {
+        let asn1_key = Asn1AMFKey::from_der(bytes)?;
+        let public_key = AMFPublicKey {
+            role: asn1_key.role.into(),
+            public_key: point_from_octet_string(asn1_key.key)?,
+        };
+        public_key
+            .validate()
+            .map_err(|_| Tag::OctetString.value_error())?;
+        Ok(public_key)
+    }
+}
+
+impl AMFSecretKey {
+    /// Encodes the secret key as an ASN.1 DER `AMFSecretKey`.
+    pub fn to_der(&self) -> der::Result<Vec<u8>> {
+        let secret_key = self.secret_key.to_bytes();
+        Asn1AMFKey {
+            role: self.role.into(),
+            key: octet_string(&secret_key),
+        }
+        .to_der()
+    }
+
+    /// Decodes a secret key from an ASN.1 DER `AMFSecretKey`.
+    pub fn from_der(bytes: &[u8]) -> der::Result<Self> {
+        let asn1_key = Asn1AMFKey::from_der(bytes)?;
+        let secret_key = AMFSecretKey {
+            role: asn1_key.role.into(),
+            secret_key: scalar_from_octet_string(asn1_key.key)?,
+        };
+        secret_key
+            .validate()
+            .map_err(|_| Tag::OctetString.value_error())?;
+        Ok(secret_key)
+    }
+}
+
+impl AMFSignature {
+    /// Encodes the signature as an ASN.1 DER `AMFSignature`.
+    pub fn to_der(&self) -> der::Result<Vec<u8>> {
+        let (or0_commitment, or1_commitment) = self.pi.prover_commitment;
+        let (or0_response, or1_response) = self.pi.prover_response;
+
+        let sender_commitment = or0_commitment.0.compress().to_bytes();
+        let j_commitment = or0_commitment.1.compress().to_bytes();
+        let judge_commitment_v = or1_commitment.0.v_t.compress().to_bytes();
+        let judge_commitment_w = or1_commitment.0.w_t.compress().to_bytes();
+        let r_commitment = or1_commitment.1.compress().to_bytes();
+        let response0 = [
+            or0_response.c_0.to_bytes(),
+            or0_response.z_0.to_bytes(),
+            or0_response.z_1.to_bytes(),
+        ];
+        let response1 = [
+            or1_response.c_0.to_bytes(),
+            or1_response.z_0.to_bytes(),
+            or1_response.z_1.to_bytes(),
+        ];
+        let J = self.J.compress().to_bytes();
+        let R = self.R.compress().to_bytes();
+        let E_J = self.E_J.compress().to_bytes();
+        let E_R = self.E_R.compress().to_bytes();
+
+        Asn1AMFSignature {
+            pi: Asn1AMFInternalSignature {
+                sender_commitment: octet_string(&sender_commitment),
+                j_commitment: octet_string(&j_commitment),
+                judge_commitment_v: octet_string(&judge_commitment_v),
+                judge_commitment_w: octet_string(&judge_commitment_w),
+                r_commitment: octet_string(&r_commitment),
+                response0: Asn1OrProverResponse {
+                    c0: octet_string(&response0[0]),
+                    z0: octet_string(&response0[1]),
+                    z1: octet_string(&response0[2]),
+                },
+                response1: Asn1OrProverResponse {
+                    c0: octet_string(&response1[0]),
+                    z0: octet_string(&response1[1]),
+                    z1: octet_string(&response1[2]),
+                },
+            },
+            j: octet_string(&J),
+            r: octet_string(&R),
+            e_j: octet_string(&E_J),
+            e_r: octet_string(&E_R),
+        }
+        .to_der()
+    }
+
+    /// Decodes a signature from an ASN.1 DER `AMFSignature`.
+    pub fn from_der(bytes: &[u8]) -> der::Result<Self> {
+        let asn1_signature = Asn1AMFSignature::from_der(bytes)?;
+
+        let pi = asn1_signature.pi;
+        let or_prover_response = |response: Asn1OrProverResponse<'_>| {
+            Ok::<_, der::Error>(OrProverResponse {
+                c_0: scalar_from_octet_string(response.c0)?,
+                z_0: scalar_from_octet_string(response.z0)?,
+                z_1: scalar_from_octet_string(response.z1)?,
+            })
+        };
+        let pi = AMFInternalSignature {
+            prover_commitment: (
+                (
+                    point_from_octet_string(pi.sender_commitment)?,
+                    point_from_octet_string(pi.j_commitment)?,
+                ),
+                (
+                    ChaumPedersenProverCommitment {
+                        v_t: point_from_octet_string(pi.judge_commitment_v)?,
+                        w_t: point_from_octet_string(pi.judge_commitment_w)?,
+                    },
+                    point_from_octet_string(pi.r_commitment)?,
+                ),
+            ),
+            prover_response: (
+                or_prover_response(pi.response0)?,
+                or_prover_response(pi.response1)?,
+            ),
+        };
+
+        let amf_signature = AMFSignature {
+            pi,
+            J: point_from_octet_string(asn1_signature.j)?,
+            R: point_from_octet_string(asn1_signature.r)?,
+            E_J: point_from_octet_string(asn1_signature.e_j)?,
+            E_R: point_from_octet_string(asn1_signature.e_r)?,
+        };
+        amf_signature
+            .validate()
+            .map_err(|_| Tag::OctetString.value_error())?;
+        Ok(amf_signature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::amf::{frank, keygen};
+
+    use super::*;
+
+    #[test]
+    fn test_key_der() {
+        let (public_key, secret_key) = keygen(AMFRole::Judge);
+
+        let encoded_public_key = public_key.to_der().unwrap();
+        assert_eq!(
+            public_key,
+            AMFPublicKey::from_der(&encoded_public_key).unwrap()
+        );
+
+        let encoded_secret_key = secret_key.to_der().unwrap();
+        assert_eq!(
+            secret_key,
+            AMFSecretKey::from_der(&encoded_secret_key).unwrap()
+        );
+
+        // A public key is not a valid DER signature
+        assert!(AMFSignature::from_der(&encoded_public_key).is_err());
+    }
+
+    #[test]
+    fn test_signature_der() {
+        // 0. Initialize a Sender, Recipient, and Judge
+        let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+        let (recipient_public_key, _recipient_secret_key) = keygen(AMFRole::Recipient);
+        let (judge_public_key, _judge_secret_key) = keygen(AMFRole::Judge);
+
+        // 1. Frank a message
+        let amf_signature = frank(
+            sender_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            b"hello world!",
+        );
+
+        // 2. Round-trip the signature through DER
+        let encoded_amf_signature = amf_signature.to_der().unwrap();
+        let decoded_amf_signature = AMFSignature::from_der(&encoded_amf_signature).unwrap();
+        assert_eq!(amf_signature, decoded_amf_signature);
+
+        // 3. Trailing data and truncations are rejected
+        let mut trailing = encoded_amf_signature.clone();
+        trailing.push(0);
+        assert!(AMFSignature::from_der(&trailing).is_err());
+        let truncated = &encoded_amf_signature[..encoded_amf_signature.len() - 1];
+        assert!(AMFSignature::from_der(truncated).is_err());
+
+        println!(
+            "DER encoded amf signature length: {:?} bytes",
+            encoded_amf_signature.len()
+        );
+    }
+}
@@ -0,0 +1,311 @@
+//! Zero-copy views of wire format AMF artifacts.
+//!
+//! `AMFSignatureRef` and `AMFPublicKeyRef` borrow the buffer they were parsed from, so servers
+//! can validate signatures and keys in network frames without copying them into owned structs.
+//! Parsing checks the header, the length, the role byte, and that every scalar is canonical;
+//! points are only decompressed on `decompress`.
+#![allow(non_snake_case)]
+
+use curve25519_dalek::{
+    ristretto::{CompressedRistretto, RistrettoPoint},
+    scalar::Scalar,
+};
+
+use super::{
+    codec::{
+        decompress, role_from_byte, CodecError, WireArtifactKind, WireHeader, WireReader,
+        SIGNATURE_BODY_LENGTH, SUITE_RISTRETTO255_SHA512, WIRE_FORMAT_V1, WIRE_FORMAT_V2,
+    },
+    AMFInternalSignature, AMFPublicKey, AMFRole, AMFSignature, CompressedAMFSignature,
+};
+use crate::pok::{chaum_pedersen::ChaumPedersenProverCommitment, or_proof::OrProverResponse};
+
+/// The number of points at the start of a signature body; the rest are scalars.
+const SIGNATURE_POINT_COUNT: usize = 9;
+
+/// A wire format `AMFSignature` borrowed from a buffer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct AMFSignatureRef<'a> {
+    body: &'a [u8; SIGNATURE_BODY_LENGTH],
+}
+
+impl<'a> AMFSignatureRef<'a> {
+    /// Parses and validates a wire format signature without copying it.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, CodecError> {
+        let (header, body) = WireHeader::read(bytes, WireArtifactKind::Signature)?;
+        match (header.version, header.suite) {
+            (WIRE_FORMAT_V2, SUITE_RISTRETTO255_SHA512) => {
+                let body = body.try_into().map_err(|_| CodecError::InvalidLength {
+                    expected: AMFSignature::WIRE_LENGTH,
+                    actual: bytes.len(),
+                })?;
+                let signature_ref = AMFSignatureRef { body };
+                signature_ref.check_scalars()?;
+                Ok(signature_ref)
+            }
+            _ => Err(header.unsupported()),
+        }
+    }
+
+    /// Wraps a wire format body that has already been length checked.
+    pub(super) fn from_body(body: &'a [u8; SIGNATURE_BODY_LENGTH]) -> Self {
+        AMFSignatureRef { body }
+    }
+
+    fn check_scalars(&self) -> Result<(), CodecError> {
+        self.body[32 * SIGNATURE_POINT_COUNT..]
+            .chunks_exact(32)
+            .try_for_each(|chunk| {
+                Option::<Scalar>::from(Scalar::from_canonical_bytes(chunk.try_into().unwrap()))
+                    .map(|_| ())
+                    .ok_or(CodecError::InvalidScalar)
+            })
+    }
+
+    /// The borrowed wire format body (everything after the header).
+    pub fn body(&self) -> &'a [u8] {
+        self.body
+    }
+
+    fn compressed_point(&self, index: usize) -> CompressedRistretto {
+        CompressedRistretto(self.body[32 * index..32 * (index + 1)].try_into().unwrap())
+    }
+
+    /// The compressed J component.
+    pub fn J(&self) -> CompressedRistretto {
+        self.compressed_point(0)
+    }
+
+    /// The compressed R component.
+    pub fn R(&self) -> CompressedRistretto {
+        self.compressed_point(1)
+    }
+
+    /// The compressed E_J component.
+    pub fn E_J(&self) -> CompressedRistretto {
+        self.compressed_point(2)
+    }
+
+    /// The compressed E_R component.
+    pub fn E_R(&self) -> CompressedRistretto {
+        self.compressed_point(3)
+    }
+
+    /// Copies the borrowed body into an owned `CompressedAMFSignature`.
+    pub fn to_compressed(&self) -> CompressedAMFSignature {
+        CompressedAMFSignature::from_body(*self.body)
+    }
+
+    /// Decompresses all points and checks all scalars.
+    pub fn decompress(&self) -> Result<AMFSignature, CodecError> {
+        self.decompress_with(&mut decompress)
+    }
+
+    /// `decompress`, decompressing the points with `decompress`
+    pub(super) fn decompress_with(
+        &self,
+        decompress: &mut impl FnMut(&CompressedRistretto) -> Option<RistrettoPoint>,
+    ) -> Result<AMFSignature, CodecError> {
+        let identity = RistrettoPoint::default();
+        let zero = OrProverResponse {
+            c_0: Scalar::ZERO,
+            z_0: Scalar::ZERO,
+            z_1: Scalar::ZERO,
+        };
+        let mut amf_signature = AMFSignature {
+            pi: AMFInternalSignature {
+                prover_commitment: (
+                    (identity, identity),
+                    (
+                        ChaumPedersenProverCommitment {
+                            v_t: identity,
+                            w_t: identity,
+                        },
+                        identity,
+                    ),
+                ),
+                prover_response: (zero, zero),
+            },
+            J: identity,
+            R: identity,
+            E_J: identity,
+            E_R: identity,
+        };
+        self.decompress_into_with(&mut amf_signature, decompress)?;
+        Ok(amf_signature)
+    }
+
+    /// Decompresses all points and checks all scalars, writing the result into `amf_signature`.
+    /// On error, `amf_signature` may be left partially overwritten.
+    pub fn decompress_into(&self, amf_signature: &mut AMFSignature) -> Result<(), CodecError> {
+        self.decompress_into_with(amf_signature, &mut decompress)
+    }
+
+    /// `decompress_into`, decompressing the points with `decompress`
+    pub(super) fn decompress_into_with(
+        &self,
+        amf_signature: &mut AMFSignature,
+        decompress: &mut impl FnMut(&CompressedRistretto) -> Option<RistrettoPoint>,
+    ) -> Result<(), CodecError> {
+        let mut reader = WireReader::new(self.body, SIGNATURE_BODY_LENGTH)?;
+        amf_signature.J = reader.read_point_with(decompress)?;
+        amf_signature.R = reader.read_point_with(decompress)?;
+        amf_signature.E_J = reader.read_point_with(decompress)?;
+        amf_signature.E_R = reader.read_point_with(decompress)?;
+
+        let (or0_commitment, or1_commitment) = &mut amf_signature.pi.prover_commitment;
+        or0_commitment.0 = reader.read_point_with(decompress)?;
+        or0_commitment.1 = reader.read_point_with(decompress)?;
+        or1_commitment.0.v_t = reader.read_point_with(decompress)?;
+        or1_commitment.0.w_t = reader.read_point_with(decompress)?;
+        or1_commitment.1 = reader.read_point_with(decompress)?;
+
+        let (or0_response, or1_response) = &mut amf_signature.pi.prover_response;
+        for response in [or0_response, or1_response] {
+            response.c_0 = reader.read_scalar()?;
+            response.z_0 = reader.read_scalar()?;
+            response.z_1 = reader.read_scalar()?;
+        }
+        amf_signature.validate()
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for AMFSignatureRef<'a> {
+    type Error = CodecError;
+
+    fn try_from(bytes: &'a [u8]) -> Result<Self, Self::Error> {
+        AMFSignatureRef::from_bytes(bytes)
+    }
+}
+
+/// A wire format `AMFPublicKey` borrowed from a buffer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct AMFPublicKeyRef<'a> {
+    role: AMFRole,
+    public_key: &'a [u8; 32],
+}
+
+impl<'a> AMFPublicKeyRef<'a> {
+    /// Parses and validates a wire format public key without copying it.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, CodecError> {
+        let (header, body) = WireHeader::read(bytes, WireArtifactKind::PublicKey)?;
+        match (header.version, header.suite) {
+            (WIRE_FORMAT_V1 | WIRE_FORMAT_V2, SUITE_RISTRETTO255_SHA512) => {
+                if bytes.len() != AMFPublicKey::WIRE_LENGTH {
+                    return Err(CodecError::InvalidLength {
+                        expected: AMFPublicKey::WIRE_LENGTH,
+                        actual: bytes.len(),
+                    });
+                }
+                let (role, public_key) = body.split_at(1);
+                Ok(AMFPublicKeyRef {
+                    role: role_from_byte(role[0])?,
+                    public_key: public_key.try_into().unwrap(),
+                })
+            }
+            _ => Err(header.unsupported()),
+        }
+    }
+
+    /// The role of the key.
+    pub fn role(&self) -> AMFRole {
+        self.role
+    }
+
+    /// The compressed public key.
+    pub fn public_key(&self) -> CompressedRistretto {
+        CompressedRistretto(*self.public_key)
+    }
+
+    /// Decompresses the public key.
+    pub fn decompress(&self) -> Result<AMFPublicKey, CodecError> {
+        self.decompress_with(&mut decompress)
+    }
+
+    /// `decompress`, decompressing the point with `decompress`
+    pub(super) fn decompress_with(
+        &self,
+        decompress: &mut impl FnMut(&CompressedRistretto) -> Option<RistrettoPoint>,
+    ) -> Result<AMFPublicKey, CodecError> {
+        let public_key = decompress(&self.public_key()).ok_or(CodecError::InvalidPoint)?;
+        let public_key = AMFPublicKey {
+            role: self.role,
+            public_key,
+        };
+        public_key.validate()?;
+        Ok(public_key)
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for AMFPublicKeyRef<'a> {
+    type Error = CodecError;
+
+    fn try_from(bytes: &'a [u8]) -> Result<Self, Self::Error> {
+        AMFPublicKeyRef::from_bytes(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::amf::{frank, keygen, verify};
+
+    use super::*;
+
+    #[test]
+    fn test_borrowed_signature() {
+        // 0. Initialize a Sender, Recipient, and Judge
+        let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+        let (recipient_public_key, recipient_secret_key) = keygen(AMFRole::Recipient);
+        let (judge_public_key, _) = keygen(AMFRole::Judge);
+
+        // 1. Frank a message and put it in a network frame
+        let message = b"hello world!";
+        let amf_signature = frank(
+            sender_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+        );
+        let mut frame = sender_public_key.to_bytes();
+        frame.extend(amf_signature.to_bytes());
+        let (encoded_public_key, encoded_signature) = frame.split_at(AMFPublicKey::WIRE_LENGTH);
+
+        // 2. Parse the key and signature straight out of the frame
+        let public_key_ref = AMFPublicKeyRef::from_bytes(encoded_public_key).unwrap();
+        let signature_ref = AMFSignatureRef::try_from(encoded_signature).unwrap();
+        assert_eq!(public_key_ref.role(), AMFRole::Sender);
+        assert_eq!(signature_ref.J(), amf_signature.J.compress());
+        assert_eq!(signature_ref.E_R(), amf_signature.E_R.compress());
+        assert!(std::ptr::eq(
+            signature_ref.body().as_ptr(),
+            encoded_signature[6..].as_ptr()
+        ));
+        assert_eq!(signature_ref.to_compressed(), amf_signature.compress());
+
+        // 3. Decompress and verify
+        assert!(verify(
+            recipient_secret_key,
+            public_key_ref.decompress().unwrap(),
+            recipient_public_key,
+            judge_public_key,
+            message,
+            signature_ref.decompress().unwrap(),
+        ));
+
+        // 4. Malformed inputs are rejected, non-canonical scalars at parse time
+        assert!(AMFSignatureRef::from_bytes(&encoded_signature[1..]).is_err());
+        assert!(AMFPublicKeyRef::from_bytes(encoded_signature).is_err());
+        let mut bad_scalar = encoded_signature.to_vec();
+        let length = bad_scalar.len();
+        bad_scalar[length - 32..].copy_from_slice(&[0xff; 32]);
+        assert_eq!(
+            AMFSignatureRef::from_bytes(&bad_scalar),
+            Err(CodecError::InvalidScalar)
+        );
+        let mut bad_point = encoded_signature.to_vec();
+        bad_point[6..38].copy_from_slice(&[0xff; 32]);
+        let bad_point_ref = AMFSignatureRef::from_bytes(&bad_point).unwrap();
+        assert_eq!(bad_point_ref.decompress(), Err(CodecError::InvalidPoint));
+    }
+}
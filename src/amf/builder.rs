@@ -0,0 +1,383 @@
+//! Builders for Franking, Verifying and Judging
+//!
+//! `Franker`, `Verifier` and `Judge` hold the keys of one party and the public keys of the other
+//! two, and optionally a context that every message is bound to (cf. `bind_context`), so that an
+//! application sets them up once and then franks, verifies or judges messages alone:
+//!
+//! ```
+//! # #[cfg(feature = "rand")] {
+//! use amaze::amf::{keygen, AMFRole, Franker, Verifier};
+//!
+//! let sender = keygen(AMFRole::Sender);
+//! let recipient = keygen(AMFRole::Recipient);
+//! let (judge_public_key, _) = keygen(AMFRole::Judge);
+//!
+//! let franker = Franker::builder()
+//!     .sender(sender.clone())
+//!     .recipient(recipient.0)
+//!     .judge(judge_public_key)
+//!     .context(b"channel 42")
+//!     .build()?;
+//! let amf_signature = franker.frank(b"hello world!");
+//!
+//! let verifier = Verifier::builder()
+//!     .recipient(recipient)
+//!     .sender(sender.0)
+//!     .judge(judge_public_key)
+//!     .context(b"channel 42")
+//!     .build()?;
+//! verifier.verify(b"hello world!", amf_signature)?;
+//! # }
+//! # Ok::<(), amaze::Error>(())
+//! ```
+//!
+//! `build` fails if a key is missing or of the wrong role, and `verify` and `judge` return why they
+//! rejected a signature, as `try_verify` and `try_judge` do.
+
+use alloc::{borrow::Cow, vec::Vec};
+
+use rand_core::CryptoRngCore;
+
+use super::{
+    bind_context, frank_with_rng, try_judge, try_verify, AMFPublicKey, AMFRole, AMFSecretKey,
+    AMFSignature,
+};
+use crate::error::{check_role, Error};
+
+/// the keys and the context that a builder collects
+#[derive(Debug, Clone, Default)]
+struct Parties {
+    secret_key: Option<AMFSecretKey>,
+    sender_public_key: Option<AMFPublicKey>,
+    recipient_public_key: Option<AMFPublicKey>,
+    judge_public_key: Option<AMFPublicKey>,
+    context: Option<Vec<u8>>,
+}
+
+/// the keys and the context of a built `Franker`, `Verifier` or `Judge`
+#[derive(Debug, Clone)]
+struct Party {
+    secret_key: AMFSecretKey,
+    sender_public_key: AMFPublicKey,
+    recipient_public_key: AMFPublicKey,
+    judge_public_key: AMFPublicKey,
+    context: Option<Vec<u8>>,
+}
+
+impl Parties {
+    /// sets the key pair of `role`, whose secret key the party uses
+    fn key_pair(
+        mut self,
+        role: AMFRole,
+        (public_key, secret_key): (AMFPublicKey, AMFSecretKey),
+    ) -> Self {
+        self.secret_key = Some(secret_key);
+        self.public_key(role, public_key)
+    }
+
+    fn public_key(mut self, role: AMFRole, public_key: AMFPublicKey) -> Self {
+        *match role {
+            AMFRole::Sender => &mut self.sender_public_key,
+            AMFRole::Recipient => &mut self.recipient_public_key,
+            AMFRole::Judge => &mut self.judge_public_key,
+        } = Some(public_key);
+        self
+    }
+
+    fn context(mut self, context: &[u8]) -> Self {
+        self.context = Some(context.to_vec());
+        self
+    }
+
+    /// checks that every key is present and of its role, with the secret key of `role`
+    fn build(self, role: AMFRole) -> Result<Party, Error> {
+        let secret_key = self.secret_key.ok_or(Error::MissingKey(role))?;
+        check_role(role, secret_key.role)?;
+        let [sender_public_key, recipient_public_key, judge_public_key] = [
+            (AMFRole::Sender, self.sender_public_key),
+            (AMFRole::Recipient, self.recipient_public_key),
+            (AMFRole::Judge, self.judge_public_key),
+        ]
+        .map(|(role, public_key)| -> Result<AMFPublicKey, Error> {
+            let public_key = public_key.ok_or(Error::MissingKey(role))?;
+            check_role(role, public_key.role)?;
+            Ok(public_key)
+        });
+        Ok(Party {
+            secret_key,
+            sender_public_key: sender_public_key?,
+            recipient_public_key: recipient_public_key?,
+            judge_public_key: judge_public_key?,
+            context: self.context,
+        })
+    }
+}
+
+impl Party {
+    /// `message`, bound to the context if there is one
+    fn bind<'a>(&self, message: &'a [u8]) -> Cow<'a, [u8]> {
+        match &self.context {
+            Some(context) => Cow::Owned(bind_context(context, message)),
+            None => Cow::Borrowed(message),
+        }
+    }
+}
+
+/// A sender's keys, the recipient's and the judge's public keys, and a context, for franking.
+#[derive(Debug, Clone)]
+pub struct Franker(Party);
+
+/// Builds a `Franker`; the sender's key pair, the recipient's and the judge's public keys are
+/// required, the context is not.
+#[derive(Debug, Clone, Default)]
+pub struct FrankerBuilder(Parties);
+
+impl Franker {
+    pub fn builder() -> FrankerBuilder {
+        FrankerBuilder::default()
+    }
+
+    /// `frank` of `message`, or `frank_with_context` if the builder was given a context
+    #[cfg(any(feature = "rand", test))]
+    pub fn frank(&self, message: &[u8]) -> AMFSignature {
+        self.frank_with_rng(&mut rand::thread_rng(), message)
+    }
+
+    /// `frank` with the randomness of `rng`, e.g. where `rand::thread_rng` is unavailable
+    pub fn frank_with_rng(&self, rng: &mut dyn CryptoRngCore, message: &[u8]) -> AMFSignature {
+        frank_with_rng(
+            rng,
+            &self.0.secret_key,
+            self.0.sender_public_key,
+            self.0.recipient_public_key,
+            self.0.judge_public_key,
+            &self.0.bind(message),
+        )
+    }
+}
+
+impl FrankerBuilder {
+    /// the sender's key pair, as returned by `keygen`
+    pub fn sender(self, key_pair: (AMFPublicKey, AMFSecretKey)) -> Self {
+        FrankerBuilder(self.0.key_pair(AMFRole::Sender, key_pair))
+    }
+
+    pub fn recipient(self, recipient_public_key: AMFPublicKey) -> Self {
+        FrankerBuilder(self.0.public_key(AMFRole::Recipient, recipient_public_key))
+    }
+
+    pub fn judge(self, judge_public_key: AMFPublicKey) -> Self {
+        FrankerBuilder(self.0.public_key(AMFRole::Judge, judge_public_key))
+    }
+
+    /// binds every message to `context`, cf. `frank_with_context`
+    pub fn context(self, context: &[u8]) -> Self {
+        FrankerBuilder(self.0.context(context))
+    }
+
+    pub fn build(self) -> Result<Franker, Error> {
+        Ok(Franker(self.0.build(AMFRole::Sender)?))
+    }
+}
+
+/// A recipient's keys, the sender's and the judge's public keys, and a context, for verifying.
+#[derive(Debug, Clone)]
+pub struct Verifier(Party);
+
+/// Builds a `Verifier`; the recipient's key pair, the sender's and the judge's public keys are
+/// required, the context is not.
+#[derive(Debug, Clone, Default)]
+pub struct VerifierBuilder(Parties);
+
+impl Verifier {
+    pub fn builder() -> VerifierBuilder {
+        VerifierBuilder::default()
+    }
+
+    /// `try_verify` of `message`, bound to the context if the builder was given one
+    pub fn verify(&self, message: &[u8], amf_signature: AMFSignature) -> Result<(), Error> {
+        try_verify(
+            &self.0.secret_key,
+            self.0.sender_public_key,
+            self.0.recipient_public_key,
+            self.0.judge_public_key,
+            &self.0.bind(message),
+            amf_signature,
+        )
+    }
+}
+
+impl VerifierBuilder {
+    /// the recipient's key pair, as returned by `keygen`
+    pub fn recipient(self, key_pair: (AMFPublicKey, AMFSecretKey)) -> Self {
+        VerifierBuilder(self.0.key_pair(AMFRole::Recipient, key_pair))
+    }
+
+    pub fn sender(self, sender_public_key: AMFPublicKey) -> Self {
+        VerifierBuilder(self.0.public_key(AMFRole::Sender, sender_public_key))
+    }
+
+    pub fn judge(self, judge_public_key: AMFPublicKey) -> Self {
+        VerifierBuilder(self.0.public_key(AMFRole::Judge, judge_public_key))
+    }
+
+    /// checks that every message is bound to `context`, cf. `verify_with_context`
+    pub fn context(self, context: &[u8]) -> Self {
+        VerifierBuilder(self.0.context(context))
+    }
+
+    pub fn build(self) -> Result<Verifier, Error> {
+        Ok(Verifier(self.0.build(AMFRole::Recipient)?))
+    }
+}
+
+/// A judge's keys, the sender's and the recipient's public keys, and a context, for judging.
+#[derive(Debug, Clone)]
+pub struct Judge(Party);
+
+/// Builds a `Judge`; the judge's key pair, the sender's and the recipient's public keys are
+/// required, the context is not.
+#[derive(Debug, Clone, Default)]
+pub struct JudgeBuilder(Parties);
+
+impl Judge {
+    pub fn builder() -> JudgeBuilder {
+        JudgeBuilder::default()
+    }
+
+    /// `try_judge` of `message`, bound to the context if the builder was given one
+    pub fn judge(&self, message: &[u8], amf_signature: AMFSignature) -> Result<(), Error> {
+        try_judge(
+            &self.0.secret_key,
+            self.0.sender_public_key,
+            self.0.recipient_public_key,
+            self.0.judge_public_key,
+            &self.0.bind(message),
+            amf_signature,
+        )
+    }
+}
+
+impl JudgeBuilder {
+    /// the judge's key pair, as returned by `keygen`
+    pub fn judge(self, key_pair: (AMFPublicKey, AMFSecretKey)) -> Self {
+        JudgeBuilder(self.0.key_pair(AMFRole::Judge, key_pair))
+    }
+
+    pub fn sender(self, sender_public_key: AMFPublicKey) -> Self {
+        JudgeBuilder(self.0.public_key(AMFRole::Sender, sender_public_key))
+    }
+
+    pub fn recipient(self, recipient_public_key: AMFPublicKey) -> Self {
+        JudgeBuilder(self.0.public_key(AMFRole::Recipient, recipient_public_key))
+    }
+
+    /// checks that every message is bound to `context`, cf. `judge_with_context`
+    pub fn context(self, context: &[u8]) -> Self {
+        JudgeBuilder(self.0.context(context))
+    }
+
+    pub fn build(self) -> Result<Judge, Error> {
+        Ok(Judge(self.0.build(AMFRole::Judge)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amf::{keygen, verify_with_context, VerifyError};
+
+    #[test]
+    fn test_builders() {
+        let sender = keygen(AMFRole::Sender);
+        let recipient = keygen(AMFRole::Recipient);
+        let judge = keygen(AMFRole::Judge);
+        let context = b"channel 42";
+        let message = b"hello world!";
+
+        // 0. Build a franker, a verifier and a judge in the same context
+        let franker = Franker::builder()
+            .sender(sender.clone())
+            .recipient(recipient.0)
+            .judge(judge.0)
+            .context(context)
+            .build()
+            .unwrap();
+        let verifier = Verifier::builder()
+            .recipient(recipient.clone())
+            .sender(sender.0)
+            .judge(judge.0)
+            .context(context)
+            .build()
+            .unwrap();
+        let judge_party = Judge::builder()
+            .judge(judge.clone())
+            .sender(sender.0)
+            .recipient(recipient.0)
+            .context(context)
+            .build()
+            .unwrap();
+
+        // 1. Their franks verify and judge, as the free functions with the context do
+        let amf_signature = franker.frank(message);
+        assert!(verifier.verify(message, amf_signature).is_ok());
+        assert!(judge_party.judge(message, amf_signature).is_ok());
+        assert!(verify_with_context(
+            recipient.1.clone(),
+            sender.0,
+            recipient.0,
+            judge.0,
+            context,
+            message,
+            amf_signature,
+        ));
+        assert!(matches!(
+            verifier.verify(b"goodbye world!", amf_signature),
+            Err(Error::Verify(VerifyError::InvalidSignature))
+        ));
+
+        // 2. Without a context, messages are franked as they are
+        let verifier_without_context = Verifier::builder()
+            .recipient(recipient.clone())
+            .sender(sender.0)
+            .judge(judge.0)
+            .build()
+            .unwrap();
+        assert!(verifier_without_context
+            .verify(message, amf_signature)
+            .is_err());
+        let franker_without_context = Franker::builder()
+            .sender(sender.clone())
+            .recipient(recipient.0)
+            .judge(judge.0)
+            .build()
+            .unwrap();
+        assert!(verifier_without_context
+            .verify(message, franker_without_context.frank(message))
+            .is_ok());
+
+        // 3. Missing keys and keys of the wrong role are reported
+        assert!(matches!(
+            Franker::builder()
+                .sender(sender.clone())
+                .recipient(recipient.0)
+                .build(),
+            Err(Error::MissingKey(AMFRole::Judge))
+        ));
+        assert!(matches!(
+            Verifier::builder().sender(sender.0).judge(judge.0).build(),
+            Err(Error::MissingKey(AMFRole::Recipient))
+        ));
+        assert!(matches!(
+            Judge::builder()
+                .judge(recipient)
+                .sender(sender.0)
+                .recipient(judge.0)
+                .build(),
+            Err(Error::RoleMismatch {
+                expected: AMFRole::Judge,
+                actual: AMFRole::Recipient,
+            })
+        ));
+    }
+}
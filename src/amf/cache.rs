@@ -0,0 +1,140 @@
+//! Caching Decompressed Points
+//!
+//! Decompressing a Ristretto point costs a field inversion, about a tenth of a verification. A
+//! moderation pipeline decodes the same sender and judge keys over and over, and sometimes the
+//! same signature (e.g., reported by several recipients); a `DecompressionCache` keeps the most
+//! recently decompressed points by their compressed bytes, so that decoding them again is a
+//! lookup. Only valid points are cached; invalid ones are rejected as usual, every time.
+//!
+//! The cache is shared between threads behind a lock, and holds only public values.
+
+use std::{fmt, num::NonZeroUsize, sync::Mutex};
+
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use lru::LruCache;
+
+use super::{
+    codec::{decompress, CodecError},
+    AMFPublicKey, AMFPublicKeyRef, AMFSignature, AMFSignatureRef,
+};
+
+/// An LRU cache of decompressed points, cf. the module documentation.
+pub struct DecompressionCache {
+    points: Mutex<LruCache<[u8; 32], RistrettoPoint>>,
+}
+
+impl DecompressionCache {
+    /// A cache of at most `capacity` points, 160 bytes each.
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        DecompressionCache {
+            points: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+
+    /// the number of cached points
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Decompresses `compressed`, or returns it from the cache.
+    pub fn decompress(&self, compressed: &CompressedRistretto) -> Option<RistrettoPoint> {
+        if let Some(point) = self.lock().get(compressed.as_bytes()) {
+            return Some(*point);
+        }
+        // Decompress without holding the lock, so that other threads can look up meanwhile
+        let point = decompress(compressed)?;
+        self.lock().put(compressed.to_bytes(), point);
+        Some(point)
+    }
+
+    /// `AMFPublicKey::from_bytes`, through the cache.
+    pub fn public_key_from_bytes(&self, bytes: &[u8]) -> Result<AMFPublicKey, CodecError> {
+        AMFPublicKeyRef::from_bytes(bytes)?
+            .decompress_with(&mut |compressed| self.decompress(compressed))
+    }
+
+    /// `AMFSignature::from_bytes`, through the cache.
+    pub fn signature_from_bytes(&self, bytes: &[u8]) -> Result<AMFSignature, CodecError> {
+        AMFSignatureRef::from_bytes(bytes)?
+            .decompress_with(&mut |compressed| self.decompress(compressed))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LruCache<[u8; 32], RistrettoPoint>> {
+        // The cache is consistent after every operation, so a panic elsewhere cannot corrupt it
+        self.points
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl fmt::Debug for DecompressionCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let points = self.lock();
+        f.debug_struct("DecompressionCache")
+            .field("len", &points.len())
+            .field("capacity", &points.cap())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amf::{frank, keygen, verify, AMFRole};
+
+    #[test]
+    fn test_decompression_cache() {
+        // 0. Initialize a Sender, Recipient, and Judge, and a cache for four points
+        let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+        let (recipient_public_key, recipient_secret_key) = keygen(AMFRole::Recipient);
+        let (judge_public_key, _) = keygen(AMFRole::Judge);
+        let cache = DecompressionCache::new(NonZeroUsize::new(4).unwrap());
+
+        // 1. Keys and signatures decode through the cache as they do without it
+        let encoded_public_key = sender_public_key.to_bytes();
+        assert_eq!(
+            cache.public_key_from_bytes(&encoded_public_key),
+            Ok(sender_public_key)
+        );
+        assert_eq!(cache.len(), 1);
+        let message = b"hello world!";
+        let amf_signature = frank(
+            sender_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+        );
+        let decoded = cache.signature_from_bytes(&amf_signature.to_bytes());
+        assert_eq!(decoded, AMFSignature::from_bytes(&amf_signature.to_bytes()));
+        assert!(verify(
+            recipient_secret_key,
+            cache.public_key_from_bytes(&encoded_public_key).unwrap(),
+            recipient_public_key,
+            judge_public_key,
+            message,
+            decoded.unwrap(),
+        ));
+
+        // 2. The cache holds the most recent points only, and the key was used last
+        assert_eq!(cache.len(), 4);
+        assert!(cache
+            .lock()
+            .contains(sender_public_key.public_key.compress().as_bytes()));
+        assert!(!cache.lock().contains(amf_signature.J.compress().as_bytes()));
+
+        // 3. Invalid points are rejected, and not cached
+        let mut bad_point = encoded_public_key.clone();
+        let length = bad_point.len();
+        bad_point[length - 32..].copy_from_slice(&[0xff; 32]);
+        assert_eq!(
+            cache.public_key_from_bytes(&bad_point),
+            Err(CodecError::InvalidPoint)
+        );
+        assert!(!cache.lock().contains(&[0xff; 32]));
+    }
+}
@@ -1,22 +1,109 @@
 //! Serializable codec for exposed AMF structs.
 //!
-//! A series of hacks to compensate for Scalar and RistrettoPoint not being serializable.
+//! A series of hacks that predate curve25519-dalek's serde support (which the `pok` types use
+//! directly); the AMF structs keep their own representations:
+//!
+//! Human-readable serializers (e.g., serde_json) get hex strings for points and scalars, while
+//! binary serializers (e.g., bincode) get the versioned wire format produced by `to_bytes`.
+//!
+//! The wire format prefixes every artifact with a header consisting of the magic value `AMF`, a
+//! format version, a suite identifier, and the kind of artifact, so that future curve/hash changes
+//! are rejected instead of silently misparsed:
+//!
+//! ```text
+//! | magic (3) | version (1) | suite (1) | kind (1) | body |
+//! ```
+//!
+//! Bodies (versions 1 and 2, suite ristretto255-sha512) are fixed-length concatenations of
+//! compressed points and canonical scalars (other suites use the same layout with their own
+//! encodings, cf. `generic`):
+//! - public key: `role (1) || public_key (32)`
+//! - secret key: `role (1) || secret_key (32)`
+//! - signature: `J || R || E_J || E_R || t_sender || t_J || v_t || w_t || t_R
+//!               || c_0 || z_0 || z_1 (first OR) || c_0 || z_0 || z_1 (second OR)`, 32 bytes each
 #![allow(non_snake_case)]
 
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(feature = "serde")]
+use core::marker::PhantomData;
+
 use curve25519_dalek::{
     ristretto::{CompressedRistretto, RistrettoPoint},
     scalar::Scalar,
+    traits::{Identity, IsIdentity},
 };
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use subtle::{Choice, ConstantTimeEq};
+#[cfg(all(feature = "serde", feature = "zeroize"))]
+use zeroize::Zeroize;
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroizing;
 
+use crate::pok::profiling;
+#[cfg(feature = "serde")]
 use crate::pok::{chaum_pedersen::ChaumPedersenProverCommitment, or_proof::OrProverResponse};
 
-use super::{AMFInternalSignature, AMFPublicKey, AMFRole, AMFSecretKey, AMFSignature};
+#[cfg(feature = "serde")]
+use super::AMFInternalSignature;
+use super::{AMFPublicKey, AMFRole, AMFSecretKey, AMFSignature, CompressedAMFSignature};
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+/// (De)serializes 32 bytes as a hex string for human-readable formats, and as raw bytes otherwise.
+#[cfg(feature = "serde")]
+mod hex_or_bytes {
+    use alloc::string::String;
+
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub(super) fn serialize<S>(bytes: &[u8; 32], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&hex::encode(bytes))
+        } else {
+            serde::Serialize::serialize(bytes, serializer)
+        }
+    }
+
+    pub(super) fn deserialize<'de, D>(deserializer: D) -> Result<[u8; 32], D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if deserializer.is_human_readable() {
+            let hex_string = String::deserialize(deserializer)?;
+            let mut bytes = [0u8; 32];
+            hex::decode_to_slice(hex_string, &mut bytes).map_err(D::Error::custom)?;
+            Ok(bytes)
+        } else {
+            <[u8; 32]>::deserialize(deserializer)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(Debug, Copy, Clone, Serialize)]
+#[serde(transparent)]
 struct SerializableRistrettoPoint {
+    #[serde(with = "hex_or_bytes")]
     point_as_bytes: [u8; 32],
 }
+// validates on deserialization, so that the conversion below cannot panic on untrusted input
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for SerializableRistrettoPoint {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let point_as_bytes = hex_or_bytes::deserialize(deserializer)?;
+        if CompressedRistretto(point_as_bytes).decompress().is_none() {
+            return Err(serde::de::Error::custom(CodecError::InvalidPoint));
+        }
+        Ok(SerializableRistrettoPoint { point_as_bytes })
+    }
+}
+#[cfg(feature = "serde")]
 impl From<RistrettoPoint> for SerializableRistrettoPoint {
     fn from(point: RistrettoPoint) -> Self {
         SerializableRistrettoPoint {
@@ -24,6 +111,7 @@ impl From<RistrettoPoint> for SerializableRistrettoPoint {
         }
     }
 }
+#[cfg(feature = "serde")]
 impl From<SerializableRistrettoPoint> for RistrettoPoint {
     fn from(serialized_point: SerializableRistrettoPoint) -> Self {
         CompressedRistretto::from_slice(&serialized_point.point_as_bytes)
@@ -33,10 +121,28 @@ impl From<SerializableRistrettoPoint> for RistrettoPoint {
     }
 }
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[cfg(feature = "serde")]
+#[derive(Debug, Copy, Clone, Serialize)]
+#[serde(transparent)]
 struct SerializableRistrettoScalar {
+    #[serde(with = "hex_or_bytes")]
     scalar_as_bytes: [u8; 32],
 }
+// ditto
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for SerializableRistrettoScalar {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let scalar_as_bytes = hex_or_bytes::deserialize(deserializer)?;
+        if bool::from(Scalar::from_canonical_bytes(scalar_as_bytes).is_none()) {
+            return Err(serde::de::Error::custom(CodecError::InvalidScalar));
+        }
+        Ok(SerializableRistrettoScalar { scalar_as_bytes })
+    }
+}
+#[cfg(feature = "serde")]
 impl From<Scalar> for SerializableRistrettoScalar {
     fn from(scalar: Scalar) -> Self {
         SerializableRistrettoScalar {
@@ -44,17 +150,20 @@ impl From<Scalar> for SerializableRistrettoScalar {
         }
     }
 }
+#[cfg(feature = "serde")]
 impl From<SerializableRistrettoScalar> for Scalar {
     fn from(serialized_scalar: SerializableRistrettoScalar) -> Self {
         Scalar::from_canonical_bytes(serialized_scalar.scalar_as_bytes).unwrap()
     }
 }
 
+#[cfg(feature = "serde")]
 #[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub struct SerializableAMFPublicKey {
     role: AMFRole,
     public_key: SerializableRistrettoPoint,
 }
+#[cfg(feature = "serde")]
 impl From<AMFPublicKey> for SerializableAMFPublicKey {
     fn from(public_key: AMFPublicKey) -> Self {
         SerializableAMFPublicKey {
@@ -63,6 +172,7 @@ impl From<AMFPublicKey> for SerializableAMFPublicKey {
         }
     }
 }
+#[cfg(feature = "serde")]
 impl From<SerializableAMFPublicKey> for AMFPublicKey {
     fn from(serializable_public_key: SerializableAMFPublicKey) -> Self {
         AMFPublicKey {
@@ -72,41 +182,82 @@ impl From<SerializableAMFPublicKey> for AMFPublicKey {
     }
 }
 
+#[cfg(feature = "serde")]
 impl Serialize for AMFPublicKey {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        let serializable_public_key = SerializableAMFPublicKey::from(*self);
-        serializable_public_key.serialize(serializer)
+        if serializer.is_human_readable() {
+            let serializable_public_key = SerializableAMFPublicKey::from(*self);
+            serializable_public_key.serialize(serializer)
+        } else {
+            serializer.serialize_bytes(&self.to_bytes())
+        }
     }
 }
+#[cfg(feature = "serde")]
 impl<'de> Deserialize<'de> for AMFPublicKey {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        let serializable_public_key = SerializableAMFPublicKey::deserialize(deserializer)?;
-        let public_key = AMFPublicKey::from(serializable_public_key);
-        Ok(public_key)
+        if deserializer.is_human_readable() {
+            let serializable_public_key = SerializableAMFPublicKey::deserialize(deserializer)?;
+            let public_key = AMFPublicKey::from(serializable_public_key);
+            public_key.validate().map_err(serde::de::Error::custom)?;
+            Ok(public_key)
+        } else {
+            deserializer.deserialize_bytes(WireVisitor::<AMFPublicKey>(PhantomData))
+        }
     }
 
     fn deserialize_in_place<D>(deserializer: D, place: &mut Self) -> Result<(), D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        // TODO: Think about actually doing this in-place?
-        let serializable_public_key = SerializableAMFPublicKey::deserialize(deserializer)?;
-        *place = AMFPublicKey::from(serializable_public_key);
-        Ok(())
+        if deserializer.is_human_readable() {
+            // Human-readable formats are not used for bulk ingestion, so just overwrite `place`.
+            *place = AMFPublicKey::deserialize(deserializer)?;
+            Ok(())
+        } else {
+            deserializer.deserialize_bytes(WireInPlaceVisitor(place))
+        }
     }
 }
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+/// With the `zeroize` feature, the scalar is overwritten when the key is dropped, which is why the
+/// key is `Clone` but not `Copy`.
+#[cfg(feature = "serde")]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct SerializableAMFSecretKey {
     role: AMFRole,
     secret_key: SerializableRistrettoScalar,
 }
+#[cfg(all(feature = "serde", feature = "zeroize"))]
+impl Zeroize for SerializableAMFSecretKey {
+    fn zeroize(&mut self) {
+        self.secret_key.scalar_as_bytes.zeroize();
+    }
+}
+#[cfg(all(feature = "serde", feature = "zeroize"))]
+impl Drop for SerializableAMFSecretKey {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+#[cfg(all(feature = "serde", feature = "zeroize"))]
+impl zeroize::ZeroizeOnDrop for SerializableAMFSecretKey {}
+#[cfg(feature = "serde")]
+impl fmt::Debug for SerializableAMFSecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SerializableAMFSecretKey")
+            .field("role", &self.role)
+            .field("secret_key", &format_args!("<redacted>"))
+            .finish()
+    }
+}
+#[cfg(feature = "serde")]
 impl From<AMFSecretKey> for SerializableAMFSecretKey {
     fn from(secret_key: AMFSecretKey) -> Self {
         SerializableAMFSecretKey {
@@ -115,6 +266,7 @@ impl From<AMFSecretKey> for SerializableAMFSecretKey {
         }
     }
 }
+#[cfg(feature = "serde")]
 impl From<SerializableAMFSecretKey> for AMFSecretKey {
     fn from(serializable_secret_key: SerializableAMFSecretKey) -> Self {
         AMFSecretKey {
@@ -124,41 +276,57 @@ impl From<SerializableAMFSecretKey> for AMFSecretKey {
     }
 }
 
+#[cfg(feature = "serde")]
 impl Serialize for AMFSecretKey {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        let serializable_secret_key = SerializableAMFSecretKey::from(*self);
-        serializable_secret_key.serialize(serializer)
+        if serializer.is_human_readable() {
+            let serializable_secret_key = SerializableAMFSecretKey::from(self.clone());
+            serializable_secret_key.serialize(serializer)
+        } else {
+            serializer.serialize_bytes(&self.to_bytes())
+        }
     }
 }
+#[cfg(feature = "serde")]
 impl<'de> Deserialize<'de> for AMFSecretKey {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        let serializable_secret_key = SerializableAMFSecretKey::deserialize(deserializer)?;
-        let secret_key = AMFSecretKey::from(serializable_secret_key);
-        Ok(secret_key)
+        if deserializer.is_human_readable() {
+            let serializable_secret_key = SerializableAMFSecretKey::deserialize(deserializer)?;
+            let secret_key = AMFSecretKey::from(serializable_secret_key);
+            secret_key.validate().map_err(serde::de::Error::custom)?;
+            Ok(secret_key)
+        } else {
+            deserializer.deserialize_bytes(WireVisitor::<AMFSecretKey>(PhantomData))
+        }
     }
 
     fn deserialize_in_place<D>(deserializer: D, place: &mut Self) -> Result<(), D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        // TODO: Think about actually doing this in-place?
-        let serializable_secret_key = SerializableAMFSecretKey::deserialize(deserializer)?;
-        *place = AMFSecretKey::from(serializable_secret_key);
-        Ok(())
+        if deserializer.is_human_readable() {
+            // Human-readable formats are not used for bulk ingestion, so just overwrite `place`.
+            *place = AMFSecretKey::deserialize(deserializer)?;
+            Ok(())
+        } else {
+            deserializer.deserialize_bytes(WireInPlaceVisitor(place))
+        }
     }
 }
 
+#[cfg(feature = "serde")]
 #[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub struct SerializableChaumPedersenProverCommitment {
     v_t: SerializableRistrettoPoint,
     w_t: SerializableRistrettoPoint,
 }
+#[cfg(feature = "serde")]
 impl From<ChaumPedersenProverCommitment> for SerializableChaumPedersenProverCommitment {
     fn from(commitment: ChaumPedersenProverCommitment) -> Self {
         SerializableChaumPedersenProverCommitment {
@@ -167,6 +335,7 @@ impl From<ChaumPedersenProverCommitment> for SerializableChaumPedersenProverComm
         }
     }
 }
+#[cfg(feature = "serde")]
 impl From<SerializableChaumPedersenProverCommitment> for ChaumPedersenProverCommitment {
     fn from(serialized_commitment: SerializableChaumPedersenProverCommitment) -> Self {
         ChaumPedersenProverCommitment {
@@ -176,12 +345,14 @@ impl From<SerializableChaumPedersenProverCommitment> for ChaumPedersenProverComm
     }
 }
 
+#[cfg(feature = "serde")]
 #[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub struct SerializableOrProverResponse {
     c_0: SerializableRistrettoScalar,
     z_0: SerializableRistrettoScalar,
     z_1: SerializableRistrettoScalar,
 }
+#[cfg(feature = "serde")]
 impl From<OrProverResponse<Scalar, Scalar>> for SerializableOrProverResponse {
     fn from(response: OrProverResponse<Scalar, Scalar>) -> Self {
         SerializableOrProverResponse {
@@ -191,6 +362,7 @@ impl From<OrProverResponse<Scalar, Scalar>> for SerializableOrProverResponse {
         }
     }
 }
+#[cfg(feature = "serde")]
 impl From<SerializableOrProverResponse> for OrProverResponse<Scalar, Scalar> {
     fn from(serialized_response: SerializableOrProverResponse) -> Self {
         OrProverResponse {
@@ -201,6 +373,7 @@ impl From<SerializableOrProverResponse> for OrProverResponse<Scalar, Scalar> {
     }
 }
 
+#[cfg(feature = "serde")]
 #[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub struct SerializableAMFInternalSignature {
     or_prover_commitment_0: (SerializableRistrettoPoint, SerializableRistrettoPoint),
@@ -211,6 +384,7 @@ pub struct SerializableAMFInternalSignature {
     or_prover_response_0: SerializableOrProverResponse,
     or_prover_response_1: SerializableOrProverResponse,
 }
+#[cfg(feature = "serde")]
 impl From<AMFInternalSignature> for SerializableAMFInternalSignature {
     fn from(signature: AMFInternalSignature) -> Self {
         SerializableAMFInternalSignature {
@@ -227,6 +401,7 @@ impl From<AMFInternalSignature> for SerializableAMFInternalSignature {
         }
     }
 }
+#[cfg(feature = "serde")]
 impl From<SerializableAMFInternalSignature> for AMFInternalSignature {
     fn from(serialized_signature: SerializableAMFInternalSignature) -> Self {
         AMFInternalSignature {
@@ -248,6 +423,7 @@ impl From<SerializableAMFInternalSignature> for AMFInternalSignature {
     }
 }
 
+#[cfg(feature = "serde")]
 #[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub struct SerializableAMFSignature {
     pi: SerializableAMFInternalSignature,
@@ -256,6 +432,7 @@ pub struct SerializableAMFSignature {
     E_J: SerializableRistrettoPoint,
     E_R: SerializableRistrettoPoint,
 }
+#[cfg(feature = "serde")]
 impl From<AMFSignature> for SerializableAMFSignature {
     fn from(amf_signature: AMFSignature) -> Self {
         SerializableAMFSignature {
@@ -267,6 +444,7 @@ impl From<AMFSignature> for SerializableAMFSignature {
         }
     }
 }
+#[cfg(feature = "serde")]
 impl From<SerializableAMFSignature> for AMFSignature {
     fn from(serialized_amf_signature: SerializableAMFSignature) -> Self {
         AMFSignature {
@@ -279,43 +457,566 @@ impl From<SerializableAMFSignature> for AMFSignature {
     }
 }
 
+#[cfg(feature = "serde")]
 impl Serialize for AMFSignature {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        let serializable_amf_signature = SerializableAMFSignature::from(*self);
-        serializable_amf_signature.serialize(serializer)
+        if serializer.is_human_readable() {
+            let serializable_amf_signature = SerializableAMFSignature::from(*self);
+            serializable_amf_signature.serialize(serializer)
+        } else {
+            serializer.serialize_bytes(&self.to_bytes())
+        }
     }
 }
+#[cfg(feature = "serde")]
 impl<'de> Deserialize<'de> for AMFSignature {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        let serializable_amf_signature = SerializableAMFSignature::deserialize(deserializer)?;
-        let amf_signature = AMFSignature::from(serializable_amf_signature);
-        Ok(amf_signature)
+        if deserializer.is_human_readable() {
+            let serializable_amf_signature = SerializableAMFSignature::deserialize(deserializer)?;
+            let amf_signature = AMFSignature::from(serializable_amf_signature);
+            amf_signature.validate().map_err(serde::de::Error::custom)?;
+            Ok(amf_signature)
+        } else {
+            deserializer.deserialize_bytes(WireVisitor::<AMFSignature>(PhantomData))
+        }
     }
 
     fn deserialize_in_place<D>(deserializer: D, place: &mut Self) -> Result<(), D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        // TODO: Think about actually doing this in-place?
-        let serializable_amf_signature = SerializableAMFSignature::deserialize(deserializer)?;
-        *place = AMFSignature::from(serializable_amf_signature);
+        if deserializer.is_human_readable() {
+            // Human-readable formats are not used for bulk ingestion, so just overwrite `place`.
+            *place = AMFSignature::deserialize(deserializer)?;
+            Ok(())
+        } else {
+            deserializer.deserialize_bytes(WireInPlaceVisitor(place))
+        }
+    }
+}
+
+//
+// Versioned wire format.
+//
+
+/// The magic value that prefixes every encoded AMF artifact.
+pub const WIRE_MAGIC: [u8; 3] = *b"AMF";
+
+/// The first version of the wire format.
+pub const WIRE_FORMAT_V1: u8 = 1;
+
+/// Version 2 signatures hash the statement into the Fiat-Shamir challenge and domain-separate
+/// every input of the hash, cf. `pok::domain`; both changes make up this one version, and any
+/// further change to the hash layout needs a new one. `AMFSignature::from_bytes` rejects version
+/// 1 signatures; `VersionedAMFSignature` decodes and verifies both. Key encodings did not change
+/// and both versions are accepted.
+pub const WIRE_FORMAT_V2: u8 = 2;
+
+/// The version of the wire format produced by `to_bytes`.
+pub const WIRE_FORMAT_VERSION: u8 = WIRE_FORMAT_V2;
+
+/// Suite identifier for AMF over ristretto255 with SHA-512 Fiat-Shamir challenges.
+pub const SUITE_RISTRETTO255_SHA512: u8 = 1;
+
+/// Suite identifier for AMF over secp256k1 with SHA-512 Fiat-Shamir challenges, cf. `secp256k1`.
+pub const SUITE_SECP256K1_SHA512: u8 = 2;
+
+/// Suite identifier for AMF over NIST P-256 with SHA-512 Fiat-Shamir challenges, cf. `p256`.
+pub const SUITE_P256_SHA512: u8 = 3;
+
+/// Suite identifier for the compact, key-binding AMF signatures of `amf::v2`, cf. `AMFSuite`.
+pub const SUITE_RISTRETTO255_SHA512_V2: u8 = 4;
+
+/// Suite identifier for AMF signatures with a publicly verifiable signature, cf. `signed`.
+pub const SUITE_RISTRETTO255_SHA512_SIGNED: u8 = 6;
+
+/// The length of the header (magic, version, suite, and kind) in bytes.
+pub const WIRE_HEADER_LENGTH: usize = 6;
+
+const PUBLIC_KEY_BODY_LENGTH: usize = 1 + 32;
+const SECRET_KEY_BODY_LENGTH: usize = 1 + 32;
+pub(super) const SIGNATURE_BODY_LENGTH: usize = 15 * 32;
+
+/// The kind of artifact that follows the header.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(u8)]
+pub(super) enum WireArtifactKind {
+    PublicKey = 1,
+    SecretKey = 2,
+    Signature = 3,
+}
+
+/// Errors that can occur while decoding the wire format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodecError {
+    /// The input was not of the expected length.
+    InvalidLength { expected: usize, actual: usize },
+    /// The input did not start with `WIRE_MAGIC`.
+    InvalidMagic,
+    /// The input uses a wire format version this library does not understand.
+    UnsupportedVersion(u8),
+    /// The input uses a suite this library does not implement.
+    UnsupportedSuite(u8),
+    /// The input encodes a different kind of artifact than the one requested.
+    UnexpectedArtifact(u8),
+    /// The role byte is not a valid `AMFRole`.
+    InvalidRole(u8),
+    /// A point is not a valid compressed Ristretto point.
+    InvalidPoint,
+    /// A scalar is not canonically encoded.
+    InvalidScalar,
+    /// A public key or signature component is the identity point.
+    IdentityPoint,
+    /// A secret key is zero.
+    ZeroScalar,
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodecError::InvalidLength { expected, actual } => {
+                write!(f, "expected {} bytes, got {}", expected, actual)
+            }
+            CodecError::InvalidMagic => write!(f, "invalid magic value"),
+            CodecError::UnsupportedVersion(version) => {
+                write!(f, "unsupported wire format version {}", version)
+            }
+            CodecError::UnsupportedSuite(suite) => write!(f, "unsupported suite {}", suite),
+            CodecError::UnexpectedArtifact(kind) => write!(f, "unexpected artifact kind {}", kind),
+            CodecError::InvalidRole(role) => write!(f, "invalid role {}", role),
+            CodecError::InvalidPoint => write!(f, "invalid compressed ristretto point"),
+            CodecError::InvalidScalar => write!(f, "non-canonical scalar"),
+            CodecError::IdentityPoint => write!(f, "unexpected identity point"),
+            CodecError::ZeroScalar => write!(f, "unexpected zero scalar"),
+        }
+    }
+}
+
+impl core::error::Error for CodecError {}
+
+/// The parsed header of an encoded artifact.
+pub(super) struct WireHeader {
+    pub(super) version: u8,
+    pub(super) suite: u8,
+}
+
+impl WireHeader {
+    pub(super) fn write(buf: &mut Vec<u8>, kind: WireArtifactKind) {
+        Self::write_suite(buf, SUITE_RISTRETTO255_SHA512, kind);
+    }
+
+    /// writes the header of an artifact of another suite, cf. `generic`
+    pub(super) fn write_suite(buf: &mut Vec<u8>, suite: u8, kind: WireArtifactKind) {
+        Self::write_version(buf, WIRE_FORMAT_VERSION, suite, kind);
+    }
+
+    /// writes the header of an artifact of an older version, cf. `versioned`
+    pub(super) fn write_version(buf: &mut Vec<u8>, version: u8, suite: u8, kind: WireArtifactKind) {
+        buf.extend(WIRE_MAGIC);
+        buf.push(version);
+        buf.push(suite);
+        buf.push(kind as u8);
+    }
+
+    /// Splits `bytes` into a header and a body, checking the magic value and artifact kind.
+    pub(super) fn read(
+        bytes: &[u8],
+        kind: WireArtifactKind,
+    ) -> Result<(WireHeader, &[u8]), CodecError> {
+        if bytes.len() < WIRE_HEADER_LENGTH {
+            return Err(CodecError::InvalidLength {
+                expected: WIRE_HEADER_LENGTH,
+                actual: bytes.len(),
+            });
+        }
+        let (header, body) = bytes.split_at(WIRE_HEADER_LENGTH);
+        if header[..3] != WIRE_MAGIC {
+            return Err(CodecError::InvalidMagic);
+        }
+        if header[5] != kind as u8 {
+            return Err(CodecError::UnexpectedArtifact(header[5]));
+        }
+        Ok((
+            WireHeader {
+                version: header[3],
+                suite: header[4],
+            },
+            body,
+        ))
+    }
+
+    /// Returns an error for (version, suite) pairs the caller could not dispatch on.
+    pub(super) fn unsupported(&self) -> CodecError {
+        if self.version != WIRE_FORMAT_VERSION {
+            CodecError::UnsupportedVersion(self.version)
+        } else {
+            CodecError::UnsupportedSuite(self.suite)
+        }
+    }
+}
+
+/// Reads fixed-size fields from a body whose length has already been checked.
+pub(super) struct WireReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> WireReader<'a> {
+    pub(super) fn new(bytes: &'a [u8], expected: usize) -> Result<Self, CodecError> {
+        if bytes.len() != expected {
+            return Err(CodecError::InvalidLength {
+                expected: WIRE_HEADER_LENGTH + expected,
+                actual: WIRE_HEADER_LENGTH + bytes.len(),
+            });
+        }
+        Ok(WireReader { bytes })
+    }
+
+    fn read_array(&mut self) -> [u8; 32] {
+        let (array, rest) = self.bytes.split_at(32);
+        self.bytes = rest;
+        array.try_into().unwrap()
+    }
+
+    fn read_role(&mut self) -> Result<AMFRole, CodecError> {
+        let (role, rest) = self.bytes.split_at(1);
+        self.bytes = rest;
+        role_from_byte(role[0])
+    }
+
+    pub(super) fn read_point(&mut self) -> Result<RistrettoPoint, CodecError> {
+        self.read_point_with(&mut decompress)
+    }
+
+    /// reads a point with `decompress`, e.g. through a cache
+    pub(super) fn read_point_with(
+        &mut self,
+        decompress: &mut impl FnMut(&CompressedRistretto) -> Option<RistrettoPoint>,
+    ) -> Result<RistrettoPoint, CodecError> {
+        decompress(&CompressedRistretto(self.read_array())).ok_or(CodecError::InvalidPoint)
+    }
+
+    pub(super) fn read_scalar(&mut self) -> Result<Scalar, CodecError> {
+        Option::from(Scalar::from_canonical_bytes(self.read_array()))
+            .ok_or(CodecError::InvalidScalar)
+    }
+}
+
+/// decompresses a point of a wire format artifact
+pub(super) fn decompress(compressed: &CompressedRistretto) -> Option<RistrettoPoint> {
+    profiling::record(|counts| counts.decompressions += 1);
+    compressed.decompress()
+}
+
+pub(crate) fn role_from_byte(role: u8) -> Result<AMFRole, CodecError> {
+    match role {
+        0 => Ok(AMFRole::Sender),
+        1 => Ok(AMFRole::Recipient),
+        2 => Ok(AMFRole::Judge),
+        role => Err(CodecError::InvalidRole(role)),
+    }
+}
+
+pub(super) fn role_to_byte(role: AMFRole) -> u8 {
+    match role {
+        AMFRole::Sender => 0,
+        AMFRole::Recipient => 1,
+        AMFRole::Judge => 2,
+    }
+}
+
+impl AMFPublicKey {
+    /// The length of `to_bytes()` in bytes.
+    pub const WIRE_LENGTH: usize = WIRE_HEADER_LENGTH + PUBLIC_KEY_BODY_LENGTH;
+
+    /// The length of `to_bytes()` in bytes, for budgeting payloads and storage.
+    pub fn serialized_size(&self) -> usize {
+        Self::WIRE_LENGTH
+    }
+
+    /// Encodes the public key in the versioned wire format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(Self::WIRE_LENGTH);
+        WireHeader::write(&mut buf, WireArtifactKind::PublicKey);
+        buf.push(role_to_byte(self.role));
+        profiling::record(|counts| counts.compressions += 1);
+        buf.extend(self.public_key.compress().as_bytes());
+        buf
+    }
+
+    /// Decodes a public key from the versioned wire format.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CodecError> {
+        let mut public_key = AMFPublicKey {
+            role: AMFRole::Sender,
+            public_key: RistrettoPoint::default(),
+        };
+        public_key.decode_in_place(bytes)?;
+        Ok(public_key)
+    }
+
+    /// Decodes a public key from the versioned wire format into `self`. On error, `self` may be
+    /// left partially overwritten.
+    pub fn decode_in_place(&mut self, bytes: &[u8]) -> Result<(), CodecError> {
+        let (header, body) = WireHeader::read(bytes, WireArtifactKind::PublicKey)?;
+        match (header.version, header.suite) {
+            (WIRE_FORMAT_V1 | WIRE_FORMAT_V2, SUITE_RISTRETTO255_SHA512) => {
+                let mut reader = WireReader::new(body, PUBLIC_KEY_BODY_LENGTH)?;
+                self.role = reader.read_role()?;
+                self.public_key = reader.read_point()?;
+                self.validate()
+            }
+            _ => Err(header.unsupported()),
+        }
+    }
+}
+
+/// The encoding of a secret key: with the `zeroize` feature, overwritten when it is dropped.
+#[cfg(feature = "zeroize")]
+pub type SecretBytes = Zeroizing<Vec<u8>>;
+/// The encoding of a secret key: with the `zeroize` feature, overwritten when it is dropped.
+#[cfg(not(feature = "zeroize"))]
+pub type SecretBytes = Vec<u8>;
+
+impl AMFSecretKey {
+    /// The length of `to_bytes()` in bytes.
+    pub const WIRE_LENGTH: usize = WIRE_HEADER_LENGTH + SECRET_KEY_BODY_LENGTH;
+
+    /// The length of `to_bytes()` in bytes, for budgeting payloads and storage.
+    pub fn serialized_size(&self) -> usize {
+        Self::WIRE_LENGTH
+    }
+
+    /// Encodes the secret key in the versioned wire format; with the `zeroize` feature, the
+    /// encoding is overwritten when it is dropped.
+    #[cfg_attr(not(feature = "zeroize"), allow(clippy::useless_conversion))]
+    pub fn to_bytes(&self) -> SecretBytes {
+        let mut buf = Vec::with_capacity(Self::WIRE_LENGTH);
+        WireHeader::write(&mut buf, WireArtifactKind::SecretKey);
+        buf.push(role_to_byte(self.role));
+        buf.extend(self.secret_key.as_bytes());
+        buf.into()
+    }
+
+    /// Decodes a secret key from the versioned wire format.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CodecError> {
+        let mut secret_key = AMFSecretKey {
+            role: AMFRole::Sender,
+            secret_key: Scalar::ZERO,
+        };
+        secret_key.decode_in_place(bytes)?;
+        Ok(secret_key)
+    }
+
+    /// Decodes a secret key from the versioned wire format into `self`. On error, `self` may be
+    /// left partially overwritten.
+    pub fn decode_in_place(&mut self, bytes: &[u8]) -> Result<(), CodecError> {
+        let (header, body) = WireHeader::read(bytes, WireArtifactKind::SecretKey)?;
+        match (header.version, header.suite) {
+            (WIRE_FORMAT_V1 | WIRE_FORMAT_V2, SUITE_RISTRETTO255_SHA512) => {
+                let mut reader = WireReader::new(body, SECRET_KEY_BODY_LENGTH)?;
+                self.role = reader.read_role()?;
+                self.secret_key = reader.read_scalar()?;
+                self.validate()
+            }
+            _ => Err(header.unsupported()),
+        }
+    }
+}
+
+impl AMFSignature {
+    /// The length of `to_bytes()` in bytes.
+    pub const WIRE_LENGTH: usize = WIRE_HEADER_LENGTH + SIGNATURE_BODY_LENGTH;
+
+    /// The length of `to_bytes()` in bytes, for budgeting payloads and storage.
+    pub fn serialized_size(&self) -> usize {
+        Self::WIRE_LENGTH
+    }
+
+    /// Encodes the signature in the versioned wire format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.compress().to_bytes()
+    }
+
+    /// Decodes a signature from the current version of the wire format; signatures of older
+    /// versions decode with `VersionedAMFSignature`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CodecError> {
+        CompressedAMFSignature::from_bytes(bytes)?.decompress()
+    }
+
+    /// Decodes a signature from the versioned wire format into `self`. On error, `self` may be
+    /// left partially overwritten.
+    pub fn decode_in_place(&mut self, bytes: &[u8]) -> Result<(), CodecError> {
+        CompressedAMFSignature::from_bytes(bytes)?.decompress_into(self)
+    }
+}
+
+//
+// Validation of decoded values.
+//
+
+pub(super) fn check_not_identity(point: &RistrettoPoint) -> Result<(), CodecError> {
+    if point.is_identity() {
+        Err(CodecError::IdentityPoint)
+    } else {
         Ok(())
     }
 }
 
+impl AMFPublicKey {
+    /// Checks that the public key is not the identity, which every secret key of zero maps to.
+    /// All decoders call this.
+    pub fn validate(&self) -> Result<(), CodecError> {
+        check_not_identity(&self.public_key)
+    }
+}
+
+impl AMFSecretKey {
+    /// Checks that the secret key is not zero. All decoders call this.
+    pub fn validate(&self) -> Result<(), CodecError> {
+        if bool::from(self.secret_key.ct_eq(&Scalar::ZERO)) {
+            Err(CodecError::ZeroScalar)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl AMFSignature {
+    /// Checks that none of J, R, E_J, and E_R is the identity. An identity J or R is trivially
+    /// "known" to anyone, and an identity E_J or E_R makes J or R unbound to the judge's or
+    /// recipient's key, so such signatures break the security argument. Ristretto has no other
+    /// low-order points. All decoders call this.
+    pub fn validate(&self) -> Result<(), CodecError> {
+        [self.J, self.R, self.E_J, self.E_R]
+            .iter()
+            .try_for_each(check_not_identity)
+    }
+
+    /// `validate` as a `Choice`, for the verifiers, which combine all their checks in constant
+    /// time
+    pub(crate) fn components_valid(&self) -> Choice {
+        let identity = RistrettoPoint::identity();
+        !(self.J.ct_eq(&identity)
+            | self.R.ct_eq(&identity)
+            | self.E_J.ct_eq(&identity)
+            | self.E_R.ct_eq(&identity))
+    }
+}
+
+/// Lets binary serde formats decode the wire format.
+#[cfg(feature = "serde")]
+trait FromWireBytes: Sized {
+    const EXPECTING: &'static str;
+    fn from_wire_bytes(bytes: &[u8]) -> Result<Self, CodecError>;
+    fn decode_wire_bytes_in_place(&mut self, bytes: &[u8]) -> Result<(), CodecError>;
+}
+#[cfg(feature = "serde")]
+impl FromWireBytes for AMFPublicKey {
+    const EXPECTING: &'static str = "an encoded AMF public key";
+    fn from_wire_bytes(bytes: &[u8]) -> Result<Self, CodecError> {
+        AMFPublicKey::from_bytes(bytes)
+    }
+    fn decode_wire_bytes_in_place(&mut self, bytes: &[u8]) -> Result<(), CodecError> {
+        self.decode_in_place(bytes)
+    }
+}
+#[cfg(feature = "serde")]
+impl FromWireBytes for AMFSecretKey {
+    const EXPECTING: &'static str = "an encoded AMF secret key";
+    fn from_wire_bytes(bytes: &[u8]) -> Result<Self, CodecError> {
+        AMFSecretKey::from_bytes(bytes)
+    }
+    fn decode_wire_bytes_in_place(&mut self, bytes: &[u8]) -> Result<(), CodecError> {
+        self.decode_in_place(bytes)
+    }
+}
+#[cfg(feature = "serde")]
+impl FromWireBytes for AMFSignature {
+    const EXPECTING: &'static str = "an encoded AMF signature";
+    fn from_wire_bytes(bytes: &[u8]) -> Result<Self, CodecError> {
+        AMFSignature::from_bytes(bytes)
+    }
+    fn decode_wire_bytes_in_place(&mut self, bytes: &[u8]) -> Result<(), CodecError> {
+        self.decode_in_place(bytes)
+    }
+}
+
+#[cfg(feature = "serde")]
+struct WireVisitor<T>(PhantomData<T>);
+
+#[cfg(feature = "serde")]
+impl<'de, T: FromWireBytes> serde::de::Visitor<'de> for WireVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(T::EXPECTING)
+    }
+
+    fn visit_bytes<E>(self, bytes: &[u8]) -> Result<T, E>
+    where
+        E: serde::de::Error,
+    {
+        T::from_wire_bytes(bytes).map_err(E::custom)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<T, A::Error>
+    where
+        A: serde::de::SeqAccess<'de>,
+    {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+        self.visit_bytes(&bytes)
+    }
+}
+
+/// Like `WireVisitor`, but decodes directly into an existing value.
+#[cfg(feature = "serde")]
+struct WireInPlaceVisitor<'a, T>(&'a mut T);
+
+#[cfg(feature = "serde")]
+impl<'de, T: FromWireBytes> serde::de::Visitor<'de> for WireInPlaceVisitor<'_, T> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(T::EXPECTING)
+    }
+
+    fn visit_bytes<E>(self, bytes: &[u8]) -> Result<(), E>
+    where
+        E: serde::de::Error,
+    {
+        self.0.decode_wire_bytes_in_place(bytes).map_err(E::custom)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<(), A::Error>
+    where
+        A: serde::de::SeqAccess<'de>,
+    {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+        self.visit_bytes(&bytes)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::amf::{frank, keygen};
+    #[cfg(feature = "serde")]
+    use crate::pok::serialized_size::SerializedSize;
 
     use super::*;
 
     #[test]
+    #[cfg(feature = "serde")]
     fn test_public_key_codec() {
         let (public_key, _secret_key) = keygen(AMFRole::Sender);
 
@@ -331,6 +1032,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "serde")]
     fn test_secret_key_codec() {
         let (_public_key, secret_key) = keygen(AMFRole::Sender);
 
@@ -346,6 +1048,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "serde")]
     fn test_signature_codec() {
         // 0. Initialize a Sender
         let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
@@ -377,4 +1080,286 @@ mod tests {
             encoded_amf_signature.len()
         );
     }
+
+    #[test]
+    fn test_wire_format() {
+        // 0. Initialize a Sender, Recipient, and Judge
+        let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+        let (recipient_public_key, _recipient_secret_key) = keygen(AMFRole::Recipient);
+        let (judge_public_key, _judge_secret_key) = keygen(AMFRole::Judge);
+
+        // 1. Frank a message
+        let amf_signature = frank(
+            sender_secret_key.clone(),
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            b"hello world!",
+        );
+
+        // 2. Round-trip everything through the wire format
+        let encoded_public_key = sender_public_key.to_bytes();
+        assert_eq!(encoded_public_key.len(), AMFPublicKey::WIRE_LENGTH);
+        assert_eq!(
+            AMFPublicKey::from_bytes(&encoded_public_key).unwrap(),
+            sender_public_key
+        );
+        let encoded_secret_key = sender_secret_key.to_bytes();
+        assert_eq!(encoded_secret_key.len(), AMFSecretKey::WIRE_LENGTH);
+        assert_eq!(
+            AMFSecretKey::from_bytes(&encoded_secret_key).unwrap(),
+            sender_secret_key
+        );
+        let encoded_amf_signature = amf_signature.to_bytes();
+        assert_eq!(encoded_amf_signature.len(), AMFSignature::WIRE_LENGTH);
+        assert_eq!(encoded_amf_signature.len(), amf_signature.serialized_size());
+        #[cfg(feature = "serde")]
+        assert_eq!(
+            bincode::serialize(&amf_signature.pi).unwrap().len(),
+            amf_signature.pi.serialized_size()
+        );
+        assert_eq!(
+            AMFSignature::from_bytes(&encoded_amf_signature).unwrap(),
+            amf_signature
+        );
+
+        // 3. Headers are checked before anything else
+        let mut bad_magic = encoded_amf_signature.clone();
+        bad_magic[0] ^= 1;
+        assert_eq!(
+            AMFSignature::from_bytes(&bad_magic),
+            Err(CodecError::InvalidMagic)
+        );
+        let mut bad_version = encoded_amf_signature.clone();
+        bad_version[3] = WIRE_FORMAT_VERSION + 1;
+        assert_eq!(
+            AMFSignature::from_bytes(&bad_version),
+            Err(CodecError::UnsupportedVersion(WIRE_FORMAT_VERSION + 1))
+        );
+        bad_version[3] = WIRE_FORMAT_V1;
+        assert_eq!(
+            AMFSignature::from_bytes(&bad_version),
+            Err(CodecError::UnsupportedVersion(WIRE_FORMAT_V1))
+        );
+        let mut version_1_key = encoded_public_key.clone();
+        version_1_key[3] = WIRE_FORMAT_V1;
+        assert_eq!(
+            AMFPublicKey::from_bytes(&version_1_key).unwrap(),
+            sender_public_key
+        );
+        let mut bad_suite = encoded_amf_signature.clone();
+        bad_suite[4] = 7;
+        assert_eq!(
+            AMFSignature::from_bytes(&bad_suite),
+            Err(CodecError::UnsupportedSuite(7))
+        );
+        assert_eq!(
+            AMFSignature::from_bytes(&encoded_public_key),
+            Err(CodecError::UnexpectedArtifact(1))
+        );
+
+        // 4. So are lengths, roles, points, and scalars
+        assert!(matches!(
+            AMFSignature::from_bytes(&encoded_amf_signature[..AMFSignature::WIRE_LENGTH - 1]),
+            Err(CodecError::InvalidLength { .. })
+        ));
+        let mut bad_role = encoded_public_key.clone();
+        bad_role[WIRE_HEADER_LENGTH] = 3;
+        assert_eq!(
+            AMFPublicKey::from_bytes(&bad_role),
+            Err(CodecError::InvalidRole(3))
+        );
+        let mut bad_scalar = encoded_secret_key;
+        bad_scalar[AMFSecretKey::WIRE_LENGTH - 1] = 0xff;
+        assert_eq!(
+            AMFSecretKey::from_bytes(&bad_scalar),
+            Err(CodecError::InvalidScalar)
+        );
+        let mut bad_point = encoded_public_key;
+        bad_point[WIRE_HEADER_LENGTH + 1..].copy_from_slice(&[0xff; 32]);
+        assert_eq!(
+            AMFPublicKey::from_bytes(&bad_point),
+            Err(CodecError::InvalidPoint)
+        );
+
+        // 5. Binary serde formats use the wire format
+        #[cfg(feature = "serde")]
+        {
+            let bincode_amf_signature = bincode::serialize(&amf_signature).unwrap();
+            assert!(bincode_amf_signature.ends_with(&encoded_amf_signature));
+            let mut bad_bincode_version = bincode_amf_signature.clone();
+            let header_start = bincode_amf_signature.len() - AMFSignature::WIRE_LENGTH;
+            bad_bincode_version[header_start + 3] = 3;
+            assert!(bincode::deserialize::<AMFSignature>(&bincode_amf_signature).is_ok());
+            assert!(bincode::deserialize::<AMFSignature>(&bad_bincode_version).is_err());
+        }
+    }
+
+    #[test]
+    fn test_reject_degenerate() {
+        // 0. Initialize a Sender, Recipient, and Judge
+        let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+        let (recipient_public_key, _recipient_secret_key) = keygen(AMFRole::Recipient);
+        let (judge_public_key, _judge_secret_key) = keygen(AMFRole::Judge);
+
+        // 1. Frank a message
+        let amf_signature = frank(
+            sender_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            b"hello world!",
+        );
+
+        // 2. Identity public keys and zero secret keys are rejected
+        let identity_public_key = AMFPublicKey {
+            role: AMFRole::Judge,
+            public_key: RistrettoPoint::default(),
+        };
+        assert_eq!(
+            AMFPublicKey::from_bytes(&identity_public_key.to_bytes()),
+            Err(CodecError::IdentityPoint)
+        );
+        let zero_secret_key = AMFSecretKey {
+            role: AMFRole::Judge,
+            secret_key: Scalar::ZERO,
+        };
+        assert_eq!(
+            AMFSecretKey::from_bytes(&zero_secret_key.to_bytes()),
+            Err(CodecError::ZeroScalar)
+        );
+        #[cfg(feature = "serde")]
+        assert!(serde_json::from_str::<AMFSecretKey>(
+            &serde_json::to_string(&zero_secret_key).unwrap()
+        )
+        .is_err());
+
+        // 3. So are signatures with an identity J, R, E_J, or E_R, in every format
+        for index in 0..4 {
+            let mut encoded_amf_signature = amf_signature.to_bytes();
+            let start = WIRE_HEADER_LENGTH + 32 * index;
+            encoded_amf_signature[start..start + 32].copy_from_slice(&[0; 32]);
+            assert_eq!(
+                AMFSignature::from_bytes(&encoded_amf_signature),
+                Err(CodecError::IdentityPoint)
+            );
+        }
+        let mut identity_E_R = amf_signature;
+        identity_E_R.E_R = RistrettoPoint::default();
+        assert_eq!(identity_E_R.validate(), Err(CodecError::IdentityPoint));
+        #[cfg(feature = "serde")]
+        assert!(
+            bincode::deserialize::<AMFSignature>(&bincode::serialize(&identity_E_R).unwrap())
+                .is_err()
+        );
+        #[cfg(feature = "unstable-der")]
+        assert!(AMFSignature::from_der(&identity_E_R.to_der().unwrap()).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_deserialize_in_place() {
+        use bincode::Options;
+
+        // 0. Initialize a Sender, Recipient, and Judge
+        let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+        let (recipient_public_key, _recipient_secret_key) = keygen(AMFRole::Recipient);
+        let (judge_public_key, _judge_secret_key) = keygen(AMFRole::Judge);
+
+        // 1. Frank two messages
+        let frank_message = |message: &[u8]| {
+            frank(
+                sender_secret_key.clone(),
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+            )
+        };
+        let amf_signatures = [frank_message(b"hello"), frank_message(b"world")];
+
+        // 2. Deserialize both into the same place, as a bulk ingestion loop would
+        let options = bincode::DefaultOptions::new()
+            .with_fixint_encoding()
+            .allow_trailing_bytes();
+        let mut place = amf_signatures[1];
+        for amf_signature in amf_signatures {
+            let encoded_amf_signature = bincode::serialize(&amf_signature).unwrap();
+            let mut deserializer =
+                bincode::Deserializer::from_slice(&encoded_amf_signature, options);
+            AMFSignature::deserialize_in_place(&mut deserializer, &mut place).unwrap();
+            assert_eq!(place, amf_signature);
+        }
+
+        // 3. Keys work the same way
+        let mut place = recipient_public_key;
+        let encoded_public_key = bincode::serialize(&sender_public_key).unwrap();
+        let mut deserializer = bincode::Deserializer::from_slice(&encoded_public_key, options);
+        AMFPublicKey::deserialize_in_place(&mut deserializer, &mut place).unwrap();
+        assert_eq!(place, sender_public_key);
+
+        let (_, mut place) = keygen(AMFRole::Judge);
+        let encoded_secret_key = bincode::serialize(&sender_secret_key).unwrap();
+        let mut deserializer = bincode::Deserializer::from_slice(&encoded_secret_key, options);
+        AMFSecretKey::deserialize_in_place(&mut deserializer, &mut place).unwrap();
+        assert_eq!(place, sender_secret_key);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_human_readable_codec() {
+        // 0. Initialize a Sender, Recipient, and Judge
+        let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+        let (recipient_public_key, _recipient_secret_key) = keygen(AMFRole::Recipient);
+        let (judge_public_key, _judge_secret_key) = keygen(AMFRole::Judge);
+
+        // 1. Frank a message
+        let amf_signature = frank(
+            sender_secret_key.clone(),
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            b"hello world!",
+        );
+
+        // 2. Keys should be encoded with named keys and hex strings
+        let encoded_public_key = serde_json::to_string(&sender_public_key).unwrap();
+        let expected_public_key = format!(
+            "{{\"role\":\"Sender\",\"public_key\":\"{}\"}}",
+            hex::encode(sender_public_key.public_key.compress().as_bytes())
+        );
+        assert_eq!(encoded_public_key, expected_public_key);
+        let decoded_public_key: AMFPublicKey = serde_json::from_str(&encoded_public_key).unwrap();
+        assert_eq!(sender_public_key, decoded_public_key);
+
+        let encoded_secret_key = serde_json::to_string(&sender_secret_key).unwrap();
+        let decoded_secret_key: AMFSecretKey = serde_json::from_str(&encoded_secret_key).unwrap();
+        assert_eq!(sender_secret_key, decoded_secret_key);
+
+        // 3. So should signatures
+        let encoded_amf_signature = serde_json::to_string(&amf_signature).unwrap();
+        assert!(encoded_amf_signature.contains(&format!(
+            "\"J\":\"{}\"",
+            hex::encode(amf_signature.J.compress().as_bytes())
+        )));
+        let decoded_amf_signature: AMFSignature =
+            serde_json::from_str(&encoded_amf_signature).unwrap();
+        assert_eq!(amf_signature, decoded_amf_signature);
+
+        // 4. Malformed hex strings are rejected instead of panicking
+        let malformed_public_key = "{\"role\":\"Sender\",\"public_key\":\"not hex\"}";
+        assert!(serde_json::from_str::<AMFPublicKey>(malformed_public_key).is_err());
+
+        // 5. So are hex strings of invalid points and non-canonical scalars
+        let invalid_point = format!(
+            "{{\"role\":\"Sender\",\"public_key\":\"{}\"}}",
+            "ff".repeat(32)
+        );
+        assert!(serde_json::from_str::<AMFPublicKey>(&invalid_point).is_err());
+        let non_canonical_scalar = encoded_secret_key.replace(
+            &hex::encode(sender_secret_key.secret_key.as_bytes()),
+            &"ff".repeat(32),
+        );
+        assert!(serde_json::from_str::<AMFSecretKey>(&non_canonical_scalar).is_err());
+    }
 }
@@ -0,0 +1,259 @@
+//! AMF signatures with deferred point decompression.
+//!
+//! Decompressing the nine points in an `AMFSignature` dominates decoding cost. A
+//! `CompressedAMFSignature` only checks the wire format header and length, so signatures that are
+//! filtered or deduplicated before verification never pay for decompression.
+#![allow(non_snake_case)]
+
+use alloc::vec::Vec;
+
+use curve25519_dalek::ristretto::CompressedRistretto;
+
+use crate::pok::profiling;
+
+use super::{
+    codec::{
+        CodecError, WireArtifactKind, WireHeader, SIGNATURE_BODY_LENGTH, SUITE_RISTRETTO255_SHA512,
+        WIRE_FORMAT_V2,
+    },
+    judge, verify, AMFPublicKey, AMFSecretKey, AMFSignature, AMFSignatureRef,
+};
+
+/// An `AMFSignature` holding the raw wire format body (compressed points and scalar bytes).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct CompressedAMFSignature {
+    body: [u8; SIGNATURE_BODY_LENGTH],
+}
+
+impl CompressedAMFSignature {
+    /// Parses the wire format header and body without decompressing any points.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CodecError> {
+        let (header, body) = WireHeader::read(bytes, WireArtifactKind::Signature)?;
+        match (header.version, header.suite) {
+            (WIRE_FORMAT_V2, SUITE_RISTRETTO255_SHA512) => {
+                let body = body.try_into().map_err(|_| CodecError::InvalidLength {
+                    expected: AMFSignature::WIRE_LENGTH,
+                    actual: bytes.len(),
+                })?;
+                Ok(CompressedAMFSignature { body })
+            }
+            _ => Err(header.unsupported()),
+        }
+    }
+
+    /// Encodes the signature in the versioned wire format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(AMFSignature::WIRE_LENGTH);
+        WireHeader::write(&mut buf, WireArtifactKind::Signature);
+        buf.extend(self.body);
+        buf
+    }
+
+    fn compressed_point(&self, index: usize) -> CompressedRistretto {
+        CompressedRistretto(self.body[32 * index..32 * (index + 1)].try_into().unwrap())
+    }
+
+    /// The compressed J component.
+    pub fn J(&self) -> CompressedRistretto {
+        self.compressed_point(0)
+    }
+
+    /// The compressed R component.
+    pub fn R(&self) -> CompressedRistretto {
+        self.compressed_point(1)
+    }
+
+    /// The compressed E_J component.
+    pub fn E_J(&self) -> CompressedRistretto {
+        self.compressed_point(2)
+    }
+
+    /// The compressed E_R component.
+    pub fn E_R(&self) -> CompressedRistretto {
+        self.compressed_point(3)
+    }
+
+    /// Decompresses all points and checks all scalars.
+    pub fn decompress(&self) -> Result<AMFSignature, CodecError> {
+        self.as_signature_ref().decompress()
+    }
+
+    /// Decompresses all points and checks all scalars, writing the result into `amf_signature`.
+    /// On error, `amf_signature` may be left partially overwritten.
+    pub fn decompress_into(&self, amf_signature: &mut AMFSignature) -> Result<(), CodecError> {
+        self.as_signature_ref().decompress_into(amf_signature)
+    }
+
+    /// Borrows the signature as an `AMFSignatureRef`.
+    pub fn as_signature_ref(&self) -> AMFSignatureRef<'_> {
+        AMFSignatureRef::from_body(&self.body)
+    }
+
+    pub(super) fn from_body(body: [u8; SIGNATURE_BODY_LENGTH]) -> Self {
+        CompressedAMFSignature { body }
+    }
+}
+
+impl AMFSignature {
+    /// Compresses all points in the signature.
+    pub fn compress(&self) -> CompressedAMFSignature {
+        let mut body = [0u8; SIGNATURE_BODY_LENGTH];
+        let (or0_commitment, or1_commitment) = self.pi.prover_commitment;
+        let points = [
+            self.J,
+            self.R,
+            self.E_J,
+            self.E_R,
+            or0_commitment.0,
+            or0_commitment.1,
+            or1_commitment.0.v_t,
+            or1_commitment.0.w_t,
+            or1_commitment.1,
+        ];
+        let (or0_response, or1_response) = self.pi.prover_response;
+        let scalars = [
+            or0_response.c_0,
+            or0_response.z_0,
+            or0_response.z_1,
+            or1_response.c_0,
+            or1_response.z_0,
+            or1_response.z_1,
+        ];
+        profiling::record(|counts| counts.compressions += points.len() as u64);
+        let chunks = points
+            .iter()
+            .map(|point| point.compress().to_bytes())
+            .chain(scalars.iter().map(|scalar| scalar.to_bytes()));
+        for (chunk, bytes) in body.chunks_exact_mut(32).zip(chunks) {
+            chunk.copy_from_slice(&bytes);
+        }
+        CompressedAMFSignature { body }
+    }
+}
+
+impl From<AMFSignature> for CompressedAMFSignature {
+    fn from(amf_signature: AMFSignature) -> Self {
+        amf_signature.compress()
+    }
+}
+
+/// Like `verify`, but decompresses the signature first. Signatures that fail to decompress are
+/// rejected.
+pub fn verify_compressed(
+    recipient_secret_key: AMFSecretKey,
+    sender_public_key: AMFPublicKey,
+    recipient_public_key: AMFPublicKey,
+    judge_public_key: AMFPublicKey,
+    message: &[u8],
+    compressed_amf_signature: &CompressedAMFSignature,
+) -> bool {
+    match compressed_amf_signature.decompress() {
+        Ok(amf_signature) => verify(
+            recipient_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        ),
+        Err(_) => false,
+    }
+}
+
+/// Like `judge`, but decompresses the signature first. Signatures that fail to decompress are
+/// rejected.
+pub fn judge_compressed(
+    judge_secret_key: AMFSecretKey,
+    sender_public_key: AMFPublicKey,
+    recipient_public_key: AMFPublicKey,
+    judge_public_key: AMFPublicKey,
+    message: &[u8],
+    compressed_amf_signature: &CompressedAMFSignature,
+) -> bool {
+    match compressed_amf_signature.decompress() {
+        Ok(amf_signature) => judge(
+            judge_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        ),
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::amf::{frank, keygen, AMFRole};
+
+    use super::*;
+
+    #[test]
+    fn test_compressed_signature() {
+        // 0. Initialize a Sender, Recipient, and Judge
+        let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+        let (recipient_public_key, recipient_secret_key) = keygen(AMFRole::Recipient);
+        let (judge_public_key, judge_secret_key) = keygen(AMFRole::Judge);
+
+        // 1. Frank a message and receive it as bytes
+        let message = b"hello world!";
+        let amf_signature = frank(
+            sender_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+        );
+        let encoded_amf_signature = amf_signature.to_bytes();
+
+        // 2. Parse it without decompressing, and deduplicate it
+        let compressed_amf_signature =
+            CompressedAMFSignature::from_bytes(&encoded_amf_signature).unwrap();
+        assert_eq!(compressed_amf_signature, amf_signature.compress());
+        assert_eq!(compressed_amf_signature.J(), amf_signature.J.compress());
+        assert_eq!(compressed_amf_signature.E_R(), amf_signature.E_R.compress());
+        assert_eq!(compressed_amf_signature.to_bytes(), encoded_amf_signature);
+        let seen: HashSet<_> = [compressed_amf_signature, compressed_amf_signature]
+            .into_iter()
+            .collect();
+        assert_eq!(seen.len(), 1);
+
+        // 3. Verify and judge it directly
+        assert!(verify_compressed(
+            recipient_secret_key.clone(),
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            &compressed_amf_signature,
+        ));
+        assert!(judge_compressed(
+            judge_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            &compressed_amf_signature,
+        ));
+
+        // 4. Invalid points are only noticed (and rejected) on verification
+        let mut bad_point = encoded_amf_signature;
+        bad_point[6..38].copy_from_slice(&[0xff; 32]);
+        let compressed_bad_point = CompressedAMFSignature::from_bytes(&bad_point).unwrap();
+        assert_eq!(
+            compressed_bad_point.decompress(),
+            Err(CodecError::InvalidPoint)
+        );
+        assert!(!verify_compressed(
+            recipient_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            &compressed_bad_point,
+        ));
+    }
+}
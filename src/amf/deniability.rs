@@ -0,0 +1,263 @@
+//! Statistical Checks for the Deniability of AMF
+//!
+//! AMF is deniable because anyone can forge signatures that a third party cannot tell apart from
+//! honest franks, cf. `amf::forge`. This module gives integrators machine-checkable evidence of it
+//! for their build of the crate: it franks and forges matched corpora of signatures on the same
+//! keys and message, and
+//!
+//! - tabulates how many signatures of each kind `verify` and `judge` accept, which must match
+//!   `SignatureKind::expected_outcome`: honest franks and `j_forge` pass both, `r_forge` passes
+//!   only `verify`, and `forge` passes neither;
+//! - compares the wire encodings of every kind of forgery with those of the honest franks, with
+//!   the histogram distance of `pok::testing::statistical_distance`.
+//!
+//! Like `pok::testing`, this is a sanity check, not a proof: it catches forgers that do not pass
+//! the checks they should, and forgeries that fix or leak part of the encoding, but not subtle
+//! correlations.
+
+use rand_core::CryptoRngCore;
+
+use super::{
+    forge::{forge_with_rng, j_forge_with_rng, r_forge_with_rng},
+    frank_with_rng, judge, keygen_with_rng, verify, AMFPublicKey, AMFRole, AMFSecretKey,
+    AMFSignature,
+};
+use crate::pok::testing::{distance_threshold, statistical_distance};
+
+/// how many signatures of each kind `assert_deniable` makes
+pub const SAMPLES: usize = 1000;
+
+/// The algorithm a signature of a corpus was made with.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SignatureKind {
+    /// `amf::frank`, with the sender's secret key
+    Frank,
+    /// `forge::forge`, with no secret key
+    Forge,
+    /// `forge::r_forge`, with the recipient's secret key
+    RForge,
+    /// `forge::j_forge`, with the judge's secret key
+    JForge,
+}
+
+impl SignatureKind {
+    pub const ALL: [SignatureKind; 4] = [
+        SignatureKind::Frank,
+        SignatureKind::Forge,
+        SignatureKind::RForge,
+        SignatureKind::JForge,
+    ];
+
+    /// Whether `verify` and `judge`, in this order, accept signatures of this kind, cf. Section 4
+    /// in [AMF].
+    ///
+    /// [AMF]: https://eprint.iacr.org/2019/565/20190527:092413
+    pub fn expected_outcome(self) -> (bool, bool) {
+        match self {
+            SignatureKind::Frank | SignatureKind::JForge => (true, true),
+            SignatureKind::RForge => (true, false),
+            SignatureKind::Forge => (false, false),
+        }
+    }
+}
+
+/// The key pairs of the sender, the recipient and the judge that a corpus is made for.
+#[derive(Debug, Clone)]
+pub struct DeniabilityKeys {
+    pub sender: (AMFPublicKey, AMFSecretKey),
+    pub recipient: (AMFPublicKey, AMFSecretKey),
+    pub judge: (AMFPublicKey, AMFSecretKey),
+}
+
+impl DeniabilityKeys {
+    pub fn generate_with_rng(rng: &mut dyn CryptoRngCore) -> Self {
+        DeniabilityKeys {
+            sender: keygen_with_rng(rng, AMFRole::Sender),
+            recipient: keygen_with_rng(rng, AMFRole::Recipient),
+            judge: keygen_with_rng(rng, AMFRole::Judge),
+        }
+    }
+}
+
+/// Makes `samples` signatures of `kind` on `message`.
+pub fn corpus_with_rng(
+    rng: &mut dyn CryptoRngCore,
+    keys: &DeniabilityKeys,
+    kind: SignatureKind,
+    message: &[u8],
+    samples: usize,
+) -> Vec<AMFSignature> {
+    let (sender, recipient, judge) = (&keys.sender, &keys.recipient, &keys.judge);
+    (0..samples)
+        .map(|_| match kind {
+            SignatureKind::Frank => {
+                frank_with_rng(rng, &sender.1, sender.0, recipient.0, judge.0, message)
+            }
+            SignatureKind::Forge => forge_with_rng(rng, sender.0, recipient.0, judge.0, message),
+            SignatureKind::RForge => {
+                r_forge_with_rng(rng, &recipient.1, sender.0, recipient.0, judge.0, message)
+            }
+            SignatureKind::JForge => {
+                j_forge_with_rng(rng, &judge.1, sender.0, recipient.0, judge.0, message)
+            }
+        })
+        .collect()
+}
+
+/// How many signatures of a corpus `verify` and `judge` accept.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    pub kind: SignatureKind,
+    pub samples: usize,
+    pub verified: usize,
+    pub judged: usize,
+}
+
+impl Outcome {
+    /// Whether all or none of the signatures pass each check, as `kind` expects.
+    pub fn is_expected(&self) -> bool {
+        let (verify, judge) = self.kind.expected_outcome();
+        let all_or_none = |accepted, expected| accepted == if expected { self.samples } else { 0 };
+        all_or_none(self.verified, verify) && all_or_none(self.judged, judge)
+    }
+}
+
+/// Runs `verify` and `judge` on every signature of `corpus`, a corpus of `kind` on `message`.
+pub fn outcome(
+    keys: &DeniabilityKeys,
+    kind: SignatureKind,
+    message: &[u8],
+    corpus: &[AMFSignature],
+) -> Outcome {
+    let (sender, recipient, judge_key) = (&keys.sender, &keys.recipient, &keys.judge);
+    let verified = corpus
+        .iter()
+        .filter(|amf_signature| {
+            verify(
+                &recipient.1,
+                sender.0,
+                recipient.0,
+                judge_key.0,
+                message,
+                *amf_signature,
+            )
+        })
+        .count();
+    let judged = corpus
+        .iter()
+        .filter(|amf_signature| {
+            judge(
+                &judge_key.1,
+                sender.0,
+                recipient.0,
+                judge_key.0,
+                message,
+                *amf_signature,
+            )
+        })
+        .count();
+    Outcome {
+        kind,
+        samples: corpus.len(),
+        verified,
+        judged,
+    }
+}
+
+/// The outcome matrix and the distances of a run of `check_deniability_with_rng`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeniabilityReport {
+    /// the outcome of every kind, in the order of `SignatureKind::ALL`
+    pub outcomes: Vec<Outcome>,
+    /// the distance of the encodings of every kind of forgery from those of the honest franks
+    pub distances: Vec<(SignatureKind, f64)>,
+    /// the distance below which two corpora pass as equally distributed
+    pub threshold: f64,
+}
+
+impl DeniabilityReport {
+    /// Whether every outcome is as expected and every corpus of forgeries is close to the franks.
+    pub fn is_deniable(&self) -> bool {
+        self.outcomes.iter().all(Outcome::is_expected)
+            && self
+                .distances
+                .iter()
+                .all(|(_, distance)| *distance < self.threshold)
+    }
+}
+
+/// Makes a corpus of `samples` signatures of every kind on fresh keys, and checks it, cf. the
+/// module documentation.
+pub fn check_deniability_with_rng(
+    rng: &mut dyn CryptoRngCore,
+    samples: usize,
+) -> DeniabilityReport {
+    let keys = DeniabilityKeys::generate_with_rng(rng);
+    let message = b"amaze deniability corpus";
+    let corpora: Vec<(SignatureKind, Vec<AMFSignature>)> = SignatureKind::ALL
+        .iter()
+        .map(|&kind| (kind, corpus_with_rng(rng, &keys, kind, message, samples)))
+        .collect();
+    let encodings: Vec<Vec<Vec<u8>>> = corpora
+        .iter()
+        .map(|(_, corpus)| corpus.iter().map(|sig| sig.to_bytes().to_vec()).collect())
+        .collect();
+    DeniabilityReport {
+        outcomes: corpora
+            .iter()
+            .map(|(kind, corpus)| outcome(&keys, *kind, message, corpus))
+            .collect(),
+        distances: corpora
+            .iter()
+            .zip(&encodings)
+            .skip(1)
+            .map(|((kind, _), encoding)| (*kind, statistical_distance(&encodings[0], encoding)))
+            .collect(),
+        threshold: distance_threshold(samples),
+    }
+}
+
+/// Panics unless `check_deniability_with_rng` with `SAMPLES` signatures of every kind passes.
+pub fn assert_deniable() {
+    let report = check_deniability_with_rng(&mut rand::thread_rng(), SAMPLES);
+    assert!(report.is_deniable(), "not deniable: {report:?}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deniability() {
+        // 0. A small corpus of every kind has the expected outcomes and is close to the franks
+        let report = check_deniability_with_rng(&mut rand::thread_rng(), 200);
+        assert!(report.is_deniable(), "{report:?}");
+        assert_eq!(
+            report
+                .outcomes
+                .iter()
+                .map(|outcome| outcome.kind)
+                .collect::<Vec<_>>(),
+            SignatureKind::ALL
+        );
+
+        // 1. Signatures of the wrong kind are caught by the outcome matrix
+        let mut rng = rand::thread_rng();
+        let keys = DeniabilityKeys::generate_with_rng(&mut rng);
+        let forgeries = corpus_with_rng(&mut rng, &keys, SignatureKind::RForge, b"hello", 4);
+        let mislabeled = outcome(&keys, SignatureKind::Frank, b"hello", &forgeries);
+        assert_eq!((mislabeled.verified, mislabeled.judged), (4, 0));
+        assert!(!mislabeled.is_expected());
+
+        // 2. A corpus that fixes part of the encoding is caught by the distance
+        let franks = corpus_with_rng(&mut rng, &keys, SignatureKind::Frank, b"hello", 200);
+        let encode = |corpus: &[AMFSignature]| -> Vec<Vec<u8>> {
+            corpus.iter().map(|sig| sig.to_bytes().to_vec()).collect()
+        };
+        let mut fixed = franks.clone();
+        for amf_signature in &mut fixed {
+            amf_signature.J = franks[0].J;
+        }
+        assert!(statistical_distance(&encode(&franks), &encode(&fixed)) > distance_threshold(200));
+    }
+}
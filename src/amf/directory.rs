@@ -0,0 +1,305 @@
+//! A Key Directory Client with Trust on First Use
+//!
+//! Applications need the public keys of senders and judges to verify franks. A `KeyDirectory`
+//! fetches them from a directory service over a `KeyTransport`, which the application provides
+//! (HTTP, a key transparency log, a local database, ...), and pins the first key it gets for an
+//! identity and role. Later lookups return the pinned key without fetching.
+//!
+//! `refresh` fetches a key again; if the directory now serves another key, the pin is kept, the
+//! lookup fails with `DirectoryError::KeyChanged`, and a `KeyEvent::Changed` is recorded, so that
+//! the application can warn the user, e.g. "the judge's key changed". Only `accept_change` or
+//! `pin` replaces a pinned key. `events` drains the recorded events, and `pins` exports the pins
+//! for storage; `pin` restores them.
+
+use alloc::{
+    boxed::Box,
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+
+use super::{codec::CodecError, AMFPublicKey, AMFRole};
+
+/// Fetches the encoded public key of an identity in a role from a directory service.
+pub trait KeyTransport {
+    type Error;
+
+    /// The wire encoding of the key, cf. `AMFPublicKey::to_bytes`.
+    fn fetch(&self, identity: &str, role: AMFRole) -> Result<Vec<u8>, Self::Error>;
+}
+
+/// Why a `KeyDirectory` lookup failed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DirectoryError<E> {
+    /// The transport could not fetch the key.
+    Transport(E),
+    /// The fetched key does not decode.
+    Codec(CodecError),
+    /// The fetched key is of another role than the one looked up.
+    RoleMismatch { expected: AMFRole, actual: AMFRole },
+    /// The directory serves the key `fetched` instead of the pinned one, which is kept.
+    KeyChanged { fetched: Box<AMFPublicKey> },
+}
+
+impl<E: fmt::Display> fmt::Display for DirectoryError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DirectoryError::Transport(error) => write!(f, "could not fetch the key: {}", error),
+            DirectoryError::Codec(error) => write!(f, "invalid key: {}", error),
+            DirectoryError::RoleMismatch { expected, actual } => {
+                write!(f, "expected a {:?} key, got a {:?} key", expected, actual)
+            }
+            DirectoryError::KeyChanged { .. } => write!(f, "the pinned key changed"),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> core::error::Error for DirectoryError<E> {}
+
+/// What happened to the pins of a `KeyDirectory`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeyEvent {
+    /// A key was pinned for an identity that had none.
+    Pinned {
+        identity: String,
+        public_key: AMFPublicKey,
+    },
+    /// The directory served another key than the pinned one, which was kept.
+    Changed {
+        identity: String,
+        pinned: AMFPublicKey,
+        fetched: AMFPublicKey,
+    },
+    /// A pinned key was replaced by `accept_change` or `pin`.
+    Repinned {
+        identity: String,
+        previous: AMFPublicKey,
+        public_key: AMFPublicKey,
+    },
+}
+
+/// Fetches, caches and pins public keys, cf. the module documentation.
+#[derive(Debug)]
+pub struct KeyDirectory<T> {
+    transport: T,
+    pins: BTreeMap<(String, AMFRole), AMFPublicKey>,
+    events: Vec<KeyEvent>,
+}
+
+impl<T: KeyTransport> KeyDirectory<T> {
+    /// A directory with no pins.
+    pub fn new(transport: T) -> Self {
+        KeyDirectory {
+            transport,
+            pins: BTreeMap::new(),
+            events: Vec::new(),
+        }
+    }
+
+    /// The key of `identity` in `role`: the pinned one, or else the fetched one, which is pinned.
+    pub fn get(
+        &mut self,
+        identity: &str,
+        role: AMFRole,
+    ) -> Result<AMFPublicKey, DirectoryError<T::Error>> {
+        match self.pinned(identity, role) {
+            Some(public_key) => Ok(public_key),
+            None => {
+                let public_key = self.fetch(identity, role)?;
+                self.pin(identity, public_key);
+                Ok(public_key)
+            }
+        }
+    }
+
+    /// Fetches the key of `identity` in `role` again, and pins it if none is pinned; fails with
+    /// `DirectoryError::KeyChanged` if it is not the pinned one.
+    pub fn refresh(
+        &mut self,
+        identity: &str,
+        role: AMFRole,
+    ) -> Result<AMFPublicKey, DirectoryError<T::Error>> {
+        let fetched = self.fetch(identity, role)?;
+        match self.pinned(identity, role) {
+            Some(pinned) if pinned != fetched => {
+                self.events.push(KeyEvent::Changed {
+                    identity: identity.to_string(),
+                    pinned,
+                    fetched,
+                });
+                Err(DirectoryError::KeyChanged {
+                    fetched: Box::new(fetched),
+                })
+            }
+            Some(pinned) => Ok(pinned),
+            None => {
+                self.pin(identity, fetched);
+                Ok(fetched)
+            }
+        }
+    }
+
+    /// Replaces the pinned key of `identity` with the changed key `fetched` of a `KeyChanged`
+    /// error, once the application or the user accepted it.
+    pub fn accept_change(&mut self, identity: &str, fetched: AMFPublicKey) {
+        self.pin(identity, fetched);
+    }
+
+    /// Pins `public_key` for `identity` in its role, e.g. a key verified out of band or restored
+    /// from storage, replacing any pinned key.
+    pub fn pin(&mut self, identity: &str, public_key: AMFPublicKey) {
+        let event = match self
+            .pins
+            .insert((identity.to_string(), public_key.role), public_key)
+        {
+            None => KeyEvent::Pinned {
+                identity: identity.to_string(),
+                public_key,
+            },
+            Some(previous) if previous == public_key => return,
+            Some(previous) => KeyEvent::Repinned {
+                identity: identity.to_string(),
+                previous,
+                public_key,
+            },
+        };
+        self.events.push(event);
+    }
+
+    /// The pinned key of `identity` in `role`, without fetching.
+    pub fn pinned(&self, identity: &str, role: AMFRole) -> Option<AMFPublicKey> {
+        self.pins.get(&(identity.to_string(), role)).copied()
+    }
+
+    /// The pinned keys and their identities, for storage.
+    pub fn pins(&self) -> impl Iterator<Item = (&str, AMFPublicKey)> {
+        self.pins
+            .iter()
+            .map(|((identity, _), public_key)| (identity.as_str(), *public_key))
+    }
+
+    /// The events since the last call, oldest first.
+    pub fn events(&mut self) -> Vec<KeyEvent> {
+        core::mem::take(&mut self.events)
+    }
+
+    /// fetches, decodes and checks the role of the key of `identity` in `role`
+    fn fetch(
+        &self,
+        identity: &str,
+        role: AMFRole,
+    ) -> Result<AMFPublicKey, DirectoryError<T::Error>> {
+        let bytes = self
+            .transport
+            .fetch(identity, role)
+            .map_err(DirectoryError::Transport)?;
+        let public_key = AMFPublicKey::from_bytes(&bytes).map_err(DirectoryError::Codec)?;
+        if public_key.role != role {
+            return Err(DirectoryError::RoleMismatch {
+                expected: role,
+                actual: public_key.role,
+            });
+        }
+        Ok(public_key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::RefCell;
+
+    use super::*;
+    use crate::amf::keygen;
+
+    /// a directory service in memory, which counts its fetches
+    #[derive(Default)]
+    struct MemoryTransport {
+        keys: BTreeMap<String, Vec<u8>>,
+        fetches: RefCell<usize>,
+    }
+
+    impl KeyTransport for MemoryTransport {
+        type Error = &'static str;
+
+        fn fetch(&self, identity: &str, _role: AMFRole) -> Result<Vec<u8>, &'static str> {
+            *self.fetches.borrow_mut() += 1;
+            self.keys.get(identity).cloned().ok_or("unknown identity")
+        }
+    }
+
+    #[test]
+    fn test_key_directory() {
+        let (alice, _) = keygen(AMFRole::Sender);
+        let (judge, _) = keygen(AMFRole::Judge);
+        let mut transport = MemoryTransport::default();
+        transport.keys.insert("alice".into(), alice.to_bytes());
+        transport.keys.insert("judge".into(), judge.to_bytes());
+        let mut directory = KeyDirectory::new(transport);
+
+        // 0. The first lookup fetches and pins, later ones use the pin
+        assert_eq!(directory.get("alice", AMFRole::Sender), Ok(alice));
+        assert_eq!(directory.get("alice", AMFRole::Sender), Ok(alice));
+        assert_eq!(*directory.transport.fetches.borrow(), 1);
+        assert_eq!(
+            directory.events(),
+            [KeyEvent::Pinned {
+                identity: "alice".into(),
+                public_key: alice,
+            }]
+        );
+
+        // 1. Unknown identities and keys of the wrong role fail, and pin nothing
+        assert_eq!(
+            directory.get("bob", AMFRole::Sender),
+            Err(DirectoryError::Transport("unknown identity"))
+        );
+        assert_eq!(
+            directory.get("judge", AMFRole::Sender),
+            Err(DirectoryError::RoleMismatch {
+                expected: AMFRole::Sender,
+                actual: AMFRole::Judge,
+            })
+        );
+        assert_eq!(directory.pinned("judge", AMFRole::Sender), None);
+
+        // 2. A changed key is reported, and the pin is kept until the change is accepted
+        let (new_alice, _) = keygen(AMFRole::Sender);
+        directory
+            .transport
+            .keys
+            .insert("alice".into(), new_alice.to_bytes());
+        assert_eq!(directory.get("alice", AMFRole::Sender), Ok(alice));
+        assert_eq!(
+            directory.refresh("alice", AMFRole::Sender),
+            Err(DirectoryError::KeyChanged {
+                fetched: Box::new(new_alice),
+            })
+        );
+        assert_eq!(directory.pinned("alice", AMFRole::Sender), Some(alice));
+        directory.accept_change("alice", new_alice);
+        assert_eq!(directory.refresh("alice", AMFRole::Sender), Ok(new_alice));
+        assert_eq!(
+            directory.events(),
+            [
+                KeyEvent::Changed {
+                    identity: "alice".into(),
+                    pinned: alice,
+                    fetched: new_alice,
+                },
+                KeyEvent::Repinned {
+                    identity: "alice".into(),
+                    previous: alice,
+                    public_key: new_alice,
+                },
+            ]
+        );
+
+        // 3. The pins restore into a new directory
+        let mut restored = KeyDirectory::new(MemoryTransport::default());
+        for (identity, public_key) in directory.pins() {
+            restored.pin(identity, public_key);
+        }
+        assert_eq!(restored.get("alice", AMFRole::Sender), Ok(new_alice));
+    }
+}
@@ -0,0 +1,267 @@
+//! Statistical Timing-Leak Tests (dudect)
+//!
+//! `verify` and `judge` claim to take as long to reject a signature as to accept it, whichever
+//! check fails (cf. `amf::verify`). This module tests the claim with the methodology of dudect
+//! [RBV17]: it times the operation on inputs of two classes, interleaved at random, and runs
+//! Welch's t-test on the two samples of timings, also after cropping the slowest measurements at a
+//! few percentiles, which removes most of the noise of interrupts and other threads. A largest
+//! |t| above `T_THRESHOLD` means that the classes are told apart by timing.
+//!
+//! For `verify` and `judge`, the first class is honest signatures, and the second is signatures
+//! that fail either b1 (checked with another secret key) or b2 (with a response scalar replaced).
+//! Every measurement franks a fresh signature: the verification equations are variable-time in
+//! the public signature, which is fine, but a fixed set of signatures would give the two classes
+//! slightly different average times. Degenerate signatures (b0) are rejected on public data, and
+//! are not part of the test.
+//!
+//! Like any statistical test, this can only find leaks, not prove their absence, and it measures
+//! the build under test; run it with optimizations to test what ships. Not for
+//! wasm32-unknown-unknown, where std::time::Instant panics.
+//!
+//! [RBV17]: https://eprint.iacr.org/2016/1123.pdf
+
+use std::time::Instant;
+
+use curve25519_dalek::scalar::Scalar;
+use rand::Rng;
+use rand_core::CryptoRngCore;
+
+use super::{
+    frank_with_rng, judge, keygen_with_rng, verify, AMFPublicKey, AMFRole, AMFSecretKey,
+    AMFSignature,
+};
+
+/// how many timings `verify_t` and `judge_t` take, of both classes together
+pub const MEASUREMENTS: usize = 5000;
+
+/// the |t| above which two classes are considered told apart, as in [RBV17]
+///
+/// [RBV17]: https://eprint.iacr.org/2016/1123.pdf
+pub const T_THRESHOLD: f64 = 4.5;
+
+/// the percentiles at which the timings are cropped, besides not at all
+const CROP_PERCENTILES: [f64; 5] = [0.5, 0.75, 0.9, 0.95, 0.99];
+
+/// Welch's t statistic of two samples; 0 if either has fewer than two elements.
+pub fn welch_t(left: &[f64], right: &[f64]) -> f64 {
+    fn mean_and_variance(sample: &[f64]) -> (f64, f64) {
+        let n = sample.len() as f64;
+        let mean = sample.iter().sum::<f64>() / n;
+        let variance = sample.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0);
+        (mean, variance)
+    }
+
+    if left.len() < 2 || right.len() < 2 {
+        return 0.0;
+    }
+    let (left_mean, left_variance) = mean_and_variance(left);
+    let (right_mean, right_variance) = mean_and_variance(right);
+    let standard_error =
+        (left_variance / left.len() as f64 + right_variance / right.len() as f64).sqrt();
+    if standard_error == 0.0 {
+        return 0.0;
+    }
+    (left_mean - right_mean) / standard_error
+}
+
+/// The largest |t| between the timings of the two classes, uncropped and cropped at every one of
+/// `CROP_PERCENTILES` of all the timings.
+pub fn max_t(timings: &[Vec<f64>; 2]) -> f64 {
+    let mut all: Vec<f64> = timings.iter().flatten().copied().collect();
+    all.sort_by(f64::total_cmp);
+    if all.is_empty() {
+        return 0.0;
+    }
+    let thresholds = CROP_PERCENTILES
+        .iter()
+        .map(|percentile| all[((all.len() - 1) as f64 * percentile) as usize])
+        .chain([f64::INFINITY]);
+    thresholds
+        .map(|threshold| {
+            let [left, right] = timings.each_ref().map(|class| {
+                class
+                    .iter()
+                    .copied()
+                    .filter(|&time| time <= threshold)
+                    .collect::<Vec<_>>()
+            });
+            welch_t(&left, &right).abs()
+        })
+        .fold(0.0, f64::max)
+}
+
+/// Times `operation` `measurements` times, each on an input that `prepare` makes for a class
+/// picked at random (`false` for the first, `true` for the second); returns the timings of each
+/// class in nanoseconds. Preparing is not timed.
+pub fn measure<I, T>(
+    rng: &mut dyn CryptoRngCore,
+    measurements: usize,
+    mut prepare: impl FnMut(&mut dyn CryptoRngCore, bool) -> I,
+    mut operation: impl FnMut(I) -> T,
+) -> [Vec<f64>; 2] {
+    let mut timings = [Vec::new(), Vec::new()];
+    for _ in 0..measurements {
+        let class = rng.gen::<bool>();
+        let input = prepare(rng, class);
+        let start = Instant::now();
+        let output = operation(input);
+        let elapsed = start.elapsed();
+        std::hint::black_box(output);
+        timings[usize::from(class)].push(elapsed.as_nanos() as f64);
+    }
+    timings
+}
+
+/// The inputs of one measurement of `verify` or `judge`: the secret key to check with, the
+/// message, and the signature.
+struct Input {
+    secret_key: AMFSecretKey,
+    message: &'static [u8],
+    amf_signature: AMFSignature,
+}
+
+/// Franks a fresh signature, and breaks it for the second class, cf. the module documentation.
+/// `role` picks whose secret key checks it.
+fn prepare(rng: &mut dyn CryptoRngCore, invalid: bool, keys: &Keys, role: AMFRole) -> Input {
+    let message = b"hello world!";
+    let mut amf_signature = frank_with_rng(
+        rng,
+        keys.sender.1.clone(),
+        keys.sender.0,
+        keys.recipient.0,
+        keys.judge.0,
+        message,
+    );
+    let mut secret_key = match role {
+        AMFRole::Judge => keys.judge.1.clone(),
+        _ => keys.recipient.1.clone(),
+    };
+    if invalid {
+        if rng.gen::<bool>() {
+            // b1 fails
+            secret_key = keygen_with_rng(rng, role).1;
+        } else {
+            // b2 fails
+            amf_signature.pi.prover_response.0.z_0 = Scalar::random(rng);
+        }
+    }
+    Input {
+        secret_key,
+        message,
+        amf_signature,
+    }
+}
+
+/// the key pairs of a sender, a recipient and a judge
+struct Keys {
+    sender: (AMFPublicKey, AMFSecretKey),
+    recipient: (AMFPublicKey, AMFSecretKey),
+    judge: (AMFPublicKey, AMFSecretKey),
+}
+
+impl Keys {
+    fn new(rng: &mut dyn CryptoRngCore) -> Self {
+        Keys {
+            sender: keygen_with_rng(rng, AMFRole::Sender),
+            recipient: keygen_with_rng(rng, AMFRole::Recipient),
+            judge: keygen_with_rng(rng, AMFRole::Judge),
+        }
+    }
+}
+
+/// `max_t` of `verify` on honest and broken signatures, cf. the module documentation
+pub fn verify_t(rng: &mut dyn CryptoRngCore, measurements: usize) -> f64 {
+    let keys = Keys::new(rng);
+    let timings = measure(
+        rng,
+        measurements,
+        |rng, invalid| prepare(rng, invalid, &keys, AMFRole::Recipient),
+        |input| {
+            verify(
+                input.secret_key,
+                keys.sender.0,
+                keys.recipient.0,
+                keys.judge.0,
+                input.message,
+                input.amf_signature,
+            )
+        },
+    );
+    max_t(&timings)
+}
+
+/// `max_t` of `judge` on honest and broken signatures, cf. the module documentation
+pub fn judge_t(rng: &mut dyn CryptoRngCore, measurements: usize) -> f64 {
+    let keys = Keys::new(rng);
+    let timings = measure(
+        rng,
+        measurements,
+        |rng, invalid| prepare(rng, invalid, &keys, AMFRole::Judge),
+        |input| {
+            judge(
+                input.secret_key,
+                keys.sender.0,
+                keys.recipient.0,
+                keys.judge.0,
+                input.message,
+                input.amf_signature,
+            )
+        },
+    );
+    max_t(&timings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_welch_t() {
+        // 0. Equal samples are not told apart, and shifted ones are
+        let sample: Vec<f64> = (0..100).map(f64::from).collect();
+        assert_eq!(welch_t(&sample, &sample), 0.0);
+        let shifted: Vec<f64> = sample.iter().map(|x| x + 50.0).collect();
+        assert!(welch_t(&sample, &shifted) < -T_THRESHOLD);
+        assert_eq!(welch_t(&sample, &[1.0]), 0.0);
+
+        // 1. Cropping ignores outliers
+        let mut with_outlier = sample.clone();
+        with_outlier[0] = 1e9;
+        assert!(max_t(&[sample.clone(), with_outlier]) < T_THRESHOLD);
+    }
+
+    #[test]
+    fn test_dudect_finds_leaks() {
+        // An operation that does more work for the second class is told apart
+        let mut rng = rand::thread_rng();
+        let keys = Keys::new(&mut rng);
+        let timings = measure(
+            &mut rng,
+            200,
+            |rng, invalid| (prepare(rng, false, &keys, AMFRole::Recipient), invalid),
+            |(input, leaky)| {
+                let verifications = if leaky { 2 } else { 1 };
+                (0..verifications).all(|_| {
+                    verify(
+                        input.secret_key.clone(),
+                        keys.sender.0,
+                        keys.recipient.0,
+                        keys.judge.0,
+                        input.message,
+                        input.amf_signature,
+                    )
+                })
+            },
+        );
+        assert!(max_t(&timings) > T_THRESHOLD);
+    }
+
+    #[test]
+    fn test_verify_and_judge_constant_time() {
+        let mut rng = rand::thread_rng();
+        let t = verify_t(&mut rng, MEASUREMENTS);
+        assert!(t < T_THRESHOLD, "verify leaks validity, |t| = {t}");
+        let t = judge_t(&mut rng, MEASUREMENTS);
+        assert!(t < T_THRESHOLD, "judge leaks validity, |t| = {t}");
+    }
+}
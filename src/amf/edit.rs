@@ -0,0 +1,495 @@
+//! Franks of Edits and Reactions
+//!
+//! An edit or a reaction refers to an earlier message. If it were franked like any other message,
+//! a judge shown a reported edit could not tell which message it edits, and a sender could deny
+//! that the edit and the original belong together. Here every frank has a `SignatureId`,
+//!
+//! ```text
+//! id = SHA-512(tag(EDIT_LABEL) || AMF signature)[..32]
+//! ```
+//!
+//! with `tag` as in `pok::domain` and the AMF signature in the wire format, and `frank_edit` and
+//! `frank_reaction` frank
+//!
+//! ```text
+//! tag(EDIT_LABEL) || kind || id of the original || message
+//! ```
+//!
+//! with kind 0 for edits and 1 for reactions, so the new frank attributes the link to the original
+//! as well as the new message. An edit of an edit links to the previous version, so the versions of
+//! a message form a chain that `verify_edit_chain` and `judge_edit_chain` check from the original
+//! to the last edit.
+//!
+//! The links are franks, so they are as deniable as the messages.
+
+use alloc::vec::Vec;
+use core::{borrow::Borrow, fmt};
+
+use rand_core::CryptoRngCore;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
+
+use super::{frank_with_rng, judge, verify, AMFPublicKey, AMFSecretKey, AMFSignature};
+use crate::pok::domain::{absorb, EDIT_LABEL};
+
+/// The id of a frank, cf. the module documentation.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SignatureId(pub [u8; 32]);
+
+impl SignatureId {
+    pub fn of(amf_signature: &AMFSignature) -> Self {
+        let mut hasher = Sha512::new();
+        absorb(&mut hasher, EDIT_LABEL);
+        hasher.update(amf_signature.to_bytes());
+        SignatureId(hasher.finalize()[..32].try_into().unwrap())
+    }
+}
+
+/// How a linked frank refers to the original message.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum LinkKind {
+    /// The message replaces the original.
+    Edit,
+    /// The message reacts to the original, e.g. an emoji.
+    Reaction,
+}
+
+impl LinkKind {
+    fn to_byte(self) -> u8 {
+        match self {
+            LinkKind::Edit => 0,
+            LinkKind::Reaction => 1,
+        }
+    }
+}
+
+/// A frank of an edit or a reaction, and the id of the frank it refers to.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LinkedFrank {
+    pub kind: LinkKind,
+    pub original: SignatureId,
+    pub amf_signature: AMFSignature,
+}
+
+impl LinkedFrank {
+    /// The id of this frank, which later edits link to.
+    pub fn id(&self) -> SignatureId {
+        SignatureId::of(&self.amf_signature)
+    }
+}
+
+/// the message that a linked frank franks, cf. the module documentation
+fn bind_link(kind: LinkKind, original: &SignatureId, message: &[u8]) -> Vec<u8> {
+    let mut bound = Vec::with_capacity(8 + EDIT_LABEL.len() + 1 + 32 + message.len());
+    bound.extend_from_slice(&(EDIT_LABEL.len() as u64).to_be_bytes());
+    bound.extend_from_slice(EDIT_LABEL);
+    bound.push(kind.to_byte());
+    bound.extend_from_slice(&original.0);
+    bound.extend_from_slice(message);
+    bound
+}
+
+#[cfg(any(feature = "rand", test))]
+/// `frank_edit_with_rng` with `rand::thread_rng`
+pub fn frank_edit(
+    sender_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    original_signature_id: SignatureId,
+    new_message: &[u8],
+) -> LinkedFrank {
+    frank_edit_with_rng(
+        &mut rand::thread_rng(),
+        sender_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        original_signature_id,
+        new_message,
+    )
+}
+
+/// Franks `new_message` as an edit of the message with the frank `original_signature_id`.
+pub fn frank_edit_with_rng(
+    rng: &mut dyn CryptoRngCore,
+    sender_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    original_signature_id: SignatureId,
+    new_message: &[u8],
+) -> LinkedFrank {
+    frank_linked_with_rng(
+        rng,
+        sender_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        LinkKind::Edit,
+        original_signature_id,
+        new_message,
+    )
+}
+
+#[cfg(any(feature = "rand", test))]
+/// `frank_reaction_with_rng` with `rand::thread_rng`
+pub fn frank_reaction(
+    sender_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    original_signature_id: SignatureId,
+    reaction: &[u8],
+) -> LinkedFrank {
+    frank_reaction_with_rng(
+        &mut rand::thread_rng(),
+        sender_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        original_signature_id,
+        reaction,
+    )
+}
+
+/// Franks `reaction` to the message with the frank `original_signature_id`.
+pub fn frank_reaction_with_rng(
+    rng: &mut dyn CryptoRngCore,
+    sender_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    original_signature_id: SignatureId,
+    reaction: &[u8],
+) -> LinkedFrank {
+    frank_linked_with_rng(
+        rng,
+        sender_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        LinkKind::Reaction,
+        original_signature_id,
+        reaction,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn frank_linked_with_rng(
+    rng: &mut dyn CryptoRngCore,
+    sender_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    kind: LinkKind,
+    original: SignatureId,
+    message: &[u8],
+) -> LinkedFrank {
+    LinkedFrank {
+        kind,
+        original,
+        amf_signature: frank_with_rng(
+            rng,
+            sender_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            &bind_link(kind, &original, message),
+        ),
+    }
+}
+
+/// `amf::verify` of a linked frank of `message`.
+pub fn verify_linked(
+    recipient_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    message: &[u8],
+    linked_frank: &LinkedFrank,
+) -> bool {
+    verify(
+        recipient_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        &bind_link(linked_frank.kind, &linked_frank.original, message),
+        linked_frank.amf_signature,
+    )
+}
+
+/// `amf::judge` of a linked frank of `message`.
+pub fn judge_linked(
+    judge_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    message: &[u8],
+    linked_frank: &LinkedFrank,
+) -> bool {
+    judge(
+        judge_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        &bind_link(linked_frank.kind, &linked_frank.original, message),
+        linked_frank.amf_signature,
+    )
+}
+
+/// Why an edit chain does not check, with the index of the version, 0 for the original.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EditChainError {
+    /// The frank of the version does not verify.
+    InvalidSignature(usize),
+    /// The version is a reaction, not an edit.
+    NotAnEdit(usize),
+    /// The version does not link to the previous one.
+    BrokenLink(usize),
+}
+
+impl fmt::Display for EditChainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EditChainError::InvalidSignature(index) => {
+                write!(f, "invalid signature of version {}", index)
+            }
+            EditChainError::NotAnEdit(index) => write!(f, "version {} is not an edit", index),
+            EditChainError::BrokenLink(index) => {
+                write!(f, "version {} does not link to the previous one", index)
+            }
+        }
+    }
+}
+
+impl core::error::Error for EditChainError {}
+
+/// checks the links of the edit chain, and every frank with `check`
+fn check_edit_chain<M: AsRef<[u8]>>(
+    check: impl Fn(&[u8], &AMFSignature) -> bool,
+    original_message: &[u8],
+    original_signature: &AMFSignature,
+    edits: &[(M, LinkedFrank)],
+) -> Result<(), EditChainError> {
+    if !check(original_message, original_signature) {
+        return Err(EditChainError::InvalidSignature(0));
+    }
+    let mut previous = SignatureId::of(original_signature);
+    for (index, (message, linked_frank)) in (1..).zip(edits) {
+        if linked_frank.kind != LinkKind::Edit {
+            return Err(EditChainError::NotAnEdit(index));
+        }
+        if linked_frank.original != previous {
+            return Err(EditChainError::BrokenLink(index));
+        }
+        let bound = bind_link(linked_frank.kind, &linked_frank.original, message.as_ref());
+        if !check(&bound, &linked_frank.amf_signature) {
+            return Err(EditChainError::InvalidSignature(index));
+        }
+        previous = linked_frank.id();
+    }
+    Ok(())
+}
+
+/// `amf::verify` of every version of an edited message, and of the links between them: the
+/// original message and its frank, then the edits in order.
+pub fn verify_edit_chain<M: AsRef<[u8]>>(
+    recipient_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    original_message: &[u8],
+    original_signature: &AMFSignature,
+    edits: &[(M, LinkedFrank)],
+) -> Result<(), EditChainError> {
+    let (recipient_secret_key, sender_public_key, recipient_public_key, judge_public_key) = (
+        recipient_secret_key.borrow(),
+        sender_public_key.borrow(),
+        recipient_public_key.borrow(),
+        judge_public_key.borrow(),
+    );
+    check_edit_chain(
+        |message, amf_signature| {
+            verify(
+                recipient_secret_key,
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+                *amf_signature,
+            )
+        },
+        original_message,
+        original_signature,
+        edits,
+    )
+}
+
+/// Like `verify_edit_chain`, with the judge's secret key, e.g. for a reported edited message.
+pub fn judge_edit_chain<M: AsRef<[u8]>>(
+    judge_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    original_message: &[u8],
+    original_signature: &AMFSignature,
+    edits: &[(M, LinkedFrank)],
+) -> Result<(), EditChainError> {
+    let (judge_secret_key, sender_public_key, recipient_public_key, judge_public_key) = (
+        judge_secret_key.borrow(),
+        sender_public_key.borrow(),
+        recipient_public_key.borrow(),
+        judge_public_key.borrow(),
+    );
+    check_edit_chain(
+        |message, amf_signature| {
+            judge(
+                judge_secret_key,
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+                *amf_signature,
+            )
+        },
+        original_message,
+        original_signature,
+        edits,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amf::{frank, keygen, AMFRole};
+
+    #[test]
+    fn test_edits_and_reactions() {
+        // 0. Frank a message, two edits of it, and a reaction to it
+        let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+        let (recipient_public_key, recipient_secret_key) = keygen(AMFRole::Recipient);
+        let (judge_public_key, judge_secret_key) = keygen(AMFRole::Judge);
+        let keys = (sender_public_key, recipient_public_key, judge_public_key);
+        let original = frank(&sender_secret_key, keys.0, keys.1, keys.2, b"helo");
+        let first = frank_edit(
+            &sender_secret_key,
+            keys.0,
+            keys.1,
+            keys.2,
+            SignatureId::of(&original),
+            b"hello",
+        );
+        let second = frank_edit(
+            &sender_secret_key,
+            keys.0,
+            keys.1,
+            keys.2,
+            first.id(),
+            b"hello world",
+        );
+        let reaction = frank_reaction(
+            &sender_secret_key,
+            keys.0,
+            keys.1,
+            keys.2,
+            SignatureId::of(&original),
+            b"+1",
+        );
+
+        // 1. Every linked frank verifies on its message, and only on it
+        for (message, linked_frank) in [
+            (b"hello".as_slice(), &first),
+            (b"hello world", &second),
+            (b"+1", &reaction),
+        ] {
+            assert!(verify_linked(
+                &recipient_secret_key,
+                keys.0,
+                keys.1,
+                keys.2,
+                message,
+                linked_frank
+            ));
+            assert!(judge_linked(
+                &judge_secret_key,
+                keys.0,
+                keys.1,
+                keys.2,
+                message,
+                linked_frank
+            ));
+            assert!(!verify_linked(
+                &recipient_secret_key,
+                keys.0,
+                keys.1,
+                keys.2,
+                b"goodbye",
+                linked_frank
+            ));
+        }
+
+        // 2. The link is franked: it cannot be moved to another message or changed in kind
+        let mut moved = first;
+        moved.original =
+            SignatureId::of(&frank(&sender_secret_key, keys.0, keys.1, keys.2, b"other"));
+        let mut as_reaction = first;
+        as_reaction.kind = LinkKind::Reaction;
+        for linked_frank in [&moved, &as_reaction] {
+            assert!(!judge_linked(
+                &judge_secret_key,
+                keys.0,
+                keys.1,
+                keys.2,
+                b"hello",
+                linked_frank
+            ));
+        }
+
+        // 3. The judge checks the whole edit chain of a reported message
+        let chain = [(b"hello".as_slice(), first), (b"hello world", second)];
+        let judge_chain = |edits: &[(&[u8], LinkedFrank)]| {
+            judge_edit_chain(
+                &judge_secret_key,
+                keys.0,
+                keys.1,
+                keys.2,
+                b"helo",
+                &original,
+                edits,
+            )
+        };
+        assert_eq!(judge_chain(&chain), Ok(()));
+        assert_eq!(
+            verify_edit_chain(
+                &recipient_secret_key,
+                keys.0,
+                keys.1,
+                keys.2,
+                b"helo",
+                &original,
+                &chain,
+            ),
+            Ok(())
+        );
+
+        // 4. Skipped, reordered or changed versions, and reactions, break the chain
+        assert_eq!(judge_chain(&chain[1..]), Err(EditChainError::BrokenLink(1)));
+        assert_eq!(
+            judge_chain(&[chain[1], chain[0]]),
+            Err(EditChainError::BrokenLink(1))
+        );
+        assert_eq!(
+            judge_chain(&[chain[0], (b"goodbye", second)]),
+            Err(EditChainError::InvalidSignature(2))
+        );
+        assert_eq!(
+            judge_chain(&[(b"+1", reaction)]),
+            Err(EditChainError::NotAnEdit(1))
+        );
+    }
+}
@@ -0,0 +1,291 @@
+//! Per-Epoch Aggregate Franks of a Conversation
+//!
+//! Archives that must keep every franked message for compliance spend most of their space on the
+//! 486-byte signatures. Here the messages that a sender sent in an epoch of a conversation, e.g. a
+//! day, are chained into a `TranscriptChain`:
+//!
+//! ```text
+//! h_0     = SHA-512(tag(EPOCH_LABEL) || tag(conversation_id) || epoch)
+//! h_{i+1} = SHA-512(tag(EPOCH_LABEL) || tag(h_i) || tag(message_i))
+//! ```
+//!
+//! with `tag` as in `pok::domain` and the epoch as an 8-byte big-endian integer. At the end of the
+//! epoch the sender franks the head of the chain and the number of messages once, as an
+//! `EpochAggregate`. The recipient checks it against the messages with `verify_epoch`, and can then
+//! discard the franks of the single messages and archive only the messages and the aggregate. A
+//! judge checks a report of the whole epoch with `judge_epoch`.
+//!
+//! The aggregate is an AMF frank of the chain in the context `EPOCH_LABEL`, cf. `bind_context`, so
+//! it is as deniable as a frank, and neither passes as a plain frank nor a plain frank as it. It
+//! attributes the messages in order: a transcript with a message left out, added or reordered does
+//! not verify. Until the sender franks the aggregate, the franks of the single messages are the
+//! only evidence, so recipients should keep them until then.
+
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+
+use rand_core::CryptoRngCore;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
+
+use super::{
+    bind_context, frank_with_rng, judge, verify, AMFPublicKey, AMFSecretKey, AMFSignature,
+};
+use crate::pok::domain::{absorb, EPOCH_LABEL};
+
+/// The hash chain over the messages of an epoch, cf. the module documentation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranscriptChain {
+    epoch: u64,
+    head: [u8; 64],
+    count: u64,
+}
+
+impl TranscriptChain {
+    /// The empty chain of `epoch` in the conversation `conversation_id`, h_0.
+    pub fn new(conversation_id: &[u8], epoch: u64) -> Self {
+        let mut hasher = Sha512::new();
+        absorb(&mut hasher, EPOCH_LABEL);
+        absorb(&mut hasher, conversation_id);
+        hasher.update(epoch.to_be_bytes());
+        TranscriptChain {
+            epoch,
+            head: hasher.finalize().into(),
+            count: 0,
+        }
+    }
+
+    /// The chain over `messages`, in order.
+    pub fn from_messages<M: AsRef<[u8]>>(
+        conversation_id: &[u8],
+        epoch: u64,
+        messages: impl IntoIterator<Item = M>,
+    ) -> Self {
+        let mut chain = Self::new(conversation_id, epoch);
+        for message in messages {
+            chain.append(message.as_ref());
+        }
+        chain
+    }
+
+    /// Appends the next message of the epoch.
+    pub fn append(&mut self, message: &[u8]) {
+        let mut hasher = Sha512::new();
+        absorb(&mut hasher, EPOCH_LABEL);
+        absorb(&mut hasher, &self.head);
+        absorb(&mut hasher, message);
+        self.head = hasher.finalize().into();
+        self.count += 1;
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// The head h_n of the chain.
+    pub fn head(&self) -> [u8; 64] {
+        self.head
+    }
+
+    /// The number n of messages in the chain.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// The message that the aggregate franks: `count || h_n` bound to the context `EPOCH_LABEL`,
+    /// cf. `bind_context`, with the count as an 8-byte big-endian integer; the epoch is in h_0.
+    fn franked(&self) -> Vec<u8> {
+        let mut aggregate = Vec::with_capacity(8 + 64);
+        aggregate.extend_from_slice(&self.count.to_be_bytes());
+        aggregate.extend_from_slice(&self.head);
+        bind_context(EPOCH_LABEL, &aggregate)
+    }
+}
+
+/// One frank for all the messages of an epoch, cf. the module documentation.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EpochAggregate {
+    pub epoch: u64,
+    /// the number of messages it covers
+    pub count: u64,
+    pub amf_signature: AMFSignature,
+}
+
+#[cfg(any(feature = "rand", test))]
+/// `aggregate_epoch_with_rng` with `rand::thread_rng`
+pub fn aggregate_epoch(
+    sender_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    chain: &TranscriptChain,
+) -> EpochAggregate {
+    aggregate_epoch_with_rng(
+        &mut rand::thread_rng(),
+        sender_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        chain,
+    )
+}
+
+/// Franks the chain of the sender's messages of an epoch.
+pub fn aggregate_epoch_with_rng(
+    rng: &mut dyn CryptoRngCore,
+    sender_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    chain: &TranscriptChain,
+) -> EpochAggregate {
+    EpochAggregate {
+        epoch: chain.epoch,
+        count: chain.count,
+        amf_signature: frank_with_rng(
+            rng,
+            sender_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            &chain.franked(),
+        ),
+    }
+}
+
+/// `amf::verify` of an aggregate against the messages of its epoch, in order.
+pub fn verify_epoch<M: AsRef<[u8]>>(
+    recipient_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    conversation_id: &[u8],
+    messages: impl IntoIterator<Item = M>,
+    aggregate: &EpochAggregate,
+) -> bool {
+    let chain = TranscriptChain::from_messages(conversation_id, aggregate.epoch, messages);
+    verify(
+        recipient_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        &chain.franked(),
+        aggregate.amf_signature,
+    ) & (chain.count == aggregate.count)
+}
+
+/// Like `verify_epoch`, with the judge's secret key.
+pub fn judge_epoch<M: AsRef<[u8]>>(
+    judge_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    conversation_id: &[u8],
+    messages: impl IntoIterator<Item = M>,
+    aggregate: &EpochAggregate,
+) -> bool {
+    let chain = TranscriptChain::from_messages(conversation_id, aggregate.epoch, messages);
+    judge(
+        judge_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        &chain.franked(),
+        aggregate.amf_signature,
+    ) & (chain.count == aggregate.count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        amf::{frank, keygen, AMFRole},
+        pok::domain::tag,
+    };
+
+    #[test]
+    fn test_epoch_aggregate() {
+        // 0. The sender chains the messages of an epoch as it sends them, and franks the chain
+        let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+        let (recipient_public_key, recipient_secret_key) = keygen(AMFRole::Recipient);
+        let (judge_public_key, judge_secret_key) = keygen(AMFRole::Judge);
+        let messages = [b"hello".as_slice(), b"world", b"!"];
+        let mut chain = TranscriptChain::new(b"conversation", 7);
+        for message in messages {
+            chain.append(message);
+        }
+        assert_eq!(
+            chain,
+            TranscriptChain::from_messages(b"conversation", 7, messages)
+        );
+        let aggregate = aggregate_epoch(
+            &sender_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            &chain,
+        );
+        assert_eq!((aggregate.epoch, aggregate.count), (7, 3));
+
+        // 1. The recipient and the judge check it against the messages
+        let keys = (sender_public_key, recipient_public_key, judge_public_key);
+        let check = |conversation_id: &[u8], messages: &[&[u8]], aggregate: &EpochAggregate| {
+            (
+                verify_epoch(
+                    &recipient_secret_key,
+                    keys.0,
+                    keys.1,
+                    keys.2,
+                    conversation_id,
+                    messages,
+                    aggregate,
+                ),
+                judge_epoch(
+                    &judge_secret_key,
+                    keys.0,
+                    keys.1,
+                    keys.2,
+                    conversation_id,
+                    messages,
+                    aggregate,
+                ),
+            )
+        };
+        assert_eq!(check(b"conversation", &messages, &aggregate), (true, true));
+
+        // 2. Left out, reordered or changed messages, another conversation or another epoch fail
+        let mut other_epoch = aggregate;
+        other_epoch.epoch = 8;
+        for (conversation_id, messages, aggregate) in [
+            (b"conversation".as_slice(), &messages[..2], &aggregate),
+            (
+                b"conversation",
+                &[messages[1], messages[0], messages[2]],
+                &aggregate,
+            ),
+            (b"conversation", &[b"hello", b"world", b"?"], &aggregate),
+            (b"other", &messages[..], &aggregate),
+            (b"conversation", &messages[..], &other_epoch),
+        ] {
+            assert_eq!(check(conversation_id, messages, aggregate), (false, false));
+        }
+
+        // 3. Plain franks of the count and the head, with or without the label, are no aggregates
+        let mut unbound = Vec::new();
+        tag(&mut unbound, EPOCH_LABEL);
+        for prefix in [&[][..], &unbound] {
+            let plain = [prefix, &chain.count().to_be_bytes(), &chain.head()].concat();
+            let mut forged = aggregate;
+            forged.amf_signature = frank(
+                &sender_secret_key,
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                &plain,
+            );
+            assert_eq!(check(b"conversation", &messages, &forged), (false, false));
+        }
+    }
+}
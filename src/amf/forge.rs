@@ -0,0 +1,260 @@
+//! AMF Forging Algorithms (Forge, RForge, JForge)
+//!
+//! Cf. Section 4 and Fig. 5 in [AMF]. Each forger proves the other branch of one or both OR proofs
+//! of pi, with a witness it picks itself:
+//!
+//! - `forge`, with no secret key: J = g^gamma and R = g^delta; verifies for nobody, cf. universal
+//!   deniability;
+//! - `r_forge`, with the recipient's secret key: R = E_R^sk_r; verifies for the recipient but not
+//!   the judge, cf. receiver compromise deniability;
+//! - `j_forge`, with the judge's secret key: J = E_J^sk_j, whose discrete logarithm the judge
+//!   knows; verifies for the recipient and the judge, cf. judge compromise deniability.
+//!
+//! Without a secret key, a forgery looks like an honest frank, under DDH; `amf::deniability`
+//! checks this statistically.
+//!
+//! [AMF]: https://eprint.iacr.org/2019/565/20190527:092413
+#![allow(non_snake_case)]
+
+use core::borrow::Borrow;
+
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use rand_core::CryptoRngCore;
+use zeroize::Zeroize;
+
+use super::{
+    spok_amf::{AMFSPoK, AMFWitness},
+    AMFPublicKey, AMFSecretKey, AMFSignature,
+};
+use crate::pok::or_proof::OrWitness;
+
+#[cfg(any(feature = "rand", test))]
+/// `forge_with_rng` with `rand::thread_rng`
+pub fn forge(
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    message: &[u8],
+) -> AMFSignature {
+    forge_with_rng(
+        &mut rand::thread_rng(),
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        message,
+    )
+}
+
+/// Forges a signature on `message` from the public keys alone (Forge).
+pub fn forge_with_rng(
+    rng: &mut dyn CryptoRngCore,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    _recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    message: &[u8],
+) -> AMFSignature {
+    let [mut alpha, mut beta, mut gamma, mut delta] = [(); 4].map(|()| Scalar::random(rng));
+    let signature = sign(
+        rng,
+        sender_public_key.borrow(),
+        judge_public_key.borrow(),
+        [
+            RistrettoPoint::mul_base(&gamma),
+            RistrettoPoint::mul_base(&delta),
+            RistrettoPoint::mul_base(&alpha),
+            RistrettoPoint::mul_base(&beta),
+        ],
+        (OrWitness::Right(gamma), OrWitness::Right(delta)),
+        message,
+    );
+    for scalar in [&mut alpha, &mut beta, &mut gamma, &mut delta] {
+        scalar.zeroize();
+    }
+    signature
+}
+
+#[cfg(any(feature = "rand", test))]
+/// `r_forge_with_rng` with `rand::thread_rng`
+pub fn r_forge(
+    recipient_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    message: &[u8],
+) -> AMFSignature {
+    r_forge_with_rng(
+        &mut rand::thread_rng(),
+        recipient_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        message,
+    )
+}
+
+/// Forges a signature on `message` that verifies for the recipient whose secret key it is
+/// (RForge).
+pub fn r_forge_with_rng(
+    rng: &mut dyn CryptoRngCore,
+    recipient_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    _recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    message: &[u8],
+) -> AMFSignature {
+    let [mut alpha, mut beta, mut gamma] = [(); 3].map(|()| Scalar::random(rng));
+    let mut w = beta * recipient_secret_key.borrow().secret_key;
+    let signature = sign(
+        rng,
+        sender_public_key.borrow(),
+        judge_public_key.borrow(),
+        [
+            RistrettoPoint::mul_base(&gamma),
+            RistrettoPoint::mul_base(&w),
+            RistrettoPoint::mul_base(&alpha),
+            RistrettoPoint::mul_base(&beta),
+        ],
+        (OrWitness::Right(gamma), OrWitness::Right(w)),
+        message,
+    );
+    for scalar in [&mut alpha, &mut beta, &mut gamma, &mut w] {
+        scalar.zeroize();
+    }
+    signature
+}
+
+#[cfg(any(feature = "rand", test))]
+/// `j_forge_with_rng` with `rand::thread_rng`
+pub fn j_forge(
+    judge_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    message: &[u8],
+) -> AMFSignature {
+    j_forge_with_rng(
+        &mut rand::thread_rng(),
+        judge_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        message,
+    )
+}
+
+/// Forges a signature on `message` that verifies for the recipient and the judge whose secret key
+/// it is (JForge).
+pub fn j_forge_with_rng(
+    rng: &mut dyn CryptoRngCore,
+    judge_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    message: &[u8],
+) -> AMFSignature {
+    let [mut alpha, mut beta] = [(); 2].map(|()| Scalar::random(rng));
+    let mut u = alpha * judge_secret_key.borrow().secret_key;
+    let signature = sign(
+        rng,
+        sender_public_key.borrow(),
+        judge_public_key.borrow(),
+        [
+            RistrettoPoint::mul_base(&u),
+            recipient_public_key.borrow().public_key * beta,
+            RistrettoPoint::mul_base(&alpha),
+            RistrettoPoint::mul_base(&beta),
+        ],
+        (OrWitness::Right(u), OrWitness::Left(alpha)),
+        message,
+    );
+    for scalar in [&mut alpha, &mut beta, &mut u] {
+        scalar.zeroize();
+    }
+    signature
+}
+
+/// proves pi for J, R, E_J and E_R, in this order, with the forger's witness
+fn sign(
+    rng: &mut dyn CryptoRngCore,
+    sender_public_key: &AMFPublicKey,
+    judge_public_key: &AMFPublicKey,
+    [J, R, E_J, E_R]: [RistrettoPoint; 4],
+    witness: AMFWitness,
+    message: &[u8],
+) -> AMFSignature {
+    let pi = AMFSPoK::new(
+        sender_public_key.public_key,
+        judge_public_key.public_key,
+        J,
+        R,
+        E_J,
+    )
+    .sign_witness(rng, witness, message)
+    .expect("the forger's witness always fits the AMF statement");
+    AMFSignature { pi, J, R, E_J, E_R }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amf::{judge, keygen, verify, AMFRole};
+
+    #[test]
+    fn test_forgers() {
+        // 0. Fix the keys, and a message that the sender never franks
+        let (sender_public_key, _) = keygen(AMFRole::Sender);
+        let (recipient_public_key, recipient_secret_key) = keygen(AMFRole::Recipient);
+        let (judge_public_key, judge_secret_key) = keygen(AMFRole::Judge);
+        let message = b"hello world!";
+
+        // 1. Forge, RForge and JForge verify for nobody, the recipient, and both, respectively
+        let forgeries = [
+            forge(
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+            ),
+            r_forge(
+                &recipient_secret_key,
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+            ),
+            j_forge(
+                &judge_secret_key,
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+            ),
+        ];
+        let outcomes = forgeries.map(|amf_signature| {
+            (
+                verify(
+                    &recipient_secret_key,
+                    sender_public_key,
+                    recipient_public_key,
+                    judge_public_key,
+                    message,
+                    amf_signature,
+                ),
+                judge(
+                    &judge_secret_key,
+                    sender_public_key,
+                    recipient_public_key,
+                    judge_public_key,
+                    message,
+                    amf_signature,
+                ),
+            )
+        });
+        assert_eq!(outcomes, [(false, false), (true, false), (true, true)]);
+
+        // 2. Every forgery is well-formed
+        for amf_signature in forgeries {
+            assert!(amf_signature.validate().is_ok());
+        }
+    }
+}
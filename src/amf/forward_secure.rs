@@ -0,0 +1,528 @@
+//! Forward-Secure Franking with Evolving Sender Keys
+//!
+//! A forward-secure sender key franks with a different AMF key pair in every epoch, e.g. every day,
+//! and `evolve_key` moves it to a later epoch by hashing its seed forward, which overwrites the
+//! seed of the epochs before. Whoever compromises the key in epoch e can therefore frank for epoch
+//! e and later, but not for the epochs before it: reports of earlier messages keep pointing at the
+//! sender.
+//!
+//! The key pair of epoch i is derived from the seed s_i, with s_{i+1} = H("evolve", s_i).
+//! Recipients and judges cannot derive the public keys of the epochs themselves, so keygen derives
+//! all of them once and publishes the root of a Merkle tree over them as the
+//! `ForwardSecurePublicKey`. A `ForwardSecureSignature` carries its epoch, the public key of the
+//! epoch and its Merkle path, and an AMF signature on
+//! `tag(FORWARD_SECURE_LABEL) || epoch || message`, so that it does not verify for any other
+//! epoch. All hashes are SHA-512, truncated to 32 bytes for the tree, with the tags of
+//! `pok::domain`.
+//!
+//! Keygen takes a scalar multiplication per epoch, and the secret key keeps the leaves of the tree
+//! (32 bytes per epoch) to compute the paths; `MAX_EPOCHS` bounds both. Deniability is that of
+//! `amf::frank`: the key of an epoch is an ordinary sender key.
+
+use alloc::vec::Vec;
+use core::{borrow::Borrow, fmt};
+
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use rand_core::CryptoRngCore;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
+use zeroize::Zeroize;
+
+use super::{frank_with_rng, judge, verify, AMFPublicKey, AMFRole, AMFSecretKey, AMFSignature};
+use crate::pok::domain::{absorb, tag, FORWARD_SECURE_LABEL};
+
+/// The largest number of epochs of a forward-secure key.
+pub const MAX_EPOCHS: u64 = 1 << 16;
+
+/// Why a forward-secure key could not be generated or evolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ForwardSecureError {
+    /// The number of epochs is 0 or above `MAX_EPOCHS`.
+    InvalidEpochs(u64),
+    /// The epoch is at or after the number of epochs of the key.
+    EpochOutOfRange { epoch: u64, epochs: u64 },
+    /// The key already evolved past the epoch; it cannot go back.
+    EpochInPast { epoch: u64, current: u64 },
+}
+
+impl fmt::Display for ForwardSecureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ForwardSecureError::InvalidEpochs(epochs) => {
+                write!(f, "{} epochs, expected 1 to {}", epochs, MAX_EPOCHS)
+            }
+            ForwardSecureError::EpochOutOfRange { epoch, epochs } => {
+                write!(f, "epoch {} of a key of {} epochs", epoch, epochs)
+            }
+            ForwardSecureError::EpochInPast { epoch, current } => {
+                write!(f, "epoch {} is before the current epoch {}", epoch, current)
+            }
+        }
+    }
+}
+
+impl core::error::Error for ForwardSecureError {}
+
+/// The root of the Merkle tree over the public keys of all epochs, and their number.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ForwardSecurePublicKey {
+    pub root: [u8; 32],
+    pub epochs: u64,
+}
+
+/// The seed of the current epoch, and the leaves of the Merkle tree, which are public.
+///
+/// The seed is overwritten when the key evolves and, with the `zeroize` feature, when the key is
+/// dropped. The key is not `Clone`, so that no copy keeps the seed of an epoch it evolved past.
+pub struct ForwardSecureSecretKey {
+    seed: [u8; 32],
+    epoch: u64,
+    leaves: Vec<[u8; 32]>,
+}
+
+/// Prints the epoch only, so that seeds cannot leak through logs.
+impl fmt::Debug for ForwardSecureSecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ForwardSecureSecretKey")
+            .field("epoch", &self.epoch)
+            .field("epochs", &self.leaves.len())
+            .field("seed", &format_args!("<redacted>"))
+            .finish()
+    }
+}
+
+/// Overwrites the seed; the leaves are public.
+#[cfg(feature = "zeroize")]
+impl Zeroize for ForwardSecureSecretKey {
+    fn zeroize(&mut self) {
+        self.seed.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for ForwardSecureSecretKey {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for ForwardSecureSecretKey {}
+
+/// An AMF signature by the key of an epoch, with the proof that the key belongs to the sender.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForwardSecureSignature {
+    pub epoch: u64,
+    /// the sender's public key of the epoch
+    pub epoch_public_key: AMFPublicKey,
+    /// the sibling hashes from the leaf of the epoch up to the root
+    pub path: Vec<[u8; 32]>,
+    pub amf_signature: AMFSignature,
+}
+
+/// `H(tag(FORWARD_SECURE_LABEL) || tag(role) || input)`, truncated to 32 bytes
+fn hash(role: &[u8], inputs: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = Sha512::new();
+    absorb(&mut hasher, FORWARD_SECURE_LABEL);
+    absorb(&mut hasher, role);
+    for input in inputs {
+        absorb(&mut hasher, input);
+    }
+    hasher.finalize()[..32].try_into().unwrap()
+}
+
+/// the key pair of the epoch of `seed`
+fn epoch_key_pair(seed: &[u8; 32]) -> (AMFPublicKey, AMFSecretKey) {
+    let mut hasher = Sha512::new();
+    absorb(&mut hasher, FORWARD_SECURE_LABEL);
+    absorb(&mut hasher, b"key");
+    absorb(&mut hasher, seed);
+    let secret_key = Scalar::from_hash(hasher);
+    (
+        AMFPublicKey {
+            role: AMFRole::Sender,
+            public_key: RistrettoPoint::mul_base(&secret_key),
+        },
+        AMFSecretKey {
+            role: AMFRole::Sender,
+            secret_key,
+        },
+    )
+}
+
+/// the seed of the next epoch
+fn evolve_seed(seed: &mut [u8; 32]) {
+    let mut next = hash(b"evolve", &[seed]);
+    seed.copy_from_slice(&next);
+    next.zeroize();
+}
+
+fn leaf(epoch_public_key: &AMFPublicKey) -> [u8; 32] {
+    hash(
+        b"leaf",
+        &[epoch_public_key.public_key.compress().as_bytes()],
+    )
+}
+
+fn node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    hash(b"node", &[left, right])
+}
+
+/// the levels of the tree over `leaves`, from the leaves up to the root; an odd node is paired with
+/// itself
+fn levels(leaves: &[[u8; 32]]) -> Vec<Vec<[u8; 32]>> {
+    let mut levels = alloc::vec![leaves.to_vec()];
+    while levels.last().unwrap().len() > 1 {
+        let level = levels.last().unwrap();
+        let next = level
+            .chunks(2)
+            .map(|pair| node(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+/// The message that the key of `epoch` franks: `tag(FORWARD_SECURE_LABEL) || epoch || message`,
+/// with the epoch as an 8-byte big-endian integer.
+pub fn bind_epoch(epoch: u64, message: &[u8]) -> Vec<u8> {
+    let mut bound = Vec::with_capacity(16 + FORWARD_SECURE_LABEL.len() + message.len());
+    tag(&mut bound, FORWARD_SECURE_LABEL);
+    bound.extend_from_slice(&epoch.to_be_bytes());
+    bound.extend_from_slice(message);
+    bound
+}
+
+#[cfg(any(feature = "rand", test))]
+/// `keygen_forward_secure_with_rng` with `rand::thread_rng`
+pub fn keygen_forward_secure(
+    epochs: u64,
+) -> Result<(ForwardSecurePublicKey, ForwardSecureSecretKey), ForwardSecureError> {
+    keygen_forward_secure_with_rng(&mut rand::thread_rng(), epochs)
+}
+
+/// Generates a forward-secure sender key for `epochs` epochs, in epoch 0.
+pub fn keygen_forward_secure_with_rng(
+    rng: &mut dyn CryptoRngCore,
+    epochs: u64,
+) -> Result<(ForwardSecurePublicKey, ForwardSecureSecretKey), ForwardSecureError> {
+    if epochs == 0 || epochs > MAX_EPOCHS {
+        return Err(ForwardSecureError::InvalidEpochs(epochs));
+    }
+    let mut seed = [0u8; 32];
+    rng.fill_bytes(&mut seed);
+    let mut epoch_seed = seed;
+    let leaves: Vec<[u8; 32]> = (0..epochs)
+        .map(|epoch| {
+            if epoch > 0 {
+                evolve_seed(&mut epoch_seed);
+            }
+            leaf(&epoch_key_pair(&epoch_seed).0)
+        })
+        .collect();
+    epoch_seed.zeroize();
+    let root = *levels(&leaves).last().unwrap().first().unwrap();
+    let secret_key = ForwardSecureSecretKey {
+        seed,
+        epoch: 0,
+        leaves,
+    };
+    seed.zeroize();
+    Ok((ForwardSecurePublicKey { root, epochs }, secret_key))
+}
+
+impl ForwardSecureSecretKey {
+    /// The current epoch.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// The number of epochs of the key.
+    pub fn epochs(&self) -> u64 {
+        self.leaves.len() as u64
+    }
+
+    /// Moves the key to `epoch`, overwriting the seeds of the epochs before it. Evolving to the
+    /// current epoch does nothing.
+    pub fn evolve_key(&mut self, epoch: u64) -> Result<(), ForwardSecureError> {
+        if epoch >= self.epochs() {
+            return Err(ForwardSecureError::EpochOutOfRange {
+                epoch,
+                epochs: self.epochs(),
+            });
+        }
+        if epoch < self.epoch {
+            return Err(ForwardSecureError::EpochInPast {
+                epoch,
+                current: self.epoch,
+            });
+        }
+        while self.epoch < epoch {
+            evolve_seed(&mut self.seed);
+            self.epoch += 1;
+        }
+        Ok(())
+    }
+
+    /// The sibling hashes from the leaf of the current epoch up to the root.
+    fn path(&self) -> Vec<[u8; 32]> {
+        let levels = levels(&self.leaves);
+        let mut index = self.epoch as usize;
+        let mut path = Vec::with_capacity(levels.len() - 1);
+        for level in &levels[..levels.len() - 1] {
+            path.push(*level.get(index ^ 1).unwrap_or(&level[index]));
+            index /= 2;
+        }
+        path
+    }
+}
+
+impl ForwardSecurePublicKey {
+    /// Checks that `epoch_public_key` is the key of `epoch`, with the Merkle `path` of a signature.
+    pub fn contains(&self, epoch: u64, epoch_public_key: &AMFPublicKey, path: &[[u8; 32]]) -> bool {
+        if epoch >= self.epochs || epoch_public_key.role != AMFRole::Sender {
+            return false;
+        }
+        // The path has one hash per level below the root
+        let depth = (u64::BITS - (self.epochs - 1).leading_zeros()) as usize;
+        if path.len() != depth {
+            return false;
+        }
+        let mut index = epoch;
+        let mut hash = leaf(epoch_public_key);
+        for sibling in path {
+            hash = if index.is_multiple_of(2) {
+                node(&hash, sibling)
+            } else {
+                node(sibling, &hash)
+            };
+            index /= 2;
+        }
+        hash == self.root
+    }
+}
+
+#[cfg(any(feature = "rand", test))]
+/// `frank_forward_secure_with_rng` with `rand::thread_rng`
+pub fn frank_forward_secure(
+    sender_secret_key: &ForwardSecureSecretKey,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    message: &[u8],
+) -> ForwardSecureSignature {
+    frank_forward_secure_with_rng(
+        &mut rand::thread_rng(),
+        sender_secret_key,
+        recipient_public_key,
+        judge_public_key,
+        message,
+    )
+}
+
+/// Franks `message` with the key of the current epoch of `sender_secret_key`.
+pub fn frank_forward_secure_with_rng(
+    rng: &mut dyn CryptoRngCore,
+    sender_secret_key: &ForwardSecureSecretKey,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    message: &[u8],
+) -> ForwardSecureSignature {
+    let epoch = sender_secret_key.epoch;
+    let (epoch_public_key, epoch_secret_key) = epoch_key_pair(&sender_secret_key.seed);
+    let amf_signature = frank_with_rng(
+        rng,
+        epoch_secret_key,
+        epoch_public_key,
+        recipient_public_key,
+        judge_public_key,
+        &bind_epoch(epoch, message),
+    );
+    ForwardSecureSignature {
+        epoch,
+        epoch_public_key,
+        path: sender_secret_key.path(),
+        amf_signature,
+    }
+}
+
+/// `amf::verify` of a forward-secure signature: the key of its epoch belongs to
+/// `sender_public_key`, and its AMF signature verifies for the message bound to the epoch.
+pub fn verify_forward_secure(
+    recipient_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: &ForwardSecurePublicKey,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    message: &[u8],
+    signature: &ForwardSecureSignature,
+) -> bool {
+    let valid = verify(
+        recipient_secret_key,
+        signature.epoch_public_key,
+        recipient_public_key,
+        judge_public_key,
+        &bind_epoch(signature.epoch, message),
+        signature.amf_signature,
+    );
+    // The path is public, so checking it apart from the AMF signature leaks nothing
+    sender_public_key.contains(
+        signature.epoch,
+        &signature.epoch_public_key,
+        &signature.path,
+    ) & valid
+}
+
+/// Like `verify_forward_secure`, with the judge's secret key.
+pub fn judge_forward_secure(
+    judge_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: &ForwardSecurePublicKey,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    message: &[u8],
+    signature: &ForwardSecureSignature,
+) -> bool {
+    let valid = judge(
+        judge_secret_key,
+        signature.epoch_public_key,
+        recipient_public_key,
+        judge_public_key,
+        &bind_epoch(signature.epoch, message),
+        signature.amf_signature,
+    );
+    sender_public_key.contains(
+        signature.epoch,
+        &signature.epoch_public_key,
+        &signature.path,
+    ) & valid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amf::keygen;
+
+    #[test]
+    fn test_forward_secure_franking() {
+        // 0. Generate a key of 5 epochs, and the recipient's and the judge's keys
+        let (sender_public_key, mut sender_secret_key) = keygen_forward_secure(5).unwrap();
+        let (recipient_public_key, recipient_secret_key) = keygen(AMFRole::Recipient);
+        let (judge_public_key, judge_secret_key) = keygen(AMFRole::Judge);
+        let message = b"hello world!";
+
+        // 1. Signatures of every epoch verify and judge
+        let mut signatures = Vec::new();
+        for epoch in 0..5 {
+            sender_secret_key.evolve_key(epoch).unwrap();
+            assert_eq!(sender_secret_key.epoch(), epoch);
+            let signature = frank_forward_secure(
+                &sender_secret_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+            );
+            assert_eq!(signature.epoch, epoch);
+            assert!(verify_forward_secure(
+                &recipient_secret_key,
+                &sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+                &signature,
+            ));
+            assert!(judge_forward_secure(
+                &judge_secret_key,
+                &sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+                &signature,
+            ));
+            signatures.push(signature);
+        }
+
+        // 2. A signature does not verify for another epoch, key or message
+        let mut other_epoch = signatures[3].clone();
+        other_epoch.epoch = 1;
+        other_epoch.path = signatures[1].path.clone();
+        let mut other_key = signatures[3].clone();
+        other_key.epoch_public_key = signatures[1].epoch_public_key;
+        for (message, signature) in [
+            (message.as_slice(), &other_epoch),
+            (message.as_slice(), &other_key),
+            (b"goodbye world!".as_slice(), &signatures[3]),
+        ] {
+            assert!(!verify_forward_secure(
+                &recipient_secret_key,
+                &sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+                signature,
+            ));
+        }
+
+        // 3. Another sender's key of the same epoch is not in the tree
+        let (other_public_key, _) = keygen_forward_secure(5).unwrap();
+        assert!(!verify_forward_secure(
+            &recipient_secret_key,
+            &other_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            &signatures[0],
+        ));
+    }
+
+    #[test]
+    fn test_evolve_key() {
+        // 0. Keys evolve forward only, within their epochs
+        let (_, mut secret_key) = keygen_forward_secure(3).unwrap();
+        secret_key.evolve_key(2).unwrap();
+        assert_eq!(
+            secret_key.evolve_key(1),
+            Err(ForwardSecureError::EpochInPast {
+                epoch: 1,
+                current: 2
+            })
+        );
+        assert_eq!(
+            secret_key.evolve_key(3),
+            Err(ForwardSecureError::EpochOutOfRange {
+                epoch: 3,
+                epochs: 3
+            })
+        );
+        assert_eq!(
+            keygen_forward_secure(0).unwrap_err(),
+            ForwardSecureError::InvalidEpochs(0)
+        );
+
+        // 1. An evolved key derives the same epoch keys as one evolved step by step
+        let (_, mut stepwise) = keygen_forward_secure(4).unwrap();
+        let mut direct = ForwardSecureSecretKey {
+            seed: stepwise.seed,
+            epoch: stepwise.epoch,
+            leaves: stepwise.leaves.clone(),
+        };
+        for epoch in 1..4 {
+            stepwise.evolve_key(epoch).unwrap();
+        }
+        direct.evolve_key(3).unwrap();
+        assert_eq!(
+            epoch_key_pair(&stepwise.seed).0,
+            epoch_key_pair(&direct.seed).0
+        );
+
+        // 2. A single-epoch key has an empty path
+        let (public_key, secret_key) = keygen_forward_secure(1).unwrap();
+        let signature = frank_forward_secure(
+            &secret_key,
+            keygen(AMFRole::Recipient).0,
+            keygen(AMFRole::Judge).0,
+            b"",
+        );
+        assert!(signature.path.is_empty());
+        assert!(public_key.contains(0, &signature.epoch_public_key, &signature.path));
+    }
+}
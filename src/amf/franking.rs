@@ -5,42 +5,116 @@
 //! [AMF]: https://eprint.iacr.org/2019/565/20190527:092413
 #![allow(non_snake_case)]
 
-use curve25519_dalek::{
-    constants::RISTRETTO_BASEPOINT_TABLE,
-    ristretto::{RistrettoBasepointTable, RistrettoPoint},
-    scalar::Scalar,
-};
+use alloc::vec::Vec;
+use core::{borrow::Borrow, fmt};
+
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar, traits::Identity};
+use rand_core::CryptoRngCore;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use subtle::{Choice, ConstantTimeEq};
+use zeroize::Zeroize;
 
+use crate::error::{check_role, Error};
+#[cfg(any(feature = "rand", test))]
+use crate::pok::batch::BatchVerifier;
 use crate::pok::{
     chaum_pedersen::ChaumPedersenProverCommitment,
-    fiat_shamir::{FiatShamirSecretKey, FiatShamirSignature, SignatureScheme},
+    domain::{tag, CONTEXT_LABEL},
+    elgamal::{ElGamalCiphertext, VerifiableElGamal},
+    fiat_shamir::{FiatShamirSignature, FiatShamirVersion, SignatureScheme},
     or_proof::{OrProverCommitment, OrProverResponse, OrWitness},
 };
 
-use super::spok_amf::AMFSPoK;
+use super::{
+    spok_amf::{AMFSPoK, AMFStatement},
+    trace,
+};
 
-#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum AMFRole {
     Sender,
     Recipient,
     Judge,
 }
 
+impl ConstantTimeEq for AMFRole {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        (*self as u8).ct_eq(&(*other as u8))
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct AMFPublicKey {
     pub role: AMFRole,
     pub public_key: RistrettoPoint,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+impl ConstantTimeEq for AMFPublicKey {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.role.ct_eq(&other.role) & self.public_key.ct_eq(&other.public_key)
+    }
+}
+
+/// With the `zeroize` feature, the secret scalar is overwritten when the key is dropped, which is
+/// why the key is `Clone` but not `Copy`.
+#[derive(Clone)]
 pub struct AMFSecretKey {
     pub role: AMFRole,
     pub secret_key: Scalar,
 }
 
-/// The internal Fiat-Shamir signature used in AMF, denoted by pi in Fig. 5 of [AMF].
-pub(crate) type AMFInternalSignature = FiatShamirSignature<
+/// Compares the role and the secret scalar without branching on either.
+impl ConstantTimeEq for AMFSecretKey {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.role.ct_eq(&other.role) & self.secret_key.ct_eq(&other.secret_key)
+    }
+}
+
+/// `ct_eq`, so that comparing secret keys takes as long wherever they differ
+impl PartialEq for AMFSecretKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl Eq for AMFSecretKey {}
+
+/// Prints the role only, so that keys cannot leak through logs.
+impl fmt::Debug for AMFSecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AMFSecretKey")
+            .field("role", &self.role)
+            .field("secret_key", &format_args!("<redacted>"))
+            .finish()
+    }
+}
+
+/// Overwrites the secret scalar; the role is public.
+#[cfg(feature = "zeroize")]
+impl Zeroize for AMFSecretKey {
+    fn zeroize(&mut self) {
+        self.secret_key.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for AMFSecretKey {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for AMFSecretKey {}
+
+/// The internal Fiat-Shamir signature used in AMF, denoted by pi in Fig. 5 of [AMF]: the
+/// commitments and responses of `AND(OR(Schnorr, Schnorr), OR(Chaum-Pedersen, Schnorr))`, with
+/// the statement of `AMFSignature::statement`. Build one from decoded parts with
+/// `OrProverResponse::new` and `ChaumPedersenProverCommitment::new`, and check it with
+/// `AMFSPoK::from_statement`.
+pub type AMFInternalSignature = FiatShamirSignature<
     (
         OrProverCommitment<RistrettoPoint, RistrettoPoint>,
         OrProverCommitment<ChaumPedersenProverCommitment, RistrettoPoint>,
@@ -61,35 +135,141 @@ pub struct AMFSignature {
     pub E_R: RistrettoPoint,
 }
 
+/// Compares the wire encodings, which are canonical, without branching on where they differ.
+impl ConstantTimeEq for AMFSignature {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.to_bytes()
+            .as_slice()
+            .ct_eq(other.to_bytes().as_slice())
+    }
+}
+
+impl AMFSignature {
+    /// (E_J, J), the encryption of the identity under the judge's public key
+    pub fn judge_ciphertext(&self) -> ElGamalCiphertext {
+        ElGamalCiphertext {
+            c1: self.E_J,
+            c2: self.J,
+        }
+    }
+
+    /// (E_R, R), the encryption of the identity under the recipient's public key
+    pub fn recipient_ciphertext(&self) -> ElGamalCiphertext {
+        ElGamalCiphertext {
+            c1: self.E_R,
+            c2: self.R,
+        }
+    }
+
+    /// The statement that pi proves, for the keys the signature claims to be franked with.
+    pub fn statement(
+        &self,
+        sender_public_key: &AMFPublicKey,
+        judge_public_key: &AMFPublicKey,
+    ) -> AMFStatement {
+        AMFStatement {
+            sender_public_key: sender_public_key.public_key,
+            judge_public_key: judge_public_key.public_key,
+            J: self.J,
+            R: self.R,
+            E_J: self.E_J,
+        }
+    }
+
+    /// Checks that `statement`, e.g. one that came with pi from a third party, is the statement of
+    /// this signature for the given keys, and that it is valid, cf. `AMFStatement::validate`. Pi
+    /// does not carry its statement, so a proof checked against another statement fails without
+    /// telling why; this tells.
+    pub fn check_statement(
+        &self,
+        sender_public_key: &AMFPublicKey,
+        judge_public_key: &AMFPublicKey,
+        statement: &AMFStatement,
+    ) -> Result<(), VerifyError> {
+        if *statement != self.statement(sender_public_key, judge_public_key) {
+            return Err(VerifyError::InconsistentStatement);
+        }
+        statement.validate()
+    }
+}
+
+#[cfg(any(feature = "rand", test))]
 pub fn keygen(role: AMFRole) -> (AMFPublicKey, AMFSecretKey) {
+    keygen_with_rng(&mut rand::thread_rng(), role)
+}
+
+/// `keygen` with the randomness of `rng`, e.g. where `rand::thread_rng` is unavailable
+pub fn keygen_with_rng(rng: &mut dyn CryptoRngCore, role: AMFRole) -> (AMFPublicKey, AMFSecretKey) {
+    let _span = trace::keygen(role);
     // cf. Fig. 5 in [AMF]
-    let mut rng = rand::thread_rng();
-    let g = RistrettoBasepointTable::basepoint(&RISTRETTO_BASEPOINT_TABLE);
-    let secret_key = Scalar::random(&mut rng);
-    let public_key = secret_key * g;
+    let secret_key = Scalar::random(rng);
+    let public_key = RistrettoPoint::mul_base(&secret_key);
     (
         AMFPublicKey { role, public_key },
         AMFSecretKey { role, secret_key },
     )
 }
 
+#[cfg(any(feature = "rand", test))]
+/// Keys can be passed by value or by reference, as can the signatures of `verify` and `judge`.
 pub fn frank(
-    sender_secret_key: AMFSecretKey,
-    sender_public_key: AMFPublicKey,
-    recipient_public_key: AMFPublicKey,
-    judge_public_key: AMFPublicKey,
+    sender_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
     message: &[u8],
 ) -> AMFSignature {
-    let mut rng = rand::thread_rng();
-    let g = RistrettoBasepointTable::basepoint(&RISTRETTO_BASEPOINT_TABLE);
-    // cf. Fig. 5 in [AMF]
-    let alpha = Scalar::random(&mut rng);
-    let beta = Scalar::random(&mut rng);
+    frank_with_rng(
+        &mut rand::thread_rng(),
+        sender_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        message,
+    )
+}
+
+/// `frank` with the randomness of `rng`, e.g. where `rand::thread_rng` is unavailable
+pub fn frank_with_rng(
+    rng: &mut dyn CryptoRngCore,
+    sender_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    message: &[u8],
+) -> AMFSignature {
+    frank_with_version(
+        rng,
+        FiatShamirVersion::default(),
+        sender_secret_key.borrow(),
+        sender_public_key.borrow(),
+        recipient_public_key.borrow(),
+        judge_public_key.borrow(),
+        message,
+    )
+}
+
+/// `frank_with_rng` with the challenge hash of `version`; only the current version is franked
+/// outside of tests, cf. `versioned`
+pub(super) fn frank_with_version(
+    rng: &mut dyn CryptoRngCore,
+    version: FiatShamirVersion,
+    sender_secret_key: &AMFSecretKey,
+    sender_public_key: &AMFPublicKey,
+    recipient_public_key: &AMFPublicKey,
+    judge_public_key: &AMFPublicKey,
+    message: &[u8],
+) -> AMFSignature {
+    let _span = trace::frank(message.len());
+    // cf. Fig. 5 in [AMF]: (E_J, J) and (E_R, R) are encryptions of the identity under the
+    // judge's and the recipient's public key
+    let (judge_ciphertext, mut alpha) = VerifiableElGamal::new(judge_public_key.public_key)
+        .encrypt(rng, RistrettoPoint::identity());
+    let (recipient_ciphertext, mut beta) = VerifiableElGamal::new(recipient_public_key.public_key)
+        .encrypt(rng, RistrettoPoint::identity());
 
-    let J = alpha * judge_public_key.public_key;
-    let R = beta * recipient_public_key.public_key;
-    let E_J = alpha * g;
-    let E_R = beta * g;
+    let (E_J, J) = (judge_ciphertext.c1, judge_ciphertext.c2);
+    let (E_R, R) = (recipient_ciphertext.c1, recipient_ciphertext.c2);
 
     let mut spok = AMFSPoK::new(
         sender_public_key.public_key,
@@ -98,74 +278,797 @@ pub fn frank(
         R,
         E_J,
     );
-    let pi = spok.sign(
-        FiatShamirSecretKey {
-            witness: (
-                OrWitness {
-                    b: false,
-                    s0_witness: Some(sender_secret_key.secret_key),
-                    s1_witness: None,
-                },
-                OrWitness {
-                    b: false,
-                    s0_witness: Some(alpha),
-                    s1_witness: None,
-                },
-            ),
-        },
+    spok.version = version;
+    let pi = spok.sign_witness(
+        rng,
+        (
+            OrWitness::Left(sender_secret_key.secret_key),
+            OrWitness::Left(alpha),
+        ),
         message,
     );
+    // The prover states were cleared with the response; clear the encryption randomness too,
+    // which opens the ciphertexts
+    alpha.zeroize();
+    beta.zeroize();
+    let pi = pi.expect("the AMF witness always fits the AMF statement");
+    trace::franked();
     AMFSignature { pi, J, R, E_J, E_R }
 }
 
+/// Checks that the recipient's ciphertext encrypts the identity (b1) and that the proof pi
+/// verifies (b2), cf. Fig. 5 in [AMF], that none of J, R, E_J and E_R is the identity (b0), cf.
+/// `AMFSignature::validate`, and that the statement of pi is valid and the recipient's secret key
+/// is the one of `recipient_public_key` (b3), cf. `AMFSignature::check_statement`. All checks
+/// always run and are combined in constant time, so the time taken does not tell a forger which
+/// of them failed; `try_verify` tells degenerate signatures apart.
 pub fn verify(
-    recipient_secret_key: AMFSecretKey,
-    sender_public_key: AMFPublicKey,
-    _recipient_public_key: AMFPublicKey,
-    judge_public_key: AMFPublicKey,
+    recipient_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
     message: &[u8],
-    amf_signature: AMFSignature,
+    amf_signature: impl Borrow<AMFSignature>,
 ) -> bool {
-    let b1 = amf_signature.R == recipient_secret_key.secret_key * amf_signature.E_R;
+    let _span = trace::verify(AMFRole::Recipient, message.len());
+    let amf_signature = amf_signature.borrow();
+    verify_with(
+        &recipient_secret_key.borrow().secret_key,
+        recipient_public_key.borrow(),
+        amf_signature.recipient_ciphertext(),
+        sender_public_key.borrow(),
+        judge_public_key.borrow(),
+        message,
+        amf_signature,
+        FiatShamirVersion::default(),
+    )
+    .into()
+}
 
-    let spok = AMFSPoK::new(
-        sender_public_key.public_key,
-        judge_public_key.public_key,
-        amf_signature.J,
-        amf_signature.R,
-        amf_signature.E_J,
-    );
-    let b2 = spok.verify(message, amf_signature.pi);
+/// Like `verify`, with the judge's ciphertext and keys.
+pub fn judge(
+    judge_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    _recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    message: &[u8],
+    amf_signature: impl Borrow<AMFSignature>,
+) -> bool {
+    let _span = trace::verify(AMFRole::Judge, message.len());
+    let amf_signature = amf_signature.borrow();
+    let judge_public_key = judge_public_key.borrow();
+    verify_with(
+        &judge_secret_key.borrow().secret_key,
+        judge_public_key,
+        amf_signature.judge_ciphertext(),
+        sender_public_key.borrow(),
+        judge_public_key,
+        message,
+        amf_signature,
+        FiatShamirVersion::default(),
+    )
+    .into()
+}
+
+/// checks b0, b1 with `ciphertext` and `secret_key`, b2 with the challenge hash of `version`, and
+/// b3, cf. `verify`
+#[allow(clippy::too_many_arguments)]
+pub(super) fn verify_with(
+    secret_key: &Scalar,
+    public_key: &AMFPublicKey,
+    ciphertext: ElGamalCiphertext,
+    sender_public_key: &AMFPublicKey,
+    judge_public_key: &AMFPublicKey,
+    message: &[u8],
+    amf_signature: &AMFSignature,
+    version: FiatShamirVersion,
+) -> Choice {
+    let b0 = amf_signature.components_valid();
+    let b1 = VerifiableElGamal::decrypt(secret_key, &ciphertext).ct_eq(&RistrettoPoint::identity());
+
+    let statement = amf_signature.statement(sender_public_key, judge_public_key);
+    let mut spok = AMFSPoK::from_statement(&statement);
+    spok.version = version;
+    let b2 = Choice::from(u8::from(spok.verify(message, amf_signature.pi)));
+
+    let b3 = keys_valid(secret_key, public_key, &statement);
 
-    b1 && b2
+    let valid = b0 & b1 & b2 & b3;
+    trace::verified(valid.into());
+    valid
 }
 
-pub fn judge(
-    judge_secret_key: AMFSecretKey,
-    sender_public_key: AMFPublicKey,
-    _recipient_public_key: AMFPublicKey,
-    judge_public_key: AMFPublicKey,
+/// b3 of `verify`: the statement is valid, cf. `AMFStatement::validate`, and `secret_key` is the
+/// one of `public_key`, the key of the role that checks
+pub(crate) fn keys_valid(
+    secret_key: &Scalar,
+    public_key: &AMFPublicKey,
+    statement: &AMFStatement,
+) -> Choice {
+    statement.keys_valid() & RistrettoPoint::mul_base(secret_key).ct_eq(&public_key.public_key)
+}
+
+/// Why `try_verify` or `try_judge` rejected a signature, cf. `Error::Verify`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyError {
+    /// One of J, R, E_J and E_R is the identity, cf. `AMFSignature::validate`. Such a signature
+    /// can pass the other checks without the sender's key, so it is rejected before them.
+    DegenerateSignature,
+    /// The statement of pi, built from the given keys and the signature, is not the one the
+    /// signature is checked against, or the sender's or the judge's public key in it is the
+    /// identity, cf. `AMFSignature::check_statement`.
+    InconsistentStatement,
+    /// The secret key of the role is not the one of the given public key of the role, so its
+    /// ciphertext cannot be checked.
+    KeyMismatch(AMFRole),
+    /// The ciphertext does not encrypt the identity, or the proof pi does not verify; which one is
+    /// not told, cf. `verify`.
+    InvalidSignature,
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::DegenerateSignature => {
+                write!(f, "signature component is the identity point")
+            }
+            VerifyError::InconsistentStatement => {
+                write!(f, "statement does not match the keys and the signature")
+            }
+            VerifyError::KeyMismatch(role) => {
+                write!(f, "the {:?} secret key does not match its public key", role)
+            }
+            VerifyError::InvalidSignature => write!(f, "invalid signature"),
+        }
+    }
+}
+
+impl core::error::Error for VerifyError {}
+
+#[cfg(any(feature = "rand", test))]
+/// `frank`, but checks the roles of the keys first
+pub fn try_frank(
+    sender_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    message: &[u8],
+) -> Result<AMFSignature, Error> {
+    check_roles(
+        AMFRole::Sender,
+        sender_secret_key.borrow(),
+        [
+            sender_public_key.borrow(),
+            recipient_public_key.borrow(),
+            judge_public_key.borrow(),
+        ],
+    )?;
+    Ok(frank(
+        sender_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        message,
+    ))
+}
+
+/// `verify`, but checks the roles of the keys first, and tells degenerate signatures, invalid
+/// statements and mismatched keys apart from invalid signatures
+pub fn try_verify(
+    recipient_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    message: &[u8],
+    amf_signature: impl Borrow<AMFSignature>,
+) -> Result<(), Error> {
+    try_check(
+        AMFRole::Recipient,
+        recipient_secret_key.borrow(),
+        sender_public_key.borrow(),
+        recipient_public_key.borrow(),
+        judge_public_key.borrow(),
+        message,
+        amf_signature.borrow(),
+    )
+}
+
+/// `judge`, but checks the roles of the keys first, and tells degenerate signatures, invalid
+/// statements and mismatched keys apart from invalid signatures
+pub fn try_judge(
+    judge_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    message: &[u8],
+    amf_signature: impl Borrow<AMFSignature>,
+) -> Result<(), Error> {
+    try_check(
+        AMFRole::Judge,
+        judge_secret_key.borrow(),
+        sender_public_key.borrow(),
+        recipient_public_key.borrow(),
+        judge_public_key.borrow(),
+        message,
+        amf_signature.borrow(),
+    )
+}
+
+/// checks that `secret_key` is of `role`, and the public keys of the sender, the recipient and the
+/// judge, in this order
+fn check_roles(
+    role: AMFRole,
+    secret_key: &AMFSecretKey,
+    public_keys: [&AMFPublicKey; 3],
+) -> Result<(), Error> {
+    check_role(role, secret_key.role)?;
+    public_keys
+        .into_iter()
+        .zip([AMFRole::Sender, AMFRole::Recipient, AMFRole::Judge])
+        .try_for_each(|(public_key, role)| check_role(role, public_key.role))
+}
+
+/// the shared body of `try_verify` (`role` Recipient) and `try_judge` (`role` Judge)
+fn try_check(
+    role: AMFRole,
+    secret_key: &AMFSecretKey,
+    sender_public_key: &AMFPublicKey,
+    recipient_public_key: &AMFPublicKey,
+    judge_public_key: &AMFPublicKey,
+    message: &[u8],
+    amf_signature: &AMFSignature,
+) -> Result<(), Error> {
+    let algorithm = match role {
+        AMFRole::Judge => judge,
+        _ => verify,
+    };
+    let result = check_roles(
+        role,
+        secret_key,
+        [sender_public_key, recipient_public_key, judge_public_key],
+    )
+    .and_then(|()| {
+        let own_public_key = match role {
+            AMFRole::Judge => judge_public_key,
+            _ => recipient_public_key,
+        };
+        if amf_signature.validate().is_err() {
+            Err(VerifyError::DegenerateSignature.into())
+        } else if amf_signature
+            .statement(sender_public_key, judge_public_key)
+            .validate()
+            .is_err()
+        {
+            Err(VerifyError::InconsistentStatement.into())
+        } else if RistrettoPoint::mul_base(&secret_key.secret_key) != own_public_key.public_key {
+            Err(VerifyError::KeyMismatch(role).into())
+        } else if algorithm(
+            secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        ) {
+            Ok(())
+        } else {
+            Err(VerifyError::InvalidSignature.into())
+        }
+    });
+    if let Err(error) = &result {
+        trace::rejected(role, error);
+    }
+    result
+}
+
+/// The message that `frank_with_context`, `verify_with_context` and `judge_with_context` frank:
+/// `tag(CONTEXT_LABEL) || tag(context) || message`, with `tag` as in `pok::domain`. A frank bound
+/// to one context thus verifies neither in another context nor as a plain frank of `message`.
+pub fn bind_context(context: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut bound = Vec::with_capacity(16 + CONTEXT_LABEL.len() + context.len() + message.len());
+    tag(&mut bound, CONTEXT_LABEL);
+    tag(&mut bound, context);
+    bound.extend_from_slice(message);
+    bound
+}
+
+#[cfg(any(feature = "rand", test))]
+/// `frank` of `message` bound to `context`, e.g., the envelope or channel that carries it
+pub fn frank_with_context(
+    sender_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    context: &[u8],
+    message: &[u8],
+) -> AMFSignature {
+    frank(
+        sender_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        &bind_context(context, message),
+    )
+}
+
+/// `verify` of a `frank_with_context` in `context`
+pub fn verify_with_context(
+    recipient_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    context: &[u8],
+    message: &[u8],
+    amf_signature: impl Borrow<AMFSignature>,
+) -> bool {
+    verify(
+        recipient_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        &bind_context(context, message),
+        amf_signature,
+    )
+}
+
+/// `judge` of a `frank_with_context` in `context`
+pub fn judge_with_context(
+    judge_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    context: &[u8],
     message: &[u8],
-    amf_signature: AMFSignature,
+    amf_signature: impl Borrow<AMFSignature>,
 ) -> bool {
-    let b1 = amf_signature.J == judge_secret_key.secret_key * amf_signature.E_J;
+    judge(
+        judge_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        &bind_context(context, message),
+        amf_signature,
+    )
+}
 
-    let spok = AMFSPoK::new(
-        sender_public_key.public_key,
-        judge_public_key.public_key,
-        amf_signature.J,
-        amf_signature.R,
-        amf_signature.E_J,
-    );
-    let b2 = spok.verify(message, amf_signature.pi);
+/// A franked message with the keys of its sender, recipient and judge, for `batch_verify` and
+/// `batch_judge`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AMFBatchEntry<'a> {
+    pub sender_public_key: AMFPublicKey,
+    pub recipient_public_key: AMFPublicKey,
+    pub judge_public_key: AMFPublicKey,
+    pub message: &'a [u8],
+    pub amf_signature: AMFSignature,
+}
+
+#[cfg(any(feature = "rand", test))]
+/// Verifies many messages franked for the same recipient at once: the equations of all the proofs
+/// pi are checked in a single multiscalar multiplication, cf. `pok::batch`. Accepts only if
+/// `verify` accepts every entry, up to the negligible error of the random weights of the batch;
+/// after a rejection, `verify` tells which entries are bad.
+pub fn batch_verify(recipient_secret_key: AMFSecretKey, entries: &[AMFBatchEntry]) -> bool {
+    batch_verify_with(
+        &recipient_secret_key.secret_key,
+        |entry| &entry.recipient_public_key,
+        AMFSignature::recipient_ciphertext,
+        BatchVerifier::new(),
+        entries,
+    )
+}
 
-    b1 && b2
+#[cfg(any(feature = "rand", test))]
+/// Judges many reported messages at once, like `batch_verify` does for `verify`.
+pub fn batch_judge(judge_secret_key: AMFSecretKey, entries: &[AMFBatchEntry]) -> bool {
+    batch_verify_with(
+        &judge_secret_key.secret_key,
+        |entry| &entry.judge_public_key,
+        AMFSignature::judge_ciphertext,
+        BatchVerifier::new(),
+        entries,
+    )
+}
+
+#[cfg(any(feature = "rand", test))]
+/// `batch_verify` for entries that mostly share a few keys, e.g. many messages from one sender
+/// for one judge: the terms of `shared_keys` are merged across the batch, cf.
+/// `BatchVerifier::with_shared_points`, which saves two of the eleven terms per entry when all
+/// entries share the sender's and the judge's keys. Entries with other keys verify as usual.
+pub fn batch_verify_shared(
+    recipient_secret_key: AMFSecretKey,
+    shared_keys: &[AMFPublicKey],
+    entries: &[AMFBatchEntry],
+) -> bool {
+    batch_verify_with(
+        &recipient_secret_key.secret_key,
+        |entry| &entry.recipient_public_key,
+        AMFSignature::recipient_ciphertext,
+        shared_batch(shared_keys),
+        entries,
+    )
+}
+
+#[cfg(any(feature = "rand", test))]
+/// `batch_judge` for entries that mostly share a few keys, like `batch_verify_shared`; e.g. many
+/// reports against one sender.
+pub fn batch_judge_shared(
+    judge_secret_key: AMFSecretKey,
+    shared_keys: &[AMFPublicKey],
+    entries: &[AMFBatchEntry],
+) -> bool {
+    batch_verify_with(
+        &judge_secret_key.secret_key,
+        |entry| &entry.judge_public_key,
+        AMFSignature::judge_ciphertext,
+        shared_batch(shared_keys),
+        entries,
+    )
+}
+
+#[cfg(any(feature = "rand", test))]
+fn shared_batch(shared_keys: &[AMFPublicKey]) -> BatchVerifier {
+    BatchVerifier::with_shared_points(shared_keys.iter().map(|key| key.public_key))
+}
+
+#[cfg(any(feature = "rand", test))]
+/// checks that `ciphertext` of every signature encrypts the identity under `secret_key`, that the
+/// keys of every entry are valid and `secret_key` is the one of `public_key` of the entry, and
+/// batches the proofs into `batch`; like `verify`, every check runs even after one fails
+fn batch_verify_with(
+    secret_key: &Scalar,
+    public_key: for<'a> fn(&'a AMFBatchEntry) -> &'a AMFPublicKey,
+    ciphertext: fn(&AMFSignature) -> ElGamalCiphertext,
+    mut batch: BatchVerifier,
+    entries: &[AMFBatchEntry],
+) -> bool {
+    let mut valid = Choice::from(1);
+    for entry in entries {
+        let amf_signature = &entry.amf_signature;
+        let b0 = amf_signature.components_valid();
+        let b1 = VerifiableElGamal::decrypt(secret_key, &ciphertext(amf_signature))
+            .ct_eq(&RistrettoPoint::identity());
+
+        let statement = amf_signature.statement(&entry.sender_public_key, &entry.judge_public_key);
+        let spok = AMFSPoK::from_statement(&statement);
+        let b2 = Choice::from(u8::from(spok.batch_verify(
+            &mut batch,
+            entry.message,
+            amf_signature.pi,
+        )));
+
+        let b3 = keys_valid(secret_key, public_key(entry), &statement);
+
+        valid &= b0 & b1 & b2 & b3;
+    }
+    let proofs_valid = Choice::from(u8::from(batch.verify(&mut rand::thread_rng())));
+    (valid & proofs_valid).into()
 }
 
 #[cfg(test)]
 mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
     use super::*;
 
+    /// also runs on wasm32, with `wasm-pack test --node`
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test::wasm_bindgen_test)]
+    fn test_franking_with_rng() {
+        // 0. Keys from a seeded rng are reproducible
+        let mut rng = StdRng::seed_from_u64(0);
+        let (sender_public_key, sender_secret_key) = keygen_with_rng(&mut rng, AMFRole::Sender);
+        let (recipient_public_key, recipient_secret_key) =
+            keygen_with_rng(&mut rng, AMFRole::Recipient);
+        let (judge_public_key, judge_secret_key) = keygen_with_rng(&mut rng, AMFRole::Judge);
+        assert_eq!(
+            keygen_with_rng(&mut StdRng::seed_from_u64(0), AMFRole::Sender),
+            (sender_public_key, sender_secret_key.clone())
+        );
+
+        // 1. Frank with the same rng, then verify and judge
+        let message = b"hello world!";
+        let amf_signature = frank_with_rng(
+            &mut rng,
+            sender_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+        );
+        assert!(verify(
+            recipient_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        ));
+        assert!(judge(
+            judge_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        ));
+    }
+
+    #[test]
+    // without std, the Fiat-Shamir hash allocates a fresh buffer every time, cf. `pok::fiat_shamir`
+    #[cfg(feature = "std")]
+    fn test_franking_allocations() {
+        use curve25519_dalek::traits::VartimeMultiscalarMul;
+
+        use crate::test_allocator::allocations;
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let (sender_public_key, sender_secret_key) = keygen_with_rng(&mut rng, AMFRole::Sender);
+        let (recipient_public_key, recipient_secret_key) =
+            keygen_with_rng(&mut rng, AMFRole::Recipient);
+        let (judge_public_key, judge_secret_key) = keygen_with_rng(&mut rng, AMFRole::Judge);
+        let message = b"hello world!";
+        let frank = |rng: &mut StdRng| {
+            frank_with_rng(
+                rng,
+                sender_secret_key.clone(),
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+            )
+        };
+
+        // 0. Warm up the thread's buffers
+        let amf_signature = frank(&mut rng);
+        assert!(verify(
+            recipient_secret_key.clone(),
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        ));
+
+        // 1. Then franking does not allocate
+        let before = allocations();
+        let amf_signature = frank(&mut rng);
+        assert_eq!(allocations(), before);
+
+        // 2. Verifying and judging allocate only inside curve25519-dalek, for the tables of the
+        //    one multiscalar multiplication over two points other than the basepoint, in the
+        //    Chaum-Pedersen equation over the judge's key (cf. `amf::heapless` for none at all)
+        let before = allocations();
+        RistrettoPoint::vartime_multiscalar_mul(
+            [Scalar::ONE, Scalar::ONE],
+            [judge_public_key.public_key, amf_signature.J],
+        );
+        let multiscalar_mul_allocations = allocations() - before;
+        let before = allocations();
+        assert!(verify(
+            recipient_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        ));
+        assert_eq!(allocations() - before, multiscalar_mul_allocations);
+        let before = allocations();
+        assert!(judge(
+            judge_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        ));
+        assert_eq!(allocations() - before, multiscalar_mul_allocations);
+    }
+
+    #[test]
+    fn test_batch_franking() {
+        // 0. Frank messages from several senders to one recipient, for one judge
+        let (recipient_public_key, recipient_secret_key) = keygen(AMFRole::Recipient);
+        let (judge_public_key, judge_secret_key) = keygen(AMFRole::Judge);
+        let messages: [&[u8]; 4] = [b"hello world!", b"hi", b"", b"goodbye world!"];
+        let entries: Vec<AMFBatchEntry> = messages
+            .iter()
+            .map(|message| {
+                let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+                AMFBatchEntry {
+                    sender_public_key,
+                    recipient_public_key,
+                    judge_public_key,
+                    message,
+                    amf_signature: frank(
+                        sender_secret_key,
+                        sender_public_key,
+                        recipient_public_key,
+                        judge_public_key,
+                        message,
+                    ),
+                }
+            })
+            .collect();
+
+        // 1. The recipient verifies, and the judge judges, them all at once
+        assert!(batch_verify(recipient_secret_key.clone(), &entries));
+        assert!(batch_judge(judge_secret_key.clone(), &entries));
+        assert!(batch_verify(recipient_secret_key.clone(), &[]));
+
+        // 2. One entry with a wrong message fails the batch
+        let mut tampered = entries.clone();
+        tampered[2].message = b"tampered";
+        assert!(!batch_verify(recipient_secret_key.clone(), &tampered));
+        assert!(!batch_judge(judge_secret_key, &tampered));
+
+        // 3. So does one signature for another recipient, or another judge
+        let (_, other_secret_key) = keygen(AMFRole::Recipient);
+        assert!(!batch_verify(other_secret_key.clone(), &entries));
+        assert!(!batch_judge(other_secret_key, &entries));
+        let mut tampered = entries.clone();
+        tampered[1].judge_public_key = keygen(AMFRole::Judge).0;
+        assert!(!batch_verify(recipient_secret_key.clone(), &tampered));
+
+        // 4. So does an entry that `verify` rejects for its keys (b3): a frank under the identity
+        //    sender key, whose secret key 0 everyone knows, or an entry for another recipient key
+        let identity_secret_key = AMFSecretKey {
+            role: AMFRole::Sender,
+            secret_key: Scalar::ZERO,
+        };
+        let identity_public_key = AMFPublicKey {
+            role: AMFRole::Sender,
+            public_key: RistrettoPoint::identity(),
+        };
+        let mut tampered = entries.clone();
+        tampered[0].sender_public_key = identity_public_key;
+        tampered[0].amf_signature = frank(
+            identity_secret_key,
+            identity_public_key,
+            recipient_public_key,
+            judge_public_key,
+            tampered[0].message,
+        );
+        assert!(!verify(
+            &recipient_secret_key,
+            identity_public_key,
+            recipient_public_key,
+            judge_public_key,
+            tampered[0].message,
+            tampered[0].amf_signature,
+        ));
+        assert!(!batch_verify(recipient_secret_key.clone(), &tampered));
+        let mut tampered = entries;
+        tampered[3].recipient_public_key = keygen(AMFRole::Recipient).0;
+        assert!(!batch_verify(recipient_secret_key, &tampered));
+    }
+
+    #[test]
+    fn test_batch_franking_shared() {
+        // 0. Frank messages from one sender to one recipient, for one judge
+        let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+        let (recipient_public_key, recipient_secret_key) = keygen(AMFRole::Recipient);
+        let (judge_public_key, judge_secret_key) = keygen(AMFRole::Judge);
+        let messages: [&[u8]; 4] = [b"hello world!", b"hi", b"", b"goodbye world!"];
+        let mut entries: Vec<AMFBatchEntry> = messages
+            .iter()
+            .map(|message| AMFBatchEntry {
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+                amf_signature: frank(
+                    sender_secret_key.clone(),
+                    sender_public_key,
+                    recipient_public_key,
+                    judge_public_key,
+                    message,
+                ),
+            })
+            .collect();
+        let shared_keys = [sender_public_key, judge_public_key];
+
+        // 1. They verify and judge with the shared keys merged, also next to another sender's
+        assert!(batch_verify_shared(
+            recipient_secret_key.clone(),
+            &shared_keys,
+            &entries
+        ));
+        assert!(batch_judge_shared(
+            judge_secret_key.clone(),
+            &shared_keys,
+            &entries
+        ));
+        let (other_public_key, other_secret_key) = keygen(AMFRole::Sender);
+        entries.push(AMFBatchEntry {
+            sender_public_key: other_public_key,
+            amf_signature: frank(
+                other_secret_key,
+                other_public_key,
+                recipient_public_key,
+                judge_public_key,
+                b"hello world!",
+            ),
+            ..entries[0]
+        });
+        assert!(batch_judge_shared(
+            judge_secret_key.clone(),
+            &shared_keys,
+            &entries
+        ));
+
+        // 2. One wrong message, or a signature attributed to the other sender, fails the batch
+        let mut tampered = entries.clone();
+        tampered[2].message = b"tampered";
+        assert!(!batch_judge_shared(
+            judge_secret_key,
+            &shared_keys,
+            &tampered
+        ));
+        let mut tampered = entries.clone();
+        tampered[1].sender_public_key = other_public_key;
+        assert!(!batch_verify_shared(
+            recipient_secret_key,
+            &shared_keys,
+            &tampered
+        ));
+    }
+
+    #[test]
+    fn test_franking_with_context() {
+        // 0. Frank a message bound to a context
+        let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+        let (recipient_public_key, recipient_secret_key) = keygen(AMFRole::Recipient);
+        let (judge_public_key, judge_secret_key) = keygen(AMFRole::Judge);
+        let message = b"hello world!";
+        let amf_signature = frank_with_context(
+            sender_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            b"context",
+            message,
+        );
+
+        // 1. It verifies and judges in that context only
+        let verify_in = |context: &[u8]| {
+            verify_with_context(
+                &recipient_secret_key,
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                context,
+                message,
+                amf_signature,
+            )
+        };
+        assert!(verify_in(b"context"));
+        assert!(!verify_in(b"other context"));
+        assert!(!verify_in(b""));
+        assert!(judge_with_context(
+            judge_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            b"context",
+            message,
+            amf_signature,
+        ));
+
+        // 2. It is not a plain frank of the message
+        assert!(!verify(
+            recipient_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        ));
+
+        // 3. Length prefixes keep the context and the message apart
+        assert_ne!(bind_context(b"ab", b"c"), bind_context(b"a", b"bc"));
+    }
+
     #[test]
     fn test_franking() {
         // 0. Initialize a Sender
@@ -209,4 +1112,488 @@ mod tests {
         );
         assert!(judging_result);
     }
+    #[test]
+    fn test_degenerate_signatures() {
+        let mut rng = rand::thread_rng();
+        let (sender_public_key, _) = keygen(AMFRole::Sender);
+        let (recipient_public_key, recipient_secret_key) = keygen(AMFRole::Recipient);
+        let (judge_public_key, judge_secret_key) = keygen(AMFRole::Judge);
+        let message = b"hello world!";
+
+        // 0. Without the sender's key, forge a signature with R = E_R = 1, so that R opens to the
+        //    identity under every recipient key, and the proof of R = g^0 is trivial
+        let u = Scalar::random(&mut rng);
+        let J = RistrettoPoint::mul_base(&u);
+        let R = RistrettoPoint::identity();
+        let E_J = RistrettoPoint::random(&mut rng);
+        let mut spok = AMFSPoK::new(
+            sender_public_key.public_key,
+            judge_public_key.public_key,
+            J,
+            R,
+            E_J,
+        );
+        let pi = spok
+            .sign_witness(
+                &mut rng,
+                (OrWitness::Right(u), OrWitness::Right(Scalar::ZERO)),
+                message,
+            )
+            .unwrap();
+        let forgery = AMFSignature {
+            pi,
+            J,
+            R,
+            E_J,
+            E_R: RistrettoPoint::identity(),
+        };
+        assert!(spok.verify(message, forgery.pi));
+
+        // 1. verify rejects it, and try_verify tells why
+        assert!(!verify(
+            recipient_secret_key.clone(),
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            forgery,
+        ));
+        assert!(matches!(
+            try_verify(
+                recipient_secret_key.clone(),
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+                forgery,
+            ),
+            Err(Error::Verify(VerifyError::DegenerateSignature))
+        ));
+        assert!(!batch_verify(
+            recipient_secret_key.clone(),
+            &[AMFBatchEntry {
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+                amf_signature: forgery,
+            }],
+        ));
+
+        // 2. Honest signatures pass, and other failures are reported as invalid
+        let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+        let amf_signature = frank(
+            sender_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+        );
+        assert!(matches!(
+            try_verify(
+                recipient_secret_key,
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+                amf_signature,
+            ),
+            Ok(())
+        ));
+        assert!(matches!(
+            try_judge(
+                judge_secret_key,
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                b"goodbye world!",
+                amf_signature,
+            ),
+            Err(Error::Verify(VerifyError::InvalidSignature))
+        ));
+    }
+
+    #[test]
+    fn test_try_checks_roles() {
+        let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+        let (recipient_public_key, recipient_secret_key) = keygen(AMFRole::Recipient);
+        let (judge_public_key, judge_secret_key) = keygen(AMFRole::Judge);
+        let message = b"hello world!";
+
+        // 0. Keys in the right places pass
+        let amf_signature = try_frank(
+            sender_secret_key.clone(),
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+        )
+        .unwrap();
+        assert!(try_judge(
+            judge_secret_key.clone(),
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        )
+        .is_ok());
+
+        // 1. A secret key of another role is reported, before the signature is checked
+        assert!(matches!(
+            try_verify(
+                judge_secret_key,
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+                amf_signature,
+            ),
+            Err(Error::RoleMismatch {
+                expected: AMFRole::Recipient,
+                actual: AMFRole::Judge,
+            })
+        ));
+
+        // 2. So are swapped public keys
+        assert!(matches!(
+            try_frank(
+                sender_secret_key,
+                sender_public_key,
+                judge_public_key,
+                recipient_public_key,
+                message,
+            ),
+            Err(Error::RoleMismatch {
+                expected: AMFRole::Recipient,
+                actual: AMFRole::Judge,
+            })
+        ));
+        let error = try_verify(
+            recipient_secret_key,
+            recipient_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "expected a Sender key, got a Recipient key"
+        );
+    }
+
+    #[test]
+    fn test_statement_consistency() {
+        let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+        let (recipient_public_key, recipient_secret_key) = keygen(AMFRole::Recipient);
+        let (judge_public_key, judge_secret_key) = keygen(AMFRole::Judge);
+        let message = b"hello world!";
+        let amf_signature = frank(
+            sender_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+        );
+
+        // 0. The statement of the signature for its keys is consistent, another one is not
+        let statement = amf_signature.statement(&sender_public_key, &judge_public_key);
+        assert_eq!(
+            amf_signature.check_statement(&sender_public_key, &judge_public_key, &statement),
+            Ok(())
+        );
+        let (other_public_key, _) = keygen(AMFRole::Sender);
+        assert_eq!(
+            amf_signature.check_statement(&other_public_key, &judge_public_key, &statement),
+            Err(VerifyError::InconsistentStatement)
+        );
+
+        // 1. An identity sender key is rejected before pi is checked
+        let identity_key = AMFPublicKey {
+            role: AMFRole::Sender,
+            public_key: RistrettoPoint::identity(),
+        };
+        let error = try_verify(
+            &recipient_secret_key,
+            identity_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        );
+        assert!(matches!(
+            error,
+            Err(Error::Verify(VerifyError::InconsistentStatement))
+        ));
+
+        // 2. A secret key that is not the one of its public key is reported, and fails verify
+        let (other_recipient_public_key, _) = keygen(AMFRole::Recipient);
+        assert!(matches!(
+            try_verify(
+                &recipient_secret_key,
+                sender_public_key,
+                other_recipient_public_key,
+                judge_public_key,
+                message,
+                amf_signature,
+            ),
+            Err(Error::Verify(VerifyError::KeyMismatch(AMFRole::Recipient)))
+        ));
+        assert!(!verify(
+            &recipient_secret_key,
+            sender_public_key,
+            other_recipient_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        ));
+        let (_, other_judge_secret_key) = keygen(AMFRole::Judge);
+        let error = try_judge(
+            &other_judge_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "the Judge secret key does not match its public key"
+        );
+        assert!(judge(
+            &judge_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        ));
+    }
+
+    #[test]
+    fn test_verify_runs_every_check() {
+        use crate::pok::profiling::{self, profile};
+
+        // 0. Frank a message, and fail b1 with another recipient's key, or b2 with another message
+        let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+        let (recipient_public_key, recipient_secret_key) = keygen(AMFRole::Recipient);
+        let (_, other_secret_key) = keygen(AMFRole::Recipient);
+        let (judge_public_key, _) = keygen(AMFRole::Judge);
+        let amf_signature = frank(
+            sender_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            b"hello world!",
+        );
+        let cases = [
+            (recipient_secret_key.clone(), &b"hello world!"[..], true),
+            (other_secret_key, b"hello world!", false),
+            (recipient_secret_key, b"goodbye world!", false),
+        ];
+
+        // 1. Rejections take the same group operations as acceptance, alone or in a batch
+        let mut verify_counts = Vec::new();
+        let mut batch_counts = Vec::new();
+        for (secret_key, message, expected) in cases {
+            let (verified, counts) = profile(|| {
+                verify(
+                    secret_key.clone(),
+                    sender_public_key,
+                    recipient_public_key,
+                    judge_public_key,
+                    message,
+                    amf_signature,
+                )
+            });
+            assert_eq!(verified, expected);
+            verify_counts.push(counts);
+            let entry = AMFBatchEntry {
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+                amf_signature,
+            };
+            let (verified, counts) = profile(|| batch_verify(secret_key, &[entry, entry]));
+            assert_eq!(verified, expected);
+            batch_counts.push(counts);
+        }
+        if profiling::ENABLED {
+            assert!(verify_counts
+                .iter()
+                .all(|counts| *counts == verify_counts[0]));
+            assert!(batch_counts.iter().all(|counts| *counts == batch_counts[0]));
+        }
+    }
+
+    #[test]
+    fn test_secret_key_debug_redacted() {
+        let (_, secret_key) = keygen(AMFRole::Judge);
+        let debug = format!("{:?}", secret_key);
+        assert_eq!(
+            debug,
+            "AMFSecretKey { role: Judge, secret_key: <redacted> }"
+        );
+        assert!(!debug.contains(&format!("{:?}", secret_key.secret_key)));
+    }
+
+    // borrows the public keys and signatures too, which are `Copy`, to test that they can be
+    #[allow(clippy::needless_borrows_for_generic_args)]
+    #[test]
+    fn test_reference_arguments() {
+        let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+        let (recipient_public_key, recipient_secret_key) = keygen(AMFRole::Recipient);
+        let (judge_public_key, judge_secret_key) = keygen(AMFRole::Judge);
+        let message = b"hello world!";
+
+        // 0. Keys and signatures can be borrowed, so that they are reused without clones
+        let amf_signature = frank(
+            &sender_secret_key,
+            &sender_public_key,
+            &recipient_public_key,
+            &judge_public_key,
+            message,
+        );
+        for _ in 0..2 {
+            assert!(verify(
+                &recipient_secret_key,
+                &sender_public_key,
+                &recipient_public_key,
+                &judge_public_key,
+                message,
+                &amf_signature,
+            ));
+            assert!(try_judge(
+                &judge_secret_key,
+                &sender_public_key,
+                &recipient_public_key,
+                &judge_public_key,
+                message,
+                &amf_signature,
+            )
+            .is_ok());
+        }
+
+        // 1. Owned and borrowed arguments mix
+        assert!(judge(
+            judge_secret_key,
+            &sender_public_key,
+            recipient_public_key,
+            &judge_public_key,
+            message,
+            amf_signature,
+        ));
+        assert!(try_frank(
+            sender_secret_key,
+            sender_public_key,
+            &recipient_public_key,
+            judge_public_key,
+            message,
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_ct_eq() {
+        let (public_key, secret_key) = keygen(AMFRole::Sender);
+        let (other_public_key, other_secret_key) = keygen(AMFRole::Sender);
+        let (judge_public_key, _) = keygen(AMFRole::Judge);
+
+        // 0. Keys equal themselves, and differ from other keys and from other roles
+        assert!(bool::from(secret_key.ct_eq(&secret_key.clone())));
+        assert!(!bool::from(secret_key.ct_eq(&other_secret_key)));
+        let relabeled = AMFSecretKey {
+            role: AMFRole::Judge,
+            secret_key: secret_key.secret_key,
+        };
+        assert!(!bool::from(secret_key.ct_eq(&relabeled)));
+        assert_ne!(secret_key, relabeled);
+        assert!(bool::from(public_key.ct_eq(&public_key)));
+        assert!(!bool::from(public_key.ct_eq(&other_public_key)));
+        assert!(!bool::from(public_key.ct_eq(&AMFPublicKey {
+            role: AMFRole::Judge,
+            ..public_key
+        })));
+
+        // 1. Signatures equal themselves, and differ from other franks of the same message
+        let message = b"hello world!";
+        let amf_signature = frank(
+            secret_key.clone(),
+            public_key,
+            other_public_key,
+            judge_public_key,
+            message,
+        );
+        let other_signature = frank(
+            secret_key,
+            public_key,
+            other_public_key,
+            judge_public_key,
+            message,
+        );
+        assert!(bool::from(amf_signature.ct_eq(&amf_signature)));
+        assert!(!bool::from(amf_signature.ct_eq(&other_signature)));
+        let mut tampered = amf_signature;
+        tampered.pi.prover_response.1.z_0 += Scalar::ONE;
+        assert!(!bool::from(amf_signature.ct_eq(&tampered)));
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn test_secret_key_zeroize() {
+        fn assert_zeroize_on_drop<T: zeroize::ZeroizeOnDrop>() {}
+        assert_zeroize_on_drop::<AMFSecretKey>();
+
+        // 0. Zeroizing a key clears its scalar, and keeps its role
+        let (_, mut secret_key) = keygen(AMFRole::Sender);
+        let copy = secret_key.clone();
+        secret_key.zeroize();
+        assert_eq!(secret_key.secret_key, Scalar::ZERO);
+        assert_eq!(secret_key.role, AMFRole::Sender);
+
+        // 1. Clones are independent
+        assert_ne!(copy.secret_key, Scalar::ZERO);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_spok_shared_across_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<AMFSPoK>();
+
+        // 0. Frank a message
+        let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+        let (recipient_public_key, _recipient_secret_key) = keygen(AMFRole::Recipient);
+        let (judge_public_key, _judge_secret_key) = keygen(AMFRole::Judge);
+        let message = b"hello world!";
+        let amf_signature = frank(
+            sender_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+        );
+
+        // 1. Share one verification context between threads
+        let spok = std::sync::Arc::new(AMFSPoK::new(
+            sender_public_key.public_key,
+            judge_public_key.public_key,
+            amf_signature.J,
+            amf_signature.R,
+            amf_signature.E_J,
+        ));
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let spok = spok.clone();
+                std::thread::spawn(move || spok.verify(message, amf_signature.pi))
+            })
+            .collect();
+        for handle in handles {
+            assert!(handle.join().unwrap());
+        }
+    }
 }
@@ -0,0 +1,573 @@
+//! AMF over Any Prime-Order Group
+//!
+//! `franking` implements Fig. 5 of [AMF] over Ristretto. This module implements the same KeyGen,
+//! Frank, Verify and Judge over any prime-order group of the `group` crate that implements
+//! `AMFGroup`, so that other curves can be plugged in, cf. `secp256k1` and `p256`. The sigma
+//! protocols of `pok` are specific to Ristretto, so the signature proof of knowledge is written out
+//! here: it is the composition of `spok_amf`, AND(OR(Schnorr, Schnorr), OR(Chaum-Pedersen,
+//! Schnorr)), with OR challenges split as c_1 = c_0 + c like `or_proof`. The Fiat-Shamir challenge
+//! follows the `V2` layout of `pok::domain`, with the protocol label wrapped in the group's label,
+//! so that a signature never verifies in another group.
+//!
+//! The wire format is the one of `codec`, under the group's suite identifier, with the group's own
+//! encodings of points and scalars (`GroupEncoding` and `PrimeField::to_repr`) in place of the
+//! 32-byte Ristretto ones:
+//! - public key: `role (1) || public_key`
+//! - secret key: `role (1) || secret_key`
+//! - signature: `J || R || E_J || E_R || t_sender || t_J || v_t || w_t || t_R
+//!               || c_0 || z_0 || z_1 (first OR) || c_0 || z_0 || z_1 (second OR)`
+//!
+//! [AMF]: https://eprint.iacr.org/2019/565/20190527:092413
+#![allow(non_snake_case)]
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use ff::{Field, PrimeField};
+use group::prime::PrimeGroup;
+use rand_core::CryptoRngCore;
+use sha2::{Digest, Sha512};
+use subtle::{Choice, ConstantTimeEq};
+
+use crate::pok::domain;
+
+use super::{
+    codec::{
+        role_from_byte, role_to_byte, CodecError, WireArtifactKind, WireHeader,
+        WIRE_FORMAT_VERSION, WIRE_HEADER_LENGTH,
+    },
+    AMFRole,
+};
+
+/// A prime-order group to run AMF in.
+pub trait AMFGroup: PrimeGroup + ConstantTimeEq {
+    /// names the group in Fiat-Shamir challenges, cf. `pok::domain`
+    const LABEL: &'static [u8];
+    /// identifies the group in the wire format, cf. `codec`
+    const SUITE: u8;
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AMFPublicKey<G: AMFGroup> {
+    pub role: AMFRole,
+    pub public_key: G,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub struct AMFSecretKey<G: AMFGroup> {
+    pub role: AMFRole,
+    pub secret_key: G::Scalar,
+}
+
+/// Prints the role only, like `amf::AMFSecretKey`.
+impl<G: AMFGroup> fmt::Debug for AMFSecretKey<G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AMFSecretKey")
+            .field("role", &self.role)
+            .field("secret_key", &format_args!("<redacted>"))
+            .finish()
+    }
+}
+
+/// The Fiat-Shamir proof of knowledge, denoted by pi in Fig. 5 of [AMF]: the commitments and
+/// responses of AND(OR(Schnorr, Schnorr), OR(Chaum-Pedersen, Schnorr)).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AMFProof<G: AMFGroup> {
+    pub(crate) t_sender: G,
+    pub(crate) t_J: G,
+    pub(crate) v_t: G,
+    pub(crate) w_t: G,
+    pub(crate) t_R: G,
+    pub(crate) sender_c_0: G::Scalar,
+    pub(crate) z_sender: G::Scalar,
+    pub(crate) z_J: G::Scalar,
+    pub(crate) judge_c_0: G::Scalar,
+    pub(crate) z_judge: G::Scalar,
+    pub(crate) z_R: G::Scalar,
+}
+
+/// The external AMF signature, denoted by sigma in Fig. 5 of [AMF].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AMFSignature<G: AMFGroup> {
+    pub pi: AMFProof<G>,
+    pub J: G,
+    pub R: G,
+    pub E_J: G,
+    pub E_R: G,
+}
+
+impl<G: AMFGroup> AMFSignature<G> {
+    /// none of J, R, E_J and E_R is the identity, as in `amf::AMFSignature::validate`
+    fn components_valid(&self) -> Choice {
+        !(self.J.is_identity()
+            | self.R.is_identity()
+            | self.E_J.is_identity()
+            | self.E_R.is_identity())
+    }
+}
+
+/// the public inputs of the proof: the sender's and judge's keys, J, R and E_J
+struct AMFStatement<G> {
+    sender_public_key: G,
+    judge_public_key: G,
+    J: G,
+    R: G,
+    E_J: G,
+}
+
+impl<G: AMFGroup> AMFStatement<G> {
+    fn new(
+        sender_public_key: &AMFPublicKey<G>,
+        judge_public_key: &AMFPublicKey<G>,
+        amf_signature: &AMFSignature<G>,
+    ) -> Self {
+        AMFStatement {
+            sender_public_key: sender_public_key.public_key,
+            judge_public_key: judge_public_key.public_key,
+            J: amf_signature.J,
+            R: amf_signature.R,
+            E_J: amf_signature.E_J,
+        }
+    }
+
+    /// the Fiat-Shamir challenge for `message` and the commitments of `pi`
+    fn challenge(&self, message: &[u8], pi: &AMFProof<G>) -> G::Scalar {
+        let amf_label = domain::composite_label(
+            domain::AND_LABEL,
+            [
+                domain::composite_label(
+                    domain::OR_LABEL,
+                    [
+                        domain::SCHNORR_LABEL.to_vec(),
+                        domain::SCHNORR_LABEL.to_vec(),
+                    ],
+                ),
+                domain::composite_label(
+                    domain::OR_LABEL,
+                    [
+                        domain::CHAUM_PEDERSEN_LABEL.to_vec(),
+                        domain::SCHNORR_LABEL.to_vec(),
+                    ],
+                ),
+            ],
+        );
+        let statement: Vec<u8> = [
+            self.sender_public_key,
+            self.judge_public_key,
+            self.J,
+            self.R,
+            self.E_J,
+        ]
+        .iter()
+        .flat_map(|point| point.to_bytes().as_ref().to_vec())
+        .collect();
+        let commitment: Vec<u8> = [pi.t_sender, pi.t_J, pi.v_t, pi.w_t, pi.t_R]
+            .iter()
+            .flat_map(|point| point.to_bytes().as_ref().to_vec())
+            .collect();
+
+        let mut hasher = Sha512::new();
+        domain::absorb(&mut hasher, domain::FIAT_SHAMIR_DOMAIN);
+        domain::absorb(&mut hasher, &domain::composite_label(G::LABEL, [amf_label]));
+        domain::absorb(&mut hasher, domain::STATEMENT_ROLE);
+        domain::absorb(&mut hasher, &statement);
+        domain::absorb(&mut hasher, domain::MESSAGE_ROLE);
+        domain::absorb(&mut hasher, message);
+        domain::absorb(&mut hasher, domain::COMMITMENT_ROLE);
+        domain::absorb(&mut hasher, &commitment);
+        hash_to_scalar(hasher)
+    }
+
+    /// checks the five equations of the proof, without stopping at the first that fails
+    fn verify(&self, message: &[u8], pi: &AMFProof<G>) -> Choice {
+        let g = G::generator();
+        let c = self.challenge(message, pi);
+        let sender_c_1 = pi.sender_c_0 + c;
+        let judge_c_1 = pi.judge_c_0 + c;
+
+        // Schnorr for sender_public_key = g^t, and for J = g^u
+        (g * pi.z_sender).ct_eq(&(pi.t_sender + self.sender_public_key * pi.sender_c_0))
+            & (g * pi.z_J).ct_eq(&(pi.t_J + self.J * sender_c_1))
+            // Chaum-Pedersen for E_J = g^v and J = judge_public_key^v
+            & (g * pi.z_judge).ct_eq(&(pi.v_t + self.E_J * pi.judge_c_0))
+            & (self.judge_public_key * pi.z_judge).ct_eq(&(pi.w_t + self.J * pi.judge_c_0))
+            // Schnorr for R = g^w
+            & (g * pi.z_R).ct_eq(&(pi.t_R + self.R * judge_c_1))
+    }
+}
+
+/// Reduces the hash to a scalar by rejection sampling, which needs nothing but `from_repr`: the
+/// hash is extended with a counter until its first bytes are a canonical scalar. For curves whose
+/// order is close to a power of two, such as secp256k1 and P-256, the first try almost always
+/// succeeds.
+pub(crate) fn hash_to_scalar<F: PrimeField>(hasher: Sha512) -> F {
+    for counter in 0..=u8::MAX {
+        let digest = hasher.clone().chain_update([counter]).finalize();
+        let mut repr = F::Repr::default();
+        let len = repr.as_ref().len();
+        repr.as_mut().copy_from_slice(&digest[..len]);
+        if let Some(scalar) = Option::from(F::from_repr(repr)) {
+            return scalar;
+        }
+    }
+    unreachable!("256 hashes in a row are not canonical scalars")
+}
+
+#[cfg(any(feature = "rand", test))]
+pub fn keygen<G: AMFGroup>(role: AMFRole) -> (AMFPublicKey<G>, AMFSecretKey<G>) {
+    keygen_with_rng(&mut rand::thread_rng(), role)
+}
+
+/// `keygen` with the randomness of `rng`, e.g. where `rand::thread_rng` is unavailable
+pub fn keygen_with_rng<G: AMFGroup>(
+    rng: &mut dyn CryptoRngCore,
+    role: AMFRole,
+) -> (AMFPublicKey<G>, AMFSecretKey<G>) {
+    // cf. Fig. 5 in [AMF]
+    let secret_key = G::Scalar::random(rng);
+    let public_key = G::generator() * secret_key;
+    (
+        AMFPublicKey { role, public_key },
+        AMFSecretKey { role, secret_key },
+    )
+}
+
+#[cfg(any(feature = "rand", test))]
+pub fn frank<G: AMFGroup>(
+    sender_secret_key: AMFSecretKey<G>,
+    sender_public_key: AMFPublicKey<G>,
+    recipient_public_key: AMFPublicKey<G>,
+    judge_public_key: AMFPublicKey<G>,
+    message: &[u8],
+) -> AMFSignature<G> {
+    frank_with_rng(
+        &mut rand::thread_rng(),
+        sender_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        message,
+    )
+}
+
+/// `frank` with the randomness of `rng`, e.g. where `rand::thread_rng` is unavailable
+pub fn frank_with_rng<G: AMFGroup>(
+    rng: &mut dyn CryptoRngCore,
+    sender_secret_key: AMFSecretKey<G>,
+    sender_public_key: AMFPublicKey<G>,
+    recipient_public_key: AMFPublicKey<G>,
+    judge_public_key: AMFPublicKey<G>,
+    message: &[u8],
+) -> AMFSignature<G> {
+    let g = G::generator();
+
+    // cf. Fig. 5 in [AMF]: (E_J, J) and (E_R, R) are encryptions of the identity under the
+    // judge's and the recipient's public key
+    let alpha = G::Scalar::random(&mut *rng);
+    let beta = G::Scalar::random(&mut *rng);
+    let (E_J, J) = (g * alpha, judge_public_key.public_key * alpha);
+    let (E_R, R) = (g * beta, recipient_public_key.public_key * beta);
+
+    // 0. Commit to the sender branch of the first OR, and simulate the J branch
+    let r_sender = G::Scalar::random(&mut *rng);
+    let t_sender = g * r_sender;
+    let (c_J, z_J) = (G::Scalar::random(&mut *rng), G::Scalar::random(&mut *rng));
+    let t_J = g * z_J - J * c_J;
+
+    // 1. Commit to the Chaum-Pedersen branch of the second OR, and simulate the R branch
+    let r_judge = G::Scalar::random(&mut *rng);
+    let (v_t, w_t) = (g * r_judge, judge_public_key.public_key * r_judge);
+    let (c_R, z_R) = (G::Scalar::random(&mut *rng), G::Scalar::random(&mut *rng));
+    let t_R = g * z_R - R * c_R;
+
+    // 2. Split the challenge so that c_1 = c_0 + c is the simulated one, and respond
+    let mut amf_signature = AMFSignature {
+        pi: AMFProof {
+            t_sender,
+            t_J,
+            v_t,
+            w_t,
+            t_R,
+            sender_c_0: G::Scalar::ZERO,
+            z_sender: G::Scalar::ZERO,
+            z_J,
+            judge_c_0: G::Scalar::ZERO,
+            z_judge: G::Scalar::ZERO,
+            z_R,
+        },
+        J,
+        R,
+        E_J,
+        E_R,
+    };
+    let c = AMFStatement::new(&sender_public_key, &judge_public_key, &amf_signature)
+        .challenge(message, &amf_signature.pi);
+    let pi = &mut amf_signature.pi;
+    pi.sender_c_0 = c_J - c;
+    pi.z_sender = r_sender + pi.sender_c_0 * sender_secret_key.secret_key;
+    pi.judge_c_0 = c_R - c;
+    pi.z_judge = r_judge + pi.judge_c_0 * alpha;
+    amf_signature
+}
+
+pub fn verify<G: AMFGroup>(
+    recipient_secret_key: AMFSecretKey<G>,
+    sender_public_key: AMFPublicKey<G>,
+    recipient_public_key: AMFPublicKey<G>,
+    judge_public_key: AMFPublicKey<G>,
+    message: &[u8],
+    amf_signature: AMFSignature<G>,
+) -> bool {
+    let b0 = amf_signature.components_valid();
+    let b1 = (amf_signature.R - amf_signature.E_R * recipient_secret_key.secret_key)
+        .ct_eq(&G::identity());
+    let b2 = AMFStatement::new(&sender_public_key, &judge_public_key, &amf_signature)
+        .verify(message, &amf_signature.pi);
+    let b3 = group_keys_valid(
+        &recipient_secret_key,
+        &recipient_public_key,
+        &sender_public_key,
+        &judge_public_key,
+    );
+
+    (b0 & b1 & b2 & b3).into()
+}
+
+pub fn judge<G: AMFGroup>(
+    judge_secret_key: AMFSecretKey<G>,
+    sender_public_key: AMFPublicKey<G>,
+    _recipient_public_key: AMFPublicKey<G>,
+    judge_public_key: AMFPublicKey<G>,
+    message: &[u8],
+    amf_signature: AMFSignature<G>,
+) -> bool {
+    let b0 = amf_signature.components_valid();
+    let b1 =
+        (amf_signature.J - amf_signature.E_J * judge_secret_key.secret_key).ct_eq(&G::identity());
+    let b2 = AMFStatement::new(&sender_public_key, &judge_public_key, &amf_signature)
+        .verify(message, &amf_signature.pi);
+    let b3 = group_keys_valid(
+        &judge_secret_key,
+        &judge_public_key,
+        &sender_public_key,
+        &judge_public_key,
+    );
+
+    (b0 & b1 & b2 & b3).into()
+}
+
+/// b3 of `amf::verify`: neither the sender's nor the judge's public key is the identity, and
+/// `secret_key` is the one of `public_key`, the key of the role that checks; `franking::keys_valid`
+/// over `G`
+fn group_keys_valid<G: AMFGroup>(
+    secret_key: &AMFSecretKey<G>,
+    public_key: &AMFPublicKey<G>,
+    sender_public_key: &AMFPublicKey<G>,
+    judge_public_key: &AMFPublicKey<G>,
+) -> Choice {
+    !(sender_public_key.public_key.is_identity() | judge_public_key.public_key.is_identity())
+        & (G::generator() * secret_key.secret_key).ct_eq(&public_key.public_key)
+}
+
+//
+// Versioned wire format.
+//
+
+fn point_length<G: AMFGroup>() -> usize {
+    G::Repr::default().as_ref().len()
+}
+
+fn scalar_length<G: AMFGroup>() -> usize {
+    <G::Scalar as PrimeField>::Repr::default().as_ref().len()
+}
+
+/// Writes the header of `codec` with the group's suite.
+fn write_header<G: AMFGroup>(buf: &mut Vec<u8>, kind: WireArtifactKind) {
+    WireHeader::write_suite(buf, G::SUITE, kind);
+}
+
+/// Reads fixed-size fields from a body, after checking the header and the body's length.
+struct GroupWireReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> GroupWireReader<'a> {
+    fn new<G: AMFGroup>(
+        bytes: &'a [u8],
+        kind: WireArtifactKind,
+        body_length: usize,
+    ) -> Result<Self, CodecError> {
+        let (header, body) = WireHeader::read(bytes, kind)?;
+        if header.version != WIRE_FORMAT_VERSION || header.suite != G::SUITE {
+            return Err(header.unsupported());
+        }
+        if body.len() != body_length {
+            return Err(CodecError::InvalidLength {
+                expected: WIRE_HEADER_LENGTH + body_length,
+                actual: bytes.len(),
+            });
+        }
+        Ok(GroupWireReader { bytes: body })
+    }
+
+    fn read_bytes(&mut self, len: usize) -> &'a [u8] {
+        let (field, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        field
+    }
+
+    fn read_role(&mut self) -> Result<AMFRole, CodecError> {
+        role_from_byte(self.read_bytes(1)[0])
+    }
+
+    fn read_point<G: AMFGroup>(&mut self) -> Result<G, CodecError> {
+        let mut repr = G::Repr::default();
+        repr.as_mut()
+            .copy_from_slice(self.read_bytes(point_length::<G>()));
+        Option::from(G::from_bytes(&repr)).ok_or(CodecError::InvalidPoint)
+    }
+
+    /// reads a point that must not be the identity, like `AMFSignature::validate` requires
+    fn read_non_identity_point<G: AMFGroup>(&mut self) -> Result<G, CodecError> {
+        let point: G = self.read_point()?;
+        if bool::from(point.is_identity()) {
+            return Err(CodecError::IdentityPoint);
+        }
+        Ok(point)
+    }
+
+    fn read_scalar<G: AMFGroup>(&mut self) -> Result<G::Scalar, CodecError> {
+        let mut repr = <G::Scalar as PrimeField>::Repr::default();
+        repr.as_mut()
+            .copy_from_slice(self.read_bytes(scalar_length::<G>()));
+        Option::from(G::Scalar::from_repr(repr)).ok_or(CodecError::InvalidScalar)
+    }
+}
+
+impl<G: AMFGroup> AMFPublicKey<G> {
+    /// The length of `to_bytes()` in bytes.
+    pub fn wire_length() -> usize {
+        WIRE_HEADER_LENGTH + 1 + point_length::<G>()
+    }
+
+    /// Encodes the public key in the versioned wire format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(Self::wire_length());
+        write_header::<G>(&mut buf, WireArtifactKind::PublicKey);
+        buf.push(role_to_byte(self.role));
+        buf.extend(self.public_key.to_bytes().as_ref());
+        buf
+    }
+
+    /// Decodes a public key from the versioned wire format; the identity is rejected.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CodecError> {
+        let mut reader = GroupWireReader::new::<G>(
+            bytes,
+            WireArtifactKind::PublicKey,
+            Self::wire_length() - WIRE_HEADER_LENGTH,
+        )?;
+        Ok(AMFPublicKey {
+            role: reader.read_role()?,
+            public_key: reader.read_non_identity_point()?,
+        })
+    }
+}
+
+impl<G: AMFGroup> AMFSecretKey<G> {
+    /// The length of `to_bytes()` in bytes.
+    pub fn wire_length() -> usize {
+        WIRE_HEADER_LENGTH + 1 + scalar_length::<G>()
+    }
+
+    /// Encodes the secret key in the versioned wire format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(Self::wire_length());
+        write_header::<G>(&mut buf, WireArtifactKind::SecretKey);
+        buf.push(role_to_byte(self.role));
+        buf.extend(self.secret_key.to_repr().as_ref());
+        buf
+    }
+
+    /// Decodes a secret key from the versioned wire format; zero is rejected.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CodecError> {
+        let mut reader = GroupWireReader::new::<G>(
+            bytes,
+            WireArtifactKind::SecretKey,
+            Self::wire_length() - WIRE_HEADER_LENGTH,
+        )?;
+        let role = reader.read_role()?;
+        let secret_key = reader.read_scalar::<G>()?;
+        if bool::from(secret_key.is_zero()) {
+            return Err(CodecError::ZeroScalar);
+        }
+        Ok(AMFSecretKey { role, secret_key })
+    }
+}
+
+impl<G: AMFGroup> AMFSignature<G> {
+    /// The length of `to_bytes()` in bytes.
+    pub fn wire_length() -> usize {
+        WIRE_HEADER_LENGTH + 9 * point_length::<G>() + 6 * scalar_length::<G>()
+    }
+
+    /// Encodes the signature in the versioned wire format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let pi = &self.pi;
+        let mut buf = Vec::with_capacity(Self::wire_length());
+        write_header::<G>(&mut buf, WireArtifactKind::Signature);
+        for point in [
+            self.J,
+            self.R,
+            self.E_J,
+            self.E_R,
+            pi.t_sender,
+            pi.t_J,
+            pi.v_t,
+            pi.w_t,
+            pi.t_R,
+        ] {
+            buf.extend(point.to_bytes().as_ref());
+        }
+        for scalar in [
+            pi.sender_c_0,
+            pi.z_sender,
+            pi.z_J,
+            pi.judge_c_0,
+            pi.z_judge,
+            pi.z_R,
+        ] {
+            buf.extend(scalar.to_repr().as_ref());
+        }
+        buf
+    }
+
+    /// Decodes a signature from the versioned wire format; an identity J, R, E_J or E_R is
+    /// rejected, cf. `AMFSignature::validate`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CodecError> {
+        let mut reader = GroupWireReader::new::<G>(
+            bytes,
+            WireArtifactKind::Signature,
+            Self::wire_length() - WIRE_HEADER_LENGTH,
+        )?;
+        let (J, R, E_J, E_R) = (
+            reader.read_non_identity_point()?,
+            reader.read_non_identity_point()?,
+            reader.read_non_identity_point()?,
+            reader.read_non_identity_point()?,
+        );
+        let pi = AMFProof {
+            t_sender: reader.read_point()?,
+            t_J: reader.read_point()?,
+            v_t: reader.read_point()?,
+            w_t: reader.read_point()?,
+            t_R: reader.read_point()?,
+            sender_c_0: reader.read_scalar::<G>()?,
+            z_sender: reader.read_scalar::<G>()?,
+            z_J: reader.read_scalar::<G>()?,
+            judge_c_0: reader.read_scalar::<G>()?,
+            z_judge: reader.read_scalar::<G>()?,
+            z_R: reader.read_scalar::<G>()?,
+        };
+        Ok(AMFSignature { pi, J, R, E_J, E_R })
+    }
+}
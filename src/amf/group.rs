@@ -0,0 +1,325 @@
+//! Group-Chat Franking
+//!
+//! In a group, the sender franks a message for every member, and every member can check it.
+//! Every member has a sender and a recipient key pair of their own, and the roster of the group,
+//! its `GroupPublicKey`, lists both public keys of every member. A group frank is one independent
+//! AMF frank per member, to the member's recipient key, so every member verifies their own with
+//! `verify_group`, any member can report theirs, and `judge_group` attributes it to the individual
+//! sender, who must be on the roster. Franking and the size of the frank grow linearly with the
+//! group.
+//!
+//! Every frank signs the message bound to the group identifier and the roster, cf.
+//! `spok_amf::bind_members`, so a frank in one group does not verify in another. Deniability is
+//! that of `amf::frank` towards each member: a member can forge the signature to their own
+//! recipient key with `forge::r_forge`, which convinces nobody else, but cannot forge the signature
+//! to another member's key, so no member can convince another member that someone sent a message
+//! they did not send.
+
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use rand_core::CryptoRngCore;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{
+    frank_with_rng, judge, spok_amf::bind_members, verify, AMFPublicKey, AMFSecretKey, AMFSignature,
+};
+
+/// The public keys of a member of a group.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GroupMember {
+    pub sender_public_key: AMFPublicKey,
+    pub recipient_public_key: AMFPublicKey,
+}
+
+/// The public part of a group: its identifier and the keys of its members.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GroupPublicKey {
+    pub group_id: Vec<u8>,
+    pub members: Vec<GroupMember>,
+}
+
+/// A group frank: the AMF signatures to the recipient keys of the members, in roster order.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GroupFrank {
+    pub amf_signatures: Vec<AMFSignature>,
+}
+
+impl GroupPublicKey {
+    pub fn new(group_id: &[u8], members: Vec<GroupMember>) -> Self {
+        GroupPublicKey {
+            group_id: group_id.to_vec(),
+            members,
+        }
+    }
+
+    /// Whether `sender_public_key` is on the roster.
+    pub fn is_member(&self, sender_public_key: &AMFPublicKey) -> bool {
+        self.members
+            .iter()
+            .any(|member| member.sender_public_key == *sender_public_key)
+    }
+
+    /// The position on the roster of the member with `recipient_public_key`.
+    pub fn position(&self, recipient_public_key: &AMFPublicKey) -> Option<usize> {
+        self.members
+            .iter()
+            .position(|member| member.recipient_public_key == *recipient_public_key)
+    }
+
+    /// The message that the proofs of knowledge sign, cf. `spok_amf::bind_members`.
+    pub fn bind(&self, message: &[u8]) -> Vec<u8> {
+        let members: Vec<(RistrettoPoint, RistrettoPoint)> = self
+            .members
+            .iter()
+            .map(|member| {
+                (
+                    member.sender_public_key.public_key,
+                    member.recipient_public_key.public_key,
+                )
+            })
+            .collect();
+        bind_members(&self.group_id, &members, message)
+    }
+}
+
+impl GroupFrank {
+    /// The signature to the member with `recipient_public_key`, if they are on the roster of
+    /// `group` and the frank has one for them.
+    pub fn amf_signature(
+        &self,
+        group: &GroupPublicKey,
+        recipient_public_key: &AMFPublicKey,
+    ) -> Option<&AMFSignature> {
+        if self.amf_signatures.len() != group.members.len() {
+            return None;
+        }
+        self.amf_signatures
+            .get(group.position(recipient_public_key)?)
+    }
+}
+
+#[cfg(any(feature = "rand", test))]
+/// `frank_group_with_rng` with `rand::thread_rng`
+pub fn frank_group(
+    sender_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    group: &GroupPublicKey,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    message: &[u8],
+) -> GroupFrank {
+    frank_group_with_rng(
+        &mut rand::thread_rng(),
+        sender_secret_key,
+        sender_public_key,
+        group,
+        judge_public_key,
+        message,
+    )
+}
+
+/// Franks `message` for every member of `group`: one `amf::frank` per member, to their recipient
+/// key, of the message bound to the group.
+pub fn frank_group_with_rng(
+    rng: &mut dyn CryptoRngCore,
+    sender_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    group: &GroupPublicKey,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    message: &[u8],
+) -> GroupFrank {
+    let [sender_public_key, judge_public_key] =
+        [sender_public_key.borrow(), judge_public_key.borrow()];
+    let bound = group.bind(message);
+    let amf_signatures = group
+        .members
+        .iter()
+        .map(|member| {
+            frank_with_rng(
+                rng,
+                sender_secret_key.borrow(),
+                sender_public_key,
+                member.recipient_public_key,
+                judge_public_key,
+                &bound,
+            )
+        })
+        .collect();
+    GroupFrank { amf_signatures }
+}
+
+/// `amf::verify` of the signature to the member with `recipient_public_key`, with their secret
+/// key; also checks that the sender is on the roster.
+pub fn verify_group(
+    recipient_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    group: &GroupPublicKey,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    message: &[u8],
+    group_frank: impl Borrow<GroupFrank>,
+) -> bool {
+    let [sender_public_key, recipient_public_key] =
+        [sender_public_key.borrow(), recipient_public_key.borrow()];
+    // The roster is public, so checking it apart from the signature leaks nothing
+    let Some(amf_signature) = group_frank
+        .borrow()
+        .amf_signature(group, recipient_public_key)
+    else {
+        return false;
+    };
+    let valid = verify(
+        recipient_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        &group.bind(message),
+        amf_signature,
+    );
+    valid & group.is_member(sender_public_key)
+}
+
+/// `amf::judge` of the signature to the member with `recipient_public_key`, who reported the
+/// frank: attributes it to `sender_public_key`, who must be on the roster.
+pub fn judge_group(
+    judge_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    group: &GroupPublicKey,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    message: &[u8],
+    group_frank: impl Borrow<GroupFrank>,
+) -> bool {
+    let [sender_public_key, recipient_public_key] =
+        [sender_public_key.borrow(), recipient_public_key.borrow()];
+    let Some(amf_signature) = group_frank
+        .borrow()
+        .amf_signature(group, recipient_public_key)
+    else {
+        return false;
+    };
+    let valid = judge(
+        judge_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        &group.bind(message),
+        amf_signature,
+    );
+    valid & group.is_member(sender_public_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amf::{forge::r_forge, keygen, AMFRole};
+
+    #[test]
+    fn test_group_franking() {
+        // 0. Three members form a group
+        let keys: Vec<_> = (0..3)
+            .map(|_| (keygen(AMFRole::Sender), keygen(AMFRole::Recipient)))
+            .collect();
+        let group = GroupPublicKey::new(
+            b"group",
+            keys.iter()
+                .map(|(sender, recipient)| GroupMember {
+                    sender_public_key: sender.0,
+                    recipient_public_key: recipient.0,
+                })
+                .collect(),
+        );
+        let (judge_public_key, judge_secret_key) = keygen(AMFRole::Judge);
+        let message = b"hello world!";
+        let verify_as = |member: usize, sender_public_key, group: &GroupPublicKey, frank| {
+            let (recipient_public_key, recipient_secret_key) = &keys[member].1;
+            verify_group(
+                recipient_secret_key,
+                sender_public_key,
+                recipient_public_key,
+                group,
+                judge_public_key,
+                message,
+                frank,
+            )
+        };
+        let judge_report_of = |member: usize, sender_public_key, frank| {
+            judge_group(
+                &judge_secret_key,
+                sender_public_key,
+                keys[member].1 .0,
+                &group,
+                judge_public_key,
+                message,
+                frank,
+            )
+        };
+
+        // 1. The first member franks once, and every member verifies with their own key
+        let ((sender_public_key, sender_secret_key), _) = &keys[0];
+        let group_frank = frank_group(
+            sender_secret_key,
+            sender_public_key,
+            &group,
+            judge_public_key,
+            message,
+        );
+        for member in 0..3 {
+            assert!(verify_as(member, *sender_public_key, &group, &group_frank));
+        }
+
+        // 2. The judge attributes a report to the sender, and to no other member
+        assert!(judge_report_of(2, *sender_public_key, &group_frank));
+        assert!(!judge_report_of(2, keys[1].0 .0, &group_frank));
+
+        // 3. The frank does not verify in a group with another roster
+        let mut other_group = group.clone();
+        other_group.members.pop();
+        assert!(!verify_as(
+            0,
+            *sender_public_key,
+            &other_group,
+            &group_frank
+        ));
+
+        // 4. A member cannot make another member believe that the first member sent a message:
+        //    the second member forges the signatures to everyone with their recipient key, which
+        //    only they accept
+        let forger_secret_key = &keys[1].1 .1;
+        let forged = GroupFrank {
+            amf_signatures: group
+                .members
+                .iter()
+                .map(|member| {
+                    r_forge(
+                        forger_secret_key,
+                        sender_public_key,
+                        member.recipient_public_key,
+                        judge_public_key,
+                        &group.bind(message),
+                    )
+                })
+                .collect(),
+        };
+        assert!(verify_as(1, *sender_public_key, &group, &forged));
+        assert!(!verify_as(2, *sender_public_key, &group, &forged));
+        assert!(!judge_report_of(1, *sender_public_key, &forged));
+
+        // 5. A sender off the roster is rejected, even with a valid frank
+        let (outsider_public_key, outsider_secret_key) = keygen(AMFRole::Sender);
+        let group_frank = frank_group(
+            &outsider_secret_key,
+            outsider_public_key,
+            &group,
+            judge_public_key,
+            message,
+        );
+        assert!(!verify_as(0, outsider_public_key, &group, &group_frank));
+        assert!(!judge_report_of(0, outsider_public_key, &group_frank));
+    }
+}
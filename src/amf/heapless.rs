@@ -0,0 +1,381 @@
+//! Heapless Frank, Verify and Judge
+//!
+//! `frank`, `verify` and `judge` here compute the same signatures as `amf::frank`, `amf::verify`
+//! and `amf::judge` (cf. Fig. 5 in [AMF]), but spelled out for the one AMF relation instead of
+//! composed from the sigma protocols of `pok`. They touch no heap: the statement and the
+//! commitment are hashed from fixed-size stack buffers, the Fiat-Shamir challenge streams the
+//! message into SHA-512, and every scalar multiplication is a fixed-base or single-point one, so
+//! that they can run where there is no allocator, e.g. on microcontroller-class secure elements.
+//! Their stack use is bounded: a few hundred bytes of buffers, plus curve25519-dalek's own.
+//!
+//! They take an explicit `rng`, since there may be no `thread_rng`, and do not apply
+//! `scalar_blinding`, whose shares come from `thread_rng`; use a constant-time core for secrets.
+//! Signatures from either side verify on the other.
+//!
+//! [AMF]: https://eprint.iacr.org/2019/565/20190527:092413
+#![allow(non_snake_case)]
+
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar, traits::Identity};
+use rand_core::CryptoRngCore;
+use sha2::{Digest, Sha512};
+use subtle::{Choice, ConstantTimeEq};
+use zeroize::Zeroize;
+
+use crate::pok::{
+    chaum_pedersen::ChaumPedersenProverCommitment,
+    domain::{self, absorb},
+    fiat_shamir::FiatShamirSignature,
+    or_proof::OrProverResponse,
+};
+
+use super::{franking::keys_valid, AMFPublicKey, AMFSecretKey, AMFSignature};
+
+/// The protocol label of the AMF signature proof of knowledge, as built by `pok::domain`.
+pub const AMF_PROTOCOL_LABEL: &[u8] = b"and(or(schnorr,schnorr),or(chaum-pedersen,schnorr))";
+
+/// sender_public_key || J for the sender's OR, then judge_public_key || E_J || J || R for the
+/// judge's
+const STATEMENT_LENGTH: usize = 6 * 32;
+
+/// the Schnorr commitments of the sender's OR, then the Chaum-Pedersen and Schnorr commitments of
+/// the judge's
+const COMMITMENT_LENGTH: usize = 5 * 32;
+
+/// the `V2` Fiat-Shamir challenge of `pok::domain`, with full-length challenges
+fn challenge(
+    statement: &[RistrettoPoint; 6],
+    message: &[u8],
+    commitment: &[RistrettoPoint; 5],
+) -> Scalar {
+    let mut statement_bytes = [0u8; STATEMENT_LENGTH];
+    for (chunk, point) in statement_bytes.chunks_exact_mut(32).zip(statement) {
+        chunk.copy_from_slice(point.compress().as_bytes());
+    }
+    let mut commitment_bytes = [0u8; COMMITMENT_LENGTH];
+    for (chunk, point) in commitment_bytes.chunks_exact_mut(32).zip(commitment) {
+        chunk.copy_from_slice(point.compress().as_bytes());
+    }
+
+    let mut hasher = Sha512::new();
+    absorb(&mut hasher, domain::FIAT_SHAMIR_DOMAIN);
+    absorb(&mut hasher, AMF_PROTOCOL_LABEL);
+    absorb(&mut hasher, domain::STATEMENT_ROLE);
+    absorb(&mut hasher, &statement_bytes);
+    absorb(&mut hasher, domain::MESSAGE_ROLE);
+    absorb(&mut hasher, message);
+    absorb(&mut hasher, domain::COMMITMENT_ROLE);
+    absorb(&mut hasher, &commitment_bytes);
+    Scalar::from_hash(hasher)
+}
+
+/// `amf::frank_with_rng` without the heap
+pub fn frank(
+    rng: &mut dyn CryptoRngCore,
+    sender_secret_key: AMFSecretKey,
+    sender_public_key: AMFPublicKey,
+    recipient_public_key: AMFPublicKey,
+    judge_public_key: AMFPublicKey,
+    message: &[u8],
+) -> AMFSignature {
+    let (sender, judge, recipient) = (
+        sender_public_key.public_key,
+        judge_public_key.public_key,
+        recipient_public_key.public_key,
+    );
+
+    // 0. (E_J, J) and (E_R, R), the encryptions of the identity
+    let mut alpha = Scalar::random(rng);
+    let mut beta = Scalar::random(rng);
+    let (E_J, J) = (RistrettoPoint::mul_base(&alpha), alpha * judge);
+    let (E_R, R) = (RistrettoPoint::mul_base(&beta), beta * recipient);
+
+    // 1. The sender's OR: a real Schnorr commitment for sender_public_key = g^t, and a simulated
+    //    one for J = g^u
+    let mut r_0 = Scalar::random(rng);
+    let t_0 = RistrettoPoint::mul_base(&r_0);
+    let c_1 = Scalar::random(rng);
+    let z_1 = Scalar::random(rng);
+    let t_1 = RistrettoPoint::mul_base(&z_1) - c_1 * J;
+
+    // 2. The judge's OR: a real Chaum-Pedersen commitment for (J = judge_public_key^v && E_J =
+    //    g^v), and a simulated Schnorr one for R = g^w
+    let mut r_2 = Scalar::random(rng);
+    let (v_t, w_t) = (RistrettoPoint::mul_base(&r_2), r_2 * judge);
+    let c_3 = Scalar::random(rng);
+    let z_3 = Scalar::random(rng);
+    let t_3 = RistrettoPoint::mul_base(&z_3) - c_3 * R;
+
+    // 3. Split the Fiat-Shamir challenge as c_0 + c = c_1, as in `pok::or_proof`, and respond
+    let c = challenge(
+        &[sender, J, judge, E_J, J, R],
+        message,
+        &[t_0, t_1, v_t, w_t, t_3],
+    );
+    let sender_c_0 = c_1 - c;
+    let judge_c_0 = c_3 - c;
+    let pi = FiatShamirSignature {
+        prover_commitment: (
+            (t_0, t_1),
+            (ChaumPedersenProverCommitment { v_t, w_t }, t_3),
+        ),
+        prover_response: (
+            OrProverResponse {
+                c_0: sender_c_0,
+                z_0: r_0 + sender_c_0 * sender_secret_key.secret_key,
+                z_1,
+            },
+            OrProverResponse {
+                c_0: judge_c_0,
+                z_0: r_2 + judge_c_0 * alpha,
+                z_1: z_3,
+            },
+        ),
+    };
+    // Clear the randomness, which opens the ciphertexts and the sender's and judge's responses
+    alpha.zeroize();
+    beta.zeroize();
+    r_0.zeroize();
+    r_2.zeroize();
+    AMFSignature { pi, J, R, E_J, E_R }
+}
+
+/// checks the proof pi of `amf_signature`
+fn verify_spok(
+    sender_public_key: &AMFPublicKey,
+    judge_public_key: &AMFPublicKey,
+    message: &[u8],
+    amf_signature: &AMFSignature,
+) -> Choice {
+    let AMFSignature { pi, J, R, E_J, .. } = *amf_signature;
+    let ((t_0, t_1), (ChaumPedersenProverCommitment { v_t, w_t }, t_3)) = pi.prover_commitment;
+    let (sender_response, judge_response) = pi.prover_response;
+    let (sender, judge) = (sender_public_key.public_key, judge_public_key.public_key);
+
+    let c = challenge(
+        &[sender, J, judge, E_J, J, R],
+        message,
+        &[t_0, t_1, v_t, w_t, t_3],
+    );
+    // z * g - c * y == t, for Schnorr over the basepoint
+    let schnorr = |y: &RistrettoPoint, t: &RistrettoPoint, c: &Scalar, z: &Scalar| {
+        RistrettoPoint::vartime_double_scalar_mul_basepoint(&-c, y, z).ct_eq(t)
+    };
+
+    let sender_c_1 = sender_response.c_0 + c;
+    let sender_or = schnorr(&sender, &t_0, &sender_response.c_0, &sender_response.z_0)
+        & schnorr(&J, &t_1, &sender_c_1, &sender_response.z_1);
+
+    let judge_c_1 = judge_response.c_0 + c;
+    let judge_or = schnorr(&E_J, &v_t, &judge_response.c_0, &judge_response.z_0)
+        & (judge_response.z_0 * judge - judge_response.c_0 * J).ct_eq(&w_t)
+        & schnorr(&R, &t_3, &judge_c_1, &judge_response.z_1);
+
+    sender_or & judge_or
+}
+
+/// `amf::verify` without the heap, with the same checks b0 to b3
+pub fn verify(
+    recipient_secret_key: AMFSecretKey,
+    sender_public_key: AMFPublicKey,
+    recipient_public_key: AMFPublicKey,
+    judge_public_key: AMFPublicKey,
+    message: &[u8],
+    amf_signature: AMFSignature,
+) -> bool {
+    let b0 = amf_signature.components_valid();
+    let b1 = (amf_signature.R - recipient_secret_key.secret_key * amf_signature.E_R)
+        .ct_eq(&RistrettoPoint::identity());
+    let b2 = verify_spok(
+        &sender_public_key,
+        &judge_public_key,
+        message,
+        &amf_signature,
+    );
+    let b3 = keys_valid(
+        &recipient_secret_key.secret_key,
+        &recipient_public_key,
+        &amf_signature.statement(&sender_public_key, &judge_public_key),
+    );
+    (b0 & b1 & b2 & b3).into()
+}
+
+/// `amf::judge` without the heap
+pub fn judge(
+    judge_secret_key: AMFSecretKey,
+    sender_public_key: AMFPublicKey,
+    _recipient_public_key: AMFPublicKey,
+    judge_public_key: AMFPublicKey,
+    message: &[u8],
+    amf_signature: AMFSignature,
+) -> bool {
+    let b0 = amf_signature.components_valid();
+    let b1 = (amf_signature.J - judge_secret_key.secret_key * amf_signature.E_J)
+        .ct_eq(&RistrettoPoint::identity());
+    let b2 = verify_spok(
+        &sender_public_key,
+        &judge_public_key,
+        message,
+        &amf_signature,
+    );
+    let b3 = keys_valid(
+        &judge_secret_key.secret_key,
+        &judge_public_key,
+        &amf_signature.statement(&sender_public_key, &judge_public_key),
+    );
+    (b0 & b1 & b2 & b3).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::{
+        amf::{self, keygen_with_rng, AMFRole},
+        test_allocator::allocations,
+    };
+
+    #[test]
+    fn test_heapless_franking() {
+        // 0. Initialize a Sender, Recipient, and Judge
+        let mut rng = StdRng::seed_from_u64(0);
+        let (sender_public_key, sender_secret_key) = keygen_with_rng(&mut rng, AMFRole::Sender);
+        let (recipient_public_key, recipient_secret_key) =
+            keygen_with_rng(&mut rng, AMFRole::Recipient);
+        let (judge_public_key, judge_secret_key) = keygen_with_rng(&mut rng, AMFRole::Judge);
+        let message = b"hello world!";
+
+        // 1. Frank, verify and judge without a single allocation
+        let before = allocations();
+        let amf_signature = frank(
+            &mut rng,
+            sender_secret_key.clone(),
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+        );
+        assert!(verify(
+            recipient_secret_key.clone(),
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        ));
+        assert!(judge(
+            judge_secret_key.clone(),
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        ));
+        assert_eq!(allocations(), before);
+
+        // 2. The signature is an ordinary AMF signature
+        assert!(amf::verify(
+            recipient_secret_key.clone(),
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        ));
+        assert!(amf::judge(
+            judge_secret_key.clone(),
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        ));
+
+        // 3. And ordinary AMF signatures verify here, but not on other messages or for other keys
+        let amf_signature = amf::frank(
+            sender_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+        );
+        assert!(verify(
+            recipient_secret_key.clone(),
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        ));
+        assert!(judge(
+            judge_secret_key.clone(),
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        ));
+        assert!(!verify(
+            recipient_secret_key.clone(),
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            b"goodbye world!",
+            amf_signature,
+        ));
+        assert!(!judge(
+            recipient_secret_key.clone(),
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        ));
+
+        // 4. A frank under the identity sender key, whose secret key 0 everyone knows, is rejected
+        //    as `amf::verify` rejects it
+        let identity_public_key = AMFPublicKey {
+            role: AMFRole::Sender,
+            public_key: RistrettoPoint::identity(),
+        };
+        let amf_signature = frank(
+            &mut rng,
+            AMFSecretKey {
+                role: AMFRole::Sender,
+                secret_key: Scalar::ZERO,
+            },
+            identity_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+        );
+        assert!(!verify(
+            recipient_secret_key,
+            identity_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        ));
+        assert!(!judge(
+            judge_secret_key,
+            identity_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        ));
+    }
+
+    #[test]
+    fn test_amf_protocol_label() {
+        // 0. The label is the one the composed proof hashes
+        let (public_key, _) = amf::keygen(AMFRole::Sender);
+        let point = public_key.public_key;
+        let spok = crate::amf::spok_amf::AMFSPoK::new(point, point, point, point, point);
+        assert_eq!(
+            crate::pok::linear_sigma::SigmaProtocol::protocol_label(&spok.protocol),
+            AMF_PROTOCOL_LABEL
+        );
+    }
+}
@@ -0,0 +1,318 @@
+//! Per-Phase Timings of Frank, Verify and Judge
+//!
+//! `frank`, `verify` and `judge` here compute the same results as `amf::frank_with_rng`,
+//! `amf::verify` and `amf::judge`, and also report how long each phase took, for performance
+//! debugging on devices where criterion does not run (cf. `benches/hacky_bench_for_android.rs`).
+//! The phases follow Fig. 5 in [AMF]: the ElGamal encryptions or the decryption, the commitments of
+//! the proof pi, the Fiat-Shamir hash, and the responses or the verification equations.
+//!
+//! Reading the clock costs a few tens of nanoseconds per phase, and the commitments are computed
+//! serially even with the `parallel` feature, so use the uninstrumented functions outside of
+//! debugging.
+//!
+//! [AMF]: https://eprint.iacr.org/2019/565/20190527:092413
+#![allow(non_snake_case)]
+
+use std::time::{Duration, Instant};
+
+use curve25519_dalek::{ristretto::RistrettoPoint, traits::Identity};
+use rand_core::CryptoRngCore;
+use subtle::{Choice, ConstantTimeEq};
+use zeroize::Zeroize;
+
+use crate::pok::{
+    elgamal::{ElGamalCiphertext, VerifiableElGamal},
+    fiat_shamir::FiatShamirSignature,
+    linear_sigma::SigmaProtocol,
+    or_proof::OrWitness,
+};
+
+use super::{franking::keys_valid, spok_amf::AMFSPoK, AMFPublicKey, AMFSecretKey, AMFSignature};
+
+/// How long each phase of `frank` took.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct FrankTimings {
+    /// encrypting the identity to the judge and the recipient
+    pub encryption: Duration,
+    /// setting up the statement of pi, and computing its commitments
+    pub commitments: Duration,
+    /// hashing the statement, the message and the commitments to the challenge
+    pub fiat_shamir_hash: Duration,
+    /// computing the responses to the challenge
+    pub responses: Duration,
+}
+
+/// How long each phase of `verify` or `judge` took.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct VerifyTimings {
+    /// checking that no signature component is the identity, that the ciphertext decrypts to the
+    /// identity, and that the keys are valid
+    pub decryption: Duration,
+    /// hashing the statement, the message and the commitments to the challenge
+    pub fiat_shamir_hash: Duration,
+    /// checking the verification equations of pi
+    pub equations: Duration,
+}
+
+impl FrankTimings {
+    pub fn total(&self) -> Duration {
+        self.encryption + self.commitments + self.fiat_shamir_hash + self.responses
+    }
+}
+
+impl VerifyTimings {
+    pub fn total(&self) -> Duration {
+        self.decryption + self.fiat_shamir_hash + self.equations
+    }
+}
+
+/// runs `phase`, and adds the time it took to `duration`
+fn timed<T>(duration: &mut Duration, phase: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let result = phase();
+    *duration += start.elapsed();
+    result
+}
+
+/// `amf::frank_with_rng`, with the time of each phase
+pub fn frank(
+    rng: &mut dyn CryptoRngCore,
+    sender_secret_key: AMFSecretKey,
+    sender_public_key: AMFPublicKey,
+    recipient_public_key: AMFPublicKey,
+    judge_public_key: AMFPublicKey,
+    message: &[u8],
+) -> (AMFSignature, FrankTimings) {
+    let mut timings = FrankTimings::default();
+
+    // 0. (E_J, J) and (E_R, R), the encryptions of the identity
+    let ((judge_ciphertext, mut alpha), (recipient_ciphertext, mut beta)) =
+        timed(&mut timings.encryption, || {
+            (
+                VerifiableElGamal::new(judge_public_key.public_key)
+                    .encrypt(rng, RistrettoPoint::identity()),
+                VerifiableElGamal::new(recipient_public_key.public_key)
+                    .encrypt(rng, RistrettoPoint::identity()),
+            )
+        });
+    let (E_J, J) = (judge_ciphertext.c1, judge_ciphertext.c2);
+    let (E_R, R) = (recipient_ciphertext.c1, recipient_ciphertext.c2);
+
+    // 1. The commitments of pi
+    let (spok, commitment) = timed(&mut timings.commitments, || {
+        let spok = AMFSPoK::new(
+            sender_public_key.public_key,
+            judge_public_key.public_key,
+            J,
+            R,
+            E_J,
+        );
+        let witness = (
+            OrWitness::Left(sender_secret_key.secret_key),
+            OrWitness::Left(alpha),
+        );
+        let commitment = spok.protocol.generate_commitment(rng, witness);
+        (spok, commitment)
+    });
+    alpha.zeroize();
+    beta.zeroize();
+    let (prover_commitment, prover_state) =
+        commitment.expect("the AMF witness always fits the AMF statement");
+
+    // 2. The challenge, and the responses to it
+    let challenge = timed(&mut timings.fiat_shamir_hash, || {
+        spok.hash_message_and_commitment_to_scalar(message, &prover_commitment)
+    });
+    let prover_response = timed(&mut timings.responses, || {
+        spok.protocol
+            .generate_response_to_challenge(prover_state, challenge)
+    });
+
+    let pi = FiatShamirSignature {
+        prover_commitment,
+        prover_response,
+    };
+    (AMFSignature { pi, J, R, E_J, E_R }, timings)
+}
+
+/// `amf::verify`, with the time of each phase
+pub fn verify(
+    recipient_secret_key: AMFSecretKey,
+    sender_public_key: AMFPublicKey,
+    recipient_public_key: AMFPublicKey,
+    judge_public_key: AMFPublicKey,
+    message: &[u8],
+    amf_signature: AMFSignature,
+) -> (bool, VerifyTimings) {
+    verify_with(
+        &recipient_secret_key,
+        &recipient_public_key,
+        amf_signature.recipient_ciphertext(),
+        sender_public_key,
+        judge_public_key,
+        message,
+        &amf_signature,
+    )
+}
+
+/// `amf::judge`, with the time of each phase
+pub fn judge(
+    judge_secret_key: AMFSecretKey,
+    sender_public_key: AMFPublicKey,
+    _recipient_public_key: AMFPublicKey,
+    judge_public_key: AMFPublicKey,
+    message: &[u8],
+    amf_signature: AMFSignature,
+) -> (bool, VerifyTimings) {
+    verify_with(
+        &judge_secret_key,
+        &judge_public_key,
+        amf_signature.judge_ciphertext(),
+        sender_public_key,
+        judge_public_key,
+        message,
+        &amf_signature,
+    )
+}
+
+/// checks that `ciphertext` encrypts the identity under `secret_key`, the proof pi, and the keys
+/// with `public_key`, the key of the role that checks
+fn verify_with(
+    secret_key: &AMFSecretKey,
+    public_key: &AMFPublicKey,
+    ciphertext: ElGamalCiphertext,
+    sender_public_key: AMFPublicKey,
+    judge_public_key: AMFPublicKey,
+    message: &[u8],
+    amf_signature: &AMFSignature,
+) -> (bool, VerifyTimings) {
+    let mut timings = VerifyTimings::default();
+
+    let (b0, b1, b3) = timed(&mut timings.decryption, || {
+        (
+            amf_signature.components_valid(),
+            VerifiableElGamal::decrypt(&secret_key.secret_key, &ciphertext)
+                .ct_eq(&RistrettoPoint::identity()),
+            keys_valid(
+                &secret_key.secret_key,
+                public_key,
+                &amf_signature.statement(&sender_public_key, &judge_public_key),
+            ),
+        )
+    });
+
+    let pi = amf_signature.pi;
+    let (spok, challenge) = timed(&mut timings.fiat_shamir_hash, || {
+        let spok = AMFSPoK::new(
+            sender_public_key.public_key,
+            judge_public_key.public_key,
+            amf_signature.J,
+            amf_signature.R,
+            amf_signature.E_J,
+        );
+        let challenge = spok.hash_message_and_commitment_to_scalar(message, &pi.prover_commitment);
+        (spok, challenge)
+    });
+    let b2 = timed(&mut timings.equations, || {
+        Choice::from(u8::from(spok.protocol.verify_response_to_challenge(
+            pi.prover_commitment,
+            challenge,
+            pi.prover_response,
+        )))
+    });
+
+    ((b0 & b1 & b2 & b3).into(), timings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amf::{self, keygen, AMFRole};
+
+    #[test]
+    fn test_instrumented_franking() {
+        // 0. Initialize a Sender, Recipient, and Judge
+        let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+        let (recipient_public_key, recipient_secret_key) = keygen(AMFRole::Recipient);
+        let (judge_public_key, judge_secret_key) = keygen(AMFRole::Judge);
+        let message = b"hello world!";
+
+        // 1. Frank, verify and judge, with every phase timed
+        let (amf_signature, frank_timings) = frank(
+            &mut rand::thread_rng(),
+            sender_secret_key.clone(),
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+        );
+        assert!(frank_timings.commitments > Duration::ZERO);
+        assert_eq!(
+            frank_timings.total(),
+            frank_timings.encryption
+                + frank_timings.commitments
+                + frank_timings.fiat_shamir_hash
+                + frank_timings.responses
+        );
+        let (verified, verify_timings) = verify(
+            recipient_secret_key.clone(),
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        );
+        assert!(verified);
+        assert!(verify_timings.equations > Duration::ZERO);
+        let (judged, _) = judge(
+            judge_secret_key.clone(),
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        );
+        assert!(judged);
+
+        // 2. The signature is an ordinary AMF signature, and ordinary ones verify here
+        assert!(amf::judge(
+            judge_secret_key.clone(),
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        ));
+        let amf_signature = amf::frank(
+            sender_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+        );
+        assert!(
+            verify(
+                recipient_secret_key,
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+                amf_signature,
+            )
+            .0
+        );
+
+        // 3. Other messages are rejected
+        assert!(
+            !judge(
+                judge_secret_key,
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                b"goodbye world!",
+                amf_signature,
+            )
+            .0
+        );
+    }
+}
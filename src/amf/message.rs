@@ -0,0 +1,229 @@
+//! Franked Messages with Metadata
+//!
+//! An `AMFMessage` is a payload with the context it was sent in (e.g., a conversation or channel
+//! identifier) and the time it was sent, and `frank_message`, `verify_message` and `judge_message`
+//! frank, verify and judge its canonical encoding
+//!
+//! ```text
+//! tag(AMF_MESSAGE_LABEL) || tag(context) || timestamp || tag(payload)
+//! ```
+//!
+//! with `tag` as in `pok::domain` and the timestamp as an 8-byte big-endian integer. Every field is
+//! delimited, so no two messages encode to the same bytes, which concatenating the fields ad hoc
+//! does not guarantee: `("ab", "c")` and `("a", "bc")` would frank the same bytes. The label also
+//! tells the encoding apart from `bind_context`. Timestamps are whatever the application agrees
+//! on, e.g. seconds since the Unix epoch; this crate does not read clocks.
+
+use alloc::vec::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(any(feature = "rand", test))]
+use super::frank;
+use super::{codec::CodecError, judge, verify, AMFPublicKey, AMFSecretKey, AMFSignature};
+use crate::pok::domain::AMF_MESSAGE_LABEL;
+
+/// A payload, the context it was sent in, and when it was sent.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AMFMessage {
+    pub payload: Vec<u8>,
+    pub context: Vec<u8>,
+    pub timestamp: u64,
+}
+
+impl AMFMessage {
+    pub fn new(payload: &[u8], context: &[u8], timestamp: u64) -> Self {
+        AMFMessage {
+            payload: payload.to_vec(),
+            context: context.to_vec(),
+            timestamp,
+        }
+    }
+
+    /// The canonical encoding, cf. the module documentation.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(
+            32 + AMF_MESSAGE_LABEL.len() + self.context.len() + self.payload.len(),
+        );
+        for tagged in [AMF_MESSAGE_LABEL, &self.context] {
+            bytes.extend_from_slice(&(tagged.len() as u64).to_be_bytes());
+            bytes.extend_from_slice(tagged);
+        }
+        bytes.extend_from_slice(&self.timestamp.to_be_bytes());
+        bytes.extend_from_slice(&(self.payload.len() as u64).to_be_bytes());
+        bytes.extend_from_slice(&self.payload);
+        bytes
+    }
+
+    /// Decodes `to_bytes`, e.g. the franked bytes of a report.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CodecError> {
+        let mut reader = Reader(bytes);
+        if reader.tagged()? != AMF_MESSAGE_LABEL {
+            return Err(CodecError::InvalidMagic);
+        }
+        let context = reader.tagged()?.to_vec();
+        let timestamp = u64::from_be_bytes(reader.take(8)?.try_into().unwrap());
+        let payload = reader.tagged()?.to_vec();
+        if !reader.0.is_empty() {
+            return Err(CodecError::InvalidLength {
+                expected: bytes.len() - reader.0.len(),
+                actual: bytes.len(),
+            });
+        }
+        Ok(AMFMessage {
+            payload,
+            context,
+            timestamp,
+        })
+    }
+}
+
+/// reads the fields of an encoded `AMFMessage` in order
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], CodecError> {
+        if self.0.len() < len {
+            return Err(CodecError::InvalidLength {
+                expected: len,
+                actual: self.0.len(),
+            });
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn tagged(&mut self) -> Result<&'a [u8], CodecError> {
+        let len = u64::from_be_bytes(self.take(8)?.try_into().unwrap());
+        self.take(usize::try_from(len).unwrap_or(usize::MAX))
+    }
+}
+
+#[cfg(any(feature = "rand", test))]
+/// `frank` of the canonical encoding of `message`
+pub fn frank_message(
+    sender_secret_key: AMFSecretKey,
+    sender_public_key: AMFPublicKey,
+    recipient_public_key: AMFPublicKey,
+    judge_public_key: AMFPublicKey,
+    message: &AMFMessage,
+) -> AMFSignature {
+    frank(
+        sender_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        &message.to_bytes(),
+    )
+}
+
+/// `verify` of a `frank_message`
+pub fn verify_message(
+    recipient_secret_key: AMFSecretKey,
+    sender_public_key: AMFPublicKey,
+    recipient_public_key: AMFPublicKey,
+    judge_public_key: AMFPublicKey,
+    message: &AMFMessage,
+    amf_signature: AMFSignature,
+) -> bool {
+    verify(
+        recipient_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        &message.to_bytes(),
+        amf_signature,
+    )
+}
+
+/// `judge` of a `frank_message`
+pub fn judge_message(
+    judge_secret_key: AMFSecretKey,
+    sender_public_key: AMFPublicKey,
+    recipient_public_key: AMFPublicKey,
+    judge_public_key: AMFPublicKey,
+    message: &AMFMessage,
+    amf_signature: AMFSignature,
+) -> bool {
+    judge(
+        judge_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        &message.to_bytes(),
+        amf_signature,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amf::{bind_context, keygen, AMFRole};
+
+    #[test]
+    fn test_amf_message() {
+        let message = AMFMessage::new(b"hello world!", b"channel 42", 1_700_000_000);
+
+        // 0. The encoding decodes, and only as a whole
+        let bytes = message.to_bytes();
+        assert_eq!(AMFMessage::from_bytes(&bytes), Ok(message.clone()));
+        assert!(AMFMessage::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(AMFMessage::from_bytes(&[bytes.as_slice(), b"!"].concat()).is_err());
+        assert_eq!(
+            AMFMessage::from_bytes(&bind_context(b"channel 42", b"hello world!")),
+            Err(CodecError::InvalidMagic)
+        );
+
+        // 1. Moving bytes between fields, or changing the timestamp, changes the encoding
+        for other in [
+            AMFMessage::new(b"hello world!c", b"hannel 42", 1_700_000_000),
+            AMFMessage::new(b"hello world!", b"channel 42", 1_700_000_001),
+            AMFMessage::new(b"", b"channel 42hello world!", 1_700_000_000),
+        ] {
+            assert_ne!(other.to_bytes(), bytes);
+        }
+
+        // 2. Franks of a message verify and judge for it only
+        let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+        let (recipient_public_key, recipient_secret_key) = keygen(AMFRole::Recipient);
+        let (judge_public_key, judge_secret_key) = keygen(AMFRole::Judge);
+        let amf_signature = frank_message(
+            sender_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            &message,
+        );
+        assert!(verify_message(
+            recipient_secret_key.clone(),
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            &message,
+            amf_signature,
+        ));
+        assert!(judge_message(
+            judge_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            &message,
+            amf_signature,
+        ));
+        let later = AMFMessage {
+            timestamp: message.timestamp + 1,
+            ..message
+        };
+        assert!(!verify_message(
+            recipient_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            &later,
+            amf_signature,
+        ));
+    }
+}
@@ -1,6 +1,60 @@
 pub mod spok_amf;
 
+#[cfg(feature = "aggregatable")]
+pub mod aggregatable;
+#[cfg(feature = "test-utils")]
+pub mod arbitrary;
+#[cfg(feature = "unstable-der")]
+pub mod asn1;
+pub mod borrowed;
+pub mod builder;
+#[cfg(feature = "decompression-cache")]
+pub mod cache;
 pub mod codec;
+pub mod compressed;
+#[cfg(feature = "deniability")]
+pub mod deniability;
+pub mod directory;
+#[cfg(feature = "dudect")]
+pub mod dudect;
+pub mod edit;
+pub mod epoch;
+pub mod forge;
+pub mod forward_secure;
 pub mod franking;
+#[cfg(feature = "generic_group")]
+pub mod generic;
+pub mod group;
+pub mod heapless;
+#[cfg(feature = "instrumented")]
+pub mod instrumented;
+pub mod message;
+#[cfg(feature = "test_vectors")]
+pub mod negative_vectors;
+#[cfg(feature = "p256")]
+pub mod p256;
+pub mod prekey;
+#[cfg(any(feature = "rand", test))]
+pub mod prepared;
+pub mod sampling;
+#[cfg(feature = "secp256k1")]
+pub mod secp256k1;
+pub mod signed;
+#[cfg(feature = "std")]
+pub mod stream;
+pub mod suite;
+#[cfg(feature = "test_vectors")]
+pub mod test_vectors;
+mod trace;
+pub mod v1;
+pub mod v2;
+pub mod versioned;
 
+pub use borrowed::*;
+pub use builder::*;
+pub use compressed::*;
 pub use franking::*;
+pub use message::*;
+#[cfg(any(feature = "rand", test))]
+pub use prepared::*;
+pub use suite::*;
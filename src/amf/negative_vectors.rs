@@ -0,0 +1,399 @@
+//! A Wycheproof-style corpus of intentionally broken AMF inputs.
+//!
+//! Every vector starts from an honestly franked message and breaks exactly one thing about it: an
+//! invalid point or scalar encoding, swapped signature components, a truncated or extended
+//! encoding, a bad header, a substituted identity point, or the wrong message or keys. Each vector
+//! names the operation (verify or judge) that must reject it. Integrators can replay the corpus
+//! through their own glue code with `NegativeVectors::run_conformance`.
+//!
+//! Like the known-answer tests, all byte strings are hex encoded, and keys and signatures use the
+//! wire format.
+#![allow(non_snake_case)]
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    codec::{SUITE_RISTRETTO255_SHA512, WIRE_FORMAT_VERSION, WIRE_HEADER_LENGTH},
+    frank, judge,
+    test_vectors::keygen_from_seed,
+    verify, AMFPublicKey, AMFRole, AMFSecretKey, AMFSignature,
+};
+
+/// The operation that must reject a negative vector.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NegativeVectorOperation {
+    Verify,
+    Judge,
+}
+
+/// A single intentionally broken input.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NegativeVector {
+    pub description: String,
+    pub operation: NegativeVectorOperation,
+    pub message: String,
+    pub sender_public_key: String,
+    pub recipient_public_key: String,
+    pub judge_public_key: String,
+    /// The recipient's secret key for `Verify` vectors, and the judge's for `Judge` vectors.
+    pub secret_key: String,
+    pub signature: String,
+}
+
+/// A corpus of negative vectors for one suite and wire format version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NegativeVectors {
+    pub format_version: u8,
+    pub suite: u8,
+    pub vectors: Vec<NegativeVector>,
+}
+
+/// The decoded-from-hex inputs of a negative vector, as handed to glue code under test.
+#[derive(Debug, Clone, Copy)]
+pub struct NegativeVectorInputs<'a> {
+    pub operation: NegativeVectorOperation,
+    pub message: &'a [u8],
+    pub sender_public_key: &'a [u8],
+    pub recipient_public_key: &'a [u8],
+    pub judge_public_key: &'a [u8],
+    pub secret_key: &'a [u8],
+    pub signature: &'a [u8],
+}
+
+/// The names of the points in the wire format signature body, in order.
+const POINT_NAMES: [&str; 9] = ["J", "R", "E_J", "E_R", "t_s", "t_J", "v_t", "w_t", "t_R"];
+
+/// The names of the scalars in the wire format signature body, in order.
+const SCALAR_NAMES: [&str; 6] = ["c_0", "z_0", "z_1", "c_0'", "z_0'", "z_1'"];
+
+/// Byte offset of the 32-byte field at `index` in a wire format signature.
+fn field_offset(index: usize) -> usize {
+    WIRE_HEADER_LENGTH + 32 * index
+}
+
+/// The keys and message that a negative vector is derived from.
+#[derive(Clone)]
+struct Fixture {
+    message: Vec<u8>,
+    sender_public_key: AMFPublicKey,
+    recipient_public_key: AMFPublicKey,
+    recipient_secret_key: AMFSecretKey,
+    judge_public_key: AMFPublicKey,
+    judge_secret_key: AMFSecretKey,
+    signature: Vec<u8>,
+}
+
+impl Fixture {
+    fn vector(&self, operation: NegativeVectorOperation, description: String) -> NegativeVector {
+        let secret_key = match operation {
+            NegativeVectorOperation::Verify => &self.recipient_secret_key,
+            NegativeVectorOperation::Judge => &self.judge_secret_key,
+        };
+        NegativeVector {
+            description,
+            operation,
+            message: hex::encode(&self.message),
+            sender_public_key: hex::encode(self.sender_public_key.to_bytes()),
+            recipient_public_key: hex::encode(self.recipient_public_key.to_bytes()),
+            judge_public_key: hex::encode(self.judge_public_key.to_bytes()),
+            secret_key: hex::encode(secret_key.to_bytes()),
+            signature: hex::encode(&self.signature),
+        }
+    }
+
+    /// Adds a vector for both operations.
+    fn both(&self, vectors: &mut Vec<NegativeVector>, description: String) {
+        vectors.push(self.vector(NegativeVectorOperation::Verify, description.clone()));
+        vectors.push(self.vector(NegativeVectorOperation::Judge, description));
+    }
+
+    /// Returns a copy of the fixture with a different signature encoding.
+    fn with_signature(&self, signature: Vec<u8>) -> Fixture {
+        Fixture {
+            signature,
+            ..self.clone()
+        }
+    }
+}
+
+/// Generates the negative vector corpus.
+pub fn generate() -> NegativeVectors {
+    // 0. Frank an honest message under seeded keys
+    let (sender_public_key, sender_secret_key) = keygen_from_seed(AMFRole::Sender, b"negative");
+    let (recipient_public_key, recipient_secret_key) =
+        keygen_from_seed(AMFRole::Recipient, b"negative");
+    let (judge_public_key, judge_secret_key) = keygen_from_seed(AMFRole::Judge, b"negative");
+    let message = b"hello world!".to_vec();
+    let amf_signature = frank(
+        sender_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        &message,
+    );
+    let honest = Fixture {
+        message,
+        sender_public_key,
+        recipient_public_key,
+        recipient_secret_key,
+        judge_public_key,
+        judge_secret_key,
+        signature: amf_signature.to_bytes(),
+    };
+    let mut vectors = Vec::new();
+
+    // 1. Invalid point and scalar encodings
+    for (index, name) in POINT_NAMES.iter().enumerate() {
+        let mut signature = honest.signature.clone();
+        signature[field_offset(index)..field_offset(index + 1)].copy_from_slice(&[0xff; 32]);
+        honest
+            .with_signature(signature)
+            .both(&mut vectors, format!("invalid point encoding for {}", name));
+    }
+    for (index, name) in SCALAR_NAMES.iter().enumerate() {
+        let mut signature = honest.signature.clone();
+        let index = POINT_NAMES.len() + index;
+        signature[field_offset(index)..field_offset(index + 1)].copy_from_slice(&[0xff; 32]);
+        honest
+            .with_signature(signature)
+            .both(&mut vectors, format!("non-canonical scalar for {}", name));
+    }
+
+    // 2. Swapped components, and the identity substituted for components
+    for (a, b) in [(0, 1), (2, 3), (0, 2), (1, 3), (4, 5)] {
+        let mut signature = honest.signature.clone();
+        let (start_a, start_b) = (field_offset(a), field_offset(b));
+        for offset in 0..32 {
+            signature.swap(start_a + offset, start_b + offset);
+        }
+        honest.with_signature(signature).both(
+            &mut vectors,
+            format!("swapped {} and {}", POINT_NAMES[a], POINT_NAMES[b]),
+        );
+    }
+    for (index, name) in POINT_NAMES[..4].iter().enumerate() {
+        let mut signature = honest.signature.clone();
+        signature[field_offset(index)..field_offset(index + 1)].copy_from_slice(&[0; 32]);
+        let fixture = honest.with_signature(signature);
+        let description = format!("identity point for {}", name);
+        // The judge never looks at E_R, so only verification must reject a substituted E_R
+        if *name == "E_R" {
+            vectors.push(fixture.vector(NegativeVectorOperation::Verify, description));
+        } else {
+            fixture.both(&mut vectors, description);
+        }
+    }
+
+    // 3. Truncated, extended, and mislabeled encodings
+    for length in [0, WIRE_HEADER_LENGTH, honest.signature.len() - 1] {
+        honest
+            .with_signature(honest.signature[..length].to_vec())
+            .both(
+                &mut vectors,
+                format!("signature truncated to {} bytes", length),
+            );
+    }
+    let mut signature = honest.signature.clone();
+    signature.push(0);
+    honest
+        .with_signature(signature)
+        .both(&mut vectors, "signature with a trailing byte".to_string());
+    for (offset, name) in [(0, "magic"), (3, "version"), (4, "suite"), (5, "kind")] {
+        let mut signature = honest.signature.clone();
+        signature[offset] ^= 0x80;
+        honest
+            .with_signature(signature)
+            .both(&mut vectors, format!("signature with a bad {}", name));
+    }
+
+    // 4. The wrong message or keys
+    let wrong_message = Fixture {
+        message: b"goodbye world!".to_vec(),
+        ..honest.clone()
+    };
+    wrong_message.both(&mut vectors, "wrong message".to_string());
+
+    let (other_sender_public_key, _) = keygen_from_seed(AMFRole::Sender, b"other");
+    let wrong_sender = Fixture {
+        sender_public_key: other_sender_public_key,
+        ..honest.clone()
+    };
+    wrong_sender.both(&mut vectors, "wrong sender public key".to_string());
+
+    let (other_judge_public_key, other_judge_secret_key) =
+        keygen_from_seed(AMFRole::Judge, b"other");
+    let wrong_judge = Fixture {
+        judge_public_key: other_judge_public_key,
+        judge_secret_key: other_judge_secret_key.clone(),
+        ..honest.clone()
+    };
+    wrong_judge.both(&mut vectors, "wrong judge key pair".to_string());
+    let wrong_judge_secret_key = Fixture {
+        judge_secret_key: other_judge_secret_key,
+        ..honest.clone()
+    };
+    vectors.push(wrong_judge_secret_key.vector(
+        NegativeVectorOperation::Judge,
+        "wrong judge secret key".to_string(),
+    ));
+
+    // Judging does not depend on the recipient's keys, so these only apply to verification
+    let (other_recipient_public_key, other_recipient_secret_key) =
+        keygen_from_seed(AMFRole::Recipient, b"other");
+    let wrong_recipient = Fixture {
+        recipient_public_key: other_recipient_public_key,
+        recipient_secret_key: other_recipient_secret_key.clone(),
+        ..honest.clone()
+    };
+    vectors.push(wrong_recipient.vector(
+        NegativeVectorOperation::Verify,
+        "wrong recipient key pair".to_string(),
+    ));
+    let wrong_recipient_secret_key = Fixture {
+        recipient_secret_key: other_recipient_secret_key,
+        ..honest.clone()
+    };
+    vectors.push(wrong_recipient_secret_key.vector(
+        NegativeVectorOperation::Verify,
+        "wrong recipient secret key".to_string(),
+    ));
+
+    NegativeVectors {
+        format_version: WIRE_FORMAT_VERSION,
+        suite: SUITE_RISTRETTO255_SHA512,
+        vectors,
+    }
+}
+
+/// Decodes and runs `verify` or `judge` on the inputs, as reference glue code would.
+pub fn reference_accepts(inputs: &NegativeVectorInputs) -> bool {
+    let decoded = (|| {
+        Some((
+            AMFPublicKey::from_bytes(inputs.sender_public_key).ok()?,
+            AMFPublicKey::from_bytes(inputs.recipient_public_key).ok()?,
+            AMFPublicKey::from_bytes(inputs.judge_public_key).ok()?,
+            AMFSecretKey::from_bytes(inputs.secret_key).ok()?,
+            AMFSignature::from_bytes(inputs.signature).ok()?,
+        ))
+    })();
+    let Some((sender_public_key, recipient_public_key, judge_public_key, secret_key, signature)) =
+        decoded
+    else {
+        return false;
+    };
+    match inputs.operation {
+        NegativeVectorOperation::Verify => verify(
+            secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            inputs.message,
+            signature,
+        ),
+        NegativeVectorOperation::Judge => judge(
+            secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            inputs.message,
+            signature,
+        ),
+    }
+}
+
+impl NegativeVectors {
+    /// Serializes the corpus as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    /// Loads a corpus from JSON.
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// Runs every vector through `accepts`, returning the indices of the vectors it wrongly
+    /// accepted. Vectors with fields that are not valid hex are reported as accepted.
+    pub fn run_conformance<F>(&self, mut accepts: F) -> Result<(), Vec<usize>>
+    where
+        F: FnMut(&NegativeVectorInputs) -> bool,
+    {
+        let accepted: Vec<usize> = self
+            .vectors
+            .iter()
+            .enumerate()
+            .filter(|(_, vector)| {
+                let decode = |field: &str| hex::decode(field).ok();
+                let fields = (|| {
+                    Some([
+                        decode(&vector.message)?,
+                        decode(&vector.sender_public_key)?,
+                        decode(&vector.recipient_public_key)?,
+                        decode(&vector.judge_public_key)?,
+                        decode(&vector.secret_key)?,
+                        decode(&vector.signature)?,
+                    ])
+                })();
+                match fields {
+                    Some(
+                        [message, sender_public_key, recipient_public_key, judge_public_key, secret_key, signature],
+                    ) => accepts(&NegativeVectorInputs {
+                        operation: vector.operation,
+                        message: &message,
+                        sender_public_key: &sender_public_key,
+                        recipient_public_key: &recipient_public_key,
+                        judge_public_key: &judge_public_key,
+                        secret_key: &secret_key,
+                        signature: &signature,
+                    }),
+                    None => true,
+                }
+            })
+            .map(|(index, _)| index)
+            .collect();
+        if accepted.is_empty() {
+            Ok(())
+        } else {
+            Err(accepted)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negative_vectors() {
+        // 0. Generate the corpus and round-trip it through JSON
+        let negative_vectors = generate();
+        let loaded = NegativeVectors::from_json(&negative_vectors.to_json()).unwrap();
+        assert_eq!(loaded, negative_vectors);
+
+        // 1. The reference glue rejects everything
+        assert_eq!(loaded.run_conformance(reference_accepts), Ok(()));
+
+        // 2. Glue that ignores trailing bytes is caught
+        let lenient = |inputs: &NegativeVectorInputs| {
+            let signature =
+                &inputs.signature[..inputs.signature.len().min(AMFSignature::WIRE_LENGTH)];
+            reference_accepts(&NegativeVectorInputs {
+                signature,
+                ..*inputs
+            })
+        };
+        let accepted = loaded.run_conformance(lenient).unwrap_err();
+        assert_eq!(accepted.len(), 2);
+        assert!(loaded.vectors[accepted[0]]
+            .description
+            .contains("trailing byte"));
+
+        // 3. Glue that only decodes the signature is caught by the vectors that decode
+        let decode_only =
+            |inputs: &NegativeVectorInputs| AMFSignature::from_bytes(inputs.signature).is_ok();
+        let accepted = loaded.run_conformance(decode_only).unwrap_err();
+        assert!(accepted
+            .iter()
+            .all(|&index| !loaded.vectors[index].description.contains("encoding")));
+    }
+}
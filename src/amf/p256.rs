@@ -0,0 +1,290 @@
+//! AMF over NIST P-256
+//!
+//! Instantiates `generic` with the P-256 curve (secp256r1) of the `p256` crate, for deployments
+//! that must stick to NIST curves and SHA-2, e.g. next to FIPS 140 validated modules. This does not
+//! make the crate itself validated, and AMF is not a FIPS-approved scheme. In the wire format,
+//! under suite `SUITE_P256_SHA512`, points are SEC1-compressed (33 bytes) and scalars big-endian
+//! (32 bytes), so a signature body is 9 * 33 + 6 * 32 = 489 bytes, as for `secp256k1`; the suite
+//! byte keeps the two apart. Known-answer tests are generated and replayed by
+//! `test_vectors::generate_generic` and `KnownAnswerTests::replay_generic`.
+
+use ::p256::ProjectivePoint;
+
+use crate::pok::domain;
+
+#[cfg(any(feature = "rand", test))]
+use super::AMFRole;
+use super::{codec::SUITE_P256_SHA512, generic, generic::AMFGroup};
+
+impl AMFGroup for ProjectivePoint {
+    const LABEL: &'static [u8] = domain::P256_LABEL;
+    const SUITE: u8 = SUITE_P256_SHA512;
+}
+
+pub type AMFPublicKey = generic::AMFPublicKey<ProjectivePoint>;
+pub type AMFSecretKey = generic::AMFSecretKey<ProjectivePoint>;
+pub type AMFSignature = generic::AMFSignature<ProjectivePoint>;
+
+#[cfg(any(feature = "rand", test))]
+pub fn keygen(role: AMFRole) -> (AMFPublicKey, AMFSecretKey) {
+    generic::keygen(role)
+}
+
+#[cfg(any(feature = "rand", test))]
+pub fn frank(
+    sender_secret_key: AMFSecretKey,
+    sender_public_key: AMFPublicKey,
+    recipient_public_key: AMFPublicKey,
+    judge_public_key: AMFPublicKey,
+    message: &[u8],
+) -> AMFSignature {
+    generic::frank(
+        sender_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        message,
+    )
+}
+
+pub fn verify(
+    recipient_secret_key: AMFSecretKey,
+    sender_public_key: AMFPublicKey,
+    recipient_public_key: AMFPublicKey,
+    judge_public_key: AMFPublicKey,
+    message: &[u8],
+    amf_signature: AMFSignature,
+) -> bool {
+    generic::verify(
+        recipient_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        message,
+        amf_signature,
+    )
+}
+
+pub fn judge(
+    judge_secret_key: AMFSecretKey,
+    sender_public_key: AMFPublicKey,
+    recipient_public_key: AMFPublicKey,
+    judge_public_key: AMFPublicKey,
+    message: &[u8],
+    amf_signature: AMFSignature,
+) -> bool {
+    generic::judge(
+        judge_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        message,
+        amf_signature,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use group::Group;
+
+    use crate::amf::codec::CodecError;
+
+    use super::*;
+
+    #[test]
+    fn test_p256_franking() {
+        // 0. Initialize a Sender, a Recipient, and a Judge
+        let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+        let (recipient_public_key, recipient_secret_key) = keygen(AMFRole::Recipient);
+        let (judge_public_key, judge_secret_key) = keygen(AMFRole::Judge);
+
+        // 1. Frank a message, then verify and judge it
+        let message = b"hello world!";
+        let amf_signature = frank(
+            sender_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+        );
+        assert!(verify(
+            recipient_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        ));
+        assert!(judge(
+            judge_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        ));
+
+        // 2. Another message, sender, or recipient is rejected
+        assert!(!verify(
+            recipient_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            b"goodbye world!",
+            amf_signature,
+        ));
+        let (other_public_key, other_secret_key) = keygen(AMFRole::Sender);
+        assert!(!judge(
+            judge_secret_key,
+            other_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        ));
+        assert!(!verify(
+            other_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        ));
+
+        // 3. Tampering with J breaks the proof
+        let mut forged = amf_signature;
+        forged.J = ProjectivePoint::random(&mut rand::thread_rng());
+        assert!(!judge(
+            judge_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            forged,
+        ));
+
+        // 4. A frank under the identity sender key, whose secret key 0 everyone knows, is rejected
+        let identity_public_key = AMFPublicKey {
+            role: AMFRole::Sender,
+            public_key: ProjectivePoint::identity(),
+        };
+        let amf_signature = frank(
+            AMFSecretKey {
+                role: AMFRole::Sender,
+                secret_key: ::p256::Scalar::ZERO,
+            },
+            identity_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+        );
+        assert!(!verify(
+            recipient_secret_key,
+            identity_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        ));
+        assert!(!judge(
+            judge_secret_key,
+            identity_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        ));
+    }
+
+    #[test]
+    fn test_p256_wire_format() {
+        let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+        let (recipient_public_key, _) = keygen(AMFRole::Recipient);
+        let (judge_public_key, _) = keygen(AMFRole::Judge);
+        let amf_signature = frank(
+            sender_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            b"hello world!",
+        );
+
+        // 0. Keys and signatures round-trip, at the documented lengths
+        let encoded = amf_signature.to_bytes();
+        assert_eq!(encoded.len(), 6 + 489);
+        assert_eq!(encoded.len(), AMFSignature::wire_length());
+        assert_eq!(AMFSignature::from_bytes(&encoded), Ok(amf_signature));
+        let encoded_public_key = sender_public_key.to_bytes();
+        assert_eq!(encoded_public_key.len(), AMFPublicKey::wire_length());
+        assert_eq!(
+            AMFPublicKey::from_bytes(&encoded_public_key),
+            Ok(sender_public_key)
+        );
+        assert_eq!(
+            AMFSecretKey::from_bytes(&sender_secret_key.to_bytes()),
+            Ok(sender_secret_key)
+        );
+
+        // 1. Ristretto artifacts are rejected by their suite, and vice versa
+        let (ristretto_public_key, _) = crate::amf::keygen(AMFRole::Sender);
+        assert_eq!(
+            AMFPublicKey::from_bytes(&ristretto_public_key.to_bytes()),
+            Err(CodecError::UnsupportedSuite(1))
+        );
+        assert!(crate::amf::AMFSignature::from_bytes(&encoded).is_err());
+        assert_eq!(encoded[4], SUITE_P256_SHA512);
+
+        // 2. Truncated signatures and identity public keys are rejected
+        assert!(matches!(
+            AMFSignature::from_bytes(&encoded[..encoded.len() - 1]),
+            Err(CodecError::InvalidLength { .. })
+        ));
+        let identity = AMFPublicKey {
+            role: AMFRole::Sender,
+            public_key: ProjectivePoint::IDENTITY,
+        };
+        assert_eq!(
+            AMFPublicKey::from_bytes(&identity.to_bytes()),
+            Err(CodecError::IdentityPoint)
+        );
+    }
+
+    #[cfg(feature = "test_vectors")]
+    #[test]
+    fn test_p256_known_answer_tests() {
+        use crate::amf::test_vectors::{
+            generate, generate_generic, KnownAnswerTestError, KnownAnswerTests,
+        };
+
+        // 0. Generate vectors, round-trip them through JSON, and replay them
+        let known_answer_tests = generate_generic::<ProjectivePoint>(8);
+        assert_eq!(known_answer_tests.suite, SUITE_P256_SHA512);
+        let loaded = KnownAnswerTests::from_json(&known_answer_tests.to_json()).unwrap();
+        assert_eq!(loaded, known_answer_tests);
+        assert_eq!(loaded.replay_generic::<ProjectivePoint>(), Ok(()));
+
+        // 1. Keys are deterministic
+        assert_eq!(
+            generate_generic::<ProjectivePoint>(1).vectors[0].sender_public_key,
+            loaded.vectors[0].sender_public_key
+        );
+
+        // 2. Vectors only replay under their own suite
+        assert!(matches!(
+            loaded.replay(),
+            Err(KnownAnswerTestError::UnsupportedSuite { suite: 3, .. })
+        ));
+        assert!(matches!(
+            generate(1).replay_generic::<ProjectivePoint>(),
+            Err(KnownAnswerTestError::UnsupportedSuite { suite: 1, .. })
+        ));
+
+        // 3. Tampering with a vector is caught
+        let mut tampered = loaded;
+        tampered.vectors[2].message = hex::encode(b"goodbye world!");
+        assert_eq!(
+            tampered.replay_generic::<ProjectivePoint>(),
+            Err(KnownAnswerTestError::VerificationFailed { index: 2 })
+        );
+    }
+}
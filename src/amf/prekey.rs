@@ -0,0 +1,438 @@
+//! Prekey Bundles for Franking the First Message
+//!
+//! As in X3DH, a recipient publishes prekeys to a server ahead of time, so that a sender can frank
+//! the very first message while the recipient is offline. The recipient's `PrekeyStore` holds:
+//!
+//! - an identity key pair, the recipient's long-term AMF key, which signs the prekeys;
+//! - a signed prekey, a medium-term AMF recipient key, for when the one-time prekeys run out;
+//! - one-time prekeys, AMF recipient keys that are each used for one first message only.
+//!
+//! Every prekey is signed together with its identifier and the judge's public key, with a Schnorr
+//! signature (cf. `pok::schnorr_signature`) under the identity key, so the server can neither
+//! substitute a prekey nor the judge. The server hands out a `PrekeyBundle` with the signed prekey
+//! and at most one one-time prekey, which it removes, cf. `PublishedPrekeys::take_bundle`. The
+//! sender checks the signatures with `PrekeyBundle::verify` and franks the first message to the
+//! one-time prekey, or else to the signed prekey, with `frank_first`. The recipient verifies it
+//! with `PrekeyStore::verify_first`, which consumes the one-time prekey, so a second first message
+//! to the same one-time prekey is rejected.
+//!
+//! A judge judges a first message like any other frank; the recipient reports the prekey as the
+//! recipient's public key.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use rand_core::CryptoRngCore;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{
+    frank_with_rng, keygen_with_rng, verify, AMFPublicKey, AMFRole, AMFSecretKey, AMFSignature,
+};
+use crate::pok::{
+    domain::PREKEY_LABEL,
+    schnorr_signature::{
+        schnorr_sign, schnorr_verify, SchnorrPublicKey, SchnorrSecretKey, SchnorrSignature,
+    },
+};
+
+/// A Schnorr signature under the recipient's identity key.
+pub type PrekeySignature = SchnorrSignature;
+
+/// Which prekey a first message is franked to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PrekeyId {
+    Signed(u32),
+    OneTime(u32),
+}
+
+impl PrekeyId {
+    fn to_bytes(self) -> [u8; 5] {
+        let (kind, id) = match self {
+            PrekeyId::Signed(id) => (0, id),
+            PrekeyId::OneTime(id) => (1, id),
+        };
+        let mut bytes = [kind; 5];
+        bytes[1..].copy_from_slice(&id.to_be_bytes());
+        bytes
+    }
+}
+
+/// A prekey and the identity key's signature on it.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SignedPrekey {
+    pub id: PrekeyId,
+    pub public_key: AMFPublicKey,
+    pub signature: PrekeySignature,
+}
+
+/// What a sender fetches from the server to frank a first message.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PrekeyBundle {
+    pub identity_key: AMFPublicKey,
+    pub judge_public_key: AMFPublicKey,
+    pub signed_prekey: SignedPrekey,
+    pub one_time_prekey: Option<SignedPrekey>,
+}
+
+/// What a recipient uploads to the server.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PublishedPrekeys {
+    pub identity_key: AMFPublicKey,
+    pub judge_public_key: AMFPublicKey,
+    pub signed_prekey: SignedPrekey,
+    pub one_time_prekeys: Vec<SignedPrekey>,
+}
+
+impl PublishedPrekeys {
+    /// The bundle for the next sender, which takes the next one-time prekey off the server, if
+    /// any is left.
+    pub fn take_bundle(&mut self) -> PrekeyBundle {
+        PrekeyBundle {
+            identity_key: self.identity_key,
+            judge_public_key: self.judge_public_key,
+            signed_prekey: self.signed_prekey,
+            one_time_prekey: self.one_time_prekeys.pop(),
+        }
+    }
+}
+
+/// A first message's frank, and the prekey it is franked to.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FirstFrank {
+    pub prekey_id: PrekeyId,
+    pub amf_signature: AMFSignature,
+}
+
+/// Why a bundle or a first message was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrekeyError {
+    /// A prekey is not signed by the identity key, or not for this judge.
+    InvalidBundleSignature,
+    /// A key of the bundle is not of the role it stands for.
+    RoleMismatch,
+    /// The prekey is unknown to the recipient, or a one-time prekey was already used.
+    UnknownPrekey,
+    /// The frank does not verify.
+    InvalidSignature,
+}
+
+impl fmt::Display for PrekeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PrekeyError::InvalidBundleSignature => write!(f, "invalid prekey signature"),
+            PrekeyError::RoleMismatch => write!(f, "prekey bundle key of the wrong role"),
+            PrekeyError::UnknownPrekey => write!(f, "unknown or used prekey"),
+            PrekeyError::InvalidSignature => write!(f, "invalid signature"),
+        }
+    }
+}
+
+impl core::error::Error for PrekeyError {}
+
+/// the message that the identity key signs for a prekey:
+/// `tag(PREKEY_LABEL) || tag(judge_public_key) || kind || id || prekey`, with `tag` as in
+/// `pok::domain`, kind 0 for the signed and 1 for one-time prekeys, and the id as a 4-byte
+/// big-endian integer
+fn prekey_message(
+    judge_public_key: &AMFPublicKey,
+    id: PrekeyId,
+    public_key: &AMFPublicKey,
+) -> Vec<u8> {
+    let judge_key = judge_public_key.public_key.compress().to_bytes();
+    let mut message = Vec::with_capacity(16 + PREKEY_LABEL.len() + 32 + 5 + 32);
+    for tagged in [PREKEY_LABEL, &judge_key] {
+        message.extend_from_slice(&(tagged.len() as u64).to_be_bytes());
+        message.extend_from_slice(tagged);
+    }
+    message.extend_from_slice(&id.to_bytes());
+    message.extend_from_slice(&public_key.public_key.compress().to_bytes());
+    message
+}
+
+impl SignedPrekey {
+    /// Whether the identity key signed this prekey for the judge.
+    pub fn verify(&self, identity_key: &AMFPublicKey, judge_public_key: &AMFPublicKey) -> bool {
+        schnorr_verify(
+            &SchnorrPublicKey {
+                public_key: identity_key.public_key,
+            },
+            &prekey_message(judge_public_key, self.id, &self.public_key),
+            &self.signature,
+        )
+    }
+}
+
+impl PrekeyBundle {
+    /// Checks the roles of the keys and the signatures on the prekeys, and returns the prekey to
+    /// frank to: the one-time prekey if there is one, or else the signed prekey.
+    pub fn verify(&self) -> Result<(PrekeyId, AMFPublicKey), PrekeyError> {
+        let mut prekeys = core::iter::once(&self.signed_prekey).chain(&self.one_time_prekey);
+        let roles_match = self.identity_key.role == AMFRole::Recipient
+            && self.judge_public_key.role == AMFRole::Judge
+            && prekeys
+                .clone()
+                .all(|prekey| prekey.public_key.role == AMFRole::Recipient);
+        if !roles_match {
+            return Err(PrekeyError::RoleMismatch);
+        }
+        let kinds_match = matches!(self.signed_prekey.id, PrekeyId::Signed(_))
+            && self
+                .one_time_prekey
+                .iter()
+                .all(|prekey| matches!(prekey.id, PrekeyId::OneTime(_)));
+        if !kinds_match
+            || !prekeys.all(|prekey| prekey.verify(&self.identity_key, &self.judge_public_key))
+        {
+            return Err(PrekeyError::InvalidBundleSignature);
+        }
+        let prekey = self.one_time_prekey.unwrap_or(self.signed_prekey);
+        Ok((prekey.id, prekey.public_key))
+    }
+}
+
+/// The recipient's secret prekeys, cf. the module documentation.
+#[derive(Debug)]
+pub struct PrekeyStore {
+    identity: (AMFPublicKey, AMFSecretKey),
+    judge_public_key: AMFPublicKey,
+    signed_prekey: (u32, AMFPublicKey, AMFSecretKey),
+    one_time_prekeys: Vec<(u32, AMFPublicKey, AMFSecretKey)>,
+    next_id: u32,
+}
+
+impl PrekeyStore {
+    /// A store for the recipient's identity key pair, with a fresh signed prekey and `count`
+    /// one-time prekeys; publish them with `published`.
+    pub fn generate_with_rng(
+        rng: &mut dyn CryptoRngCore,
+        identity: (AMFPublicKey, AMFSecretKey),
+        judge_public_key: AMFPublicKey,
+        count: u32,
+    ) -> Self {
+        let (public_key, secret_key) = keygen_with_rng(rng, AMFRole::Recipient);
+        let mut store = PrekeyStore {
+            identity,
+            judge_public_key,
+            signed_prekey: (0, public_key, secret_key),
+            one_time_prekeys: Vec::new(),
+            next_id: 1,
+        };
+        store.add_one_time_prekeys_with_rng(rng, count);
+        store
+    }
+
+    /// Generates `count` more one-time prekeys, and returns them signed, to upload.
+    pub fn add_one_time_prekeys_with_rng(
+        &mut self,
+        rng: &mut dyn CryptoRngCore,
+        count: u32,
+    ) -> Vec<SignedPrekey> {
+        (0..count)
+            .map(|_| {
+                let id = self.next_id;
+                self.next_id += 1;
+                let (public_key, secret_key) = keygen_with_rng(rng, AMFRole::Recipient);
+                self.one_time_prekeys.push((id, public_key, secret_key));
+                self.sign(rng, PrekeyId::OneTime(id), public_key)
+            })
+            .collect()
+    }
+
+    /// All prekeys, signed, to upload to the server.
+    pub fn published_with_rng(&self, rng: &mut dyn CryptoRngCore) -> PublishedPrekeys {
+        let (id, public_key, _) = &self.signed_prekey;
+        PublishedPrekeys {
+            identity_key: self.identity.0,
+            judge_public_key: self.judge_public_key,
+            signed_prekey: self.sign(rng, PrekeyId::Signed(*id), *public_key),
+            one_time_prekeys: self
+                .one_time_prekeys
+                .iter()
+                .map(|(id, public_key, _)| self.sign(rng, PrekeyId::OneTime(*id), *public_key))
+                .collect(),
+        }
+    }
+
+    /// Checks the frank of a first message from `sender_public_key`. A one-time prekey is removed
+    /// once a first message to it verifies.
+    pub fn verify_first(
+        &mut self,
+        sender_public_key: &AMFPublicKey,
+        message: &[u8],
+        first_frank: &FirstFrank,
+    ) -> Result<(), PrekeyError> {
+        let (position, public_key, secret_key) = match first_frank.prekey_id {
+            PrekeyId::Signed(id) if id == self.signed_prekey.0 => {
+                (None, &self.signed_prekey.1, &self.signed_prekey.2)
+            }
+            PrekeyId::OneTime(id) => {
+                let position = self
+                    .one_time_prekeys
+                    .iter()
+                    .position(|(one_time_id, _, _)| *one_time_id == id)
+                    .ok_or(PrekeyError::UnknownPrekey)?;
+                let (_, public_key, secret_key) = &self.one_time_prekeys[position];
+                (Some(position), public_key, secret_key)
+            }
+            PrekeyId::Signed(_) => return Err(PrekeyError::UnknownPrekey),
+        };
+        if !verify(
+            secret_key,
+            sender_public_key,
+            public_key,
+            self.judge_public_key,
+            message,
+            first_frank.amf_signature,
+        ) {
+            return Err(PrekeyError::InvalidSignature);
+        }
+        if let Some(position) = position {
+            self.one_time_prekeys.swap_remove(position);
+        }
+        Ok(())
+    }
+
+    /// signs a prekey with the identity key
+    fn sign(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        id: PrekeyId,
+        public_key: AMFPublicKey,
+    ) -> SignedPrekey {
+        let (_, identity_secret_key) = &self.identity;
+        let signature = schnorr_sign(
+            rng,
+            &SchnorrSecretKey {
+                secret_key: identity_secret_key.secret_key,
+            },
+            &prekey_message(&self.judge_public_key, id, &public_key),
+        );
+        SignedPrekey {
+            id,
+            public_key,
+            signature,
+        }
+    }
+}
+
+#[cfg(any(feature = "rand", test))]
+/// `frank_first_with_rng` with `rand::thread_rng`
+pub fn frank_first(
+    sender_secret_key: &AMFSecretKey,
+    sender_public_key: &AMFPublicKey,
+    bundle: &PrekeyBundle,
+    message: &[u8],
+) -> Result<FirstFrank, PrekeyError> {
+    frank_first_with_rng(
+        &mut rand::thread_rng(),
+        sender_secret_key,
+        sender_public_key,
+        bundle,
+        message,
+    )
+}
+
+/// Checks `bundle`, and franks the first message to its one-time prekey, or else to its signed
+/// prekey.
+pub fn frank_first_with_rng(
+    rng: &mut dyn CryptoRngCore,
+    sender_secret_key: &AMFSecretKey,
+    sender_public_key: &AMFPublicKey,
+    bundle: &PrekeyBundle,
+    message: &[u8],
+) -> Result<FirstFrank, PrekeyError> {
+    let (prekey_id, prekey) = bundle.verify()?;
+    Ok(FirstFrank {
+        prekey_id,
+        amf_signature: frank_with_rng(
+            rng,
+            sender_secret_key,
+            sender_public_key,
+            prekey,
+            bundle.judge_public_key,
+            message,
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amf::{judge, keygen};
+
+    #[test]
+    fn test_prekey_bundles() {
+        // 0. The recipient publishes a signed prekey and two one-time prekeys, and goes offline
+        let mut rng = rand::thread_rng();
+        let (judge_public_key, judge_secret_key) = keygen(AMFRole::Judge);
+        let mut store = PrekeyStore::generate_with_rng(
+            &mut rng,
+            keygen(AMFRole::Recipient),
+            judge_public_key,
+            2,
+        );
+        let mut server = store.published_with_rng(&mut rng);
+
+        // 1. A sender franks a first message to a one-time prekey, and the judge can judge it
+        let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+        let bundle = server.take_bundle();
+        let first = frank_first(&sender_secret_key, &sender_public_key, &bundle, b"hi").unwrap();
+        assert!(matches!(first.prekey_id, PrekeyId::OneTime(_)));
+        assert!(judge(
+            &judge_secret_key,
+            sender_public_key,
+            bundle.one_time_prekey.unwrap().public_key,
+            judge_public_key,
+            b"hi",
+            first.amf_signature,
+        ));
+
+        // 2. The recipient verifies it once; the one-time prekey is then used up
+        assert_eq!(
+            store.verify_first(&sender_public_key, b"bye", &first),
+            Err(PrekeyError::InvalidSignature)
+        );
+        assert_eq!(
+            store.verify_first(&sender_public_key, b"hi", &first),
+            Ok(())
+        );
+        assert_eq!(
+            store.verify_first(&sender_public_key, b"hi", &first),
+            Err(PrekeyError::UnknownPrekey)
+        );
+
+        // 3. Once the server runs out of one-time prekeys, first messages use the signed prekey
+        server.take_bundle();
+        let bundle = server.take_bundle();
+        assert_eq!(bundle.one_time_prekey, None);
+        let first = frank_first(&sender_secret_key, &sender_public_key, &bundle, b"hi").unwrap();
+        assert_eq!(first.prekey_id, PrekeyId::Signed(0));
+        assert_eq!(
+            store.verify_first(&sender_public_key, b"hi", &first),
+            Ok(())
+        );
+        assert_eq!(
+            store.verify_first(&sender_public_key, b"hi", &first),
+            Ok(())
+        );
+
+        // 4. A substituted prekey or judge, or a one-time prekey passed as signed, is rejected
+        let (other_public_key, _) = keygen(AMFRole::Recipient);
+        let mut substituted = bundle.clone();
+        substituted.signed_prekey.public_key = other_public_key;
+        let (other_judge_public_key, _) = keygen(AMFRole::Judge);
+        let mut other_judge = bundle.clone();
+        other_judge.judge_public_key = other_judge_public_key;
+        let mut relabeled = bundle.clone();
+        relabeled.signed_prekey = store.add_one_time_prekeys_with_rng(&mut rng, 1)[0];
+        for bundle in [substituted, other_judge, relabeled] {
+            assert_eq!(bundle.verify(), Err(PrekeyError::InvalidBundleSignature));
+        }
+    }
+}
@@ -0,0 +1,217 @@
+//! Verification with Prepared Public Keys
+//!
+//! A platform verifies or judges millions of franks against the same judge key. A
+//! `PreparedPublicKey` precomputes a table for it once (cf. `pok::batch::PreparedPoints`), and
+//! `verify_prepared` and `judge_prepared` check the equations of the proof pi in one multiscalar
+//! multiplication that takes the key's terms from that table, instead of multiplying the key
+//! from scratch for every frank. As with `batch_verify`, the equations are combined with random
+//! weights, so a bad proof passes with probability about 2^-128.
+#![allow(non_snake_case)]
+
+use alloc::sync::Arc;
+use core::fmt;
+
+use curve25519_dalek::{ristretto::RistrettoPoint, traits::Identity};
+use subtle::{Choice, ConstantTimeEq};
+
+use crate::pok::{
+    batch::{BatchVerifier, PreparedPoints},
+    elgamal::{ElGamalCiphertext, VerifiableElGamal},
+};
+
+use super::{spok_amf::AMFSPoK, AMFPublicKey, AMFSecretKey, AMFSignature};
+
+/// A public key with a precomputed table for verification; clones share the table.
+#[derive(Clone)]
+pub struct PreparedPublicKey {
+    pub public_key: AMFPublicKey,
+    points: Arc<PreparedPoints>,
+}
+
+impl PreparedPublicKey {
+    pub fn new(public_key: AMFPublicKey) -> Self {
+        PreparedPublicKey {
+            points: Arc::new(PreparedPoints::new([public_key.public_key])),
+            public_key,
+        }
+    }
+}
+
+impl From<AMFPublicKey> for PreparedPublicKey {
+    fn from(public_key: AMFPublicKey) -> Self {
+        Self::new(public_key)
+    }
+}
+
+impl fmt::Debug for PreparedPublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PreparedPublicKey")
+            .field("public_key", &self.public_key)
+            .finish_non_exhaustive()
+    }
+}
+
+/// `verify` with the judge's public key prepared
+pub fn verify_prepared(
+    recipient_secret_key: AMFSecretKey,
+    sender_public_key: AMFPublicKey,
+    _recipient_public_key: AMFPublicKey,
+    judge_public_key: &PreparedPublicKey,
+    message: &[u8],
+    amf_signature: AMFSignature,
+) -> bool {
+    verify_prepared_with(
+        &recipient_secret_key,
+        AMFSignature::recipient_ciphertext,
+        sender_public_key,
+        judge_public_key,
+        message,
+        &amf_signature,
+    )
+}
+
+/// `judge` with the judge's public key prepared
+pub fn judge_prepared(
+    judge_secret_key: AMFSecretKey,
+    sender_public_key: AMFPublicKey,
+    _recipient_public_key: AMFPublicKey,
+    judge_public_key: &PreparedPublicKey,
+    message: &[u8],
+    amf_signature: AMFSignature,
+) -> bool {
+    verify_prepared_with(
+        &judge_secret_key,
+        AMFSignature::judge_ciphertext,
+        sender_public_key,
+        judge_public_key,
+        message,
+        &amf_signature,
+    )
+}
+
+/// checks that `ciphertext` of the signature encrypts the identity under `secret_key`, and the
+/// proof with the prepared judge key
+fn verify_prepared_with(
+    secret_key: &AMFSecretKey,
+    ciphertext: fn(&AMFSignature) -> ElGamalCiphertext,
+    sender_public_key: AMFPublicKey,
+    judge_public_key: &PreparedPublicKey,
+    message: &[u8],
+    amf_signature: &AMFSignature,
+) -> bool {
+    let b0 = amf_signature.components_valid();
+    let b1 = VerifiableElGamal::decrypt(&secret_key.secret_key, &ciphertext(amf_signature))
+        .ct_eq(&RistrettoPoint::identity());
+
+    let spok = AMFSPoK::new(
+        sender_public_key.public_key,
+        judge_public_key.public_key.public_key,
+        amf_signature.J,
+        amf_signature.R,
+        amf_signature.E_J,
+    );
+    let mut batch = BatchVerifier::new();
+    let b2 = Choice::from(u8::from(spok.batch_verify(
+        &mut batch,
+        message,
+        amf_signature.pi,
+    )));
+
+    let b3 = Choice::from(u8::from(
+        batch.verify_prepared(&mut rand::thread_rng(), &judge_public_key.points),
+    ));
+    (b0 & b1 & b2 & b3).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amf::{frank, judge, keygen, verify, AMFRole};
+
+    #[test]
+    fn test_prepared_public_key() {
+        // 0. Prepare the judge's key once, and frank messages from several senders
+        let (recipient_public_key, recipient_secret_key) = keygen(AMFRole::Recipient);
+        let (judge_public_key, judge_secret_key) = keygen(AMFRole::Judge);
+        let prepared = PreparedPublicKey::new(judge_public_key);
+        for message in [&b"hello world!"[..], b"", b"goodbye world!"] {
+            let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+            let amf_signature = frank(
+                sender_secret_key,
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+            );
+
+            // 1. The prepared functions agree with verify and judge
+            assert!(verify(
+                recipient_secret_key.clone(),
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+                amf_signature,
+            ));
+            assert!(verify_prepared(
+                recipient_secret_key.clone(),
+                sender_public_key,
+                recipient_public_key,
+                &prepared,
+                message,
+                amf_signature,
+            ));
+            assert!(judge(
+                judge_secret_key.clone(),
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+                amf_signature,
+            ));
+            assert!(judge_prepared(
+                judge_secret_key.clone(),
+                sender_public_key,
+                recipient_public_key,
+                &prepared,
+                message,
+                amf_signature,
+            ));
+
+            // 2. Other messages, senders, judges and judge secret keys are rejected
+            assert!(!judge_prepared(
+                judge_secret_key.clone(),
+                sender_public_key,
+                recipient_public_key,
+                &prepared,
+                b"tampered",
+                amf_signature,
+            ));
+            assert!(!judge_prepared(
+                judge_secret_key.clone(),
+                keygen(AMFRole::Sender).0,
+                recipient_public_key,
+                &prepared,
+                message,
+                amf_signature,
+            ));
+            let (other_public_key, other_secret_key) = keygen(AMFRole::Judge);
+            assert!(!verify_prepared(
+                recipient_secret_key.clone(),
+                sender_public_key,
+                recipient_public_key,
+                &other_public_key.into(),
+                message,
+                amf_signature,
+            ));
+            assert!(!judge_prepared(
+                other_secret_key,
+                sender_public_key,
+                recipient_public_key,
+                &prepared,
+                message,
+                amf_signature,
+            ));
+        }
+    }
+}
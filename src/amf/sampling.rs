@@ -0,0 +1,266 @@
+//! Auditable Sampling of Reports for Review
+//!
+//! A judge who cannot review every report reviews a random sample. Here the judge's VRF (cf.
+//! `pok::vrf`) picks the sample. A report is sampled if the VRF output for its identifier, read as
+//! a 64-bit integer, is below the threshold of the `SamplingRate`. The VRF proof comes with the
+//! decision, so anyone who knows the judge's public key can check the decision with
+//! `verify_sample`. The judge can neither steer which reports are reviewed nor pretend a report was
+//! not drawn. Nobody else can predict the sample before the judge publishes it.
+//!
+//! The VRF input is `tag(SAMPLING_LABEL) || tag(round) || report identifier`, with `tag` as in
+//! `pok::domain`. The round, e.g. an audit period, lets a judge sample the same reports again
+//! independently. Use a judge key that does nothing but sample, so that its VRF outputs are not
+//! tied to the key that judges franks.
+//!
+//! Sampling is only as auditable as the list of reports. `verify_selection` checks a whole round,
+//! but only over the identifiers it is given, so publish or commit to them, e.g. in a transparency
+//! log.
+
+use alloc::vec::Vec;
+
+use rand_core::CryptoRngCore;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
+
+use super::{AMFPublicKey, AMFSecretKey, AMFSignature};
+use crate::pok::{
+    domain::{absorb, SAMPLING_LABEL},
+    vrf::{self, VrfOutput, VrfProof},
+};
+
+/// The fraction of reports that are sampled, as a threshold on 64-bit integers.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SamplingRate {
+    /// a report is sampled if its VRF output, as a big-endian integer, is below this
+    pub threshold: u64,
+}
+
+impl SamplingRate {
+    /// Samples `numerator / denominator` of the reports, up to rounding to multiples of 2^-64;
+    /// all of them if `numerator >= denominator`.
+    pub fn from_fraction(numerator: u64, denominator: u64) -> Self {
+        assert!(denominator > 0, "the denominator of a rate is positive");
+        let threshold = (u128::from(numerator) << 64) / u128::from(denominator);
+        SamplingRate {
+            threshold: u64::try_from(threshold).unwrap_or(u64::MAX),
+        }
+    }
+
+    /// Whether a VRF output falls in the sample.
+    pub fn selects(&self, output: &VrfOutput) -> bool {
+        let draw = u64::from_be_bytes(output[..8].try_into().unwrap());
+        // A threshold of u64::MAX is a rate of 1, which also selects the largest draw
+        draw < self.threshold || (self.threshold == u64::MAX && draw == u64::MAX)
+    }
+}
+
+/// Whether a report was sampled, and the judge's VRF proof of it.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SamplingDecision {
+    pub report_id: Vec<u8>,
+    pub selected: bool,
+    pub proof: VrfProof,
+}
+
+/// An identifier of a report, for reports that have none of their own: a hash of the message and
+/// the signature. The judge gets both with every report.
+pub fn report_id(message: &[u8], amf_signature: &AMFSignature) -> [u8; 64] {
+    let mut hasher = Sha512::new();
+    absorb(&mut hasher, SAMPLING_LABEL);
+    absorb(&mut hasher, &amf_signature.to_bytes());
+    absorb(&mut hasher, message);
+    hasher.finalize().into()
+}
+
+/// the VRF input of a report in a round, cf. the module documentation
+fn vrf_input(round: &[u8], report_id: &[u8]) -> Vec<u8> {
+    let mut input = Vec::with_capacity(16 + SAMPLING_LABEL.len() + round.len() + report_id.len());
+    for tagged in [SAMPLING_LABEL, round] {
+        input.extend_from_slice(&(tagged.len() as u64).to_be_bytes());
+        input.extend_from_slice(tagged);
+    }
+    input.extend_from_slice(report_id);
+    input
+}
+
+#[cfg(any(feature = "rand", test))]
+/// `sample_with_rng` with `rand::thread_rng`
+pub fn sample(
+    judge_secret_key: &AMFSecretKey,
+    round: &[u8],
+    rate: SamplingRate,
+    report_id: &[u8],
+) -> SamplingDecision {
+    sample_with_rng(
+        &mut rand::thread_rng(),
+        judge_secret_key,
+        round,
+        rate,
+        report_id,
+    )
+}
+
+/// Decides whether the report `report_id` is sampled in `round`, with a proof of the decision.
+pub fn sample_with_rng(
+    rng: &mut dyn CryptoRngCore,
+    judge_secret_key: &AMFSecretKey,
+    round: &[u8],
+    rate: SamplingRate,
+    report_id: &[u8],
+) -> SamplingDecision {
+    let (output, proof) = vrf::prove(
+        rng,
+        &judge_secret_key.secret_key,
+        &vrf_input(round, report_id),
+    );
+    SamplingDecision {
+        report_id: report_id.to_vec(),
+        selected: rate.selects(&output),
+        proof,
+    }
+}
+
+/// Checks a decision of `sample` with the judge's public key: the proof is valid, and the report
+/// is selected if and only if the VRF output falls in the sample.
+pub fn verify_sample(
+    judge_public_key: &AMFPublicKey,
+    round: &[u8],
+    rate: SamplingRate,
+    decision: &SamplingDecision,
+) -> bool {
+    match vrf::verify(
+        &judge_public_key.public_key,
+        &vrf_input(round, &decision.report_id),
+        &decision.proof,
+    ) {
+        Some(output) => rate.selects(&output) == decision.selected,
+        None => false,
+    }
+}
+
+/// `sample_with_rng` for every report of a round.
+pub fn select_with_rng(
+    rng: &mut dyn CryptoRngCore,
+    judge_secret_key: &AMFSecretKey,
+    round: &[u8],
+    rate: SamplingRate,
+    report_ids: &[&[u8]],
+) -> Vec<SamplingDecision> {
+    report_ids
+        .iter()
+        .map(|report_id| sample_with_rng(rng, judge_secret_key, round, rate, report_id))
+        .collect()
+}
+
+/// Checks that `decisions` decide exactly the reports `report_ids`, in order, and that every
+/// decision verifies; an auditor then knows that the selected reports are the whole sample.
+pub fn verify_selection(
+    judge_public_key: &AMFPublicKey,
+    round: &[u8],
+    rate: SamplingRate,
+    report_ids: &[&[u8]],
+    decisions: &[SamplingDecision],
+) -> bool {
+    report_ids.len() == decisions.len()
+        && report_ids
+            .iter()
+            .zip(decisions)
+            .all(|(report_id, decision)| {
+                decision.report_id == *report_id
+                    && verify_sample(judge_public_key, round, rate, decision)
+            })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amf::{frank, keygen, AMFRole};
+
+    #[test]
+    fn test_sampling_rate() {
+        // 0. The threshold is the fraction of 2^64, and full rates select every output
+        assert_eq!(SamplingRate::from_fraction(0, 10).threshold, 0);
+        assert_eq!(SamplingRate::from_fraction(1, 2).threshold, 1 << 63);
+        let all = SamplingRate::from_fraction(3, 3);
+        assert!(all.selects(&[0xff; 64]) && all.selects(&[0; 64]));
+        assert!(!SamplingRate::from_fraction(0, 1).selects(&[0; 64]));
+    }
+
+    #[test]
+    fn test_report_sampling() {
+        let (judge_public_key, judge_secret_key) = keygen(AMFRole::Judge);
+        let rate = SamplingRate::from_fraction(1, 4);
+
+        // 0. Sample the reports of a round
+        let ids: Vec<[u8; 8]> = (0u64..200).map(u64::to_be_bytes).collect();
+        let report_ids: Vec<&[u8]> = ids.iter().map(|id| &id[..]).collect();
+        let mut rng = rand::thread_rng();
+        let decisions = select_with_rng(&mut rng, &judge_secret_key, b"2026-10", rate, &report_ids);
+        assert!(verify_selection(
+            &judge_public_key,
+            b"2026-10",
+            rate,
+            &report_ids,
+            &decisions
+        ));
+
+        // 1. About a quarter is selected, and sampling again selects the same reports
+        let selected = decisions
+            .iter()
+            .filter(|decision| decision.selected)
+            .count();
+        assert!((20..=80).contains(&selected), "{selected} of 200");
+        let again = select_with_rng(&mut rng, &judge_secret_key, b"2026-10", rate, &report_ids);
+        assert!(decisions
+            .iter()
+            .zip(&again)
+            .all(|(decision, again)| decision.selected == again.selected));
+
+        // 2. A flipped decision, another round, or a dropped report is caught
+        let mut flipped = decisions.clone();
+        flipped[0].selected = !flipped[0].selected;
+        assert!(!verify_selection(
+            &judge_public_key,
+            b"2026-10",
+            rate,
+            &report_ids,
+            &flipped
+        ));
+        assert!(!verify_sample(
+            &judge_public_key,
+            b"2026-11",
+            rate,
+            &decisions[0]
+        ));
+        assert!(!verify_selection(
+            &judge_public_key,
+            b"2026-10",
+            rate,
+            &report_ids,
+            &decisions[1..]
+        ));
+
+        // 3. Reports of franks have identifiers of their own
+        let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+        let (recipient_public_key, _) = keygen(AMFRole::Recipient);
+        let amf_signature = frank(
+            &sender_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            b"hello world!",
+        );
+        let id = report_id(b"hello world!", &amf_signature);
+        assert_ne!(id, report_id(b"goodbye world!", &amf_signature));
+        let decision = sample(&judge_secret_key, b"2026-10", rate, &id);
+        assert!(verify_sample(
+            &judge_public_key,
+            b"2026-10",
+            rate,
+            &decision
+        ));
+    }
+}
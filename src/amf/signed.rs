@@ -0,0 +1,275 @@
+//! Opt-In Non-Repudiation
+//!
+//! Some messages should be attributable by anyone, e.g. announcements of official accounts. A
+//! `SignedFrank` carries, next to the deniable AMF signature, an ordinary Schnorr signature (cf.
+//! `pok::schnorr_signature`) under the sender's AMF public key, which anyone can check with
+//! `verify_public`. The recipient and the judge check both with `verify_signed` and
+//! `judge_signed`.
+//!
+//! The Schnorr signature signs `tag(SIGNED_LABEL) || tag(AMF signature) || message`, with `tag` as
+//! in `pok::domain` and the AMF signature in the wire format, so it cannot be moved to another
+//! frank. Such messages are NOT deniable: only use `frank_signed` for messages that the sender
+//! wants to be publicly attributed.
+//!
+//! The wire format uses the suite `SUITE_RISTRETTO255_SHA512_SIGNED`: the header, the body of the
+//! AMF signature, then the commitment and the response of the Schnorr signature.
+
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+
+use rand_core::CryptoRngCore;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{
+    codec::{
+        CodecError, WireArtifactKind, WireHeader, WireReader, SIGNATURE_BODY_LENGTH,
+        SUITE_RISTRETTO255_SHA512_SIGNED, WIRE_FORMAT_VERSION, WIRE_HEADER_LENGTH,
+    },
+    frank_with_rng, judge, verify, AMFPublicKey, AMFSecretKey, AMFSignature,
+    CompressedAMFSignature,
+};
+use crate::pok::{
+    domain::SIGNED_LABEL,
+    fiat_shamir::FiatShamirSignature,
+    schnorr_signature::{
+        schnorr_sign, schnorr_verify, SchnorrPublicKey, SchnorrSecretKey, SchnorrSignature,
+    },
+};
+
+/// An AMF signature and a publicly verifiable signature of the same message.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SignedFrank {
+    pub amf_signature: AMFSignature,
+    pub signature: SchnorrSignature,
+}
+
+/// the message of the Schnorr signature, cf. the module documentation
+fn bind_signature(amf_signature: &AMFSignature, message: &[u8]) -> Vec<u8> {
+    let amf_signature = amf_signature.to_bytes();
+    let mut bound =
+        Vec::with_capacity(16 + SIGNED_LABEL.len() + amf_signature.len() + message.len());
+    for tagged in [SIGNED_LABEL, &amf_signature] {
+        bound.extend_from_slice(&(tagged.len() as u64).to_be_bytes());
+        bound.extend_from_slice(tagged);
+    }
+    bound.extend_from_slice(message);
+    bound
+}
+
+#[cfg(any(feature = "rand", test))]
+/// `frank_signed_with_rng` with `rand::thread_rng`
+pub fn frank_signed(
+    sender_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    message: &[u8],
+) -> SignedFrank {
+    frank_signed_with_rng(
+        &mut rand::thread_rng(),
+        sender_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        message,
+    )
+}
+
+/// Franks `message`, and signs it publicly with the sender's key.
+pub fn frank_signed_with_rng(
+    rng: &mut dyn CryptoRngCore,
+    sender_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    message: &[u8],
+) -> SignedFrank {
+    let (sender_secret_key, sender_public_key) =
+        (sender_secret_key.borrow(), sender_public_key.borrow());
+    let amf_signature = frank_with_rng(
+        rng,
+        sender_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        message,
+    );
+    let signature = schnorr_sign(
+        rng,
+        &SchnorrSecretKey {
+            secret_key: sender_secret_key.secret_key,
+        },
+        &bind_signature(&amf_signature, message),
+    );
+    SignedFrank {
+        amf_signature,
+        signature,
+    }
+}
+
+/// Checks the public signature of `signed_frank` on `message` under the sender's key; needs no
+/// secret key.
+pub fn verify_public(
+    sender_public_key: &AMFPublicKey,
+    message: &[u8],
+    signed_frank: &SignedFrank,
+) -> bool {
+    schnorr_verify(
+        &SchnorrPublicKey {
+            public_key: sender_public_key.public_key,
+        },
+        &bind_signature(&signed_frank.amf_signature, message),
+        &signed_frank.signature,
+    )
+}
+
+/// `amf::verify` and `verify_public`.
+pub fn verify_signed(
+    recipient_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    message: &[u8],
+    signed_frank: &SignedFrank,
+) -> bool {
+    let sender_public_key = sender_public_key.borrow();
+    verify(
+        recipient_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        message,
+        signed_frank.amf_signature,
+    ) & verify_public(sender_public_key, message, signed_frank)
+}
+
+/// `amf::judge` and `verify_public`.
+pub fn judge_signed(
+    judge_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    message: &[u8],
+    signed_frank: &SignedFrank,
+) -> bool {
+    let sender_public_key = sender_public_key.borrow();
+    judge(
+        judge_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        message,
+        signed_frank.amf_signature,
+    ) & verify_public(sender_public_key, message, signed_frank)
+}
+
+impl SignedFrank {
+    /// The length of the wire encoding in bytes.
+    pub const WIRE_LENGTH: usize = WIRE_HEADER_LENGTH + SIGNATURE_BODY_LENGTH + 2 * 32;
+
+    /// Encodes the signed frank in the wire format, cf. the module documentation.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(Self::WIRE_LENGTH);
+        WireHeader::write_suite(
+            &mut buf,
+            SUITE_RISTRETTO255_SHA512_SIGNED,
+            WireArtifactKind::Signature,
+        );
+        buf.extend(&self.amf_signature.to_bytes()[WIRE_HEADER_LENGTH..]);
+        buf.extend(self.signature.to_bytes());
+        buf
+    }
+
+    /// Decodes a signed frank, checking the AMF signature as `AMFSignature::from_bytes` does.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CodecError> {
+        let (header, body) = WireHeader::read(bytes, WireArtifactKind::Signature)?;
+        if (header.version, header.suite) != (WIRE_FORMAT_VERSION, SUITE_RISTRETTO255_SHA512_SIGNED)
+        {
+            return Err(header.unsupported());
+        }
+        // checks the length of the whole body first
+        WireReader::new(body, SIGNATURE_BODY_LENGTH + 2 * 32)?;
+        let (amf_body, schnorr_body) = body.split_at(SIGNATURE_BODY_LENGTH);
+        let amf_signature =
+            CompressedAMFSignature::from_body(amf_body.try_into().unwrap()).decompress()?;
+        let mut reader = WireReader::new(schnorr_body, 2 * 32)?;
+        Ok(SignedFrank {
+            amf_signature,
+            signature: SchnorrSignature(FiatShamirSignature {
+                prover_commitment: reader.read_point()?,
+                prover_response: reader.read_scalar()?,
+            }),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amf::{keygen, AMFRole};
+
+    #[test]
+    fn test_signed_franking() {
+        // 0. Frank and sign an announcement
+        let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+        let (recipient_public_key, recipient_secret_key) = keygen(AMFRole::Recipient);
+        let (judge_public_key, judge_secret_key) = keygen(AMFRole::Judge);
+        let message = b"hello world!";
+        let signed_frank = frank_signed(
+            &sender_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+        );
+
+        // 1. The recipient, the judge, and anyone else attribute it to the sender
+        assert!(verify_signed(
+            &recipient_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            &signed_frank,
+        ));
+        assert!(judge_signed(
+            &judge_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            &signed_frank,
+        ));
+        assert!(verify_public(&sender_public_key, message, &signed_frank));
+
+        // 2. The public signature is bound to the message, the sender and the AMF signature
+        let (other_public_key, other_secret_key) = keygen(AMFRole::Sender);
+        assert!(!verify_public(
+            &sender_public_key,
+            b"goodbye world!",
+            &signed_frank
+        ));
+        assert!(!verify_public(&other_public_key, message, &signed_frank));
+        let mut moved = signed_frank;
+        moved.amf_signature = frank_signed(
+            &other_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+        )
+        .amf_signature;
+        assert!(!verify_public(&sender_public_key, message, &moved));
+
+        // 3. The wire format round-trips, and other suites are rejected
+        let bytes = signed_frank.to_bytes();
+        assert_eq!(bytes.len(), SignedFrank::WIRE_LENGTH);
+        assert_eq!(SignedFrank::from_bytes(&bytes), Ok(signed_frank));
+        assert_eq!(
+            SignedFrank::from_bytes(&signed_frank.amf_signature.to_bytes()),
+            Err(CodecError::UnsupportedSuite(1))
+        );
+    }
+}
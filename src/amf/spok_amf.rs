@@ -5,36 +5,129 @@
 //! [AMF]: https://eprint.iacr.org/2019/565/20190527:092413
 #![allow(non_snake_case)]
 
-use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use alloc::vec::Vec;
 
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar, traits::Identity};
+#[cfg(feature = "parallel")]
+use rand::{rngs::StdRng, SeedableRng};
+use rand_core::CryptoRngCore;
+use subtle::{Choice, ConstantTimeEq};
+
+use super::franking::{AMFInternalSignature, VerifyError};
+#[cfg(not(feature = "parallel"))]
+use crate::pok::fiat_shamir::{FiatShamirSecretKey, SignatureScheme};
 use crate::pok::{
-    and_proof::{AndProver, AndVerifier},
-    chaum_pedersen::{
-        ChaumPedersenProver, ChaumPedersenProverCommitment, ChaumPedersenVerifier,
-        ChaumPedersenWitnessStatement,
-    },
+    and_proof::AndProof,
+    chaum_pedersen::ChaumPedersenProof,
+    domain::{tag, GROUP_LABEL},
+    elgamal::{ElGamalCiphertext, VerifiableElGamal},
+    error::PokError,
     fiat_shamir::FiatShamir,
-    or_proof::{OrProver, OrProverResponse, OrVerifier, OrWitness},
-    schnorr::{SchnorrProver, SchnorrVerifier},
+    linear_sigma::SigmaProtocol,
+    or_proof::{OrProof, OrWitness},
+    schnorr::SchnorrProof,
 };
 
-pub type AMFSPoK = FiatShamir<
-    (OrWitness<Scalar, Scalar>, OrWitness<Scalar, Scalar>),
-    (
-        (RistrettoPoint, RistrettoPoint),
-        (ChaumPedersenWitnessStatement, RistrettoPoint),
-    ),
-    (
-        (RistrettoPoint, RistrettoPoint),
-        (ChaumPedersenProverCommitment, RistrettoPoint),
-    ),
-    (
-        OrProverResponse<Scalar, Scalar>,
-        OrProverResponse<Scalar, Scalar>,
-    ),
->;
+/// the OR of the Schnorr proofs for sender_public_key = g^t and J = g^u
+type AMFSenderOrProof = OrProof<SchnorrProof, SchnorrProof>;
+
+/// the OR of the Chaum-Pedersen proof for (J = judge_public_key^v && E_J = g^v) and the Schnorr
+/// proof for R = g^w
+type AMFJudgeOrProof = OrProof<ChaumPedersenProof, SchnorrProof>;
+
+pub type AMFSPoK = FiatShamir<AndProof<AMFSenderOrProof, AMFJudgeOrProof>>;
+
+/// the witness for the sender's and the judge's OR proofs
+pub type AMFWitness = (OrWitness<Scalar, Scalar>, OrWitness<Scalar, Scalar>);
+
+/// The message that every AMF signature of a group frank signs, cf. `amf::group`:
+/// `tag(GROUP_LABEL) || tag(group_id) || tag(sender_1) || tag(recipient_1) || ... ||
+/// tag(sender_n) || tag(recipient_n) || message`, with the compressed sender and recipient public
+/// keys of the members and `tag` as in `pok::domain`. The roster is bound through the message
+/// only; the statement of the proof is that of `amf::frank`. The tags of the keys delimit the
+/// roster, so a frank in one group does not verify in another, nor with a member added or removed.
+pub fn bind_members(
+    group_id: &[u8],
+    members: &[(RistrettoPoint, RistrettoPoint)],
+    message: &[u8],
+) -> Vec<u8> {
+    let mut bound = Vec::with_capacity(
+        8 * (2 + 2 * members.len()) + GROUP_LABEL.len() + group_id.len() + 64 * members.len(),
+    );
+    let keys: Vec<[u8; 32]> = members
+        .iter()
+        .flat_map(|(sender_public_key, recipient_public_key)| {
+            [sender_public_key, recipient_public_key]
+        })
+        .map(|public_key| public_key.compress().to_bytes())
+        .collect();
+    tag(&mut bound, GROUP_LABEL);
+    tag(&mut bound, group_id);
+    for key in &keys {
+        tag(&mut bound, key);
+    }
+    bound.extend_from_slice(message);
+    bound
+}
+
+/// The public inputs of the AMF proof of knowledge, cf. Fig. 5 in [AMF]: the sender knows the
+/// secret key of `sender_public_key` or the discrete logarithm of J, and (E_J, J) encrypts the
+/// identity under `judge_public_key` or the discrete logarithm of R is known.
+///
+/// [AMF]: https://eprint.iacr.org/2019/565/20190527:092413
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AMFStatement {
+    pub sender_public_key: RistrettoPoint,
+    pub judge_public_key: RistrettoPoint,
+    pub J: RistrettoPoint,
+    pub R: RistrettoPoint,
+    pub E_J: RistrettoPoint,
+}
+
+impl AMFStatement {
+    /// Checks that neither the sender's nor the judge's public key is the identity: anyone knows
+    /// the secret key of an identity sender key, and an identity judge key makes (E_J, J) encrypt
+    /// the identity for any J and E_J.
+    pub fn validate(&self) -> Result<(), VerifyError> {
+        if bool::from(self.keys_valid()) {
+            Ok(())
+        } else {
+            Err(VerifyError::InconsistentStatement)
+        }
+    }
+
+    /// `validate` as a `Choice`, for the verifiers
+    pub(crate) fn keys_valid(&self) -> Choice {
+        let identity = RistrettoPoint::identity();
+        !(self.sender_public_key.ct_eq(&identity) | self.judge_public_key.ct_eq(&identity))
+    }
+}
 
 impl AMFSPoK {
+    /// The proof of knowledge of `statement`.
+    pub fn from_statement(statement: &AMFStatement) -> Self {
+        Self::new(
+            statement.sender_public_key,
+            statement.judge_public_key,
+            statement.J,
+            statement.R,
+            statement.E_J,
+        )
+    }
+
+    /// The statement that the proof of knowledge proves, read back from its sigma protocols.
+    pub fn statement(&self) -> AMFStatement {
+        let (or0, or1) = (&self.protocol.s0, &self.protocol.s1);
+        let judge_statement = or1.s0.witness_statement;
+        AMFStatement {
+            sender_public_key: or0.s0.witness_statement,
+            judge_public_key: judge_statement.u,
+            J: or0.s1.witness_statement,
+            R: or1.s1.witness_statement,
+            E_J: judge_statement.v,
+        }
+    }
+
     pub fn new(
         sender_public_key: RistrettoPoint,
         judge_public_key: RistrettoPoint,
@@ -43,69 +136,126 @@ impl AMFSPoK {
         E_J: RistrettoPoint,
     ) -> Self {
         // 0. Initialize Schnorr for the statement sender_public_key = g^t; cf. Fig 5 of [AMF]
-        let s0_prover = SchnorrProver::new(sender_public_key);
-        let s0_verifier = SchnorrVerifier::new(sender_public_key);
+        let s0 = SchnorrProof::new(sender_public_key);
 
         // 1. Initialize Schnorr for the statement J = g^u; cf. Fig 5 of [AMF]
-        let s1_prover = SchnorrProver::new(J);
-        let s1_verifier = SchnorrVerifier::new(J);
+        let s1 = SchnorrProof::new(J);
 
         // 2. Combine the Schnorr proofs s0 and s1 into an OR proof or0
-        let or0_prover = OrProver {
-            s0_prover: Box::new(s0_prover),
-            s0_verifier: Box::new(s0_verifier),
-            s1_prover: Box::new(s1_prover),
-            s1_verifier: Box::new(s1_verifier),
-            witness: None,
-            per_verifier_secret: None,
-        };
-        let or0_verifier = OrVerifier {
-            s0_verifier: Box::new(s0_verifier),
-            s1_verifier: Box::new(s1_verifier),
-        };
+        let or0 = s0.or(s1);
 
-        // 3. Initialize Chaum-Pedersen for the statement (J = judge_public_key^v && E_j = g^v); cf. Fig 5 of [AMF]
-        let s3_witness_statement = ChaumPedersenWitnessStatement {
-            u: judge_public_key,
-            v: E_J,
-            w: J,
-        };
-        let s2_prover = ChaumPedersenProver::new(s3_witness_statement);
-        let s2_verifier = ChaumPedersenVerifier::new(s3_witness_statement);
+        // 3. Initialize Chaum-Pedersen for the statement (J = judge_public_key^v && E_j = g^v),
+        //    i.e., that (E_J, J) encrypts the identity under judge_public_key; cf. Fig 5 of [AMF]
+        let s2 = VerifiableElGamal::new(judge_public_key).well_formedness_protocol(
+            &ElGamalCiphertext { c1: E_J, c2: J },
+            &RistrettoPoint::identity(),
+        );
 
         // 4. Initialize Schnorr for the statement R = g^w; cf. Fig 5 of [AMF]
-        let s3_prover = SchnorrProver::new(R);
-        let s3_verifier = SchnorrVerifier::new(R);
+        let s3 = SchnorrProof::new(R);
 
         // 5. Combine the Chaum-Pedersen and Schnorr proofs s2 and s3 into an OR proof or1
-        let or1_prover = OrProver {
-            s0_prover: Box::new(s2_prover),
-            s0_verifier: Box::new(s2_verifier),
-            s1_prover: Box::new(s3_prover),
-            s1_verifier: Box::new(s3_verifier),
-            witness: None,
-            per_verifier_secret: None,
-        };
-        let or1_verifier = OrVerifier {
-            s0_verifier: Box::new(s2_verifier),
-            s1_verifier: Box::new(s3_verifier),
-        };
+        let or1 = s2.or(s3);
 
-        // 6. Combine the OR proofs or0 and or1 into an AND proof and
-        let and_prover = AndProver {
-            s0_prover: Box::new(or0_prover),
-            s1_prover: Box::new(or1_prover),
-        };
-        let and_verifier = AndVerifier {
-            s0_verifier: Box::new(or0_verifier),
-            s1_verifier: Box::new(or1_verifier),
-        };
+        // 6. Combine the OR proofs or0 and or1 into an AND proof and, and finally create a
+        //    Fiat-Shamir Signature Scheme from it
+        or0.and(or1).fiat_shamir()
+    }
 
-        // 7. Finally, create a Fiat-Shamir Signature Scheme from the AND proof and
+    /// `sign` with the AMF witness; with the `parallel` feature, the commitments of the two OR
+    /// proofs are computed on two threads, and each draws its randomness from a generator seeded
+    /// from `rng`, since they cannot share it
+    pub(crate) fn sign_witness(
+        &mut self,
+        rng: &mut dyn CryptoRngCore,
+        witness: AMFWitness,
+        message: &[u8],
+    ) -> Result<AMFInternalSignature, PokError> {
+        #[cfg(not(feature = "parallel"))]
+        return self.sign(rng, FiatShamirSecretKey { witness }, message);
 
-        FiatShamir {
-            prover: Box::from(and_prover),
-            verifier: Box::from(and_verifier),
+        #[cfg(feature = "parallel")]
+        {
+            let mut s0_rng = StdRng::from_rng(&mut *rng).expect("a CryptoRngCore seeds StdRng");
+            let mut s1_rng = StdRng::from_rng(&mut *rng).expect("a CryptoRngCore seeds StdRng");
+            let (s0, s1) = rayon::join(
+                || self.protocol.s0.generate_commitment(&mut s0_rng, witness.0),
+                || self.protocol.s1.generate_commitment(&mut s1_rng, witness.1),
+            );
+            let ((s0_commitment, s0_state), (s1_commitment, s1_state)) = (s0?, s1?);
+            Ok(self.sign_with_commitment(
+                message,
+                (s0_commitment, s1_commitment),
+                (s0_state, s1_state),
+            ))
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+
+    use super::*;
+    use crate::{
+        amf::{frank, keygen, AMFRole},
+        pok::{
+            chaum_pedersen::ChaumPedersenProverCommitment, fiat_shamir::SignatureScheme,
+            or_proof::OrProverResponse,
+        },
+    };
+
+    #[test]
+    fn test_rebuild_and_cross_check() {
+        // 0. Frank a message
+        let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+        let (recipient_public_key, _) = keygen(AMFRole::Recipient);
+        let (judge_public_key, _) = keygen(AMFRole::Judge);
+        let message = b"hello world!";
+        let amf_signature = frank(
+            &sender_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+        );
+
+        // 1. The statement reads back from the proof of knowledge
+        let statement = amf_signature.statement(&sender_public_key, &judge_public_key);
+        let spok = AMFSPoK::from_statement(&statement);
+        assert_eq!(spok.statement(), statement);
+
+        // 2. pi rebuilt from its parts verifies
+        let pi = amf_signature.pi;
+        let ((t_0, t_1), (judge_commitment, t_3)) = pi.prover_commitment;
+        let (or0_response, or1_response) = pi.prover_response;
+        let rebuilt = AMFInternalSignature {
+            prover_commitment: (
+                (t_0, t_1),
+                (
+                    ChaumPedersenProverCommitment::new(
+                        judge_commitment.v_t(),
+                        judge_commitment.w_t(),
+                    ),
+                    t_3,
+                ),
+            ),
+            prover_response: (
+                OrProverResponse::new(or0_response.c_0(), or0_response.z_0(), or0_response.z_1()),
+                OrProverResponse::new(or1_response.c_0(), or1_response.z_0(), or1_response.z_1()),
+            ),
+        };
+        assert_eq!(rebuilt, pi);
+        assert!(spok.verify(message, rebuilt));
+
+        // 3. The sender's OR proof checks by hand against the challenge, with c_1 = c_0 + c
+        let c = spok.challenge(message, &pi.prover_commitment);
+        let (c_0, c_1) = (or0_response.c_0(), or0_response.c_0() + c);
+        let g = RISTRETTO_BASEPOINT_POINT;
+        assert_eq!(
+            t_0,
+            g * or0_response.z_0() - statement.sender_public_key * c_0
+        );
+        assert_eq!(t_1, g * or0_response.z_1() - statement.J * c_1);
+    }
+}
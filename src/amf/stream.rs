@@ -0,0 +1,277 @@
+//! A framed container format for long sequences of franked messages.
+//!
+//! Meant for exporting and importing moderation evidence to disk or through pipes. A stream is a
+//! short header followed by length-prefixed records, each protected by a CRC-32 checksum so that
+//! truncation and corruption are detected per record:
+//!
+//! ```text
+//! stream: magic "AMFS" (4) || version (1) || record*
+//! record: length (4, big-endian) || signature (AMFSignature::WIRE_LENGTH) || message
+//!         || crc32(signature || message) (4, big-endian)
+//! ```
+//!
+//! `length` counts the signature and message bytes only.
+
+use std::{
+    fmt,
+    io::{self, Read, Write},
+};
+
+use super::{codec::CodecError, AMFSignature};
+
+/// The magic value that starts every signature stream.
+pub const STREAM_MAGIC: [u8; 4] = *b"AMFS";
+
+/// The version of the signature stream format.
+pub const STREAM_FORMAT_VERSION: u8 = 1;
+
+/// The largest record a `SignatureStreamReader` accepts, to bound allocations on corrupt input.
+pub const MAX_RECORD_LENGTH: usize = 16 * 1024 * 1024;
+
+/// A franked message read from a signature stream.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignatureRecord {
+    pub message: Vec<u8>,
+    pub signature: AMFSignature,
+}
+
+/// Errors that can occur while reading or writing a signature stream.
+#[derive(Debug)]
+pub enum StreamError {
+    /// The underlying reader or writer failed, or the stream ended mid-record.
+    Io(io::Error),
+    /// The stream did not start with `STREAM_MAGIC`.
+    InvalidMagic,
+    /// The stream uses a format version this library does not understand.
+    UnsupportedVersion(u8),
+    /// A record is shorter than a signature, or longer than `MAX_RECORD_LENGTH`.
+    InvalidRecordLength { record: usize, length: usize },
+    /// A record's checksum does not match its contents.
+    ChecksumMismatch { record: usize },
+    /// A record's signature did not decode.
+    Codec { record: usize, error: CodecError },
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StreamError::Io(error) => write!(f, "i/o error: {}", error),
+            StreamError::InvalidMagic => write!(f, "invalid stream magic value"),
+            StreamError::UnsupportedVersion(version) => {
+                write!(f, "unsupported stream format version {}", version)
+            }
+            StreamError::InvalidRecordLength { record, length } => {
+                write!(f, "record {} has invalid length {}", record, length)
+            }
+            StreamError::ChecksumMismatch { record } => {
+                write!(f, "record {} failed its checksum", record)
+            }
+            StreamError::Codec { record, error } => write!(f, "record {}: {}", record, error),
+        }
+    }
+}
+
+impl std::error::Error for StreamError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StreamError::Io(error) => Some(error),
+            StreamError::Codec { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for StreamError {
+    fn from(error: io::Error) -> Self {
+        StreamError::Io(error)
+    }
+}
+
+/// Writes franked messages to a signature stream.
+pub struct SignatureStreamWriter<W: Write> {
+    writer: W,
+    record: usize,
+}
+
+impl<W: Write> SignatureStreamWriter<W> {
+    /// Starts a stream by writing its header to `writer`.
+    pub fn new(mut writer: W) -> Result<Self, StreamError> {
+        writer.write_all(&STREAM_MAGIC)?;
+        writer.write_all(&[STREAM_FORMAT_VERSION])?;
+        Ok(SignatureStreamWriter { writer, record: 0 })
+    }
+
+    /// Appends a record.
+    pub fn write(&mut self, message: &[u8], signature: &AMFSignature) -> Result<(), StreamError> {
+        let encoded_signature = signature.to_bytes();
+        let length = encoded_signature.len() + message.len();
+        if length > MAX_RECORD_LENGTH {
+            return Err(StreamError::InvalidRecordLength {
+                record: self.record,
+                length,
+            });
+        }
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(&encoded_signature);
+        hasher.update(message);
+
+        self.writer.write_all(&(length as u32).to_be_bytes())?;
+        self.writer.write_all(&encoded_signature)?;
+        self.writer.write_all(message)?;
+        self.writer.write_all(&hasher.finalize().to_be_bytes())?;
+        self.record += 1;
+        Ok(())
+    }
+
+    /// Flushes the stream and returns the underlying writer.
+    pub fn finish(mut self) -> Result<W, StreamError> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Reads franked messages from a signature stream, one record per iteration.
+///
+/// Iteration ends cleanly at the end of the input if it falls on a record boundary. After an
+/// error, the stream position is unspecified, so callers should stop reading.
+pub struct SignatureStreamReader<R: Read> {
+    reader: R,
+    record: usize,
+}
+
+impl<R: Read> SignatureStreamReader<R> {
+    /// Opens a stream by reading and checking its header from `reader`.
+    pub fn new(mut reader: R) -> Result<Self, StreamError> {
+        let mut header = [0u8; 5];
+        reader.read_exact(&mut header)?;
+        if header[..4] != STREAM_MAGIC {
+            return Err(StreamError::InvalidMagic);
+        }
+        if header[4] != STREAM_FORMAT_VERSION {
+            return Err(StreamError::UnsupportedVersion(header[4]));
+        }
+        Ok(SignatureStreamReader { reader, record: 0 })
+    }
+
+    /// Reads the next record, or returns `None` at the end of the stream.
+    pub fn read_record(&mut self) -> Result<Option<SignatureRecord>, StreamError> {
+        let record = self.record;
+
+        // 0. Read the length, distinguishing a clean end of stream from a truncated one
+        let mut length = [0u8; 4];
+        let mut filled = 0;
+        while filled < length.len() {
+            match self.reader.read(&mut length[filled..]) {
+                Ok(0) if filled == 0 => return Ok(None),
+                Ok(0) => return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+                Ok(read) => filled += read,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => return Err(error.into()),
+            }
+        }
+        let length = u32::from_be_bytes(length) as usize;
+        if !(AMFSignature::WIRE_LENGTH..=MAX_RECORD_LENGTH).contains(&length) {
+            return Err(StreamError::InvalidRecordLength { record, length });
+        }
+
+        // 1. Read the body and check its checksum
+        let mut body = vec![0u8; length];
+        self.reader.read_exact(&mut body)?;
+        let mut checksum = [0u8; 4];
+        self.reader.read_exact(&mut checksum)?;
+        if crc32fast::hash(&body) != u32::from_be_bytes(checksum) {
+            return Err(StreamError::ChecksumMismatch { record });
+        }
+
+        // 2. Decode the signature
+        let message = body.split_off(AMFSignature::WIRE_LENGTH);
+        let signature = AMFSignature::from_bytes(&body)
+            .map_err(|error| StreamError::Codec { record, error })?;
+        self.record += 1;
+        Ok(Some(SignatureRecord { message, signature }))
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: Read> Iterator for SignatureStreamReader<R> {
+    type Item = Result<SignatureRecord, StreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::amf::{frank, keygen, verify, AMFRole};
+
+    use super::*;
+
+    #[test]
+    fn test_signature_stream() {
+        // 0. Initialize a Sender, Recipient, and Judge
+        let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+        let (recipient_public_key, recipient_secret_key) = keygen(AMFRole::Recipient);
+        let (judge_public_key, _) = keygen(AMFRole::Judge);
+
+        // 1. Frank some messages and export them
+        let messages: [&[u8]; 3] = [b"", b"hello world!", &[0xa5; 1024]];
+        let mut writer = SignatureStreamWriter::new(Vec::new()).unwrap();
+        for message in messages {
+            let amf_signature = frank(
+                sender_secret_key.clone(),
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+            );
+            writer.write(message, &amf_signature).unwrap();
+        }
+        let stream = writer.finish().unwrap();
+        println!(
+            "Stream of {} franks: {} bytes",
+            messages.len(),
+            stream.len()
+        );
+
+        // 2. Import them, and check that they still verify
+        let records = SignatureStreamReader::new(stream.as_slice())
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(records.len(), messages.len());
+        for (record, message) in records.iter().zip(messages) {
+            assert_eq!(record.message, message);
+            assert!(verify(
+                recipient_secret_key.clone(),
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                &record.message,
+                record.signature,
+            ));
+        }
+
+        // 3. Corruption and truncation are detected
+        let mut corrupted = stream.clone();
+        let length = corrupted.len();
+        corrupted[length - 10] ^= 1;
+        let mut reader = SignatureStreamReader::new(corrupted.as_slice()).unwrap();
+        assert!(reader.next().unwrap().is_ok());
+        assert!(reader.next().unwrap().is_ok());
+        assert!(matches!(
+            reader.next(),
+            Some(Err(StreamError::ChecksumMismatch { record: 2 }))
+        ));
+        let mut reader = SignatureStreamReader::new(&stream[..stream.len() - 1]).unwrap();
+        assert!(matches!(reader.nth(2), Some(Err(StreamError::Io(_)))));
+        assert!(matches!(
+            SignatureStreamReader::new(&stream[1..]),
+            Err(StreamError::InvalidMagic)
+        ));
+    }
+}
@@ -0,0 +1,277 @@
+//! Versioned AMF Suites
+//!
+//! An `AMFSuite` names a version of the AMF signature scheme, cf. `v1` and `v2`. Every encoded
+//! signature carries its suite identifier in the wire header, so `SuiteSignature::from_bytes`
+//! decodes signatures of any suite, and `SuiteSignature::verify` and `SuiteSignature::judge` check
+//! each with its own algorithms. Senders can thus move to a newer suite while recipients and judges
+//! keep verifying existing signatures. Keys are the same in all suites.
+
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+
+use rand_core::CryptoRngCore;
+
+use super::{
+    codec::{
+        CodecError, WireArtifactKind, WireHeader, SUITE_RISTRETTO255_SHA512,
+        SUITE_RISTRETTO255_SHA512_V2, WIRE_FORMAT_VERSION,
+    },
+    v1, v2, AMFPublicKey, AMFSecretKey,
+};
+
+/// A version of the AMF signature scheme.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum AMFSuite {
+    /// `amf::v1`, the scheme of `amf::frank`
+    #[default]
+    V1,
+    /// `amf::v2`, with key binding and compact proofs
+    V2,
+}
+
+impl AMFSuite {
+    /// The newest suite, for new signatures.
+    pub const LATEST: AMFSuite = AMFSuite::V2;
+
+    /// The suite identifier in the wire header.
+    pub fn id(self) -> u8 {
+        match self {
+            AMFSuite::V1 => SUITE_RISTRETTO255_SHA512,
+            AMFSuite::V2 => SUITE_RISTRETTO255_SHA512_V2,
+        }
+    }
+
+    /// The suite of a suite identifier.
+    pub fn from_id(id: u8) -> Result<Self, CodecError> {
+        match id {
+            SUITE_RISTRETTO255_SHA512 => Ok(AMFSuite::V1),
+            SUITE_RISTRETTO255_SHA512_V2 => Ok(AMFSuite::V2),
+            id => Err(CodecError::UnsupportedSuite(id)),
+        }
+    }
+
+    /// The suite of an encoded signature, from its header only.
+    pub fn of_signature(bytes: &[u8]) -> Result<Self, CodecError> {
+        let (header, _) = WireHeader::read(bytes, WireArtifactKind::Signature)?;
+        if header.version != WIRE_FORMAT_VERSION {
+            return Err(header.unsupported());
+        }
+        Self::from_id(header.suite)
+    }
+
+    #[cfg(any(feature = "rand", test))]
+    /// `amf::frank` in this suite
+    pub fn frank(
+        self,
+        sender_secret_key: impl Borrow<AMFSecretKey>,
+        sender_public_key: impl Borrow<AMFPublicKey>,
+        recipient_public_key: impl Borrow<AMFPublicKey>,
+        judge_public_key: impl Borrow<AMFPublicKey>,
+        message: &[u8],
+    ) -> SuiteSignature {
+        self.frank_with_rng(
+            &mut rand::thread_rng(),
+            sender_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+        )
+    }
+
+    /// `frank` with the randomness of `rng`
+    pub fn frank_with_rng(
+        self,
+        rng: &mut dyn CryptoRngCore,
+        sender_secret_key: impl Borrow<AMFSecretKey>,
+        sender_public_key: impl Borrow<AMFPublicKey>,
+        recipient_public_key: impl Borrow<AMFPublicKey>,
+        judge_public_key: impl Borrow<AMFPublicKey>,
+        message: &[u8],
+    ) -> SuiteSignature {
+        match self {
+            AMFSuite::V1 => SuiteSignature::V1(v1::frank_with_rng(
+                rng,
+                sender_secret_key,
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+            )),
+            AMFSuite::V2 => SuiteSignature::V2(v2::frank_with_rng(
+                rng,
+                sender_secret_key,
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+            )),
+        }
+    }
+}
+
+/// An AMF signature of any suite. Both variants are uncompressed, as the signatures of `amf`.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SuiteSignature {
+    V1(v1::AMFSignature),
+    V2(v2::AMFSignature),
+}
+
+impl SuiteSignature {
+    /// The suite of the signature.
+    pub fn suite(&self) -> AMFSuite {
+        match self {
+            SuiteSignature::V1(_) => AMFSuite::V1,
+            SuiteSignature::V2(_) => AMFSuite::V2,
+        }
+    }
+
+    /// Encodes the signature in the wire format of its suite.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            SuiteSignature::V1(amf_signature) => amf_signature.to_bytes(),
+            SuiteSignature::V2(amf_signature) => amf_signature.to_bytes(),
+        }
+    }
+
+    /// Decodes a signature of the suite named in its header.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CodecError> {
+        match AMFSuite::of_signature(bytes)? {
+            AMFSuite::V1 => v1::AMFSignature::from_bytes(bytes).map(SuiteSignature::V1),
+            AMFSuite::V2 => v2::AMFSignature::from_bytes(bytes).map(SuiteSignature::V2),
+        }
+    }
+
+    /// `verify` of the signature's suite
+    pub fn verify(
+        &self,
+        recipient_secret_key: impl Borrow<AMFSecretKey>,
+        sender_public_key: impl Borrow<AMFPublicKey>,
+        recipient_public_key: impl Borrow<AMFPublicKey>,
+        judge_public_key: impl Borrow<AMFPublicKey>,
+        message: &[u8],
+    ) -> bool {
+        match self {
+            SuiteSignature::V1(amf_signature) => v1::verify(
+                recipient_secret_key,
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+                amf_signature,
+            ),
+            SuiteSignature::V2(amf_signature) => v2::verify(
+                recipient_secret_key,
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+                amf_signature,
+            ),
+        }
+    }
+
+    /// `judge` of the signature's suite
+    pub fn judge(
+        &self,
+        judge_secret_key: impl Borrow<AMFSecretKey>,
+        sender_public_key: impl Borrow<AMFPublicKey>,
+        recipient_public_key: impl Borrow<AMFPublicKey>,
+        judge_public_key: impl Borrow<AMFPublicKey>,
+        message: &[u8],
+    ) -> bool {
+        match self {
+            SuiteSignature::V1(amf_signature) => v1::judge(
+                judge_secret_key,
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+                amf_signature,
+            ),
+            SuiteSignature::V2(amf_signature) => v2::judge(
+                judge_secret_key,
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+                amf_signature,
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amf::{keygen, AMFRole};
+
+    #[test]
+    fn test_suite_dispatch() {
+        let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+        let (recipient_public_key, recipient_secret_key) = keygen(AMFRole::Recipient);
+        let (judge_public_key, judge_secret_key) = keygen(AMFRole::Judge);
+        let message = b"hello world!";
+
+        for suite in [AMFSuite::V1, AMFSuite::V2] {
+            // 0. Frank in the suite, and read the suite back from the encoding
+            let amf_signature = suite.frank(
+                &sender_secret_key,
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+            );
+            let bytes = amf_signature.to_bytes();
+            assert_eq!(AMFSuite::of_signature(&bytes), Ok(suite));
+            assert_eq!(AMFSuite::from_id(suite.id()), Ok(suite));
+
+            // 1. The decoded signature verifies with the algorithms of its suite
+            let decoded = SuiteSignature::from_bytes(&bytes).unwrap();
+            assert_eq!(decoded, amf_signature);
+            assert_eq!(decoded.suite(), suite);
+            assert!(decoded.verify(
+                &recipient_secret_key,
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+            ));
+            assert!(!decoded.judge(
+                &judge_secret_key,
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                b"goodbye world!",
+            ));
+        }
+
+        // 2. Existing V1 signatures still verify
+        let amf_signature = crate::amf::frank(
+            &sender_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+        );
+        assert!(SuiteSignature::from_bytes(&amf_signature.to_bytes())
+            .unwrap()
+            .judge(
+                &judge_secret_key,
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+            ));
+
+        // 3. Unknown suites are rejected
+        let mut bytes = amf_signature.to_bytes();
+        bytes[4] = 5;
+        assert_eq!(
+            SuiteSignature::from_bytes(&bytes),
+            Err(CodecError::UnsupportedSuite(5))
+        );
+        assert_eq!(AMFSuite::from_id(2), Err(CodecError::UnsupportedSuite(2)));
+    }
+}
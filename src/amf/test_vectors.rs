@@ -0,0 +1,450 @@
+//! Known-answer test vectors (KATs) for AMF.
+//!
+//! Keys are derived deterministically from per-vector seeds, so a port to another language only
+//! needs the seed derivation below to reproduce them:
+//!
+//! ```text
+//! secret_key = Scalar::from_hash(SHA-512("amaze-kat-keygen" || role || seed))
+//! ```
+//!
+//! where `role` is 0 (sender), 1 (recipient), or 2 (judge). The suites of `generic`, e.g. P-256,
+//! reduce the same hash to a scalar with `generic`'s rejection sampling instead of
+//! `Scalar::from_hash`, and have their own `generate_generic` and `replay_generic`.
+//!
+//! The randomness of franking is drawn from ChaCha20 (`rand_chacha::ChaCha20Rng`), seeded with
+//!
+//! ```text
+//! rng_seed = SHA-512("amaze-kat-frank" || seed)[..32]
+//! ```
+//!
+//! so the signature of each vector is deterministic too. Replaying a vector checks that the
+//! derived keys match the recorded ones, that the recorded signature decodes, verifies, and
+//! judges, and that franking again with the seeded randomness gives exactly the recorded bytes.
+//! All byte strings are hex encoded, and keys and signatures use the wire format.
+
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
+
+#[cfg(feature = "generic_group")]
+use super::generic::{self, AMFGroup};
+use super::{
+    codec::{CodecError, SUITE_RISTRETTO255_SHA512, WIRE_FORMAT_VERSION},
+    frank_with_rng, judge, verify, AMFPublicKey, AMFRole, AMFSecretKey, AMFSignature,
+};
+
+/// A single known-answer test.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KnownAnswerTest {
+    pub seed: String,
+    pub message: String,
+    pub sender_public_key: String,
+    pub recipient_public_key: String,
+    pub judge_public_key: String,
+    pub signature: String,
+}
+
+/// A collection of known-answer tests for one suite and wire format version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KnownAnswerTests {
+    pub format_version: u8,
+    pub suite: u8,
+    pub vectors: Vec<KnownAnswerTest>,
+}
+
+/// Why replaying a known-answer test failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KnownAnswerTestError {
+    /// The vectors are for a different wire format version or suite.
+    UnsupportedSuite { format_version: u8, suite: u8 },
+    /// A field was not valid hex.
+    InvalidHex { index: usize },
+    /// A key or signature did not decode.
+    Codec { index: usize, error: CodecError },
+    /// A recorded public key does not match the one derived from the seed.
+    KeyMismatch { index: usize, role: AMFRole },
+    /// The recorded signature failed to verify.
+    VerificationFailed { index: usize },
+    /// The recorded signature failed to judge.
+    JudgingFailed { index: usize },
+    /// The recorded signature verifies, but is not the one franked with the seeded randomness.
+    SignatureMismatch { index: usize },
+}
+
+/// Messages franked by `generate`, cycled through if more vectors are requested.
+const MESSAGES: [&[u8]; 4] = [
+    b"",
+    b"hello world!",
+    b"The quick brown fox jumps over the lazy dog",
+    &[0xa5; 1024],
+];
+
+fn role_byte(role: AMFRole) -> u8 {
+    match role {
+        AMFRole::Sender => 0,
+        AMFRole::Recipient => 1,
+        AMFRole::Judge => 2,
+    }
+}
+
+/// the hash the secret key for `role` is derived from, in every suite
+fn keygen_hasher(role: AMFRole, seed: &[u8]) -> Sha512 {
+    let mut hasher = Sha512::new();
+    hasher.update(b"amaze-kat-keygen");
+    hasher.update([role_byte(role)]);
+    hasher.update(seed);
+    hasher
+}
+
+/// Deterministically derives a key pair for `role` from `seed`.
+pub fn keygen_from_seed(role: AMFRole, seed: &[u8]) -> (AMFPublicKey, AMFSecretKey) {
+    let secret_key = Scalar::from_hash(keygen_hasher(role, seed));
+    let public_key = RistrettoPoint::mul_base(&secret_key);
+    (
+        AMFPublicKey { role, public_key },
+        AMFSecretKey { role, secret_key },
+    )
+}
+
+/// Deterministically derives a key pair for `role` from `seed`, in the group `G`.
+#[cfg(feature = "generic_group")]
+pub fn generic_keygen_from_seed<G: AMFGroup>(
+    role: AMFRole,
+    seed: &[u8],
+) -> (generic::AMFPublicKey<G>, generic::AMFSecretKey<G>) {
+    let secret_key: G::Scalar = generic::hash_to_scalar(keygen_hasher(role, seed));
+    (
+        generic::AMFPublicKey {
+            role,
+            public_key: G::generator() * secret_key,
+        },
+        generic::AMFSecretKey { role, secret_key },
+    )
+}
+
+/// the randomness of franking the vector of `seed`
+fn frank_rng(seed: &[u8]) -> ChaCha20Rng {
+    let mut hasher = Sha512::new();
+    hasher.update(b"amaze-kat-frank");
+    hasher.update(seed);
+    ChaCha20Rng::from_seed(hasher.finalize()[..32].try_into().unwrap())
+}
+
+/// the seed and message of the vector at `index`
+fn seed_and_message(index: usize) -> ([u8; 8], &'static [u8]) {
+    (
+        (index as u64).to_be_bytes(),
+        MESSAGES[index % MESSAGES.len()],
+    )
+}
+
+/// Generates `count` known-answer tests.
+pub fn generate(count: usize) -> KnownAnswerTests {
+    let vectors = (0..count)
+        .map(|index| {
+            let (seed, message) = seed_and_message(index);
+            let (sender_public_key, sender_secret_key) = keygen_from_seed(AMFRole::Sender, &seed);
+            let (recipient_public_key, _) = keygen_from_seed(AMFRole::Recipient, &seed);
+            let (judge_public_key, _) = keygen_from_seed(AMFRole::Judge, &seed);
+            let amf_signature = frank_with_rng(
+                &mut frank_rng(&seed),
+                sender_secret_key,
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+            );
+            KnownAnswerTest {
+                seed: hex::encode(seed),
+                message: hex::encode(message),
+                sender_public_key: hex::encode(sender_public_key.to_bytes()),
+                recipient_public_key: hex::encode(recipient_public_key.to_bytes()),
+                judge_public_key: hex::encode(judge_public_key.to_bytes()),
+                signature: hex::encode(amf_signature.to_bytes()),
+            }
+        })
+        .collect();
+    KnownAnswerTests {
+        format_version: WIRE_FORMAT_VERSION,
+        suite: SUITE_RISTRETTO255_SHA512,
+        vectors,
+    }
+}
+
+/// Generates `count` known-answer tests in the group `G`, under its suite.
+#[cfg(feature = "generic_group")]
+pub fn generate_generic<G: AMFGroup>(count: usize) -> KnownAnswerTests {
+    let vectors = (0..count)
+        .map(|index| {
+            let (seed, message) = seed_and_message(index);
+            let (sender_public_key, sender_secret_key) =
+                generic_keygen_from_seed::<G>(AMFRole::Sender, &seed);
+            let (recipient_public_key, _) =
+                generic_keygen_from_seed::<G>(AMFRole::Recipient, &seed);
+            let (judge_public_key, _) = generic_keygen_from_seed::<G>(AMFRole::Judge, &seed);
+            let amf_signature = generic::frank_with_rng(
+                &mut frank_rng(&seed),
+                sender_secret_key,
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+            );
+            KnownAnswerTest {
+                seed: hex::encode(seed),
+                message: hex::encode(message),
+                sender_public_key: hex::encode(sender_public_key.to_bytes()),
+                recipient_public_key: hex::encode(recipient_public_key.to_bytes()),
+                judge_public_key: hex::encode(judge_public_key.to_bytes()),
+                signature: hex::encode(amf_signature.to_bytes()),
+            }
+        })
+        .collect();
+    KnownAnswerTests {
+        format_version: WIRE_FORMAT_VERSION,
+        suite: G::SUITE,
+        vectors,
+    }
+}
+
+impl KnownAnswerTests {
+    /// Serializes the vectors as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    /// Loads vectors from JSON.
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// Replays every vector, returning the first failure.
+    pub fn replay(&self) -> Result<(), KnownAnswerTestError> {
+        self.check_suite(SUITE_RISTRETTO255_SHA512)?;
+        for (index, vector) in self.vectors.iter().enumerate() {
+            vector.replay(index)?;
+        }
+        Ok(())
+    }
+
+    /// Replays every vector in the group `G`, returning the first failure.
+    #[cfg(feature = "generic_group")]
+    pub fn replay_generic<G: AMFGroup>(&self) -> Result<(), KnownAnswerTestError> {
+        self.check_suite(G::SUITE)?;
+        for (index, vector) in self.vectors.iter().enumerate() {
+            vector.replay_generic::<G>(index)?;
+        }
+        Ok(())
+    }
+
+    fn check_suite(&self, suite: u8) -> Result<(), KnownAnswerTestError> {
+        if self.format_version != WIRE_FORMAT_VERSION || self.suite != suite {
+            return Err(KnownAnswerTestError::UnsupportedSuite {
+                format_version: self.format_version,
+                suite: self.suite,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl KnownAnswerTest {
+    fn replay(&self, index: usize) -> Result<(), KnownAnswerTestError> {
+        let decode_hex = |field: &str| {
+            hex::decode(field).map_err(|_| KnownAnswerTestError::InvalidHex { index })
+        };
+        let codec_error = |error| KnownAnswerTestError::Codec { index, error };
+
+        // 0. Re-derive the keys from the seed, and check them against the recorded ones
+        let seed = decode_hex(&self.seed)?;
+        let (sender_public_key, sender_secret_key) = keygen_from_seed(AMFRole::Sender, &seed);
+        let (recipient_public_key, recipient_secret_key) =
+            keygen_from_seed(AMFRole::Recipient, &seed);
+        let (judge_public_key, judge_secret_key) = keygen_from_seed(AMFRole::Judge, &seed);
+        for (public_key, recorded) in [
+            (sender_public_key, &self.sender_public_key),
+            (recipient_public_key, &self.recipient_public_key),
+            (judge_public_key, &self.judge_public_key),
+        ] {
+            let recorded = AMFPublicKey::from_bytes(&decode_hex(recorded)?).map_err(codec_error)?;
+            if recorded != public_key {
+                return Err(KnownAnswerTestError::KeyMismatch {
+                    index,
+                    role: public_key.role,
+                });
+            }
+        }
+
+        // 1. Check that the recorded signature verifies and judges
+        let message = decode_hex(&self.message)?;
+        let recorded_signature = decode_hex(&self.signature)?;
+        let amf_signature = AMFSignature::from_bytes(&recorded_signature).map_err(codec_error)?;
+        if !verify(
+            recipient_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            &message,
+            amf_signature,
+        ) {
+            return Err(KnownAnswerTestError::VerificationFailed { index });
+        }
+        if !judge(
+            judge_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            &message,
+            amf_signature,
+        ) {
+            return Err(KnownAnswerTestError::JudgingFailed { index });
+        }
+
+        // 2. Frank again with the seeded randomness, which must give exactly the recorded bytes
+        let amf_signature = frank_with_rng(
+            &mut frank_rng(&seed),
+            sender_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            &message,
+        );
+        if amf_signature.to_bytes() != recorded_signature {
+            return Err(KnownAnswerTestError::SignatureMismatch { index });
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "generic_group")]
+    fn replay_generic<G: AMFGroup>(&self, index: usize) -> Result<(), KnownAnswerTestError> {
+        let decode_hex = |field: &str| {
+            hex::decode(field).map_err(|_| KnownAnswerTestError::InvalidHex { index })
+        };
+        let codec_error = |error| KnownAnswerTestError::Codec { index, error };
+
+        // 0. Re-derive the keys from the seed, and check them against the recorded ones
+        let seed = decode_hex(&self.seed)?;
+        let (sender_public_key, sender_secret_key) =
+            generic_keygen_from_seed::<G>(AMFRole::Sender, &seed);
+        let (recipient_public_key, recipient_secret_key) =
+            generic_keygen_from_seed::<G>(AMFRole::Recipient, &seed);
+        let (judge_public_key, judge_secret_key) =
+            generic_keygen_from_seed::<G>(AMFRole::Judge, &seed);
+        for (public_key, recorded) in [
+            (sender_public_key, &self.sender_public_key),
+            (recipient_public_key, &self.recipient_public_key),
+            (judge_public_key, &self.judge_public_key),
+        ] {
+            let recorded = generic::AMFPublicKey::<G>::from_bytes(&decode_hex(recorded)?)
+                .map_err(codec_error)?;
+            if recorded != public_key {
+                return Err(KnownAnswerTestError::KeyMismatch {
+                    index,
+                    role: public_key.role,
+                });
+            }
+        }
+
+        // 1. Check that the recorded signature verifies and judges
+        let message = decode_hex(&self.message)?;
+        let recorded_signature = decode_hex(&self.signature)?;
+        let amf_signature =
+            generic::AMFSignature::<G>::from_bytes(&recorded_signature).map_err(codec_error)?;
+        if !generic::verify(
+            recipient_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            &message,
+            amf_signature,
+        ) {
+            return Err(KnownAnswerTestError::VerificationFailed { index });
+        }
+        if !generic::judge(
+            judge_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            &message,
+            amf_signature,
+        ) {
+            return Err(KnownAnswerTestError::JudgingFailed { index });
+        }
+
+        // 2. Frank again with the seeded randomness, which must give exactly the recorded bytes
+        let amf_signature = generic::frank_with_rng(
+            &mut frank_rng(&seed),
+            sender_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            &message,
+        );
+        if amf_signature.to_bytes() != recorded_signature {
+            return Err(KnownAnswerTestError::SignatureMismatch { index });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_answer_tests() {
+        // 0. Generate vectors and round-trip them through JSON
+        let known_answer_tests = generate(8);
+        let json = known_answer_tests.to_json();
+        let loaded = KnownAnswerTests::from_json(&json).unwrap();
+        assert_eq!(loaded, known_answer_tests);
+
+        // 1. Keys and signatures are deterministic
+        assert_eq!(generate(8), known_answer_tests);
+
+        // 2. The loaded vectors replay
+        assert_eq!(loaded.replay(), Ok(()));
+
+        // 3. Tampering with a vector is caught
+        let mut tampered = loaded.clone();
+        tampered.vectors[3].message = hex::encode(b"goodbye world!");
+        assert_eq!(
+            tampered.replay(),
+            Err(KnownAnswerTestError::VerificationFailed { index: 3 })
+        );
+        let mut tampered = loaded.clone();
+        tampered.vectors[5].judge_public_key = loaded.vectors[4].judge_public_key.clone();
+        assert_eq!(
+            tampered.replay(),
+            Err(KnownAnswerTestError::KeyMismatch {
+                index: 5,
+                role: AMFRole::Judge
+            })
+        );
+        let mut tampered = loaded.clone();
+        let vector = &tampered.vectors[6];
+        let seed = hex::decode(&vector.seed).unwrap();
+        let (sender_public_key, sender_secret_key) = keygen_from_seed(AMFRole::Sender, &seed);
+        let (recipient_public_key, _) = keygen_from_seed(AMFRole::Recipient, &seed);
+        let (judge_public_key, _) = keygen_from_seed(AMFRole::Judge, &seed);
+        let other_signature = crate::amf::frank(
+            sender_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            &hex::decode(&vector.message).unwrap(),
+        );
+        tampered.vectors[6].signature = hex::encode(other_signature.to_bytes());
+        assert_eq!(
+            tampered.replay(),
+            Err(KnownAnswerTestError::SignatureMismatch { index: 6 })
+        );
+        let mut tampered = loaded;
+        tampered.format_version += 1;
+        assert!(matches!(
+            tampered.replay(),
+            Err(KnownAnswerTestError::UnsupportedSuite { .. })
+        ));
+    }
+}
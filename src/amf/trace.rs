@@ -0,0 +1,216 @@
+//! Tracing of KeyGen, Frank, Verify and Judge
+//!
+//! With the `tracing` feature, `keygen`, `frank`, `verify` and `judge` (and every function that
+//! goes through them) run in debug-level spans of the [tracing] crate, named after the protocol
+//! step, and emit an event with their outcome; `try_verify` and `try_judge` also emit why they
+//! rejected a signature or its keys. Subscribers time the spans, e.g. tracing-subscriber with
+//! `FmtSpan::CLOSE`. Without the feature, the functions here do nothing and compile away.
+//!
+//! No secret material is ever recorded: the functions here only take public values (roles,
+//! lengths, and outcomes), so keys, randomness, messages and signatures cannot reach a subscriber
+//! through them. The outcome of `verify` and `judge` is a single bit, as returned to the caller;
+//! which of their checks failed is not recorded, cf. `amf::verify`.
+//!
+//! [tracing]: https://docs.rs/tracing
+
+use super::AMFRole;
+use crate::error::Error;
+
+/// the span of a protocol step, exited when dropped
+#[must_use]
+pub(crate) struct Span {
+    #[cfg(feature = "tracing")]
+    _entered: tracing::span::EnteredSpan,
+}
+
+/// enters the span of `keygen` for `role`
+pub(crate) fn keygen(role: AMFRole) -> Span {
+    #[cfg(not(feature = "tracing"))]
+    let _ = role;
+    Span {
+        #[cfg(feature = "tracing")]
+        _entered: tracing::debug_span!("amf::keygen", ?role).entered(),
+    }
+}
+
+/// enters the span of `frank` for a message of `message_len` bytes
+pub(crate) fn frank(message_len: usize) -> Span {
+    #[cfg(not(feature = "tracing"))]
+    let _ = message_len;
+    Span {
+        #[cfg(feature = "tracing")]
+        _entered: tracing::debug_span!("amf::frank", message_len).entered(),
+    }
+}
+
+/// records that `frank` returned a signature
+pub(crate) fn franked() {
+    #[cfg(feature = "tracing")]
+    tracing::debug!(signature_len = super::AMFSignature::WIRE_LENGTH, "franked");
+}
+
+/// enters the span of `verify` (`role` Recipient) or `judge` (`role` Judge) for a message of
+/// `message_len` bytes
+pub(crate) fn verify(role: AMFRole, message_len: usize) -> Span {
+    #[cfg(not(feature = "tracing"))]
+    let _ = (role, message_len);
+    Span {
+        #[cfg(feature = "tracing")]
+        _entered: match role {
+            AMFRole::Judge => tracing::debug_span!("amf::judge", message_len),
+            _ => tracing::debug_span!("amf::verify", message_len),
+        }
+        .entered(),
+    }
+}
+
+/// records the outcome of `verify` or `judge`
+pub(crate) fn verified(valid: bool) {
+    #[cfg(not(feature = "tracing"))]
+    let _ = valid;
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        valid,
+        signature_len = super::AMFSignature::WIRE_LENGTH,
+        "verified"
+    );
+}
+
+/// records why `try_verify` (`role` Recipient) or `try_judge` (`role` Judge) rejected a signature
+pub(crate) fn rejected(role: AMFRole, error: &Error) {
+    #[cfg(not(feature = "tracing"))]
+    let _ = (role, error);
+    #[cfg(feature = "tracing")]
+    tracing::debug!(?role, cause = %error, "rejected");
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use tracing::{
+        field::{Field, Visit},
+        span, Event, Metadata, Subscriber,
+    };
+
+    use crate::amf::{frank, judge, keygen, try_verify, AMFRole};
+
+    /// a subscriber that keeps the name and the fields of every span and event as text
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    impl Visit for Recorder {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("{} = {:?}", field.name(), value));
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+            self.0.lock().unwrap().push(span.metadata().name().into());
+            span.record(&mut self.clone());
+            span::Id::from_u64(1)
+        }
+
+        fn record(&self, _: &span::Id, values: &span::Record<'_>) {
+            values.record(&mut self.clone());
+        }
+
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            event.record(&mut self.clone());
+        }
+
+        fn enter(&self, _: &span::Id) {}
+
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    #[test]
+    fn test_trace_has_no_secrets() {
+        let recorder = Recorder::default();
+        let message = b"hello world!";
+        let (sender_public_key, sender_secret_key, recipient_secret_key, judge_secret_key) =
+            tracing::subscriber::with_default(recorder.clone(), || {
+                // 0. Trace a round of the protocol, and a rejection
+                let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+                let (recipient_public_key, recipient_secret_key) = keygen(AMFRole::Recipient);
+                let (judge_public_key, judge_secret_key) = keygen(AMFRole::Judge);
+                let amf_signature = frank(
+                    sender_secret_key.clone(),
+                    sender_public_key,
+                    recipient_public_key,
+                    judge_public_key,
+                    message,
+                );
+                assert!(judge(
+                    judge_secret_key.clone(),
+                    sender_public_key,
+                    recipient_public_key,
+                    judge_public_key,
+                    message,
+                    amf_signature,
+                ));
+                assert!(try_verify(
+                    recipient_secret_key.clone(),
+                    sender_public_key,
+                    recipient_public_key,
+                    judge_public_key,
+                    b"goodbye world!",
+                    amf_signature,
+                )
+                .is_err());
+                (
+                    sender_public_key,
+                    sender_secret_key,
+                    recipient_secret_key,
+                    judge_secret_key,
+                )
+            });
+        let trace = recorder.0.lock().unwrap().join("\n");
+
+        // 1. The steps, sizes and outcomes are recorded
+        for expected in [
+            "amf::keygen",
+            "role = Sender",
+            "amf::frank",
+            "message_len = 12",
+            "signature_len = 486",
+            "amf::judge",
+            "valid = true",
+            "amf::verify",
+            "valid = false",
+            "cause = invalid signature",
+        ] {
+            assert!(trace.contains(expected), "{expected} not in {trace}");
+        }
+
+        // 2. The secret keys, the message and the public key are not, in any encoding
+        let secrets = [sender_secret_key, recipient_secret_key, judge_secret_key]
+            .map(|secret_key| secret_key.secret_key.to_bytes());
+        for bytes in secrets.iter().map(|bytes| bytes.as_slice()).chain([
+            message.as_slice(),
+            &sender_public_key.public_key.compress().0,
+        ]) {
+            for encoding in [
+                hex::encode(bytes),
+                format!("{bytes:?}"),
+                String::from_utf8_lossy(bytes).into(),
+            ] {
+                assert!(!trace.contains(&encoding));
+            }
+        }
+        for bytes in secrets {
+            let scalar = curve25519_dalek::scalar::Scalar::from_bytes_mod_order(bytes);
+            assert!(!trace.contains(&format!("{scalar:?}")));
+        }
+    }
+}
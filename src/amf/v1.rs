@@ -0,0 +1,11 @@
+//! AMF Suite V1
+//!
+//! Suite `V1` (suite identifier `SUITE_RISTRETTO255_SHA512`) is the scheme of Fig. 5 in [AMF],
+//! with strong Fiat-Shamir, as `amf::frank`, `amf::verify` and `amf::judge` implement it. This
+//! module names the suite next to `v2`; its items are those of `amf`.
+//!
+//! [AMF]: https://eprint.iacr.org/2019/565/20190527:092413
+
+#[cfg(any(feature = "rand", test))]
+pub use super::franking::frank;
+pub use super::franking::{frank_with_rng, judge, verify, AMFSignature};
@@ -0,0 +1,485 @@
+//! AMF Suite V2: Compact, Key-Binding Signatures
+//!
+//! Suite `V2` (suite identifier `SUITE_RISTRETTO255_SHA512_V2`) franks with the same keys,
+//! ciphertexts and proof of knowledge as `v1`. It changes two things.
+//!
+//! - Key binding. The Fiat-Shamir message is `bind_keys(sender, recipient, judge, message)`. A `v1`
+//!   signature does not depend on the recipient's public key, and `verify` ignores it. A `V2`
+//!   signature only verifies for the keys it was franked for.
+//! - Compact encoding. The proof pi carries the challenge instead of the five commitment points,
+//!   cf. `FiatShamirCompactSignature`. The verifier recomputes the commitments. This takes the wire
+//!   encoding from 486 to 358 bytes.
+//!
+//! Both suites already use strong Fiat-Shamir, cf. `FiatShamirVersion::V2`. `AMFSuite` picks a
+//! suite at runtime, and `SuiteSignature` verifies signatures of either suite by their suite byte.
+#![allow(non_snake_case)]
+
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar, traits::Identity};
+use rand_core::CryptoRngCore;
+use subtle::{Choice, ConstantTimeEq};
+
+use crate::pok::{
+    domain::AMF_V2_LABEL,
+    elgamal::{ElGamalCiphertext, VerifiableElGamal},
+    fiat_shamir::FiatShamirCompactSignature,
+    or_proof::OrProverResponse,
+};
+
+use super::{
+    codec::{
+        check_not_identity, CodecError, WireArtifactKind, WireHeader, WireReader,
+        SUITE_RISTRETTO255_SHA512_V2, WIRE_FORMAT_VERSION, WIRE_HEADER_LENGTH,
+    },
+    franking::keys_valid,
+    spok_amf::AMFSPoK,
+    AMFPublicKey, AMFSecretKey,
+};
+
+/// four points, then the challenge and the two OR responses (c_0, z_0, z_1)
+const SIGNATURE_BODY_LENGTH: usize = 11 * 32;
+
+/// The compact proof pi: the challenge and the responses of the two OR proofs.
+pub type AMFCompactProof = FiatShamirCompactSignature<(
+    OrProverResponse<Scalar, Scalar>,
+    OrProverResponse<Scalar, Scalar>,
+)>;
+
+/// The AMF signature of suite `V2`, cf. the module documentation.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AMFSignature {
+    pub pi: AMFCompactProof,
+    pub J: RistrettoPoint,
+    pub R: RistrettoPoint,
+    pub E_J: RistrettoPoint,
+    pub E_R: RistrettoPoint,
+}
+
+/// The message that suite `V2` signs:
+/// `tag(AMF_V2_LABEL) || tag(sender) || tag(recipient) || tag(judge) || message`, with the
+/// compressed public keys and `tag` as in `pok::domain`.
+pub fn bind_keys(
+    sender_public_key: &AMFPublicKey,
+    recipient_public_key: &AMFPublicKey,
+    judge_public_key: &AMFPublicKey,
+    message: &[u8],
+) -> Vec<u8> {
+    let mut bound = Vec::with_capacity(32 + AMF_V2_LABEL.len() + 3 * 32 + message.len());
+    let keys = [sender_public_key, recipient_public_key, judge_public_key]
+        .map(|public_key| public_key.public_key.compress().to_bytes());
+    for tagged in [AMF_V2_LABEL]
+        .into_iter()
+        .chain(keys.iter().map(|key| &key[..]))
+    {
+        bound.extend_from_slice(&(tagged.len() as u64).to_be_bytes());
+        bound.extend_from_slice(tagged);
+    }
+    bound.extend_from_slice(message);
+    bound
+}
+
+#[cfg(any(feature = "rand", test))]
+/// `amf::frank` in suite `V2`
+pub fn frank(
+    sender_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    message: &[u8],
+) -> AMFSignature {
+    frank_with_rng(
+        &mut rand::thread_rng(),
+        sender_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        message,
+    )
+}
+
+/// `frank` with the randomness of `rng`
+pub fn frank_with_rng(
+    rng: &mut dyn CryptoRngCore,
+    sender_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    message: &[u8],
+) -> AMFSignature {
+    let [sender_public_key, recipient_public_key, judge_public_key] = [
+        sender_public_key.borrow(),
+        recipient_public_key.borrow(),
+        judge_public_key.borrow(),
+    ];
+    let bound = bind_keys(
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        message,
+    );
+    let amf_signature = super::frank_with_rng(
+        rng,
+        sender_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        &bound,
+    );
+    let spok = AMFSPoK::new(
+        sender_public_key.public_key,
+        judge_public_key.public_key,
+        amf_signature.J,
+        amf_signature.R,
+        amf_signature.E_J,
+    );
+    AMFSignature {
+        pi: spok.compact(&bound, amf_signature.pi),
+        J: amf_signature.J,
+        R: amf_signature.R,
+        E_J: amf_signature.E_J,
+        E_R: amf_signature.E_R,
+    }
+}
+
+/// `amf::verify` in suite `V2`; unlike it, checks the signature against `recipient_public_key`
+pub fn verify(
+    recipient_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    message: &[u8],
+    amf_signature: impl Borrow<AMFSignature>,
+) -> bool {
+    let amf_signature = amf_signature.borrow();
+    let recipient_public_key = recipient_public_key.borrow();
+    verify_with(
+        &recipient_secret_key.borrow().secret_key,
+        recipient_public_key,
+        amf_signature.recipient_ciphertext(),
+        [
+            sender_public_key.borrow(),
+            recipient_public_key,
+            judge_public_key.borrow(),
+        ],
+        message,
+        amf_signature,
+    )
+    .into()
+}
+
+/// Like `verify`, with the judge's ciphertext.
+pub fn judge(
+    judge_secret_key: impl Borrow<AMFSecretKey>,
+    sender_public_key: impl Borrow<AMFPublicKey>,
+    recipient_public_key: impl Borrow<AMFPublicKey>,
+    judge_public_key: impl Borrow<AMFPublicKey>,
+    message: &[u8],
+    amf_signature: impl Borrow<AMFSignature>,
+) -> bool {
+    let amf_signature = amf_signature.borrow();
+    let judge_public_key = judge_public_key.borrow();
+    verify_with(
+        &judge_secret_key.borrow().secret_key,
+        judge_public_key,
+        amf_signature.judge_ciphertext(),
+        [
+            sender_public_key.borrow(),
+            recipient_public_key.borrow(),
+            judge_public_key,
+        ],
+        message,
+        amf_signature,
+    )
+    .into()
+}
+
+/// checks b0, b1 with `ciphertext` and `secret_key`, b2 on the bound message, and b3 with
+/// `public_key`, the key of the role that checks, in constant time as `amf::verify` does
+fn verify_with(
+    secret_key: &Scalar,
+    public_key: &AMFPublicKey,
+    ciphertext: ElGamalCiphertext,
+    [sender_public_key, recipient_public_key, judge_public_key]: [&AMFPublicKey; 3],
+    message: &[u8],
+    amf_signature: &AMFSignature,
+) -> Choice {
+    let b0 = amf_signature.components_valid();
+    let b1 = VerifiableElGamal::decrypt(secret_key, &ciphertext).ct_eq(&RistrettoPoint::identity());
+
+    let spok = AMFSPoK::new(
+        sender_public_key.public_key,
+        judge_public_key.public_key,
+        amf_signature.J,
+        amf_signature.R,
+        amf_signature.E_J,
+    );
+    let bound = bind_keys(
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        message,
+    );
+    let b2 = Choice::from(u8::from(spok.verify_compact(&bound, amf_signature.pi)));
+
+    let b3 = keys_valid(secret_key, public_key, &spok.statement());
+
+    b0 & b1 & b2 & b3
+}
+
+impl AMFSignature {
+    /// The length of `to_bytes()` in bytes.
+    pub const WIRE_LENGTH: usize = WIRE_HEADER_LENGTH + SIGNATURE_BODY_LENGTH;
+
+    /// (E_J, J), the encryption of the identity under the judge's public key
+    pub fn judge_ciphertext(&self) -> ElGamalCiphertext {
+        ElGamalCiphertext {
+            c1: self.E_J,
+            c2: self.J,
+        }
+    }
+
+    /// (E_R, R), the encryption of the identity under the recipient's public key
+    pub fn recipient_ciphertext(&self) -> ElGamalCiphertext {
+        ElGamalCiphertext {
+            c1: self.E_R,
+            c2: self.R,
+        }
+    }
+
+    /// Checks that none of J, R, E_J, and E_R is the identity, cf. `amf::AMFSignature::validate`.
+    pub fn validate(&self) -> Result<(), CodecError> {
+        [self.J, self.R, self.E_J, self.E_R]
+            .iter()
+            .try_for_each(check_not_identity)
+    }
+
+    /// `validate` as a `Choice`
+    fn components_valid(&self) -> Choice {
+        let identity = RistrettoPoint::identity();
+        !(self.J.ct_eq(&identity)
+            | self.R.ct_eq(&identity)
+            | self.E_J.ct_eq(&identity)
+            | self.E_R.ct_eq(&identity))
+    }
+
+    /// Encodes the signature in the wire format, with suite `SUITE_RISTRETTO255_SHA512_V2`:
+    /// `J || R || E_J || E_R || c || c_0 || z_0 || z_1 || c_0 || z_0 || z_1` after the header.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(Self::WIRE_LENGTH);
+        WireHeader::write_suite(
+            &mut buf,
+            SUITE_RISTRETTO255_SHA512_V2,
+            WireArtifactKind::Signature,
+        );
+        for point in [self.J, self.R, self.E_J, self.E_R] {
+            buf.extend(point.compress().as_bytes());
+        }
+        let (or0_response, or1_response) = self.pi.prover_response;
+        for scalar in [
+            self.pi.challenge,
+            or0_response.c_0,
+            or0_response.z_0,
+            or0_response.z_1,
+            or1_response.c_0,
+            or1_response.z_0,
+            or1_response.z_1,
+        ] {
+            buf.extend(scalar.as_bytes());
+        }
+        buf
+    }
+
+    /// Decodes a signature of suite `V2`, rejecting other suites, invalid points, non-canonical
+    /// scalars and the signatures that `validate` rejects.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CodecError> {
+        let (header, body) = WireHeader::read(bytes, WireArtifactKind::Signature)?;
+        if (header.version, header.suite) != (WIRE_FORMAT_VERSION, SUITE_RISTRETTO255_SHA512_V2) {
+            return Err(header.unsupported());
+        }
+        let mut reader = WireReader::new(body, SIGNATURE_BODY_LENGTH)?;
+        let [J, R, E_J, E_R] = [(); 4].map(|_| reader.read_point());
+        let [challenge, c_0, z_0, z_1, d_0, y_0, y_1] = [(); 7].map(|_| reader.read_scalar());
+        let amf_signature = AMFSignature {
+            pi: FiatShamirCompactSignature {
+                challenge: challenge?,
+                prover_response: (
+                    OrProverResponse {
+                        c_0: c_0?,
+                        z_0: z_0?,
+                        z_1: z_1?,
+                    },
+                    OrProverResponse {
+                        c_0: d_0?,
+                        z_0: y_0?,
+                        z_1: y_1?,
+                    },
+                ),
+            },
+            J: J?,
+            R: R?,
+            E_J: E_J?,
+            E_R: E_R?,
+        };
+        amf_signature.validate()?;
+        Ok(amf_signature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amf::{keygen, AMFRole};
+
+    #[test]
+    fn test_v2_franking() {
+        // 0. Frank a message in suite V2
+        let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+        let (recipient_public_key, recipient_secret_key) = keygen(AMFRole::Recipient);
+        let (judge_public_key, judge_secret_key) = keygen(AMFRole::Judge);
+        let message = b"hello world!";
+        let amf_signature = frank(
+            &sender_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+        );
+
+        // 1. The recipient and the judge accept it, also after a round trip through the wire format
+        let decoded = AMFSignature::from_bytes(&amf_signature.to_bytes()).unwrap();
+        assert_eq!(decoded, amf_signature);
+        assert_eq!(amf_signature.to_bytes().len(), AMFSignature::WIRE_LENGTH);
+        const { assert!(AMFSignature::WIRE_LENGTH < crate::amf::AMFSignature::WIRE_LENGTH) };
+        for amf_signature in [amf_signature, decoded] {
+            assert!(verify(
+                &recipient_secret_key,
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+                amf_signature,
+            ));
+            assert!(judge(
+                &judge_secret_key,
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+                amf_signature,
+            ));
+        }
+
+        // 2. Another message, another recipient key, or a changed response is rejected
+        assert!(!verify(
+            &recipient_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            b"goodbye world!",
+            amf_signature,
+        ));
+        let (other_public_key, _) = keygen(AMFRole::Recipient);
+        assert!(!verify(
+            &recipient_secret_key,
+            sender_public_key,
+            other_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        ));
+        let mut tampered = amf_signature;
+        tampered.pi.prover_response.1.z_0 += Scalar::ONE;
+        assert!(!judge(
+            &judge_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            tampered,
+        ));
+
+        // 3. So is a frank under the identity sender key, whose secret key 0 everyone knows
+        let identity_public_key = AMFPublicKey {
+            role: AMFRole::Sender,
+            public_key: RistrettoPoint::identity(),
+        };
+        let amf_signature = frank(
+            AMFSecretKey {
+                role: AMFRole::Sender,
+                secret_key: Scalar::ZERO,
+            },
+            identity_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+        );
+        assert!(!verify(
+            &recipient_secret_key,
+            identity_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        ));
+        assert!(!judge(
+            &judge_secret_key,
+            identity_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+            amf_signature,
+        ));
+    }
+
+    #[test]
+    fn test_v2_encoding() {
+        let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+        let (recipient_public_key, _) = keygen(AMFRole::Recipient);
+        let (judge_public_key, _) = keygen(AMFRole::Judge);
+        let amf_signature = frank(
+            &sender_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            b"hello world!",
+        );
+        let bytes = amf_signature.to_bytes();
+
+        // 0. The suites do not decode each other's signatures
+        assert_eq!(
+            crate::amf::AMFSignature::from_bytes(&bytes),
+            Err(CodecError::UnsupportedSuite(SUITE_RISTRETTO255_SHA512_V2))
+        );
+        let v1_signature = crate::amf::frank(
+            &sender_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            b"hello world!",
+        );
+        assert_eq!(
+            AMFSignature::from_bytes(&v1_signature.to_bytes()),
+            Err(CodecError::UnsupportedSuite(1))
+        );
+
+        // 1. Truncated signatures, identity points and non-canonical scalars are rejected
+        assert!(matches!(
+            AMFSignature::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(CodecError::InvalidLength { .. })
+        ));
+        let mut identity = bytes.clone();
+        identity[WIRE_HEADER_LENGTH..WIRE_HEADER_LENGTH + 32].fill(0);
+        assert_eq!(
+            AMFSignature::from_bytes(&identity),
+            Err(CodecError::IdentityPoint)
+        );
+        let mut non_canonical = bytes;
+        non_canonical[WIRE_HEADER_LENGTH + 4 * 32..WIRE_HEADER_LENGTH + 5 * 32].fill(0xff);
+        assert_eq!(
+            AMFSignature::from_bytes(&non_canonical),
+            Err(CodecError::InvalidScalar)
+        );
+    }
+}
@@ -0,0 +1,199 @@
+//! AMF Signatures of Older Wire Format Versions
+//!
+//! `AMFSignature::from_bytes` only decodes signatures of the current wire format version. Version
+//! 1 signatures were franked with `FiatShamirVersion::V1`, whose challenge hashes neither the
+//! statement nor domain separation tags; a `VersionedAMFSignature` decodes them too and verifies
+//! and judges every signature with the challenge hash of its version. New signatures are always
+//! franked with the current version.
+
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+
+use crate::pok::fiat_shamir::FiatShamirVersion;
+
+use super::{
+    codec::{
+        CodecError, WireArtifactKind, WireHeader, SIGNATURE_BODY_LENGTH, SUITE_RISTRETTO255_SHA512,
+        WIRE_FORMAT_V1, WIRE_FORMAT_V2, WIRE_HEADER_LENGTH,
+    },
+    franking::verify_with,
+    trace, AMFPublicKey, AMFRole, AMFSecretKey, AMFSignature, CompressedAMFSignature,
+};
+
+/// An AMF signature with the Fiat-Shamir version it was franked with.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct VersionedAMFSignature {
+    pub version: FiatShamirVersion,
+    pub amf_signature: AMFSignature,
+}
+
+/// a signature of the current version
+impl From<AMFSignature> for VersionedAMFSignature {
+    fn from(amf_signature: AMFSignature) -> Self {
+        VersionedAMFSignature {
+            version: FiatShamirVersion::default(),
+            amf_signature,
+        }
+    }
+}
+
+impl VersionedAMFSignature {
+    /// Encodes the signature in the wire format version of its Fiat-Shamir version.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let version = match self.version {
+            FiatShamirVersion::V1 => WIRE_FORMAT_V1,
+            FiatShamirVersion::V2 => WIRE_FORMAT_V2,
+        };
+        let mut buf = Vec::with_capacity(AMFSignature::WIRE_LENGTH);
+        WireHeader::write_version(
+            &mut buf,
+            version,
+            SUITE_RISTRETTO255_SHA512,
+            WireArtifactKind::Signature,
+        );
+        buf.extend(&self.amf_signature.to_bytes()[WIRE_HEADER_LENGTH..]);
+        buf
+    }
+
+    /// Decodes a signature of any supported wire format version, checking it as
+    /// `AMFSignature::from_bytes` does.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CodecError> {
+        let (header, body) = WireHeader::read(bytes, WireArtifactKind::Signature)?;
+        let version = match (header.version, header.suite) {
+            (WIRE_FORMAT_V1, SUITE_RISTRETTO255_SHA512) => FiatShamirVersion::V1,
+            (WIRE_FORMAT_V2, SUITE_RISTRETTO255_SHA512) => FiatShamirVersion::V2,
+            _ => return Err(header.unsupported()),
+        };
+        let body: [u8; SIGNATURE_BODY_LENGTH] =
+            body.try_into().map_err(|_| CodecError::InvalidLength {
+                expected: AMFSignature::WIRE_LENGTH,
+                actual: bytes.len(),
+            })?;
+        Ok(VersionedAMFSignature {
+            version,
+            amf_signature: CompressedAMFSignature::from_body(body).decompress()?,
+        })
+    }
+
+    /// `amf::verify` with the challenge hash of the signature's version
+    pub fn verify(
+        &self,
+        recipient_secret_key: impl Borrow<AMFSecretKey>,
+        sender_public_key: impl Borrow<AMFPublicKey>,
+        recipient_public_key: impl Borrow<AMFPublicKey>,
+        judge_public_key: impl Borrow<AMFPublicKey>,
+        message: &[u8],
+    ) -> bool {
+        let _span = trace::verify(AMFRole::Recipient, message.len());
+        verify_with(
+            &recipient_secret_key.borrow().secret_key,
+            recipient_public_key.borrow(),
+            self.amf_signature.recipient_ciphertext(),
+            sender_public_key.borrow(),
+            judge_public_key.borrow(),
+            message,
+            &self.amf_signature,
+            self.version,
+        )
+        .into()
+    }
+
+    /// `amf::judge` with the challenge hash of the signature's version
+    pub fn judge(
+        &self,
+        judge_secret_key: impl Borrow<AMFSecretKey>,
+        sender_public_key: impl Borrow<AMFPublicKey>,
+        _recipient_public_key: impl Borrow<AMFPublicKey>,
+        judge_public_key: impl Borrow<AMFPublicKey>,
+        message: &[u8],
+    ) -> bool {
+        let _span = trace::verify(AMFRole::Judge, message.len());
+        let judge_public_key = judge_public_key.borrow();
+        verify_with(
+            &judge_secret_key.borrow().secret_key,
+            judge_public_key,
+            self.amf_signature.judge_ciphertext(),
+            sender_public_key.borrow(),
+            judge_public_key,
+            message,
+            &self.amf_signature,
+            self.version,
+        )
+        .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amf::{codec::WIRE_FORMAT_VERSION, franking::frank_with_version, keygen};
+
+    #[test]
+    fn test_versioned_signatures() {
+        let mut rng = rand::thread_rng();
+        let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+        let (recipient_public_key, recipient_secret_key) = keygen(AMFRole::Recipient);
+        let (judge_public_key, judge_secret_key) = keygen(AMFRole::Judge);
+        let message = b"hello world!";
+        let frank_as = |version, rng: &mut rand::rngs::ThreadRng| {
+            frank_with_version(
+                rng,
+                version,
+                &sender_secret_key,
+                &sender_public_key,
+                &recipient_public_key,
+                &judge_public_key,
+                message,
+            )
+        };
+
+        // 0. A version 1 signature is rejected by `AMFSignature::from_bytes`, but decodes here
+        let legacy = VersionedAMFSignature {
+            version: FiatShamirVersion::V1,
+            amf_signature: frank_as(FiatShamirVersion::V1, &mut rng),
+        };
+        let encoded = legacy.to_bytes();
+        assert_eq!(encoded[3], WIRE_FORMAT_V1);
+        assert_eq!(
+            AMFSignature::from_bytes(&encoded),
+            Err(CodecError::UnsupportedVersion(WIRE_FORMAT_V1))
+        );
+        let decoded = VersionedAMFSignature::from_bytes(&encoded).unwrap();
+        assert_eq!(decoded, legacy);
+
+        // 1. It verifies and judges with the V1 challenge hash, and only with it
+        let keys = (sender_public_key, recipient_public_key, judge_public_key);
+        let checks = |signature: &VersionedAMFSignature| {
+            (
+                signature.verify(&recipient_secret_key, keys.0, keys.1, keys.2, message),
+                signature.judge(&judge_secret_key, keys.0, keys.1, keys.2, message),
+            )
+        };
+        assert_eq!(checks(&decoded), (true, true));
+        assert!(!crate::amf::verify(
+            &recipient_secret_key,
+            keys.0,
+            keys.1,
+            keys.2,
+            message,
+            decoded.amf_signature
+        ));
+        let relabeled = VersionedAMFSignature::from(decoded.amf_signature);
+        assert_eq!(checks(&relabeled), (false, false));
+
+        // 2. A current signature round-trips as one of the current version
+        let current = VersionedAMFSignature::from(frank_as(FiatShamirVersion::V2, &mut rng));
+        let encoded = current.to_bytes();
+        assert_eq!(encoded, current.amf_signature.to_bytes());
+        assert_eq!(VersionedAMFSignature::from_bytes(&encoded), Ok(current));
+        assert_eq!(checks(&current), (true, true));
+
+        // 3. Unknown versions are still rejected
+        let mut unknown = encoded.clone();
+        unknown[3] = WIRE_FORMAT_VERSION + 1;
+        assert_eq!(
+            VersionedAMFSignature::from_bytes(&unknown),
+            Err(CodecError::UnsupportedVersion(WIRE_FORMAT_VERSION + 1))
+        );
+    }
+}
@@ -0,0 +1,106 @@
+//! The Crate-Wide Error Type
+//!
+//! `Error` wraps the errors of the modules, so that callers can propagate any of them with `?`,
+//! and adds the role mismatches of the checked AMF algorithms (`amf::try_frank`,
+//! `amf::try_verify` and `amf::try_judge`) and the missing keys of the builders of `amf::builder`.
+//! `ffi` maps it to an `AmazeStatus`.
+
+use core::fmt;
+
+#[cfg(feature = "std")]
+use crate::amf::stream::StreamError;
+use crate::{
+    amf::{codec::CodecError, forward_secure::ForwardSecureError, AMFRole, VerifyError},
+    pok::error::PokError,
+};
+
+/// Errors of the public fallible APIs of this crate.
+#[derive(Debug)]
+pub enum Error {
+    /// A key or signature did not decode.
+    Codec(CodecError),
+    /// A signature stream did not read or write.
+    #[cfg(feature = "std")]
+    Stream(StreamError),
+    /// A key of one role was given where a key of another role was expected.
+    RoleMismatch { expected: AMFRole, actual: AMFRole },
+    /// A builder was built without the key of a role.
+    MissingKey(AMFRole),
+    /// A signature was rejected, with the reason.
+    Verify(VerifyError),
+    /// A prover or signer could not prove its witness.
+    Pok(PokError),
+    /// A forward-secure key could not be generated or evolved.
+    ForwardSecure(ForwardSecureError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Codec(error) => error.fmt(f),
+            #[cfg(feature = "std")]
+            Error::Stream(error) => error.fmt(f),
+            Error::RoleMismatch { expected, actual } => {
+                write!(f, "expected a {:?} key, got a {:?} key", expected, actual)
+            }
+            Error::MissingKey(role) => write!(f, "missing the {:?} key", role),
+            Error::Verify(error) => error.fmt(f),
+            Error::Pok(error) => error.fmt(f),
+            Error::ForwardSecure(error) => error.fmt(f),
+        }
+    }
+}
+
+impl core::error::Error for Error {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Error::Codec(error) => Some(error),
+            #[cfg(feature = "std")]
+            Error::Stream(error) => Some(error),
+            Error::RoleMismatch { .. } | Error::MissingKey(_) => None,
+            Error::Verify(error) => Some(error),
+            Error::Pok(error) => Some(error),
+            Error::ForwardSecure(error) => Some(error),
+        }
+    }
+}
+
+impl From<CodecError> for Error {
+    fn from(error: CodecError) -> Self {
+        Error::Codec(error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<StreamError> for Error {
+    fn from(error: StreamError) -> Self {
+        Error::Stream(error)
+    }
+}
+
+impl From<VerifyError> for Error {
+    fn from(error: VerifyError) -> Self {
+        Error::Verify(error)
+    }
+}
+
+impl From<PokError> for Error {
+    fn from(error: PokError) -> Self {
+        Error::Pok(error)
+    }
+}
+
+impl From<ForwardSecureError> for Error {
+    fn from(error: ForwardSecureError) -> Self {
+        Error::ForwardSecure(error)
+    }
+}
+
+/// Checks that a key has the `expected` role.
+pub(crate) fn check_role(expected: AMFRole, actual: AMFRole) -> Result<(), Error> {
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(Error::RoleMismatch { expected, actual })
+    }
+}
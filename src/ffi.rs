@@ -0,0 +1,368 @@
+//! C ABI
+//!
+//! With the `ffi` feature, KeyGen, Frank, Verify and Judge are exported as `extern "C"` functions
+//! on byte buffers, for C, C++ and Swift code. Keys and signatures are in the wire format of
+//! `amf::codec`, in caller-allocated buffers of exactly `AMAZE_PUBLIC_KEY_LENGTH`,
+//! `AMAZE_SECRET_KEY_LENGTH` and `AMAZE_SIGNATURE_LENGTH` bytes; roles are the wire format's role
+//! bytes (0 sender, 1 recipient, 2 judge). Every function returns an `AmazeStatus`, and never
+//! unwinds into the caller.
+//!
+//! `include/amaze.h` is generated from this module by cbindgen, with `cbindgen.toml`:
+//!
+//! ```shell
+//! cbindgen --config cbindgen.toml --output include/amaze.h
+//! cargo rustc --release --features ffi --crate-type staticlib
+//! ```
+
+use std::{panic, slice};
+
+use crate::{
+    amf::{
+        self,
+        codec::{role_from_byte, CodecError},
+        AMFPublicKey, AMFSecretKey, AMFSignature,
+    },
+    Error,
+};
+
+// Literals rather than the `WIRE_LENGTH`s, which cbindgen cannot evaluate; `test_ffi` checks them.
+/// The length of an encoded public key.
+pub const AMAZE_PUBLIC_KEY_LENGTH: usize = 39;
+/// The length of an encoded secret key.
+pub const AMAZE_SECRET_KEY_LENGTH: usize = 39;
+/// The length of an encoded signature.
+pub const AMAZE_SIGNATURE_LENGTH: usize = 486;
+
+/// The outcome of a call.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AmazeStatus {
+    /// The call succeeded, and for `amaze_verify` and `amaze_judge`, the signature is valid.
+    Ok = 0,
+    /// The signature is well-formed, but does not verify.
+    Rejected = 1,
+    /// A required pointer was null.
+    NullPointer = 2,
+    /// The role byte is not 0, 1, or 2, or a key is of another role than its argument.
+    InvalidRole = 3,
+    /// A key or signature did not decode.
+    InvalidEncoding = 4,
+    /// The library panicked; this is a bug.
+    Panic = 5,
+}
+
+impl From<CodecError> for AmazeStatus {
+    fn from(error: CodecError) -> Self {
+        match error {
+            CodecError::InvalidRole(_) => AmazeStatus::InvalidRole,
+            _ => AmazeStatus::InvalidEncoding,
+        }
+    }
+}
+
+/// Rejections are `Rejected`, whatever their reason; these calls neither read streams, nor prove
+/// witnesses of their own, nor build from builders, so the other errors are encoding errors.
+impl From<Error> for AmazeStatus {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::Codec(error) => error.into(),
+            Error::RoleMismatch { .. } => AmazeStatus::InvalidRole,
+            Error::Verify(_) => AmazeStatus::Rejected,
+            Error::Stream(_) | Error::Pok(_) | Error::MissingKey(_) | Error::ForwardSecure(_) => {
+                AmazeStatus::InvalidEncoding
+            }
+        }
+    }
+}
+
+/// views `len` bytes at `ptr`, or fails with `NullPointer`; a null `ptr` is fine for `len == 0`
+unsafe fn input<'a>(ptr: *const u8, len: usize) -> Result<&'a [u8], AmazeStatus> {
+    match (ptr.is_null(), len) {
+        (true, 0) => Ok(&[]),
+        (true, _) => Err(AmazeStatus::NullPointer),
+        (false, _) => Ok(slice::from_raw_parts(ptr, len)),
+    }
+}
+
+/// copies `bytes` to `ptr`, or fails with `NullPointer`
+unsafe fn output(ptr: *mut u8, bytes: &[u8]) -> Result<(), AmazeStatus> {
+    if ptr.is_null() {
+        return Err(AmazeStatus::NullPointer);
+    }
+    slice::from_raw_parts_mut(ptr, bytes.len()).copy_from_slice(bytes);
+    Ok(())
+}
+
+/// the decoded public keys of a Frank, Verify or Judge call
+unsafe fn public_keys(
+    sender_public_key: *const u8,
+    recipient_public_key: *const u8,
+    judge_public_key: *const u8,
+) -> Result<[AMFPublicKey; 3], AmazeStatus> {
+    let decode = |ptr| -> Result<AMFPublicKey, AmazeStatus> {
+        Ok(AMFPublicKey::from_bytes(input(
+            ptr,
+            AMAZE_PUBLIC_KEY_LENGTH,
+        )?)?)
+    };
+    Ok([
+        decode(sender_public_key)?,
+        decode(recipient_public_key)?,
+        decode(judge_public_key)?,
+    ])
+}
+
+/// runs `f`, turning its errors and panics into a status
+fn status<F: FnOnce() -> Result<AmazeStatus, AmazeStatus> + panic::UnwindSafe>(
+    f: F,
+) -> AmazeStatus {
+    match panic::catch_unwind(f) {
+        Ok(Ok(status)) | Ok(Err(status)) => status,
+        Err(_) => AmazeStatus::Panic,
+    }
+}
+
+/// Generates a key pair for `role`.
+///
+/// # Safety
+///
+/// `public_key_out` and `secret_key_out` must be valid for writes of `AMAZE_PUBLIC_KEY_LENGTH` and
+/// `AMAZE_SECRET_KEY_LENGTH` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn amaze_keygen(
+    role: u8,
+    public_key_out: *mut u8,
+    secret_key_out: *mut u8,
+) -> AmazeStatus {
+    status(|| {
+        let (public_key, secret_key) = amf::keygen(role_from_byte(role)?);
+        output(public_key_out, &public_key.to_bytes())?;
+        output(secret_key_out, &secret_key.to_bytes())?;
+        Ok(AmazeStatus::Ok)
+    })
+}
+
+/// Franks the `message_len` bytes at `message`.
+///
+/// # Safety
+///
+/// The key pointers must be valid for reads of their `AMAZE_*_LENGTH`, `message` for reads of
+/// `message_len` bytes (or null if `message_len` is 0), and `signature_out` for writes of
+/// `AMAZE_SIGNATURE_LENGTH` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn amaze_frank(
+    sender_secret_key: *const u8,
+    sender_public_key: *const u8,
+    recipient_public_key: *const u8,
+    judge_public_key: *const u8,
+    message: *const u8,
+    message_len: usize,
+    signature_out: *mut u8,
+) -> AmazeStatus {
+    status(|| {
+        let sender_secret_key =
+            AMFSecretKey::from_bytes(input(sender_secret_key, AMAZE_SECRET_KEY_LENGTH)?)?;
+        let [sender_public_key, recipient_public_key, judge_public_key] =
+            public_keys(sender_public_key, recipient_public_key, judge_public_key)?;
+        let amf_signature = amf::try_frank(
+            sender_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            input(message, message_len)?,
+        )?;
+        output(signature_out, &amf_signature.to_bytes())?;
+        Ok(AmazeStatus::Ok)
+    })
+}
+
+/// `amf::try_verify` or `amf::try_judge`
+type Check = fn(
+    AMFSecretKey,
+    AMFPublicKey,
+    AMFPublicKey,
+    AMFPublicKey,
+    &[u8],
+    AMFSignature,
+) -> Result<(), Error>;
+
+/// the shared body of `amaze_verify` and `amaze_judge`
+#[allow(clippy::too_many_arguments)]
+unsafe fn check(
+    algorithm: Check,
+    secret_key: *const u8,
+    sender_public_key: *const u8,
+    recipient_public_key: *const u8,
+    judge_public_key: *const u8,
+    message: *const u8,
+    message_len: usize,
+    signature: *const u8,
+) -> AmazeStatus {
+    status(|| {
+        let secret_key = AMFSecretKey::from_bytes(input(secret_key, AMAZE_SECRET_KEY_LENGTH)?)?;
+        let [sender_public_key, recipient_public_key, judge_public_key] =
+            public_keys(sender_public_key, recipient_public_key, judge_public_key)?;
+        let amf_signature = AMFSignature::from_bytes(input(signature, AMAZE_SIGNATURE_LENGTH)?)?;
+        algorithm(
+            secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            input(message, message_len)?,
+            amf_signature,
+        )?;
+        Ok(AmazeStatus::Ok)
+    })
+}
+
+/// Verifies a franked message as its recipient: `Ok` if the signature is valid, `Rejected` if not.
+///
+/// # Safety
+///
+/// As for `amaze_frank`, with `signature` valid for reads of `AMAZE_SIGNATURE_LENGTH` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn amaze_verify(
+    recipient_secret_key: *const u8,
+    sender_public_key: *const u8,
+    recipient_public_key: *const u8,
+    judge_public_key: *const u8,
+    message: *const u8,
+    message_len: usize,
+    signature: *const u8,
+) -> AmazeStatus {
+    check(
+        amf::try_verify,
+        recipient_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        message,
+        message_len,
+        signature,
+    )
+}
+
+/// Judges a reported message: `Ok` if the signature is valid, `Rejected` if not.
+///
+/// # Safety
+///
+/// As for `amaze_verify`.
+#[no_mangle]
+pub unsafe extern "C" fn amaze_judge(
+    judge_secret_key: *const u8,
+    sender_public_key: *const u8,
+    recipient_public_key: *const u8,
+    judge_public_key: *const u8,
+    message: *const u8,
+    message_len: usize,
+    signature: *const u8,
+) -> AmazeStatus {
+    check(
+        amf::try_judge,
+        judge_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        message,
+        message_len,
+        signature,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ptr;
+
+    use super::*;
+
+    #[test]
+    fn test_ffi() {
+        assert_eq!(AMAZE_PUBLIC_KEY_LENGTH, AMFPublicKey::WIRE_LENGTH);
+        assert_eq!(AMAZE_SECRET_KEY_LENGTH, AMFSecretKey::WIRE_LENGTH);
+        assert_eq!(AMAZE_SIGNATURE_LENGTH, AMFSignature::WIRE_LENGTH);
+
+        unsafe {
+            // 0. Generate keys into caller buffers
+            let mut public_keys = [[0u8; AMAZE_PUBLIC_KEY_LENGTH]; 3];
+            let mut secret_keys = [[0u8; AMAZE_SECRET_KEY_LENGTH]; 3];
+            for role in 0..3 {
+                assert_eq!(
+                    amaze_keygen(
+                        role,
+                        public_keys[role as usize].as_mut_ptr(),
+                        secret_keys[role as usize].as_mut_ptr()
+                    ),
+                    AmazeStatus::Ok
+                );
+            }
+            let [sender, recipient, judge] = [0, 1, 2].map(|role| public_keys[role].as_ptr());
+
+            // 1. Frank, verify and judge
+            let message = b"hello world!";
+            let mut signature = [0u8; AMAZE_SIGNATURE_LENGTH];
+            assert_eq!(
+                amaze_frank(
+                    secret_keys[0].as_ptr(),
+                    sender,
+                    recipient,
+                    judge,
+                    message.as_ptr(),
+                    message.len(),
+                    signature.as_mut_ptr()
+                ),
+                AmazeStatus::Ok
+            );
+            let check = |algorithm: unsafe extern "C" fn(_, _, _, _, _, _, _) -> _,
+                         secret_key: &[u8],
+                         message: &[u8]| {
+                algorithm(
+                    secret_key.as_ptr(),
+                    sender,
+                    recipient,
+                    judge,
+                    message.as_ptr(),
+                    message.len(),
+                    signature.as_ptr(),
+                )
+            };
+            assert_eq!(
+                check(amaze_verify, &secret_keys[1], message),
+                AmazeStatus::Ok
+            );
+            assert_eq!(
+                check(amaze_judge, &secret_keys[2], message),
+                AmazeStatus::Ok
+            );
+            assert_eq!(
+                check(amaze_verify, &secret_keys[1], b"goodbye world!"),
+                AmazeStatus::Rejected
+            );
+
+            // 2. Bad roles, encodings and pointers are reported
+            assert_eq!(
+                amaze_keygen(3, public_keys[0].as_mut_ptr(), secret_keys[0].as_mut_ptr()),
+                AmazeStatus::InvalidRole
+            );
+            assert_eq!(
+                check(amaze_verify, &public_keys[1], message),
+                AmazeStatus::InvalidEncoding
+            );
+            assert_eq!(
+                check(amaze_verify, &secret_keys[2], message),
+                AmazeStatus::InvalidRole
+            );
+            assert_eq!(
+                amaze_verify(
+                    secret_keys[1].as_ptr(),
+                    sender,
+                    ptr::null(),
+                    judge,
+                    message.as_ptr(),
+                    message.len(),
+                    signature.as_ptr()
+                ),
+                AmazeStatus::NullPointer
+            );
+        }
+    }
+}
@@ -0,0 +1,329 @@
+//! Franking Matrix Events
+//!
+//! The sender franks the canonical JSON (as in the Matrix specification's signing rules) of
+//!
+//! ```text
+//! {"content": <content without SIGNATURE_FIELD>, "room_id": <room id>, "type": <event type>}
+//! ```
+//!
+//! and attaches the signature to the content as `SIGNATURE_FIELD`, in unpadded base64 of the wire
+//! format. Binding the room and event type keeps a franked message from being replayed into
+//! another room or as another kind of event; the fields the homeserver adds (`event_id`,
+//! `sender`, `origin_server_ts`, ...) are unknown to the sender, and are not franked. In encrypted
+//! rooms, frank the plaintext event before encrypting it, and verify it after decrypting it.
+//!
+//! ```rust,ignore
+//! use amaze::integrations::matrix;
+//!
+//! let mut content = serde_json::json!({"msgtype": "m.text", "body": "hello world!"});
+//! matrix::frank_content(sender_sk, sender_pk, recipient_pk, judge_pk, room_id, "m.room.message",
+//!     &mut content)?;
+//! // ... send, receive ...
+//! assert!(matrix::verify_content(recipient_sk, sender_pk, recipient_pk, judge_pk, room_id,
+//!     "m.room.message", &content)?);
+//! ```
+
+use std::fmt;
+
+use base64::{engine::general_purpose::STANDARD_NO_PAD, Engine};
+use serde_json::{Map, Value};
+
+use crate::amf::{self, codec::CodecError, AMFPublicKey, AMFSecretKey, AMFSignature};
+
+/// The content field that carries the signature.
+pub const SIGNATURE_FIELD: &str = "io.github.sgmenda.amaze.signature";
+
+/// The largest magnitude of an integer in canonical JSON.
+const MAX_SAFE_INTEGER: i64 = (1 << 53) - 1;
+
+/// Errors that can occur while franking, verifying or judging an event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MatrixError {
+    /// The content is not a JSON object.
+    NotAnObject,
+    /// The content has a number that canonical JSON does not allow: a float, or an integer
+    /// outside of `[-(2^53 - 1), 2^53 - 1]`.
+    InvalidNumber,
+    /// The content has no `SIGNATURE_FIELD`.
+    MissingSignature,
+    /// `SIGNATURE_FIELD` is not an unpadded base64 string.
+    InvalidBase64,
+    /// `SIGNATURE_FIELD` does not decode to a signature.
+    InvalidSignature(CodecError),
+}
+
+impl fmt::Display for MatrixError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatrixError::NotAnObject => write!(f, "event content is not a JSON object"),
+            MatrixError::InvalidNumber => write!(f, "number not allowed in canonical JSON"),
+            MatrixError::MissingSignature => write!(f, "no {} in event content", SIGNATURE_FIELD),
+            MatrixError::InvalidBase64 => write!(f, "{} is not unpadded base64", SIGNATURE_FIELD),
+            MatrixError::InvalidSignature(error) => write!(f, "invalid signature: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for MatrixError {}
+
+/// Encodes `value` as canonical JSON: object keys sorted by code point, no insignificant
+/// whitespace, and only integers in the safe range.
+pub fn canonical_json(value: &Value) -> Result<String, MatrixError> {
+    let mut out = String::new();
+    write_canonical_json(value, &mut out)?;
+    Ok(out)
+}
+
+fn write_canonical_json(value: &Value, out: &mut String) -> Result<(), MatrixError> {
+    match value {
+        Value::Null | Value::Bool(_) | Value::String(_) => out.push_str(&value.to_string()),
+        Value::Number(number) => match number.as_i64() {
+            Some(integer) if (-MAX_SAFE_INTEGER..=MAX_SAFE_INTEGER).contains(&integer) => {
+                out.push_str(&integer.to_string())
+            }
+            _ => return Err(MatrixError::InvalidNumber),
+        },
+        Value::Array(values) => {
+            out.push('[');
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical_json(value, out)?;
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_unstable_by_key(|(key, _)| *key);
+            out.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical_json(value, out)?;
+            }
+            out.push('}');
+        }
+    }
+    Ok(())
+}
+
+/// the franked bytes of an event
+fn franked_message(
+    room_id: &str,
+    event_type: &str,
+    content: &Map<String, Value>,
+) -> Result<Vec<u8>, MatrixError> {
+    let mut content = content.clone();
+    content.remove(SIGNATURE_FIELD);
+    let mut franked = Map::new();
+    franked.insert("content".into(), Value::Object(content));
+    franked.insert("room_id".into(), room_id.into());
+    franked.insert("type".into(), event_type.into());
+    Ok(canonical_json(&Value::Object(franked))?.into_bytes())
+}
+
+/// the franked bytes of an event, and its signature
+fn franked_message_and_signature(
+    room_id: &str,
+    event_type: &str,
+    content: &Value,
+) -> Result<(Vec<u8>, AMFSignature), MatrixError> {
+    let content = content.as_object().ok_or(MatrixError::NotAnObject)?;
+    let encoded_signature = content
+        .get(SIGNATURE_FIELD)
+        .ok_or(MatrixError::MissingSignature)?
+        .as_str()
+        .ok_or(MatrixError::InvalidBase64)?;
+    let signature_bytes = STANDARD_NO_PAD
+        .decode(encoded_signature)
+        .map_err(|_| MatrixError::InvalidBase64)?;
+    let amf_signature =
+        AMFSignature::from_bytes(&signature_bytes).map_err(MatrixError::InvalidSignature)?;
+    Ok((
+        franked_message(room_id, event_type, content)?,
+        amf_signature,
+    ))
+}
+
+/// Franks the `content` of an event of type `event_type` in `room_id`, and attaches the signature
+/// to it as `SIGNATURE_FIELD`.
+pub fn frank_content(
+    sender_secret_key: AMFSecretKey,
+    sender_public_key: AMFPublicKey,
+    recipient_public_key: AMFPublicKey,
+    judge_public_key: AMFPublicKey,
+    room_id: &str,
+    event_type: &str,
+    content: &mut Value,
+) -> Result<(), MatrixError> {
+    let message = franked_message(
+        room_id,
+        event_type,
+        content.as_object().ok_or(MatrixError::NotAnObject)?,
+    )?;
+    let amf_signature = amf::frank(
+        sender_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        &message,
+    );
+    content.as_object_mut().unwrap().insert(
+        SIGNATURE_FIELD.into(),
+        STANDARD_NO_PAD.encode(amf_signature.to_bytes()).into(),
+    );
+    Ok(())
+}
+
+/// Verifies the franked `content` of an incoming event as its recipient; errors are for content
+/// that is not franked or malformed, and `Ok(false)` for a signature that does not verify.
+pub fn verify_content(
+    recipient_secret_key: AMFSecretKey,
+    sender_public_key: AMFPublicKey,
+    recipient_public_key: AMFPublicKey,
+    judge_public_key: AMFPublicKey,
+    room_id: &str,
+    event_type: &str,
+    content: &Value,
+) -> Result<bool, MatrixError> {
+    let (message, amf_signature) = franked_message_and_signature(room_id, event_type, content)?;
+    Ok(amf::verify(
+        recipient_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        &message,
+        amf_signature,
+    ))
+}
+
+/// Judges the franked `content` of a reported event, as `verify_content`.
+pub fn judge_content(
+    judge_secret_key: AMFSecretKey,
+    sender_public_key: AMFPublicKey,
+    recipient_public_key: AMFPublicKey,
+    judge_public_key: AMFPublicKey,
+    room_id: &str,
+    event_type: &str,
+    content: &Value,
+) -> Result<bool, MatrixError> {
+    let (message, amf_signature) = franked_message_and_signature(room_id, event_type, content)?;
+    Ok(amf::judge(
+        judge_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        &message,
+        amf_signature,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::amf::{keygen, AMFRole};
+
+    #[test]
+    fn test_canonical_json() {
+        // 0. The examples of the Matrix specification
+        assert_eq!(
+            canonical_json(&json!({"b": "2", "a": "1"})).unwrap(),
+            r#"{"a":"1","b":"2"}"#
+        );
+        assert_eq!(
+            canonical_json(&json!({"本": 2, "日": 1})).unwrap(),
+            r#"{"日":1,"本":2}"#
+        );
+        assert_eq!(
+            canonical_json(&json!({"a": "\u{65e5}", "b": [1, {"d": null, "c": true}]})).unwrap(),
+            r#"{"a":"日","b":[1,{"c":true,"d":null}]}"#
+        );
+
+        // 1. Floats and unsafe integers have no canonical encoding
+        assert_eq!(
+            canonical_json(&json!({"a": 1.5})),
+            Err(MatrixError::InvalidNumber)
+        );
+        assert_eq!(
+            canonical_json(&json!(1u64 << 53)),
+            Err(MatrixError::InvalidNumber)
+        );
+    }
+
+    #[test]
+    fn test_matrix_franking() {
+        // 0. Initialize a Sender, Recipient, and Judge
+        let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+        let (recipient_public_key, recipient_secret_key) = keygen(AMFRole::Recipient);
+        let (judge_public_key, judge_secret_key) = keygen(AMFRole::Judge);
+        let room_id = "!room:example.org";
+        let event_type = "m.room.message";
+
+        // 1. Frank the content of an event
+        let mut content = json!({"msgtype": "m.text", "body": "hello world!"});
+        frank_content(
+            sender_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            room_id,
+            event_type,
+            &mut content,
+        )
+        .unwrap();
+        assert!(content[SIGNATURE_FIELD].is_string());
+
+        // 2. The recipient verifies it after it went over the wire, and the judge judges it
+        let received: Value =
+            serde_json::from_str(&serde_json::to_string_pretty(&content).unwrap()).unwrap();
+        let verify = |room_id, content: &Value| {
+            verify_content(
+                recipient_secret_key.clone(),
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                room_id,
+                event_type,
+                content,
+            )
+        };
+        assert_eq!(verify(room_id, &received), Ok(true));
+        assert_eq!(
+            judge_content(
+                judge_secret_key,
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                room_id,
+                event_type,
+                &received,
+            ),
+            Ok(true)
+        );
+
+        // 3. Edited content, or content replayed into another room, does not verify
+        let mut edited = content.clone();
+        edited["body"] = "goodbye world!".into();
+        assert_eq!(verify(room_id, &edited), Ok(false));
+        assert_eq!(verify("!other:example.org", &content), Ok(false));
+
+        // 4. Content that is not franked is an error
+        let mut unfranked = content.clone();
+        unfranked.as_object_mut().unwrap().remove(SIGNATURE_FIELD);
+        assert_eq!(
+            verify(room_id, &unfranked),
+            Err(MatrixError::MissingSignature)
+        );
+        unfranked[SIGNATURE_FIELD] = "AAAA".into();
+        assert!(matches!(
+            verify(room_id, &unfranked),
+            Err(MatrixError::InvalidSignature(_))
+        ));
+    }
+}
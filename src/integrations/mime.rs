@@ -0,0 +1,445 @@
+//! Franking MIME Messages
+//!
+//! Two ways to carry a signature in email and other MIME-based transports:
+//!
+//! - As a signature part: `frank_multipart` wraps a MIME entity (its headers and body) into a
+//!   `multipart/signed` entity (cf. RFC 1847) whose second part is the ASCII-armored signature,
+//!   with `protocol="application/amf-signature"`. The franked message is the inner entity, so its
+//!   headers (e.g., a `Subject` inside the entity) are franked too.
+//! - As a header: `frank_header` prepends an `AMF-Signature` header field to a message, and franks
+//!   its body only, so that header fields added in transit (`Received`, `DKIM-Signature`, ...)
+//!   do not break verification.
+//!
+//! Both franks are of the canonical form of the content, with CRLF line endings, and
+//! `open_multipart` and `open_header` extract the `Franked` content and signature on receipt. As
+//! with any AMF signature, the recipient cannot convince anyone but the judge that the sender sent
+//! the message.
+//!
+//! ```text
+//! Content-Type: multipart/signed; protocol="application/amf-signature"; micalg=sha512;
+//!  boundary="amf-..."
+//!
+//! --amf-...
+//! Content-Type: text/plain
+//!
+//! hello world!
+//! --amf-...
+//! Content-Type: application/amf-signature
+//!
+//! -----BEGIN AMF SIGNATURE-----
+//! QU1GAgEDRB...
+//! -----END AMF SIGNATURE-----
+//! --amf-...--
+//! ```
+
+use std::{borrow::Cow, fmt};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use crate::amf::{self, codec::CodecError, AMFPublicKey, AMFSecretKey, AMFSignature};
+
+/// The header field that carries the signature in `frank_header`.
+pub const HEADER_NAME: &str = "AMF-Signature";
+/// The media type of the signature part in `frank_multipart`.
+pub const SIGNATURE_MEDIA_TYPE: &str = "application/amf-signature";
+
+const ARMOR_BEGIN: &str = "-----BEGIN AMF SIGNATURE-----";
+const ARMOR_END: &str = "-----END AMF SIGNATURE-----";
+/// The length of armored base64 lines, and of folded header lines.
+const LINE_LENGTH: usize = 64;
+
+/// Errors that can occur while opening a franked message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MimeError {
+    /// The entity has no header block.
+    MissingHeaders,
+    /// The entity is not `multipart/signed` with `protocol="application/amf-signature"`.
+    NotAmfSigned,
+    /// The `multipart/signed` entity has no boundary, or not two parts.
+    MalformedMultipart,
+    /// The message has no `AMF-Signature` header field.
+    MissingSignature,
+    /// The signature part is not an armored signature.
+    InvalidArmor,
+    /// The signature is not valid base64.
+    InvalidBase64,
+    /// The signature does not decode.
+    InvalidSignature(CodecError),
+}
+
+impl fmt::Display for MimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MimeError::MissingHeaders => write!(f, "no header block"),
+            MimeError::NotAmfSigned => {
+                write!(
+                    f,
+                    "not multipart/signed with protocol {}",
+                    SIGNATURE_MEDIA_TYPE
+                )
+            }
+            MimeError::MalformedMultipart => write!(f, "malformed multipart/signed entity"),
+            MimeError::MissingSignature => write!(f, "no {} header field", HEADER_NAME),
+            MimeError::InvalidArmor => write!(f, "invalid signature armor"),
+            MimeError::InvalidBase64 => write!(f, "invalid base64 in signature"),
+            MimeError::InvalidSignature(error) => write!(f, "invalid signature: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for MimeError {}
+
+/// Franked content and its signature, as extracted from a message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Franked {
+    /// The franked content: the inner entity of a `multipart/signed` entity, or the body of a
+    /// message with an `AMF-Signature` header field, in canonical form.
+    pub content: Vec<u8>,
+    pub signature: AMFSignature,
+}
+
+impl Franked {
+    /// Verifies the content as its recipient.
+    pub fn verify(
+        &self,
+        recipient_secret_key: AMFSecretKey,
+        sender_public_key: AMFPublicKey,
+        recipient_public_key: AMFPublicKey,
+        judge_public_key: AMFPublicKey,
+    ) -> bool {
+        amf::verify(
+            recipient_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            &self.content,
+            self.signature,
+        )
+    }
+
+    /// Judges the reported content.
+    pub fn judge(
+        &self,
+        judge_secret_key: AMFSecretKey,
+        sender_public_key: AMFPublicKey,
+        recipient_public_key: AMFPublicKey,
+        judge_public_key: AMFPublicKey,
+    ) -> bool {
+        amf::judge(
+            judge_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            &self.content,
+            self.signature,
+        )
+    }
+}
+
+/// ASCII-armors a signature.
+pub fn armor(amf_signature: &AMFSignature) -> String {
+    let encoded = STANDARD.encode(amf_signature.to_bytes());
+    let mut armored = String::from(ARMOR_BEGIN);
+    for line in encoded.as_bytes().chunks(LINE_LENGTH) {
+        armored.push_str("\r\n");
+        armored.push_str(std::str::from_utf8(line).unwrap());
+    }
+    armored.push_str("\r\n");
+    armored.push_str(ARMOR_END);
+    armored
+}
+
+/// Decodes an ASCII-armored signature, ignoring surrounding whitespace.
+pub fn dearmor(armored: &str) -> Result<AMFSignature, MimeError> {
+    let encoded = armored
+        .trim()
+        .strip_prefix(ARMOR_BEGIN)
+        .and_then(|armored| armored.strip_suffix(ARMOR_END))
+        .ok_or(MimeError::InvalidArmor)?;
+    decode_base64_signature(encoded)
+}
+
+fn decode_base64_signature(encoded: &str) -> Result<AMFSignature, MimeError> {
+    let encoded: String = encoded.split_ascii_whitespace().collect();
+    let bytes = STANDARD
+        .decode(encoded)
+        .map_err(|_| MimeError::InvalidBase64)?;
+    AMFSignature::from_bytes(&bytes).map_err(MimeError::InvalidSignature)
+}
+
+/// `bytes` with every line ending as CRLF
+fn canonicalize(bytes: &[u8]) -> Cow<'_, [u8]> {
+    let has_bare_line_feed = bytes
+        .iter()
+        .enumerate()
+        .any(|(i, &byte)| byte == b'\n' && (i == 0 || bytes[i - 1] != b'\r'));
+    if !has_bare_line_feed {
+        return Cow::Borrowed(bytes);
+    }
+    let mut canonical = Vec::with_capacity(bytes.len() + bytes.len() / 16);
+    for (i, &byte) in bytes.iter().enumerate() {
+        if byte == b'\n' && (i == 0 || bytes[i - 1] != b'\r') {
+            canonical.push(b'\r');
+        }
+        canonical.push(byte);
+    }
+    Cow::Owned(canonical)
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// splits a canonical entity into its header block (with its final CRLF) and its body
+fn split_entity(entity: &[u8]) -> Result<(&[u8], &[u8]), MimeError> {
+    if let Some(body) = entity.strip_prefix(b"\r\n") {
+        return Ok((&[], body));
+    }
+    let end = find(entity, b"\r\n\r\n").ok_or(MimeError::MissingHeaders)?;
+    Ok((&entity[..end + 2], &entity[end + 4..]))
+}
+
+/// the header fields of a header block, as `(name, raw field with its CRLF)`s
+fn header_fields(headers: &[u8]) -> Vec<(String, &[u8])> {
+    let mut fields: Vec<(String, &[u8])> = Vec::new();
+    let mut start = 0;
+    while start < headers.len() {
+        // a field runs until a CRLF that is not followed by folding whitespace
+        let mut end = start;
+        loop {
+            end += find(&headers[end..], b"\r\n").map_or(headers.len() - end, |i| i + 2);
+            if end >= headers.len() || !matches!(headers[end], b' ' | b'\t') {
+                break;
+            }
+        }
+        let field = &headers[start..end];
+        let name = field.split(|&byte| byte == b':').next().unwrap_or(&[]);
+        fields.push((String::from_utf8_lossy(name).trim().to_string(), field));
+        start = end;
+    }
+    fields
+}
+
+/// the unfolded value of the first `name` field
+fn header_value(fields: &[(String, &[u8])], name: &str) -> Option<String> {
+    let (_, field) = fields
+        .iter()
+        .find(|(field_name, _)| field_name.eq_ignore_ascii_case(name))?;
+    let field = String::from_utf8_lossy(field);
+    let (_, value) = field.split_once(':')?;
+    Some(value.replace("\r\n", "").trim().to_string())
+}
+
+/// the value of the parameter `name` in a structured header value such as a `Content-Type`
+fn parameter(value: &str, name: &str) -> Option<String> {
+    value.split(';').skip(1).find_map(|parameter| {
+        let (key, value) = parameter.split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case(name)
+            .then(|| value.trim().trim_matches('"').to_string())
+    })
+}
+
+/// Franks a MIME entity (its header fields, an empty line, and its body), and wraps it into a
+/// `multipart/signed` entity with its armored signature; prepend the message's own header fields
+/// (`From`, `To`, `MIME-Version`, ...) to send it.
+pub fn frank_multipart(
+    sender_secret_key: AMFSecretKey,
+    sender_public_key: AMFPublicKey,
+    recipient_public_key: AMFPublicKey,
+    judge_public_key: AMFPublicKey,
+    entity: &[u8],
+) -> Vec<u8> {
+    let entity = canonicalize(entity);
+    let amf_signature = amf::frank(
+        sender_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        &entity,
+    );
+    let boundary = format!("amf-{}", hex::encode(rand::random::<[u8; 16]>()));
+    let mut signed = format!(
+        "Content-Type: multipart/signed; protocol=\"{}\"; micalg=sha512;\r\n boundary=\"{}\"\r\n\r\n--{}\r\n",
+        SIGNATURE_MEDIA_TYPE, boundary, boundary
+    )
+    .into_bytes();
+    signed.extend_from_slice(&entity);
+    signed.extend_from_slice(
+        format!(
+            "\r\n--{}\r\nContent-Type: {}\r\n\r\n{}\r\n--{}--\r\n",
+            boundary,
+            SIGNATURE_MEDIA_TYPE,
+            armor(&amf_signature),
+            boundary
+        )
+        .as_bytes(),
+    );
+    signed
+}
+
+/// Extracts the franked entity and signature of a `multipart/signed` entity or message made with
+/// `frank_multipart`.
+pub fn open_multipart(message: &[u8]) -> Result<Franked, MimeError> {
+    let message = canonicalize(message);
+    let (headers, body) = split_entity(&message)?;
+    let content_type =
+        header_value(&header_fields(headers), "Content-Type").ok_or(MimeError::NotAmfSigned)?;
+    let is_amf_signed = content_type
+        .split(';')
+        .next()
+        .is_some_and(|media_type| media_type.trim().eq_ignore_ascii_case("multipart/signed"))
+        && parameter(&content_type, "protocol")
+            .is_some_and(|protocol| protocol.eq_ignore_ascii_case(SIGNATURE_MEDIA_TYPE));
+    if !is_amf_signed {
+        return Err(MimeError::NotAmfSigned);
+    }
+    let boundary = parameter(&content_type, "boundary").ok_or(MimeError::MalformedMultipart)?;
+
+    // the CRLF before a delimiter belongs to the delimiter (RFC 2046)
+    let delimiter = format!("\r\n--{}", boundary).into_bytes();
+    let first = find(body, &delimiter[2..]).ok_or(MimeError::MalformedMultipart)?;
+    let mut parts = body[first + delimiter.len() - 2..]
+        .strip_prefix(b"\r\n")
+        .ok_or(MimeError::MalformedMultipart)?;
+    let entity_end = find(parts, &delimiter).ok_or(MimeError::MalformedMultipart)?;
+    let entity = &parts[..entity_end];
+    parts = parts[entity_end + delimiter.len()..]
+        .strip_prefix(b"\r\n")
+        .ok_or(MimeError::MalformedMultipart)?;
+    let signature_end = find(parts, &delimiter).ok_or(MimeError::MalformedMultipart)?;
+    let (_, armored) = split_entity(&parts[..signature_end])?;
+
+    let armored = std::str::from_utf8(armored).map_err(|_| MimeError::InvalidArmor)?;
+    Ok(Franked {
+        content: entity.to_vec(),
+        signature: dearmor(armored)?,
+    })
+}
+
+/// Franks the body of a message (its header fields, an empty line, and its body), and prepends
+/// the signature as an `AMF-Signature` header field.
+pub fn frank_header(
+    sender_secret_key: AMFSecretKey,
+    sender_public_key: AMFPublicKey,
+    recipient_public_key: AMFPublicKey,
+    judge_public_key: AMFPublicKey,
+    message: &[u8],
+) -> Result<Vec<u8>, MimeError> {
+    let message = canonicalize(message);
+    let (_, body) = split_entity(&message)?;
+    let amf_signature = amf::frank(
+        sender_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        body,
+    );
+    let encoded = STANDARD.encode(amf_signature.to_bytes());
+    let mut signed = format!("{}:", HEADER_NAME).into_bytes();
+    for line in encoded.as_bytes().chunks(LINE_LENGTH) {
+        signed.push(b' ');
+        signed.extend_from_slice(line);
+        signed.extend_from_slice(b"\r\n");
+    }
+    signed.extend_from_slice(&message);
+    Ok(signed)
+}
+
+/// Extracts the franked body and signature of a message made with `frank_header`.
+pub fn open_header(message: &[u8]) -> Result<Franked, MimeError> {
+    let message = canonicalize(message);
+    let (headers, body) = split_entity(&message)?;
+    let encoded =
+        header_value(&header_fields(headers), HEADER_NAME).ok_or(MimeError::MissingSignature)?;
+    Ok(Franked {
+        content: body.to_vec(),
+        signature: decode_base64_signature(&encoded)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amf::{keygen, AMFRole};
+
+    #[test]
+    fn test_mime_franking() {
+        // 0. Initialize a Sender, Recipient, and Judge
+        let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+        let (recipient_public_key, recipient_secret_key) = keygen(AMFRole::Recipient);
+        let (judge_public_key, judge_secret_key) = keygen(AMFRole::Judge);
+        let check = |franked: &Franked| {
+            (
+                franked.verify(
+                    recipient_secret_key.clone(),
+                    sender_public_key,
+                    recipient_public_key,
+                    judge_public_key,
+                ),
+                franked.judge(
+                    judge_secret_key.clone(),
+                    sender_public_key,
+                    recipient_public_key,
+                    judge_public_key,
+                ),
+            )
+        };
+
+        // 1. Frank an entity as a multipart/signed entity, with LF line endings
+        let entity = b"Content-Type: text/plain\nSubject: hi\n\nhello world!\n";
+        let signed = frank_multipart(
+            sender_secret_key.clone(),
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            entity,
+        );
+        let message = [
+            b"From: sender@example.org\r\nMIME-Version: 1.0\r\n".as_slice(),
+            &signed,
+        ]
+        .concat();
+        let franked = open_multipart(&message).unwrap();
+        assert_eq!(franked.content, canonicalize(entity).as_ref());
+        assert_eq!(check(&franked), (true, true));
+
+        // 2. Edits of the inner entity do not verify
+        let edited = String::from_utf8(message.clone())
+            .unwrap()
+            .replace("Subject: hi", "Subject: ho");
+        assert_eq!(
+            check(&open_multipart(edited.as_bytes()).unwrap()),
+            (false, false)
+        );
+
+        // 3. Frank a message in a header field, and add header fields in transit
+        let message = b"From: sender@example.org\r\nSubject: hi\r\n\r\nhello world!\r\n";
+        let signed = frank_header(
+            sender_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+        )
+        .unwrap();
+        let received = [b"Received: from mx.example.org\r\n".as_slice(), &signed].concat();
+        let franked = open_header(&received).unwrap();
+        assert_eq!(franked.content, b"hello world!\r\n");
+        assert_eq!(check(&franked), (true, true));
+
+        // 4. Armor round-trips, and malformed messages are errors
+        assert_eq!(
+            dearmor(&armor(&franked.signature)).unwrap(),
+            franked.signature
+        );
+        assert_eq!(open_header(message), Err(MimeError::MissingSignature));
+        assert_eq!(open_multipart(message), Err(MimeError::NotAmfSigned));
+        assert_eq!(
+            dearmor("-----BEGIN AMF SIGNATURE-----\r\n!!\r\n-----END AMF SIGNATURE-----"),
+            Err(MimeError::InvalidBase64)
+        );
+    }
+}
@@ -0,0 +1,10 @@
+//! Integrations with Messaging Protocols
+//!
+//! Glue for franking the messages of existing protocols, each behind a feature of its name.
+
+#[cfg(feature = "matrix")]
+pub mod matrix;
+#[cfg(feature = "mime")]
+pub mod mime;
+#[cfg(feature = "sealed-sender")]
+pub mod sealed_sender;
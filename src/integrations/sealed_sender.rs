@@ -0,0 +1,320 @@
+//! Franking Inside Sealed-Sender Envelopes
+//!
+//! An example of carrying an AMF signature inside a Signal-style sealed-sender envelope, where the
+//! server sees neither the sender nor the message:
+//!
+//! 1. The sender picks the ephemeral key `E = e * G` of the envelope, and franks the plaintext
+//!    bound to the envelope context `E || P`, where `P` is the recipient's sealing public key
+//!    (`amf::frank_with_context`).
+//! 2. The sender's AMF public key, the signature and the plaintext are encrypted with
+//!    ChaCha20-Poly1305 under `SHA-512(tag(SEALED_SENDER_LABEL) || tag(e * P) || tag(E || P))`
+//!    (truncated to 32 bytes), with `E || P` as associated data.
+//! 3. The recipient unseals the envelope, and verifies the frank in the same context.
+//!
+//! The frank cannot cover the outer ciphertext itself, which encrypts the frank; binding the
+//! ephemeral key, from which the encryption key is derived, ties the frank to this one envelope
+//! instead, so a franked message cannot be reported as delivered in another envelope. The
+//! recipient reports an `Unsealed` message with its context, and the judge judges it in that
+//! context. A real deployment would also authenticate the sender to the recipient (e.g., with a
+//! sender certificate) and pad the plaintext; this module only shows where the frank goes.
+
+use std::fmt;
+
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use curve25519_dalek::{
+    ristretto::{CompressedRistretto, RistrettoPoint},
+    scalar::Scalar,
+};
+use sha2::{Digest, Sha512};
+
+use crate::{
+    amf::{self, codec::CodecError, AMFPublicKey, AMFSecretKey, AMFSignature},
+    pok::domain::{absorb, SEALED_SENDER_LABEL},
+};
+
+/// The length of the envelope context `E || P`.
+pub const CONTEXT_LENGTH: usize = 64;
+
+/// Errors that can occur while unsealing an envelope.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SealedSenderError {
+    /// The envelope is too short.
+    InvalidLength,
+    /// The ephemeral key is not a valid point.
+    InvalidPoint,
+    /// The envelope does not decrypt under the sealing key, or was modified.
+    DecryptionFailed,
+    /// The sender's public key or the signature inside does not decode.
+    InvalidContent(CodecError),
+}
+
+impl fmt::Display for SealedSenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SealedSenderError::InvalidLength => write!(f, "envelope too short"),
+            SealedSenderError::InvalidPoint => write!(f, "invalid ephemeral key"),
+            SealedSenderError::DecryptionFailed => write!(f, "envelope does not decrypt"),
+            SealedSenderError::InvalidContent(error) => write!(f, "invalid content: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for SealedSenderError {}
+
+/// A recipient's public key for sealing envelopes to it, distinct from its AMF public key.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SealingPublicKey(pub RistrettoPoint);
+
+/// A recipient's secret key for unsealing envelopes.
+#[derive(Copy, Clone)]
+pub struct SealingSecretKey(pub Scalar);
+
+/// Generates a sealing key pair for a recipient.
+pub fn sealing_keygen() -> (SealingPublicKey, SealingSecretKey) {
+    let secret_key = Scalar::random(&mut rand::thread_rng());
+    (
+        SealingPublicKey(RistrettoPoint::mul_base(&secret_key)),
+        SealingSecretKey(secret_key),
+    )
+}
+
+/// A sealed envelope: the ephemeral key, and the encrypted sender, signature and plaintext.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Envelope {
+    pub ephemeral_public_key: CompressedRistretto,
+    pub ciphertext: Vec<u8>,
+}
+
+impl Envelope {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        [
+            self.ephemeral_public_key.as_bytes(),
+            self.ciphertext.as_slice(),
+        ]
+        .concat()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SealedSenderError> {
+        if bytes.len() < 32 {
+            return Err(SealedSenderError::InvalidLength);
+        }
+        Ok(Envelope {
+            ephemeral_public_key: CompressedRistretto::from_slice(&bytes[..32]).unwrap(),
+            ciphertext: bytes[32..].to_vec(),
+        })
+    }
+}
+
+/// An unsealed message: who sent it, what they sent, the frank, and the envelope context that the
+/// frank is bound to. Keep the context to report the message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unsealed {
+    pub sender_public_key: AMFPublicKey,
+    pub message: Vec<u8>,
+    pub amf_signature: AMFSignature,
+    pub context: [u8; CONTEXT_LENGTH],
+}
+
+impl Unsealed {
+    /// Verifies the frank as its recipient.
+    pub fn verify(
+        &self,
+        recipient_secret_key: AMFSecretKey,
+        recipient_public_key: AMFPublicKey,
+        judge_public_key: AMFPublicKey,
+    ) -> bool {
+        amf::verify_with_context(
+            recipient_secret_key,
+            self.sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            &self.context,
+            &self.message,
+            self.amf_signature,
+        )
+    }
+
+    /// Judges the reported frank.
+    pub fn judge(
+        &self,
+        judge_secret_key: AMFSecretKey,
+        recipient_public_key: AMFPublicKey,
+        judge_public_key: AMFPublicKey,
+    ) -> bool {
+        amf::judge_with_context(
+            judge_secret_key,
+            self.sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            &self.context,
+            &self.message,
+            self.amf_signature,
+        )
+    }
+}
+
+fn context(
+    ephemeral_public_key: &CompressedRistretto,
+    sealing_public_key: &SealingPublicKey,
+) -> [u8; CONTEXT_LENGTH] {
+    let mut context = [0u8; CONTEXT_LENGTH];
+    context[..32].copy_from_slice(ephemeral_public_key.as_bytes());
+    context[32..].copy_from_slice(sealing_public_key.0.compress().as_bytes());
+    context
+}
+
+fn cipher(shared_secret: &RistrettoPoint, context: &[u8; CONTEXT_LENGTH]) -> ChaCha20Poly1305 {
+    let mut hasher = Sha512::new();
+    absorb(&mut hasher, SEALED_SENDER_LABEL);
+    absorb(&mut hasher, shared_secret.compress().as_bytes());
+    absorb(&mut hasher, context);
+    ChaCha20Poly1305::new(Key::from_slice(&hasher.finalize()[..32]))
+}
+
+/// Franks `message` for the recipient and judge, bound to a fresh envelope, and seals it to the
+/// recipient's sealing key.
+pub fn seal(
+    sender_secret_key: AMFSecretKey,
+    sender_public_key: AMFPublicKey,
+    recipient_public_key: AMFPublicKey,
+    judge_public_key: AMFPublicKey,
+    sealing_public_key: SealingPublicKey,
+    message: &[u8],
+) -> Envelope {
+    let ephemeral_secret_key = Scalar::random(&mut rand::thread_rng());
+    let ephemeral_public_key = RistrettoPoint::mul_base(&ephemeral_secret_key).compress();
+    let context = context(&ephemeral_public_key, &sealing_public_key);
+
+    let amf_signature = amf::frank_with_context(
+        sender_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+        &context,
+        message,
+    );
+    let plaintext = [
+        sender_public_key.to_bytes(),
+        amf_signature.to_bytes(),
+        message.to_vec(),
+    ]
+    .concat();
+
+    // every key encrypts one envelope, so a fixed nonce is fine
+    let ciphertext = cipher(&(ephemeral_secret_key * sealing_public_key.0), &context)
+        .encrypt(
+            &Nonce::default(),
+            Payload {
+                msg: &plaintext,
+                aad: &context,
+            },
+        )
+        .expect("ChaCha20-Poly1305 encrypts messages of any practical length");
+    Envelope {
+        ephemeral_public_key,
+        ciphertext,
+    }
+}
+
+/// Unseals an envelope with the recipient's sealing key pair; verify the `Unsealed` frank next.
+pub fn unseal(
+    sealing_secret_key: SealingSecretKey,
+    sealing_public_key: SealingPublicKey,
+    envelope: &Envelope,
+) -> Result<Unsealed, SealedSenderError> {
+    let ephemeral_public_key = envelope
+        .ephemeral_public_key
+        .decompress()
+        .ok_or(SealedSenderError::InvalidPoint)?;
+    let context = context(&envelope.ephemeral_public_key, &sealing_public_key);
+    let plaintext = cipher(&(sealing_secret_key.0 * ephemeral_public_key), &context)
+        .decrypt(
+            &Nonce::default(),
+            Payload {
+                msg: &envelope.ciphertext,
+                aad: &context,
+            },
+        )
+        .map_err(|_| SealedSenderError::DecryptionFailed)?;
+
+    let signature_start = AMFPublicKey::WIRE_LENGTH;
+    let message_start = signature_start + AMFSignature::WIRE_LENGTH;
+    if plaintext.len() < message_start {
+        return Err(SealedSenderError::InvalidLength);
+    }
+    Ok(Unsealed {
+        sender_public_key: AMFPublicKey::from_bytes(&plaintext[..signature_start])
+            .map_err(SealedSenderError::InvalidContent)?,
+        amf_signature: AMFSignature::from_bytes(&plaintext[signature_start..message_start])
+            .map_err(SealedSenderError::InvalidContent)?,
+        message: plaintext[message_start..].to_vec(),
+        context,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amf::{keygen, AMFRole};
+
+    #[test]
+    fn test_sealed_sender() {
+        // 0. Initialize a Sender, Recipient (with a sealing key pair), and Judge
+        let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+        let (recipient_public_key, recipient_secret_key) = keygen(AMFRole::Recipient);
+        let (judge_public_key, judge_secret_key) = keygen(AMFRole::Judge);
+        let (sealing_public_key, sealing_secret_key) = sealing_keygen();
+
+        // 1. Seal a franked message, and send the envelope
+        let envelope = seal(
+            sender_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            sealing_public_key,
+            b"hello world!",
+        );
+        let envelope = Envelope::from_bytes(&envelope.to_bytes()).unwrap();
+
+        // 2. The recipient unseals and verifies it, and the judge judges the report
+        let unsealed = unseal(sealing_secret_key, sealing_public_key, &envelope).unwrap();
+        assert_eq!(unsealed.sender_public_key, sender_public_key);
+        assert_eq!(unsealed.message, b"hello world!");
+        assert!(unsealed.verify(
+            recipient_secret_key.clone(),
+            recipient_public_key,
+            judge_public_key
+        ));
+        assert!(unsealed.judge(
+            judge_secret_key.clone(),
+            recipient_public_key,
+            judge_public_key
+        ));
+
+        // 3. The frank does not verify in another envelope's context
+        let mut rewrapped = unsealed.clone();
+        rewrapped.context[..32].copy_from_slice(
+            RistrettoPoint::mul_base(&Scalar::from(7u8))
+                .compress()
+                .as_bytes(),
+        );
+        assert!(!rewrapped.verify(recipient_secret_key, recipient_public_key, judge_public_key));
+        assert!(!rewrapped.judge(judge_secret_key, recipient_public_key, judge_public_key));
+
+        // 4. Modified envelopes, and envelopes for other keys, do not unseal
+        let mut modified = envelope.clone();
+        modified.ciphertext[0] ^= 1;
+        assert_eq!(
+            unseal(sealing_secret_key, sealing_public_key, &modified),
+            Err(SealedSenderError::DecryptionFailed)
+        );
+        let (other_public_key, other_secret_key) = sealing_keygen();
+        assert_eq!(
+            unseal(other_secret_key, other_public_key, &envelope),
+            Err(SealedSenderError::DecryptionFailed)
+        );
+    }
+}
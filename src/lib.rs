@@ -1,4 +1,49 @@
-#![doc = include_str!("../README.md")]
+// the example of the README franks with thread_rng
+#![cfg_attr(feature = "rand", doc = include_str!("../README.md"))]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+// for the generated UniFFI scaffolding, which cannot carry its own lint attributes
+#![cfg_attr(feature = "uniffi", allow(clippy::empty_line_after_doc_comments))]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
 pub mod amf;
-pub(crate) mod pok;
+#[cfg(feature = "alloc")]
+pub mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(any(feature = "matrix", feature = "mime", feature = "sealed-sender"))]
+pub mod integrations;
+#[cfg(feature = "alloc")]
+pub mod pok;
+#[cfg(feature = "alloc")]
+pub mod prelude;
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(any(feature = "rest-server", feature = "rest-client"))]
+pub mod rest;
+#[cfg(all(test, feature = "alloc"))]
+mod test_allocator;
+#[cfg(feature = "uniffi")]
+pub mod uniffi_api;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[cfg(feature = "alloc")]
+pub use error::Error;
+
+// UniFFI's scaffolding must live at the crate root, with the exported items in scope.
+#[cfg(feature = "uniffi")]
+use uniffi_api::*;
+#[cfg(feature = "uniffi")]
+uniffi::include_scaffolding!("amaze");
+
+/// Dependencies used by macro expansions.
+#[doc(hidden)]
+pub mod __private {
+    pub use curve25519_dalek;
+    pub use rand_core;
+    pub use subtle;
+    pub use zeroize;
+}
@@ -4,9 +4,18 @@
 //!
 //! [BS0.5]: https://crypto.stanford.edu/~dabo/cryptobook/BonehShoup_0_5.pdf
 
+use alloc::vec::Vec;
+
 use curve25519_dalek::scalar::Scalar;
+use rand_core::CryptoRngCore;
+use subtle::Choice;
 
-use crate::pok::linear_sigma::{SigmaProver, SigmaVerifier};
+use crate::pok::{
+    batch::BatchVerifier,
+    domain,
+    error::PokError,
+    linear_sigma::{ChallengeLength, SigmaProtocol},
+};
 
 /// the secret witness, denoted by (y_0,y_1) in Section 19.7.1 in [BS0.5]
 pub type AndWitness<S0Witness, S1Witness> = (S0Witness, S1Witness);
@@ -26,217 +35,158 @@ pub type AndVerifierChallenge = Scalar;
 pub type AndProverResponse<S0ProverResponse, S1ProverResponse> =
     (S0ProverResponse, S1ProverResponse);
 
-pub struct AndProver<
-    S0Witness,
-    S0WitnessStatement,
-    S0ProverCommitment,
-    S0ProverResponse,
-    S1Witness,
-    S1WitnessStatement,
-    S1ProverCommitment,
-    S1ProverResponse,
-> {
-    pub s0_prover: Box<
-        dyn SigmaProver<
-            S0Witness,
-            S0WitnessStatement,
-            S0ProverCommitment,
-            AndVerifierChallenge,
-            S0ProverResponse,
-        >,
-    >,
-    pub s1_prover: Box<
-        dyn SigmaProver<
-            S1Witness,
-            S1WitnessStatement,
-            S1ProverCommitment,
-            AndVerifierChallenge,
-            S1ProverResponse,
-        >,
-    >,
+pub struct AndProof<S0, S1> {
+    pub s0: S0,
+    pub s1: S1,
 }
 
-impl<
-        S0Witness,
-        S0WitnessStatement,
-        S0ProverCommitment,
-        S0ProverResponse,
-        S1Witness,
-        S1WitnessStatement,
-        S1ProverCommitment,
-        S1ProverResponse,
-    >
-    SigmaProver<
-        AndWitness<S0Witness, S1Witness>,
-        AndWitnessStatement<S0WitnessStatement, S1WitnessStatement>,
-        AndProverCommitment<S0ProverCommitment, S1ProverCommitment>,
-        AndVerifierChallenge,
-        AndProverResponse<S0ProverResponse, S1ProverResponse>,
-    >
-    for AndProver<
-        S0Witness,
-        S0WitnessStatement,
-        S0ProverCommitment,
-        S0ProverResponse,
-        S1Witness,
-        S1WitnessStatement,
-        S1ProverCommitment,
-        S1ProverResponse,
-    >
-{
+impl<S0: SigmaProtocol, S1: SigmaProtocol> SigmaProtocol for AndProof<S0, S1> {
+    type Witness = AndWitness<S0::Witness, S1::Witness>;
+    type Statement = AndWitnessStatement<S0::Statement, S1::Statement>;
+    type Commitment = AndProverCommitment<S0::Commitment, S1::Commitment>;
+    type Response = AndProverResponse<S0::Response, S1::Response>;
+    type ProverState = (S0::ProverState, S1::ProverState);
+
     fn generate_commitment(
-        &mut self,
-        witness: AndWitness<S0Witness, S1Witness>,
-    ) -> AndProverCommitment<S0ProverCommitment, S1ProverCommitment> {
-        let s0_commitment = self.s0_prover.as_mut().generate_commitment(witness.0);
-        let s1_commitment = self.s1_prover.as_mut().generate_commitment(witness.1);
-        (s0_commitment, s1_commitment)
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        witness: Self::Witness,
+    ) -> Result<(Self::Commitment, Self::ProverState), PokError> {
+        let (s0_commitment, s0_state) = self.s0.generate_commitment(rng, witness.0)?;
+        let (s1_commitment, s1_state) = self.s1.generate_commitment(rng, witness.1)?;
+        Ok(((s0_commitment, s1_commitment), (s0_state, s1_state)))
     }
 
-    fn serialize_commitment(
-        &self,
-        commitment: &AndProverCommitment<S0ProverCommitment, S1ProverCommitment>,
-    ) -> Vec<u8> {
+    fn serialize_commitment(&self, commitment: &Self::Commitment, buf: &mut Vec<u8>) {
+        self.s0.serialize_commitment(&commitment.0, buf);
+        self.s1.serialize_commitment(&commitment.1, buf);
+    }
+
+    fn protocol_label(&self) -> Vec<u8> {
+        let mut label = Vec::new();
+        self.write_protocol_label(&mut label);
+        label
+    }
+
+    fn write_protocol_label(&self, buf: &mut Vec<u8>) {
+        domain::write_composite_label(buf, domain::AND_LABEL, 2, |i, buf| match i {
+            0 => self.s0.write_protocol_label(buf),
+            _ => self.s1.write_protocol_label(buf),
+        });
+    }
+
+    fn serialize_witness_statement(&self) -> Vec<u8> {
         let mut buf = Vec::new();
-        buf.extend(self.s0_prover.as_ref().serialize_commitment(&commitment.0));
-        buf.extend(self.s1_prover.as_ref().serialize_commitment(&commitment.1));
+        self.write_witness_statement(&mut buf);
         buf
     }
 
+    fn write_witness_statement(&self, buf: &mut Vec<u8>) {
+        self.s0.write_witness_statement(buf);
+        self.s1.write_witness_statement(buf);
+    }
+
     fn generate_response_to_challenge(
-        &mut self,
+        &self,
+        prover_state: Self::ProverState,
         random_challenge: AndVerifierChallenge,
-    ) -> AndProverResponse<S0ProverResponse, S1ProverResponse> {
+    ) -> Self::Response {
+        let (s0_state, s1_state) = prover_state;
         let s0_response = self
-            .s0_prover
-            .as_mut()
-            .generate_response_to_challenge(random_challenge);
+            .s0
+            .generate_response_to_challenge(s0_state, random_challenge);
         let s1_response = self
-            .s1_prover
-            .as_mut()
-            .generate_response_to_challenge(random_challenge);
+            .s1
+            .generate_response_to_challenge(s1_state, random_challenge);
         (s0_response, s1_response)
     }
-}
-
-pub struct AndVerifier<
-    S0Witness,
-    S0WitnessStatement,
-    S0ProverCommitment,
-    S0ProverResponse,
-    S1Witness,
-    S1WitnessStatement,
-    S1ProverCommitment,
-    S1ProverResponse,
-> {
-    pub s0_verifier: Box<
-        dyn SigmaVerifier<
-            S0Witness,
-            S0WitnessStatement,
-            S0ProverCommitment,
-            AndVerifierChallenge,
-            S0ProverResponse,
-        >,
-    >,
-    pub s1_verifier: Box<
-        dyn SigmaVerifier<
-            S1Witness,
-            S1WitnessStatement,
-            S1ProverCommitment,
-            AndVerifierChallenge,
-            S1ProverResponse,
-        >,
-    >,
-}
-
-impl<
-        S0Witness,
-        S0WitnessStatement,
-        S0ProverCommitment,
-        S0ProverResponse,
-        S1Witness,
-        S1WitnessStatement,
-        S1ProverCommitment,
-        S1ProverResponse,
-    >
-    SigmaVerifier<
-        AndWitness<S0Witness, S1Witness>,
-        AndWitnessStatement<S0WitnessStatement, S1WitnessStatement>,
-        AndProverCommitment<S0ProverCommitment, S1ProverCommitment>,
-        AndVerifierChallenge,
-        AndProverResponse<S0ProverResponse, S1ProverResponse>,
-    >
-    for AndVerifier<
-        S0Witness,
-        S0WitnessStatement,
-        S0ProverCommitment,
-        S0ProverResponse,
-        S1Witness,
-        S1WitnessStatement,
-        S1ProverCommitment,
-        S1ProverResponse,
-    >
-{
-    fn generate_random_challenge(&mut self) -> AndVerifierChallenge {
-        let mut rng = rand::thread_rng();
-        Scalar::random(&mut rng)
-    }
 
     fn verify_response_to_challenge(
         &self,
-        prover_commitment: AndProverCommitment<S0ProverCommitment, S1ProverCommitment>,
+        prover_commitment: Self::Commitment,
         random_challenge: AndVerifierChallenge,
-        prover_response_to_challenge: AndProverResponse<S0ProverResponse, S1ProverResponse>,
+        prover_response_to_challenge: Self::Response,
     ) -> bool {
-        let s0_verification_result = self.s0_verifier.as_ref().verify_response_to_challenge(
+        let s0_verification_result = self.s0.verify_response_to_challenge(
             prover_commitment.0,
             random_challenge,
             prover_response_to_challenge.0,
         );
-        let s1_verification_result = self.s1_verifier.as_ref().verify_response_to_challenge(
+        let s1_verification_result = self.s1.verify_response_to_challenge(
             prover_commitment.1,
             random_challenge,
             prover_response_to_challenge.1,
         );
-        s0_verification_result && s1_verification_result
+        (Choice::from(u8::from(s0_verification_result))
+            & Choice::from(u8::from(s1_verification_result)))
+        .into()
     }
 
     fn simulate_prover_responses(
         &self,
+        rng: &mut dyn CryptoRngCore,
         random_challenge: AndVerifierChallenge,
-    ) -> (
-        AndProverCommitment<S0ProverCommitment, S1ProverCommitment>,
-        AndProverResponse<S0ProverResponse, S1ProverResponse>,
-    ) {
-        let (s0_commitment, s0_response) = self
-            .s0_verifier
-            .as_ref()
-            .simulate_prover_responses(random_challenge);
-        let (s1_commitment, s1_response) = self
-            .s1_verifier
-            .as_ref()
-            .simulate_prover_responses(random_challenge);
+    ) -> (Self::Commitment, Self::Response) {
+        let (s0_commitment, s0_response) = self.s0.simulate_prover_responses(rng, random_challenge);
+        let (s1_commitment, s1_response) = self.s1.simulate_prover_responses(rng, random_challenge);
         ((s0_commitment, s1_commitment), (s0_response, s1_response))
     }
+
+    fn batch_verify_response_to_challenge(
+        &self,
+        batch: &mut BatchVerifier,
+        prover_commitment: Self::Commitment,
+        random_challenge: AndVerifierChallenge,
+        prover_response_to_challenge: Self::Response,
+    ) -> bool {
+        let s0_verification_result = self.s0.batch_verify_response_to_challenge(
+            batch,
+            prover_commitment.0,
+            random_challenge,
+            prover_response_to_challenge.0,
+        );
+        let s1_verification_result = self.s1.batch_verify_response_to_challenge(
+            batch,
+            prover_commitment.1,
+            random_challenge,
+            prover_response_to_challenge.1,
+        );
+        (Choice::from(u8::from(s0_verification_result))
+            & Choice::from(u8::from(s1_verification_result)))
+        .into()
+    }
+
+    fn recompute_commitment(
+        &self,
+        random_challenge: AndVerifierChallenge,
+        prover_response_to_challenge: Self::Response,
+    ) -> Option<Self::Commitment> {
+        Some((
+            self.s0
+                .recompute_commitment(random_challenge, prover_response_to_challenge.0)?,
+            self.s1
+                .recompute_commitment(random_challenge, prover_response_to_challenge.1)?,
+        ))
+    }
+
+    fn set_challenge_length(&mut self, challenge_length: ChallengeLength) {
+        self.s0.set_challenge_length(challenge_length);
+        self.s1.set_challenge_length(challenge_length);
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use curve25519_dalek::{
-        constants::RISTRETTO_BASEPOINT_TABLE, ristretto::RistrettoBasepointTable,
-    };
+    use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
 
-    use crate::pok::{
-        schnorr::{SchnorrProver, SchnorrVerifier},
-        test_macros::test_sigma_protocol,
-    };
+    use crate::pok::{schnorr::SchnorrProof, test_macros::test_sigma_protocol};
 
     use super::*;
 
     #[test]
     fn test_schnorr_and_schnorr() {
         let mut rng = rand::thread_rng();
-        let g = RistrettoBasepointTable::basepoint(&RISTRETTO_BASEPOINT_TABLE);
+        let g = RISTRETTO_BASEPOINT_POINT;
 
         // 0. Fix two witnesses, and define the statements to prove
         let witness0 = Scalar::random(&mut rng);
@@ -244,25 +194,14 @@ mod tests {
         let witness1 = Scalar::random(&mut rng);
         let witness1_statement = witness1 * g;
 
-        // 1. Initialize Schnorr provers and verifiers with the respective statements to prove
-        let s0_prover = SchnorrProver::new(witness0_statement);
-        let s1_prover = SchnorrProver::new(witness1_statement);
-        let s0_verifier = SchnorrVerifier::new(witness0_statement);
-        let s1_verifier = SchnorrVerifier::new(witness1_statement);
-
-        // 2. Create an AND prover using the two schnorr provers
-        let mut and_prover = AndProver {
-            s0_prover: Box::new(s0_prover),
-            s1_prover: Box::new(s1_prover),
-        };
+        // 1. Initialize Schnorr's protocol with the respective statements to prove
+        let s0 = SchnorrProof::new(witness0_statement);
+        let s1 = SchnorrProof::new(witness1_statement);
 
-        // 3. Create an AND verifier using the two schnorr verifiers
-        let mut and_verifier = AndVerifier {
-            s0_verifier: Box::new(s0_verifier),
-            s1_verifier: Box::new(s1_verifier),
-        };
+        // 2. Create an AND proof from the two Schnorr proofs
+        let and_proof = s0.and(s1);
 
-        // 4. Run tests with the verifier and prover
-        test_sigma_protocol!((witness0, witness1), and_verifier, and_prover);
+        // 3. Run tests with the protocol
+        test_sigma_protocol!((witness0, witness1), and_proof);
     }
 }
@@ -0,0 +1,451 @@
+//! Batch Verification of Linear Equations
+//!
+//! Verifying a Schnorr or Chaum-Pedersen proof means checking equations of the form z * g == t +
+//! c * y, each costing a multiscalar multiplication. `BatchVerifier` collects many such
+//! equations, written as sum_i a_i * P_i == identity, and checks them all at once: it draws a
+//! random 128-bit weight w_j per equation, and checks that sum_j w_j * (sum_i a_ij * P_ij) is the
+//! identity, in a single multiscalar multiplication. If any equation fails, so does the sum,
+//! except with probability about 2^-128 over the weights. The terms with the Ristretto basepoint
+//! are merged into one, since every Schnorr and Chaum-Pedersen proof in this crate has one.
+//!
+//! A failed batch only says that some equation fails, not which one; callers that need to know
+//! verify the proofs one by one afterwards. Sigma protocols add their equations with
+//! `SigmaProtocol::batch_verify_response_to_challenge`, and Fiat-Shamir signatures with
+//! `FiatShamir::batch_verify`.
+//!
+//! Points that recur in every batch, like a judge's public key, can be prepared once as
+//! `PreparedPoints`: `verify_prepared` then multiplies their terms with a precomputed table
+//! instead of from scratch.
+//!
+//! Points that recur within one batch, like the keys of a sender and a judge shared by many
+//! reports, can be declared with `with_shared_points`: their terms are merged as they are added,
+//! so that each costs one term of the multiscalar multiplication per batch instead of one per
+//! equation.
+
+use alloc::{vec, vec::Vec};
+use core::iter;
+
+use core::fmt;
+
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT,
+    ristretto::{RistrettoPoint, VartimeRistrettoPrecomputation},
+    scalar::Scalar,
+    traits::{IsIdentity, VartimeMultiscalarMul, VartimePrecomputedMultiscalarMul},
+};
+use rand_core::CryptoRngCore;
+
+use crate::pok::{
+    linear_sigma::{ChallengeLength, SigmaChallenge},
+    profiling,
+};
+
+/// An accumulator of equations sum_i a_i * P_i == identity, checked together by `verify`.
+#[derive(Debug, Clone, Default)]
+pub struct BatchVerifier {
+    /// the coefficient of the basepoint in each equation
+    basepoint_scalars: Vec<Scalar>,
+    /// the other coefficients, a_i, of all equations one after the other
+    scalars: Vec<Scalar>,
+    /// the other points, P_i, of all equations one after the other
+    points: Vec<RistrettoPoint>,
+    /// where the terms of each equation end in `scalars` and `points`
+    ends: Vec<usize>,
+    /// the points whose terms are merged, cf. `with_shared_points`
+    shared_points: Vec<RistrettoPoint>,
+    /// the coefficients of `shared_points` in each equation, one equation after the other
+    shared_scalars: Vec<Scalar>,
+}
+
+impl BatchVerifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A batch that merges the terms of every equation with any of `points`, for points that occur
+    /// in many equations. Finding them costs a point comparison per term and shared point, which
+    /// is cheap next to a term of the multiscalar multiplication, but share only a few points.
+    pub fn with_shared_points(points: impl IntoIterator<Item = RistrettoPoint>) -> Self {
+        BatchVerifier {
+            shared_points: points.into_iter().collect(),
+            ..Self::default()
+        }
+    }
+
+    /// the number of equations added so far
+    pub fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ends.is_empty()
+    }
+
+    /// Adds the equation `basepoint_scalar * g + sum_i a_i * P_i == identity`, where g is the
+    /// Ristretto basepoint and `terms` are the pairs (a_i, P_i).
+    pub fn add_equation(
+        &mut self,
+        basepoint_scalar: Scalar,
+        terms: impl IntoIterator<Item = (Scalar, RistrettoPoint)>,
+    ) {
+        let shared_start = self.shared_scalars.len();
+        self.shared_scalars
+            .resize(shared_start + self.shared_points.len(), Scalar::ZERO);
+        for (scalar, point) in terms {
+            match self
+                .shared_points
+                .iter()
+                .position(|shared| *shared == point)
+            {
+                Some(i) => self.shared_scalars[shared_start + i] += scalar,
+                None => {
+                    self.scalars.push(scalar);
+                    self.points.push(point);
+                }
+            }
+        }
+        self.basepoint_scalars.push(basepoint_scalar);
+        self.ends.push(self.scalars.len());
+    }
+
+    /// Adds the verification equation of Schnorr's protocol for the statement `y = x * g`, z * g
+    /// == t + c * y, for the commitment `t`, the challenge `c`, and the response `z`.
+    pub fn add_schnorr(
+        &mut self,
+        g: &RistrettoPoint,
+        y: &RistrettoPoint,
+        t: &RistrettoPoint,
+        c: &SigmaChallenge,
+        z: &Scalar,
+    ) {
+        let terms = [(-c, *y), (-Scalar::ONE, *t)];
+        if *g == RISTRETTO_BASEPOINT_POINT {
+            self.add_equation(*z, terms);
+        } else {
+            self.add_equation(Scalar::ZERO, iter::once((*z, *g)).chain(terms));
+        }
+    }
+
+    /// Checks all the equations added so far; `rng` draws the weights, and must not be known to
+    /// whoever crafted the proofs.
+    pub fn verify(self, rng: &mut dyn CryptoRngCore) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+
+        let (basepoint_scalar, scalars, points) = self.weighted(rng);
+        profiling::record_multiscalar_mul(1 + points.len());
+        RistrettoPoint::vartime_multiscalar_mul(
+            iter::once(basepoint_scalar).chain(scalars),
+            iter::once(RISTRETTO_BASEPOINT_POINT).chain(points),
+        )
+        .is_identity()
+    }
+
+    /// Like `verify`, but the terms with the basepoint or one of the points of `prepared` go
+    /// through its precomputation.
+    pub fn verify_prepared(self, rng: &mut dyn CryptoRngCore, prepared: &PreparedPoints) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+
+        let (basepoint_scalar, weighted_scalars, weighted_points) = self.weighted(rng);
+        let mut static_scalars = vec![Scalar::ZERO; prepared.points.len()];
+        static_scalars[0] = basepoint_scalar;
+        let mut scalars = Vec::with_capacity(weighted_scalars.len());
+        let mut points = Vec::with_capacity(weighted_points.len());
+        for (scalar, point) in weighted_scalars.into_iter().zip(weighted_points) {
+            match prepared
+                .points
+                .iter()
+                .position(|prepared| *prepared == point)
+            {
+                Some(i) => static_scalars[i] += scalar,
+                None => {
+                    scalars.push(scalar);
+                    points.push(point);
+                }
+            }
+        }
+
+        profiling::record_multiscalar_mul(static_scalars.len() + points.len());
+        prepared
+            .precomputation
+            .vartime_mixed_multiscalar_mul(static_scalars, scalars, points)
+            .is_identity()
+    }
+
+    /// the merged basepoint coefficient, and the other terms, of the weighted sum of the equations;
+    /// the terms of the shared points come last
+    fn weighted(self, rng: &mut dyn CryptoRngCore) -> (Scalar, Vec<Scalar>, Vec<RistrettoPoint>) {
+        let mut basepoint_scalar = Scalar::ZERO;
+        let mut scalars = Vec::with_capacity(self.scalars.len() + self.shared_points.len());
+        let mut shared_scalars = vec![Scalar::ZERO; self.shared_points.len()];
+        let mut start = 0;
+        let equations = self.ends.iter().zip(&self.basepoint_scalars);
+        for (i, (end, equation_basepoint_scalar)) in equations.enumerate() {
+            let weight = ChallengeLength::Bits128.random(rng);
+            basepoint_scalar += weight * equation_basepoint_scalar;
+            scalars.extend(
+                self.scalars[start..*end]
+                    .iter()
+                    .map(|scalar| weight * scalar),
+            );
+            let shared = self.shared_points.len();
+            for (shared_scalar, scalar) in shared_scalars
+                .iter_mut()
+                .zip(&self.shared_scalars[i * shared..(i + 1) * shared])
+            {
+                *shared_scalar += weight * scalar;
+            }
+            start = *end;
+        }
+        let mut points = self.points;
+        scalars.extend(shared_scalars);
+        points.extend(self.shared_points);
+        (basepoint_scalar, scalars, points)
+    }
+}
+
+/// Points with a precomputation for `BatchVerifier::verify_prepared`, always including the
+/// Ristretto basepoint; building one costs about as much as a few hundred scalar
+/// multiplications, so prepare only points that recur across many batches.
+pub struct PreparedPoints {
+    /// the basepoint, then the prepared points
+    points: Vec<RistrettoPoint>,
+    precomputation: VartimeRistrettoPrecomputation,
+}
+
+impl PreparedPoints {
+    pub fn new(points: impl IntoIterator<Item = RistrettoPoint>) -> Self {
+        let points: Vec<_> = iter::once(RISTRETTO_BASEPOINT_POINT)
+            .chain(points)
+            .collect();
+        PreparedPoints {
+            precomputation: VartimeRistrettoPrecomputation::new(&points),
+            points,
+        }
+    }
+
+    /// the prepared points, without the basepoint
+    pub fn points(&self) -> &[RistrettoPoint] {
+        &self.points[1..]
+    }
+}
+
+impl fmt::Debug for PreparedPoints {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PreparedPoints")
+            .field("points", &self.points())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::RngCore;
+
+    use crate::pok::{
+        chaum_pedersen::{ChaumPedersenProof, ChaumPedersenWitnessStatement},
+        dleq::{DleqProof, DleqWitnessStatement},
+        fiat_shamir::{FiatShamirSecretKey, SignatureScheme},
+        linear_sigma::{Generator, SigmaProtocol},
+        or_proof::OrWitness,
+        schnorr::SchnorrProof,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_batch_verifier() {
+        let mut rng = rand::thread_rng();
+
+        // 0. An empty batch verifies
+        assert!(BatchVerifier::new().verify(&mut rng));
+
+        // 1. Valid Schnorr equations verify together, with the basepoint and another generator
+        let h = RistrettoPoint::random(&mut rng);
+        let mut batch = BatchVerifier::new();
+        let mut equations = Vec::new();
+        for g in [RISTRETTO_BASEPOINT_POINT, h, RISTRETTO_BASEPOINT_POINT] {
+            let (x, r, c) = (
+                Scalar::random(&mut rng),
+                Scalar::random(&mut rng),
+                Scalar::random(&mut rng),
+            );
+            let equation = (g, x * g, r * g, c, r + c * x);
+            batch.add_schnorr(
+                &equation.0,
+                &equation.1,
+                &equation.2,
+                &equation.3,
+                &equation.4,
+            );
+            equations.push(equation);
+        }
+        assert_eq!(batch.len(), 3);
+        assert!(batch.clone().verify(&mut rng));
+
+        // 2. A single wrong response fails the whole batch, wherever it is
+        for wrong in 0..equations.len() {
+            let mut batch = BatchVerifier::new();
+            for (i, (g, y, t, c, z)) in equations.iter().enumerate() {
+                let z = if i == wrong { z + Scalar::ONE } else { *z };
+                batch.add_schnorr(g, y, t, c, &z);
+            }
+            assert!(!batch.verify(&mut rng));
+        }
+
+        // 3. Generic equations: x * g - y == identity
+        let x = Scalar::random(&mut rng);
+        let mut batch = BatchVerifier::new();
+        batch.add_equation(x, [(-Scalar::ONE, RistrettoPoint::mul_base(&x))]);
+        batch.add_equation(Scalar::ZERO, [(x, h), (-x, h)]);
+        assert!(batch.verify(&mut rng));
+        let mut batch = BatchVerifier::new();
+        batch.add_equation(x, [(-Scalar::ONE, h)]);
+        assert!(!batch.verify(&mut rng));
+    }
+
+    #[test]
+    fn test_batch_verifier_prepared() {
+        let mut rng = rand::thread_rng();
+
+        // 0. Schnorr equations over a prepared generator, a prepared statement, and neither
+        let (h, y) = (
+            RistrettoPoint::random(&mut rng),
+            RistrettoPoint::random(&mut rng),
+        );
+        let prepared = PreparedPoints::new([h, y]);
+        assert_eq!(prepared.points(), [h, y]);
+        let x = Scalar::random(&mut rng);
+        let mut equations = Vec::new();
+        for g in [h, RistrettoPoint::random(&mut rng)] {
+            let (r, c) = (Scalar::random(&mut rng), Scalar::random(&mut rng));
+            equations.push((g, x * g, r * g, c, r + c * x));
+        }
+        // y = x' * g for an unknown x'; the commitment is simulated from the response
+        let (z, c) = (Scalar::random(&mut rng), Scalar::random(&mut rng));
+        let g = RISTRETTO_BASEPOINT_POINT;
+        equations.push((g, y, z * g - c * y, c, z));
+
+        // 1. They verify with and without the preparation
+        let mut batch = BatchVerifier::new();
+        for (g, y, t, c, z) in &equations {
+            batch.add_schnorr(g, y, t, c, z);
+        }
+        assert!(batch.clone().verify(&mut rng));
+        assert!(batch.verify_prepared(&mut rng, &prepared));
+        assert!(BatchVerifier::new().verify_prepared(&mut rng, &prepared));
+
+        // 2. A single wrong response fails the prepared batch, wherever it is
+        for wrong in 0..equations.len() {
+            let mut batch = BatchVerifier::new();
+            for (i, (g, y, t, c, z)) in equations.iter().enumerate() {
+                let z = if i == wrong { z + Scalar::ONE } else { *z };
+                batch.add_schnorr(g, y, t, c, &z);
+            }
+            assert!(!batch.verify_prepared(&mut rng, &prepared));
+        }
+    }
+
+    #[test]
+    fn test_batch_verifier_shared_points() {
+        let mut rng = rand::thread_rng();
+
+        // 0. Schnorr equations for one statement y = x * g, and one for another statement
+        let x = Scalar::random(&mut rng);
+        let (g, y) = (RISTRETTO_BASEPOINT_POINT, RistrettoPoint::mul_base(&x));
+        let mut equations = Vec::new();
+        for _ in 0..4 {
+            let (r, c) = (Scalar::random(&mut rng), Scalar::random(&mut rng));
+            equations.push((y, r * g, c, r + c * x));
+        }
+        let other_x = Scalar::random(&mut rng);
+        let (r, c) = (Scalar::random(&mut rng), Scalar::random(&mut rng));
+        equations.push((other_x * g, r * g, c, r + c * other_x));
+
+        // 1. With y shared, its terms merge into one, and the batch still verifies
+        let mut batch = BatchVerifier::with_shared_points([y]);
+        for (y, t, c, z) in &equations {
+            batch.add_schnorr(&g, y, t, c, z);
+        }
+        assert_eq!(batch.len(), 5);
+        assert_eq!(batch.points.len(), 6);
+        assert!(batch.verify(&mut rng));
+
+        // 2. A single wrong response fails it, wherever it is
+        for wrong in 0..equations.len() {
+            let mut batch = BatchVerifier::with_shared_points([y]);
+            for (i, (y, t, c, z)) in equations.iter().enumerate() {
+                let z = if i == wrong { z + Scalar::ONE } else { *z };
+                batch.add_schnorr(&g, y, t, c, &z);
+            }
+            assert!(!batch.verify(&mut rng));
+        }
+    }
+
+    #[test]
+    fn test_batch_verify_fiat_shamir() {
+        let mut rng = rand::thread_rng();
+        let mut message = [0u8; 32];
+        rng.fill_bytes(&mut message);
+
+        // 0. Schnorr, Chaum-Pedersen, DLEQ and OR signatures, under the same batch
+        let x = Scalar::random(&mut rng);
+        let h = RistrettoPoint::random(&mut rng);
+        let mut schnorr = SchnorrProof::new(RistrettoPoint::mul_base(&x)).fiat_shamir();
+        let schnorr_signature = schnorr
+            .sign(&mut rng, FiatShamirSecretKey { witness: x }, &message)
+            .unwrap();
+        let mut chaum_pedersen = ChaumPedersenProof::with_generator(
+            Generator::new(&h),
+            ChaumPedersenWitnessStatement {
+                u: h + h,
+                v: x * h,
+                w: x * (h + h),
+            },
+        )
+        .fiat_shamir();
+        let chaum_pedersen_signature = chaum_pedersen
+            .sign(&mut rng, FiatShamirSecretKey { witness: x }, &message)
+            .unwrap();
+        let mut dleq = DleqProof::new(DleqWitnessStatement {
+            g1: RISTRETTO_BASEPOINT_POINT,
+            p1: RistrettoPoint::mul_base(&x),
+            g2: h,
+            p2: x * h,
+        })
+        .fiat_shamir();
+        let dleq_signature = dleq
+            .sign(&mut rng, FiatShamirSecretKey { witness: x }, &message)
+            .unwrap();
+        let mut or = SchnorrProof::new(h)
+            .or(SchnorrProof::new(RistrettoPoint::mul_base(&x)))
+            .fiat_shamir()
+            .with_challenge_length(ChallengeLength::Bits128);
+        let or_signature = or
+            .sign(
+                &mut rng,
+                FiatShamirSecretKey {
+                    witness: OrWitness::Right(x),
+                },
+                &message,
+            )
+            .unwrap();
+
+        let mut batch = BatchVerifier::new();
+        assert!(schnorr.batch_verify(&mut batch, &message, schnorr_signature));
+        assert!(chaum_pedersen.batch_verify(&mut batch, &message, chaum_pedersen_signature));
+        assert!(dleq.batch_verify(&mut batch, &message, dleq_signature));
+        assert!(or.batch_verify(&mut batch, &message, or_signature));
+        assert_eq!(batch.len(), 1 + 2 + 2 + 2);
+        assert!(batch.verify(&mut rng));
+
+        // 1. A signature on another message fails the batch
+        let mut batch = BatchVerifier::new();
+        assert!(schnorr.batch_verify(&mut batch, &message, schnorr_signature));
+        assert!(dleq.batch_verify(&mut batch, b"goodbye world!", dleq_signature));
+        assert!(!batch.verify(&mut rng));
+    }
+}
@@ -4,10 +4,22 @@
 //!
 //! [BS0.5]: https://crypto.stanford.edu/~dabo/cryptobook/BonehShoup_0_5.pdf
 
+use alloc::vec::Vec;
+
 use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use rand_core::CryptoRngCore;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
 
-use crate::pok::linear_sigma::{
-    GenericSigmaProver, GenericSigmaVerifier, SigmaProver, SigmaVerifier,
+use crate::pok::{
+    batch::BatchVerifier,
+    domain,
+    error::PokError,
+    linear_sigma::{
+        vartime_recompute_commitment, GenericSigmaProtocol, GenericSigmaProverState, SigmaProtocol,
+    },
+    profiling, scalar_blinding,
 };
 
 /// the secret witness, denoted by beta in Section 19.5.2 of [BS0.5]
@@ -27,89 +39,111 @@ pub type ChaumPedersenPerVerifierSecret = Scalar;
 /// cf.
 /// the prover's commitment, denoted by u_t in Section 19.5.2 of [BS0.5]
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ChaumPedersenProverCommitment {
     pub(crate) v_t: RistrettoPoint,
     pub(crate) w_t: RistrettoPoint,
 }
 
+impl ChaumPedersenProverCommitment {
+    /// Builds a commitment from its parts, e.g. to rebuild a decoded proof.
+    pub fn new(v_t: RistrettoPoint, w_t: RistrettoPoint) -> Self {
+        ChaumPedersenProverCommitment { v_t, w_t }
+    }
+
+    /// The commitment to the witness in the base g, denoted by v_t.
+    pub fn v_t(&self) -> RistrettoPoint {
+        self.v_t
+    }
+
+    /// The commitment to the witness in the base u, denoted by w_t.
+    pub fn w_t(&self) -> RistrettoPoint {
+        self.w_t
+    }
+}
+
 /// the verifier's challenge, denoted by c in Section 19.5.2 of [BS0.5]
 pub type ChaumPedersenVerifierChallenge = Scalar;
 
 /// the prover's response, denoted by beta_z in Section 19.5.2 of [BS0.5]
 pub type ChaumPedersenProverResponse = Scalar;
 
-pub type ChaumPedersenProver = GenericSigmaProver<
-    ChaumPedersenWitness,
-    ChaumPedersenWitnessStatement,
-    ChaumPedersenPerVerifierSecret,
->;
-
-impl
-    SigmaProver<
-        ChaumPedersenWitness,
-        ChaumPedersenWitnessStatement,
-        ChaumPedersenProverCommitment,
-        ChaumPedersenVerifierChallenge,
-        ChaumPedersenProverResponse,
-    > for ChaumPedersenProver
-{
-    fn generate_commitment(
-        &mut self,
-        witness: ChaumPedersenWitness,
-    ) -> ChaumPedersenProverCommitment {
-        let mut rng = rand::thread_rng();
+/// the prover's state between its commitment and its response
+pub type ChaumPedersenProverState =
+    GenericSigmaProverState<ChaumPedersenWitness, ChaumPedersenPerVerifierSecret>;
+
+pub type ChaumPedersenProof = GenericSigmaProtocol<ChaumPedersenWitnessStatement>;
 
-        // Store the witness for future use
-        self.witness = Some(witness);
+impl SigmaProtocol for ChaumPedersenProof {
+    type Witness = ChaumPedersenWitness;
+    type Statement = ChaumPedersenWitnessStatement;
+    type Commitment = ChaumPedersenProverCommitment;
+    type Response = ChaumPedersenProverResponse;
+    type ProverState = ChaumPedersenProverState;
 
+    fn generate_commitment(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        witness: ChaumPedersenWitness,
+    ) -> Result<(ChaumPedersenProverCommitment, ChaumPedersenProverState), PokError> {
         // Generate a new random commitment for this verifier
 
         // per verifier secret, denoted by beta_t in Section 19.5.2 of [BS0.5]
-        let per_verifier_secret = Scalar::random(&mut rng);
+        let per_verifier_secret = Scalar::random(rng);
         // v_t and w_t as defined in Section 19.5.2 of [BS0.5]
-        let per_verifier_v_t = per_verifier_secret * self.g;
-        let per_verifier_w_t = per_verifier_secret * self.witness_statement.u;
-
-        // Store the secret in a hashmap for future reference
-        self.per_verifier_secret = Some(per_verifier_secret);
+        let per_verifier_v_t = self.g.mul(&per_verifier_secret);
+        let per_verifier_w_t =
+            scalar_blinding::mul(&per_verifier_secret, &self.witness_statement.u);
 
-        // Return commitment
-        ChaumPedersenProverCommitment {
-            v_t: per_verifier_v_t,
-            w_t: per_verifier_w_t,
-        }
+        // Return the commitment, and keep the witness and secret for the response
+        Ok((
+            ChaumPedersenProverCommitment {
+                v_t: per_verifier_v_t,
+                w_t: per_verifier_w_t,
+            },
+            ChaumPedersenProverState {
+                witness,
+                per_verifier_secret,
+            },
+        ))
     }
 
-    fn serialize_commitment(&self, commitment: &ChaumPedersenProverCommitment) -> Vec<u8> {
-        let mut buf = Vec::new();
+    fn serialize_commitment(&self, commitment: &ChaumPedersenProverCommitment, buf: &mut Vec<u8>) {
+        profiling::record(|counts| counts.compressions += 2);
         buf.extend(commitment.v_t.compress().as_bytes());
         buf.extend(commitment.w_t.compress().as_bytes());
+    }
+
+    fn protocol_label(&self) -> Vec<u8> {
+        domain::CHAUM_PEDERSEN_LABEL.to_vec()
+    }
+
+    fn write_protocol_label(&self, buf: &mut Vec<u8>) {
+        buf.extend(domain::CHAUM_PEDERSEN_LABEL);
+    }
+
+    fn serialize_witness_statement(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.write_witness_statement(&mut buf);
         buf
     }
 
+    fn write_witness_statement(&self, buf: &mut Vec<u8>) {
+        profiling::record(|counts| counts.compressions += 3);
+        buf.extend(self.witness_statement.u.compress().as_bytes());
+        buf.extend(self.witness_statement.v.compress().as_bytes());
+        buf.extend(self.witness_statement.w.compress().as_bytes());
+    }
+
     fn generate_response_to_challenge(
-        &mut self,
+        &self,
+        prover_state: ChaumPedersenProverState,
         random_challenge: ChaumPedersenVerifierChallenge,
     ) -> ChaumPedersenProverResponse {
         // Construct response using the per_verifier_secret and random_challenge
-        self.per_verifier_secret.unwrap() + (self.witness.unwrap() * random_challenge)
-    }
-}
-
-pub type ChaumPedersenVerifier = GenericSigmaVerifier<ChaumPedersenWitnessStatement>;
-
-impl
-    SigmaVerifier<
-        ChaumPedersenWitness,
-        ChaumPedersenWitnessStatement,
-        ChaumPedersenProverCommitment,
-        ChaumPedersenVerifierChallenge,
-        ChaumPedersenProverResponse,
-    > for ChaumPedersenVerifier
-{
-    fn generate_random_challenge(&mut self) -> ChaumPedersenVerifierChallenge {
-        let mut rng = rand::thread_rng();
-        Scalar::random(&mut rng)
+        prover_state.respond(|witness, per_verifier_secret| {
+            scalar_blinding::response(per_verifier_secret, witness, &random_challenge)
+        })
     }
 
     fn verify_response_to_challenge(
@@ -118,37 +152,97 @@ impl
         random_challenge: ChaumPedersenVerifierChallenge,
         prover_response_to_challenge: ChaumPedersenProverResponse,
     ) -> bool {
-        // cf. Section 19.5.2 of [BS0.5]
-        let left1 = prover_response_to_challenge * self.g;
-        let right1 = prover_commitment.v_t + (random_challenge * self.witness_statement.v);
+        // cf. Section 19.5.2 of [BS0.5]; g^z == v_t * v^c and u^z == w_t * w^c, checked as
+        // g^z * v^-c == v_t and u^z * w^-c == w_t
+        let statement = &self.witness_statement;
+        let v_t = vartime_recompute_commitment(
+            &self.g.point(),
+            &statement.v,
+            &prover_response_to_challenge,
+            &random_challenge,
+        );
+        let w_t = vartime_recompute_commitment(
+            &statement.u,
+            &statement.w,
+            &prover_response_to_challenge,
+            &random_challenge,
+        );
 
-        let left2 = prover_response_to_challenge * self.witness_statement.u;
-        let right2 = prover_commitment.w_t + (random_challenge * self.witness_statement.w);
+        (v_t.ct_eq(&prover_commitment.v_t) & w_t.ct_eq(&prover_commitment.w_t)).into()
+    }
 
-        (left1 == right1) && (left2 == right2)
+    fn recompute_commitment(
+        &self,
+        random_challenge: ChaumPedersenVerifierChallenge,
+        prover_response_to_challenge: ChaumPedersenProverResponse,
+    ) -> Option<ChaumPedersenProverCommitment> {
+        let statement = &self.witness_statement;
+        Some(ChaumPedersenProverCommitment {
+            v_t: vartime_recompute_commitment(
+                &self.g.point(),
+                &statement.v,
+                &prover_response_to_challenge,
+                &random_challenge,
+            ),
+            w_t: vartime_recompute_commitment(
+                &statement.u,
+                &statement.w,
+                &prover_response_to_challenge,
+                &random_challenge,
+            ),
+        })
     }
 
     fn simulate_prover_responses(
         &self,
+        rng: &mut dyn CryptoRngCore,
         random_challenge: ChaumPedersenVerifierChallenge,
     ) -> (ChaumPedersenProverCommitment, ChaumPedersenProverResponse) {
-        let mut rng = rand::thread_rng();
-        let simulated_prover_response = Scalar::random(&mut rng);
+        let simulated_prover_response = Scalar::random(rng);
+        profiling::record(|counts| {
+            counts.scalar_muls += 3;
+            counts.point_additions += 2;
+        });
         let simulated_prover_commitment = ChaumPedersenProverCommitment {
-            v_t: (simulated_prover_response * self.g)
+            v_t: self.g.mul(&simulated_prover_response)
                 - (random_challenge * self.witness_statement.v),
             w_t: (simulated_prover_response * self.witness_statement.u)
                 - (random_challenge * self.witness_statement.w),
         };
         (simulated_prover_commitment, simulated_prover_response)
     }
+
+    fn batch_verify_response_to_challenge(
+        &self,
+        batch: &mut BatchVerifier,
+        prover_commitment: ChaumPedersenProverCommitment,
+        random_challenge: ChaumPedersenVerifierChallenge,
+        prover_response_to_challenge: ChaumPedersenProverResponse,
+    ) -> bool {
+        // the two equations of `verify_response_to_challenge`:
+        // g^z == v_t * v^c and u^z == w_t * w^c
+        let statement = &self.witness_statement;
+        batch.add_schnorr(
+            &self.g.point(),
+            &statement.v,
+            &prover_commitment.v_t,
+            &random_challenge,
+            &prover_response_to_challenge,
+        );
+        batch.add_schnorr(
+            &statement.u,
+            &statement.w,
+            &prover_commitment.w_t,
+            &random_challenge,
+            &prover_response_to_challenge,
+        );
+        true
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use curve25519_dalek::{
-        constants::RISTRETTO_BASEPOINT_TABLE, ristretto::RistrettoBasepointTable,
-    };
+    use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
 
     use crate::pok::test_macros::test_sigma_protocol;
 
@@ -157,7 +251,7 @@ mod tests {
     #[test]
     fn test_chaum_pedersen() {
         let mut rng = rand::thread_rng();
-        let g = RistrettoBasepointTable::basepoint(&RISTRETTO_BASEPOINT_TABLE);
+        let g = RISTRETTO_BASEPOINT_POINT;
 
         // 0. Fix a witness and a statement to prove
         let witness = Scalar::random(&mut rng);
@@ -166,11 +260,25 @@ mod tests {
         let w = witness * u;
         let witness_statement = ChaumPedersenWitnessStatement { u, v, w };
 
-        // 1. Initialize a Schnorr prover and verifier with the statement to prove
-        let mut prover = ChaumPedersenProver::new(witness_statement);
-        let mut verifier = ChaumPedersenVerifier::new(witness_statement);
+        // 1. Initialize the Chaum-Pedersen protocol with the statement to prove
+        let chaum_pedersen = ChaumPedersenProof::new(witness_statement);
+
+        // 2. Run tests with the protocol
+        test_sigma_protocol!(witness, chaum_pedersen);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_chaum_pedersen_commitment_serde() {
+        let mut rng = rand::thread_rng();
+        let commitment = ChaumPedersenProverCommitment {
+            v_t: RistrettoPoint::random(&mut rng),
+            w_t: RistrettoPoint::random(&mut rng),
+        };
 
-        // 2. Run tests with the verifier and prover
-        test_sigma_protocol!(witness, verifier, prover);
+        let encoded_commitment = bincode::serialize(&commitment).unwrap();
+        let decoded_commitment: ChaumPedersenProverCommitment =
+            bincode::deserialize(&encoded_commitment).unwrap();
+        assert_eq!(commitment, decoded_commitment);
     }
 }
@@ -0,0 +1,298 @@
+//! Compressed Sigma Protocols for Linear Forms
+//!
+//! Proves knowledge of x = (x_1,...,x_n) with P = x_1 * G_1 + ... + x_n * G_n, cf. [AC20]. The
+//! plain sigma protocol of Section 19.5.3 in [BS0.5] answers the challenge c with the n scalars
+//! z = r + c * x; here the prover instead proves knowledge of z with a Bulletproofs-style folding
+//! argument, which halves the vector in each of log n rounds. A proof is one commitment, 2 log n
+//! points and a single scalar, instead of one commitment and n scalars, which pays off for
+//! statements over hundreds of generators.
+//!
+//! The argument has log n + 1 rounds, so it is not a `SigmaProtocol`; it is made non-interactive
+//! directly, with the `V2` hash layout of `pok::domain` chained over all rounds.
+//!
+//! [AC20]: https://eprint.iacr.org/2020/152
+//! [BS0.5]: https://crypto.stanford.edu/~dabo/cryptobook/BonehShoup_0_5.pdf
+
+use alloc::{vec, vec::Vec};
+
+use curve25519_dalek::{
+    ristretto::RistrettoPoint,
+    scalar::Scalar,
+    traits::{Identity, MultiscalarMul, VartimeMultiscalarMul},
+};
+use rand_core::CryptoRngCore;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
+use subtle::ConstantTimeEq;
+
+use crate::pok::{
+    domain,
+    error::PokError,
+    fiat_shamir::{FiatShamirSecretKey, SignatureScheme},
+};
+
+/// the secret witness, x = (x_1,...,x_n)
+pub type CompressedWitness = Vec<Scalar>;
+
+/// the proof, the commitment A = r_1 * G_1 + ... + r_n * G_n, the points (L_j,R_j) of every
+/// folding round, and the final folded response
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CompressedSignature {
+    pub(crate) commitment: RistrettoPoint,
+    pub(crate) rounds: Vec<(RistrettoPoint, RistrettoPoint)>,
+    pub(crate) response: Scalar,
+}
+
+/// the statement P = x_1 * G_1 + ... + x_n * G_n
+#[derive(Clone)]
+pub struct CompressedLinearProof {
+    pub generators: Vec<RistrettoPoint>,
+    pub image: RistrettoPoint,
+}
+
+impl CompressedLinearProof {
+    pub fn new(generators: Vec<RistrettoPoint>, image: RistrettoPoint) -> Self {
+        CompressedLinearProof { generators, image }
+    }
+
+    /// the generators, padded with the identity to a power of two
+    fn padded_generators(&self) -> Vec<RistrettoPoint> {
+        let mut generators = self.generators.clone();
+        generators.resize(
+            self.generators.len().next_power_of_two(),
+            RistrettoPoint::identity(),
+        );
+        generators
+    }
+
+    /// starts the transcript with the statement and the message
+    fn transcript(&self, message: &[u8]) -> Sha512 {
+        let mut statement = Vec::new();
+        for generator in &self.generators {
+            statement.extend(generator.compress().as_bytes());
+        }
+        statement.extend(self.image.compress().as_bytes());
+
+        let mut hasher = Sha512::new();
+        domain::absorb(&mut hasher, domain::FIAT_SHAMIR_DOMAIN);
+        domain::absorb(&mut hasher, domain::COMPRESSED_LABEL);
+        domain::absorb(&mut hasher, domain::STATEMENT_ROLE);
+        domain::absorb(&mut hasher, &statement);
+        domain::absorb(&mut hasher, domain::MESSAGE_ROLE);
+        domain::absorb(&mut hasher, message);
+        hasher
+    }
+
+    /// absorbs the prover's points for the next round, and derives its challenge
+    fn challenge(transcript: &mut Sha512, points: &[RistrettoPoint]) -> Scalar {
+        let mut serialized_points = Vec::new();
+        for point in points {
+            serialized_points.extend(point.compress().as_bytes());
+        }
+        domain::absorb(transcript, domain::COMMITMENT_ROLE);
+        domain::absorb(transcript, &serialized_points);
+        Scalar::from_hash(transcript.clone())
+    }
+}
+
+/// the coefficient of every generator in the fully folded generator: in the round with challenge
+/// c, the generators of the left half are multiplied by c, and those of the right half by one
+fn folded_coefficients(challenges: &[Scalar]) -> Vec<Scalar> {
+    let mut coefficients = vec![Scalar::ONE];
+    for challenge in challenges.iter().rev() {
+        let left = coefficients
+            .iter()
+            .map(|coefficient| coefficient * challenge);
+        coefficients = left.chain(coefficients.iter().copied()).collect();
+    }
+    coefficients
+}
+
+impl SignatureScheme<FiatShamirSecretKey<CompressedWitness>, CompressedSignature>
+    for CompressedLinearProof
+{
+    fn sign(
+        &mut self,
+        rng: &mut dyn CryptoRngCore,
+        secret_key: FiatShamirSecretKey<CompressedWitness>,
+        message: &[u8],
+    ) -> Result<CompressedSignature, PokError> {
+        let mut witness = secret_key.witness;
+        if witness.len() != self.generators.len() {
+            return Err(PokError::LengthMismatch {
+                expected: self.generators.len(),
+                actual: witness.len(),
+            });
+        }
+        let mut generators = self.padded_generators();
+        witness.resize(generators.len(), Scalar::ZERO);
+        let mut transcript = self.transcript(message);
+
+        // 0. The sigma protocol: commit to random r, and compute the response z = r + c * x
+        let blinding: Vec<Scalar> = (0..generators.len()).map(|_| Scalar::random(rng)).collect();
+        let commitment = RistrettoPoint::multiscalar_mul(&blinding, &generators);
+        let challenge = Self::challenge(&mut transcript, &[commitment]);
+        let mut response: Vec<Scalar> = blinding
+            .iter()
+            .zip(&witness)
+            .map(|(r, x)| r + challenge * x)
+            .collect();
+
+        // 1. Instead of sending z, halve it until a single scalar is left
+        let mut rounds = Vec::new();
+        while response.len() > 1 {
+            let half = response.len() / 2;
+            let (z_left, z_right) = response.split_at(half);
+            let (g_left, g_right) = generators.split_at(half);
+            let left = RistrettoPoint::multiscalar_mul(z_left, g_right);
+            let right = RistrettoPoint::multiscalar_mul(z_right, g_left);
+            let challenge = Self::challenge(&mut transcript, &[left, right]);
+
+            response = z_left
+                .iter()
+                .zip(z_right)
+                .map(|(z_l, z_r)| z_l + challenge * z_r)
+                .collect();
+            generators = g_left
+                .iter()
+                .zip(g_right)
+                .map(|(g_l, g_r)| challenge * g_l + g_r)
+                .collect();
+            rounds.push((left, right));
+        }
+
+        Ok(CompressedSignature {
+            commitment,
+            rounds,
+            response: response[0],
+        })
+    }
+
+    fn verify(&self, message: &[u8], signature: CompressedSignature) -> bool {
+        let generators = self.padded_generators();
+        if signature.rounds.len() != generators.len().trailing_zeros() as usize {
+            return false;
+        }
+        let mut transcript = self.transcript(message);
+
+        // The response of the sigma protocol would satisfy <z, G> = A + c * P
+        let challenge = Self::challenge(&mut transcript, &[signature.commitment]);
+        let mut folded_image = signature.commitment + challenge * self.image;
+
+        // Every round folds the image into L + c * image + c^2 * R
+        let mut challenges = Vec::with_capacity(signature.rounds.len());
+        for (left, right) in &signature.rounds {
+            let challenge = Self::challenge(&mut transcript, &[*left, *right]);
+            folded_image = RistrettoPoint::vartime_multiscalar_mul(
+                [Scalar::ONE, challenge, challenge * challenge],
+                [left, &folded_image, right],
+            );
+            challenges.push(challenge);
+        }
+
+        // The final scalar must open the folded image under the folded generator
+        let coefficients = folded_coefficients(&challenges)
+            .into_iter()
+            .map(|coefficient| coefficient * signature.response);
+        RistrettoPoint::vartime_multiscalar_mul(coefficients, &generators)
+            .ct_eq(&folded_image)
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::RngCore;
+
+    use crate::pok::test_macros::test_fiat_shamir_signature;
+
+    use super::*;
+
+    #[test]
+    fn test_compressed_linear_proof() {
+        let mut rng = rand::thread_rng();
+
+        for n in [1usize, 2, 3, 8, 100] {
+            // 0. Fix a witness, and define the statement to prove
+            let generators: Vec<RistrettoPoint> =
+                (0..n).map(|_| RistrettoPoint::random(&mut rng)).collect();
+            let witness: Vec<Scalar> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+            let image = RistrettoPoint::multiscalar_mul(&witness, &generators);
+
+            // 1. Run tests with the signature proof of knowledge
+            let mut compressed = CompressedLinearProof::new(generators, image);
+            test_fiat_shamir_signature!(witness.clone(), compressed);
+
+            // 2. Proofs are logarithmic in n
+            let signature = compressed
+                .sign(
+                    &mut rng,
+                    FiatShamirSecretKey {
+                        witness: witness.clone(),
+                    },
+                    b"hello world!",
+                )
+                .unwrap();
+            assert_eq!(
+                signature.rounds.len(),
+                n.next_power_of_two().trailing_zeros() as usize
+            );
+        }
+    }
+
+    #[test]
+    fn test_compressed_linear_proof_rejects() {
+        let mut rng = rand::thread_rng();
+        let message = b"hello world!";
+        let generators: Vec<RistrettoPoint> =
+            (0..4).map(|_| RistrettoPoint::random(&mut rng)).collect();
+        let witness: Vec<Scalar> = (0..4).map(|_| Scalar::random(&mut rng)).collect();
+        let image = RistrettoPoint::multiscalar_mul(&witness, &generators);
+        let mut compressed = CompressedLinearProof::new(generators, image);
+
+        // 0. A wrong witness is rejected
+        let mut bad_witness = witness.clone();
+        bad_witness[2] += Scalar::ONE;
+        let signature = compressed
+            .sign(
+                &mut rng,
+                FiatShamirSecretKey {
+                    witness: bad_witness,
+                },
+                message,
+            )
+            .unwrap();
+        assert!(!compressed.verify(message, signature));
+
+        // 1. So are tampered rounds, and a different message
+        let signature = compressed
+            .sign(&mut rng, FiatShamirSecretKey { witness }, message)
+            .unwrap();
+        assert!(!compressed.verify(b"goodbye world!", signature.clone()));
+        let mut tampered = signature.clone();
+        tampered.rounds.pop();
+        assert!(!compressed.verify(message, tampered));
+        let mut tampered = signature;
+        tampered.rounds[0].0 += RistrettoPoint::random(&mut rng);
+        assert!(!compressed.verify(message, tampered));
+
+        // 2. A witness of the wrong length is an error, not a panic
+        assert_eq!(
+            compressed
+                .sign(
+                    &mut rng,
+                    FiatShamirSecretKey {
+                        witness: vec![Scalar::ONE]
+                    },
+                    message
+                )
+                .err(),
+            Some(PokError::LengthMismatch {
+                expected: 4,
+                actual: 1
+            })
+        );
+    }
+}
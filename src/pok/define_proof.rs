@@ -0,0 +1,359 @@
+//! A Declarative Macro for Sigma Protocols over Linear Relations
+//!
+//! `define_proof!` generates the prover, verifier, and serialization code of the generic linear
+//! sigma protocol of Section 19.5.3 in [BS0.5] from the statement alone. Secrets, public points,
+//! and equations are listed in that order:
+//!
+//! ```
+//! amaze::define_proof! {
+//!     amf_stmt: (t, u), (X, J, G) : X = t * G, J = u * G
+//! }
+//! ```
+//!
+//! Public points are declared separately since bases such as `G` can appear in several equations.
+//! Each equation is a sum of `secret * point` terms. The macro expands to a module `amf_stmt` with
+//! `Witness` and `WitnessStatement` structs (one field per secret and point), a `ProverCommitment`
+//! (one point per equation, named after its left-hand side) and `ProverResponse` (one scalar per
+//! secret) with `to_bytes`/`from_bytes`, a `Protocol` type implementing `SigmaProtocol`, and a
+//! `fiat_shamir` constructor for the signature proof of knowledge. The generated protocols
+//! compose with `and`, `or`, and friends like the hand-written ones.
+//!
+//! [BS0.5]: https://crypto.stanford.edu/~dabo/cryptobook/BonehShoup_0_5.pdf
+
+#[macro_export]
+macro_rules! define_proof {
+    (
+        $name:ident : ( $($secret:ident),+ $(,)? ), ( $($point:ident),+ $(,)? ) :
+        $(
+            $lhs:ident = $first_secret:ident * $first_point:ident
+                $( + $term_secret:ident * $term_point:ident )*
+        ),+ $(,)?
+    ) => {
+        #[allow(non_snake_case, dead_code, clippy::wrong_self_convention)]
+        pub mod $name {
+            use $crate::__private::curve25519_dalek::{
+                ristretto::{CompressedRistretto, RistrettoPoint},
+                scalar::Scalar,
+            };
+            use $crate::__private::rand_core::CryptoRngCore;
+            use $crate::__private::subtle::{Choice, ConstantTimeEq};
+            use $crate::__private::zeroize::Zeroize;
+            use $crate::pok::{
+                domain,
+                error::PokError,
+                fiat_shamir::FiatShamir,
+                linear_sigma::{GenericSigmaProverState, SigmaProtocol},
+                scalar_blinding,
+            };
+
+            /// the secret witness, one scalar per secret
+            #[derive(Debug, Clone, Copy, PartialEq)]
+            pub struct Witness {
+                $(pub $secret: Scalar,)+
+            }
+
+            /// the statement the witness is used to prove, one point per public point
+            #[derive(Debug, Clone, Copy, PartialEq)]
+            pub struct WitnessStatement {
+                $(pub $point: RistrettoPoint,)+
+            }
+
+            /// the prover's commitment, one point per equation
+            #[derive(Debug, Clone, Copy, PartialEq)]
+            pub struct ProverCommitment {
+                $(pub $lhs: RistrettoPoint,)+
+            }
+
+            /// the prover's response, one scalar per secret
+            #[derive(Debug, Clone, Copy, PartialEq)]
+            pub struct ProverResponse {
+                $(pub $secret: Scalar,)+
+            }
+
+            impl Zeroize for Witness {
+                fn zeroize(&mut self) {
+                    $(self.$secret.zeroize();)+
+                }
+            }
+
+            impl Zeroize for ProverResponse {
+                fn zeroize(&mut self) {
+                    $(self.$secret.zeroize();)+
+                }
+            }
+
+            impl ProverCommitment {
+                /// The length of `to_bytes()` in bytes.
+                pub const LENGTH: usize = 32 * [$(stringify!($lhs)),+].len();
+
+                pub fn to_bytes(&self) -> Vec<u8> {
+                    let mut buf = Vec::with_capacity(Self::LENGTH);
+                    $(buf.extend(self.$lhs.compress().as_bytes());)+
+                    buf
+                }
+
+                pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+                    if bytes.len() != Self::LENGTH {
+                        return None;
+                    }
+                    let mut chunks = bytes.chunks_exact(32);
+                    Some(ProverCommitment {
+                        $($lhs: CompressedRistretto::from_slice(chunks.next()?)
+                            .ok()?
+                            .decompress()?,)+
+                    })
+                }
+            }
+
+            impl ProverResponse {
+                /// The length of `to_bytes()` in bytes.
+                pub const LENGTH: usize = 32 * [$(stringify!($secret)),+].len();
+
+                pub fn to_bytes(&self) -> Vec<u8> {
+                    let mut buf = Vec::with_capacity(Self::LENGTH);
+                    $(buf.extend(self.$secret.as_bytes());)+
+                    buf
+                }
+
+                pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+                    if bytes.len() != Self::LENGTH {
+                        return None;
+                    }
+                    let mut chunks = bytes.chunks_exact(32);
+                    Some(ProverResponse {
+                        $($secret: Option::from(Scalar::from_canonical_bytes(
+                            chunks.next()?.try_into().ok()?,
+                        ))?,)+
+                    })
+                }
+            }
+
+            /// the per verifier secret, one random scalar per secret
+            pub type PerVerifierSecret = ProverResponse;
+
+            /// the prover's state between its commitment and its response
+            pub type ProverState = GenericSigmaProverState<Witness, PerVerifierSecret>;
+
+            /// the sigma protocol for the statement; all bases are public points of the statement,
+            /// so unlike `GenericSigmaProtocol` it has no generator of its own
+            #[derive(Clone)]
+            pub struct Protocol {
+                pub witness_statement: WitnessStatement,
+            }
+
+            impl Protocol {
+                pub fn new(witness_statement: WitnessStatement) -> Self {
+                    Protocol { witness_statement }
+                }
+            }
+
+            impl SigmaProtocol for Protocol {
+                type Witness = Witness;
+                type Statement = WitnessStatement;
+                type Commitment = ProverCommitment;
+                type Response = ProverResponse;
+                type ProverState = ProverState;
+
+                fn generate_commitment(
+                    &self,
+                    rng: &mut dyn CryptoRngCore,
+                    witness: Witness,
+                ) -> Result<(ProverCommitment, ProverState), PokError> {
+                    // Pick a random blinding scalar per secret, and keep it with the witness
+                    let blinding = ProverResponse {
+                        $($secret: Scalar::random(rng),)+
+                    };
+                    let statement = &self.witness_statement;
+                    let commitment = ProverCommitment {
+                        $($lhs: scalar_blinding::mul(&blinding.$first_secret, &statement.$first_point)
+                            $(+ scalar_blinding::mul(&blinding.$term_secret, &statement.$term_point))*,)+
+                    };
+                    Ok((
+                        commitment,
+                        ProverState {
+                            witness,
+                            per_verifier_secret: blinding,
+                        },
+                    ))
+                }
+
+                fn serialize_commitment(&self, commitment: &ProverCommitment, buf: &mut Vec<u8>) {
+                    $(buf.extend(commitment.$lhs.compress().as_bytes());)+
+                }
+
+                fn protocol_label(&self) -> Vec<u8> {
+                    let mut label = domain::DEFINE_PROOF_LABEL.to_vec();
+                    label.push(b':');
+                    label.extend(stringify!($name).as_bytes());
+                    label
+                }
+
+                fn serialize_witness_statement(&self) -> Vec<u8> {
+                    let mut buf = Vec::new();
+                    $(buf.extend(self.witness_statement.$point.compress().as_bytes());)+
+                    buf
+                }
+
+                fn generate_response_to_challenge(
+                    &self,
+                    prover_state: ProverState,
+                    random_challenge: Scalar,
+                ) -> ProverResponse {
+                    prover_state.respond(|witness, blinding| ProverResponse {
+                        $($secret: scalar_blinding::response(
+                            &blinding.$secret,
+                            &witness.$secret,
+                            &random_challenge,
+                        ),)+
+                    })
+                }
+
+                fn verify_response_to_challenge(
+                    &self,
+                    prover_commitment: ProverCommitment,
+                    random_challenge: Scalar,
+                    prover_response_to_challenge: ProverResponse,
+                ) -> bool {
+                    let statement = &self.witness_statement;
+                    let response = &prover_response_to_challenge;
+                    let verified = Choice::from(1) $(
+                        & (response.$first_secret * statement.$first_point
+                            $(+ response.$term_secret * statement.$term_point)*)
+                            .ct_eq(&(prover_commitment.$lhs + (random_challenge * statement.$lhs)))
+                    )+;
+                    verified.into()
+                }
+
+                fn simulate_prover_responses(
+                    &self,
+                    rng: &mut dyn CryptoRngCore,
+                    random_challenge: Scalar,
+                ) -> (ProverCommitment, ProverResponse) {
+                    let statement = &self.witness_statement;
+                    let response = ProverResponse {
+                        $($secret: Scalar::random(rng),)+
+                    };
+                    let commitment = ProverCommitment {
+                        $($lhs: (response.$first_secret * statement.$first_point
+                            $(+ response.$term_secret * statement.$term_point)*)
+                            - (random_challenge * statement.$lhs),)+
+                    };
+                    (commitment, response)
+                }
+            }
+
+            /// The signature proof of knowledge for the statement.
+            pub fn fiat_shamir(witness_statement: WitnessStatement) -> FiatShamir<Protocol> {
+                FiatShamir::from_sigma_protocol(Protocol::new(witness_statement))
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod tests {
+    use curve25519_dalek::{
+        constants::RISTRETTO_BASEPOINT_POINT, ristretto::RistrettoPoint, scalar::Scalar,
+    };
+    use rand::RngCore;
+
+    use crate::pok::{
+        fiat_shamir::{FiatShamirSecretKey, SignatureScheme},
+        linear_sigma::SigmaProtocol,
+        test_macros::{test_fiat_shamir_signature, test_sigma_protocol},
+    };
+
+    crate::define_proof! {
+        amf_stmt: (t, u), (X, J, G) : X = t * G, J = u * G
+    }
+
+    crate::define_proof! {
+        pedersen_dleq: (x, r), (C, P, G, H) : C = x * G + r * H, P = x * H,
+    }
+
+    #[test]
+    fn test_define_proof() {
+        let mut rng = rand::thread_rng();
+        let G = RISTRETTO_BASEPOINT_POINT;
+
+        // 0. Fix a witness, and define the statement to prove
+        let witness = amf_stmt::Witness {
+            t: Scalar::random(&mut rng),
+            u: Scalar::random(&mut rng),
+        };
+        let witness_statement = amf_stmt::WitnessStatement {
+            X: witness.t * G,
+            J: witness.u * G,
+            G,
+        };
+
+        // 1. Run tests with the generated protocol
+        let protocol = amf_stmt::Protocol::new(witness_statement);
+        test_sigma_protocol!(witness, protocol);
+
+        // 2. Run tests with the generated signature proof of knowledge
+        let mut fiat_shamir = amf_stmt::fiat_shamir(witness_statement);
+        test_fiat_shamir_signature!(witness, fiat_shamir);
+
+        // 3. A wrong witness is rejected
+        let bad_witness = amf_stmt::Witness {
+            u: Scalar::random(&mut rng),
+            ..witness
+        };
+        let signature = fiat_shamir
+            .sign(
+                &mut rng,
+                FiatShamirSecretKey {
+                    witness: bad_witness,
+                },
+                b"hello world!",
+            )
+            .unwrap();
+        assert!(!fiat_shamir.verify(b"hello world!", signature));
+    }
+
+    #[test]
+    fn test_define_proof_multiple_terms() {
+        let mut rng = rand::thread_rng();
+        let G = RISTRETTO_BASEPOINT_POINT;
+        let H = RistrettoPoint::random(&mut rng);
+
+        // 0. Fix a witness, and define the statement to prove
+        let witness = pedersen_dleq::Witness {
+            x: Scalar::random(&mut rng),
+            r: Scalar::random(&mut rng),
+        };
+        let witness_statement = pedersen_dleq::WitnessStatement {
+            C: witness.x * G + witness.r * H,
+            P: witness.x * H,
+            G,
+            H,
+        };
+
+        // 1. Run tests with the generated protocol
+        let protocol = pedersen_dleq::Protocol::new(witness_statement);
+        test_sigma_protocol!(witness, protocol);
+
+        // 2. Commitments and responses round-trip through bytes
+        let message = b"hello world!";
+        let signature = pedersen_dleq::fiat_shamir(witness_statement)
+            .sign(&mut rng, FiatShamirSecretKey { witness }, message)
+            .unwrap();
+        let encoded_commitment = signature.prover_commitment.to_bytes();
+        let encoded_response = signature.prover_response.to_bytes();
+        assert_eq!(
+            encoded_commitment.len(),
+            pedersen_dleq::ProverCommitment::LENGTH
+        );
+        assert_eq!(
+            pedersen_dleq::ProverCommitment::from_bytes(&encoded_commitment),
+            Some(signature.prover_commitment)
+        );
+        assert_eq!(
+            pedersen_dleq::ProverResponse::from_bytes(&encoded_response),
+            Some(signature.prover_response)
+        );
+        assert_eq!(pedersen_dleq::ProverResponse::from_bytes(&[0xff; 64]), None);
+    }
+}
@@ -0,0 +1,249 @@
+//! Discrete Log Equality (DLEQ) over arbitrary bases
+//!
+//! Proves log_{g_1}(p_1) = log_{g_2}(p_2) for caller-supplied generators g_1 and g_2. This is the
+//! Chaum-Pedersen protocol of Section 19.5.2 in [BS0.5] without fixing g_1 to the Ristretto
+//! basepoint; `ChaumPedersenWitnessStatement`s convert into DLEQ statements with g_1 = g.
+//!
+//! The generators come from the statement, so the `g` field of `GenericSigmaProtocol` is unused
+//! here.
+//!
+//! [BS0.5]: https://crypto.stanford.edu/~dabo/cryptobook/BonehShoup_0_5.pdf
+
+use alloc::vec::Vec;
+
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT, ristretto::RistrettoPoint, scalar::Scalar,
+};
+use rand_core::CryptoRngCore;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
+
+use crate::pok::{
+    batch::BatchVerifier,
+    chaum_pedersen::ChaumPedersenWitnessStatement,
+    domain,
+    error::PokError,
+    linear_sigma::{GenericSigmaProtocol, GenericSigmaProverState, SigmaProtocol},
+    scalar_blinding,
+};
+
+/// the secret witness, denoted by x
+pub type DleqWitness = Scalar;
+
+/// the statement the witness is used to prove, p_1 = g_1^x and p_2 = g_2^x
+#[derive(Clone, Copy)]
+pub struct DleqWitnessStatement {
+    pub g1: RistrettoPoint,
+    pub p1: RistrettoPoint,
+    pub g2: RistrettoPoint,
+    pub p2: RistrettoPoint,
+}
+
+impl From<ChaumPedersenWitnessStatement> for DleqWitnessStatement {
+    fn from(statement: ChaumPedersenWitnessStatement) -> Self {
+        DleqWitnessStatement {
+            g1: RISTRETTO_BASEPOINT_POINT,
+            p1: statement.v,
+            g2: statement.u,
+            p2: statement.w,
+        }
+    }
+}
+
+/// the per verifier secret, denoted by x_t
+pub type DleqPerVerifierSecret = Scalar;
+
+/// the prover's commitment, (t_1,t_2) = (g_1^x_t, g_2^x_t)
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DleqProverCommitment {
+    pub(crate) t1: RistrettoPoint,
+    pub(crate) t2: RistrettoPoint,
+}
+
+/// the verifier's challenge, denoted by c
+pub type DleqVerifierChallenge = Scalar;
+
+/// the prover's response, denoted by x_z
+pub type DleqProverResponse = Scalar;
+
+/// the prover's state between its commitment and its response
+pub type DleqProverState = GenericSigmaProverState<DleqWitness, DleqPerVerifierSecret>;
+
+pub type DleqProof = GenericSigmaProtocol<DleqWitnessStatement>;
+
+impl SigmaProtocol for DleqProof {
+    type Witness = DleqWitness;
+    type Statement = DleqWitnessStatement;
+    type Commitment = DleqProverCommitment;
+    type Response = DleqProverResponse;
+    type ProverState = DleqProverState;
+
+    fn generate_commitment(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        witness: DleqWitness,
+    ) -> Result<(DleqProverCommitment, DleqProverState), PokError> {
+        // Generate a new random commitment for this verifier
+        let per_verifier_secret = Scalar::random(rng);
+        Ok((
+            DleqProverCommitment {
+                t1: scalar_blinding::mul(&per_verifier_secret, &self.witness_statement.g1),
+                t2: scalar_blinding::mul(&per_verifier_secret, &self.witness_statement.g2),
+            },
+            DleqProverState {
+                witness,
+                per_verifier_secret,
+            },
+        ))
+    }
+
+    fn serialize_commitment(&self, commitment: &DleqProverCommitment, buf: &mut Vec<u8>) {
+        buf.extend(commitment.t1.compress().as_bytes());
+        buf.extend(commitment.t2.compress().as_bytes());
+    }
+
+    fn protocol_label(&self) -> Vec<u8> {
+        domain::DLEQ_LABEL.to_vec()
+    }
+
+    fn serialize_witness_statement(&self) -> Vec<u8> {
+        let statement = &self.witness_statement;
+        let mut buf = Vec::new();
+        for point in [statement.g1, statement.p1, statement.g2, statement.p2] {
+            buf.extend(point.compress().as_bytes());
+        }
+        buf
+    }
+
+    fn generate_response_to_challenge(
+        &self,
+        prover_state: DleqProverState,
+        random_challenge: DleqVerifierChallenge,
+    ) -> DleqProverResponse {
+        // Construct response using the per_verifier_secret and random_challenge
+        prover_state.respond(|witness, per_verifier_secret| {
+            scalar_blinding::response(per_verifier_secret, witness, &random_challenge)
+        })
+    }
+
+    fn verify_response_to_challenge(
+        &self,
+        prover_commitment: DleqProverCommitment,
+        random_challenge: DleqVerifierChallenge,
+        prover_response_to_challenge: DleqProverResponse,
+    ) -> bool {
+        let statement = &self.witness_statement;
+        let left1 = prover_response_to_challenge * statement.g1;
+        let right1 = prover_commitment.t1 + (random_challenge * statement.p1);
+
+        let left2 = prover_response_to_challenge * statement.g2;
+        let right2 = prover_commitment.t2 + (random_challenge * statement.p2);
+
+        (left1.ct_eq(&right1) & left2.ct_eq(&right2)).into()
+    }
+
+    fn simulate_prover_responses(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        random_challenge: DleqVerifierChallenge,
+    ) -> (DleqProverCommitment, DleqProverResponse) {
+        let statement = &self.witness_statement;
+        let simulated_prover_response = Scalar::random(rng);
+        let simulated_prover_commitment = DleqProverCommitment {
+            t1: (simulated_prover_response * statement.g1) - (random_challenge * statement.p1),
+            t2: (simulated_prover_response * statement.g2) - (random_challenge * statement.p2),
+        };
+        (simulated_prover_commitment, simulated_prover_response)
+    }
+
+    fn batch_verify_response_to_challenge(
+        &self,
+        batch: &mut BatchVerifier,
+        prover_commitment: DleqProverCommitment,
+        random_challenge: DleqVerifierChallenge,
+        prover_response_to_challenge: DleqProverResponse,
+    ) -> bool {
+        let statement = &self.witness_statement;
+        batch.add_schnorr(
+            &statement.g1,
+            &statement.p1,
+            &prover_commitment.t1,
+            &random_challenge,
+            &prover_response_to_challenge,
+        );
+        batch.add_schnorr(
+            &statement.g2,
+            &statement.p2,
+            &prover_commitment.t2,
+            &random_challenge,
+            &prover_response_to_challenge,
+        );
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pok::test_macros::test_sigma_protocol;
+
+    use super::*;
+
+    #[test]
+    fn test_dleq() {
+        let mut rng = rand::thread_rng();
+
+        // 0. Fix a witness and a statement over two random generators
+        let witness = Scalar::random(&mut rng);
+        let g1 = RistrettoPoint::random(&mut rng);
+        let g2 = RistrettoPoint::random(&mut rng);
+        let witness_statement = DleqWitnessStatement {
+            g1,
+            p1: witness * g1,
+            g2,
+            p2: witness * g2,
+        };
+
+        // 1. Initialize the DLEQ protocol with the statement to prove
+        let dleq = DleqProof::new(witness_statement);
+
+        // 2. Run tests with the protocol
+        test_sigma_protocol!(witness, dleq);
+
+        // 3. Unequal discrete logs are rejected
+        let bad_statement = DleqWitnessStatement {
+            p2: (witness + Scalar::ONE) * g2,
+            ..witness_statement
+        };
+        let bad_dleq = DleqProof::new(bad_statement);
+        let (prover_commitment, prover_state) =
+            dleq.generate_commitment(&mut rng, witness).unwrap();
+        let random_challenge = dleq.generate_random_challenge(&mut rng);
+        let prover_response = dleq.generate_response_to_challenge(prover_state, random_challenge);
+        assert!(!bad_dleq.verify_response_to_challenge(
+            prover_commitment,
+            random_challenge,
+            prover_response,
+        ));
+    }
+
+    #[test]
+    fn test_dleq_from_chaum_pedersen() {
+        let mut rng = rand::thread_rng();
+        let g = RISTRETTO_BASEPOINT_POINT;
+
+        // 0. Fix a witness and a Chaum-Pedersen statement to prove
+        let witness = Scalar::random(&mut rng);
+        let u = RistrettoPoint::random(&mut rng);
+        let witness_statement = ChaumPedersenWitnessStatement {
+            u,
+            v: witness * g,
+            w: witness * u,
+        };
+
+        // 1. Prove it as a DLEQ statement
+        let dleq = DleqProof::new(witness_statement.into());
+        test_sigma_protocol!(witness, dleq);
+    }
+}
@@ -0,0 +1,226 @@
+//! Domain Separation Tags for Fiat-Shamir Hashing
+//!
+//! Every `V2` Fiat-Shamir challenge is computed as
+//!
+//! ```text
+//! SHA-512( tag(FIAT_SHAMIR_DOMAIN)
+//!       || tag(protocol label)
+//!       || tag(STATEMENT_ROLE) || tag(serialized statement)
+//!       || tag(MESSAGE_ROLE)   || tag(message)
+//!       || tag(COMMITMENT_ROLE) || tag(serialized commitment)
+//!     [ || tag(CHALLENGE_LENGTH_ROLE) || tag(number of bits as one byte) ] )
+//! ```
+//!
+//! where `tag(x)` is the length of `x` as an 8-byte big-endian integer followed by `x`, and the
+//! last two tags are only present for short challenges, which are the low bits of the hash.
+//! Protocol labels name the sigma protocol being proven: leaf protocols use the constants below,
+//! and compositions wrap the labels of their parts, e.g.
+//! `and(or(schnorr,schnorr),or(chaum-pedersen,schnorr))` for the AMF signature proof of knowledge.
+//! The constants are part of the proof format; other implementations must use the same bytes.
+
+use alloc::vec::Vec;
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use sha2::{Digest, Sha512};
+
+/// Identifies this crate, the Fiat-Shamir transform, and the version of the hash layout.
+pub const FIAT_SHAMIR_DOMAIN: &[u8] = b"amaze/fiat-shamir/v2";
+
+/// Tags the serialized witness statement.
+pub const STATEMENT_ROLE: &[u8] = b"statement";
+/// Tags the signed message.
+pub const MESSAGE_ROLE: &[u8] = b"message";
+/// Tags the serialized prover commitment.
+pub const COMMITMENT_ROLE: &[u8] = b"commitment";
+/// Tags the number of bits of short challenges, cf. `ChallengeLength`; absent for full ones.
+pub const CHALLENGE_LENGTH_ROLE: &[u8] = b"challenge-length";
+
+pub const SCHNORR_LABEL: &[u8] = b"schnorr";
+pub const CHAUM_PEDERSEN_LABEL: &[u8] = b"chaum-pedersen";
+pub const DLEQ_LABEL: &[u8] = b"dleq";
+pub const RANGE_LABEL: &[u8] = b"range";
+/// Labels the shuffle proofs of `shuffle`.
+pub const SHUFFLE_LABEL: &[u8] = b"shuffle";
+/// Labels the compressed linear form proofs of `compressed_sigma`.
+pub const COMPRESSED_LABEL: &[u8] = b"compressed-linear";
+/// Prefix of the labels of `define_proof!` protocols, followed by `:` and the module name.
+pub const DEFINE_PROOF_LABEL: &[u8] = b"define-proof";
+
+/// Wraps the AMF protocol label in `amf::secp256k1`, cf. `amf::generic`.
+pub const SECP256K1_LABEL: &[u8] = b"secp256k1";
+
+/// Wraps the AMF protocol label in `amf::p256`, cf. `amf::generic`.
+pub const P256_LABEL: &[u8] = b"p256";
+
+pub const AND_LABEL: &[u8] = b"and";
+pub const OR_LABEL: &[u8] = b"or";
+pub const NARY_OR_LABEL: &[u8] = b"nary-or";
+pub const THRESHOLD_LABEL: &[u8] = b"threshold";
+
+/// Labels the key aggregation coefficients of `musig`.
+pub const MUSIG_KEY_AGGREGATION_LABEL: &[u8] = b"musig/key-aggregation";
+/// Labels the nonce coefficient of `musig`.
+pub const MUSIG_NONCE_LABEL: &[u8] = b"musig/nonce";
+
+/// Labels the hash of the commitment generators of `shuffle` to points.
+pub const SHUFFLE_GENERATOR_LABEL: &[u8] = b"shuffle/generator";
+/// Derives the second generator of the Pedersen commitments of `vss`, cf. `derive_generator`.
+pub const VSS_GENERATOR_LABEL: &[u8] = b"vss/generator";
+/// Labels the hash of `vrf` inputs to points.
+pub const VRF_HASH_TO_CURVE_LABEL: &[u8] = b"vrf/hash-to-curve";
+/// Labels the hash of `vrf` outputs.
+pub const VRF_OUTPUT_LABEL: &[u8] = b"vrf/output";
+/// The hash-to-curve domain separation tag of `amf::aggregatable`, in the format of RFC 9380.
+pub const AGGREGATABLE_HASH_TO_CURVE_LABEL: &[u8] =
+    b"amaze/aggregatable/v1/BLS12381G1_XMD:SHA-256_SSWU_RO_";
+/// Labels the hash of context strings to generators in `derive_generator`.
+pub const GENERATOR_LABEL: &[u8] = b"amaze/generator";
+/// Labels the messages bound to a context by `amf::bind_context`.
+pub const CONTEXT_LABEL: &[u8] = b"amaze/context";
+/// Labels the canonical encoding of `amf::AMFMessage`.
+pub const AMF_MESSAGE_LABEL: &[u8] = b"amaze/message";
+/// Labels the keys that `amf::v2` binds to the franked message.
+pub const AMF_V2_LABEL: &[u8] = b"amaze/amf/v2";
+/// Labels the seeds, keys and Merkle tree of `amf::forward_secure`, and the epoch its franks bind.
+pub const FORWARD_SECURE_LABEL: &[u8] = b"amaze/forward-secure";
+/// Labels the group and its roster, which group franks bind, cf. `amf::spok_amf::bind_members`.
+pub const GROUP_LABEL: &[u8] = b"amaze/group";
+/// Labels the VRF inputs and the report identifiers of `amf::sampling`.
+pub const SAMPLING_LABEL: &[u8] = b"amaze/sampling";
+/// Labels the transcript hash chain and the aggregate franks of `amf::epoch`.
+pub const EPOCH_LABEL: &[u8] = b"amaze/epoch";
+/// Labels the prekeys that the identity key signs in `amf::prekey`.
+pub const PREKEY_LABEL: &[u8] = b"amaze/prekey";
+/// Labels the messages that `schnorr_signature` signs, so that signatures and other Schnorr
+/// proofs of knowledge do not verify as one another.
+pub const SCHNORR_SIGNATURE_LABEL: &[u8] = b"amaze/schnorr-signature";
+/// Labels the public signatures of `amf::signed`.
+pub const SIGNED_LABEL: &[u8] = b"amaze/signed";
+/// Labels the signature ids and the linked franks of `amf::edit`.
+pub const EDIT_LABEL: &[u8] = b"amaze/edit";
+/// Labels the envelope key derivation of `integrations::sealed_sender`.
+pub const SEALED_SENDER_LABEL: &[u8] = b"amaze/sealed-sender";
+
+/// Builds the label of a composition, `combinator(part_1,...,part_n)`.
+pub fn composite_label<I: IntoIterator<Item = Vec<u8>>>(combinator: &[u8], parts: I) -> Vec<u8> {
+    let mut label = combinator.to_vec();
+    label.push(b'(');
+    for (i, part) in parts.into_iter().enumerate() {
+        if i > 0 {
+            label.push(b',');
+        }
+        label.extend(part);
+    }
+    label.push(b')');
+    label
+}
+
+/// Appends the label of a composition of `parts` protocols to `buf`, as `composite_label` does,
+/// with `write_part(i, buf)` appending the label of the `i`-th part.
+pub fn write_composite_label(
+    buf: &mut Vec<u8>,
+    combinator: &[u8],
+    parts: usize,
+    mut write_part: impl FnMut(usize, &mut Vec<u8>),
+) {
+    buf.extend(combinator);
+    buf.push(b'(');
+    for i in 0..parts {
+        if i > 0 {
+            buf.push(b',');
+        }
+        write_part(i, buf);
+    }
+    buf.push(b')');
+}
+
+/// Absorbs `bytes` into `hasher`, prefixed by its length.
+pub fn absorb(hasher: &mut Sha512, bytes: &[u8]) {
+    hasher.update((bytes.len() as u64).to_be_bytes());
+    hasher.update(bytes);
+}
+
+/// Appends `bytes` to `buf`, prefixed by its length, as `absorb` hashes it: `tag(bytes)` in the
+/// layouts of this crate.
+pub fn tag(buf: &mut Vec<u8>, bytes: &[u8]) {
+    buf.extend_from_slice(&(bytes.len() as u64).to_be_bytes());
+    buf.extend_from_slice(bytes);
+}
+
+/// Derives a generator from the context string `domain`, as
+/// `RistrettoPoint::from_uniform_bytes(SHA-512(tag(GENERATOR_LABEL) || tag(domain)))`.
+///
+/// Nobody knows the discrete logarithm of the result with respect to the basepoint or to any other
+/// derived generator, so it can serve as the second generator of a Pedersen commitment, as an
+/// Okamoto generator, or in a `define_proof!` statement. Since anyone can recompute it from
+/// `domain`, it is also plain that nothing was hidden up anyone's sleeve. Use a distinct `domain`,
+/// e.g. `b"myapp/pedersen/h"`, for every generator that must be independent.
+pub fn derive_generator(domain: &[u8]) -> RistrettoPoint {
+    let mut hasher = Sha512::new();
+    absorb(&mut hasher, GENERATOR_LABEL);
+    absorb(&mut hasher, domain);
+    RistrettoPoint::from_uniform_bytes(&hasher.finalize().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use curve25519_dalek::{
+        constants::RISTRETTO_BASEPOINT_POINT, scalar::Scalar, traits::Identity,
+    };
+
+    use crate::pok::{
+        linear_sigma::{Generator, SigmaProtocol},
+        range_proof::{RangeProof, RangeWitness, RangeWitnessStatement},
+        test_macros::test_sigma_protocol,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_composite_label() {
+        // 0. Labels nest
+        let or_label = composite_label(
+            OR_LABEL,
+            [SCHNORR_LABEL.to_vec(), CHAUM_PEDERSEN_LABEL.to_vec()],
+        );
+        assert_eq!(or_label, b"or(schnorr,chaum-pedersen)");
+        assert_eq!(
+            composite_label(AND_LABEL, [or_label, DLEQ_LABEL.to_vec()]),
+            b"and(or(schnorr,chaum-pedersen),dleq)"
+        );
+
+        // 1. Length prefixes keep adjacent inputs from running together
+        let mut split_early = Sha512::new();
+        absorb(&mut split_early, b"ab");
+        absorb(&mut split_early, b"c");
+        let mut split_late = Sha512::new();
+        absorb(&mut split_late, b"a");
+        absorb(&mut split_late, b"bc");
+        assert_ne!(split_early.finalize(), split_late.finalize());
+    }
+
+    #[test]
+    fn test_derive_generator() {
+        // 0. Generators are deterministic, and independent across domains
+        let h = derive_generator(b"amaze/test/pedersen/h");
+        assert_eq!(h, derive_generator(b"amaze/test/pedersen/h"));
+        assert_ne!(h, derive_generator(b"amaze/test/pedersen/h2"));
+        assert_ne!(h, RISTRETTO_BASEPOINT_POINT);
+        assert_ne!(derive_generator(b""), RistrettoPoint::identity());
+
+        // 1. A derived generator serves as the h of Pedersen commitments in range proofs
+        let h = Generator::new(&h);
+        let witness = RangeWitness {
+            value: 200,
+            blinding: Scalar::random(&mut rand::thread_rng()),
+        };
+        let range_proof = RangeProof::new(
+            h.clone(),
+            RangeWitnessStatement {
+                commitment: witness.commit(&h),
+                bits: 8,
+            },
+        );
+        test_sigma_protocol!(witness, range_proof);
+    }
+}
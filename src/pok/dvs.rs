@@ -0,0 +1,200 @@
+//! Designated-Verifier Signatures
+//!
+//! A signature from the signer with public key S = g^s for the verifier with public key V = g^v is
+//! a Fiat-Shamir OR proof (cf. `or_proof`) of "I know s OR I know v", as in the sender half of
+//! Fig. 5 in [AMF]. The verifier knows it did not sign, so the signature convinces it; anyone else
+//! sees a proof the verifier could have produced with `forge`, which makes the signature deniable.
+//!
+//! [AMF]: https://eprint.iacr.org/2019/565/20190527:092413
+
+use core::fmt;
+
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use rand_core::CryptoRngCore;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::pok::{
+    fiat_shamir::{FiatShamir, FiatShamirSecretKey, FiatShamirSignature, SignatureScheme},
+    linear_sigma::SigmaProtocol,
+    or_proof::{OrProof, OrProverCommitment, OrProverResponse, OrWitness},
+    schnorr::SchnorrProof,
+};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DvsPublicKey {
+    pub public_key: RistrettoPoint,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub struct DvsSecretKey {
+    pub secret_key: Scalar,
+}
+
+/// Prints a placeholder, so that keys cannot leak through logs.
+impl fmt::Debug for DvsSecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DvsSecretKey")
+            .field("secret_key", &format_args!("<redacted>"))
+            .finish()
+    }
+}
+
+/// the Fiat-Shamir OR proof inside a `DvsSignature`
+type DvsInternalSignature = FiatShamirSignature<
+    OrProverCommitment<RistrettoPoint, RistrettoPoint>,
+    OrProverResponse<Scalar, Scalar>,
+>;
+
+/// the signature, a Fiat-Shamir OR proof of two Schnorr proofs
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DvsSignature(pub(crate) DvsInternalSignature);
+
+/// the OR of the Schnorr proofs for S = g^s and V = g^v
+type DvsProof = FiatShamir<OrProof<SchnorrProof, SchnorrProof>>;
+
+fn dvs_proof(signer_public_key: DvsPublicKey, verifier_public_key: DvsPublicKey) -> DvsProof {
+    SchnorrProof::new(signer_public_key.public_key)
+        .or(SchnorrProof::new(verifier_public_key.public_key))
+        .fiat_shamir()
+}
+
+pub fn keygen(rng: &mut dyn CryptoRngCore) -> (DvsPublicKey, DvsSecretKey) {
+    let secret_key = Scalar::random(rng);
+    let public_key = RistrettoPoint::mul_base(&secret_key);
+    (DvsPublicKey { public_key }, DvsSecretKey { secret_key })
+}
+
+/// Signs `message` for the verifier, with the signer's secret key.
+pub fn sign(
+    rng: &mut dyn CryptoRngCore,
+    signer_secret_key: DvsSecretKey,
+    verifier_public_key: DvsPublicKey,
+    message: &[u8],
+) -> DvsSignature {
+    let signer_public_key = DvsPublicKey {
+        public_key: RistrettoPoint::mul_base(&signer_secret_key.secret_key),
+    };
+    let signature = dvs_proof(signer_public_key, verifier_public_key)
+        .sign(
+            rng,
+            FiatShamirSecretKey {
+                witness: OrWitness::Left(signer_secret_key.secret_key),
+            },
+            message,
+        )
+        .expect("a Schnorr witness always fits an OR of Schnorr statements");
+    DvsSignature(signature)
+}
+
+/// Produces, with the verifier's secret key, a signature that verifies just like one by the
+/// signer.
+pub fn forge(
+    rng: &mut dyn CryptoRngCore,
+    verifier_secret_key: DvsSecretKey,
+    signer_public_key: DvsPublicKey,
+    message: &[u8],
+) -> DvsSignature {
+    let verifier_public_key = DvsPublicKey {
+        public_key: RistrettoPoint::mul_base(&verifier_secret_key.secret_key),
+    };
+    let signature = dvs_proof(signer_public_key, verifier_public_key)
+        .sign(
+            rng,
+            FiatShamirSecretKey {
+                witness: OrWitness::Right(verifier_secret_key.secret_key),
+            },
+            message,
+        )
+        .expect("a Schnorr witness always fits an OR of Schnorr statements");
+    DvsSignature(signature)
+}
+
+pub fn verify(
+    signer_public_key: DvsPublicKey,
+    verifier_public_key: DvsPublicKey,
+    message: &[u8],
+    signature: DvsSignature,
+) -> bool {
+    dvs_proof(signer_public_key, verifier_public_key).verify(message, signature.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dvs() {
+        let mut rng = rand::thread_rng();
+        let message = b"hello world!";
+
+        // 0. The signer signs for the verifier
+        let (signer_public_key, signer_secret_key) = keygen(&mut rng);
+        let (verifier_public_key, _) = keygen(&mut rng);
+        let signature = sign(&mut rng, signer_secret_key, verifier_public_key, message);
+        assert!(verify(
+            signer_public_key,
+            verifier_public_key,
+            message,
+            signature
+        ));
+
+        // 1. The signature is bound to the message and both keys
+        assert!(!verify(
+            signer_public_key,
+            verifier_public_key,
+            b"goodbye world!",
+            signature
+        ));
+        let (other_public_key, _) = keygen(&mut rng);
+        assert!(!verify(
+            other_public_key,
+            verifier_public_key,
+            message,
+            signature
+        ));
+        assert!(!verify(
+            signer_public_key,
+            other_public_key,
+            message,
+            signature
+        ));
+
+        // 2. It survives serialization
+        #[cfg(feature = "serde")]
+        {
+            let encoded = bincode::serialize(&signature).unwrap();
+            let decoded: DvsSignature = bincode::deserialize(&encoded).unwrap();
+            assert_eq!(signature, decoded);
+        }
+    }
+
+    #[test]
+    fn test_dvs_forge() {
+        let mut rng = rand::thread_rng();
+        let message = b"hello world!";
+        let (signer_public_key, _) = keygen(&mut rng);
+        let (verifier_public_key, verifier_secret_key) = keygen(&mut rng);
+
+        // 0. The verifier can produce a signature that verifies, so it convinces no one else
+        let forged = forge(&mut rng, verifier_secret_key, signer_public_key, message);
+        assert!(verify(
+            signer_public_key,
+            verifier_public_key,
+            message,
+            forged
+        ));
+
+        // 1. Without either secret key, there is nothing to forge with
+        let (_, outsider_secret_key) = keygen(&mut rng);
+        let forged = forge(&mut rng, outsider_secret_key, signer_public_key, message);
+        assert!(!verify(
+            signer_public_key,
+            verifier_public_key,
+            message,
+            forged
+        ));
+    }
+}
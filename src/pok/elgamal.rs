@@ -0,0 +1,306 @@
+//! Verifiable ElGamal Encryption
+//!
+//! A point M is encrypted under the public key pk = g^x as (c_1, c_2) = (g^alpha, M + pk^alpha),
+//! and decrypted as M = c_2 - x * c_1. Anyone who knows alpha can prove with Chaum-Pedersen
+//! (cf. `chaum_pedersen`) that a ciphertext is well-formed, i.e., that (pk, c_1, c_2 - M) is a
+//! DH-triple.
+//!
+//! The holder of x can in turn prove that it decrypted correctly: it publishes the decryption
+//! share D = x * c_1, so that M = c_2 - D, with a DLEQ proof (cf. `dleq`) that log_g(pk) =
+//! log_{c_1}(D). Shares of a key split among several parties are proven the same way, each under
+//! the party's own public key.
+//!
+//! AMF uses encryptions of the identity: (E_J, J) for the judge and (E_R, R) for the recipient in
+//! Fig. 5 of [AMF].
+//!
+//! [AMF]: https://eprint.iacr.org/2019/565/20190527:092413
+
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT, ristretto::RistrettoPoint, scalar::Scalar,
+    traits::Identity,
+};
+use rand_core::CryptoRngCore;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::pok::{
+    chaum_pedersen::{
+        ChaumPedersenProof, ChaumPedersenProverCommitment, ChaumPedersenProverResponse,
+        ChaumPedersenWitnessStatement,
+    },
+    dleq::{DleqProof, DleqProverCommitment, DleqProverResponse, DleqWitnessStatement},
+    fiat_shamir::{FiatShamirSecretKey, FiatShamirSignature, SignatureScheme},
+    linear_sigma::SigmaProtocol,
+    profiling, scalar_blinding,
+};
+
+/// the encryption randomness, denoted by alpha
+pub type ElGamalRandomness = Scalar;
+
+/// the ciphertext, (c_1, c_2) = (g^alpha, M + pk^alpha)
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ElGamalCiphertext {
+    pub c1: RistrettoPoint,
+    pub c2: RistrettoPoint,
+}
+
+/// the proof that a ciphertext is well-formed, a Fiat-Shamir Chaum-Pedersen proof
+pub type ElGamalWellFormednessProof =
+    FiatShamirSignature<ChaumPedersenProverCommitment, ChaumPedersenProverResponse>;
+
+/// the decryption share D = x * c_1, and the Fiat-Shamir DLEQ proof that it is correct
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ElGamalDecryptionShare {
+    pub share: RistrettoPoint,
+    pub proof: FiatShamirSignature<DleqProverCommitment, DleqProverResponse>,
+}
+
+/// ElGamal encryption under `public_key`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VerifiableElGamal {
+    pub public_key: RistrettoPoint,
+}
+
+impl VerifiableElGamal {
+    pub fn new(public_key: RistrettoPoint) -> Self {
+        VerifiableElGamal { public_key }
+    }
+
+    /// Encrypts `plaintext` with fresh randomness, and returns the randomness to prove with.
+    pub fn encrypt(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        plaintext: RistrettoPoint,
+    ) -> (ElGamalCiphertext, ElGamalRandomness) {
+        let randomness = Scalar::random(rng);
+        (
+            self.encrypt_with_randomness(plaintext, &randomness),
+            randomness,
+        )
+    }
+
+    pub fn encrypt_with_randomness(
+        &self,
+        plaintext: RistrettoPoint,
+        randomness: &ElGamalRandomness,
+    ) -> ElGamalCiphertext {
+        profiling::record(|counts| counts.point_additions += 1);
+        ElGamalCiphertext {
+            c1: scalar_blinding::mul_base(randomness),
+            c2: plaintext + scalar_blinding::mul(randomness, &self.public_key),
+        }
+    }
+
+    /// Re-encrypts `ciphertext` with more randomness, so that it can no longer be linked to the
+    /// original.
+    pub fn rerandomize(
+        &self,
+        ciphertext: &ElGamalCiphertext,
+        randomness: &ElGamalRandomness,
+    ) -> ElGamalCiphertext {
+        let zero = self.encrypt_with_randomness(RistrettoPoint::identity(), randomness);
+        ElGamalCiphertext {
+            c1: ciphertext.c1 + zero.c1,
+            c2: ciphertext.c2 + zero.c2,
+        }
+    }
+
+    /// Recovers the plaintext with the secret key for `public_key`.
+    pub fn decrypt(secret_key: &Scalar, ciphertext: &ElGamalCiphertext) -> RistrettoPoint {
+        profiling::record(|counts| {
+            counts.scalar_muls += 1;
+            counts.point_additions += 1;
+        });
+        ciphertext.c2 - secret_key * ciphertext.c1
+    }
+
+    /// the Chaum-Pedersen protocol for c_1 = g^alpha and c_2 - M = pk^alpha, to compose into larger
+    /// proofs
+    pub fn well_formedness_protocol(
+        &self,
+        ciphertext: &ElGamalCiphertext,
+        plaintext: &RistrettoPoint,
+    ) -> ChaumPedersenProof {
+        ChaumPedersenProof::new(ChaumPedersenWitnessStatement {
+            u: self.public_key,
+            v: ciphertext.c1,
+            w: ciphertext.c2 - plaintext,
+        })
+    }
+
+    /// Proves that `ciphertext` encrypts `plaintext` under `public_key`; `message` is bound into
+    /// the proof like a signed message.
+    pub fn prove_well_formed(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        ciphertext: &ElGamalCiphertext,
+        plaintext: &RistrettoPoint,
+        randomness: ElGamalRandomness,
+        message: &[u8],
+    ) -> ElGamalWellFormednessProof {
+        self.well_formedness_protocol(ciphertext, plaintext)
+            .fiat_shamir()
+            .sign(
+                rng,
+                FiatShamirSecretKey {
+                    witness: randomness,
+                },
+                message,
+            )
+            .expect("a Chaum-Pedersen witness always fits a Chaum-Pedersen statement")
+    }
+
+    pub fn verify_well_formed(
+        &self,
+        ciphertext: &ElGamalCiphertext,
+        plaintext: &RistrettoPoint,
+        message: &[u8],
+        proof: ElGamalWellFormednessProof,
+    ) -> bool {
+        self.well_formedness_protocol(ciphertext, plaintext)
+            .fiat_shamir()
+            .verify(message, proof)
+    }
+
+    /// the DLEQ protocol for log_g(pk) = log_{c_1}(D)
+    pub fn decryption_protocol(
+        &self,
+        ciphertext: &ElGamalCiphertext,
+        share: &RistrettoPoint,
+    ) -> DleqProof {
+        DleqProof::new(DleqWitnessStatement {
+            g1: RISTRETTO_BASEPOINT_POINT,
+            p1: self.public_key,
+            g2: ciphertext.c1,
+            p2: *share,
+        })
+    }
+
+    /// Computes the decryption share with the secret key for `public_key`, and proves it correct;
+    /// `message` is bound into the proof like a signed message.
+    pub fn prove_decryption(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        secret_key: &Scalar,
+        ciphertext: &ElGamalCiphertext,
+        message: &[u8],
+    ) -> ElGamalDecryptionShare {
+        let share = secret_key * ciphertext.c1;
+        let proof = self
+            .decryption_protocol(ciphertext, &share)
+            .fiat_shamir()
+            .sign(
+                rng,
+                FiatShamirSecretKey {
+                    witness: *secret_key,
+                },
+                message,
+            )
+            .expect("a DLEQ witness always fits a DLEQ statement");
+        ElGamalDecryptionShare { share, proof }
+    }
+
+    /// Returns the plaintext, c_2 - D, if the decryption share is proven correct.
+    pub fn verify_decryption(
+        &self,
+        ciphertext: &ElGamalCiphertext,
+        message: &[u8],
+        decryption_share: &ElGamalDecryptionShare,
+    ) -> Option<RistrettoPoint> {
+        self.decryption_protocol(ciphertext, &decryption_share.share)
+            .fiat_shamir()
+            .verify(message, decryption_share.proof)
+            .then(|| ciphertext.c2 - decryption_share.share)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verifiable_elgamal() {
+        let mut rng = rand::thread_rng();
+        let secret_key = Scalar::random(&mut rng);
+        let elgamal = VerifiableElGamal::new(RistrettoPoint::mul_base(&secret_key));
+        let plaintext = RistrettoPoint::random(&mut rng);
+        let message = b"hello world!";
+
+        // 0. Encrypt and decrypt
+        let (ciphertext, randomness) = elgamal.encrypt(&mut rng, plaintext);
+        assert_eq!(
+            VerifiableElGamal::decrypt(&secret_key, &ciphertext),
+            plaintext
+        );
+        assert_ne!(
+            VerifiableElGamal::decrypt(&Scalar::random(&mut rng), &ciphertext),
+            plaintext
+        );
+
+        // 1. Prove that the ciphertext is well-formed
+        let proof =
+            elgamal.prove_well_formed(&mut rng, &ciphertext, &plaintext, randomness, message);
+        assert!(elgamal.verify_well_formed(&ciphertext, &plaintext, message, proof));
+
+        // 2. The proof does not hold for another plaintext, ciphertext, or message
+        let other_plaintext = RistrettoPoint::random(&mut rng);
+        assert!(!elgamal.verify_well_formed(&ciphertext, &other_plaintext, message, proof));
+        let (other_ciphertext, _) = elgamal.encrypt(&mut rng, plaintext);
+        assert!(!elgamal.verify_well_formed(&other_ciphertext, &plaintext, message, proof));
+        assert!(!elgamal.verify_well_formed(&ciphertext, &plaintext, b"goodbye world!", proof));
+
+        // 3. Nor for a ciphertext under another key
+        let other_elgamal = VerifiableElGamal::new(RistrettoPoint::random(&mut rng));
+        let forged = other_elgamal.encrypt_with_randomness(plaintext, &randomness);
+        let proof = elgamal.prove_well_formed(&mut rng, &forged, &plaintext, randomness, message);
+        assert!(!elgamal.verify_well_formed(&forged, &plaintext, message, proof));
+    }
+
+    #[test]
+    fn test_verifiable_elgamal_decryption() {
+        let mut rng = rand::thread_rng();
+        let secret_key = Scalar::random(&mut rng);
+        let elgamal = VerifiableElGamal::new(RistrettoPoint::mul_base(&secret_key));
+        let plaintext = RistrettoPoint::random(&mut rng);
+        let message = b"hello world!";
+        let (ciphertext, _) = elgamal.encrypt(&mut rng, plaintext);
+
+        // 0. A correct decryption is proven, and yields the plaintext
+        let decryption_share =
+            elgamal.prove_decryption(&mut rng, &secret_key, &ciphertext, message);
+        assert_eq!(
+            elgamal.verify_decryption(&ciphertext, message, &decryption_share),
+            Some(plaintext)
+        );
+
+        // 1. A wrong share is rejected, and so is the proof for another ciphertext or message
+        let wrong_share = ElGamalDecryptionShare {
+            share: decryption_share.share + RISTRETTO_BASEPOINT_POINT,
+            ..decryption_share
+        };
+        assert_eq!(
+            elgamal.verify_decryption(&ciphertext, message, &wrong_share),
+            None
+        );
+        let (other_ciphertext, _) = elgamal.encrypt(&mut rng, plaintext);
+        assert_eq!(
+            elgamal.verify_decryption(&other_ciphertext, message, &decryption_share),
+            None
+        );
+        assert_eq!(
+            elgamal.verify_decryption(&ciphertext, b"goodbye world!", &decryption_share),
+            None
+        );
+
+        // 2. Decrypting with a key other than the one for public_key cannot be proven
+        let other_secret_key = Scalar::random(&mut rng);
+        let decryption_share =
+            elgamal.prove_decryption(&mut rng, &other_secret_key, &ciphertext, message);
+        assert_eq!(
+            elgamal.verify_decryption(&ciphertext, message, &decryption_share),
+            None
+        );
+    }
+}
@@ -0,0 +1,60 @@
+//! Errors of Sigma Protocol Provers
+
+use core::fmt;
+
+/// Errors that can occur while a prover commits to a witness, or a signer signs.
+///
+/// Verifiers do not return these: a malformed proof is simply rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PokError {
+    /// The index of the statement the prover knows a witness for is out of range.
+    WitnessIndexOutOfRange { index: usize, len: usize },
+    /// The numbers of provers, verifiers, or witnesses of a composition differ.
+    LengthMismatch { expected: usize, actual: usize },
+    /// The threshold is not between 1 and the number of statements.
+    InvalidThreshold { threshold: usize, len: usize },
+    /// Fewer witnesses than the threshold were given.
+    InsufficientWitnesses { threshold: usize, actual: usize },
+    /// The committed value of a range proof does not fit in the number of bits.
+    ValueOutOfRange { bits: usize },
+    /// The number of bits of a range proof is not between 1 and 64.
+    InvalidBitLength { bits: usize },
+    /// The signer's public key is not among the keys of a multisignature.
+    UnknownSigner,
+}
+
+impl fmt::Display for PokError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PokError::WitnessIndexOutOfRange { index, len } => {
+                write!(
+                    f,
+                    "witness index {} out of range for {} statements",
+                    index, len
+                )
+            }
+            PokError::LengthMismatch { expected, actual } => {
+                write!(f, "expected {} statements, got {}", expected, actual)
+            }
+            PokError::InvalidThreshold { threshold, len } => {
+                write!(f, "invalid threshold {} for {} statements", threshold, len)
+            }
+            PokError::InsufficientWitnesses { threshold, actual } => {
+                write!(
+                    f,
+                    "expected at least {} witnesses, got {}",
+                    threshold, actual
+                )
+            }
+            PokError::ValueOutOfRange { bits } => {
+                write!(f, "value does not fit in {} bits", bits)
+            }
+            PokError::InvalidBitLength { bits } => {
+                write!(f, "invalid bit length {}, expected 1 to 64", bits)
+            }
+            PokError::UnknownSigner => write!(f, "signer is not among the aggregated keys"),
+        }
+    }
+}
+
+impl core::error::Error for PokError {}
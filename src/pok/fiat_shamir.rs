@@ -4,108 +4,285 @@
 //!
 //! [BS0.5]: https://crypto.stanford.edu/~dabo/cryptobook/BonehShoup_0_5.pdf
 
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use core::cell::Cell;
+use core::fmt;
+
 use curve25519_dalek::scalar::Scalar;
+use rand_core::CryptoRngCore;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha512};
+use subtle::ConstantTimeEq;
 
-use crate::pok::linear_sigma::{SigmaProver, SigmaVerifier};
+use crate::pok::{
+    batch::BatchVerifier,
+    domain,
+    error::PokError,
+    linear_sigma::{ChallengeLength, SigmaProtocol},
+};
 
-/// the verifier's challenge, denoted by c in Section 19.6.1 of [BS0.5]
-type FiatShamirChallenge = Scalar;
+#[cfg(feature = "std")]
+std::thread_local! {
+    /// the serialization buffer of `hash_message_and_commitment_to_scalar`; taken while in use, so
+    /// that a nested hash, if any, gets a fresh one
+    static SCRATCH: Cell<Vec<u8>> = const { Cell::new(Vec::new()) };
+}
 
 /// the secret key, denoted by x in Section 19.6.1 of [BS0.5]
 pub struct FiatShamirSecretKey<Witness> {
     pub witness: Witness,
 }
 
+/// Prints a placeholder for the witness, so that it cannot leak through logs.
+impl<Witness> fmt::Debug for FiatShamirSecretKey<Witness> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FiatShamirSecretKey")
+            .field("witness", &format_args!("<redacted>"))
+            .finish()
+    }
+}
+
 /// the signature, denoted by sigma=(t,z) in Section 19.6.1 of [BS0.5]
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct FiatShamirSignature<ProverCommitment, ProverResponse> {
     pub prover_commitment: ProverCommitment,
     pub prover_response: ProverResponse,
 }
 
-pub struct FiatShamir<Witness, WitnessStatement, ProverCommitment, ProverResponse> {
-    pub prover: Box<
-        dyn SigmaProver<
-            Witness,
-            WitnessStatement,
-            ProverCommitment,
-            FiatShamirChallenge,
-            ProverResponse,
-        >,
-    >,
-    pub verifier: Box<
-        dyn SigmaVerifier<
-            Witness,
-            WitnessStatement,
-            ProverCommitment,
-            FiatShamirChallenge,
-            ProverResponse,
-        >,
-    >,
+/// the compact form of a signature, which carries the challenge c instead of the commitment t;
+/// the verifier recomputes t from (c, z), cf. `FiatShamir::verify_compact`. For AND and OR
+/// compositions of Schnorr and Chaum-Pedersen proofs, this replaces a point per branch by one
+/// scalar in all.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FiatShamirCompactSignature<ProverResponse> {
+    pub challenge: Scalar,
+    pub prover_response: ProverResponse,
+}
+
+/// which inputs are hashed into the simulated challenge
+///
+/// `V1` hashes only the message and the commitment ("weak" Fiat-Shamir), so a proof can be
+/// re-targeted to a different statement chosen after the fact. `V2` also absorbs the serialized
+/// witness statement ("strong" Fiat-Shamir), cf. [BPW12], and domain-separates every input
+/// (see `pok::domain`). `V1` is kept to verify existing proofs; new protocols should use `V2`.
+/// Both layouts are fixed, as are the wire format versions 1 and 2 of AMF signatures that use
+/// them; a change to either is a new version.
+///
+/// [BPW12]: https://eprint.iacr.org/2016/771.pdf
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum FiatShamirVersion {
+    V1,
+    #[default]
+    V2,
+}
+
+/// the signature scheme obtained from the sigma protocol `Protocol`; concrete types let the
+/// compiler inline the whole proof, but `DynSigmaProtocol` works too
+pub struct FiatShamir<Protocol> {
+    pub protocol: Protocol,
+    pub version: FiatShamirVersion,
+    /// `Full` unless set with `with_challenge_length`
+    pub challenge_length: ChallengeLength,
 }
 
 pub trait SignatureScheme<SecretKey, Signature> {
-    fn sign(&mut self, secret_key: SecretKey, message: &[u8]) -> Signature;
+    /// fails if the secret key does not fit the statement, cf. `PokError`
+    fn sign(
+        &mut self,
+        rng: &mut dyn CryptoRngCore,
+        secret_key: SecretKey,
+        message: &[u8],
+    ) -> Result<Signature, PokError>;
     fn verify(&self, message: &[u8], signature: Signature) -> bool;
 }
 
-impl<Witness, WitnessStatement, ProverCommitment, ProverResponse>
-    FiatShamir<Witness, WitnessStatement, ProverCommitment, ProverResponse>
-{
+impl<Protocol: SigmaProtocol> FiatShamir<Protocol> {
+    /// creates a signature scheme with the current `FiatShamirVersion`
+    pub fn from_sigma_protocol(protocol: Protocol) -> Self {
+        FiatShamir {
+            protocol,
+            version: FiatShamirVersion::default(),
+            challenge_length: ChallengeLength::Full,
+        }
+    }
+
+    /// Truncates the challenges to `challenge_length`, and switches the OR compositions in the
+    /// protocol to split them accordingly. This trades soundness for shorter OR challenge shares
+    /// and a cheaper verifier, cf. `ChallengeLength`; under `V2`, the length is hashed into the
+    /// challenge, so that signatures for one length do not verify under another.
+    pub fn with_challenge_length(mut self, challenge_length: ChallengeLength) -> Self {
+        self.protocol.set_challenge_length(challenge_length);
+        self.challenge_length = challenge_length;
+        self
+    }
+
+    /// Like `verify`, but adds the verification equations to `batch` instead of checking them,
+    /// cf. `pok::batch`; the signature is valid only if this returns true and `batch` verifies.
+    pub fn batch_verify(
+        &self,
+        batch: &mut BatchVerifier,
+        message: &[u8],
+        signature: FiatShamirSignature<Protocol::Commitment, Protocol::Response>,
+    ) -> bool {
+        let simulated_challenge =
+            self.hash_message_and_commitment_to_scalar(message, &signature.prover_commitment);
+
+        self.protocol.batch_verify_response_to_challenge(
+            batch,
+            signature.prover_commitment,
+            simulated_challenge,
+            signature.prover_response,
+        )
+    }
+
+    /// The challenge of a signature on `message` with `prover_commitment`, e.g. to cross-check a
+    /// proof against another implementation of the hash layout of `pok::domain`.
+    pub fn challenge(&self, message: &[u8], prover_commitment: &Protocol::Commitment) -> Scalar {
+        self.hash_message_and_commitment_to_scalar(message, prover_commitment)
+    }
+
     /// creates a simulated challenge by hashing the message and the commitment
-    /// to a scalar.
-    fn hash_message_and_commitment_to_scalar(
+    /// to a scalar. `V2` also hashes the witness statement, with the domain
+    /// separation tags of `pok::domain`.
+    pub(crate) fn hash_message_and_commitment_to_scalar(
         &self,
         message: &[u8],
-        prover_commitment: &ProverCommitment,
+        prover_commitment: &Protocol::Commitment,
     ) -> Scalar {
-        let serialized_commitment = self.prover.as_ref().serialize_commitment(prover_commitment);
+        // The label, the statement and the commitment are serialized one after the other into a
+        // buffer of the thread, which keeps its capacity, so that hashing does not allocate after
+        // the first call; without std, into a fresh buffer
+        #[cfg(feature = "std")]
+        let mut buf = SCRATCH.take();
+        #[cfg(not(feature = "std"))]
+        let mut buf = Vec::new();
+        buf.clear();
+        let protocol = &self.protocol;
+        if self.version == FiatShamirVersion::V2 {
+            protocol.write_protocol_label(&mut buf);
+        }
+        let label_end = buf.len();
+        if self.version == FiatShamirVersion::V2 {
+            protocol.write_witness_statement(&mut buf);
+        }
+        let statement_end = buf.len();
+        protocol.serialize_commitment(prover_commitment, &mut buf);
+        let (label, rest) = buf.split_at(label_end);
+        let (statement, serialized_commitment) = rest.split_at(statement_end - label_end);
 
         let mut hasher = Sha512::new();
-        hasher.update(message);
-        hasher.update(b"||");
-        hasher.update(&serialized_commitment);
+        match self.version {
+            FiatShamirVersion::V1 => {
+                hasher.update(message);
+                hasher.update(b"||");
+                hasher.update(serialized_commitment);
+            }
+            FiatShamirVersion::V2 => {
+                domain::absorb(&mut hasher, domain::FIAT_SHAMIR_DOMAIN);
+                domain::absorb(&mut hasher, label);
+                domain::absorb(&mut hasher, domain::STATEMENT_ROLE);
+                domain::absorb(&mut hasher, statement);
+                domain::absorb(&mut hasher, domain::MESSAGE_ROLE);
+                domain::absorb(&mut hasher, message);
+                domain::absorb(&mut hasher, domain::COMMITMENT_ROLE);
+                domain::absorb(&mut hasher, serialized_commitment);
+                // Absent for full-length challenges, which keeps their hashes as they were
+                if self.challenge_length != ChallengeLength::Full {
+                    domain::absorb(&mut hasher, domain::CHALLENGE_LENGTH_ROLE);
+                    domain::absorb(&mut hasher, &[self.challenge_length.bits()]);
+                }
+            }
+        }
+        #[cfg(feature = "std")]
+        SCRATCH.set(buf);
 
-        Scalar::from_hash(hasher)
+        self.challenge_length.truncate(Scalar::from_hash(hasher))
     }
-}
 
-impl<Witness, WitnessStatement, ProverCommitment, ProverResponse>
-    SignatureScheme<
-        FiatShamirSecretKey<Witness>,
-        FiatShamirSignature<ProverCommitment, ProverResponse>,
-    > for FiatShamir<Witness, WitnessStatement, ProverCommitment, ProverResponse>
-{
-    fn sign(
-        &mut self,
-        secret_key: FiatShamirSecretKey<Witness>,
+    /// the compact form of `signature` on `message`
+    pub fn compact(
+        &self,
         message: &[u8],
-    ) -> FiatShamirSignature<ProverCommitment, ProverResponse> {
-        let prover_commitment = self.prover.generate_commitment(secret_key.witness);
+        signature: FiatShamirSignature<Protocol::Commitment, Protocol::Response>,
+    ) -> FiatShamirCompactSignature<Protocol::Response> {
+        FiatShamirCompactSignature {
+            challenge: self
+                .hash_message_and_commitment_to_scalar(message, &signature.prover_commitment),
+            prover_response: signature.prover_response,
+        }
+    }
+
+    /// Verifies a `compact` signature: recomputes the commitment that the response answers the
+    /// challenge for, and checks that it hashes to the challenge. Fails for protocols that cannot
+    /// recompute their commitments, cf. `SigmaProtocol::recompute_commitment`.
+    pub fn verify_compact(
+        &self,
+        message: &[u8],
+        signature: FiatShamirCompactSignature<Protocol::Response>,
+    ) -> bool {
+        let Some(prover_commitment) = self
+            .protocol
+            .recompute_commitment(signature.challenge, signature.prover_response)
+        else {
+            return false;
+        };
+        self.hash_message_and_commitment_to_scalar(message, &prover_commitment)
+            .ct_eq(&signature.challenge)
+            .into()
+    }
 
+    /// the rest of `sign` after the prover's commitment, for callers that compute the commitment
+    /// themselves, e.g. with the branches of a composition in parallel
+    pub(crate) fn sign_with_commitment(
+        &self,
+        message: &[u8],
+        prover_commitment: Protocol::Commitment,
+        prover_state: Protocol::ProverState,
+    ) -> FiatShamirSignature<Protocol::Commitment, Protocol::Response> {
         let simulated_challenge =
             self.hash_message_and_commitment_to_scalar(message, &prover_commitment);
 
         let prover_response = self
-            .prover
-            .generate_response_to_challenge(simulated_challenge);
+            .protocol
+            .generate_response_to_challenge(prover_state, simulated_challenge);
 
         FiatShamirSignature {
             prover_commitment,
             prover_response,
         }
     }
+}
+
+impl<Protocol: SigmaProtocol>
+    SignatureScheme<
+        FiatShamirSecretKey<Protocol::Witness>,
+        FiatShamirSignature<Protocol::Commitment, Protocol::Response>,
+    > for FiatShamir<Protocol>
+{
+    fn sign(
+        &mut self,
+        rng: &mut dyn CryptoRngCore,
+        secret_key: FiatShamirSecretKey<Protocol::Witness>,
+        message: &[u8],
+    ) -> Result<FiatShamirSignature<Protocol::Commitment, Protocol::Response>, PokError> {
+        let (prover_commitment, prover_state) =
+            self.protocol.generate_commitment(rng, secret_key.witness)?;
+        Ok(self.sign_with_commitment(message, prover_commitment, prover_state))
+    }
 
     fn verify(
         &self,
         message: &[u8],
-        signature: FiatShamirSignature<ProverCommitment, ProverResponse>,
+        signature: FiatShamirSignature<Protocol::Commitment, Protocol::Response>,
     ) -> bool {
         let simulated_challenge =
             self.hash_message_and_commitment_to_scalar(message, &signature.prover_commitment);
 
-        self.verifier.verify_response_to_challenge(
+        self.protocol.verify_response_to_challenge(
             signature.prover_commitment,
             simulated_challenge,
             signature.prover_response,
@@ -115,14 +292,14 @@ impl<Witness, WitnessStatement, ProverCommitment, ProverResponse>
 
 #[cfg(test)]
 mod tests {
-    use rand::RngCore;
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
 
-    use curve25519_dalek::{
-        constants::RISTRETTO_BASEPOINT_TABLE, ristretto::RistrettoBasepointTable,
-    };
+    use curve25519_dalek::{constants::RISTRETTO_BASEPOINT_POINT, ristretto::RistrettoPoint};
 
+    #[cfg(feature = "serde")]
+    use crate::pok::serialized_size::SerializedSize;
     use crate::pok::{
-        schnorr::{SchnorrProver, SchnorrVerifier},
+        linear_sigma::DynSigmaProtocol, or_proof::OrWitness, schnorr::SchnorrProof,
         test_macros::test_fiat_shamir_signature,
     };
 
@@ -131,22 +308,235 @@ mod tests {
     #[test]
     fn test_fiat_shamir_schnorr() {
         let mut rng = rand::thread_rng();
-        let g = RistrettoBasepointTable::basepoint(&RISTRETTO_BASEPOINT_TABLE);
+        let g = RISTRETTO_BASEPOINT_POINT;
 
         // 0. Fix a witness, and define the statement to prove
         let witness = Scalar::random(&mut rng);
         let witness_statement = witness * g;
 
-        // 1. Initialize a Schnorr prover and verifier with the statement to prove
-        let prover = SchnorrProver::new(witness_statement);
-        let verifier = SchnorrVerifier::new(witness_statement);
+        // 1. Initialize Schnorr's protocol with the statement to prove
+        let schnorr = SchnorrProof::new(witness_statement);
 
         // 2. Create a Fiat-Shamir Signature Scheme
-        let mut schnorr_fiat_shamir = FiatShamir {
-            prover: Box::from(prover),
-            verifier: Box::from(verifier),
+        let mut schnorr_fiat_shamir = schnorr.clone().fiat_shamir();
+        test_fiat_shamir_signature!(witness, schnorr_fiat_shamir);
+
+        // 3. The same, with the protocol behind a trait object
+        let mut dyn_schnorr_fiat_shamir =
+            FiatShamir::from_sigma_protocol(Box::new(schnorr) as DynSigmaProtocol<_, _, _, _, _>);
+        test_fiat_shamir_signature!(witness, dyn_schnorr_fiat_shamir);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_fiat_shamir_signature_serde() {
+        let mut rng = rand::thread_rng();
+        let g = RISTRETTO_BASEPOINT_POINT;
+
+        // 0. Sign a message with a Schnorr Fiat-Shamir signature
+        let witness = Scalar::random(&mut rng);
+        let witness_statement = witness * g;
+        let mut schnorr_fiat_shamir = SchnorrProof::new(witness_statement).fiat_shamir();
+        let message = b"hello world!";
+        let signature = schnorr_fiat_shamir
+            .sign(&mut rng, FiatShamirSecretKey { witness }, message)
+            .unwrap();
+
+        // 1. Persist the standalone proof and load it back
+        let encoded_signature = bincode::serialize(&signature).unwrap();
+        assert_eq!(encoded_signature.len(), signature.serialized_size());
+        let decoded_signature: FiatShamirSignature<RistrettoPoint, Scalar> =
+            bincode::deserialize(&encoded_signature).unwrap();
+        assert_eq!(signature, decoded_signature);
+        assert!(schnorr_fiat_shamir.verify(message, decoded_signature));
+    }
+
+    #[test]
+    fn test_fiat_shamir_compact() {
+        let mut rng = rand::thread_rng();
+        let g = RISTRETTO_BASEPOINT_POINT;
+
+        // 0. Sign with the AND of a Schnorr proof and an OR of two, with short challenges too
+        let witness = Scalar::random(&mut rng);
+        let statement = witness * g;
+        let other_statement = RistrettoPoint::random(&mut rng);
+        for challenge_length in [ChallengeLength::Full, ChallengeLength::Bits128] {
+            let mut fiat_shamir = SchnorrProof::new(statement)
+                .and(SchnorrProof::new(other_statement).or(SchnorrProof::new(statement)))
+                .fiat_shamir()
+                .with_challenge_length(challenge_length);
+            let message = b"hello world!";
+            let witness = (witness, OrWitness::Right(witness));
+            let signature = fiat_shamir
+                .sign(&mut rng, FiatShamirSecretKey { witness }, message)
+                .unwrap();
+
+            // 1. Its compact form verifies, without the commitments
+            let compact = fiat_shamir.compact(message, signature);
+            assert!(fiat_shamir.verify_compact(message, compact));
+
+            // 2. Not for another message, or with a changed challenge or response
+            assert!(!fiat_shamir.verify_compact(b"goodbye world!", compact));
+            let mut tampered = compact;
+            tampered.challenge += Scalar::ONE;
+            assert!(!fiat_shamir.verify_compact(message, tampered));
+            let mut tampered = compact;
+            tampered.prover_response.1.z_1 += Scalar::ONE;
+            assert!(!fiat_shamir.verify_compact(message, tampered));
+        }
+    }
+
+    #[test]
+    fn test_fiat_shamir_seeded_rng() {
+        let mut rng = rand::thread_rng();
+        let g = RISTRETTO_BASEPOINT_POINT;
+
+        // 0. An OR of two Schnorr proofs, so that both the real and the simulated branch draw
+        //    randomness
+        let witness = Scalar::random(&mut rng);
+        let s0_statement = witness * g;
+        let s1_statement = RistrettoPoint::random(&mut rng);
+        let mut or_fiat_shamir = SchnorrProof::new(s0_statement)
+            .or(SchnorrProof::new(s1_statement))
+            .fiat_shamir();
+        let message = b"hello world!";
+        let mut sign_with_seed = |seed| {
+            or_fiat_shamir
+                .sign(
+                    &mut StdRng::seed_from_u64(seed),
+                    FiatShamirSecretKey {
+                        witness: OrWitness::Left(witness),
+                    },
+                    message,
+                )
+                .unwrap()
         };
 
-        test_fiat_shamir_signature!(witness, schnorr_fiat_shamir);
+        // 1. The same seed gives the same signature, and a different seed a different one
+        let signature = sign_with_seed(0);
+        assert_eq!(signature, sign_with_seed(0));
+        assert_ne!(signature, sign_with_seed(1));
+        assert!(or_fiat_shamir.verify(message, signature));
+    }
+
+    #[test]
+    fn test_fiat_shamir_short_challenges() {
+        let mut rng = rand::thread_rng();
+        let g = RISTRETTO_BASEPOINT_POINT;
+        let message = b"hello world!";
+        let witness = Scalar::random(&mut rng);
+        let or = || {
+            SchnorrProof::new(witness * g).or(SchnorrProof::new(RistrettoPoint::random(
+                &mut rand::thread_rng(),
+            )))
+        };
+
+        for (challenge_length, len) in [
+            (ChallengeLength::Bits128, 16),
+            (ChallengeLength::Bits192, 24),
+        ] {
+            // 0. An OR proof with short challenges signs and verifies
+            let protocol = or();
+            let statement = (protocol.s0.witness_statement, protocol.s1.witness_statement);
+            let mut short = protocol
+                .fiat_shamir()
+                .with_challenge_length(challenge_length);
+            test_fiat_shamir_signature!(OrWitness::Left(witness), short);
+
+            // 1. Both the challenge and the challenge shares are short
+            let signature = short
+                .sign(
+                    &mut rng,
+                    FiatShamirSecretKey {
+                        witness: OrWitness::Left(witness),
+                    },
+                    message,
+                )
+                .unwrap();
+            let challenge =
+                short.hash_message_and_commitment_to_scalar(message, &signature.prover_commitment);
+            let c_0 = signature.prover_response.c_0;
+            for scalar in [challenge, c_0] {
+                assert!(scalar.as_bytes()[len..].iter().all(|&byte| byte == 0));
+            }
+
+            // 2. The signature does not verify with full-length challenges, nor does a share that
+            //    is not short
+            let full = SchnorrProof::new(statement.0)
+                .or(SchnorrProof::new(statement.1))
+                .fiat_shamir();
+            assert!(!full.verify(message, signature));
+            let mut long_share = signature;
+            long_share.prover_response.c_0 += Scalar::from_bytes_mod_order({
+                let mut modulus = [0u8; 32];
+                modulus[len] = 1;
+                modulus
+            });
+            assert!(!short.verify(message, long_share));
+        }
+    }
+
+    #[test]
+    fn test_fiat_shamir_statement_binding() {
+        let mut rng = rand::thread_rng();
+        let g = RISTRETTO_BASEPOINT_POINT;
+        let message = b"hello world!";
+
+        // 0. A Schnorr proof for a random statement
+        let witness = Scalar::random(&mut rng);
+        let witness_statement = witness * g;
+        let schnorr_fiat_shamir = |statement, version| {
+            let mut fiat_shamir = SchnorrProof::new(statement).fiat_shamir();
+            fiat_shamir.version = version;
+            fiat_shamir
+        };
+
+        // 1. Under V1, the proof can be re-targeted to another statement: with challenge c and
+        //    response z, pick a fresh commitment t and solve for the statement y' = (g^z - t)/c
+        let retarget = |version| {
+            let t = RistrettoPoint::random(&mut rand::thread_rng());
+            let z = Scalar::random(&mut rand::thread_rng());
+            let fs = schnorr_fiat_shamir(witness_statement, version);
+            let c = fs.hash_message_and_commitment_to_scalar(message, &t);
+            let forged_statement = (z * g - t) * c.invert();
+            let signature = FiatShamirSignature {
+                prover_commitment: t,
+                prover_response: z,
+            };
+            schnorr_fiat_shamir(forged_statement, version).verify(message, signature)
+        };
+        assert!(retarget(FiatShamirVersion::V1));
+
+        // 2. Under V2, the challenge depends on the statement, so the same attack fails
+        assert!(!retarget(FiatShamirVersion::V2));
+
+        // 3. An honest V2 proof does not verify against a different statement
+        let signature = schnorr_fiat_shamir(witness_statement, FiatShamirVersion::V2)
+            .sign(&mut rng, FiatShamirSecretKey { witness }, message)
+            .unwrap();
+        let other_statement = RistrettoPoint::random(&mut rng);
+        assert!(
+            !schnorr_fiat_shamir(other_statement, FiatShamirVersion::V2).verify(message, signature)
+        );
+
+        // 4. The hash layouts are pinned: wire format version 2 is the domain-separated layout of
+        //    `pok::domain`, and changing either layout needs a new `FiatShamirVersion` and version
+        let statement = Scalar::from(7u64) * g;
+        let commitment = Scalar::from(11u64) * g;
+        let challenge = |version| {
+            hex::encode(
+                schnorr_fiat_shamir(statement, version)
+                    .challenge(message, &commitment)
+                    .as_bytes(),
+            )
+        };
+        assert_eq!(
+            challenge(FiatShamirVersion::V1),
+            "14932d13347930400d7601fe5a90d1d815b974e798e6c5cb78d520f2e6595c05"
+        );
+        assert_eq!(
+            challenge(FiatShamirVersion::V2),
+            "0acfe2b9589b68ee6c93b81734921c2aa966d1732aa83dbdde54f98a06e6250a"
+        );
     }
 }
@@ -4,91 +4,559 @@
 //!
 //! [BS0.5]: https://crypto.stanford.edu/~dabo/cryptobook/BonehShoup_0_5.pdf
 
+#[cfg(feature = "precomputed-tables")]
+use alloc::sync::Arc;
+use alloc::{boxed::Box, vec::Vec};
+use core::fmt;
+
+#[cfg(feature = "precomputed-tables")]
+use curve25519_dalek::ristretto::RistrettoBasepointTable;
 use curve25519_dalek::{
-    constants::RISTRETTO_BASEPOINT_TABLE,
-    ristretto::{RistrettoBasepointTable, RistrettoPoint},
+    constants::RISTRETTO_BASEPOINT_POINT, ristretto::RistrettoPoint, scalar::Scalar,
+    traits::VartimeMultiscalarMul,
 };
+use rand_core::CryptoRngCore;
+use zeroize::Zeroize;
 
-pub trait SigmaProver<
-    Witness,
-    WitnessStatement,
-    ProverCommitment,
-    VerifierChallenge,
-    ProverResponse,
->
-{
-    fn generate_commitment(&mut self, witness: Witness) -> ProverCommitment;
-    fn serialize_commitment(&self, commitment: &ProverCommitment) -> Vec<u8>;
-    fn generate_response_to_challenge(
-        &mut self,
-        random_challenge: VerifierChallenge,
-    ) -> ProverResponse;
+use crate::pok::{
+    and_proof::AndProof, batch::BatchVerifier, error::PokError, fiat_shamir::FiatShamir,
+    or_proof::OrProof, profiling, scalar_blinding,
+};
+
+/// the verifier's challenge; every protocol in this crate uses scalars, so that compositions can
+/// split and recombine challenges
+pub type SigmaChallenge = Scalar;
+
+/// the space challenges are drawn from
+///
+/// `Full` challenges are uniform scalars. Shorter challenges are integers below 2^128 or 2^192:
+/// soundness drops from about 2^-252 to 2^-128 or 2^-192 (a cheating prover succeeds with one
+/// guess of the challenge in as many tries), in exchange for challenge shares that fit in 16 or 24
+/// bytes and cheaper multiplications by challenges for the verifier. OR compositions then split
+/// challenges modulo 2^128 or 2^192, so that every share stays short; cf.
+/// `FiatShamir::with_challenge_length`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum ChallengeLength {
+    #[default]
+    Full,
+    Bits192,
+    Bits128,
 }
 
-pub trait SigmaVerifier<
-    Witness,
-    WitnessStatement,
-    ProverCommitment,
-    VerifierChallenge,
-    ProverResponse,
->
-{
-    fn generate_random_challenge(&mut self) -> VerifierChallenge;
+impl ChallengeLength {
+    /// the number of bytes of a short challenge
+    fn len(self) -> Option<usize> {
+        match self {
+            ChallengeLength::Full => None,
+            ChallengeLength::Bits192 => Some(24),
+            ChallengeLength::Bits128 => Some(16),
+        }
+    }
+
+    /// the number of bits of a short challenge, and 0 for a full one
+    pub fn bits(self) -> u8 {
+        self.len().map_or(0, |len| 8 * len as u8)
+    }
+
+    /// reduces a scalar into the challenge space, keeping its low bits
+    pub fn truncate(self, challenge: Scalar) -> SigmaChallenge {
+        let Some(len) = self.len() else {
+            return challenge;
+        };
+        let mut bytes = challenge.to_bytes();
+        bytes[len..].fill(0);
+        Scalar::from_bytes_mod_order(bytes)
+    }
+
+    pub fn random(self, rng: &mut dyn CryptoRngCore) -> SigmaChallenge {
+        let Some(len) = self.len() else {
+            return Scalar::random(rng);
+        };
+        let mut bytes = [0u8; 32];
+        rng.fill_bytes(&mut bytes[..len]);
+        Scalar::from_bytes_mod_order(bytes)
+    }
+
+    /// a + b in the challenge space, i.e., modulo 2^128 or 2^192 for short challenges
+    pub fn add(self, a: SigmaChallenge, b: SigmaChallenge) -> SigmaChallenge {
+        // Both are below 2^192, so the sum does not wrap around the group order
+        self.truncate(a + b)
+    }
+
+    /// a - b in the challenge space, i.e., modulo 2^128 or 2^192 for short challenges
+    pub fn sub(self, a: SigmaChallenge, b: SigmaChallenge) -> SigmaChallenge {
+        let Some(len) = self.len() else {
+            return a - b;
+        };
+        let mut modulus = [0u8; 32];
+        modulus[len] = 1;
+        self.truncate(a + Scalar::from_bytes_mod_order(modulus) - b)
+    }
+}
+
+/// A three-move sigma protocol: the prover commits, the verifier challenges, and the prover
+/// responds. One value holds the statement and plays both roles; the prover only additionally
+/// needs the witness, which it passes to `generate_commitment`.
+pub trait SigmaProtocol {
+    /// the secret the prover knows
+    type Witness;
+    /// the public statement about the witness
+    type Statement;
+    /// the prover's first message
+    type Commitment;
+    /// the prover's answer to the challenge
+    type Response;
+    /// the secrets the prover keeps between its commitment and its response
+    type ProverState;
+
+    /// returns the commitment, and the state to answer the challenge with; fails if the witness
+    /// does not fit the statement's shape, e.g. an n-ary OR witness index out of range
+    fn generate_commitment(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        witness: Self::Witness,
+    ) -> Result<(Self::Commitment, Self::ProverState), PokError>;
+    /// appends the bytes of `commitment` to `buf`, so that compositions and the Fiat-Shamir hash
+    /// share one buffer
+    fn serialize_commitment(&self, commitment: &Self::Commitment, buf: &mut Vec<u8>);
+    fn serialize_witness_statement(&self) -> Vec<u8>;
+    /// names the protocol for domain separation, cf. `pok::domain`
+    fn protocol_label(&self) -> Vec<u8>;
+    /// appends `protocol_label` to `buf`; compositions and the Fiat-Shamir hash use this, and the
+    /// protocols on the franking path override it so that it does not allocate
+    fn write_protocol_label(&self, buf: &mut Vec<u8>) {
+        buf.extend(self.protocol_label());
+    }
+    /// appends `serialize_witness_statement` to `buf`, like `write_protocol_label`
+    fn write_witness_statement(&self, buf: &mut Vec<u8>) {
+        buf.extend(self.serialize_witness_statement());
+    }
+    /// consumes the state of `generate_commitment`, so that it cannot answer two challenges
+    fn generate_response_to_challenge(
+        &self,
+        prover_state: Self::ProverState,
+        random_challenge: SigmaChallenge,
+    ) -> Self::Response;
+
+    fn generate_random_challenge(&self, rng: &mut dyn CryptoRngCore) -> SigmaChallenge {
+        Scalar::random(rng)
+    }
     fn verify_response_to_challenge(
         &self,
-        prover_commitment: ProverCommitment,
-        random_challenge: VerifierChallenge,
-        prover_response_to_challenge: ProverResponse,
+        prover_commitment: Self::Commitment,
+        random_challenge: SigmaChallenge,
+        prover_response_to_challenge: Self::Response,
     ) -> bool;
     fn simulate_prover_responses(
         &self,
-        random_challenge: VerifierChallenge,
-    ) -> (ProverCommitment, ProverResponse);
+        rng: &mut dyn CryptoRngCore,
+        random_challenge: SigmaChallenge,
+    ) -> (Self::Commitment, Self::Response);
+
+    /// Like `verify_response_to_challenge`, but adds the verification equations to `batch`
+    /// instead of checking them, cf. `batch`; returns false if a check that cannot be batched
+    /// fails right away. Protocols without linear equations to batch verify everything at once.
+    fn batch_verify_response_to_challenge(
+        &self,
+        _batch: &mut BatchVerifier,
+        prover_commitment: Self::Commitment,
+        random_challenge: SigmaChallenge,
+        prover_response_to_challenge: Self::Response,
+    ) -> bool {
+        self.verify_response_to_challenge(
+            prover_commitment,
+            random_challenge,
+            prover_response_to_challenge,
+        )
+    }
+
+    /// the commitment that `prover_response_to_challenge` answers `random_challenge` for, so
+    /// that signatures can carry the challenge instead of the commitment, cf.
+    /// `FiatShamir::verify_compact`; None if the protocol cannot recompute it, or if the response
+    /// is malformed
+    fn recompute_commitment(
+        &self,
+        _random_challenge: SigmaChallenge,
+        _prover_response_to_challenge: Self::Response,
+    ) -> Option<Self::Commitment> {
+        None
+    }
+
+    /// switches to challenges of `challenge_length`; only compositions that split challenges,
+    /// cf. `or_proof`, need to know, and pass it on to their parts
+    fn set_challenge_length(&mut self, _challenge_length: ChallengeLength) {}
+
+    /// proves both this statement and `other`, cf. `and_proof`
+    fn and<Other: SigmaProtocol>(self, other: Other) -> AndProof<Self, Other>
+    where
+        Self: Sized,
+    {
+        AndProof {
+            s0: self,
+            s1: other,
+        }
+    }
+
+    /// proves this statement or `other`, without revealing which, cf. `or_proof`
+    fn or<Other: SigmaProtocol>(self, other: Other) -> OrProof<Self, Other>
+    where
+        Self: Sized,
+    {
+        OrProof {
+            s0: self,
+            s1: other,
+            challenge_length: ChallengeLength::Full,
+        }
+    }
+
+    /// the signature proof of knowledge for the statement, cf. `fiat_shamir`
+    fn fiat_shamir(self) -> FiatShamir<Self>
+    where
+        Self: Sized,
+    {
+        FiatShamir::from_sigma_protocol(self)
+    }
+}
+
+/// a type-erased protocol, e.g. for heterogeneous lists of statements, that can be shared across
+/// threads
+pub type DynSigmaProtocol<Witness, Statement, Commitment, Response, ProverState> = Box<
+    dyn SigmaProtocol<
+            Witness = Witness,
+            Statement = Statement,
+            Commitment = Commitment,
+            Response = Response,
+            ProverState = ProverState,
+        > + Send
+        + Sync,
+>;
+
+// Boxed protocols, e.g. for heterogeneous lists of statements, compose like any other.
+impl<T: SigmaProtocol + ?Sized> SigmaProtocol for Box<T> {
+    type Witness = T::Witness;
+    type Statement = T::Statement;
+    type Commitment = T::Commitment;
+    type Response = T::Response;
+    type ProverState = T::ProverState;
+
+    fn generate_commitment(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        witness: Self::Witness,
+    ) -> Result<(Self::Commitment, Self::ProverState), PokError> {
+        self.as_ref().generate_commitment(rng, witness)
+    }
+    fn serialize_commitment(&self, commitment: &Self::Commitment, buf: &mut Vec<u8>) {
+        self.as_ref().serialize_commitment(commitment, buf)
+    }
+    fn serialize_witness_statement(&self) -> Vec<u8> {
+        self.as_ref().serialize_witness_statement()
+    }
+    fn protocol_label(&self) -> Vec<u8> {
+        self.as_ref().protocol_label()
+    }
+    fn write_protocol_label(&self, buf: &mut Vec<u8>) {
+        self.as_ref().write_protocol_label(buf)
+    }
+    fn write_witness_statement(&self, buf: &mut Vec<u8>) {
+        self.as_ref().write_witness_statement(buf)
+    }
+    fn generate_response_to_challenge(
+        &self,
+        prover_state: Self::ProverState,
+        random_challenge: SigmaChallenge,
+    ) -> Self::Response {
+        self.as_ref()
+            .generate_response_to_challenge(prover_state, random_challenge)
+    }
+    fn generate_random_challenge(&self, rng: &mut dyn CryptoRngCore) -> SigmaChallenge {
+        self.as_ref().generate_random_challenge(rng)
+    }
+    fn verify_response_to_challenge(
+        &self,
+        prover_commitment: Self::Commitment,
+        random_challenge: SigmaChallenge,
+        prover_response_to_challenge: Self::Response,
+    ) -> bool {
+        self.as_ref().verify_response_to_challenge(
+            prover_commitment,
+            random_challenge,
+            prover_response_to_challenge,
+        )
+    }
+    fn simulate_prover_responses(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        random_challenge: SigmaChallenge,
+    ) -> (Self::Commitment, Self::Response) {
+        self.as_ref()
+            .simulate_prover_responses(rng, random_challenge)
+    }
+    fn batch_verify_response_to_challenge(
+        &self,
+        batch: &mut BatchVerifier,
+        prover_commitment: Self::Commitment,
+        random_challenge: SigmaChallenge,
+        prover_response_to_challenge: Self::Response,
+    ) -> bool {
+        self.as_ref().batch_verify_response_to_challenge(
+            batch,
+            prover_commitment,
+            random_challenge,
+            prover_response_to_challenge,
+        )
+    }
+    fn recompute_commitment(
+        &self,
+        random_challenge: SigmaChallenge,
+        prover_response_to_challenge: Self::Response,
+    ) -> Option<Self::Commitment> {
+        self.as_ref()
+            .recompute_commitment(random_challenge, prover_response_to_challenge)
+    }
+    fn set_challenge_length(&mut self, challenge_length: ChallengeLength) {
+        self.as_mut().set_challenge_length(challenge_length)
+    }
+}
+
+/// a generator with a precomputed table for fixed-base scalar multiplication, which is several
+/// times faster than multiplying the point itself; without the `precomputed-tables` feature, the
+/// point itself
+#[derive(Clone)]
+pub enum Generator {
+    /// the Ristretto basepoint, with the table that comes with curve25519-dalek
+    Basepoint,
+    /// any other generator, with a table computed once and shared between clones
+    #[cfg(feature = "precomputed-tables")]
+    Custom(Arc<RistrettoBasepointTable>),
+    /// any other generator
+    #[cfg(not(feature = "precomputed-tables"))]
+    Custom(RistrettoPoint),
+}
+
+impl Generator {
+    /// precomputes the table for `point`, unless it is the Ristretto basepoint
+    pub fn new(point: &RistrettoPoint) -> Self {
+        if *point == RISTRETTO_BASEPOINT_POINT {
+            return Generator::Basepoint;
+        }
+        #[cfg(feature = "precomputed-tables")]
+        return Generator::Custom(Arc::new(RistrettoBasepointTable::create(point)));
+        #[cfg(not(feature = "precomputed-tables"))]
+        return Generator::Custom(*point);
+    }
+
+    pub fn point(&self) -> RistrettoPoint {
+        match self {
+            Generator::Basepoint => RISTRETTO_BASEPOINT_POINT,
+            #[cfg(feature = "precomputed-tables")]
+            Generator::Custom(table) => table.basepoint(),
+            #[cfg(not(feature = "precomputed-tables"))]
+            Generator::Custom(point) => *point,
+        }
+    }
+
+    /// computes `scalar * g` in constant time, cf. `scalar_blinding`
+    pub fn mul(&self, scalar: &Scalar) -> RistrettoPoint {
+        match self {
+            Generator::Basepoint => scalar_blinding::mul_base(scalar),
+            #[cfg(feature = "precomputed-tables")]
+            Generator::Custom(table) => scalar_blinding::mul_table(scalar, table),
+            #[cfg(not(feature = "precomputed-tables"))]
+            Generator::Custom(point) => scalar_blinding::mul(scalar, point),
+        }
+    }
 }
 
 //
 // Generic structs that capture Schnorr and Chaum-Pedersen proofs.
 //
-#[derive(Clone, Copy)]
-pub struct GenericSigmaProver<Witness, WitnessStatement, PerVerifierSecret> {
+#[derive(Clone)]
+pub struct GenericSigmaProtocol<WitnessStatement> {
     /// g is the Ristretto basepoint/generator
-    pub g: RistrettoPoint,
-    /// denoted by (alpha_1,...,alpha_n) in Section 19.5.3 in [BS0.5]
-    pub witness: Option<Witness>,
+    pub g: Generator,
     /// denoted by phi in Section 19.5.3 in [BS0.5]
     pub witness_statement: WitnessStatement,
-    /// stores the secret generated for the verifier to create the commitment;
+}
+/// the state between the commitment and the response of a `GenericSigmaProtocol`
+pub struct GenericSigmaProverState<Witness, PerVerifierSecret> {
+    /// denoted by (alpha_1,...,alpha_n) in Section 19.5.3 in [BS0.5]
+    pub witness: Witness,
+    /// the secret generated for the verifier to create the commitment;
     /// denoted by alpha_tj in Section 19.5.3 in [BS0.5]
-    pub per_verifier_secret: Option<PerVerifierSecret>,
+    pub per_verifier_secret: PerVerifierSecret,
 }
-#[derive(Clone, Copy)]
-pub struct GenericSigmaVerifier<WitnessStatement> {
-    /// g is the Ristretto basepoint/generator
-    pub g: RistrettoPoint,
-    /// denoted by phi in Section 19.5.3 in [BS0.5]
-    pub witness_statement: WitnessStatement,
+
+/// Prints placeholders for the secrets, so that they cannot leak through logs.
+impl<Witness, PerVerifierSecret> fmt::Debug
+    for GenericSigmaProverState<Witness, PerVerifierSecret>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GenericSigmaProverState")
+            .field("witness", &format_args!("<redacted>"))
+            .field("per_verifier_secret", &format_args!("<redacted>"))
+            .finish()
+    }
 }
 
-impl<Witness, WitnessStatement, PerVerifierSecret>
-    GenericSigmaProver<Witness, WitnessStatement, PerVerifierSecret>
+impl<Witness: Zeroize, PerVerifierSecret: Zeroize>
+    GenericSigmaProverState<Witness, PerVerifierSecret>
 {
+    /// Computes the response from the witness and the per verifier secret, then clears both, so
+    /// that they do not outlive the response in memory. Scalars are `Copy`, so this can only clear
+    /// the state itself; the callers in this crate do not copy the secrets elsewhere.
+    pub fn respond<Response>(
+        mut self,
+        respond: impl FnOnce(&Witness, &PerVerifierSecret) -> Response,
+    ) -> Response {
+        let response = respond(&self.witness, &self.per_verifier_secret);
+        self.witness.zeroize();
+        self.per_verifier_secret.zeroize();
+        response
+    }
+}
+
+impl<WitnessStatement> GenericSigmaProtocol<WitnessStatement> {
     pub fn new(witness_statement: WitnessStatement) -> Self {
-        let g = RistrettoBasepointTable::basepoint(&RISTRETTO_BASEPOINT_TABLE);
+        Self::with_generator(Generator::Basepoint, witness_statement)
+    }
+
+    pub fn with_generator(g: Generator, witness_statement: WitnessStatement) -> Self {
         Self {
             g,
-            witness: None,
             witness_statement,
-            per_verifier_secret: None,
         }
     }
 }
 
-impl<WitnessStatement> GenericSigmaVerifier<WitnessStatement> {
-    pub fn new(witness_statement: WitnessStatement) -> Self {
-        let g = RistrettoBasepointTable::basepoint(&RISTRETTO_BASEPOINT_TABLE);
-        Self {
-            g,
-            witness_statement,
+/// Recomputes the commitment `z * g - c * y` from the response `z` and the challenge `c` for the
+/// statement `y = x * g`, in variable time. Verification only handles public values, so this is
+/// safe there, and much faster than separate multiplications; never use it on secrets.
+pub(crate) fn vartime_recompute_commitment(
+    g: &RistrettoPoint,
+    y: &RistrettoPoint,
+    z: &Scalar,
+    c: &Scalar,
+) -> RistrettoPoint {
+    let minus_c = -c;
+    profiling::record_multiscalar_mul(2);
+    if *g == RISTRETTO_BASEPOINT_POINT {
+        RistrettoPoint::vartime_double_scalar_mul_basepoint(&minus_c, y, z)
+    } else {
+        RistrettoPoint::vartime_multiscalar_mul([z, &minus_c], [g, y])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vartime_recompute_commitment() {
+        let mut rng = rand::thread_rng();
+        let y = RistrettoPoint::random(&mut rng);
+        let z = Scalar::random(&mut rng);
+        let c = Scalar::random(&mut rng);
+
+        // 0. With the basepoint, and with any other generator
+        for g in [RISTRETTO_BASEPOINT_POINT, RistrettoPoint::random(&mut rng)] {
+            assert_eq!(vartime_recompute_commitment(&g, &y, &z, &c), z * g - c * y);
+        }
+    }
+
+    #[test]
+    fn test_prover_state_respond() {
+        // A witness that records what it held when it was cleared
+        struct Witness<'a>(Scalar, &'a std::cell::Cell<Option<Scalar>>);
+        impl Zeroize for Witness<'_> {
+            fn zeroize(&mut self) {
+                self.0.zeroize();
+                self.1.set(Some(self.0));
+            }
         }
+
+        // 0. The response is computed from the state, and the witness is cleared after it
+        let cleared = std::cell::Cell::new(None);
+        let prover_state = GenericSigmaProverState {
+            witness: Witness(Scalar::from(3u8), &cleared),
+            per_verifier_secret: Scalar::from(5u8),
+        };
+        let response = prover_state.respond(|witness, per_verifier_secret| {
+            assert_eq!(cleared.get(), None);
+            per_verifier_secret + witness.0 * Scalar::from(7u8)
+        });
+        assert_eq!(response, Scalar::from(26u8));
+        assert_eq!(cleared.get(), Some(Scalar::ZERO));
+    }
+
+    #[test]
+    fn test_secret_debug_redacted() {
+        use crate::pok::{
+            fiat_shamir::FiatShamirSecretKey, or_proof::OrWitness, schnorr::SchnorrProof,
+        };
+
+        let mut rng = rand::thread_rng();
+        let witness = Scalar::random(&mut rng);
+        let schnorr = SchnorrProof::new(RistrettoPoint::mul_base(&witness));
+        let leaks = |debug: String| debug.contains(&format!("{:?}", witness));
+
+        // 0. Neither the prover state of a proof nor a Fiat-Shamir secret key print the witness
+        let (_, prover_state) = schnorr.generate_commitment(&mut rng, witness).unwrap();
+        let debug = format!("{:?}", prover_state);
+        assert!(debug.contains("<redacted>"));
+        assert!(!leaks(debug));
+        let (_, or_prover_state) = schnorr
+            .clone()
+            .or(schnorr)
+            .generate_commitment(&mut rng, OrWitness::Left(witness))
+            .unwrap();
+        assert!(!leaks(format!("{:?}", or_prover_state)));
+        assert!(!leaks(format!("{:?}", FiatShamirSecretKey { witness })));
+    }
+
+    #[test]
+    fn test_challenge_length() {
+        let mut rng = rand::thread_rng();
+
+        for (challenge_length, len) in [
+            (ChallengeLength::Bits128, 16),
+            (ChallengeLength::Bits192, 24),
+        ] {
+            // 0. Random and truncated challenges are short
+            let a = challenge_length.random(&mut rng);
+            let b = challenge_length.truncate(Scalar::random(&mut rng));
+            assert!(a.as_bytes()[len..].iter().all(|&byte| byte == 0));
+            assert!(b.as_bytes()[len..].iter().all(|&byte| byte == 0));
+
+            // 1. Addition and subtraction wrap around 2^(8 * len), and undo each other
+            assert_eq!(challenge_length.sub(challenge_length.add(a, b), b), a);
+            assert_eq!(challenge_length.add(challenge_length.sub(a, b), b), a);
+            let minus_one = challenge_length.sub(Scalar::ZERO, Scalar::ONE);
+            assert!(minus_one.as_bytes()[..len].iter().all(|&byte| byte == 0xff));
+            assert_eq!(challenge_length.add(minus_one, Scalar::ONE), Scalar::ZERO);
+        }
+
+        // 2. Full challenges are plain scalars
+        let a = Scalar::random(&mut rng);
+        assert_eq!(ChallengeLength::Full.truncate(a), a);
+        assert_eq!(ChallengeLength::Full.sub(Scalar::ZERO, a), -a);
+    }
+
+    #[test]
+    fn test_generator() {
+        let mut rng = rand::thread_rng();
+        let scalar = Scalar::random(&mut rng);
+
+        // 0. The basepoint uses the built-in table
+        let basepoint = Generator::new(&RISTRETTO_BASEPOINT_POINT);
+        assert!(matches!(basepoint, Generator::Basepoint));
+        assert_eq!(basepoint.mul(&scalar), scalar * RISTRETTO_BASEPOINT_POINT);
+
+        // 1. Any other generator gets its own table
+        let point = RistrettoPoint::random(&mut rng);
+        let custom = Generator::new(&point);
+        assert!(matches!(custom, Generator::Custom(_)));
+        assert_eq!(custom.point(), point);
+        assert_eq!(custom.mul(&scalar), scalar * point);
     }
 }
@@ -1,11 +1,40 @@
-pub(crate) mod linear_sigma;
+pub mod error;
+pub mod linear_sigma;
 
-pub(crate) mod chaum_pedersen;
-pub(crate) mod schnorr;
+pub mod define_proof;
 
-pub(crate) mod and_proof;
-pub(crate) mod or_proof;
+pub mod chaum_pedersen;
+pub mod dleq;
+pub mod schnorr;
+pub mod schnorr_signature;
 
-pub(crate) mod fiat_shamir;
+pub mod and_proof;
+pub mod nary_or_proof;
+pub mod or_proof;
+pub mod range_proof;
+pub mod threshold_proof;
+
+pub mod batch;
+pub mod domain;
+pub mod dvs;
+pub mod elgamal;
+pub mod fiat_shamir;
+pub mod musig;
+pub mod profiling;
+pub mod scalar_blinding;
+#[cfg(any(feature = "rand", test))]
+pub mod shuffle;
+#[cfg(feature = "std")]
+pub mod transcript;
+pub mod vrf;
+pub mod vss;
+
+#[cfg(feature = "compressed_sigma")]
+pub mod compressed_sigma;
+
+pub mod serialized_size;
+
+#[cfg(any(all(test, feature = "serde"), feature = "testing"))]
+pub mod testing;
 
 pub(crate) mod test_macros;
@@ -0,0 +1,315 @@
+//! Two-Round Schnorr Multisignatures
+//!
+//! MuSig2 [NRS21] over Ristretto: n signers aggregate their public keys X_i into one key
+//! X = a_1 * X_1 + ... + a_n * X_n, with coefficients a_i = H(L, X_i) over the list L of all keys,
+//! which defeats rogue-key attacks. To sign, every signer publishes two nonces (R_i1, R_i2); the
+//! aggregate nonce is R = R_1 + b * R_2 for the sums R_1, R_2 and b = H(X, R_1, R_2, m), and signer
+//! i answers the challenge c with s_i = r_i1 + b * r_i2 + c * a_i * x_i.
+//!
+//! The aggregate signature (R, s_1 + ... + s_n) is a `V2` Fiat-Shamir Schnorr signature for X, so
+//! anyone can verify it with `SchnorrProof::new(X).fiat_shamir()` without knowing it was co-signed.
+//!
+//! Nonces must never be reused: a `MusigSecretNonce` is consumed by `partial_sign`.
+//!
+//! [NRS21]: https://eprint.iacr.org/2020/1261
+
+use alloc::vec::Vec;
+
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar, traits::VartimeMultiscalarMul};
+use rand_core::CryptoRngCore;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
+
+use crate::pok::{
+    domain,
+    error::PokError,
+    fiat_shamir::{FiatShamir, FiatShamirSignature},
+    linear_sigma::SigmaProtocol,
+    schnorr::SchnorrProof,
+};
+
+/// the aggregate signature, a Schnorr signature (R, s) for the aggregate public key
+pub type MusigSignature = FiatShamirSignature<RistrettoPoint, Scalar>;
+
+/// signer i's share s_i of the aggregate signature
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MusigPartialSignature(pub(crate) Scalar);
+
+/// the nonces (r_i1, r_i2) a signer keeps between the two rounds
+pub struct MusigSecretNonce {
+    r1: Scalar,
+    r2: Scalar,
+}
+
+/// the nonces (R_i1, R_i2) a signer publishes in the first round, or their sums
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MusigPublicNonce {
+    pub(crate) r1: RistrettoPoint,
+    pub(crate) r2: RistrettoPoint,
+}
+
+/// The public keys of the signers, and their aggregate.
+#[derive(Debug, Clone)]
+pub struct MusigKeyAggregation {
+    pub public_keys: Vec<RistrettoPoint>,
+    /// a_i, one coefficient per public key
+    coefficients: Vec<Scalar>,
+    /// X, the key the aggregate signature verifies under
+    pub aggregate_public_key: RistrettoPoint,
+}
+
+impl MusigKeyAggregation {
+    pub fn new(public_keys: Vec<RistrettoPoint>) -> Self {
+        let mut key_list = Vec::with_capacity(32 * public_keys.len());
+        for public_key in &public_keys {
+            key_list.extend(public_key.compress().as_bytes());
+        }
+        let coefficients: Vec<Scalar> = public_keys
+            .iter()
+            .map(|public_key| {
+                let mut hasher = Sha512::new();
+                domain::absorb(&mut hasher, domain::MUSIG_KEY_AGGREGATION_LABEL);
+                domain::absorb(&mut hasher, &key_list);
+                domain::absorb(&mut hasher, public_key.compress().as_bytes());
+                Scalar::from_hash(hasher)
+            })
+            .collect();
+        let aggregate_public_key =
+            RistrettoPoint::vartime_multiscalar_mul(&coefficients, &public_keys);
+        MusigKeyAggregation {
+            public_keys,
+            coefficients,
+            aggregate_public_key,
+        }
+    }
+
+    /// a_i for `public_key`, if it is one of the signers'
+    fn coefficient(&self, public_key: &RistrettoPoint) -> Option<Scalar> {
+        self.public_keys
+            .iter()
+            .position(|key| key == public_key)
+            .map(|i| self.coefficients[i])
+    }
+
+    /// the signature scheme the aggregate signatures verify under
+    pub fn fiat_shamir(&self) -> FiatShamir<SchnorrProof> {
+        SchnorrProof::new(self.aggregate_public_key).fiat_shamir()
+    }
+
+    /// b and c, the nonce coefficient and the Schnorr challenge, and the aggregate nonce R
+    fn session(
+        &self,
+        aggregate_nonce: &MusigPublicNonce,
+        message: &[u8],
+    ) -> (Scalar, Scalar, RistrettoPoint) {
+        let mut hasher = Sha512::new();
+        domain::absorb(&mut hasher, domain::MUSIG_NONCE_LABEL);
+        domain::absorb(&mut hasher, self.aggregate_public_key.compress().as_bytes());
+        domain::absorb(&mut hasher, aggregate_nonce.r1.compress().as_bytes());
+        domain::absorb(&mut hasher, aggregate_nonce.r2.compress().as_bytes());
+        domain::absorb(&mut hasher, message);
+        let b = Scalar::from_hash(hasher);
+
+        let r = aggregate_nonce.r1 + b * aggregate_nonce.r2;
+        let c = self
+            .fiat_shamir()
+            .hash_message_and_commitment_to_scalar(message, &r);
+        (b, c, r)
+    }
+}
+
+/// The first round: picks fresh nonces, keeps the secret ones, and returns the public ones to send
+/// to the other signers.
+pub fn generate_nonce(rng: &mut dyn CryptoRngCore) -> (MusigSecretNonce, MusigPublicNonce) {
+    let r1 = Scalar::random(rng);
+    let r2 = Scalar::random(rng);
+    (
+        MusigSecretNonce { r1, r2 },
+        MusigPublicNonce {
+            r1: RistrettoPoint::mul_base(&r1),
+            r2: RistrettoPoint::mul_base(&r2),
+        },
+    )
+}
+
+/// Sums the public nonces of all signers.
+pub fn aggregate_nonces(public_nonces: &[MusigPublicNonce]) -> MusigPublicNonce {
+    MusigPublicNonce {
+        r1: public_nonces.iter().map(|nonce| nonce.r1).sum(),
+        r2: public_nonces.iter().map(|nonce| nonce.r2).sum(),
+    }
+}
+
+/// The second round: signs `message` with the signer's secret key and nonce; fails if the
+/// signer's public key is not among the aggregated ones.
+pub fn partial_sign(
+    key_aggregation: &MusigKeyAggregation,
+    secret_key: &Scalar,
+    secret_nonce: MusigSecretNonce,
+    aggregate_nonce: &MusigPublicNonce,
+    message: &[u8],
+) -> Result<MusigPartialSignature, PokError> {
+    let public_key = RistrettoPoint::mul_base(secret_key);
+    let a = key_aggregation
+        .coefficient(&public_key)
+        .ok_or(PokError::UnknownSigner)?;
+    let (b, c, _) = key_aggregation.session(aggregate_nonce, message);
+    Ok(MusigPartialSignature(
+        secret_nonce.r1 + b * secret_nonce.r2 + c * a * secret_key,
+    ))
+}
+
+/// Checks one signer's share, to identify who spoiled an aggregate signature that does not
+/// verify.
+pub fn partial_verify(
+    key_aggregation: &MusigKeyAggregation,
+    public_key: &RistrettoPoint,
+    public_nonce: &MusigPublicNonce,
+    aggregate_nonce: &MusigPublicNonce,
+    message: &[u8],
+    partial_signature: &MusigPartialSignature,
+) -> bool {
+    let Some(a) = key_aggregation.coefficient(public_key) else {
+        return false;
+    };
+    let (b, c, _) = key_aggregation.session(aggregate_nonce, message);
+    // s_i * g == R_i1 + b * R_i2 + c * a_i * X_i
+    RistrettoPoint::mul_base(&partial_signature.0)
+        == public_nonce.r1
+            + RistrettoPoint::vartime_multiscalar_mul([b, c * a], [public_nonce.r2, *public_key])
+}
+
+/// Sums the shares of all signers into the aggregate signature.
+pub fn aggregate_partial_signatures(
+    key_aggregation: &MusigKeyAggregation,
+    aggregate_nonce: &MusigPublicNonce,
+    message: &[u8],
+    partial_signatures: &[MusigPartialSignature],
+) -> MusigSignature {
+    let (_, _, r) = key_aggregation.session(aggregate_nonce, message);
+    FiatShamirSignature {
+        prover_commitment: r,
+        prover_response: partial_signatures.iter().map(|partial| partial.0).sum(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pok::fiat_shamir::SignatureScheme;
+
+    use super::*;
+
+    #[test]
+    fn test_musig() {
+        let mut rng = rand::thread_rng();
+        let message = b"hello world!";
+
+        // 0. Three signers aggregate their keys
+        let secret_keys: Vec<Scalar> = (0..3).map(|_| Scalar::random(&mut rng)).collect();
+        let public_keys: Vec<RistrettoPoint> =
+            secret_keys.iter().map(RistrettoPoint::mul_base).collect();
+        let key_aggregation = MusigKeyAggregation::new(public_keys.clone());
+
+        // 1. The first round: every signer publishes its nonces
+        let (secret_nonces, public_nonces): (Vec<_>, Vec<_>) =
+            (0..3).map(|_| generate_nonce(&mut rng)).unzip();
+        let aggregate_nonce = aggregate_nonces(&public_nonces);
+
+        // 2. The second round: every signer signs, and the shares check out
+        let partial_signatures: Vec<MusigPartialSignature> = secret_keys
+            .iter()
+            .zip(secret_nonces)
+            .map(|(secret_key, secret_nonce)| {
+                partial_sign(
+                    &key_aggregation,
+                    secret_key,
+                    secret_nonce,
+                    &aggregate_nonce,
+                    message,
+                )
+                .unwrap()
+            })
+            .collect();
+        for ((public_key, public_nonce), partial_signature) in public_keys
+            .iter()
+            .zip(&public_nonces)
+            .zip(&partial_signatures)
+        {
+            assert!(partial_verify(
+                &key_aggregation,
+                public_key,
+                public_nonce,
+                &aggregate_nonce,
+                message,
+                partial_signature,
+            ));
+        }
+
+        // 3. The aggregate is an ordinary Schnorr signature for the aggregate key
+        let signature = aggregate_partial_signatures(
+            &key_aggregation,
+            &aggregate_nonce,
+            message,
+            &partial_signatures,
+        );
+        assert!(key_aggregation.fiat_shamir().verify(message, signature));
+        assert!(!key_aggregation
+            .fiat_shamir()
+            .verify(b"goodbye world!", signature));
+
+        // 4. A spoiled share is caught, and so is the aggregate it ends up in
+        let spoiled = MusigPartialSignature(partial_signatures[1].0 + Scalar::ONE);
+        assert!(!partial_verify(
+            &key_aggregation,
+            &public_keys[1],
+            &public_nonces[1],
+            &aggregate_nonce,
+            message,
+            &spoiled,
+        ));
+        let signature = aggregate_partial_signatures(
+            &key_aggregation,
+            &aggregate_nonce,
+            message,
+            &[partial_signatures[0], spoiled, partial_signatures[2]],
+        );
+        assert!(!key_aggregation.fiat_shamir().verify(message, signature));
+    }
+
+    #[test]
+    fn test_musig_key_aggregation() {
+        let mut rng = rand::thread_rng();
+        let public_keys: Vec<RistrettoPoint> =
+            (0..3).map(|_| RistrettoPoint::random(&mut rng)).collect();
+
+        // 0. The aggregate key is not simply the sum, so that no signer can cancel out the others
+        let key_aggregation = MusigKeyAggregation::new(public_keys.clone());
+        assert_ne!(
+            key_aggregation.aggregate_public_key,
+            public_keys.iter().sum::<RistrettoPoint>()
+        );
+
+        // 1. It depends on the order of the keys
+        let reversed = MusigKeyAggregation::new(public_keys.iter().rev().copied().collect());
+        assert_ne!(
+            key_aggregation.aggregate_public_key,
+            reversed.aggregate_public_key
+        );
+
+        // 2. Outsiders cannot sign
+        let (secret_nonce, public_nonce) = generate_nonce(&mut rng);
+        assert_eq!(
+            partial_sign(
+                &key_aggregation,
+                &Scalar::random(&mut rng),
+                secret_nonce,
+                &public_nonce,
+                b"hello world!",
+            ),
+            Err(PokError::UnknownSigner)
+        );
+    }
+}
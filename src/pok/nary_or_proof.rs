@@ -0,0 +1,370 @@
+//! Sigma Protocol for The OR of n Sigma Protocols.
+//!
+//! Generalizes `or_proof` from two to n statements of the same type, cf. [CDS94] and Exercise
+//! 19.17 in [BS0.5]. The prover runs the real protocol for the one statement it has a witness for,
+//! and simulates the other n-1 with challenges of its choosing; the challenges must sum to the
+//! verifier's challenge. The sum is taken modulo the group order whatever the `ChallengeLength`,
+//! which is only passed on to the statements.
+//!
+//! [BS0.5]: https://crypto.stanford.edu/~dabo/cryptobook/BonehShoup_0_5.pdf
+//! [CDS94]: https://link.springer.com/content/pdf/10.1007/3-540-48658-5_19.pdf
+
+use alloc::{vec, vec::Vec};
+use core::fmt;
+
+use curve25519_dalek::scalar::Scalar;
+use rand_core::CryptoRngCore;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use subtle::Choice;
+
+use crate::pok::{
+    domain,
+    error::PokError,
+    linear_sigma::{ChallengeLength, DynSigmaProtocol, SigmaProtocol},
+};
+
+/// the prover knows a witness for the statement at `index`
+#[derive(Clone, Copy)]
+pub struct NaryOrWitness<Witness> {
+    pub index: usize,
+    pub witness: Witness,
+}
+
+/// the statement the witness is used to prove, (R_0,...,R_{n-1})
+pub type NaryOrWitnessStatement<WitnessStatement> = Vec<WitnessStatement>;
+
+/// the prover's commitment, (t_0,...,t_{n-1})
+pub type NaryOrProverCommitment<ProverCommitment> = Vec<ProverCommitment>;
+
+/// the verifier's challenge, denoted by c
+pub type NaryOrVerifierChallenge = Scalar;
+
+/// the prover's response, (c_0,...,c_{n-2},z_0,...,z_{n-1}),
+/// where c_{n-1} = c - c_0 - ... - c_{n-2}
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NaryOrProverResponse<ProverResponse> {
+    pub(crate) challenges: Vec<NaryOrVerifierChallenge>,
+    pub(crate) responses: Vec<ProverResponse>,
+}
+
+/// the prover's state between its commitment and its response: the state of the statement at
+/// `index`, and the challenges and responses of the simulated statements
+pub struct NaryOrProverState<ProverState, ProverResponse> {
+    pub index: usize,
+    pub state: ProverState,
+    pub challenges: Vec<NaryOrVerifierChallenge>,
+    pub simulated_responses: Vec<Option<ProverResponse>>,
+}
+
+/// Prints a placeholder, since `index` reveals which statement the prover knows a witness for.
+impl<ProverState, ProverResponse> fmt::Debug for NaryOrProverState<ProverState, ProverResponse> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("NaryOrProverState")
+            .field(&format_args!("<redacted>"))
+            .finish()
+    }
+}
+
+pub struct NaryOrProof<Witness, Statement, Commitment, Response, ProverState> {
+    pub protocols: Vec<DynSigmaProtocol<Witness, Statement, Commitment, Response, ProverState>>,
+}
+
+/// Derives the challenge for the last statement from the others, so that they sum to
+/// `random_challenge`.
+fn last_challenge(
+    challenges: &[NaryOrVerifierChallenge],
+    random_challenge: NaryOrVerifierChallenge,
+) -> NaryOrVerifierChallenge {
+    random_challenge - challenges.iter().sum::<Scalar>()
+}
+
+impl<Witness, Statement, Commitment, Response, ProverState> SigmaProtocol
+    for NaryOrProof<Witness, Statement, Commitment, Response, ProverState>
+{
+    type Witness = NaryOrWitness<Witness>;
+    type Statement = NaryOrWitnessStatement<Statement>;
+    type Commitment = NaryOrProverCommitment<Commitment>;
+    type Response = NaryOrProverResponse<Response>;
+    type ProverState = NaryOrProverState<ProverState, Response>;
+
+    fn generate_commitment(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        witness: NaryOrWitness<Witness>,
+    ) -> Result<(Self::Commitment, Self::ProverState), PokError> {
+        let n = self.protocols.len();
+        if witness.index >= n {
+            return Err(PokError::WitnessIndexOutOfRange {
+                index: witness.index,
+                len: n,
+            });
+        }
+
+        // Commit to the statement we have a witness for
+        let (real_commitment, state) =
+            self.protocols[witness.index].generate_commitment(rng, witness.witness)?;
+
+        // Simulate every other statement, and remember the simulated challenges and responses
+        // for later
+        let mut challenges = vec![Scalar::ZERO; n];
+        let mut simulated_responses = Vec::with_capacity(n);
+        let mut commitments = Vec::with_capacity(n);
+        for (i, protocol) in self.protocols.iter().enumerate() {
+            if i == witness.index {
+                simulated_responses.push(None);
+                continue;
+            }
+            challenges[i] = Scalar::random(rng);
+            let (simulated_commitment, simulated_response) =
+                protocol.simulate_prover_responses(rng, challenges[i]);
+            commitments.push(simulated_commitment);
+            simulated_responses.push(Some(simulated_response));
+        }
+        commitments.insert(witness.index, real_commitment);
+        Ok((
+            commitments,
+            NaryOrProverState {
+                index: witness.index,
+                state,
+                challenges,
+                simulated_responses,
+            },
+        ))
+    }
+
+    fn serialize_commitment(&self, commitment: &Self::Commitment, buf: &mut Vec<u8>) {
+        for (protocol, commitment) in self.protocols.iter().zip(commitment) {
+            protocol.serialize_commitment(commitment, buf);
+        }
+    }
+
+    fn protocol_label(&self) -> Vec<u8> {
+        domain::composite_label(
+            domain::NARY_OR_LABEL,
+            self.protocols
+                .iter()
+                .map(|protocol| protocol.protocol_label()),
+        )
+    }
+
+    fn serialize_witness_statement(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        for protocol in &self.protocols {
+            buf.extend(protocol.serialize_witness_statement());
+        }
+        buf
+    }
+
+    fn generate_response_to_challenge(
+        &self,
+        prover_state: Self::ProverState,
+        random_challenge: NaryOrVerifierChallenge,
+    ) -> Self::Response {
+        let NaryOrProverState {
+            index,
+            state,
+            mut challenges,
+            simulated_responses,
+        } = prover_state;
+
+        // The real challenge is whatever makes all challenges sum to the verifier's
+        let simulated_sum: Scalar = challenges.iter().sum();
+        let real_challenge = random_challenge - simulated_sum;
+        challenges[index] = real_challenge;
+
+        let mut real_state = Some(state);
+        let responses = simulated_responses
+            .into_iter()
+            .zip(&self.protocols)
+            .map(|(simulated_response, protocol)| match simulated_response {
+                Some(simulated_response) => simulated_response,
+                None => protocol
+                    .generate_response_to_challenge(real_state.take().unwrap(), real_challenge),
+            })
+            .collect();
+        challenges.pop();
+        NaryOrProverResponse {
+            challenges,
+            responses,
+        }
+    }
+
+    fn verify_response_to_challenge(
+        &self,
+        prover_commitment: Self::Commitment,
+        random_challenge: NaryOrVerifierChallenge,
+        prover_response_to_challenge: Self::Response,
+    ) -> bool {
+        let n = self.protocols.len();
+        let NaryOrProverResponse {
+            mut challenges,
+            responses,
+        } = prover_response_to_challenge;
+        if n == 0
+            || prover_commitment.len() != n
+            || challenges.len() != n - 1
+            || responses.len() != n
+        {
+            return false;
+        }
+        challenges.push(last_challenge(&challenges, random_challenge));
+
+        self.protocols
+            .iter()
+            .zip(prover_commitment)
+            .zip(challenges)
+            .zip(responses)
+            .fold(
+                Choice::from(1),
+                |verified, (((protocol, commitment), challenge), response)| {
+                    let result =
+                        protocol.verify_response_to_challenge(commitment, challenge, response);
+                    verified & Choice::from(u8::from(result))
+                },
+            )
+            .into()
+    }
+
+    fn simulate_prover_responses(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        random_challenge: NaryOrVerifierChallenge,
+    ) -> (Self::Commitment, Self::Response) {
+        let n = self.protocols.len();
+        let mut challenges: Vec<Scalar> = (1..n).map(|_| Scalar::random(rng)).collect();
+        challenges.push(last_challenge(&challenges, random_challenge));
+
+        let (commitments, responses) = self
+            .protocols
+            .iter()
+            .zip(&challenges)
+            .map(|(protocol, challenge)| protocol.simulate_prover_responses(rng, *challenge))
+            .unzip();
+        challenges.pop();
+        (
+            commitments,
+            NaryOrProverResponse {
+                challenges,
+                responses,
+            },
+        )
+    }
+
+    fn set_challenge_length(&mut self, challenge_length: ChallengeLength) {
+        for protocol in &mut self.protocols {
+            protocol.set_challenge_length(challenge_length);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use curve25519_dalek::{constants::RISTRETTO_BASEPOINT_POINT, ristretto::RistrettoPoint};
+
+    use crate::pok::{
+        chaum_pedersen::{ChaumPedersenProof, ChaumPedersenWitnessStatement},
+        schnorr::SchnorrProof,
+        test_macros::test_sigma_protocol,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_nary_or_schnorr() {
+        let mut rng = rand::thread_rng();
+        let g = RISTRETTO_BASEPOINT_POINT;
+
+        for n in 1..5 {
+            // 0. Fix n witnesses, and define the statements to prove
+            let witnesses: Vec<Scalar> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+            let witness_statements: Vec<RistrettoPoint> =
+                witnesses.iter().map(|witness| witness * g).collect();
+
+            for (index, witness) in witnesses.iter().enumerate() {
+                // 1. Create an n-ary OR proof from Schnorr proofs
+                let or_proof = NaryOrProof {
+                    protocols: witness_statements
+                        .iter()
+                        .map(|statement| {
+                            Box::new(SchnorrProof::new(*statement))
+                                as DynSigmaProtocol<_, _, _, _, _>
+                        })
+                        .collect(),
+                };
+
+                // 2. Run tests with the protocol, knowing only the witness at index
+                test_sigma_protocol!(
+                    NaryOrWitness {
+                        index,
+                        witness: *witness,
+                    },
+                    or_proof
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_nary_or_chaum_pedersen() {
+        let mut rng = rand::thread_rng();
+        let g = RISTRETTO_BASEPOINT_POINT;
+
+        // 0. Fix one witness, and hide its statement among random ones
+        let witness = Scalar::random(&mut rng);
+        let witness_statements: Vec<ChaumPedersenWitnessStatement> = (0..3)
+            .map(|i| {
+                let u = RistrettoPoint::random(&mut rng);
+                if i == 1 {
+                    ChaumPedersenWitnessStatement {
+                        u,
+                        v: witness * g,
+                        w: witness * u,
+                    }
+                } else {
+                    ChaumPedersenWitnessStatement {
+                        u,
+                        v: RistrettoPoint::random(&mut rng),
+                        w: RistrettoPoint::random(&mut rng),
+                    }
+                }
+            })
+            .collect();
+
+        // 1. Create an n-ary OR proof from Chaum-Pedersen proofs
+        let or_proof = NaryOrProof {
+            protocols: witness_statements
+                .iter()
+                .map(|statement| {
+                    Box::new(ChaumPedersenProof::new(*statement)) as DynSigmaProtocol<_, _, _, _, _>
+                })
+                .collect(),
+        };
+
+        // 2. Run tests with the protocol
+        test_sigma_protocol!(NaryOrWitness { index: 1, witness }, or_proof);
+
+        // 3. Responses whose challenges do not sum to the verifier's challenge are rejected
+        let (prover_commitment, prover_state) = or_proof
+            .generate_commitment(&mut rng, NaryOrWitness { index: 1, witness })
+            .unwrap();
+        let random_challenge = or_proof.generate_random_challenge(&mut rng);
+        let mut prover_response =
+            or_proof.generate_response_to_challenge(prover_state, random_challenge);
+        prover_response.challenges[0] += Scalar::ONE;
+        assert!(!or_proof.verify_response_to_challenge(
+            prover_commitment,
+            random_challenge,
+            prover_response,
+        ));
+
+        // 4. A witness index past the last statement is an error, not a panic
+        assert_eq!(
+            or_proof
+                .generate_commitment(&mut rng, NaryOrWitness { index: 3, witness })
+                .err(),
+            Some(PokError::WitnessIndexOutOfRange { index: 3, len: 3 })
+        );
+    }
+}
@@ -5,18 +5,30 @@
 //! [BS0.5]: https://crypto.stanford.edu/~dabo/cryptobook/BonehShoup_0_5.pdf
 //! [CS97]: https://crypto.ethz.ch/publications/files/CamSta97b.pdf
 
-use curve25519_dalek::scalar::Scalar;
-
-use crate::pok::linear_sigma::{SigmaProver, SigmaVerifier};
+use alloc::vec::Vec;
+use core::fmt;
 
-/// if b == 0 / false: then the prover knows a witness (s0_witness) for R0
-/// if b == 1 / true : then the prover knows a witness (s1_witness) for R1
-/// cf. Section 19.7.2 in [BS0.5]
-#[derive(Clone, Copy, Default)]
-pub struct OrWitness<S0Witness, S1Witness> {
-    pub b: bool,
-    pub s0_witness: Option<S0Witness>,
-    pub s1_witness: Option<S1Witness>,
+use curve25519_dalek::scalar::Scalar;
+use rand_core::CryptoRngCore;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use subtle::{Choice, ConstantTimeEq};
+
+use crate::pok::{
+    batch::BatchVerifier,
+    domain,
+    error::PokError,
+    linear_sigma::{ChallengeLength, SigmaProtocol},
+};
+
+/// the witness for one of the two statements; `Left` is b == 0 and `Right` is b == 1 in
+/// Section 19.7.2 in [BS0.5]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OrWitness<S0Witness, S1Witness> {
+    /// the prover knows a witness for R0
+    Left(S0Witness),
+    /// the prover knows a witness for R1
+    Right(S1Witness),
 }
 
 /// the statement the witness is used to prove, denoted by (R_0,R_1) in Section 19.7.2 in [BS0.5]
@@ -32,293 +44,231 @@ pub type OrVerifierChallenge = Scalar;
 
 /// the prover's response, denoted by (c_0,z_0,z_1) in Section 19.7.2 of [BS0.5]
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OrProverResponse<S0ProverResponse, S1ProverResponse> {
     pub(crate) c_0: OrVerifierChallenge,
     pub(crate) z_0: S0ProverResponse,
     pub(crate) z_1: S1ProverResponse,
 }
 
-/// the per verifier secret, denoted by c_d and z_d in Section 19.7.2 of [BS0.5]
-#[derive(Default)]
-pub struct OrPerVerifierSecret<S0ProverResponse, S1ProverResponse> {
-    pub s0_challenge: Option<OrVerifierChallenge>,
-    pub s1_challenge: Option<OrVerifierChallenge>,
-    pub s0_prover_response: Option<S0ProverResponse>,
-    pub s1_prover_response: Option<S1ProverResponse>,
+impl<S0ProverResponse: Copy, S1ProverResponse: Copy>
+    OrProverResponse<S0ProverResponse, S1ProverResponse>
+{
+    /// Builds a response from its parts, e.g. to rebuild a decoded proof; `verify` checks them.
+    pub fn new(c_0: OrVerifierChallenge, z_0: S0ProverResponse, z_1: S1ProverResponse) -> Self {
+        OrProverResponse { c_0, z_0, z_1 }
+    }
+
+    /// The challenge of the first branch; that of the second is c_0 + c in the challenge space, for
+    /// the verifier's challenge c.
+    pub fn c_0(&self) -> OrVerifierChallenge {
+        self.c_0
+    }
+
+    /// The response of the first branch.
+    pub fn z_0(&self) -> S0ProverResponse {
+        self.z_0
+    }
+
+    /// The response of the second branch.
+    pub fn z_1(&self) -> S1ProverResponse {
+        self.z_1
+    }
 }
 
-pub struct OrProver<
-    S0Witness,
-    S0WitnessStatement,
-    S0ProverCommitment,
-    S0ProverResponse,
-    S1Witness,
-    S1WitnessStatement,
-    S1ProverCommitment,
-    S1ProverResponse,
-> {
-    pub s0_prover: Box<
-        dyn SigmaProver<
-            S0Witness,
-            S0WitnessStatement,
-            S0ProverCommitment,
-            OrVerifierChallenge,
-            S0ProverResponse,
-        >,
-    >,
-    pub s0_verifier: Box<
-        dyn SigmaVerifier<
-            S0Witness,
-            S0WitnessStatement,
-            S0ProverCommitment,
-            OrVerifierChallenge,
-            S0ProverResponse,
-        >,
-    >,
-    pub s1_prover: Box<
-        dyn SigmaProver<
-            S1Witness,
-            S1WitnessStatement,
-            S1ProverCommitment,
-            OrVerifierChallenge,
-            S1ProverResponse,
-        >,
-    >,
-    pub s1_verifier: Box<
-        dyn SigmaVerifier<
-            S1Witness,
-            S1WitnessStatement,
-            S1ProverCommitment,
-            OrVerifierChallenge,
-            S1ProverResponse,
-        >,
-    >,
-    pub witness: Option<OrWitness<S0Witness, S1Witness>>,
-    pub per_verifier_secret: Option<OrPerVerifierSecret<S0ProverResponse, S1ProverResponse>>,
+/// the prover's state between its commitment and its response: the state of the branch it has a
+/// witness for, and the simulated challenge and response, denoted by c_d and z_d in Section 19.7.2
+/// of [BS0.5], of the other branch
+pub enum OrProverState<S0ProverState, S1ProverState, S0ProverResponse, S1ProverResponse> {
+    S0 {
+        s0_state: S0ProverState,
+        s1_challenge: OrVerifierChallenge,
+        s1_prover_response: S1ProverResponse,
+    },
+    S1 {
+        s1_state: S1ProverState,
+        s0_challenge: OrVerifierChallenge,
+        s0_prover_response: S0ProverResponse,
+    },
 }
 
-impl<
-        S0Witness,
-        S0WitnessStatement,
-        S0ProverCommitment,
-        S0ProverResponse,
-        S1Witness,
-        S1WitnessStatement,
-        S1ProverCommitment,
-        S1ProverResponse,
-    >
-    SigmaProver<
-        OrWitness<S0Witness, S1Witness>,
-        OrWitnessStatement<S0WitnessStatement, S1WitnessStatement>,
-        OrProverCommitment<S0ProverCommitment, S1ProverCommitment>,
-        OrVerifierChallenge,
-        OrProverResponse<S0ProverResponse, S1ProverResponse>,
-    >
-    for OrProver<
-        S0Witness,
-        S0WitnessStatement,
-        S0ProverCommitment,
-        S0ProverResponse,
-        S1Witness,
-        S1WitnessStatement,
-        S1ProverCommitment,
-        S1ProverResponse,
-    >
-where
-    S0Witness: Copy,
-    S1Witness: Copy,
-    S0ProverResponse: Default + Copy,
-    S1ProverResponse: Default + Copy,
+/// Prints a placeholder: even the variant would reveal which statement the prover knows a witness
+/// for.
+impl<S0ProverState, S1ProverState, S0ProverResponse, S1ProverResponse> fmt::Debug
+    for OrProverState<S0ProverState, S1ProverState, S0ProverResponse, S1ProverResponse>
 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("OrProverState")
+            .field(&format_args!("<redacted>"))
+            .finish()
+    }
+}
+
+pub struct OrProof<S0, S1> {
+    pub s0: S0,
+    pub s1: S1,
+    /// the space the challenges are split in, cf. `ChallengeLength`
+    pub challenge_length: ChallengeLength,
+}
+
+impl<S0: SigmaProtocol, S1: SigmaProtocol> SigmaProtocol for OrProof<S0, S1> {
+    type Witness = OrWitness<S0::Witness, S1::Witness>;
+    type Statement = OrWitnessStatement<S0::Statement, S1::Statement>;
+    type Commitment = OrProverCommitment<S0::Commitment, S1::Commitment>;
+    type Response = OrProverResponse<S0::Response, S1::Response>;
+    type ProverState = OrProverState<S0::ProverState, S1::ProverState, S0::Response, S1::Response>;
+
     fn generate_commitment(
-        &mut self,
-        witness: OrWitness<S0Witness, S1Witness>,
-    ) -> OrProverCommitment<S0ProverCommitment, S1ProverCommitment> {
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        witness: Self::Witness,
+    ) -> Result<(Self::Commitment, Self::ProverState), PokError> {
         // This is a bit complicated, see Section 19.7.2 of [BS0.5]
-
-        // Store the witness
-        self.witness = Some(witness);
-        if !self.witness.unwrap().b {
-            // Generate a valid commitment for R0 using the witness
-            let s0_commitment = self
-                .s0_prover
-                .as_mut()
-                .generate_commitment(self.witness.unwrap().s0_witness.unwrap());
-            // Simulate a commitment for R1
-            let s1_challenge = self.s1_verifier.as_mut().generate_random_challenge();
-            let (s1_simulated_commitment, s1_simulated_response) = self
-                .s1_verifier
-                .as_ref()
-                .simulate_prover_responses(s1_challenge);
-            // Store the simulated extra values for future use
-            self.per_verifier_secret = Some(OrPerVerifierSecret {
-                s0_challenge: None,
-                s1_challenge: Some(s1_challenge),
-                s0_prover_response: None,
-                s1_prover_response: Some(s1_simulated_response),
-            });
-            (s0_commitment, s1_simulated_commitment)
-        } else {
-            // Simulate a commitment for R0
-            let s0_challenge = self.s0_verifier.as_mut().generate_random_challenge();
-            let (s0_simulated_commitment, s0_simulated_response) = self
-                .s0_verifier
-                .as_ref()
-                .simulate_prover_responses(s0_challenge);
-            // Store the simulated extra values for future use
-            self.per_verifier_secret = Some(OrPerVerifierSecret {
-                s0_challenge: Some(s0_challenge),
-                s1_challenge: None,
-                s0_prover_response: Some(s0_simulated_response),
-                s1_prover_response: None,
-            });
-            // Generate a valid commitment for R1 using the witness
-            let s1_commitment = self
-                .s1_prover
-                .as_mut()
-                .generate_commitment(self.witness.unwrap().s1_witness.unwrap());
-            (s0_simulated_commitment, s1_commitment)
+        match witness {
+            OrWitness::Left(s0_witness) => {
+                // Generate a valid commitment for R0 using the witness
+                let (s0_commitment, s0_state) = self.s0.generate_commitment(rng, s0_witness)?;
+                // Simulate a commitment for R1
+                let s1_challenge = self.challenge_length.random(rng);
+                let (s1_simulated_commitment, s1_simulated_response) =
+                    self.s1.simulate_prover_responses(rng, s1_challenge);
+                // Keep the simulated values for the response
+                Ok((
+                    (s0_commitment, s1_simulated_commitment),
+                    OrProverState::S0 {
+                        s0_state,
+                        s1_challenge,
+                        s1_prover_response: s1_simulated_response,
+                    },
+                ))
+            }
+            OrWitness::Right(s1_witness) => {
+                // Simulate a commitment for R0
+                let s0_challenge = self.challenge_length.random(rng);
+                let (s0_simulated_commitment, s0_simulated_response) =
+                    self.s0.simulate_prover_responses(rng, s0_challenge);
+                // Generate a valid commitment for R1 using the witness
+                let (s1_commitment, s1_state) = self.s1.generate_commitment(rng, s1_witness)?;
+                // Keep the simulated values for the response
+                Ok((
+                    (s0_simulated_commitment, s1_commitment),
+                    OrProverState::S1 {
+                        s1_state,
+                        s0_challenge,
+                        s0_prover_response: s0_simulated_response,
+                    },
+                ))
+            }
         }
     }
 
-    fn serialize_commitment(
-        &self,
-        commitment: &OrProverCommitment<S0ProverCommitment, S1ProverCommitment>,
-    ) -> Vec<u8> {
+    fn serialize_commitment(&self, commitment: &Self::Commitment, buf: &mut Vec<u8>) {
+        self.s0.serialize_commitment(&commitment.0, buf);
+        self.s1.serialize_commitment(&commitment.1, buf);
+    }
+
+    fn protocol_label(&self) -> Vec<u8> {
+        let mut label = Vec::new();
+        self.write_protocol_label(&mut label);
+        label
+    }
+
+    fn write_protocol_label(&self, buf: &mut Vec<u8>) {
+        domain::write_composite_label(buf, domain::OR_LABEL, 2, |i, buf| match i {
+            0 => self.s0.write_protocol_label(buf),
+            _ => self.s1.write_protocol_label(buf),
+        });
+    }
+
+    fn serialize_witness_statement(&self) -> Vec<u8> {
         let mut buf = Vec::new();
-        buf.extend(self.s0_prover.as_ref().serialize_commitment(&commitment.0));
-        buf.extend(self.s1_prover.as_ref().serialize_commitment(&commitment.1));
+        self.write_witness_statement(&mut buf);
         buf
     }
 
+    fn write_witness_statement(&self, buf: &mut Vec<u8>) {
+        self.s0.write_witness_statement(buf);
+        self.s1.write_witness_statement(buf);
+    }
+
     fn generate_response_to_challenge(
-        &mut self,
+        &self,
+        prover_state: Self::ProverState,
         random_challenge: OrVerifierChallenge,
-    ) -> OrProverResponse<S0ProverResponse, S1ProverResponse> {
+    ) -> Self::Response {
         // This is a bit complicated, see Section 19.7.2 of [BS0.5]. We use scalar arithmetic
         // instead of XOR, as per [CS97].
-        let per_verifier_secret = self.per_verifier_secret.as_ref().unwrap();
         // We consistently let c₁ = c₀ + chal
-        if !self.witness.unwrap().b {
-            let c_0 = per_verifier_secret.s1_challenge.unwrap() - random_challenge;
-            let z_0 = self.s0_prover.as_mut().generate_response_to_challenge(c_0);
-            OrProverResponse {
-                c_0,
-                z_0,
-                z_1: per_verifier_secret.s1_prover_response.unwrap(),
+        match prover_state {
+            OrProverState::S0 {
+                s0_state,
+                s1_challenge,
+                s1_prover_response,
+            } => {
+                let c_0 = self.challenge_length.sub(s1_challenge, random_challenge);
+                let z_0 = self.s0.generate_response_to_challenge(s0_state, c_0);
+                OrProverResponse {
+                    c_0,
+                    z_0,
+                    z_1: s1_prover_response,
+                }
             }
-        } else {
-            let c_1 = per_verifier_secret.s0_challenge.unwrap() + random_challenge;
-            let z_1 = self.s1_prover.as_mut().generate_response_to_challenge(c_1);
-            OrProverResponse {
-                c_0: per_verifier_secret.s0_challenge.unwrap(),
-                z_0: per_verifier_secret.s0_prover_response.unwrap(),
-                z_1,
+            OrProverState::S1 {
+                s1_state,
+                s0_challenge,
+                s0_prover_response,
+            } => {
+                let c_1 = self.challenge_length.add(s0_challenge, random_challenge);
+                let z_1 = self.s1.generate_response_to_challenge(s1_state, c_1);
+                OrProverResponse {
+                    c_0: s0_challenge,
+                    z_0: s0_prover_response,
+                    z_1,
+                }
             }
         }
     }
-}
-
-pub struct OrVerifier<
-    S0Witness,
-    S0WitnessStatement,
-    S0ProverCommitment,
-    S0ProverResponse,
-    S1Witness,
-    S1WitnessStatement,
-    S1ProverCommitment,
-    S1ProverResponse,
-> {
-    pub s0_verifier: Box<
-        dyn SigmaVerifier<
-            S0Witness,
-            S0WitnessStatement,
-            S0ProverCommitment,
-            OrVerifierChallenge,
-            S0ProverResponse,
-        >,
-    >,
-    pub s1_verifier: Box<
-        dyn SigmaVerifier<
-            S1Witness,
-            S1WitnessStatement,
-            S1ProverCommitment,
-            OrVerifierChallenge,
-            S1ProverResponse,
-        >,
-    >,
-}
-
-impl<
-        S0Witness,
-        S0WitnessStatement,
-        S0ProverCommitment,
-        S0ProverResponse,
-        S1Witness,
-        S1WitnessStatement,
-        S1ProverCommitment,
-        S1ProverResponse,
-    >
-    SigmaVerifier<
-        OrWitness<S0Witness, S1Witness>,
-        OrWitnessStatement<S0WitnessStatement, S1WitnessStatement>,
-        OrProverCommitment<S0ProverCommitment, S1ProverCommitment>,
-        OrVerifierChallenge,
-        OrProverResponse<S0ProverResponse, S1ProverResponse>,
-    >
-    for OrVerifier<
-        S0Witness,
-        S0WitnessStatement,
-        S0ProverCommitment,
-        S0ProverResponse,
-        S1Witness,
-        S1WitnessStatement,
-        S1ProverCommitment,
-        S1ProverResponse,
-    >
-{
-    fn generate_random_challenge(&mut self) -> OrVerifierChallenge {
-        let mut rng = rand::thread_rng();
-        Scalar::random(&mut rng)
-    }
 
     fn verify_response_to_challenge(
         &self,
-        prover_commitment: OrProverCommitment<S0ProverCommitment, S1ProverCommitment>,
+        prover_commitment: Self::Commitment,
         random_challenge: OrVerifierChallenge,
-        prover_response_to_challenge: OrProverResponse<S0ProverResponse, S1ProverResponse>,
+        prover_response_to_challenge: Self::Response,
     ) -> bool {
         // This is a bit complicated, see Section 19.7.2 of [BS0.5]. We use scalar arithmetic
         // instead of XOR, as per [CS97].
-        let c_1 = prover_response_to_challenge.c_0 + random_challenge;
-        let s0_verification_result = self.s0_verifier.as_ref().verify_response_to_challenge(
+        // c_0 must be a challenge itself, so that it has one encoding
+        let c_0 = prover_response_to_challenge.c_0;
+        let c_0_is_challenge = self.challenge_length.truncate(c_0).ct_eq(&c_0);
+        let c_1 = self.challenge_length.add(c_0, random_challenge);
+        let s0_verification_result = self.s0.verify_response_to_challenge(
             prover_commitment.0,
             prover_response_to_challenge.c_0,
             prover_response_to_challenge.z_0,
         );
-        let s1_verification_result = self.s1_verifier.as_ref().verify_response_to_challenge(
+        let s1_verification_result = self.s1.verify_response_to_challenge(
             prover_commitment.1,
             c_1,
             prover_response_to_challenge.z_1,
         );
-        s0_verification_result && s1_verification_result
+        (c_0_is_challenge
+            & Choice::from(u8::from(s0_verification_result))
+            & Choice::from(u8::from(s1_verification_result)))
+        .into()
     }
 
     fn simulate_prover_responses(
         &self,
+        rng: &mut dyn CryptoRngCore,
         random_challenge: OrVerifierChallenge,
-    ) -> (
-        OrProverCommitment<S0ProverCommitment, S1ProverCommitment>,
-        OrProverResponse<S0ProverResponse, S1ProverResponse>,
-    ) {
+    ) -> (Self::Commitment, Self::Response) {
         // This is a bit complicated, see Section 19.7.2 of [BS0.5]. We use scalar arithmetic
         // instead of XOR, as per [CS97].
-        let mut rng = rand::thread_rng();
-        let c_0 = Scalar::random(&mut rng);
-        let c_1 = c_0 + random_challenge;
+        let c_0 = self.challenge_length.random(rng);
+        let c_1 = self.challenge_length.add(c_0, random_challenge);
 
-        let (s0_commitment, s0_response) = self.s0_verifier.as_ref().simulate_prover_responses(c_0);
-        let (s1_commitment, s1_response) = self.s1_verifier.as_ref().simulate_prover_responses(c_1);
+        let (s0_commitment, s0_response) = self.s0.simulate_prover_responses(rng, c_0);
+        let (s1_commitment, s1_response) = self.s1.simulate_prover_responses(rng, c_1);
         (
             (s0_commitment, s1_commitment),
             OrProverResponse {
@@ -328,20 +278,73 @@ impl<
             },
         )
     }
+
+    fn batch_verify_response_to_challenge(
+        &self,
+        batch: &mut BatchVerifier,
+        prover_commitment: Self::Commitment,
+        random_challenge: OrVerifierChallenge,
+        prover_response_to_challenge: Self::Response,
+    ) -> bool {
+        // as in `verify_response_to_challenge`; only the branches' equations are batched
+        let c_0 = prover_response_to_challenge.c_0;
+        let c_0_is_challenge = self.challenge_length.truncate(c_0).ct_eq(&c_0);
+        let c_1 = self.challenge_length.add(c_0, random_challenge);
+        let s0_verification_result = self.s0.batch_verify_response_to_challenge(
+            batch,
+            prover_commitment.0,
+            c_0,
+            prover_response_to_challenge.z_0,
+        );
+        let s1_verification_result = self.s1.batch_verify_response_to_challenge(
+            batch,
+            prover_commitment.1,
+            c_1,
+            prover_response_to_challenge.z_1,
+        );
+        (c_0_is_challenge
+            & Choice::from(u8::from(s0_verification_result))
+            & Choice::from(u8::from(s1_verification_result)))
+        .into()
+    }
+
+    fn generate_random_challenge(&self, rng: &mut dyn CryptoRngCore) -> OrVerifierChallenge {
+        self.challenge_length.random(rng)
+    }
+
+    fn recompute_commitment(
+        &self,
+        random_challenge: OrVerifierChallenge,
+        prover_response_to_challenge: Self::Response,
+    ) -> Option<Self::Commitment> {
+        // as in `verify_response_to_challenge`, each branch from its share of the challenge
+        let c_0 = prover_response_to_challenge.c_0;
+        if !bool::from(self.challenge_length.truncate(c_0).ct_eq(&c_0)) {
+            return None;
+        }
+        let c_1 = self.challenge_length.add(c_0, random_challenge);
+        Some((
+            self.s0
+                .recompute_commitment(c_0, prover_response_to_challenge.z_0)?,
+            self.s1
+                .recompute_commitment(c_1, prover_response_to_challenge.z_1)?,
+        ))
+    }
+
+    fn set_challenge_length(&mut self, challenge_length: ChallengeLength) {
+        self.challenge_length = challenge_length;
+        self.s0.set_challenge_length(challenge_length);
+        self.s1.set_challenge_length(challenge_length);
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use curve25519_dalek::{
-        constants::RISTRETTO_BASEPOINT_TABLE,
-        ristretto::{RistrettoBasepointTable, RistrettoPoint},
-    };
+    use curve25519_dalek::{constants::RISTRETTO_BASEPOINT_POINT, ristretto::RistrettoPoint};
 
     use crate::pok::{
-        chaum_pedersen::{
-            ChaumPedersenProver, ChaumPedersenVerifier, ChaumPedersenWitnessStatement,
-        },
-        schnorr::{SchnorrProver, SchnorrVerifier},
+        chaum_pedersen::{ChaumPedersenProof, ChaumPedersenWitnessStatement},
+        schnorr::SchnorrProof,
         test_macros::test_sigma_protocol,
     };
 
@@ -350,7 +353,7 @@ mod tests {
     #[test]
     fn test_schnorr_and_schnorr() {
         let mut rng = rand::thread_rng();
-        let g = RistrettoBasepointTable::basepoint(&RISTRETTO_BASEPOINT_TABLE);
+        let g = RISTRETTO_BASEPOINT_POINT;
 
         // 0. Fix two witnesses, and define the statements to prove
         let witness0 = Scalar::random(&mut rng);
@@ -358,53 +361,22 @@ mod tests {
         let witness1 = Scalar::random(&mut rng);
         let witness1_statement = witness1 * g;
 
-        // 1. Initialize Schnorr provers and verifiers with the respective statements to prove
-        let s0_prover = SchnorrProver::new(witness0_statement);
-        let s1_prover = SchnorrProver::new(witness1_statement);
-        let s0_verifier = SchnorrVerifier::new(witness0_statement);
-        let s1_verifier = SchnorrVerifier::new(witness1_statement);
-
-        // 2. Create an OR prover using the two schnorr provers
-        let mut or_prover = OrProver {
-            s0_prover: Box::new(s0_prover),
-            s0_verifier: Box::new(s0_verifier),
-            s1_prover: Box::new(s1_prover),
-            s1_verifier: Box::new(s1_verifier),
-            witness: None,
-            per_verifier_secret: None,
-        };
-
-        // 3. Create an OR verifier using the two schnorr verifiers
-        let mut or_verifier = OrVerifier {
-            s0_verifier: Box::new(s0_verifier),
-            s1_verifier: Box::new(s1_verifier),
-        };
-
-        // 4. Run tests with the verifier and prover
-        test_sigma_protocol!(
-            OrWitness {
-                b: false,
-                s0_witness: Some(witness0),
-                s1_witness: None,
-            },
-            or_verifier,
-            or_prover
-        );
-        test_sigma_protocol!(
-            OrWitness {
-                b: true,
-                s0_witness: None,
-                s1_witness: Some(witness1),
-            },
-            or_verifier,
-            or_prover
-        );
+        // 1. Initialize Schnorr's protocol with the respective statements to prove
+        let s0 = SchnorrProof::new(witness0_statement);
+        let s1 = SchnorrProof::new(witness1_statement);
+
+        // 2. Create an OR proof from the two proofs
+        let or_proof = s0.or(s1);
+
+        // 3. Run tests with the protocol, knowing either witness
+        test_sigma_protocol!(OrWitness::Left(witness0), or_proof);
+        test_sigma_protocol!(OrWitness::Right(witness1), or_proof);
     }
 
     #[test]
     fn test_schnorr_and_chaum_pedersen() {
         let mut rng = rand::thread_rng();
-        let g = RistrettoBasepointTable::basepoint(&RISTRETTO_BASEPOINT_TABLE);
+        let g = RISTRETTO_BASEPOINT_POINT;
 
         // 0. Fix two witnesses, and define the statements to prove
         let witness0 = Scalar::random(&mut rng);
@@ -415,46 +387,15 @@ mod tests {
         let w = witness1 * u;
         let witness1_statement = ChaumPedersenWitnessStatement { u, v, w };
 
-        // 1. Initialize Schnorr provers and verifiers with the respective statements to prove
-        let s0_prover = SchnorrProver::new(witness0_statement);
-        let s1_prover = ChaumPedersenProver::new(witness1_statement);
-        let s0_verifier = SchnorrVerifier::new(witness0_statement);
-        let s1_verifier = ChaumPedersenVerifier::new(witness1_statement);
-
-        // 2. Create an OR prover using the two schnorr provers
-        let mut or_prover = OrProver {
-            s0_prover: Box::new(s0_prover),
-            s0_verifier: Box::new(s0_verifier),
-            s1_prover: Box::new(s1_prover),
-            s1_verifier: Box::new(s1_verifier),
-            witness: None,
-            per_verifier_secret: None,
-        };
-
-        // 3. Create an OR verifier using the two schnorr verifiers
-        let mut or_verifier = OrVerifier {
-            s0_verifier: Box::new(s0_verifier),
-            s1_verifier: Box::new(s1_verifier),
-        };
-
-        // 4. Run tests with the verifier and prover
-        test_sigma_protocol!(
-            OrWitness {
-                b: false,
-                s0_witness: Some(witness0),
-                s1_witness: None,
-            },
-            or_verifier,
-            or_prover
-        );
-        test_sigma_protocol!(
-            OrWitness {
-                b: true,
-                s0_witness: None,
-                s1_witness: Some(witness1),
-            },
-            or_verifier,
-            or_prover
-        );
+        // 1. Initialize Schnorr and Chaum-Pedersen with the respective statements to prove
+        let s0 = SchnorrProof::new(witness0_statement);
+        let s1 = ChaumPedersenProof::new(witness1_statement);
+
+        // 2. Create an OR proof from the two proofs
+        let or_proof = s0.or(s1);
+
+        // 3. Run tests with the protocol, knowing either witness
+        test_sigma_protocol!(OrWitness::Left(witness0), or_proof);
+        test_sigma_protocol!(OrWitness::Right(witness1), or_proof);
     }
 }
@@ -0,0 +1,222 @@
+//! Counting Group Operations
+//!
+//! With the `profiling` feature, the group operations of the provers and verifiers of `schnorr`
+//! and `chaum_pedersen`, of `BatchVerifier`, of ElGamal encryption and decryption, and of the AMF
+//! wire format (`amf::codec`) are counted per thread: scalar multiplications (including each
+//! fixed-base one), multiscalar multiplications and their terms, point additions and subtractions
+//! outside of these, and point compressions and decompressions. Optimizations such as multiscalar
+//! multiplication or precomputed tables then show up as exact changes in the counts, which tests
+//! can pin down. `profile` counts the operations of a closure:
+//!
+//! ```
+//! # #[cfg(feature = "profiling")] {
+//! use amaze::{amf, pok::profiling};
+//!
+//! let (sender_public_key, sender_secret_key) = amf::keygen(amf::AMFRole::Sender);
+//! let (recipient_public_key, _) = amf::keygen(amf::AMFRole::Recipient);
+//! let (judge_public_key, _) = amf::keygen(amf::AMFRole::Judge);
+//! let (_, counts) = profiling::profile(|| {
+//!     amf::frank(sender_secret_key, sender_public_key, recipient_public_key, judge_public_key,
+//!         b"hello world!")
+//! });
+//! assert!(counts.scalar_muls > 0);
+//! # }
+//! ```
+//!
+//! Without the feature, nothing is counted, and the counts stay zero. Operations on other threads,
+//! e.g. the commitments that frank computes on rayon's threads with the `parallel` feature, are
+//! not counted for the calling thread.
+
+#[cfg(feature = "profiling")]
+use core::cell::Cell;
+use core::ops::{Add, Sub};
+
+/// whether the crate was built with the `profiling` feature
+pub const ENABLED: bool = cfg!(feature = "profiling");
+
+/// Counts of group operations, cf. the module documentation.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct OpCounts {
+    /// single scalar multiplications, with a table or not
+    pub scalar_muls: u64,
+    /// multiscalar multiplications, including double-base ones
+    pub multiscalar_muls: u64,
+    /// the terms of all multiscalar multiplications
+    pub multiscalar_terms: u64,
+    /// additions and subtractions of points
+    pub point_additions: u64,
+    pub compressions: u64,
+    pub decompressions: u64,
+}
+
+impl Add for OpCounts {
+    type Output = OpCounts;
+
+    fn add(self, other: OpCounts) -> OpCounts {
+        OpCounts {
+            scalar_muls: self.scalar_muls + other.scalar_muls,
+            multiscalar_muls: self.multiscalar_muls + other.multiscalar_muls,
+            multiscalar_terms: self.multiscalar_terms + other.multiscalar_terms,
+            point_additions: self.point_additions + other.point_additions,
+            compressions: self.compressions + other.compressions,
+            decompressions: self.decompressions + other.decompressions,
+        }
+    }
+}
+
+impl Sub for OpCounts {
+    type Output = OpCounts;
+
+    fn sub(self, other: OpCounts) -> OpCounts {
+        OpCounts {
+            scalar_muls: self.scalar_muls - other.scalar_muls,
+            multiscalar_muls: self.multiscalar_muls - other.multiscalar_muls,
+            multiscalar_terms: self.multiscalar_terms - other.multiscalar_terms,
+            point_additions: self.point_additions - other.point_additions,
+            compressions: self.compressions - other.compressions,
+            decompressions: self.decompressions - other.decompressions,
+        }
+    }
+}
+
+#[cfg(feature = "profiling")]
+std::thread_local! {
+    static COUNTS: Cell<OpCounts> = const {
+        Cell::new(OpCounts {
+            scalar_muls: 0,
+            multiscalar_muls: 0,
+            multiscalar_terms: 0,
+            point_additions: 0,
+            compressions: 0,
+            decompressions: 0,
+        })
+    };
+}
+
+/// the operations the current thread counted so far
+#[cfg(feature = "profiling")]
+pub fn counts() -> OpCounts {
+    COUNTS.with(Cell::get)
+}
+
+/// the operations the current thread counted so far, none without the feature
+#[cfg(not(feature = "profiling"))]
+pub fn counts() -> OpCounts {
+    OpCounts::default()
+}
+
+/// runs `f`, and returns its result with the operations it counted on the current thread
+pub fn profile<T>(f: impl FnOnce() -> T) -> (T, OpCounts) {
+    let before = counts();
+    let result = f();
+    (result, counts() - before)
+}
+
+/// adds to the counts of the current thread, if `ENABLED`
+pub(crate) fn record(update: impl FnOnce(&mut OpCounts)) {
+    #[cfg(not(feature = "profiling"))]
+    let _ = update;
+    #[cfg(feature = "profiling")]
+    COUNTS.with(|counts| {
+        let mut updated = counts.get();
+        update(&mut updated);
+        counts.set(updated);
+    });
+}
+
+/// records one multiscalar multiplication of `terms` terms
+pub(crate) fn record_multiscalar_mul(terms: usize) {
+    record(|counts| {
+        counts.multiscalar_muls += 1;
+        counts.multiscalar_terms += terms as u64;
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        amf::{self, keygen, AMFRole, AMFSignature},
+        pok::scalar_blinding,
+    };
+
+    #[test]
+    fn test_amf_op_counts() {
+        // 0. Initialize a Sender, Recipient, and Judge
+        let (sender_public_key, sender_secret_key) = keygen(AMFRole::Sender);
+        let (recipient_public_key, recipient_secret_key) = keygen(AMFRole::Recipient);
+        let (judge_public_key, _) = keygen(AMFRole::Judge);
+        let message = b"hello world!";
+
+        // 1. Count the operations of franking, verifying, and a round trip through the wire format
+        let (amf_signature, frank_counts) = profile(|| {
+            amf::frank(
+                sender_secret_key,
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+            )
+        });
+        let (verified, verify_counts) = profile(|| {
+            amf::verify(
+                recipient_secret_key,
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+                amf_signature,
+            )
+        });
+        assert!(verified);
+        let (_, codec_counts) =
+            profile(|| AMFSignature::from_bytes(&amf_signature.to_bytes()).unwrap());
+
+        // 2. Without the feature, nothing is counted
+        if !ENABLED {
+            assert_eq!(frank_counts + verify_counts, OpCounts::default());
+            assert_eq!(codec_counts, OpCounts::default());
+            return;
+        }
+
+        // 3. frank encrypts twice (two multiplications and an addition each), commits to the real
+        //    Schnorr and Chaum-Pedersen proofs (one and two multiplications), and simulates the
+        //    other two Schnorr proofs (two multiplications and an addition each); blinding and
+        //    threads change these counts
+        if !scalar_blinding::ENABLED && !cfg!(feature = "parallel") {
+            assert_eq!(
+                frank_counts,
+                OpCounts {
+                    scalar_muls: 11,
+                    point_additions: 4,
+                    compressions: 11,
+                    ..OpCounts::default()
+                }
+            );
+        }
+
+        // 4. verify decrypts, and recomputes the five commitments with double-base
+        //    multiplications; both hash the six statement points and the five commitments
+        assert_eq!(
+            verify_counts,
+            OpCounts {
+                scalar_muls: 1,
+                multiscalar_muls: 5,
+                multiscalar_terms: 10,
+                point_additions: 1,
+                compressions: 11,
+                decompressions: 0,
+            }
+        );
+
+        // 5. The wire format compresses and decompresses the nine points of the signature
+        assert_eq!(
+            codec_counts,
+            OpCounts {
+                compressions: 9,
+                decompressions: 9,
+                ..OpCounts::default()
+            }
+        );
+    }
+}
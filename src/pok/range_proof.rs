@@ -0,0 +1,375 @@
+//! Range Proof for Pedersen Commitments
+//!
+//! Proves that a Pedersen commitment C = v * g + r * h opens to a value v in [0, 2^n) without
+//! revealing v or r. The prover commits to every bit b_i of v as C_i = b_i * g + r_i * h, with the
+//! r_i chosen so that the sum of 2^i r_i is r, and proves for every bit that C_i or C_i - g is a
+//! multiple of h, i.e. the OR of two Schnorr proofs with generator h, cf. `or_proof`. The verifier
+//! checks that the sum of 2^i C_i is C, so the bits open the commitment.
+//!
+//! Proofs grow linearly in n; g must be the Ristretto basepoint, and nobody may know the discrete
+//! log of h with respect to g, or they could open C to any value; `domain::derive_generator`
+//! derives such an h.
+
+use alloc::{vec, vec::Vec};
+use core::fmt;
+
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT, ristretto::RistrettoPoint, scalar::Scalar,
+    traits::VartimeMultiscalarMul,
+};
+use rand_core::CryptoRngCore;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use subtle::{Choice, ConstantTimeEq};
+
+use crate::pok::{
+    domain,
+    error::PokError,
+    linear_sigma::{Generator, SigmaProtocol},
+    or_proof::{OrProof, OrProverResponse, OrProverState, OrWitness},
+    schnorr::{SchnorrProof, SchnorrProverState},
+};
+
+/// the secret witness, the value v and the blinding r of the commitment
+#[derive(Clone, Copy)]
+pub struct RangeWitness {
+    pub value: u64,
+    pub blinding: Scalar,
+}
+
+impl RangeWitness {
+    /// computes the Pedersen commitment v * g + r * h
+    pub fn commit(&self, h: &Generator) -> RistrettoPoint {
+        RistrettoPoint::mul_base(&Scalar::from(self.value)) + h.mul(&self.blinding)
+    }
+}
+
+/// the statement the witness is used to prove, C opens to a value with at most `bits` bits
+#[derive(Clone, Copy)]
+pub struct RangeWitnessStatement {
+    pub commitment: RistrettoPoint,
+    pub bits: usize,
+}
+
+/// the prover's commitment, the bit commitments (C_0,...,C_{n-1}) and the commitments of their
+/// OR proofs
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RangeProverCommitment {
+    pub(crate) bit_commitments: Vec<RistrettoPoint>,
+    pub(crate) or_commitments: Vec<(RistrettoPoint, RistrettoPoint)>,
+}
+
+/// the verifier's challenge, denoted by c
+pub type RangeVerifierChallenge = Scalar;
+
+/// the prover's response, one OR response per bit
+pub type RangeProverResponse = Vec<OrProverResponse<Scalar, Scalar>>;
+
+/// the prover's state between its commitment and its response
+pub struct RangeProverState {
+    pub bit_commitments: Vec<RistrettoPoint>,
+    pub states: Vec<OrProverState<SchnorrProverState, SchnorrProverState, Scalar, Scalar>>,
+}
+
+/// Prints the public bit commitments, and a placeholder for the states of their proofs.
+impl fmt::Debug for RangeProverState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RangeProverState")
+            .field("bit_commitments", &self.bit_commitments)
+            .field("states", &format_args!("<redacted>"))
+            .finish()
+    }
+}
+
+/// proves that C_i or C_i - g is a multiple of h
+type BitProof = OrProof<SchnorrProof, SchnorrProof>;
+
+#[derive(Clone)]
+pub struct RangeProof {
+    /// h, the second generator of the Pedersen commitment
+    pub h: Generator,
+    pub witness_statement: RangeWitnessStatement,
+}
+
+/// 2^i as a scalar
+fn power_of_two(i: usize) -> Scalar {
+    Scalar::from(1u64 << i)
+}
+
+impl RangeProof {
+    pub fn new(h: Generator, witness_statement: RangeWitnessStatement) -> Self {
+        RangeProof {
+            h,
+            witness_statement,
+        }
+    }
+
+    fn bit_proof(&self, bit_commitment: RistrettoPoint) -> BitProof {
+        SchnorrProof::with_generator(self.h.clone(), bit_commitment).or(
+            SchnorrProof::with_generator(
+                self.h.clone(),
+                bit_commitment - RISTRETTO_BASEPOINT_POINT,
+            ),
+        )
+    }
+
+    /// whether the bit commitments open the statement's commitment
+    fn bit_commitments_sum_to_commitment(&self, bit_commitments: &[RistrettoPoint]) -> Choice {
+        let powers = (0..bit_commitments.len()).map(power_of_two);
+        RistrettoPoint::vartime_multiscalar_mul(powers, bit_commitments)
+            .ct_eq(&self.witness_statement.commitment)
+    }
+
+    /// the bit commitment C_0, chosen so that all of them sum to the statement's commitment
+    fn first_bit_commitment(&self, other_bit_commitments: &[RistrettoPoint]) -> RistrettoPoint {
+        let powers = (1..=other_bit_commitments.len()).map(power_of_two);
+        self.witness_statement.commitment
+            - RistrettoPoint::vartime_multiscalar_mul(powers, other_bit_commitments)
+    }
+}
+
+impl SigmaProtocol for RangeProof {
+    type Witness = RangeWitness;
+    type Statement = RangeWitnessStatement;
+    type Commitment = RangeProverCommitment;
+    type Response = RangeProverResponse;
+    type ProverState = RangeProverState;
+
+    fn generate_commitment(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        witness: RangeWitness,
+    ) -> Result<(RangeProverCommitment, RangeProverState), PokError> {
+        let bits = self.witness_statement.bits;
+        if !(1..=64).contains(&bits) {
+            return Err(PokError::InvalidBitLength { bits });
+        }
+        if bits < 64 && witness.value >> bits != 0 {
+            return Err(PokError::ValueOutOfRange { bits });
+        }
+
+        // Pick r_1,...,r_{n-1} at random, and r_0 so that the sum of 2^i r_i is r
+        let mut blindings: Vec<Scalar> = (0..bits).map(|_| Scalar::random(rng)).collect();
+        blindings[0] = witness.blinding
+            - (1..bits)
+                .map(|i| power_of_two(i) * blindings[i])
+                .sum::<Scalar>();
+
+        // Commit to every bit, and prove that it is 0 or 1
+        let mut bit_commitments = Vec::with_capacity(bits);
+        let mut or_commitments = Vec::with_capacity(bits);
+        let mut states = Vec::with_capacity(bits);
+        for (i, blinding) in blindings.into_iter().enumerate() {
+            let bit = (witness.value >> i) & 1 == 1;
+            let mut bit_commitment = self.h.mul(&blinding);
+            let bit_witness = if bit {
+                bit_commitment += RISTRETTO_BASEPOINT_POINT;
+                OrWitness::Right(blinding)
+            } else {
+                OrWitness::Left(blinding)
+            };
+            let (or_commitment, state) = self
+                .bit_proof(bit_commitment)
+                .generate_commitment(rng, bit_witness)?;
+            bit_commitments.push(bit_commitment);
+            or_commitments.push(or_commitment);
+            states.push(state);
+        }
+        Ok((
+            RangeProverCommitment {
+                bit_commitments: bit_commitments.clone(),
+                or_commitments,
+            },
+            RangeProverState {
+                bit_commitments,
+                states,
+            },
+        ))
+    }
+
+    fn serialize_commitment(&self, commitment: &RangeProverCommitment, buf: &mut Vec<u8>) {
+        for bit_commitment in &commitment.bit_commitments {
+            buf.extend(bit_commitment.compress().as_bytes());
+        }
+        for (t_0, t_1) in &commitment.or_commitments {
+            buf.extend(t_0.compress().as_bytes());
+            buf.extend(t_1.compress().as_bytes());
+        }
+    }
+
+    fn protocol_label(&self) -> Vec<u8> {
+        domain::RANGE_LABEL.to_vec()
+    }
+
+    fn serialize_witness_statement(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.extend(self.witness_statement.commitment.compress().as_bytes());
+        buf.extend((self.witness_statement.bits as u64).to_be_bytes());
+        buf.extend(self.h.point().compress().as_bytes());
+        buf
+    }
+
+    fn generate_response_to_challenge(
+        &self,
+        prover_state: RangeProverState,
+        random_challenge: RangeVerifierChallenge,
+    ) -> RangeProverResponse {
+        prover_state
+            .bit_commitments
+            .into_iter()
+            .zip(prover_state.states)
+            .map(|(bit_commitment, state)| {
+                self.bit_proof(bit_commitment)
+                    .generate_response_to_challenge(state, random_challenge)
+            })
+            .collect()
+    }
+
+    fn verify_response_to_challenge(
+        &self,
+        prover_commitment: RangeProverCommitment,
+        random_challenge: RangeVerifierChallenge,
+        prover_response_to_challenge: RangeProverResponse,
+    ) -> bool {
+        let bits = self.witness_statement.bits;
+        let RangeProverCommitment {
+            bit_commitments,
+            or_commitments,
+        } = prover_commitment;
+        // Only the shape of the proof is checked up front; the equations all run
+        if !(1..=64).contains(&bits)
+            || bit_commitments.len() != bits
+            || or_commitments.len() != bits
+            || prover_response_to_challenge.len() != bits
+        {
+            return false;
+        }
+        let sums_to_commitment = self.bit_commitments_sum_to_commitment(&bit_commitments);
+
+        bit_commitments
+            .into_iter()
+            .zip(or_commitments)
+            .zip(prover_response_to_challenge)
+            .fold(
+                sums_to_commitment,
+                |verified, ((bit_commitment, or_commitment), response)| {
+                    let result = self.bit_proof(bit_commitment).verify_response_to_challenge(
+                        or_commitment,
+                        random_challenge,
+                        response,
+                    );
+                    verified & Choice::from(u8::from(result))
+                },
+            )
+            .into()
+    }
+
+    fn simulate_prover_responses(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        random_challenge: RangeVerifierChallenge,
+    ) -> (RangeProverCommitment, RangeProverResponse) {
+        let bits = self.witness_statement.bits;
+        let other_bit_commitments: Vec<RistrettoPoint> =
+            (1..bits).map(|_| RistrettoPoint::random(rng)).collect();
+        let mut bit_commitments = vec![self.first_bit_commitment(&other_bit_commitments)];
+        bit_commitments.extend(other_bit_commitments);
+
+        let (or_commitments, responses) = bit_commitments
+            .iter()
+            .map(|bit_commitment| {
+                self.bit_proof(*bit_commitment)
+                    .simulate_prover_responses(rng, random_challenge)
+            })
+            .unzip();
+        (
+            RangeProverCommitment {
+                bit_commitments,
+                or_commitments,
+            },
+            responses,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pok::test_macros::test_sigma_protocol;
+
+    use super::*;
+
+    #[test]
+    fn test_range_proof() {
+        let mut rng = rand::thread_rng();
+        let h = Generator::new(&RistrettoPoint::random(&mut rng));
+
+        for (value, bits) in [(0, 1), (1, 1), (5, 3), (200, 8), (u64::MAX, 64)] {
+            // 0. Commit to a value, and define the statement to prove
+            let witness = RangeWitness {
+                value,
+                blinding: Scalar::random(&mut rng),
+            };
+            let witness_statement = RangeWitnessStatement {
+                commitment: witness.commit(&h),
+                bits,
+            };
+
+            // 1. Run tests with the protocol
+            let range_proof = RangeProof::new(h.clone(), witness_statement);
+            test_sigma_protocol!(witness, range_proof);
+        }
+    }
+
+    #[test]
+    fn test_range_proof_rejects() {
+        let mut rng = rand::thread_rng();
+        let h = Generator::new(&RistrettoPoint::random(&mut rng));
+        let witness = RangeWitness {
+            value: 200,
+            blinding: Scalar::random(&mut rng),
+        };
+        let witness_statement = RangeWitnessStatement {
+            commitment: witness.commit(&h),
+            bits: 8,
+        };
+
+        // 0. A value that does not fit, or an invalid number of bits, is an error, not a panic
+        let too_few_bits = RangeProof::new(
+            h.clone(),
+            RangeWitnessStatement {
+                bits: 7,
+                ..witness_statement
+            },
+        );
+        assert_eq!(
+            too_few_bits.generate_commitment(&mut rng, witness).err(),
+            Some(PokError::ValueOutOfRange { bits: 7 })
+        );
+        let too_many_bits = RangeProof::new(
+            h.clone(),
+            RangeWitnessStatement {
+                bits: 65,
+                ..witness_statement
+            },
+        );
+        assert_eq!(
+            too_many_bits.generate_commitment(&mut rng, witness).err(),
+            Some(PokError::InvalidBitLength { bits: 65 })
+        );
+
+        // 1. Bit commitments that do not open the commitment are rejected
+        let range_proof = RangeProof::new(h, witness_statement);
+        let (mut prover_commitment, prover_state) =
+            range_proof.generate_commitment(&mut rng, witness).unwrap();
+        let random_challenge = range_proof.generate_random_challenge(&mut rng);
+        let prover_response =
+            range_proof.generate_response_to_challenge(prover_state, random_challenge);
+        prover_commitment.bit_commitments[0] += RISTRETTO_BASEPOINT_POINT;
+        assert!(!range_proof.verify_response_to_challenge(
+            prover_commitment,
+            random_challenge,
+            prover_response,
+        ));
+    }
+}
@@ -0,0 +1,131 @@
+//! Scalar Blinding against Side Channels
+//!
+//! The scalar multiplications and the scalar arithmetic of curve25519-dalek run in constant time,
+//! but on platforms whose hardware or runtime gives no constant-time guarantees (e.g., older ARM
+//! cores with an early-terminating multiplier, or wasm engines), their power draw or timing may
+//! still leak a secret scalar, a few bits per operation. With the `scalar_blinding` feature, the
+//! helpers here split every secret scalar into two shares that are fresh and random on every call,
+//! and only ever operate on the shares: s * P is computed as (s - k) * P + k * P, and the
+//! response r + x * c of a prover as (r + (x - k) * c) + k * c, for a random k. Repeated operations
+//! on one secret then never handle the same values, which defeats attacks that average many
+//! traces. This costs a second scalar multiplication per operation; without the feature, the
+//! helpers compute the plain result.
+//!
+//! The provers of `schnorr`, `chaum_pedersen`, `dleq` and `define_proof!`, `Generator::mul`, and
+//! ElGamal encryption, and thus `amf::frank`, go through these helpers.
+
+#[cfg(feature = "precomputed-tables")]
+use curve25519_dalek::ristretto::RistrettoBasepointTable;
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+
+use crate::pok::profiling;
+
+/// whether the crate was built with the `scalar_blinding` feature
+pub const ENABLED: bool = cfg!(feature = "scalar_blinding");
+
+/// a fresh share k; `thread_rng` so that callers without an rng, e.g. responses, can blind too
+#[cfg(feature = "scalar_blinding")]
+fn random_share() -> Scalar {
+    Scalar::random(&mut rand::thread_rng())
+}
+
+/// never called, as nothing is blinded unless `ENABLED`
+#[cfg(not(feature = "scalar_blinding"))]
+fn random_share() -> Scalar {
+    unreachable!("scalar blinding is not enabled")
+}
+
+/// counts the operations of one multiplication by the helpers, cf. `pok::profiling`
+fn record_mul() {
+    profiling::record(|counts| {
+        if ENABLED {
+            counts.scalar_muls += 2;
+            counts.point_additions += 1;
+        } else {
+            counts.scalar_muls += 1;
+        }
+    });
+}
+
+/// computes `scalar * point`, blinding the scalar if `ENABLED`
+pub fn mul(scalar: &Scalar, point: &RistrettoPoint) -> RistrettoPoint {
+    record_mul();
+    if !ENABLED {
+        return scalar * point;
+    }
+    let share = random_share();
+    (scalar - share) * point + share * point
+}
+
+/// computes `scalar * g` for the Ristretto basepoint g, with its precomputed table if the crate was
+/// built with the `precomputed-tables` feature, blinding the scalar if `ENABLED`
+pub fn mul_base(scalar: &Scalar) -> RistrettoPoint {
+    record_mul();
+    if !ENABLED {
+        return RistrettoPoint::mul_base(scalar);
+    }
+    let share = random_share();
+    RistrettoPoint::mul_base(&(scalar - share)) + RistrettoPoint::mul_base(&share)
+}
+
+/// computes `scalar * table.basepoint()` with the precomputed `table`, blinding the scalar if
+/// `ENABLED`
+#[cfg(feature = "precomputed-tables")]
+pub fn mul_table(scalar: &Scalar, table: &RistrettoBasepointTable) -> RistrettoPoint {
+    record_mul();
+    if !ENABLED {
+        return scalar * table;
+    }
+    let share = random_share();
+    &(scalar - share) * table + &share * table
+}
+
+/// computes the response `per_verifier_secret + witness * challenge` of a linear sigma protocol,
+/// blinding the witness if `ENABLED`
+pub fn response(per_verifier_secret: &Scalar, witness: &Scalar, challenge: &Scalar) -> Scalar {
+    if !ENABLED {
+        return per_verifier_secret + witness * challenge;
+    }
+    let share = random_share();
+    (per_verifier_secret + (witness - share) * challenge) + share * challenge
+}
+
+#[cfg(test)]
+mod tests {
+    use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+
+    use super::*;
+
+    #[test]
+    fn test_scalar_blinding() {
+        let mut rng = rand::thread_rng();
+        let scalar = Scalar::random(&mut rng);
+        let point = RistrettoPoint::random(&mut rng);
+
+        // 0. With or without the feature, the helpers compute the plain results
+        assert_eq!(mul(&scalar, &point), scalar * point);
+        assert_eq!(mul_base(&scalar), scalar * RISTRETTO_BASEPOINT_POINT);
+        #[cfg(feature = "precomputed-tables")]
+        assert_eq!(
+            mul_table(
+                &scalar,
+                curve25519_dalek::constants::RISTRETTO_BASEPOINT_TABLE
+            ),
+            scalar * RISTRETTO_BASEPOINT_POINT
+        );
+        let (per_verifier_secret, challenge) = (Scalar::random(&mut rng), Scalar::random(&mut rng));
+        assert_eq!(
+            response(&per_verifier_secret, &scalar, &challenge),
+            per_verifier_secret + scalar * challenge
+        );
+
+        // 1. Including for the edge cases zero and one
+        for scalar in [Scalar::ZERO, Scalar::ONE] {
+            assert_eq!(mul(&scalar, &point), scalar * point);
+            assert_eq!(
+                response(&Scalar::ZERO, &scalar, &challenge),
+                scalar * challenge
+            );
+        }
+    }
+}
@@ -4,10 +4,20 @@
 //!
 //! [BS0.5]: https://crypto.stanford.edu/~dabo/cryptobook/BonehShoup_0_5.pdf
 
-use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use alloc::vec::Vec;
 
-use crate::pok::linear_sigma::{
-    GenericSigmaProver, GenericSigmaVerifier, SigmaProver, SigmaVerifier,
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use rand_core::CryptoRngCore;
+use subtle::ConstantTimeEq;
+
+use crate::pok::{
+    batch::BatchVerifier,
+    domain,
+    error::PokError,
+    linear_sigma::{
+        vartime_recompute_commitment, GenericSigmaProtocol, GenericSigmaProverState, SigmaProtocol,
+    },
+    profiling, scalar_blinding,
 };
 
 /// the secret witness, denoted by alpha in Section 19.1 in [BS0.5]
@@ -28,58 +38,67 @@ pub type SchnorrVerifierChallenge = Scalar;
 /// the prover's response, denoted by alpha_z in Section 19.1 of [BS0.5]
 pub type SchnorrProverResponse = Scalar;
 
-pub type SchnorrProver =
-    GenericSigmaProver<SchnorrWitness, SchnorrWitnessStatement, SchnorrPerVerifierSecret>;
-
-impl
-    SigmaProver<
-        SchnorrWitness,
-        SchnorrWitnessStatement,
-        SchnorrProverCommitment,
-        SchnorrVerifierChallenge,
-        SchnorrProverResponse,
-    > for SchnorrProver
-{
-    fn generate_commitment(&mut self, witness: SchnorrWitness) -> SchnorrProverCommitment {
-        let mut rng = rand::thread_rng();
-        // Store the witness for future use
-        self.witness = Some(witness);
+/// the prover's state between its commitment and its response
+pub type SchnorrProverState = GenericSigmaProverState<SchnorrWitness, SchnorrPerVerifierSecret>;
+
+pub type SchnorrProof = GenericSigmaProtocol<SchnorrWitnessStatement>;
+
+impl SigmaProtocol for SchnorrProof {
+    type Witness = SchnorrWitness;
+    type Statement = SchnorrWitnessStatement;
+    type Commitment = SchnorrProverCommitment;
+    type Response = SchnorrProverResponse;
+    type ProverState = SchnorrProverState;
+
+    fn generate_commitment(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        witness: SchnorrWitness,
+    ) -> Result<(SchnorrProverCommitment, SchnorrProverState), PokError> {
         // Generate a new random commitment for this verifier
-        let per_verifier_secret = Scalar::random(&mut rng);
-        let per_verifier_commitment = per_verifier_secret * self.g;
-        // Store the secret for future reference
-        self.per_verifier_secret = Some(per_verifier_secret);
-        // Return the commitment
-        per_verifier_commitment
+        let per_verifier_secret = Scalar::random(rng);
+        let per_verifier_commitment = self.g.mul(&per_verifier_secret);
+        // Return the commitment, and keep the witness and secret for the response
+        Ok((
+            per_verifier_commitment,
+            SchnorrProverState {
+                witness,
+                per_verifier_secret,
+            },
+        ))
+    }
+
+    fn serialize_commitment(&self, commitment: &SchnorrProverCommitment, buf: &mut Vec<u8>) {
+        profiling::record(|counts| counts.compressions += 1);
+        buf.extend(commitment.compress().as_bytes());
     }
 
-    fn serialize_commitment(&self, commitment: &SchnorrProverCommitment) -> Vec<u8> {
-        commitment.compress().as_bytes().to_vec()
+    fn protocol_label(&self) -> Vec<u8> {
+        domain::SCHNORR_LABEL.to_vec()
+    }
+
+    fn write_protocol_label(&self, buf: &mut Vec<u8>) {
+        buf.extend(domain::SCHNORR_LABEL);
+    }
+
+    fn serialize_witness_statement(&self) -> Vec<u8> {
+        self.witness_statement.compress().as_bytes().to_vec()
+    }
+
+    fn write_witness_statement(&self, buf: &mut Vec<u8>) {
+        profiling::record(|counts| counts.compressions += 1);
+        buf.extend(self.witness_statement.compress().as_bytes());
     }
 
     fn generate_response_to_challenge(
-        &mut self,
+        &self,
+        prover_state: SchnorrProverState,
         random_challenge: SchnorrVerifierChallenge,
     ) -> SchnorrProverResponse {
         // Construct response using the per_verifier_secret and random_challenge
-        self.per_verifier_secret.unwrap() + (self.witness.unwrap() * random_challenge)
-    }
-}
-
-pub type SchnorrVerifier = GenericSigmaVerifier<SchnorrWitnessStatement>;
-
-impl
-    SigmaVerifier<
-        SchnorrWitness,
-        SchnorrWitnessStatement,
-        SchnorrProverCommitment,
-        SchnorrVerifierChallenge,
-        SchnorrProverResponse,
-    > for SchnorrVerifier
-{
-    fn generate_random_challenge(&mut self) -> SchnorrVerifierChallenge {
-        let mut rng = rand::thread_rng();
-        Scalar::random(&mut rng)
+        prover_state.respond(|witness, per_verifier_secret| {
+            scalar_blinding::response(per_verifier_secret, witness, &random_challenge)
+        })
     }
 
     fn verify_response_to_challenge(
@@ -88,47 +107,100 @@ impl
         random_challenge: SchnorrVerifierChallenge,
         prover_response_to_challenge: SchnorrProverResponse,
     ) -> bool {
-        let left = prover_response_to_challenge * self.g;
-        let right = prover_commitment + (random_challenge * self.witness_statement);
-        left == right
+        // g^z == t * y^c, checked as g^z * y^-c == t
+        vartime_recompute_commitment(
+            &self.g.point(),
+            &self.witness_statement,
+            &prover_response_to_challenge,
+            &random_challenge,
+        )
+        .ct_eq(&prover_commitment)
+        .into()
+    }
+
+    fn recompute_commitment(
+        &self,
+        random_challenge: SchnorrVerifierChallenge,
+        prover_response_to_challenge: SchnorrProverResponse,
+    ) -> Option<SchnorrProverCommitment> {
+        Some(vartime_recompute_commitment(
+            &self.g.point(),
+            &self.witness_statement,
+            &prover_response_to_challenge,
+            &random_challenge,
+        ))
     }
 
     fn simulate_prover_responses(
         &self,
+        rng: &mut dyn CryptoRngCore,
         random_challenge: SchnorrVerifierChallenge,
     ) -> (SchnorrProverCommitment, SchnorrProverResponse) {
-        let mut rng = rand::thread_rng();
-        let simulated_prover_response = Scalar::random(&mut rng);
+        let simulated_prover_response = Scalar::random(rng);
+        profiling::record(|counts| {
+            counts.scalar_muls += 1;
+            counts.point_additions += 1;
+        });
         let simulated_prover_commitment =
-            (simulated_prover_response * self.g) - (random_challenge * self.witness_statement);
+            self.g.mul(&simulated_prover_response) - (random_challenge * self.witness_statement);
         (simulated_prover_commitment, simulated_prover_response)
     }
+
+    fn batch_verify_response_to_challenge(
+        &self,
+        batch: &mut BatchVerifier,
+        prover_commitment: SchnorrProverCommitment,
+        random_challenge: SchnorrVerifierChallenge,
+        prover_response_to_challenge: SchnorrProverResponse,
+    ) -> bool {
+        batch.add_schnorr(
+            &self.g.point(),
+            &self.witness_statement,
+            &prover_commitment,
+            &random_challenge,
+            &prover_response_to_challenge,
+        );
+        true
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use curve25519_dalek::{
-        constants::RISTRETTO_BASEPOINT_TABLE, ristretto::RistrettoBasepointTable,
-    };
+    use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
 
-    use crate::pok::test_macros::test_sigma_protocol;
+    use crate::pok::{linear_sigma::Generator, test_macros::test_sigma_protocol};
 
     use super::*;
 
     #[test]
     fn test_schnorr() {
         let mut rng = rand::thread_rng();
-        let g = RistrettoBasepointTable::basepoint(&RISTRETTO_BASEPOINT_TABLE);
+        let g = RISTRETTO_BASEPOINT_POINT;
 
         // 0. Fix a witness, and define the statement to prove
         let witness = Scalar::random(&mut rng);
         let witness_statement = witness * g;
 
-        // 1. Initialize a Schnorr prover and verifier with the statement to prove
-        let mut prover = SchnorrProver::new(witness_statement);
-        let mut verifier = SchnorrVerifier::new(witness_statement);
-
-        // 2. Run tests with the verifier and prover
-        test_sigma_protocol!(witness, verifier, prover);
+        // 1. Initialize Schnorr's protocol with the statement to prove
+        let schnorr = SchnorrProof::new(witness_statement);
+
+        // 2. Run tests with the protocol
+        test_sigma_protocol!(witness, schnorr);
+
+        // 3. One prover can run interleaved sessions, since each keeps its own state
+        let (commitment0, state0) = schnorr.generate_commitment(&mut rng, witness).unwrap();
+        let (commitment1, state1) = schnorr.generate_commitment(&mut rng, witness).unwrap();
+        let challenge0 = schnorr.generate_random_challenge(&mut rng);
+        let challenge1 = schnorr.generate_random_challenge(&mut rng);
+        let response1 = schnorr.generate_response_to_challenge(state1, challenge1);
+        let response0 = schnorr.generate_response_to_challenge(state0, challenge0);
+        assert!(schnorr.verify_response_to_challenge(commitment0, challenge0, response0));
+        assert!(schnorr.verify_response_to_challenge(commitment1, challenge1, response1));
+
+        // 4. The same, for a generator other than the basepoint
+        let h = Generator::new(&RistrettoPoint::random(&mut rng));
+        let witness_statement = h.mul(&witness);
+        let schnorr = SchnorrProof::with_generator(h, witness_statement);
+        test_sigma_protocol!(witness, schnorr);
     }
 }
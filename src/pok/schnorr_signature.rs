@@ -0,0 +1,244 @@
+//! Schnorr Signatures over Ristretto
+//!
+//! An ordinary, publicly verifiable signature scheme: the Fiat-Shamir transform (cf.
+//! `fiat_shamir`) of Schnorr's protocol (cf. `schnorr`) for the public key Y = g^x. It is what
+//! certificates, verdicts and key bundles need; unlike franks and `dvs` signatures, these
+//! signatures are NOT deniable. The proof signs `tag(SCHNORR_SIGNATURE_LABEL) || message`, with
+//! `tag` as in `pok::domain`, so a Schnorr proof of knowledge on `message` for the same key is no
+//! signature, and a signature is no such proof.
+//!
+//! A signature encodes to 64 bytes, the compressed commitment and the response, and a public key
+//! to the 32 bytes of the compressed point.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use curve25519_dalek::{
+    ristretto::{CompressedRistretto, RistrettoPoint},
+    scalar::Scalar,
+    traits::Identity,
+};
+use rand_core::CryptoRngCore;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use subtle::{Choice, ConstantTimeEq};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+use crate::pok::{
+    domain::{tag, SCHNORR_SIGNATURE_LABEL},
+    fiat_shamir::{FiatShamir, FiatShamirSecretKey, FiatShamirSignature, SignatureScheme},
+    linear_sigma::SigmaProtocol,
+    schnorr::SchnorrProof,
+};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SchnorrPublicKey {
+    pub public_key: RistrettoPoint,
+}
+
+/// With the `zeroize` feature, the secret scalar is overwritten when the key is dropped, which is
+/// why the key is `Clone` but not `Copy`.
+#[derive(Clone)]
+pub struct SchnorrSecretKey {
+    pub secret_key: Scalar,
+}
+
+impl ConstantTimeEq for SchnorrSecretKey {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.secret_key.ct_eq(&other.secret_key)
+    }
+}
+
+/// `ct_eq`, so that comparing secret keys takes as long wherever they differ
+impl PartialEq for SchnorrSecretKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl Eq for SchnorrSecretKey {}
+
+/// Prints a placeholder, so that keys cannot leak through logs.
+impl fmt::Debug for SchnorrSecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SchnorrSecretKey")
+            .field("secret_key", &format_args!("<redacted>"))
+            .finish()
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Zeroize for SchnorrSecretKey {
+    fn zeroize(&mut self) {
+        self.secret_key.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for SchnorrSecretKey {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for SchnorrSecretKey {}
+
+/// the signature, a Fiat-Shamir Schnorr proof
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SchnorrSignature(pub(crate) FiatShamirSignature<RistrettoPoint, Scalar>);
+
+impl SchnorrPublicKey {
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.public_key.compress().to_bytes()
+    }
+
+    /// Decodes `to_bytes`; fails for bytes that are not a canonical point encoding.
+    pub fn from_bytes(bytes: &[u8; 32]) -> Option<Self> {
+        let public_key = CompressedRistretto(*bytes).decompress()?;
+        Some(SchnorrPublicKey { public_key })
+    }
+}
+
+impl SchnorrSecretKey {
+    pub fn public_key(&self) -> SchnorrPublicKey {
+        SchnorrPublicKey {
+            public_key: RistrettoPoint::mul_base(&self.secret_key),
+        }
+    }
+}
+
+impl SchnorrSignature {
+    pub fn to_bytes(&self) -> [u8; 64] {
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(self.0.prover_commitment.compress().as_bytes());
+        bytes[32..].copy_from_slice(self.0.prover_response.as_bytes());
+        bytes
+    }
+
+    /// Decodes `to_bytes`; fails for a non-canonical point or scalar.
+    pub fn from_bytes(bytes: &[u8; 64]) -> Option<Self> {
+        let prover_commitment =
+            CompressedRistretto(bytes[..32].try_into().unwrap()).decompress()?;
+        let prover_response = Option::from(Scalar::from_canonical_bytes(
+            bytes[32..].try_into().unwrap(),
+        ))?;
+        Some(SchnorrSignature(FiatShamirSignature {
+            prover_commitment,
+            prover_response,
+        }))
+    }
+}
+
+fn schnorr_scheme(public_key: &SchnorrPublicKey) -> FiatShamir<SchnorrProof> {
+    SchnorrProof::new(public_key.public_key).fiat_shamir()
+}
+
+/// `tag(SCHNORR_SIGNATURE_LABEL) || message`, what the proof of knowledge signs
+fn bind_label(message: &[u8]) -> Vec<u8> {
+    let mut bound = Vec::with_capacity(8 + SCHNORR_SIGNATURE_LABEL.len() + message.len());
+    tag(&mut bound, SCHNORR_SIGNATURE_LABEL);
+    bound.extend_from_slice(message);
+    bound
+}
+
+pub fn schnorr_keygen(rng: &mut dyn CryptoRngCore) -> (SchnorrPublicKey, SchnorrSecretKey) {
+    let secret_key = SchnorrSecretKey {
+        secret_key: Scalar::random(rng),
+    };
+    (secret_key.public_key(), secret_key)
+}
+
+/// Signs `message` with the secret key.
+pub fn schnorr_sign(
+    rng: &mut dyn CryptoRngCore,
+    secret_key: &SchnorrSecretKey,
+    message: &[u8],
+) -> SchnorrSignature {
+    let signature = schnorr_scheme(&secret_key.public_key())
+        .sign(
+            rng,
+            FiatShamirSecretKey {
+                witness: secret_key.secret_key,
+            },
+            &bind_label(message),
+        )
+        .expect("a secret key always fits its public key");
+    SchnorrSignature(signature)
+}
+
+/// Verifies a signature on `message` under `public_key`; rejects the identity, whose secret key
+/// is known to everyone.
+pub fn schnorr_verify(
+    public_key: &SchnorrPublicKey,
+    message: &[u8],
+    signature: &SchnorrSignature,
+) -> bool {
+    public_key.public_key != RistrettoPoint::identity()
+        && schnorr_scheme(public_key).verify(&bind_label(message), signature.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schnorr_signature() {
+        let mut rng = rand::thread_rng();
+        let message = b"hello world!";
+
+        // 0. A signature verifies under the signer's key
+        let (public_key, secret_key) = schnorr_keygen(&mut rng);
+        assert_eq!(secret_key.public_key(), public_key);
+        let signature = schnorr_sign(&mut rng, &secret_key, message);
+        assert!(schnorr_verify(&public_key, message, &signature));
+
+        // 1. It is bound to the message and the key
+        assert!(!schnorr_verify(&public_key, b"goodbye world!", &signature));
+        let (other_public_key, _) = schnorr_keygen(&mut rng);
+        assert!(!schnorr_verify(&other_public_key, message, &signature));
+
+        // 2. The identity key is rejected, though its secret key 0 signs
+        let zero = SchnorrSecretKey {
+            secret_key: Scalar::ZERO,
+        };
+        let signature_of_zero = schnorr_sign(&mut rng, &zero, message);
+        assert!(!schnorr_verify(
+            &zero.public_key(),
+            message,
+            &signature_of_zero
+        ));
+
+        // 3. A plain Schnorr proof of knowledge on the message is no signature, nor the reverse
+        let proof = schnorr_scheme(&public_key)
+            .sign(
+                &mut rng,
+                FiatShamirSecretKey {
+                    witness: secret_key.secret_key,
+                },
+                message,
+            )
+            .unwrap();
+        assert!(!schnorr_verify(
+            &public_key,
+            message,
+            &SchnorrSignature(proof)
+        ));
+        assert!(!schnorr_scheme(&public_key).verify(message, signature.0));
+
+        // 4. Keys and signatures round-trip through their encodings, which reject garbage
+        assert_eq!(
+            SchnorrPublicKey::from_bytes(&public_key.to_bytes()),
+            Some(public_key)
+        );
+        assert_eq!(
+            SchnorrSignature::from_bytes(&signature.to_bytes()),
+            Some(signature)
+        );
+        assert_eq!(SchnorrPublicKey::from_bytes(&[0xff; 32]), None);
+        assert_eq!(SchnorrSignature::from_bytes(&[0xff; 64]), None);
+    }
+}
@@ -0,0 +1,62 @@
+//! Serialized Sizes of Proof Components
+//!
+//! Reports how many bytes a proof occupies in compact binary serde formats (e.g., bincode), where
+//! every point and scalar takes 32 bytes and structs and tuples add no framing, so that callers
+//! can budget payloads without serializing first.
+
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+
+use crate::pok::{
+    chaum_pedersen::ChaumPedersenProverCommitment, dleq::DleqProverCommitment,
+    fiat_shamir::FiatShamirSignature, or_proof::OrProverResponse,
+};
+
+pub trait SerializedSize {
+    fn serialized_size(&self) -> usize;
+}
+
+impl SerializedSize for RistrettoPoint {
+    fn serialized_size(&self) -> usize {
+        32
+    }
+}
+
+impl SerializedSize for Scalar {
+    fn serialized_size(&self) -> usize {
+        32
+    }
+}
+
+impl<A: SerializedSize, B: SerializedSize> SerializedSize for (A, B) {
+    fn serialized_size(&self) -> usize {
+        self.0.serialized_size() + self.1.serialized_size()
+    }
+}
+
+impl SerializedSize for ChaumPedersenProverCommitment {
+    fn serialized_size(&self) -> usize {
+        self.v_t.serialized_size() + self.w_t.serialized_size()
+    }
+}
+
+impl SerializedSize for DleqProverCommitment {
+    fn serialized_size(&self) -> usize {
+        self.t1.serialized_size() + self.t2.serialized_size()
+    }
+}
+
+impl<S0ProverResponse: SerializedSize, S1ProverResponse: SerializedSize> SerializedSize
+    for OrProverResponse<S0ProverResponse, S1ProverResponse>
+{
+    fn serialized_size(&self) -> usize {
+        self.c_0.serialized_size() + self.z_0.serialized_size() + self.z_1.serialized_size()
+    }
+}
+
+impl<ProverCommitment: SerializedSize, ProverResponse: SerializedSize> SerializedSize
+    for FiatShamirSignature<ProverCommitment, ProverResponse>
+{
+    fn serialized_size(&self) -> usize {
+        self.prover_commitment.serialized_size() + self.prover_response.serialized_size()
+    }
+}
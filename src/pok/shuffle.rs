@@ -0,0 +1,428 @@
+//! Verifiable Shuffles of ElGamal Ciphertexts
+//!
+//! A mix node permutes a batch of ciphertexts (cf. `elgamal`) and rerandomizes every one of them,
+//! so that no output can be linked to its input, and proves that the outputs decrypt to the same
+//! plaintexts as the inputs. The proof is the one of [TW10], as specified in [HLKD17]: the prover
+//! commits to the permutation matrix with Pedersen commitments over independent generators h,
+//! h_1, ..., h_N, and proves in zero-knowledge that the committed matrix is a permutation, and that
+//! the outputs are the permuted, rerandomized inputs. The proof has O(N) points and scalars, and is
+//! made non-interactive with the `V2` hash layout of `pok::domain`.
+//!
+//! [TW10]: Terelius and Wikström, Proofs of Restricted Shuffles, AFRICACRYPT 2010
+//! [HLKD17]: Haenni, Locher, Koenig, and Dubuis, Pseudo-Code Algorithms for Verifiable
+//!           Re-Encryption Mix-Nets, FC 2017 Workshops
+
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT,
+    ristretto::RistrettoPoint,
+    scalar::Scalar,
+    traits::{Identity, MultiscalarMul, VartimeMultiscalarMul},
+};
+use rand::seq::SliceRandom;
+use rand_core::CryptoRngCore;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
+
+use crate::pok::{
+    domain,
+    elgamal::{ElGamalCiphertext, VerifiableElGamal},
+};
+
+/// the proof, denoted by pi = (t, s, c, c_hat) in [HLKD17]; the challenge is recomputed from the
+/// statement and the prover's commitments t
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ShuffleProof {
+    /// the commitment to the permutation, c = (c_1,...,c_N)
+    pub(crate) permutation_commitment: Vec<RistrettoPoint>,
+    /// the commitment chain, c_hat = (c_hat_1,...,c_hat_N)
+    pub(crate) chain: Vec<RistrettoPoint>,
+    /// the prover's commitments (t_1, t_2, t_3, t_4,1, t_4,2)
+    pub(crate) t: [RistrettoPoint; 5],
+    /// the prover's commitments t_hat = (t_hat_1,...,t_hat_N)
+    pub(crate) t_hat: Vec<RistrettoPoint>,
+    /// the prover's responses (s_1, s_2, s_3, s_4)
+    pub(crate) s: [Scalar; 4],
+    /// the prover's responses s_hat = (s_hat_1,...,s_hat_N)
+    pub(crate) s_hat: Vec<Scalar>,
+    /// the prover's responses s' = (s'_1,...,s'_N)
+    pub(crate) s_prime: Vec<Scalar>,
+}
+
+/// h, and h_1, ..., h_N, hashed to points so that nobody knows their discrete logarithms
+fn generators(n: usize) -> (RistrettoPoint, Vec<RistrettoPoint>) {
+    let generator = |index: u64| {
+        let mut hasher = Sha512::new();
+        domain::absorb(&mut hasher, domain::SHUFFLE_GENERATOR_LABEL);
+        domain::absorb(&mut hasher, &index.to_be_bytes());
+        RistrettoPoint::from_hash(hasher)
+    };
+    (generator(0), (1..=n as u64).map(generator).collect())
+}
+
+fn serialize_points<'a, I: IntoIterator<Item = &'a RistrettoPoint>>(points: I) -> Vec<u8> {
+    let mut serialized = Vec::new();
+    for point in points {
+        serialized.extend(point.compress().as_bytes());
+    }
+    serialized
+}
+
+/// starts the transcript with the statement: the public key, the inputs, the outputs, and the
+/// commitment to the permutation
+fn transcript(
+    elgamal: &VerifiableElGamal,
+    inputs: &[ElGamalCiphertext],
+    outputs: &[ElGamalCiphertext],
+    permutation_commitment: &[RistrettoPoint],
+) -> Sha512 {
+    let ciphertexts = inputs
+        .iter()
+        .chain(outputs)
+        .flat_map(|ciphertext| [&ciphertext.c1, &ciphertext.c2]);
+    let mut statement = serialize_points([&elgamal.public_key]);
+    statement.extend(serialize_points(ciphertexts));
+    statement.extend(serialize_points(permutation_commitment));
+
+    let mut hasher = Sha512::new();
+    domain::absorb(&mut hasher, domain::FIAT_SHAMIR_DOMAIN);
+    domain::absorb(&mut hasher, domain::SHUFFLE_LABEL);
+    domain::absorb(&mut hasher, domain::STATEMENT_ROLE);
+    domain::absorb(&mut hasher, &statement);
+    hasher
+}
+
+/// u = (u_1,...,u_N), the challenges that compress the permutation matrix into a vector
+fn permutation_challenges(transcript: &Sha512, n: usize) -> Vec<Scalar> {
+    (0..n as u64)
+        .map(|index| {
+            let mut hasher = transcript.clone();
+            domain::absorb(&mut hasher, &index.to_be_bytes());
+            Scalar::from_hash(hasher)
+        })
+        .collect()
+}
+
+/// c, the challenge for the prover's commitments
+fn challenge(
+    transcript: &Sha512,
+    chain: &[RistrettoPoint],
+    t: &[RistrettoPoint; 5],
+    t_hat: &[RistrettoPoint],
+) -> Scalar {
+    let mut hasher = transcript.clone();
+    domain::absorb(&mut hasher, domain::COMMITMENT_ROLE);
+    domain::absorb(
+        &mut hasher,
+        &serialize_points(chain.iter().chain(t).chain(t_hat)),
+    );
+    Scalar::from_hash(hasher)
+}
+
+/// Permutes and rerandomizes `inputs`, and proves that the outputs decrypt to the same plaintexts.
+pub fn shuffle(
+    rng: &mut dyn CryptoRngCore,
+    elgamal: &VerifiableElGamal,
+    inputs: &[ElGamalCiphertext],
+) -> (Vec<ElGamalCiphertext>, ShuffleProof) {
+    let n = inputs.len();
+    let mut permutation: Vec<usize> = (0..n).collect();
+    permutation.shuffle(rng);
+    let randomness: Vec<Scalar> = (0..n).map(|_| Scalar::random(rng)).collect();
+    let outputs: Vec<ElGamalCiphertext> = permutation
+        .iter()
+        .zip(&randomness)
+        .map(|(&j, r)| elgamal.rerandomize(&inputs[j], r))
+        .collect();
+    let proof = prove(rng, elgamal, inputs, &outputs, &permutation, &randomness);
+    (outputs, proof)
+}
+
+/// Proves that `outputs[i]` rerandomizes `inputs[permutation[i]]` with `randomness[i]`; cf.
+/// GenShuffleProof in [HLKD17].
+fn prove(
+    rng: &mut dyn CryptoRngCore,
+    elgamal: &VerifiableElGamal,
+    inputs: &[ElGamalCiphertext],
+    outputs: &[ElGamalCiphertext],
+    permutation: &[usize],
+    randomness: &[Scalar],
+) -> ShuffleProof {
+    let n = inputs.len();
+    let (h, hs) = generators(n);
+    let mut random_scalars =
+        |count: usize| -> Vec<Scalar> { (0..count).map(|_| Scalar::random(&mut *rng)).collect() };
+
+    // 0. Commit to the permutation, c_{permutation[i]} = r_{permutation[i]} * g + h_i
+    let r = random_scalars(n);
+    let mut permutation_commitment = vec![RistrettoPoint::identity(); n];
+    for (i, &j) in permutation.iter().enumerate() {
+        permutation_commitment[j] = RistrettoPoint::mul_base(&r[j]) + hs[i];
+    }
+    let transcript = transcript(elgamal, inputs, outputs, &permutation_commitment);
+    let u = permutation_challenges(&transcript, n);
+    let u_prime: Vec<Scalar> = permutation.iter().map(|&j| u[j]).collect();
+
+    // 1. Commit to the product of the permuted challenges,
+    //    c_hat_i = r_hat_i * g + u'_i * c_hat_{i-1}
+    let r_hat = random_scalars(n);
+    let mut chain = Vec::with_capacity(n);
+    let mut previous = h;
+    for (r_hat_i, u_prime_i) in r_hat.iter().zip(&u_prime) {
+        previous = RistrettoPoint::mul_base(r_hat_i) + u_prime_i * previous;
+        chain.push(previous);
+    }
+
+    // 2. The prover's commitments
+    let omega = random_scalars(4);
+    let omega_hat = random_scalars(n);
+    let omega_prime = random_scalars(n);
+    let output_c1s: Vec<RistrettoPoint> = outputs.iter().map(|output| output.c1).collect();
+    let output_c2s: Vec<RistrettoPoint> = outputs.iter().map(|output| output.c2).collect();
+    let t = [
+        RistrettoPoint::mul_base(&omega[0]),
+        RistrettoPoint::mul_base(&omega[1]),
+        RistrettoPoint::mul_base(&omega[2]) + RistrettoPoint::multiscalar_mul(&omega_prime, &hs),
+        -(omega[3] * elgamal.public_key)
+            + RistrettoPoint::multiscalar_mul(&omega_prime, &output_c2s),
+        -RistrettoPoint::mul_base(&omega[3])
+            + RistrettoPoint::multiscalar_mul(&omega_prime, &output_c1s),
+    ];
+    let t_hat: Vec<RistrettoPoint> = (0..n)
+        .map(|i| {
+            let previous = if i == 0 { h } else { chain[i - 1] };
+            RistrettoPoint::mul_base(&omega_hat[i]) + omega_prime[i] * previous
+        })
+        .collect();
+    let c = challenge(&transcript, &chain, &t, &t_hat);
+
+    // 3. The prover's responses
+    let r_bar: Scalar = r.iter().sum();
+    // v_i = u'_{i+1} * ... * u'_N
+    let mut v = vec![Scalar::ONE; n];
+    for i in (1..n).rev() {
+        v[i - 1] = u_prime[i] * v[i];
+    }
+    let r_hat_sum: Scalar = r_hat
+        .iter()
+        .zip(&v)
+        .map(|(r_hat_i, v_i)| r_hat_i * v_i)
+        .sum();
+    let r_tilde: Scalar = r.iter().zip(&u).map(|(r_j, u_j)| r_j * u_j).sum();
+    let r_prime: Scalar = randomness
+        .iter()
+        .zip(&u_prime)
+        .map(|(r_i, u_prime_i)| r_i * u_prime_i)
+        .sum();
+    let s = [
+        omega[0] + c * r_bar,
+        omega[1] + c * r_hat_sum,
+        omega[2] + c * r_tilde,
+        omega[3] + c * r_prime,
+    ];
+    let s_hat = omega_hat
+        .iter()
+        .zip(&r_hat)
+        .map(|(omega_hat_i, r_hat_i)| omega_hat_i + c * r_hat_i)
+        .collect();
+    let s_prime = omega_prime
+        .iter()
+        .zip(&u_prime)
+        .map(|(omega_prime_i, u_prime_i)| omega_prime_i + c * u_prime_i)
+        .collect();
+
+    ShuffleProof {
+        permutation_commitment,
+        chain,
+        t,
+        t_hat,
+        s,
+        s_hat,
+        s_prime,
+    }
+}
+
+/// Checks that `outputs` are a permutation of rerandomized `inputs`; cf. CheckShuffleProof in
+/// [HLKD17].
+pub fn verify_shuffle(
+    elgamal: &VerifiableElGamal,
+    inputs: &[ElGamalCiphertext],
+    outputs: &[ElGamalCiphertext],
+    proof: &ShuffleProof,
+) -> bool {
+    let n = inputs.len();
+    if [
+        outputs.len(),
+        proof.permutation_commitment.len(),
+        proof.chain.len(),
+        proof.t_hat.len(),
+        proof.s_hat.len(),
+        proof.s_prime.len(),
+    ]
+    .iter()
+    .any(|&len| len != n)
+    {
+        return false;
+    }
+
+    let g = RISTRETTO_BASEPOINT_POINT;
+    let (h, hs) = generators(n);
+    let transcript = transcript(elgamal, inputs, outputs, &proof.permutation_commitment);
+    let u = permutation_challenges(&transcript, n);
+    let c = challenge(&transcript, &proof.chain, &proof.t, &proof.t_hat);
+    let [s1, s2, s3, s4] = proof.s;
+
+    // c_bar = sum of c_j - sum of h_i, and c_hat = c_hat_N - (u_1 * ... * u_N) * h
+    let c_bar = proof.permutation_commitment.iter().sum::<RistrettoPoint>()
+        - hs.iter().sum::<RistrettoPoint>();
+    let u_product: Scalar = u.iter().product();
+    let c_hat = proof.chain.last().copied().unwrap_or(h) - u_product * h;
+
+    let input_c1s = inputs.iter().map(|input| input.c1);
+    let input_c2s = inputs.iter().map(|input| input.c2);
+    let output_c1s = outputs.iter().map(|output| output.c1);
+    let output_c2s = outputs.iter().map(|output| output.c2);
+    let negated_c = -c;
+
+    let t1 = RistrettoPoint::vartime_multiscalar_mul([s1, negated_c], [g, c_bar]);
+    let t2 = RistrettoPoint::vartime_multiscalar_mul([s2, negated_c], [g, c_hat]);
+    let t3 = RistrettoPoint::vartime_multiscalar_mul(
+        [s3].into_iter()
+            .chain(proof.s_prime.iter().copied())
+            .chain(u.iter().map(|u_j| negated_c * u_j)),
+        [g].into_iter()
+            .chain(hs.iter().copied())
+            .chain(proof.permutation_commitment.iter().copied()),
+    );
+    let t41 = RistrettoPoint::vartime_multiscalar_mul(
+        [-s4]
+            .into_iter()
+            .chain(proof.s_prime.iter().copied())
+            .chain(u.iter().map(|u_j| negated_c * u_j)),
+        [elgamal.public_key]
+            .into_iter()
+            .chain(output_c2s)
+            .chain(input_c2s),
+    );
+    let t42 = RistrettoPoint::vartime_multiscalar_mul(
+        [-s4]
+            .into_iter()
+            .chain(proof.s_prime.iter().copied())
+            .chain(u.iter().map(|u_j| negated_c * u_j)),
+        [g].into_iter().chain(output_c1s).chain(input_c1s),
+    );
+    if [t1, t2, t3, t41, t42] != proof.t {
+        return false;
+    }
+
+    (0..n).all(|i| {
+        let previous = if i == 0 { h } else { proof.chain[i - 1] };
+        RistrettoPoint::vartime_multiscalar_mul(
+            [proof.s_hat[i], proof.s_prime[i], negated_c],
+            [g, previous, proof.chain[i]],
+        ) == proof.t_hat[i]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// the compressed encodings of the points, sorted, to compare them as multisets
+    fn sorted(points: impl Iterator<Item = RistrettoPoint>) -> Vec<[u8; 32]> {
+        let mut encodings: Vec<[u8; 32]> =
+            points.map(|point| point.compress().to_bytes()).collect();
+        encodings.sort();
+        encodings
+    }
+
+    #[test]
+    fn test_shuffle() {
+        let mut rng = rand::thread_rng();
+        let secret_key = Scalar::random(&mut rng);
+        let elgamal = VerifiableElGamal::new(RistrettoPoint::mul_base(&secret_key));
+
+        for n in [0, 1, 2, 10] {
+            // 0. Encrypt a batch of reports
+            let plaintexts: Vec<RistrettoPoint> =
+                (0..n).map(|_| RistrettoPoint::random(&mut rng)).collect();
+            let inputs: Vec<ElGamalCiphertext> = plaintexts
+                .iter()
+                .map(|plaintext| elgamal.encrypt(&mut rng, *plaintext).0)
+                .collect();
+
+            // 1. Shuffle them, and verify the proof
+            let (outputs, proof) = shuffle(&mut rng, &elgamal, &inputs);
+            assert!(verify_shuffle(&elgamal, &inputs, &outputs, &proof));
+
+            // 2. The outputs decrypt to the same plaintexts, and no ciphertext is left unchanged
+            let decrypted = outputs
+                .iter()
+                .map(|output| VerifiableElGamal::decrypt(&secret_key, output));
+            assert_eq!(sorted(decrypted), sorted(plaintexts.into_iter()));
+            assert!(outputs.iter().all(|output| !inputs.contains(output)));
+
+            // 3. The proof survives serialization
+            #[cfg(feature = "serde")]
+            {
+                let encoded = bincode::serialize(&proof).unwrap();
+                let decoded: ShuffleProof = bincode::deserialize(&encoded).unwrap();
+                assert!(verify_shuffle(&elgamal, &inputs, &outputs, &decoded));
+            }
+        }
+    }
+
+    #[test]
+    fn test_shuffle_rejects() {
+        let mut rng = rand::thread_rng();
+        let elgamal = VerifiableElGamal::new(RistrettoPoint::random(&mut rng));
+        let plaintexts: Vec<RistrettoPoint> =
+            (0..6).map(|_| RistrettoPoint::random(&mut rng)).collect();
+        let inputs: Vec<ElGamalCiphertext> = plaintexts[..5]
+            .iter()
+            .map(|plaintext| elgamal.encrypt(&mut rng, *plaintext).0)
+            .collect();
+        let (outputs, proof) = shuffle(&mut rng, &elgamal, &inputs);
+
+        // 0. A replaced output is rejected
+        let mut replaced = outputs.clone();
+        replaced[2] = elgamal.encrypt(&mut rng, plaintexts[5]).0;
+        assert!(!verify_shuffle(&elgamal, &inputs, &replaced, &proof));
+
+        // 1. So are reordered outputs, which the proof does not cover
+        let mut reordered = outputs.clone();
+        reordered.swap(0, 1);
+        assert!(!verify_shuffle(&elgamal, &inputs, &reordered, &proof));
+
+        // 2. And proofs for another key, or of the wrong length
+        let other_elgamal = VerifiableElGamal::new(RistrettoPoint::random(&mut rng));
+        assert!(!verify_shuffle(&other_elgamal, &inputs, &outputs, &proof));
+        let mut truncated = proof.clone();
+        truncated.s_prime.pop();
+        assert!(!verify_shuffle(&elgamal, &inputs, &outputs, &truncated));
+
+        // 3. A mix node that duplicates a report and drops another cannot prove it
+        let not_a_permutation = [0, 0, 2, 3, 4];
+        let randomness: Vec<Scalar> = (0..5).map(|_| Scalar::random(&mut rng)).collect();
+        let cheating_outputs: Vec<ElGamalCiphertext> = not_a_permutation
+            .iter()
+            .zip(&randomness)
+            .map(|(&j, r)| elgamal.rerandomize(&inputs[j], r))
+            .collect();
+        let cheating_proof = prove(
+            &mut rng,
+            &elgamal,
+            &inputs,
+            &cheating_outputs,
+            &not_a_permutation,
+            &randomness,
+        );
+        assert!(!verify_shuffle(
+            &elgamal,
+            &inputs,
+            &cheating_outputs,
+            &cheating_proof
+        ));
+    }
+}
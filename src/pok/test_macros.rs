@@ -2,19 +2,21 @@
 // These macros are used in other files to test.
 
 macro_rules! test_sigma_protocol {
-    ($witness:expr, $verifier:expr, $prover:expr) => {
+    ($witness:expr, $protocol:expr) => {
+    let mut rng = rand::thread_rng();
 
     // 1. Verifier asks the prover for a commitment
     //    The prover is given the witness so they can respond correctly.
-    let prover_commitment = $prover.generate_commitment($witness);
+    //    The prover keeps its state to answer the challenge with.
+    let (prover_commitment, prover_state) = $protocol.generate_commitment(&mut rng, $witness).unwrap();
 
     // 2. Verifier generates a random challenge and sends it to the prover
-    let random_challenge = $verifier.generate_random_challenge();
+    let random_challenge = $protocol.generate_random_challenge(&mut rng);
     let prover_response_to_challenge =
-        $prover.generate_response_to_challenge(random_challenge);
+        $protocol.generate_response_to_challenge(prover_state, random_challenge);
 
     // 3. Verifier verifies the response
-    let result_from_prover = $verifier.verify_response_to_challenge(
+    let result_from_prover = $protocol.verify_response_to_challenge(
         prover_commitment,
         random_challenge,
         prover_response_to_challenge,
@@ -23,10 +25,10 @@ macro_rules! test_sigma_protocol {
 
     //----------------------------------------------------------------------
     // 4. As usual, since this is a zero-knowledge proof, the verifier can also generate valid prover responses without knowing the witness, and verify that they are valid.
-    let random_challenge = $verifier.generate_random_challenge();
+    let random_challenge = $protocol.generate_random_challenge(&mut rng);
     let (prover_commitment, prover_response_to_challenge) =
-        $verifier.simulate_prover_responses(random_challenge);
-    let result_from_simulator = $verifier.verify_response_to_challenge(
+        $protocol.simulate_prover_responses(&mut rng, random_challenge);
+    let result_from_simulator = $protocol.verify_response_to_challenge(
         prover_commitment,
         random_challenge,
         prover_response_to_challenge,
@@ -44,7 +46,13 @@ macro_rules! test_fiat_shamir_signature {
         rng.fill_bytes(&mut message);
 
         // 2. Sign the message
-        let signature = $fiat_shamir.sign(FiatShamirSecretKey { witness: $witness }, &message);
+        let signature = $fiat_shamir
+            .sign(
+                &mut rng,
+                FiatShamirSecretKey { witness: $witness },
+                &message,
+            )
+            .unwrap();
 
         // 3. Verify the signature
         let verification_result = $fiat_shamir.verify(&message, signature);
@@ -0,0 +1,241 @@
+//! Statistical Checks for Honest-Verifier Zero-Knowledge
+//!
+//! A sigma protocol is honest-verifier zero-knowledge if the transcripts (t,c,z) of the simulator
+//! are distributed like those of a real prover, cf. Section 19.4 in [BS0.5]. This module samples
+//! many transcripts of both kinds for any `SigmaProtocol` and compares them: for every byte of the
+//! serialized commitment and response, it builds a histogram of the top `BUCKET_BITS` bits, and
+//! reports the largest total variation distance between the real and the simulated histograms.
+//!
+//! This is a sanity check, not a proof: it catches simulators that fix or leak part of the
+//! transcript (e.g., a zero response, or a commitment that does not depend on the challenge), but
+//! not subtle correlations between bytes.
+//!
+//! [BS0.5]: https://crypto.stanford.edu/~dabo/cryptobook/BonehShoup_0_5.pdf
+
+use rand_core::CryptoRngCore;
+use serde::Serialize;
+
+use crate::pok::linear_sigma::SigmaProtocol;
+
+/// how many transcripts of each kind `assert_zero_knowledge` samples
+pub const SAMPLES: usize = 1000;
+
+/// how many of the top bits of every byte are histogrammed
+pub const BUCKET_BITS: u32 = 3;
+
+/// serializes a transcript: the commitment, as hashed by Fiat-Shamir, then the response
+fn serialize_transcript<P: SigmaProtocol>(
+    protocol: &P,
+    commitment: &P::Commitment,
+    response: &P::Response,
+) -> Vec<u8>
+where
+    P::Response: Serialize,
+{
+    let mut transcript = Vec::new();
+    protocol.serialize_commitment(commitment, &mut transcript);
+    transcript.extend(bincode::serialize(response).expect("responses always serialize"));
+    transcript
+}
+
+/// Runs the real prover `samples` times on random challenges, and returns the serialized
+/// transcripts; panics if one does not verify.
+pub fn real_transcripts<P: SigmaProtocol>(
+    protocol: &P,
+    witness: &P::Witness,
+    rng: &mut dyn CryptoRngCore,
+    samples: usize,
+) -> Vec<Vec<u8>>
+where
+    P::Witness: Clone,
+    P::Response: Serialize,
+{
+    (0..samples)
+        .map(|_| {
+            let (commitment, state) = protocol
+                .generate_commitment(rng, witness.clone())
+                .expect("the witness must fit the statement");
+            let challenge = protocol.generate_random_challenge(rng);
+            let response = protocol.generate_response_to_challenge(state, challenge);
+            let transcript = serialize_transcript(protocol, &commitment, &response);
+            assert!(protocol.verify_response_to_challenge(commitment, challenge, response));
+            transcript
+        })
+        .collect()
+}
+
+/// Runs the simulator `samples` times on random challenges, and returns the serialized
+/// transcripts; panics if one does not verify.
+pub fn simulated_transcripts<P: SigmaProtocol>(
+    protocol: &P,
+    rng: &mut dyn CryptoRngCore,
+    samples: usize,
+) -> Vec<Vec<u8>>
+where
+    P::Response: Serialize,
+{
+    (0..samples)
+        .map(|_| {
+            let challenge = protocol.generate_random_challenge(rng);
+            let (commitment, response) = protocol.simulate_prover_responses(rng, challenge);
+            let transcript = serialize_transcript(protocol, &commitment, &response);
+            assert!(protocol.verify_response_to_challenge(commitment, challenge, response));
+            transcript
+        })
+        .collect()
+}
+
+/// The largest total variation distance, over all byte positions, between the histograms of the
+/// two samples; 1 if the transcripts differ in length.
+pub fn statistical_distance(left: &[Vec<u8>], right: &[Vec<u8>]) -> f64 {
+    let Some(len) = left.first().map(Vec::len) else {
+        return 0.0;
+    };
+    if left
+        .iter()
+        .chain(right)
+        .any(|transcript| transcript.len() != len)
+    {
+        return 1.0;
+    }
+
+    let buckets = 1 << BUCKET_BITS;
+    let histogram = |sample: &[Vec<u8>], position: usize| {
+        let mut counts = vec![0usize; buckets];
+        for transcript in sample {
+            counts[(transcript[position] >> (8 - BUCKET_BITS)) as usize] += 1;
+        }
+        counts
+            .into_iter()
+            .map(|count| count as f64 / sample.len() as f64)
+            .collect::<Vec<f64>>()
+    };
+    (0..len)
+        .map(|position| {
+            let (p, q) = (histogram(left, position), histogram(right, position));
+            p.iter().zip(&q).map(|(p, q)| (p - q).abs()).sum::<f64>() / 2.0
+        })
+        .fold(0.0, f64::max)
+}
+
+/// the distance below which two samples of `samples` transcripts pass as equally distributed: a
+/// few times the expected distance sqrt(buckets / samples) due to sampling alone
+pub fn distance_threshold(samples: usize) -> f64 {
+    3.0 * ((1 << BUCKET_BITS) as f64 / samples as f64).sqrt()
+}
+
+/// Panics unless the real and simulated transcripts of `protocol` are statistically close.
+pub fn assert_zero_knowledge<P: SigmaProtocol>(protocol: &P, witness: &P::Witness)
+where
+    P::Witness: Clone,
+    P::Response: Serialize,
+{
+    let mut rng = rand::thread_rng();
+    let real = real_transcripts(protocol, witness, &mut rng, SAMPLES);
+    let simulated = simulated_transcripts(protocol, &mut rng, SAMPLES);
+    let distance = statistical_distance(&real, &simulated);
+    assert!(
+        distance < distance_threshold(SAMPLES),
+        "real and simulated transcripts are {distance} apart"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+
+    use crate::pok::{
+        error::PokError,
+        linear_sigma::SigmaChallenge,
+        or_proof::OrWitness,
+        schnorr::{SchnorrProof, SchnorrProverState},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_zero_knowledge() {
+        let mut rng = rand::thread_rng();
+
+        // 0. Schnorr's protocol
+        let witness = Scalar::random(&mut rng);
+        let schnorr = SchnorrProof::new(RistrettoPoint::mul_base(&witness));
+        assert_zero_knowledge(&schnorr, &witness);
+
+        // 1. An OR proof, whose real branch must be indistinguishable from the simulated one
+        let or = schnorr.or(SchnorrProof::new(RistrettoPoint::random(&mut rng)));
+        assert_zero_knowledge(&or, &OrWitness::Left(witness));
+    }
+
+    /// Schnorr's protocol with a simulator that always responds with zero; its transcripts verify,
+    /// but are easy to tell apart from real ones
+    struct BrokenSimulator(SchnorrProof);
+
+    impl SigmaProtocol for BrokenSimulator {
+        type Witness = Scalar;
+        type Statement = RistrettoPoint;
+        type Commitment = RistrettoPoint;
+        type Response = Scalar;
+        type ProverState = SchnorrProverState;
+
+        fn generate_commitment(
+            &self,
+            rng: &mut dyn CryptoRngCore,
+            witness: Scalar,
+        ) -> Result<(RistrettoPoint, SchnorrProverState), PokError> {
+            self.0.generate_commitment(rng, witness)
+        }
+        fn serialize_commitment(&self, prover_commitment: &RistrettoPoint, buf: &mut Vec<u8>) {
+            self.0.serialize_commitment(prover_commitment, buf)
+        }
+        fn serialize_witness_statement(&self) -> Vec<u8> {
+            self.0.serialize_witness_statement()
+        }
+        fn protocol_label(&self) -> Vec<u8> {
+            self.0.protocol_label()
+        }
+        fn generate_response_to_challenge(
+            &self,
+            prover_state: SchnorrProverState,
+            random_challenge: SigmaChallenge,
+        ) -> Scalar {
+            self.0
+                .generate_response_to_challenge(prover_state, random_challenge)
+        }
+        fn verify_response_to_challenge(
+            &self,
+            prover_commitment: RistrettoPoint,
+            random_challenge: SigmaChallenge,
+            prover_response_to_challenge: Scalar,
+        ) -> bool {
+            self.0.verify_response_to_challenge(
+                prover_commitment,
+                random_challenge,
+                prover_response_to_challenge,
+            )
+        }
+        fn simulate_prover_responses(
+            &self,
+            _rng: &mut dyn CryptoRngCore,
+            random_challenge: SigmaChallenge,
+        ) -> (RistrettoPoint, Scalar) {
+            // g^0 = t * y^c for t = y^-c
+            (-(random_challenge * self.0.witness_statement), Scalar::ZERO)
+        }
+    }
+
+    #[test]
+    fn test_zero_knowledge_catches_broken_simulator() {
+        let mut rng = rand::thread_rng();
+        let witness = Scalar::random(&mut rng);
+        let broken = BrokenSimulator(SchnorrProof::new(RistrettoPoint::mul_base(&witness)));
+
+        // 0. The simulated transcripts verify, but the responses are all zero
+        let real = real_transcripts(&broken, &witness, &mut rng, SAMPLES);
+        let simulated = simulated_transcripts(&broken, &mut rng, SAMPLES);
+        assert!(statistical_distance(&real, &simulated) > distance_threshold(SAMPLES));
+
+        // 1. Transcripts of different lengths are as far apart as can be
+        assert_eq!(statistical_distance(&real, &[vec![0u8; 1]]), 1.0);
+    }
+}
@@ -0,0 +1,573 @@
+//! Sigma Protocol for k-out-of-n Sigma Protocols.
+//!
+//! Cf. [CDS94]. The challenges of the n statements are the evaluations f(1),...,f(n) of a
+//! polynomial f of degree n-k with f(0) = c. The prover picks the challenges of n-k statements it
+//! has no witness for and simulates them; those n-k points and f(0) = c fix f, and with it the
+//! challenges of the k statements it proves for real. f is over the scalars whatever the
+//! `ChallengeLength`, which is only passed on to the statements.
+//!
+//! `SigmaLeaf` lets Schnorr and Chaum-Pedersen statements be mixed in one composition.
+//!
+//! [CDS94]: https://link.springer.com/content/pdf/10.1007/3-540-48658-5_19.pdf
+
+use alloc::{vec, vec::Vec};
+use core::fmt;
+
+use curve25519_dalek::scalar::Scalar;
+use rand_core::CryptoRngCore;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use subtle::Choice;
+
+use crate::pok::{
+    chaum_pedersen::{ChaumPedersenProof, ChaumPedersenProverCommitment},
+    domain,
+    error::PokError,
+    linear_sigma::{ChallengeLength, DynSigmaProtocol, SigmaProtocol},
+    schnorr::{SchnorrProof, SchnorrProverCommitment, SchnorrProverState},
+};
+
+/// the prover's witnesses, at least k of which must be known
+pub type ThresholdWitness<Witness> = Vec<Option<Witness>>;
+
+/// the statement the witnesses are used to prove, (R_1,...,R_n)
+pub type ThresholdWitnessStatement<WitnessStatement> = Vec<WitnessStatement>;
+
+/// the prover's commitment, (t_1,...,t_n)
+pub type ThresholdProverCommitment<ProverCommitment> = Vec<ProverCommitment>;
+
+/// the verifier's challenge, denoted by c
+pub type ThresholdVerifierChallenge = Scalar;
+
+/// the prover's response, the coefficients (f_1,...,f_{n-k}) of f (f_0 = c), and (z_1,...,z_n)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ThresholdProverResponse<ProverResponse> {
+    pub(crate) coefficients: Vec<Scalar>,
+    pub(crate) responses: Vec<ProverResponse>,
+}
+
+/// the prover's state for one statement between its commitment and its response
+pub enum ThresholdStatementState<ProverState, ProverResponse> {
+    /// the state of a statement proven with a witness
+    Real(ProverState),
+    /// the challenge and response of a simulated statement
+    Simulated {
+        challenge: ThresholdVerifierChallenge,
+        response: ProverResponse,
+    },
+}
+
+/// Prints a placeholder, since the variant reveals which statements the prover knows witnesses
+/// for.
+impl<ProverState, ProverResponse> fmt::Debug
+    for ThresholdStatementState<ProverState, ProverResponse>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ThresholdStatementState")
+            .field(&format_args!("<redacted>"))
+            .finish()
+    }
+}
+
+/// the prover's state between its commitment and its response, one entry per statement
+pub type ThresholdProverState<ProverState, ProverResponse> =
+    Vec<ThresholdStatementState<ProverState, ProverResponse>>;
+
+/// Interpolates the coefficients (lowest degree first) of the polynomial through `points`.
+fn interpolate(points: &[(Scalar, Scalar)]) -> Vec<Scalar> {
+    let mut coefficients = vec![Scalar::ZERO; points.len()];
+    for (j, (x_j, y_j)) in points.iter().enumerate() {
+        // Expand the Lagrange basis polynomial prod_{m != j} (x - x_m) / (x_j - x_m)
+        let mut basis = vec![Scalar::ONE];
+        let mut denominator = Scalar::ONE;
+        for (m, (x_m, _)) in points.iter().enumerate() {
+            if m == j {
+                continue;
+            }
+            let mut product = vec![Scalar::ZERO; basis.len() + 1];
+            for (degree, coefficient) in basis.iter().enumerate() {
+                product[degree + 1] += coefficient;
+                product[degree] -= coefficient * x_m;
+            }
+            basis = product;
+            denominator *= x_j - x_m;
+        }
+        let scale = y_j * denominator.invert();
+        for (coefficient, basis_coefficient) in coefficients.iter_mut().zip(basis) {
+            *coefficient += scale * basis_coefficient;
+        }
+    }
+    coefficients
+}
+
+/// Evaluates the polynomial f(x) = c + f_1 x + ... + f_{n-k} x^{n-k} at the index of statement i.
+fn challenge_for_statement(
+    random_challenge: ThresholdVerifierChallenge,
+    coefficients: &[Scalar],
+    i: usize,
+) -> ThresholdVerifierChallenge {
+    let x = Scalar::from(i as u64 + 1);
+    let higher_terms = coefficients
+        .iter()
+        .rev()
+        .fold(Scalar::ZERO, |acc, coefficient| acc * x + coefficient);
+    random_challenge + higher_terms * x
+}
+
+pub struct ThresholdProof<Witness, Statement, Commitment, Response, ProverState> {
+    /// k, the number of statements the prover must have witnesses for
+    pub threshold: usize,
+    pub protocols: Vec<DynSigmaProtocol<Witness, Statement, Commitment, Response, ProverState>>,
+}
+
+impl<Witness, Statement, Commitment, Response, ProverState> SigmaProtocol
+    for ThresholdProof<Witness, Statement, Commitment, Response, ProverState>
+{
+    type Witness = ThresholdWitness<Witness>;
+    type Statement = ThresholdWitnessStatement<Statement>;
+    type Commitment = ThresholdProverCommitment<Commitment>;
+    type Response = ThresholdProverResponse<Response>;
+    type ProverState = ThresholdProverState<ProverState, Response>;
+
+    fn generate_commitment(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        witness: ThresholdWitness<Witness>,
+    ) -> Result<(Self::Commitment, Self::ProverState), PokError> {
+        let n = self.protocols.len();
+        if !(1..=n).contains(&self.threshold) {
+            return Err(PokError::InvalidThreshold {
+                threshold: self.threshold,
+                len: n,
+            });
+        }
+        if witness.len() != n {
+            return Err(PokError::LengthMismatch {
+                expected: n,
+                actual: witness.len(),
+            });
+        }
+        let known = witness.iter().filter(|witness| witness.is_some()).count();
+        if known < self.threshold {
+            return Err(PokError::InsufficientWitnesses {
+                threshold: self.threshold,
+                actual: known,
+            });
+        }
+
+        // Prove the first k statements we have witnesses for, and simulate the rest
+        let mut real_remaining = self.threshold;
+        let mut states = Vec::with_capacity(n);
+        let mut commitments = Vec::with_capacity(n);
+        for (protocol, witness) in self.protocols.iter().zip(witness) {
+            match witness {
+                Some(witness) if real_remaining > 0 => {
+                    real_remaining -= 1;
+                    let (commitment, state) = protocol.generate_commitment(rng, witness)?;
+                    commitments.push(commitment);
+                    states.push(ThresholdStatementState::Real(state));
+                }
+                _ => {
+                    let challenge = Scalar::random(rng);
+                    let (simulated_commitment, response) =
+                        protocol.simulate_prover_responses(rng, challenge);
+                    commitments.push(simulated_commitment);
+                    states.push(ThresholdStatementState::Simulated {
+                        challenge,
+                        response,
+                    });
+                }
+            }
+        }
+        Ok((commitments, states))
+    }
+
+    fn serialize_commitment(&self, commitment: &Self::Commitment, buf: &mut Vec<u8>) {
+        for (protocol, commitment) in self.protocols.iter().zip(commitment) {
+            protocol.serialize_commitment(commitment, buf);
+        }
+    }
+
+    fn protocol_label(&self) -> Vec<u8> {
+        domain::composite_label(
+            domain::THRESHOLD_LABEL,
+            self.protocols
+                .iter()
+                .map(|protocol| protocol.protocol_label()),
+        )
+    }
+
+    fn serialize_witness_statement(&self) -> Vec<u8> {
+        // The threshold is part of the statement
+        let mut buf = (self.threshold as u64).to_be_bytes().to_vec();
+        for protocol in &self.protocols {
+            buf.extend(protocol.serialize_witness_statement());
+        }
+        buf
+    }
+
+    fn generate_response_to_challenge(
+        &self,
+        prover_state: Self::ProverState,
+        random_challenge: ThresholdVerifierChallenge,
+    ) -> Self::Response {
+        // f(0) = c and the n-k simulated challenges fix f
+        let points: Vec<(Scalar, Scalar)> = core::iter::once((Scalar::ZERO, random_challenge))
+            .chain(
+                prover_state
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, state)| match state {
+                        ThresholdStatementState::Simulated { challenge, .. } => {
+                            Some((Scalar::from(i as u64 + 1), *challenge))
+                        }
+                        ThresholdStatementState::Real(_) => None,
+                    }),
+            )
+            .collect();
+        let coefficients = interpolate(&points).split_off(1);
+
+        let responses = prover_state
+            .into_iter()
+            .zip(&self.protocols)
+            .enumerate()
+            .map(|(i, (state, protocol))| match state {
+                ThresholdStatementState::Simulated { response, .. } => response,
+                ThresholdStatementState::Real(state) => protocol.generate_response_to_challenge(
+                    state,
+                    challenge_for_statement(random_challenge, &coefficients, i),
+                ),
+            })
+            .collect();
+        ThresholdProverResponse {
+            coefficients,
+            responses,
+        }
+    }
+
+    fn verify_response_to_challenge(
+        &self,
+        prover_commitment: Self::Commitment,
+        random_challenge: ThresholdVerifierChallenge,
+        prover_response_to_challenge: Self::Response,
+    ) -> bool {
+        let n = self.protocols.len();
+        let ThresholdProverResponse {
+            coefficients,
+            responses,
+        } = prover_response_to_challenge;
+        // The degree of f is what enforces the threshold, so it must be checked
+        if !(1..=n).contains(&self.threshold)
+            || prover_commitment.len() != n
+            || coefficients.len() != n - self.threshold
+            || responses.len() != n
+        {
+            return false;
+        }
+
+        self.protocols
+            .iter()
+            .zip(prover_commitment)
+            .zip(responses)
+            .enumerate()
+            .fold(
+                Choice::from(1),
+                |verified, (i, ((protocol, commitment), response))| {
+                    let challenge = challenge_for_statement(random_challenge, &coefficients, i);
+                    let result =
+                        protocol.verify_response_to_challenge(commitment, challenge, response);
+                    verified & Choice::from(u8::from(result))
+                },
+            )
+            .into()
+    }
+
+    fn simulate_prover_responses(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        random_challenge: ThresholdVerifierChallenge,
+    ) -> (Self::Commitment, Self::Response) {
+        let coefficients: Vec<Scalar> = (self.threshold..self.protocols.len())
+            .map(|_| Scalar::random(rng))
+            .collect();
+        let (commitments, responses) = self
+            .protocols
+            .iter()
+            .enumerate()
+            .map(|(i, protocol)| {
+                protocol.simulate_prover_responses(
+                    rng,
+                    challenge_for_statement(random_challenge, &coefficients, i),
+                )
+            })
+            .unzip();
+        (
+            commitments,
+            ThresholdProverResponse {
+                coefficients,
+                responses,
+            },
+        )
+    }
+
+    fn set_challenge_length(&mut self, challenge_length: ChallengeLength) {
+        for protocol in &mut self.protocols {
+            protocol.set_challenge_length(challenge_length);
+        }
+    }
+}
+
+//
+// Leaves that let Schnorr and Chaum-Pedersen statements share one composition.
+//
+
+/// the commitment of a Schnorr or Chaum-Pedersen leaf
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SigmaLeafCommitment {
+    Schnorr(SchnorrProverCommitment),
+    ChaumPedersen(ChaumPedersenProverCommitment),
+}
+
+// Leaves are boxed as trait objects anyway, so the variants are not boxed again
+#[allow(clippy::large_enum_variant)]
+pub enum SigmaLeaf {
+    Schnorr(SchnorrProof),
+    ChaumPedersen(ChaumPedersenProof),
+}
+
+impl SigmaProtocol for SigmaLeaf {
+    type Witness = Scalar;
+    type Statement = ();
+    type Commitment = SigmaLeafCommitment;
+    type Response = Scalar;
+    // Schnorr and Chaum-Pedersen keep the same prover state
+    type ProverState = SchnorrProverState;
+
+    fn generate_commitment(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        witness: Scalar,
+    ) -> Result<(SigmaLeafCommitment, SchnorrProverState), PokError> {
+        match self {
+            SigmaLeaf::Schnorr(protocol) => {
+                let (commitment, state) = protocol.generate_commitment(rng, witness)?;
+                Ok((SigmaLeafCommitment::Schnorr(commitment), state))
+            }
+            SigmaLeaf::ChaumPedersen(protocol) => {
+                let (commitment, state) = protocol.generate_commitment(rng, witness)?;
+                Ok((SigmaLeafCommitment::ChaumPedersen(commitment), state))
+            }
+        }
+    }
+
+    fn serialize_commitment(&self, commitment: &SigmaLeafCommitment, buf: &mut Vec<u8>) {
+        // The same bytes as SchnorrProof and ChaumPedersenProof produce
+        match commitment {
+            SigmaLeafCommitment::Schnorr(commitment) => {
+                buf.extend(commitment.compress().as_bytes())
+            }
+            SigmaLeafCommitment::ChaumPedersen(commitment) => {
+                buf.extend(commitment.v_t.compress().as_bytes());
+                buf.extend(commitment.w_t.compress().as_bytes());
+            }
+        }
+    }
+
+    fn protocol_label(&self) -> Vec<u8> {
+        match self {
+            SigmaLeaf::Schnorr(protocol) => protocol.protocol_label(),
+            SigmaLeaf::ChaumPedersen(protocol) => protocol.protocol_label(),
+        }
+    }
+
+    fn serialize_witness_statement(&self) -> Vec<u8> {
+        match self {
+            SigmaLeaf::Schnorr(protocol) => protocol.serialize_witness_statement(),
+            SigmaLeaf::ChaumPedersen(protocol) => protocol.serialize_witness_statement(),
+        }
+    }
+
+    fn generate_response_to_challenge(
+        &self,
+        prover_state: SchnorrProverState,
+        random_challenge: Scalar,
+    ) -> Scalar {
+        match self {
+            SigmaLeaf::Schnorr(protocol) => {
+                protocol.generate_response_to_challenge(prover_state, random_challenge)
+            }
+            SigmaLeaf::ChaumPedersen(protocol) => {
+                protocol.generate_response_to_challenge(prover_state, random_challenge)
+            }
+        }
+    }
+
+    fn verify_response_to_challenge(
+        &self,
+        prover_commitment: SigmaLeafCommitment,
+        random_challenge: Scalar,
+        prover_response_to_challenge: Scalar,
+    ) -> bool {
+        match (self, prover_commitment) {
+            (SigmaLeaf::Schnorr(protocol), SigmaLeafCommitment::Schnorr(commitment)) => protocol
+                .verify_response_to_challenge(
+                    commitment,
+                    random_challenge,
+                    prover_response_to_challenge,
+                ),
+            (
+                SigmaLeaf::ChaumPedersen(protocol),
+                SigmaLeafCommitment::ChaumPedersen(commitment),
+            ) => protocol.verify_response_to_challenge(
+                commitment,
+                random_challenge,
+                prover_response_to_challenge,
+            ),
+            _ => false,
+        }
+    }
+
+    fn simulate_prover_responses(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        random_challenge: Scalar,
+    ) -> (SigmaLeafCommitment, Scalar) {
+        match self {
+            SigmaLeaf::Schnorr(protocol) => {
+                let (commitment, response) =
+                    protocol.simulate_prover_responses(rng, random_challenge);
+                (SigmaLeafCommitment::Schnorr(commitment), response)
+            }
+            SigmaLeaf::ChaumPedersen(protocol) => {
+                let (commitment, response) =
+                    protocol.simulate_prover_responses(rng, random_challenge);
+                (SigmaLeafCommitment::ChaumPedersen(commitment), response)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use curve25519_dalek::{constants::RISTRETTO_BASEPOINT_POINT, ristretto::RistrettoPoint};
+
+    use crate::pok::{
+        chaum_pedersen::ChaumPedersenWitnessStatement, test_macros::test_sigma_protocol,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_interpolate() {
+        // f(x) = 3 + 2x + x^2
+        let f = |x: u64| Scalar::from(3 + 2 * x + x * x);
+        let points: Vec<_> = [0u64, 4, 7]
+            .iter()
+            .map(|&x| (Scalar::from(x), f(x)))
+            .collect();
+        assert_eq!(
+            interpolate(&points),
+            vec![Scalar::from(3u64), Scalar::from(2u64), Scalar::ONE]
+        );
+    }
+
+    #[test]
+    fn test_threshold_schnorr_and_chaum_pedersen() {
+        let mut rng = rand::thread_rng();
+        let g = RISTRETTO_BASEPOINT_POINT;
+
+        // 0. Fix four witnesses, for two Schnorr and two Chaum-Pedersen statements
+        let witnesses: Vec<Scalar> = (0..4).map(|_| Scalar::random(&mut rng)).collect();
+        let u = RistrettoPoint::random(&mut rng);
+        let schnorr_statement = |witness: Scalar| witness * g;
+        let chaum_pedersen_statement = |witness: Scalar| ChaumPedersenWitnessStatement {
+            u,
+            v: witness * g,
+            w: witness * u,
+        };
+        let new_protocols = || -> Vec<DynSigmaProtocol<_, _, _, _, _>> {
+            vec![
+                Box::new(SigmaLeaf::Schnorr(SchnorrProof::new(schnorr_statement(
+                    witnesses[0],
+                )))),
+                Box::new(SigmaLeaf::ChaumPedersen(ChaumPedersenProof::new(
+                    chaum_pedersen_statement(witnesses[1]),
+                ))),
+                Box::new(SigmaLeaf::Schnorr(SchnorrProof::new(schnorr_statement(
+                    witnesses[2],
+                )))),
+                Box::new(SigmaLeaf::ChaumPedersen(ChaumPedersenProof::new(
+                    chaum_pedersen_statement(witnesses[3]),
+                ))),
+            ]
+        };
+
+        for threshold in 1..=4 {
+            // 1. Create a k-of-n proof
+            let threshold_proof = ThresholdProof {
+                threshold,
+                protocols: new_protocols(),
+            };
+
+            // 2. Run tests knowing only the last k witnesses
+            let known_witnesses: Vec<Option<Scalar>> = witnesses
+                .iter()
+                .enumerate()
+                .map(|(i, witness)| (i >= 4 - threshold).then_some(*witness))
+                .collect();
+            test_sigma_protocol!(known_witnesses.clone(), threshold_proof);
+
+            // 3. A response for a lower threshold is rejected
+            if threshold > 1 {
+                let lower_threshold_proof = ThresholdProof {
+                    threshold: threshold - 1,
+                    protocols: new_protocols(),
+                };
+                let (prover_commitment, prover_state) = lower_threshold_proof
+                    .generate_commitment(&mut rng, known_witnesses.clone())
+                    .unwrap();
+                let random_challenge = threshold_proof.generate_random_challenge(&mut rng);
+                let prover_response = lower_threshold_proof
+                    .generate_response_to_challenge(prover_state, random_challenge);
+                assert!(!threshold_proof.verify_response_to_challenge(
+                    prover_commitment,
+                    random_challenge,
+                    prover_response,
+                ));
+            }
+        }
+
+        // 4. Too few witnesses, or an out of range threshold, are errors, not panics
+        let threshold_proof = ThresholdProof {
+            threshold: 3,
+            protocols: new_protocols(),
+        };
+        assert_eq!(
+            threshold_proof
+                .generate_commitment(
+                    &mut rng,
+                    vec![Some(witnesses[0]), None, None, Some(witnesses[3])]
+                )
+                .err(),
+            Some(PokError::InsufficientWitnesses {
+                threshold: 3,
+                actual: 2
+            })
+        );
+        let threshold_proof = ThresholdProof {
+            threshold: 5,
+            protocols: new_protocols(),
+        };
+        assert_eq!(
+            threshold_proof
+                .generate_commitment(&mut rng, witnesses.iter().copied().map(Some).collect())
+                .err(),
+            Some(PokError::InvalidThreshold {
+                threshold: 5,
+                len: 4
+            })
+        );
+    }
+}
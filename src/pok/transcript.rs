@@ -0,0 +1,248 @@
+//! Recorded Transcripts of Interactive Proofs
+//!
+//! An interactive run of a sigma protocol leaves nothing behind to show a third party: the
+//! verifier accepted or not. `TranscriptRecorder` wraps a protocol and records every
+//! (commitment, challenge, response) it is asked to verify, along with the protocol label and the
+//! serialized statement, so that an auditor can later replay the verification with
+//! `Transcript::replay`.
+//!
+//! A transcript only shows that the prover answered the recorded challenges; since the verifier
+//! could have simulated it (cf. `simulate_prover_responses`), it convinces an auditor only if the
+//! auditor trusts the verifier to have picked the challenges after the commitments.
+
+use std::sync::Mutex;
+
+use rand_core::CryptoRngCore;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::pok::{
+    error::PokError,
+    linear_sigma::{ChallengeLength, SigmaChallenge, SigmaProtocol},
+};
+
+/// one round of an interactive proof, (t,c,z)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TranscriptRound<Commitment, Response> {
+    pub commitment: Commitment,
+    pub challenge: SigmaChallenge,
+    pub response: Response,
+}
+
+/// the rounds recorded for one statement
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Transcript<Commitment, Response> {
+    pub protocol_label: Vec<u8>,
+    pub statement: Vec<u8>,
+    pub rounds: Vec<TranscriptRound<Commitment, Response>>,
+}
+
+impl<Commitment: Clone, Response: Clone> Transcript<Commitment, Response> {
+    /// an empty transcript for the statement of `protocol`
+    pub fn new<P>(protocol: &P) -> Self
+    where
+        P: SigmaProtocol<Commitment = Commitment, Response = Response>,
+    {
+        Transcript {
+            protocol_label: protocol.protocol_label(),
+            statement: protocol.serialize_witness_statement(),
+            rounds: Vec::new(),
+        }
+    }
+
+    pub fn record(
+        &mut self,
+        commitment: Commitment,
+        challenge: SigmaChallenge,
+        response: Response,
+    ) {
+        self.rounds.push(TranscriptRound {
+            commitment,
+            challenge,
+            response,
+        });
+    }
+
+    /// Checks that the transcript was recorded for the statement of `protocol`, and that every
+    /// round verifies.
+    pub fn replay<P>(&self, protocol: &P) -> bool
+    where
+        P: SigmaProtocol<Commitment = Commitment, Response = Response>,
+    {
+        self.protocol_label == protocol.protocol_label()
+            && self.statement == protocol.serialize_witness_statement()
+            && self.rounds.iter().all(|round| {
+                protocol.verify_response_to_challenge(
+                    round.commitment.clone(),
+                    round.challenge,
+                    round.response.clone(),
+                )
+            })
+    }
+}
+
+/// A sigma protocol that records a `Transcript` of everything it verifies.
+pub struct TranscriptRecorder<Protocol: SigmaProtocol> {
+    pub protocol: Protocol,
+    transcript: Mutex<Transcript<Protocol::Commitment, Protocol::Response>>,
+}
+
+impl<Protocol: SigmaProtocol> TranscriptRecorder<Protocol>
+where
+    Protocol::Commitment: Clone,
+    Protocol::Response: Clone,
+{
+    pub fn new(protocol: Protocol) -> Self {
+        let transcript = Mutex::new(Transcript::new(&protocol));
+        TranscriptRecorder {
+            protocol,
+            transcript,
+        }
+    }
+
+    /// the rounds recorded so far
+    pub fn transcript(&self) -> Transcript<Protocol::Commitment, Protocol::Response> {
+        self.transcript.lock().unwrap().clone()
+    }
+}
+
+impl<Protocol: SigmaProtocol> SigmaProtocol for TranscriptRecorder<Protocol>
+where
+    Protocol::Commitment: Clone,
+    Protocol::Response: Clone,
+{
+    type Witness = Protocol::Witness;
+    type Statement = Protocol::Statement;
+    type Commitment = Protocol::Commitment;
+    type Response = Protocol::Response;
+    type ProverState = Protocol::ProverState;
+
+    fn generate_commitment(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        witness: Self::Witness,
+    ) -> Result<(Self::Commitment, Self::ProverState), PokError> {
+        self.protocol.generate_commitment(rng, witness)
+    }
+    fn serialize_commitment(&self, prover_commitment: &Self::Commitment, buf: &mut Vec<u8>) {
+        self.protocol.serialize_commitment(prover_commitment, buf)
+    }
+    fn serialize_witness_statement(&self) -> Vec<u8> {
+        self.protocol.serialize_witness_statement()
+    }
+    fn protocol_label(&self) -> Vec<u8> {
+        self.protocol.protocol_label()
+    }
+    fn write_protocol_label(&self, buf: &mut Vec<u8>) {
+        self.protocol.write_protocol_label(buf)
+    }
+    fn write_witness_statement(&self, buf: &mut Vec<u8>) {
+        self.protocol.write_witness_statement(buf)
+    }
+    fn generate_response_to_challenge(
+        &self,
+        prover_state: Self::ProverState,
+        random_challenge: SigmaChallenge,
+    ) -> Self::Response {
+        self.protocol
+            .generate_response_to_challenge(prover_state, random_challenge)
+    }
+    fn generate_random_challenge(&self, rng: &mut dyn CryptoRngCore) -> SigmaChallenge {
+        self.protocol.generate_random_challenge(rng)
+    }
+    fn verify_response_to_challenge(
+        &self,
+        prover_commitment: Self::Commitment,
+        random_challenge: SigmaChallenge,
+        prover_response_to_challenge: Self::Response,
+    ) -> bool {
+        self.transcript.lock().unwrap().record(
+            prover_commitment.clone(),
+            random_challenge,
+            prover_response_to_challenge.clone(),
+        );
+        self.protocol.verify_response_to_challenge(
+            prover_commitment,
+            random_challenge,
+            prover_response_to_challenge,
+        )
+    }
+    fn simulate_prover_responses(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        random_challenge: SigmaChallenge,
+    ) -> (Self::Commitment, Self::Response) {
+        self.protocol
+            .simulate_prover_responses(rng, random_challenge)
+    }
+    fn set_challenge_length(&mut self, challenge_length: ChallengeLength) {
+        self.protocol.set_challenge_length(challenge_length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+
+    use crate::pok::{
+        or_proof::OrWitness, schnorr::SchnorrProof, test_macros::test_sigma_protocol,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_transcript_recorder() {
+        let mut rng = rand::thread_rng();
+
+        // 0. Record a real and a simulated run of an OR proof
+        let witness = Scalar::random(&mut rng);
+        let statement = RistrettoPoint::mul_base(&witness);
+        let other_statement = RistrettoPoint::random(&mut rng);
+        let or = || SchnorrProof::new(statement).or(SchnorrProof::new(other_statement));
+        let recorder = TranscriptRecorder::new(or());
+        test_sigma_protocol!(OrWitness::Left(witness), recorder);
+        let transcript = recorder.transcript();
+        assert_eq!(transcript.rounds.len(), 2);
+
+        // 1. Persist the transcript (with serde), load it back, and replay it against the same
+        // statement
+        #[cfg(feature = "serde")]
+        let decoded: Transcript<_, _> =
+            bincode::deserialize(&bincode::serialize(&transcript).unwrap()).unwrap();
+        #[cfg(not(feature = "serde"))]
+        let decoded = transcript.clone();
+        assert_eq!(transcript, decoded);
+        assert!(decoded.replay(&or()));
+
+        // 2. It does not replay against another statement, or with a tampered challenge
+        let swapped = SchnorrProof::new(other_statement).or(SchnorrProof::new(statement));
+        assert!(!decoded.replay(&swapped));
+        let mut tampered = decoded;
+        tampered.rounds[0].challenge += Scalar::ONE;
+        assert!(!tampered.replay(&or()));
+    }
+
+    #[test]
+    fn test_transcript_records_failures() {
+        let mut rng = rand::thread_rng();
+
+        // 0. A response that fails verification is still recorded, and fails on replay
+        let recorder = TranscriptRecorder::new(SchnorrProof::new(RistrettoPoint::random(&mut rng)));
+        let witness = Scalar::random(&mut rng);
+        let (commitment, _) = recorder.generate_commitment(&mut rng, witness).unwrap();
+        let challenge = recorder.generate_random_challenge(&mut rng);
+        assert!(!recorder.verify_response_to_challenge(
+            commitment,
+            challenge,
+            Scalar::random(&mut rng)
+        ));
+        let transcript = recorder.transcript();
+        assert_eq!(transcript.rounds.len(), 1);
+        assert!(!transcript.replay(&recorder.protocol));
+
+        // 1. An empty transcript replays as long as the statement matches
+        assert!(Transcript::new(&recorder.protocol).replay(&recorder.protocol));
+    }
+}
@@ -0,0 +1,140 @@
+//! Verifiable Random Function over Ristretto
+//!
+//! In the style of ECVRF [RFC9381]: the input alpha is hashed to a point H, and the holder of the
+//! secret key x computes Gamma = x * H, with a DLEQ proof (cf. `dleq`) that log_g(Y) = log_H(Gamma)
+//! for the public key Y = x * g. The output is a hash of Gamma, so it is unique for the key and the
+//! input, and unpredictable without x.
+//!
+//! Unlike [RFC9381], the proof's nonce is random rather than derived from the key and the input;
+//! proofs differ between runs, but outputs do not.
+//!
+//! [RFC9381]: https://www.rfc-editor.org/rfc/rfc9381
+
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT, ristretto::RistrettoPoint, scalar::Scalar,
+    traits::Identity,
+};
+use rand_core::CryptoRngCore;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
+
+use crate::pok::{
+    dleq::{DleqProof, DleqProverCommitment, DleqWitnessStatement},
+    domain,
+    fiat_shamir::{FiatShamir, FiatShamirSecretKey, FiatShamirSignature, SignatureScheme},
+    linear_sigma::SigmaProtocol,
+};
+
+/// the output, beta in [RFC9381]
+pub type VrfOutput = [u8; 64];
+
+/// the proof, Gamma and the DLEQ proof for it
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct VrfProof {
+    pub(crate) gamma: RistrettoPoint,
+    pub(crate) dleq: FiatShamirSignature<DleqProverCommitment, Scalar>,
+}
+
+/// H, the input hashed to a point, bound to the public key
+fn hash_to_curve(public_key: &RistrettoPoint, input: &[u8]) -> RistrettoPoint {
+    let mut hasher = Sha512::new();
+    domain::absorb(&mut hasher, domain::VRF_HASH_TO_CURVE_LABEL);
+    domain::absorb(&mut hasher, public_key.compress().as_bytes());
+    domain::absorb(&mut hasher, input);
+    RistrettoPoint::from_hash(hasher)
+}
+
+/// the DLEQ signature scheme for log_g(Y) = log_H(Gamma)
+fn dleq(public_key: RistrettoPoint, input: &[u8], gamma: RistrettoPoint) -> FiatShamir<DleqProof> {
+    DleqProof::new(DleqWitnessStatement {
+        g1: RISTRETTO_BASEPOINT_POINT,
+        p1: public_key,
+        g2: hash_to_curve(&public_key, input),
+        p2: gamma,
+    })
+    .fiat_shamir()
+}
+
+/// Computes the output for `input`, and the proof that it is the right one.
+pub fn prove(
+    rng: &mut dyn CryptoRngCore,
+    secret_key: &Scalar,
+    input: &[u8],
+) -> (VrfOutput, VrfProof) {
+    let public_key = RistrettoPoint::mul_base(secret_key);
+    let gamma = secret_key * hash_to_curve(&public_key, input);
+    let dleq = dleq(public_key, input, gamma)
+        .sign(
+            rng,
+            FiatShamirSecretKey {
+                witness: *secret_key,
+            },
+            input,
+        )
+        .expect("a DLEQ witness always fits a DLEQ statement");
+    let proof = VrfProof { gamma, dleq };
+    (output(&proof), proof)
+}
+
+/// Returns the output the proof claims, without verifying it.
+pub fn output(proof: &VrfProof) -> VrfOutput {
+    let mut hasher = Sha512::new();
+    domain::absorb(&mut hasher, domain::VRF_OUTPUT_LABEL);
+    domain::absorb(&mut hasher, proof.gamma.compress().as_bytes());
+    hasher.finalize().into()
+}
+
+/// Returns the output for `input` if the proof is valid for `public_key`.
+pub fn verify(public_key: &RistrettoPoint, input: &[u8], proof: &VrfProof) -> Option<VrfOutput> {
+    // With x = 0, every input would map to the same output
+    if *public_key == RistrettoPoint::identity() {
+        return None;
+    }
+    dleq(*public_key, input, proof.gamma)
+        .verify(input, proof.dleq)
+        .then(|| output(proof))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vrf() {
+        let mut rng = rand::thread_rng();
+        let secret_key = Scalar::random(&mut rng);
+        let public_key = RistrettoPoint::mul_base(&secret_key);
+
+        // 0. A proof verifies, and yields its output
+        let (output0, proof0) = prove(&mut rng, &secret_key, b"epoch 0");
+        assert_eq!(verify(&public_key, b"epoch 0", &proof0), Some(output0));
+
+        // 1. Proofs differ between runs, but the output is unique for the key and the input
+        let (output0_again, proof0_again) = prove(&mut rng, &secret_key, b"epoch 0");
+        assert_ne!(proof0, proof0_again);
+        assert_eq!(output0, output0_again);
+        let (output1, _) = prove(&mut rng, &secret_key, b"epoch 1");
+        assert_ne!(output0, output1);
+
+        // 2. The proof is bound to the input and the key
+        assert_eq!(verify(&public_key, b"epoch 1", &proof0), None);
+        let other_public_key = RistrettoPoint::random(&mut rng);
+        assert_eq!(verify(&other_public_key, b"epoch 0", &proof0), None);
+
+        // 3. A different Gamma, and so a different output, is rejected
+        let tampered = VrfProof {
+            gamma: proof0.gamma + RISTRETTO_BASEPOINT_POINT,
+            ..proof0
+        };
+        assert_eq!(verify(&public_key, b"epoch 0", &tampered), None);
+
+        // 4. So is the identity as a public key
+        let (_, zero_proof) = prove(&mut rng, &Scalar::ZERO, b"epoch 0");
+        assert_eq!(
+            verify(&RistrettoPoint::identity(), b"epoch 0", &zero_proof),
+            None
+        );
+    }
+}
@@ -0,0 +1,484 @@
+//! Feldman and Pedersen Verifiable Secret Sharing over Ristretto
+//!
+//! A dealer shares a secret scalar s among n parties such that any t of them can reconstruct it,
+//! and fewer learn nothing, as in Shamir's scheme: it picks a random polynomial
+//! f(x) = s + a_1 x + ... + a_{t-1} x^{t-1}, and gives party i the share f(i), for i = 1, ..., n.
+//! To let the parties check their shares, the dealer also publishes commitments to the
+//! coefficients:
+//!
+//! - Feldman [Fel87]: C_k = g^{a_k}, with a_0 = s. Share i is valid if g^{f(i)} = prod_k C_k^{i^k}.
+//!   C_0 = g^s is the public key of the shared secret, which is what threshold judging and DKG
+//!   need, but it reveals g^s.
+//! - Pedersen [Ped91]: C_k = g^{a_k} h^{b_k}, with a second random polynomial b and
+//!   h = `derive_generator(VSS_GENERATOR_LABEL)`. Party i also gets b(i), and checks
+//!   g^{f(i)} h^{b(i)} = prod_k C_k^{i^k}. The commitments hide s perfectly, e.g. for key backup.
+//!
+//! `reconstruct` interpolates the secret from any t valid shares, with `lagrange_coefficient`,
+//! which also combines values computed from shares "in the exponent".
+//!
+//! [Fel87]: https://doi.org/10.1109/SFCS.1987.4
+//! [Ped91]: https://doi.org/10.1007/3-540-46766-1_9
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT, ristretto::RistrettoPoint, scalar::Scalar,
+    traits::MultiscalarMul,
+};
+use rand_core::CryptoRngCore;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use subtle::{Choice, ConstantTimeEq};
+use zeroize::Zeroize;
+
+use crate::pok::domain::{derive_generator, VSS_GENERATOR_LABEL};
+
+/// Why sharing or reconstructing failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VssError {
+    /// The threshold is not between 1 and the number of shares.
+    InvalidThreshold { threshold: usize, shares: usize },
+    /// Fewer shares than the threshold were given.
+    InsufficientShares { threshold: usize, actual: usize },
+    /// A share has the index 0, the point the secret is at.
+    ZeroIndex,
+    /// Two shares have the same index.
+    DuplicateIndex(u32),
+    /// The index is not among the indices of the shares.
+    MissingIndex(u32),
+}
+
+impl fmt::Display for VssError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VssError::InvalidThreshold { threshold, shares } => {
+                write!(f, "invalid threshold {} for {} shares", threshold, shares)
+            }
+            VssError::InsufficientShares { threshold, actual } => {
+                write!(f, "expected at least {} shares, got {}", threshold, actual)
+            }
+            VssError::ZeroIndex => write!(f, "share index 0"),
+            VssError::DuplicateIndex(index) => write!(f, "duplicate share index {}", index),
+            VssError::MissingIndex(index) => {
+                write!(f, "share index {} not among the shares", index)
+            }
+        }
+    }
+}
+
+impl core::error::Error for VssError {}
+
+/// The share f(i) of party i. With the `zeroize` feature, the value is overwritten when the share
+/// is dropped, which is why it is `Clone` but not `Copy`.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Share {
+    pub index: u32,
+    pub value: Scalar,
+}
+
+/// A Pedersen share: the share f(i), and the blinding b(i).
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PedersenShare {
+    pub share: Share,
+    pub blinding: Scalar,
+}
+
+/// Compares the index and the value without branching on either.
+impl ConstantTimeEq for Share {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.index.ct_eq(&other.index) & self.value.ct_eq(&other.value)
+    }
+}
+
+/// `ct_eq`, so that comparing shares takes as long wherever they differ
+impl PartialEq for Share {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl Eq for Share {}
+
+/// Compares the share and the blinding without branching on either.
+impl ConstantTimeEq for PedersenShare {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.share.ct_eq(&other.share) & self.blinding.ct_eq(&other.blinding)
+    }
+}
+
+/// `ct_eq`, so that comparing shares takes as long wherever they differ
+impl PartialEq for PedersenShare {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl Eq for PedersenShare {}
+
+/// Prints the index only, so that shares cannot leak through logs.
+impl fmt::Debug for Share {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Share")
+            .field("index", &self.index)
+            .field("value", &format_args!("<redacted>"))
+            .finish()
+    }
+}
+
+/// Prints the index only, so that shares cannot leak through logs.
+impl fmt::Debug for PedersenShare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PedersenShare")
+            .field("share", &self.share)
+            .field("blinding", &format_args!("<redacted>"))
+            .finish()
+    }
+}
+
+/// Overwrites the value; the index is public.
+#[cfg(feature = "zeroize")]
+impl Zeroize for Share {
+    fn zeroize(&mut self) {
+        self.value.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for Share {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for Share {}
+
+/// Overwrites the blinding; the share zeroizes itself.
+#[cfg(feature = "zeroize")]
+impl Drop for PedersenShare {
+    fn drop(&mut self) {
+        self.blinding.zeroize();
+    }
+}
+
+/// The commitments C_k = g^{a_k} of a Feldman sharing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FeldmanCommitments(pub Vec<RistrettoPoint>);
+
+/// The commitments C_k = g^{a_k} h^{b_k} of a Pedersen sharing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PedersenCommitments(pub Vec<RistrettoPoint>);
+
+/// h, the second generator of the Pedersen commitments
+pub fn pedersen_generator() -> RistrettoPoint {
+    derive_generator(VSS_GENERATOR_LABEL)
+}
+
+/// prod_k C_k^{i^k}, the commitment to f(i)
+fn evaluate_commitments(commitments: &[RistrettoPoint], index: u32) -> RistrettoPoint {
+    let x = Scalar::from(index);
+    let powers: Vec<Scalar> = commitments
+        .iter()
+        .scan(Scalar::ONE, |power, _| {
+            let current = *power;
+            *power *= x;
+            Some(current)
+        })
+        .collect();
+    RistrettoPoint::multiscalar_mul(powers, commitments)
+}
+
+impl FeldmanCommitments {
+    /// The number of shares needed to reconstruct, the number of commitments.
+    pub fn threshold(&self) -> usize {
+        self.0.len()
+    }
+
+    /// g^s, the public key of the shared secret; `None` without commitments.
+    pub fn public_key(&self) -> Option<RistrettoPoint> {
+        self.0.first().copied()
+    }
+
+    /// Whether `share` is the share of its index. Without commitments, no share is, as the empty
+    /// product would accept any share of value 0.
+    pub fn verify(&self, share: &Share) -> bool {
+        !self.0.is_empty()
+            && share.index != 0
+            && RistrettoPoint::mul_base(&share.value) == evaluate_commitments(&self.0, share.index)
+    }
+}
+
+impl PedersenCommitments {
+    /// The number of shares needed to reconstruct, the number of commitments.
+    pub fn threshold(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether `share` is the share of its index; without commitments, no share is.
+    pub fn verify(&self, share: &PedersenShare) -> bool {
+        if self.0.is_empty() {
+            return false;
+        }
+        let commitment = RistrettoPoint::multiscalar_mul(
+            [share.share.value, share.blinding],
+            [RISTRETTO_BASEPOINT_POINT, pedersen_generator()],
+        );
+        share.share.index != 0 && commitment == evaluate_commitments(&self.0, share.share.index)
+    }
+}
+
+/// a polynomial of degree threshold - 1 with constant term `constant`, and its values at 1..=n
+fn random_polynomial(
+    rng: &mut dyn CryptoRngCore,
+    constant: Scalar,
+    threshold: usize,
+    shares: usize,
+) -> (Vec<Scalar>, Vec<Scalar>) {
+    let coefficients: Vec<Scalar> = core::iter::once(constant)
+        .chain((1..threshold).map(|_| Scalar::random(rng)))
+        .collect();
+    let values = (1..=shares as u64)
+        .map(|i| {
+            let x = Scalar::from(i);
+            coefficients
+                .iter()
+                .rev()
+                .fold(Scalar::ZERO, |value, coefficient| value * x + coefficient)
+        })
+        .collect();
+    (coefficients, values)
+}
+
+fn check_threshold(threshold: usize, shares: usize) -> Result<(), VssError> {
+    if threshold == 0 || threshold > shares || shares > u32::MAX as usize {
+        return Err(VssError::InvalidThreshold { threshold, shares });
+    }
+    Ok(())
+}
+
+/// Shares `secret` into `shares` shares, any `threshold` of which reconstruct it, with Feldman
+/// commitments.
+pub fn feldman_share(
+    rng: &mut dyn CryptoRngCore,
+    secret: &Scalar,
+    threshold: usize,
+    shares: usize,
+) -> Result<(Vec<Share>, FeldmanCommitments), VssError> {
+    check_threshold(threshold, shares)?;
+    let (mut coefficients, values) = random_polynomial(rng, *secret, threshold, shares);
+    let commitments = coefficients.iter().map(RistrettoPoint::mul_base).collect();
+    coefficients.zeroize();
+    Ok((
+        (1..)
+            .zip(values)
+            .map(|(index, value)| Share { index, value })
+            .collect(),
+        FeldmanCommitments(commitments),
+    ))
+}
+
+/// Like `feldman_share`, with Pedersen commitments, which hide the secret.
+pub fn pedersen_share(
+    rng: &mut dyn CryptoRngCore,
+    secret: &Scalar,
+    threshold: usize,
+    shares: usize,
+) -> Result<(Vec<PedersenShare>, PedersenCommitments), VssError> {
+    check_threshold(threshold, shares)?;
+    let (mut coefficients, values) = random_polynomial(rng, *secret, threshold, shares);
+    let blinding = Scalar::random(rng);
+    let (mut blinding_coefficients, blindings) =
+        random_polynomial(rng, blinding, threshold, shares);
+    let h = pedersen_generator();
+    let commitments = coefficients
+        .iter()
+        .zip(&blinding_coefficients)
+        .map(|(a, b)| RistrettoPoint::mul_base(a) + h * b)
+        .collect();
+    coefficients.zeroize();
+    blinding_coefficients.zeroize();
+    Ok((
+        (1..)
+            .zip(values.into_iter().zip(blindings))
+            .map(|(index, (value, blinding))| PedersenShare {
+                share: Share { index, value },
+                blinding,
+            })
+            .collect(),
+        PedersenCommitments(commitments),
+    ))
+}
+
+/// The Lagrange coefficient of `index` for interpolating at 0 from the shares of `indices`:
+/// prod_{m != index} m / (m - index). Fails for the index 0, repeated indices, or an `index` that
+/// is not among `indices`.
+pub fn lagrange_coefficient(index: u32, indices: &[u32]) -> Result<Scalar, VssError> {
+    if index == 0 {
+        return Err(VssError::ZeroIndex);
+    }
+    let x = Scalar::from(index);
+    let mut numerator = Scalar::ONE;
+    let mut denominator = Scalar::ONE;
+    let mut seen = false;
+    for &other in indices {
+        if other == 0 {
+            return Err(VssError::ZeroIndex);
+        }
+        if other == index {
+            if seen {
+                return Err(VssError::DuplicateIndex(index));
+            }
+            seen = true;
+            continue;
+        }
+        let x_m = Scalar::from(other);
+        numerator *= x_m;
+        denominator *= x_m - x;
+    }
+    if !seen {
+        return Err(VssError::MissingIndex(index));
+    }
+    Ok(numerator * denominator.invert())
+}
+
+/// Reconstructs the secret from at least `threshold` shares with distinct indices; shares that do
+/// not verify against the commitments yield a wrong secret, so verify them first.
+pub fn reconstruct(shares: &[Share], threshold: usize) -> Result<Scalar, VssError> {
+    if shares.len() < threshold || threshold == 0 {
+        return Err(VssError::InsufficientShares {
+            threshold,
+            actual: shares.len(),
+        });
+    }
+    let shares = &shares[..threshold];
+    let indices: Vec<u32> = shares.iter().map(|share| share.index).collect();
+    shares.iter().try_fold(Scalar::ZERO, |secret, share| {
+        Ok(secret + lagrange_coefficient(share.index, &indices)? * share.value)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feldman_vss() {
+        let mut rng = rand::thread_rng();
+        let secret = Scalar::random(&mut rng);
+
+        // 0. Share a secret 3-out-of-5; every share verifies, and C_0 is its public key
+        let (shares, commitments) = feldman_share(&mut rng, &secret, 3, 5).unwrap();
+        assert_eq!(shares.len(), 5);
+        assert_eq!(commitments.threshold(), 3);
+        assert_eq!(
+            commitments.public_key(),
+            Some(RistrettoPoint::mul_base(&secret))
+        );
+        assert!(shares.iter().all(|share| commitments.verify(share)));
+
+        // 1. Any 3 shares reconstruct the secret, 2 do not
+        for subset in [[0, 1, 2], [4, 2, 0], [1, 3, 4]] {
+            let subset: Vec<Share> = subset.iter().map(|&i| shares[i].clone()).collect();
+            assert_eq!(reconstruct(&subset, 3), Ok(secret));
+        }
+        assert_eq!(
+            reconstruct(&shares[..2], 3),
+            Err(VssError::InsufficientShares {
+                threshold: 3,
+                actual: 2
+            })
+        );
+        assert_ne!(reconstruct(&shares[..2], 2), Ok(secret));
+
+        // 2. Tampered shares are caught
+        let mut tampered = shares[0].clone();
+        tampered.value += Scalar::ONE;
+        assert!(!commitments.verify(&tampered));
+        assert!(bool::from(shares[0].ct_eq(&shares[0].clone())));
+        assert_ne!(tampered, shares[0]);
+        let mut moved = shares[0].clone();
+        moved.index = 2;
+        assert!(!commitments.verify(&moved));
+
+        // 3. Bad parameters and indices are rejected
+        assert_eq!(
+            feldman_share(&mut rng, &secret, 0, 5),
+            Err(VssError::InvalidThreshold {
+                threshold: 0,
+                shares: 5
+            })
+        );
+        assert_eq!(
+            feldman_share(&mut rng, &secret, 6, 5),
+            Err(VssError::InvalidThreshold {
+                threshold: 6,
+                shares: 5
+            })
+        );
+        assert_eq!(
+            reconstruct(&[shares[0].clone(), shares[0].clone()], 2),
+            Err(VssError::DuplicateIndex(1))
+        );
+        assert_eq!(lagrange_coefficient(0, &[1, 2]), Err(VssError::ZeroIndex));
+        assert_eq!(
+            lagrange_coefficient(3, &[1, 2]),
+            Err(VssError::MissingIndex(3))
+        );
+
+        // 4. Empty commitments, e.g. deserialized ones, have no public key and verify no share,
+        //    not even one of value 0
+        let empty = FeldmanCommitments(Vec::new());
+        assert_eq!(empty.public_key(), None);
+        assert!(!empty.verify(&Share {
+            index: 1,
+            value: Scalar::ZERO
+        }));
+    }
+
+    #[test]
+    fn test_pedersen_vss() {
+        let mut rng = rand::thread_rng();
+        let secret = Scalar::random(&mut rng);
+
+        // 0. Share a secret 2-out-of-3; every share verifies, and the secret reconstructs
+        let (shares, commitments) = pedersen_share(&mut rng, &secret, 2, 3).unwrap();
+        assert_eq!(commitments.threshold(), 2);
+        assert!(shares.iter().all(|share| commitments.verify(share)));
+        let subset = [shares[2].share.clone(), shares[0].share.clone()];
+        assert_eq!(reconstruct(&subset, 2), Ok(secret));
+
+        // 1. The commitments hide g^s, and shares with a wrong value or blinding are caught
+        assert_ne!(commitments.0[0], RistrettoPoint::mul_base(&secret));
+        let mut tampered = shares[1].clone();
+        tampered.blinding += Scalar::ONE;
+        assert!(!commitments.verify(&tampered));
+        let mut tampered = shares[1].clone();
+        tampered.share.value += Scalar::ONE;
+        assert!(!commitments.verify(&tampered));
+        let zero = PedersenShare {
+            share: Share {
+                index: 1,
+                value: Scalar::ZERO,
+            },
+            blinding: Scalar::ZERO,
+        };
+        assert!(!PedersenCommitments(Vec::new()).verify(&zero));
+
+        // 2. Lagrange coefficients combine values in the exponent, e.g. partial public keys
+        let indices = [1, 3];
+        let public_key: RistrettoPoint = [&shares[0], &shares[2]]
+            .iter()
+            .zip(indices)
+            .map(|(share, index)| {
+                lagrange_coefficient(index, &indices).unwrap()
+                    * RistrettoPoint::mul_base(&share.share.value)
+            })
+            .sum();
+        assert_eq!(public_key, RistrettoPoint::mul_base(&secret));
+    }
+}
@@ -0,0 +1,17 @@
+//! The Commonly Used Types and Functions
+//!
+//! `use amaze::prelude::*;` brings the keys, roles, signatures and messages of AMF into scope,
+//! with KeyGen, Frank, Verify and Judge, their checked variants and builders, and the errors they
+//! return. Everything else is in `amf` and `pok`. The functions that take their randomness from
+//! `rand::thread_rng` need the `rand` feature; `keygen_with_rng` and `frank_with_rng` do not.
+
+#[cfg(any(feature = "rand", test))]
+pub use crate::amf::{frank, frank_message, keygen, try_frank};
+pub use crate::{
+    amf::{
+        codec::CodecError, frank_with_rng, judge, judge_message, keygen_with_rng, try_judge,
+        try_verify, verify, verify_message, AMFMessage, AMFPublicKey, AMFRole, AMFSecretKey,
+        AMFSignature, Franker, Judge, Verifier, VerifyError,
+    },
+    Error,
+};
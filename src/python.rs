@@ -0,0 +1,266 @@
+//! Python Bindings
+//!
+//! With the `python` feature, PyO3 exports KeyGen, Frank, Verify and Judge, and the keys and
+//! signatures with their wire format, as the Python module `amaze`. `maturin develop --release`
+//! builds and installs it into the current virtualenv (see `pyproject.toml`):
+//!
+//! ```python
+//! import amaze
+//!
+//! sender_pk, sender_sk = amaze.keygen(amaze.Role.Sender)
+//! signature = amaze.frank(sender_sk, sender_pk, recipient_pk, judge_pk, b"hello world!")
+//! assert amaze.verify(recipient_sk, sender_pk, recipient_pk, judge_pk, b"hello world!", signature)
+//! blob = bytes(signature)  # == signature.to_bytes()
+//! signature = amaze.Signature.from_bytes(blob)
+//! ```
+//!
+//! Malformed keys or signatures raise `ValueError` with the `CodecError` message; a well-formed
+//! signature that does not verify makes `verify` and `judge` return `False`.
+
+// PyO3 0.22 wraps the `PyResult`s of `#[pymethods]` in a redundant `.into()`
+#![allow(clippy::useless_conversion)]
+
+use pyo3::{exceptions::PyValueError, prelude::*, types::PyBytes};
+
+use crate::amf::{self, codec::CodecError, AMFPublicKey, AMFRole, AMFSecretKey, AMFSignature};
+
+fn to_value_error(error: CodecError) -> PyErr {
+    PyValueError::new_err(error.to_string())
+}
+
+#[pyclass(eq, eq_int, frozen, module = "amaze")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Role {
+    Sender,
+    Recipient,
+    Judge,
+}
+
+impl From<Role> for AMFRole {
+    fn from(role: Role) -> Self {
+        match role {
+            Role::Sender => AMFRole::Sender,
+            Role::Recipient => AMFRole::Recipient,
+            Role::Judge => AMFRole::Judge,
+        }
+    }
+}
+
+impl From<AMFRole> for Role {
+    fn from(role: AMFRole) -> Self {
+        match role {
+            AMFRole::Sender => Role::Sender,
+            AMFRole::Recipient => Role::Recipient,
+            AMFRole::Judge => Role::Judge,
+        }
+    }
+}
+
+#[pyclass(eq, frozen, module = "amaze")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PublicKey(AMFPublicKey);
+
+#[pymethods]
+impl PublicKey {
+    #[staticmethod]
+    fn from_bytes(bytes: &[u8]) -> PyResult<Self> {
+        AMFPublicKey::from_bytes(bytes)
+            .map(PublicKey)
+            .map_err(to_value_error)
+    }
+
+    fn to_bytes<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new_bound(py, &self.0.to_bytes())
+    }
+
+    fn __bytes__<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        self.to_bytes(py)
+    }
+
+    #[getter]
+    fn role(&self) -> Role {
+        self.0.role.into()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("PublicKey(role={:?})", self.0.role)
+    }
+}
+
+#[pyclass(frozen, module = "amaze")]
+#[derive(Clone)]
+pub struct SecretKey(AMFSecretKey);
+
+#[pymethods]
+impl SecretKey {
+    #[staticmethod]
+    fn from_bytes(bytes: &[u8]) -> PyResult<Self> {
+        AMFSecretKey::from_bytes(bytes)
+            .map(SecretKey)
+            .map_err(to_value_error)
+    }
+
+    fn to_bytes<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new_bound(py, &self.0.to_bytes())
+    }
+
+    fn __bytes__<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        self.to_bytes(py)
+    }
+
+    #[getter]
+    fn role(&self) -> Role {
+        self.0.role.into()
+    }
+
+    // no secrets in notebook outputs
+    fn __repr__(&self) -> String {
+        format!("SecretKey(role={:?})", self.0.role)
+    }
+}
+
+#[pyclass(eq, frozen, module = "amaze")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Signature(AMFSignature);
+
+#[pymethods]
+impl Signature {
+    #[staticmethod]
+    fn from_bytes(bytes: &[u8]) -> PyResult<Self> {
+        AMFSignature::from_bytes(bytes)
+            .map(Signature)
+            .map_err(to_value_error)
+    }
+
+    fn to_bytes<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new_bound(py, &self.0.to_bytes())
+    }
+
+    fn __bytes__<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        self.to_bytes(py)
+    }
+}
+
+/// Generates a `(public_key, secret_key)` pair for `role`.
+#[pyfunction]
+fn keygen(role: Role) -> (PublicKey, SecretKey) {
+    let (public_key, secret_key) = amf::keygen(role.into());
+    (PublicKey(public_key), SecretKey(secret_key))
+}
+
+/// Franks `message`.
+#[pyfunction]
+fn frank(
+    sender_secret_key: SecretKey,
+    sender_public_key: PublicKey,
+    recipient_public_key: PublicKey,
+    judge_public_key: PublicKey,
+    message: &[u8],
+) -> Signature {
+    Signature(amf::frank(
+        sender_secret_key.0,
+        sender_public_key.0,
+        recipient_public_key.0,
+        judge_public_key.0,
+        message,
+    ))
+}
+
+/// Verifies a franked message as its recipient.
+#[pyfunction]
+fn verify(
+    recipient_secret_key: SecretKey,
+    sender_public_key: PublicKey,
+    recipient_public_key: PublicKey,
+    judge_public_key: PublicKey,
+    message: &[u8],
+    signature: Signature,
+) -> bool {
+    amf::verify(
+        recipient_secret_key.0,
+        sender_public_key.0,
+        recipient_public_key.0,
+        judge_public_key.0,
+        message,
+        signature.0,
+    )
+}
+
+/// Judges a reported message.
+#[pyfunction]
+fn judge(
+    judge_secret_key: SecretKey,
+    sender_public_key: PublicKey,
+    recipient_public_key: PublicKey,
+    judge_public_key: PublicKey,
+    message: &[u8],
+    signature: Signature,
+) -> bool {
+    amf::judge(
+        judge_secret_key.0,
+        sender_public_key.0,
+        recipient_public_key.0,
+        judge_public_key.0,
+        message,
+        signature.0,
+    )
+}
+
+#[pymodule]
+fn amaze(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<Role>()?;
+    module.add_class::<PublicKey>()?;
+    module.add_class::<SecretKey>()?;
+    module.add_class::<Signature>()?;
+    module.add_function(wrap_pyfunction!(keygen, module)?)?;
+    module.add_function(wrap_pyfunction!(frank, module)?)?;
+    module.add_function(wrap_pyfunction!(verify, module)?)?;
+    module.add_function(wrap_pyfunction!(judge, module)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pyo3::{types::PyDict, wrap_pymodule};
+
+    use super::*;
+
+    #[test]
+    fn test_python_module() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let globals = PyDict::new_bound(py);
+            globals
+                .set_item("amaze", wrap_pymodule!(amaze)(py))
+                .unwrap();
+            py.run_bound(
+                r#"
+# 0. Frank, verify and judge
+sender_pk, sender_sk = amaze.keygen(amaze.Role.Sender)
+recipient_pk, recipient_sk = amaze.keygen(amaze.Role.Recipient)
+judge_pk, judge_sk = amaze.keygen(amaze.Role.Judge)
+keys = (sender_pk, recipient_pk, judge_pk)
+signature = amaze.frank(sender_sk, *keys, b"hello world!")
+assert amaze.verify(recipient_sk, *keys, b"hello world!", signature)
+assert amaze.judge(judge_sk, *keys, b"hello world!", signature)
+assert not amaze.verify(recipient_sk, *keys, b"goodbye world!", signature)
+
+# 1. Keys and signatures round-trip through bytes
+assert amaze.Signature.from_bytes(bytes(signature)) == signature
+assert amaze.PublicKey.from_bytes(recipient_pk.to_bytes()).role == amaze.Role.Recipient
+assert amaze.SecretKey.from_bytes(bytes(judge_sk)).role == amaze.Role.Judge
+
+# 2. Malformed encodings raise ValueError
+try:
+    amaze.PublicKey.from_bytes(bytes(sender_sk))
+    assert False
+except ValueError:
+    pass
+"#,
+                Some(&globals),
+                None,
+            )
+            .unwrap();
+        });
+    }
+}
@@ -0,0 +1,304 @@
+//! HTTP Report Submission
+//!
+//! A lightweight alternative to a gRPC moderation service: recipients `POST` a `Report` as JSON to
+//! `REPORTS_PATH`, and the judge's endpoint answers with a `Verdict`. Keys and signatures use the
+//! human-readable codec of `amf::codec` (hex strings for points and scalars), and the message is a
+//! hex string too:
+//!
+//! ```text
+//! POST /v1/reports
+//! {"sender_public_key": {...}, "recipient_public_key": {...}, "message": "68656c6c6f",
+//!  "signature": {...}}
+//!
+//! 200 OK
+//! {"valid": true}
+//! ```
+//!
+//! With the `rest-server` feature, `router` builds the axum endpoint for a judge's key pair; with
+//! the `rest-client` feature, `ReportClient` submits reports to it with reqwest. Bodies that do not
+//! deserialize are rejected by axum's `Json` extractor with a 4xx status.
+//!
+//! Platforms plug their business logic (rate limits, sender history, content categories) into the
+//! endpoint with a `JudgePolicy` and `router_with_policy`. The policy reviews every report after
+//! its signature is judged, and can let the verdict through, or answer with 429 Too Many Requests
+//! or 403 Forbidden instead; `router` lets every verdict through.
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "rest-server")]
+use crate::amf::{self, AMFSecretKey};
+use crate::amf::{AMFPublicKey, AMFSignature};
+
+/// Where reports are submitted, relative to the judge's base URL.
+pub const REPORTS_PATH: &str = "/v1/reports";
+
+/// A franked message reported to the judge.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Report {
+    pub sender_public_key: AMFPublicKey,
+    pub recipient_public_key: AMFPublicKey,
+    #[serde(with = "hex")]
+    pub message: Vec<u8>,
+    pub signature: AMFSignature,
+}
+
+/// The judge's answer to a `Report`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Verdict {
+    /// Whether the signature is valid, i.e., the sender sent the message.
+    pub valid: bool,
+}
+
+/// What a `JudgePolicy` decides about a judged report.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PolicyDecision {
+    /// Answer with the verdict.
+    Accept,
+    /// Answer with 429 Too Many Requests, e.g. when the recipient reports too often.
+    RateLimited,
+    /// Answer with 403 Forbidden, e.g. when the content is not in a category this judge handles.
+    Refuse,
+}
+
+/// Business logic that the report endpoint runs after the cryptographic verification, cf. the
+/// module documentation. A policy must not tell the reporter more than the verdict does, e.g. it
+/// must not refuse valid reports of one sender only, which would reveal that the sender sent
+/// them.
+pub trait JudgePolicy: Send + Sync + 'static {
+    /// Reviews `report`, which was judged `verdict`; lets every verdict through by default.
+    fn review(&self, report: &Report, verdict: Verdict) -> PolicyDecision {
+        let _ = (report, verdict);
+        PolicyDecision::Accept
+    }
+}
+
+/// The policy that lets every verdict through.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct PassThrough;
+
+impl JudgePolicy for PassThrough {}
+
+#[cfg(feature = "rest-server")]
+pub use server::{router, router_with_policy};
+
+#[cfg(feature = "rest-server")]
+mod server {
+    use std::sync::Arc;
+
+    use axum::{extract::State, http::StatusCode, routing::post, Json, Router};
+
+    use super::*;
+
+    #[derive(Clone)]
+    struct JudgeState {
+        public_key: AMFPublicKey,
+        secret_key: AMFSecretKey,
+        policy: Arc<dyn JudgePolicy>,
+    }
+
+    async fn judge_report(
+        State(state): State<JudgeState>,
+        Json(report): Json<Report>,
+    ) -> Result<Json<Verdict>, StatusCode> {
+        let verdict = Verdict {
+            valid: amf::judge(
+                &state.secret_key,
+                report.sender_public_key,
+                report.recipient_public_key,
+                state.public_key,
+                &report.message,
+                report.signature,
+            ),
+        };
+        match state.policy.review(&report, verdict) {
+            PolicyDecision::Accept => Ok(Json(verdict)),
+            PolicyDecision::RateLimited => Err(StatusCode::TOO_MANY_REQUESTS),
+            PolicyDecision::Refuse => Err(StatusCode::FORBIDDEN),
+        }
+    }
+
+    /// The report submission endpoint of the judge with the given key pair; serve it with
+    /// `axum::serve`, or nest it into a larger application.
+    pub fn router(judge_public_key: AMFPublicKey, judge_secret_key: AMFSecretKey) -> Router {
+        router_with_policy(judge_public_key, judge_secret_key, PassThrough)
+    }
+
+    /// Like `router`, with `policy` reviewing every judged report.
+    pub fn router_with_policy(
+        judge_public_key: AMFPublicKey,
+        judge_secret_key: AMFSecretKey,
+        policy: impl JudgePolicy,
+    ) -> Router {
+        Router::new()
+            .route(REPORTS_PATH, post(judge_report))
+            .with_state(JudgeState {
+                public_key: judge_public_key,
+                secret_key: judge_secret_key,
+                policy: Arc::new(policy),
+            })
+    }
+}
+
+/// Submits reports to a judge's endpoint.
+#[cfg(feature = "rest-client")]
+#[derive(Debug, Clone)]
+pub struct ReportClient {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+#[cfg(feature = "rest-client")]
+impl ReportClient {
+    /// A client for the judge at `base_url`, e.g., `https://judge.example.com`.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self::with_client(reqwest::Client::new(), base_url)
+    }
+
+    /// Like `new`, but with a configured `reqwest::Client` (TLS, timeouts, proxies, ...).
+    pub fn with_client(client: reqwest::Client, base_url: impl Into<String>) -> Self {
+        ReportClient {
+            client,
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Submits `report`, and returns the judge's verdict; HTTP error statuses are errors.
+    pub async fn submit(&self, report: &Report) -> Result<Verdict, reqwest::Error> {
+        self.client
+            .post(format!("{}{}", self.base_url, REPORTS_PATH))
+            .json(report)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+}
+
+#[cfg(all(test, feature = "rest-server", feature = "rest-client"))]
+mod tests {
+    use super::*;
+    use crate::amf::{self, AMFRole};
+
+    #[tokio::test]
+    async fn test_rest_report_submission() {
+        // 0. Serve a judge on an ephemeral port
+        let (judge_public_key, judge_secret_key) = amf::keygen(AMFRole::Judge);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            axum::serve(listener, router(judge_public_key, judge_secret_key))
+                .await
+                .unwrap()
+        });
+
+        // 1. Frank a message, and report it
+        let (sender_public_key, sender_secret_key) = amf::keygen(AMFRole::Sender);
+        let (recipient_public_key, _) = amf::keygen(AMFRole::Recipient);
+        let message = b"hello world!";
+        let signature = amf::frank(
+            sender_secret_key,
+            sender_public_key,
+            recipient_public_key,
+            judge_public_key,
+            message,
+        );
+        let mut report = Report {
+            sender_public_key,
+            recipient_public_key,
+            message: message.to_vec(),
+            signature,
+        };
+        let client = ReportClient::new(base_url.clone());
+        assert_eq!(
+            client.submit(&report).await.unwrap(),
+            Verdict { valid: true }
+        );
+
+        // 2. A report of a different message is judged invalid
+        report.message = b"goodbye world!".to_vec();
+        assert_eq!(
+            client.submit(&report).await.unwrap(),
+            Verdict { valid: false }
+        );
+
+        // 3. A malformed body is an HTTP error
+        let response = reqwest::Client::new()
+            .post(format!("{}{}", base_url, REPORTS_PATH))
+            .json(&serde_json::json!({ "message": "zz" }))
+            .send()
+            .await
+            .unwrap();
+        assert!(response.status().is_client_error());
+    }
+
+    /// refuses reports of a message, and rate-limits each recipient to two reports
+    struct TestPolicy {
+        reports: std::sync::Mutex<std::collections::HashMap<Vec<u8>, usize>>,
+    }
+
+    impl JudgePolicy for TestPolicy {
+        fn review(&self, report: &Report, _verdict: Verdict) -> PolicyDecision {
+            if report.message == b"off topic" {
+                return PolicyDecision::Refuse;
+            }
+            let recipient = report.recipient_public_key.public_key.compress().to_bytes();
+            let mut reports = self.reports.lock().unwrap();
+            let count = reports.entry(recipient.to_vec()).or_default();
+            *count += 1;
+            if *count > 2 {
+                PolicyDecision::RateLimited
+            } else {
+                PolicyDecision::Accept
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rest_judge_policy() {
+        // 0. Serve a judge with a policy
+        let (judge_public_key, judge_secret_key) = amf::keygen(AMFRole::Judge);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let policy = TestPolicy {
+            reports: Default::default(),
+        };
+        tokio::spawn(async move {
+            axum::serve(
+                listener,
+                router_with_policy(judge_public_key, judge_secret_key, policy),
+            )
+            .await
+            .unwrap()
+        });
+        let (sender_public_key, sender_secret_key) = amf::keygen(AMFRole::Sender);
+        let (recipient_public_key, _) = amf::keygen(AMFRole::Recipient);
+        let report = |message: &[u8]| Report {
+            sender_public_key,
+            recipient_public_key,
+            message: message.to_vec(),
+            signature: amf::frank(
+                &sender_secret_key,
+                sender_public_key,
+                recipient_public_key,
+                judge_public_key,
+                message,
+            ),
+        };
+        let client = ReportClient::new(base_url);
+
+        // 1. A refused report is an HTTP 403, and does not count towards the rate limit
+        let error = client.submit(&report(b"off topic")).await.unwrap_err();
+        assert_eq!(error.status(), Some(reqwest::StatusCode::FORBIDDEN));
+
+        // 2. The policy lets the first two verdicts through, and rate-limits the third
+        for _ in 0..2 {
+            assert_eq!(
+                client.submit(&report(b"hello world!")).await.unwrap(),
+                Verdict { valid: true }
+            );
+        }
+        let error = client.submit(&report(b"hello world!")).await.unwrap_err();
+        assert_eq!(error.status(), Some(reqwest::StatusCode::TOO_MANY_REQUESTS));
+    }
+}
@@ -0,0 +1,34 @@
+//! An allocator for tests that counts the allocations of each thread, so that tests of code that
+//! must not allocate (cf. `amf::heapless` and the franking hot path) can check it, while other
+//! tests run in parallel.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        // try_with, since the thread-local may already be gone while a thread shuts down
+        let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// the number of allocations the current thread made so far
+pub(crate) fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
@@ -0,0 +1,227 @@
+//! UniFFI Bindings for Kotlin and Swift
+//!
+//! With the `uniffi` feature, `src/amaze.udl` describes the franking API, keys, signatures and
+//! errors once, and UniFFI generates from it both the Rust scaffolding (in `build.rs`, included at
+//! the crate root) and the Kotlin and Swift bindings:
+//!
+//! ```shell
+//! cargo rustc --release --features uniffi --crate-type cdylib
+//! cargo run --features uniffi-cli --bin uniffi-bindgen -- generate src/amaze.udl \
+//!     --language kotlin --language swift --out-dir bindings
+//! ```
+//!
+//! Keys and signatures are objects that decode from and encode to the wire format of
+//! `amf::codec`; decoding errors surface as `AmazeError`, with the `CodecError` as message.
+
+use std::{fmt, sync::Arc};
+
+use crate::amf::{self, codec::CodecError, AMFPublicKey, AMFRole, AMFSecretKey, AMFSignature};
+
+pub type Role = AMFRole;
+
+/// Why a key or signature did not decode, by kind.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AmazeError {
+    /// The input was too short or too long.
+    InvalidLength(CodecError),
+    /// The wire header was not for this library, version, suite, or kind of artifact.
+    InvalidHeader(CodecError),
+    /// The role byte was invalid.
+    InvalidRole(CodecError),
+    /// A point or scalar was invalid.
+    InvalidEncoding(CodecError),
+}
+
+impl From<CodecError> for AmazeError {
+    fn from(error: CodecError) -> Self {
+        match error {
+            CodecError::InvalidLength { .. } => AmazeError::InvalidLength(error),
+            CodecError::InvalidMagic
+            | CodecError::UnsupportedVersion(_)
+            | CodecError::UnsupportedSuite(_)
+            | CodecError::UnexpectedArtifact(_) => AmazeError::InvalidHeader(error),
+            CodecError::InvalidRole(_) => AmazeError::InvalidRole(error),
+            CodecError::InvalidPoint
+            | CodecError::InvalidScalar
+            | CodecError::IdentityPoint
+            | CodecError::ZeroScalar => AmazeError::InvalidEncoding(error),
+        }
+    }
+}
+
+impl fmt::Display for AmazeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AmazeError::InvalidLength(error)
+            | AmazeError::InvalidHeader(error)
+            | AmazeError::InvalidRole(error)
+            | AmazeError::InvalidEncoding(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for AmazeError {}
+
+pub struct PublicKey(AMFPublicKey);
+
+impl PublicKey {
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, AmazeError> {
+        Ok(PublicKey(AMFPublicKey::from_bytes(&bytes)?))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.to_bytes()
+    }
+
+    pub fn role(&self) -> Role {
+        self.0.role
+    }
+}
+
+pub struct SecretKey(AMFSecretKey);
+
+impl SecretKey {
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, AmazeError> {
+        Ok(SecretKey(AMFSecretKey::from_bytes(&bytes)?))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.to_bytes().to_vec()
+    }
+
+    pub fn role(&self) -> Role {
+        self.0.role
+    }
+}
+
+pub struct Signature(AMFSignature);
+
+impl Signature {
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, AmazeError> {
+        Ok(Signature(AMFSignature::from_bytes(&bytes)?))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.to_bytes()
+    }
+}
+
+pub struct KeyPair {
+    pub public_key: Arc<PublicKey>,
+    pub secret_key: Arc<SecretKey>,
+}
+
+pub fn keygen(role: Role) -> KeyPair {
+    let (public_key, secret_key) = amf::keygen(role);
+    KeyPair {
+        public_key: Arc::new(PublicKey(public_key)),
+        secret_key: Arc::new(SecretKey(secret_key)),
+    }
+}
+
+pub fn frank(
+    sender_secret_key: Arc<SecretKey>,
+    sender_public_key: Arc<PublicKey>,
+    recipient_public_key: Arc<PublicKey>,
+    judge_public_key: Arc<PublicKey>,
+    message: Vec<u8>,
+) -> Arc<Signature> {
+    Arc::new(Signature(amf::frank(
+        sender_secret_key.0.clone(),
+        sender_public_key.0,
+        recipient_public_key.0,
+        judge_public_key.0,
+        &message,
+    )))
+}
+
+pub fn verify(
+    recipient_secret_key: Arc<SecretKey>,
+    sender_public_key: Arc<PublicKey>,
+    recipient_public_key: Arc<PublicKey>,
+    judge_public_key: Arc<PublicKey>,
+    message: Vec<u8>,
+    signature: Arc<Signature>,
+) -> bool {
+    amf::verify(
+        recipient_secret_key.0.clone(),
+        sender_public_key.0,
+        recipient_public_key.0,
+        judge_public_key.0,
+        &message,
+        signature.0,
+    )
+}
+
+pub fn judge(
+    judge_secret_key: Arc<SecretKey>,
+    sender_public_key: Arc<PublicKey>,
+    recipient_public_key: Arc<PublicKey>,
+    judge_public_key: Arc<PublicKey>,
+    message: Vec<u8>,
+    signature: Arc<Signature>,
+) -> bool {
+    amf::judge(
+        judge_secret_key.0.clone(),
+        sender_public_key.0,
+        recipient_public_key.0,
+        judge_public_key.0,
+        &message,
+        signature.0,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uniffi_api() {
+        // 0. Frank, verify and judge with the exported objects
+        let sender = keygen(Role::Sender);
+        let recipient = keygen(Role::Recipient);
+        let judge_key_pair = keygen(Role::Judge);
+        let message = b"hello world!".to_vec();
+        let signature = frank(
+            sender.secret_key.clone(),
+            sender.public_key.clone(),
+            recipient.public_key.clone(),
+            judge_key_pair.public_key.clone(),
+            message.clone(),
+        );
+        assert!(verify(
+            recipient.secret_key.clone(),
+            sender.public_key.clone(),
+            recipient.public_key.clone(),
+            judge_key_pair.public_key.clone(),
+            message.clone(),
+            signature.clone(),
+        ));
+        assert!(judge(
+            judge_key_pair.secret_key.clone(),
+            sender.public_key.clone(),
+            recipient.public_key.clone(),
+            judge_key_pair.public_key.clone(),
+            message,
+            signature.clone(),
+        ));
+
+        // 1. Objects round-trip through bytes, and decoding errors are classified
+        let decoded = Signature::from_bytes(signature.to_bytes()).unwrap();
+        assert_eq!(decoded.0, signature.0);
+        assert_eq!(
+            PublicKey::from_bytes(recipient.public_key.to_bytes())
+                .unwrap()
+                .role(),
+            Role::Recipient
+        );
+        assert!(matches!(
+            PublicKey::from_bytes(sender.secret_key.to_bytes()),
+            Err(AmazeError::InvalidHeader(_))
+        ));
+        assert!(matches!(
+            Signature::from_bytes(vec![]),
+            Err(AmazeError::InvalidLength(_))
+        ));
+    }
+}
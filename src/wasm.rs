@@ -0,0 +1,246 @@
+//! JavaScript/TypeScript Bindings
+//!
+//! With the `wasm` feature, `wasm-bindgen` exports KeyGen, Frank, Verify and Judge to JavaScript,
+//! on keys and signatures in the wire format of `amf::codec` as `Uint8Array`s, so that web
+//! messengers can store and send them as they are. `wasm-pack build --features wasm` generates the
+//! JavaScript glue and the TypeScript definitions:
+//!
+//! ```typescript
+//! import { keygen, frank, verify, judge, Role } from "amaze";
+//!
+//! const sender = keygen(Role.Sender);
+//! const signature: Uint8Array = frank(sender.secretKey, sender.publicKey, recipientPublicKey,
+//!     judgePublicKey, message);
+//! ```
+//!
+//! Malformed keys or signatures throw an `Error` with the `CodecError` message; a well-formed
+//! signature that does not verify makes `verify` and `judge` return `false`.
+
+use wasm_bindgen::prelude::*;
+
+use crate::amf::{self, codec::CodecError, AMFPublicKey, AMFRole, AMFSecretKey, AMFSignature};
+
+#[wasm_bindgen(typescript_custom_section)]
+const TYPESCRIPT_TYPES: &'static str = r#"
+/** An encoded public key, as returned by `KeyPair.publicKey`. */
+export type PublicKeyBytes = Uint8Array;
+/** An encoded secret key, as returned by `KeyPair.secretKey`. */
+export type SecretKeyBytes = Uint8Array;
+/** An encoded AMF signature, as returned by `frank`. */
+export type SignatureBytes = Uint8Array;
+"#;
+
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Role {
+    Sender,
+    Recipient,
+    Judge,
+}
+
+impl From<Role> for AMFRole {
+    fn from(role: Role) -> Self {
+        match role {
+            Role::Sender => AMFRole::Sender,
+            Role::Recipient => AMFRole::Recipient,
+            Role::Judge => AMFRole::Judge,
+        }
+    }
+}
+
+/// an encoded key pair
+#[wasm_bindgen]
+pub struct KeyPair {
+    public_key: Vec<u8>,
+    secret_key: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl KeyPair {
+    #[wasm_bindgen(getter, js_name = publicKey)]
+    pub fn public_key(&self) -> Vec<u8> {
+        self.public_key.clone()
+    }
+
+    #[wasm_bindgen(getter, js_name = secretKey)]
+    pub fn secret_key(&self) -> Vec<u8> {
+        self.secret_key.clone()
+    }
+}
+
+/// the decoded keys of a Frank, Verify or Judge call
+struct Keys {
+    secret_key: AMFSecretKey,
+    sender_public_key: AMFPublicKey,
+    recipient_public_key: AMFPublicKey,
+    judge_public_key: AMFPublicKey,
+}
+
+impl Keys {
+    fn from_bytes(
+        secret_key: &[u8],
+        sender_public_key: &[u8],
+        recipient_public_key: &[u8],
+        judge_public_key: &[u8],
+    ) -> Result<Self, CodecError> {
+        Ok(Keys {
+            secret_key: AMFSecretKey::from_bytes(secret_key)?,
+            sender_public_key: AMFPublicKey::from_bytes(sender_public_key)?,
+            recipient_public_key: AMFPublicKey::from_bytes(recipient_public_key)?,
+            judge_public_key: AMFPublicKey::from_bytes(judge_public_key)?,
+        })
+    }
+}
+
+fn to_js_error(error: CodecError) -> JsError {
+    JsError::new(&error.to_string())
+}
+
+#[wasm_bindgen]
+pub fn keygen(role: Role) -> KeyPair {
+    let (public_key, secret_key) = amf::keygen(role.into());
+    KeyPair {
+        public_key: public_key.to_bytes(),
+        secret_key: secret_key.to_bytes().to_vec(),
+    }
+}
+
+fn frank_bytes(keys: Keys, message: &[u8]) -> Vec<u8> {
+    amf::frank(
+        keys.secret_key,
+        keys.sender_public_key,
+        keys.recipient_public_key,
+        keys.judge_public_key,
+        message,
+    )
+    .to_bytes()
+}
+
+fn verify_bytes(keys: Keys, message: &[u8], signature: &[u8]) -> Result<bool, CodecError> {
+    Ok(amf::verify(
+        keys.secret_key,
+        keys.sender_public_key,
+        keys.recipient_public_key,
+        keys.judge_public_key,
+        message,
+        AMFSignature::from_bytes(signature)?,
+    ))
+}
+
+fn judge_bytes(keys: Keys, message: &[u8], signature: &[u8]) -> Result<bool, CodecError> {
+    Ok(amf::judge(
+        keys.secret_key,
+        keys.sender_public_key,
+        keys.recipient_public_key,
+        keys.judge_public_key,
+        message,
+        AMFSignature::from_bytes(signature)?,
+    ))
+}
+
+#[wasm_bindgen]
+pub fn frank(
+    sender_secret_key: &[u8],
+    sender_public_key: &[u8],
+    recipient_public_key: &[u8],
+    judge_public_key: &[u8],
+    message: &[u8],
+) -> Result<Vec<u8>, JsError> {
+    let keys = Keys::from_bytes(
+        sender_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+    )
+    .map_err(to_js_error)?;
+    Ok(frank_bytes(keys, message))
+}
+
+#[wasm_bindgen]
+pub fn verify(
+    recipient_secret_key: &[u8],
+    sender_public_key: &[u8],
+    recipient_public_key: &[u8],
+    judge_public_key: &[u8],
+    message: &[u8],
+    signature: &[u8],
+) -> Result<bool, JsError> {
+    Keys::from_bytes(
+        recipient_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+    )
+    .and_then(|keys| verify_bytes(keys, message, signature))
+    .map_err(to_js_error)
+}
+
+#[wasm_bindgen]
+pub fn judge(
+    judge_secret_key: &[u8],
+    sender_public_key: &[u8],
+    recipient_public_key: &[u8],
+    judge_public_key: &[u8],
+    message: &[u8],
+    signature: &[u8],
+) -> Result<bool, JsError> {
+    Keys::from_bytes(
+        judge_secret_key,
+        sender_public_key,
+        recipient_public_key,
+        judge_public_key,
+    )
+    .and_then(|keys| judge_bytes(keys, message, signature))
+    .map_err(to_js_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wasm_bindings() {
+        // 0. Generate encoded keys
+        let sender = keygen(Role::Sender);
+        let recipient = keygen(Role::Recipient);
+        let judge_key_pair = keygen(Role::Judge);
+        let keys = |key_pair: &KeyPair| {
+            Keys::from_bytes(
+                &key_pair.secret_key(),
+                &sender.public_key(),
+                &recipient.public_key(),
+                &judge_key_pair.public_key(),
+            )
+            .unwrap()
+        };
+
+        // 1. Frank, verify and judge on bytes
+        let message = b"hello world!";
+        let signature = frank_bytes(keys(&sender), message);
+        assert_eq!(
+            verify_bytes(keys(&recipient), message, &signature),
+            Ok(true)
+        );
+        assert_eq!(
+            judge_bytes(keys(&judge_key_pair), message, &signature),
+            Ok(true)
+        );
+        assert_eq!(
+            verify_bytes(keys(&recipient), b"goodbye world!", &signature),
+            Ok(false)
+        );
+
+        // 2. Malformed inputs are errors, not rejections
+        assert!(matches!(
+            verify_bytes(keys(&recipient), message, &signature[1..]),
+            Err(CodecError::InvalidMagic)
+        ));
+        assert!(Keys::from_bytes(
+            &recipient.public_key(),
+            &sender.public_key(),
+            &recipient.public_key(),
+            &judge_key_pair.public_key(),
+        )
+        .is_err());
+    }
+}
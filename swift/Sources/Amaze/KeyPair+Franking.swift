@@ -0,0 +1,44 @@
+import Foundation
+
+// The generated bindings in amaze.swift mirror src/amaze.udl; these spell the three algorithms
+// from the point of view of the key pair that runs them.
+extension KeyPair {
+    /// Franks `message` as its sender, for `recipient` and `judge`.
+    public func frank(_ message: Data, recipient: PublicKey, judge: PublicKey) -> Signature {
+        Amaze.frank(
+            senderSecretKey: secretKey,
+            senderPublicKey: publicKey,
+            recipientPublicKey: recipient,
+            judgePublicKey: judge,
+            message: message
+        )
+    }
+
+    /// Verifies a franked message as its recipient.
+    public func verify(
+        _ message: Data, signature: Signature, sender: PublicKey, judge: PublicKey
+    ) -> Bool {
+        Amaze.verify(
+            recipientSecretKey: secretKey,
+            senderPublicKey: sender,
+            recipientPublicKey: publicKey,
+            judgePublicKey: judge,
+            message: message,
+            signature: signature
+        )
+    }
+
+    /// Judges a reported message as its judge.
+    public func judge(
+        _ message: Data, signature: Signature, sender: PublicKey, recipient: PublicKey
+    ) -> Bool {
+        Amaze.judge(
+            judgeSecretKey: secretKey,
+            senderPublicKey: sender,
+            recipientPublicKey: recipient,
+            judgePublicKey: publicKey,
+            message: message,
+            signature: signature
+        )
+    }
+}
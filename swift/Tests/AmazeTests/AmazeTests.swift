@@ -0,0 +1,27 @@
+import Amaze
+import XCTest
+
+final class AmazeTests: XCTestCase {
+    func testFranking() throws {
+        // 0. Frank, verify and judge
+        let sender = keygen(role: .sender)
+        let recipient = keygen(role: .recipient)
+        let judge = keygen(role: .judge)
+        let message = Data("hello world!".utf8)
+        let signature = sender.frank(message, recipient: recipient.publicKey, judge: judge.publicKey)
+        XCTAssertTrue(recipient.verify(
+            message, signature: signature, sender: sender.publicKey, judge: judge.publicKey))
+        XCTAssertTrue(judge.judge(
+            message, signature: signature, sender: sender.publicKey, recipient: recipient.publicKey))
+        XCTAssertFalse(recipient.verify(
+            Data("goodbye world!".utf8), signature: signature, sender: sender.publicKey,
+            judge: judge.publicKey))
+
+        // 1. Keys and signatures round-trip through bytes, and malformed ones throw
+        let decoded = try Signature.fromBytes(bytes: signature.toBytes())
+        XCTAssertTrue(recipient.verify(
+            message, signature: decoded, sender: sender.publicKey, judge: judge.publicKey))
+        XCTAssertEqual(try PublicKey.fromBytes(bytes: judge.publicKey.toBytes()).role(), .judge)
+        XCTAssertThrowsError(try PublicKey.fromBytes(bytes: sender.secretKey.toBytes()))
+    }
+}
@@ -0,0 +1,38 @@
+#!/bin/sh
+# Builds swift/amazeFFI.xcframework (the `uniffi` static library for iOS devices, iOS simulators
+# and macOS) and the Swift bindings in swift/Sources/Amaze/amaze.swift, for Package.swift.
+#
+# Needs macOS with Xcode, and the Rust targets:
+#   rustup target add aarch64-apple-ios aarch64-apple-ios-sim x86_64-apple-ios \
+#       aarch64-apple-darwin x86_64-apple-darwin
+set -eu
+cd "$(dirname "$0")/.."
+
+out=target/xcframework
+rm -rf "$out" swift/amazeFFI.xcframework
+mkdir -p "$out/headers" "$out/ios-simulator" "$out/macos"
+
+# 0. The Swift bindings, and the C header and module map they import as amazeFFI
+cargo run --release --features uniffi-cli --bin uniffi-bindgen -- generate src/amaze.udl \
+    --language swift --out-dir "$out/bindings"
+cp "$out/bindings/amaze.swift" swift/Sources/Amaze/
+cp "$out/bindings/amazeFFI.h" "$out/headers/"
+cp "$out/bindings/amazeFFI.modulemap" "$out/headers/module.modulemap"
+
+# 1. A static library per target
+for target in aarch64-apple-ios aarch64-apple-ios-sim x86_64-apple-ios \
+    aarch64-apple-darwin x86_64-apple-darwin; do
+    cargo rustc --release --lib --features uniffi --target "$target" --crate-type staticlib
+done
+
+# 2. An XCFramework takes one library per platform, so the simulator and macOS slices are fat
+lipo -create -output "$out/ios-simulator/libamaze.a" \
+    target/aarch64-apple-ios-sim/release/libamaze.a target/x86_64-apple-ios/release/libamaze.a
+lipo -create -output "$out/macos/libamaze.a" \
+    target/aarch64-apple-darwin/release/libamaze.a target/x86_64-apple-darwin/release/libamaze.a
+
+xcodebuild -create-xcframework \
+    -library target/aarch64-apple-ios/release/libamaze.a -headers "$out/headers" \
+    -library "$out/ios-simulator/libamaze.a" -headers "$out/headers" \
+    -library "$out/macos/libamaze.a" -headers "$out/headers" \
+    -output swift/amazeFFI.xcframework